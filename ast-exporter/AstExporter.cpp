@@ -4,6 +4,7 @@
 #include <unordered_set>
 #include <set>
 #include <fstream>
+#include <cstring>
 
 #include "llvm/Support/Debug.h"
 // Declares clang::SyntaxOnlyAction.
@@ -14,7 +15,11 @@
 #include "clang/AST/TypeVisitor.h"
 #include "clang/AST/StmtVisitor.h"
 #include "clang/AST/DeclVisitor.h"
+#include "clang/Basic/DiagnosticLex.h"
 #include "clang/Frontend/CompilerInstance.h"
+#include "clang/Lex/Lexer.h"
+#include "clang/Lex/PPCallbacks.h"
+#include "clang/Lex/Preprocessor.h"
 #include "clang/Tooling/Tooling.h"
 
 #include <tinycbor/cbor.h>
@@ -203,10 +208,11 @@ public:
     void VisitRecordType(const RecordType *T);
 
     void VisitVectorType(const clang::VectorType *T) {
-        auto t = T->desugar();
+        auto t = T->getElementType();
         auto qt = encodeQualType(t);
-        encodeType(T, TagVectorType, [qt](CborEncoder *local){
+        encodeType(T, TagVectorType, [T,qt](CborEncoder *local){
             cbor_encode_uint(local, qt);
+            cbor_encode_uint(local, T->getNumElements());
         });
         VisitQualType(t);
     }
@@ -695,6 +701,43 @@ class TranslateASTVisitor final
           return true;
       }
       
+      /*
+       A GNU `__atomic_*` or C11 `__c11_atomic_*` builtin. Clang gives these their own node rather
+       than a `CallExpr`, with operands in a different order from the source.
+       Children: pointer, order, val1, order_fail, val2, weak, each null when the builtin doesn't
+       take it
+       Extras: builtin name
+       */
+      bool VisitAtomicExpr(AtomicExpr *E) {
+          auto op = E->getOp();
+          bool is_load = op == AtomicExpr::AO__c11_atomic_load || op == AtomicExpr::AO__atomic_load_n;
+          bool has_order = op != AtomicExpr::AO__c11_atomic_init;
+          bool has_val2 = op == AtomicExpr::AO__atomic_exchange || E->isCmpXChg();
+          bool has_weak = op == AtomicExpr::AO__atomic_compare_exchange ||
+                          op == AtomicExpr::AO__atomic_compare_exchange_n;
+
+          std::vector<void*> childIds {
+              E->getPtr(),
+              has_order ? E->getOrder() : nullptr,
+              is_load ? nullptr : E->getVal1(),
+              E->isCmpXChg() ? E->getOrderFail() : nullptr,
+              has_val2 ? E->getVal2() : nullptr,
+              has_weak ? E->getWeak() : nullptr,
+          };
+
+          const char *name = "";
+          switch (op) {
+#define BUILTIN(ID, TYPE, ATTRS)
+#define ATOMIC_BUILTIN(ID, TYPE, ATTRS) case AtomicExpr::AO ## ID: name = #ID; break;
+#include "clang/Basic/Builtins.def"
+          }
+
+          encode_entry(E, TagAtomicExpr, childIds, [name](CborEncoder *extras){
+              cbor_encode_text_stringz(extras, name);
+          });
+          return true;
+      }
+      
       bool VisitUnaryExprOrTypeTraitExpr(UnaryExprOrTypeTraitExpr *E) {
           std::vector<void*> childIds { E->isArgumentType() ? nullptr : E->getArgumentExpr() };
           auto t = E->getTypeOfArgument();
@@ -739,6 +782,16 @@ class TranslateASTVisitor final
           encode_entry(E, TagParenExpr, childIds);
           return true;
       }
+
+      /*
+       [C11 6.5.1.1] Generic selection. Clang has already picked the association matching the
+       controlling expression, so only that one is exported, as if it were parenthesized.
+       */
+      bool VisitGenericSelectionExpr(GenericSelectionExpr *E) {
+          std::vector<void*> childIds { E->getResultExpr() };
+          encode_entry(E, TagParenExpr, childIds);
+          return true;
+      }
       
       /*
        [C99 6.5.2.3] Structure and Union Members.
@@ -775,8 +828,12 @@ class TranslateASTVisitor final
        Extras: (none)
        */
       bool VisitInitListExpr(InitListExpr *ILE) {
-          auto inits = ILE->inits();
-          std::vector<void*> childIds(inits.begin(), inits.end());
+          // Designators are already resolved in this, the semantic form, so that every element
+          // has its final initializer. Elements left out take the array filler.
+          std::vector<void*> childIds;
+          for (auto init : ILE->inits()) {
+              childIds.push_back(init ? init : ILE->getArrayFiller());
+          }
           encode_entry(ILE, TagInitListExpr, childIds, [ILE](CborEncoder *extras) {
               auto union_field = ILE->getInitializedFieldInUnion();
               if (union_field) {
@@ -800,6 +857,25 @@ class TranslateASTVisitor final
           encode_entry(E, TagImplicitValueInitExpr, childIds);
           return true;
       }
+
+      /*
+       A designator updating part of an element that an earlier initializer set as a whole, like
+       `[0].x` in `{ [0] = s, [0].x = 1 }`.
+       Children:
+       - base expression, the earlier initializer
+       - updater, an initializer list holding `NoInitExpr` for the parts kept from the base
+       */
+      bool VisitDesignatedInitUpdateExpr(DesignatedInitUpdateExpr *E) {
+          std::vector<void*> childIds { E->getBase(), E->getUpdater() };
+          encode_entry(E, TagDesignatedInitUpdateExpr, childIds);
+          return true;
+      }
+
+      bool VisitNoInitExpr(NoInitExpr *E) {
+          std::vector<void*> childIds;
+          encode_entry(E, TagNoInitExpr, childIds);
+          return true;
+      }
       
       bool VisitImplicitCastExpr(ImplicitCastExpr *ICE) {
           std::vector<void*> childIds = { ICE->getSubExpr() };
@@ -887,6 +963,9 @@ class TranslateASTVisitor final
                                  
                                  encode_qualtype(array, computationLHSType);
                                  encode_qualtype(array, computationResultType);
+
+                                 // Whether `#pragma STDC FP_CONTRACT ON` is in effect
+                                 cbor_encode_boolean(array, BO->isFPContractableWithinStatement());
                              });
           return true;
       }
@@ -970,6 +1049,37 @@ class TranslateASTVisitor final
 
                                  auto is_main = FD->isMain();
                                  cbor_encode_boolean(array, is_main);
+
+                                 auto is_pure = FD->hasAttr<PureAttr>();
+                                 cbor_encode_boolean(array, is_pure);
+
+                                 auto is_const = FD->hasAttr<ConstAttr>();
+                                 cbor_encode_boolean(array, is_const);
+
+                                 // `warn_unused_result` on the function or on the type it returns
+                                 auto is_must_use = FD->hasAttr<WarnUnusedResultAttr>();
+                                 if (auto ret = FD->getReturnType()->getAsTagDecl()) {
+                                     is_must_use |= ret->hasAttr<WarnUnusedResultAttr>();
+                                 }
+                                 cbor_encode_boolean(array, is_must_use);
+
+                                 // `section` may only be given on the definition
+                                 auto attrFD = def ? def : FD;
+                                 if (auto section = attrFD->getAttr<SectionAttr>()) {
+                                     cbor_encode_string(array, section->getName().str());
+                                 } else {
+                                     cbor_encode_null(array);
+                                 }
+
+                                 auto is_overloadable = FD->hasAttr<OverloadableAttr>();
+                                 cbor_encode_boolean(array, is_overloadable);
+
+                                 // Target symbol of `__attribute__((alias("target")))`
+                                 if (auto alias = FD->getAttr<AliasAttr>()) {
+                                     cbor_encode_string(array, alias->getAliasee().str());
+                                 } else {
+                                     cbor_encode_null(array);
+                                 }
                              });
           typeEncoder.VisitQualType(functionType);
 
@@ -1009,7 +1119,14 @@ class TranslateASTVisitor final
           
           // Use the type from the definition in case the extern was an incomplete type
           auto T = def->getType();
-          
+
+          // Array parameters decay to pointers. The `DecayedType` sugar itself is unqualified,
+          // so we use the decayed pointer type to keep qualifiers written inside the brackets,
+          // ex: `int a[const]` is `int *const a`
+          if (auto DT = dyn_cast<DecayedType>(T.getTypePtr())) {
+              T = DT->getDecayedType();
+          }
+
           encode_entry(VD, TagVarDecl, childIds, T,
                              [VD, is_defn](CborEncoder *array){
                                  auto name = VD->getNameAsString();
@@ -1022,6 +1139,19 @@ class TranslateASTVisitor final
                                  cbor_encode_boolean(array, is_extern);
 
                                  cbor_encode_boolean(array, is_defn);
+
+                                 // Alignment requested with `__attribute__((aligned))` or
+                                 // `_Alignas`, on the variable or on a typedef of its type, in
+                                 // bytes (0 if none, if the underlying type is already aligned
+                                 // as much, or for parameters, which are copies)
+                                 auto manual_alignment = VD->hasAttr<AlignedAttr>() ? VD->getMaxAlignment() / 8 : 0;
+                                 auto type_alignment = Context->getTypeAlignInChars(VD->getType()).getQuantity();
+                                 if (type_alignment > manual_alignment)
+                                     manual_alignment = type_alignment;
+                                 auto natural_alignment = Context->getTypeAlignInChars(VD->getType().getCanonicalType()).getQuantity();
+                                 if (isa<ParmVarDecl>(VD) || manual_alignment <= natural_alignment)
+                                     manual_alignment = 0;
+                                 cbor_encode_uint(array, manual_alignment);
                              });
           
           typeEncoder.VisitQualType(T);
@@ -1055,7 +1185,7 @@ class TranslateASTVisitor final
           auto tag = D->isStruct() ? TagStructDecl : TagUnionDecl;
           
           encode_entry(D, tag, childIds, QualType(),
-          [D,def](CborEncoder *local){
+          [this,D,def](CborEncoder *local){
               auto name = D->getNameAsString();
               if (name.empty()) {
                   cbor_encode_null(local);
@@ -1063,6 +1193,41 @@ class TranslateASTVisitor final
                   cbor_encode_string(local, name);
               }
               cbor_encode_boolean(local, !!def);
+
+              // Alignment requested with `__attribute__((aligned))`, in bytes (0 if none)
+              auto manual_alignment = def ? def->getMaxAlignment() / 8 : 0;
+              cbor_encode_uint(local, manual_alignment);
+
+              // Field alignment cap from `__attribute__((packed))` or `#pragma pack(N)`,
+              // in bytes (0 if none, or if no field is aligned more than that anyway, since
+              // clang attaches a cap to every record declared under a `#pragma pack`)
+              unsigned max_field_alignment = 0;
+              if (def && def->hasAttr<PackedAttr>()) {
+                  max_field_alignment = 1;
+              } else if (auto *pack = def ? def->getAttr<MaxFieldAlignmentAttr>() : nullptr) {
+                  max_field_alignment = pack->getAlignment() / 8;
+              }
+              if (max_field_alignment) {
+                  unsigned natural_alignment = 1;
+                  for (auto x : def->fields()) {
+                      unsigned field_alignment =
+                          Context->getTypeAlignInChars(x->getType()).getQuantity();
+                      if (x->hasAttr<AlignedAttr>() && x->getMaxAlignment() / 8 > field_alignment)
+                          field_alignment = x->getMaxAlignment() / 8;
+                      if (field_alignment > natural_alignment)
+                          natural_alignment = field_alignment;
+                  }
+                  if (max_field_alignment >= natural_alignment)
+                      max_field_alignment = 0;
+              }
+              cbor_encode_uint(local, max_field_alignment);
+
+              auto is_must_use = (def ? def : D)->hasAttr<WarnUnusedResultAttr>();
+              cbor_encode_boolean(local, is_must_use);
+
+              // Unions passed in the ABI like their first field
+              auto is_transparent = def && def->hasAttr<TransparentUnionAttr>();
+              cbor_encode_boolean(local, is_transparent);
           });
           
           return true;
@@ -1074,14 +1239,21 @@ class TranslateASTVisitor final
           if(!D->isCanonicalDecl())
               return true;
 
+          // The canonical decl may be a forward declaration `enum E;`, which has no
+          // enumerators or underlying type of its own
+          auto def = D->getDefinition();
+
           std::vector<void*> childIds;
-          for (auto x : D->enumerators()) {
-              childIds.push_back(x->getCanonicalDecl());
+          QualType underlying_type;
+          if (def) {
+              for (auto x : def->enumerators()) {
+                  childIds.push_back(x->getCanonicalDecl());
+              }
+
+              underlying_type = def->getIntegerType();
+              typeEncoder.VisitQualType(underlying_type);
           }
           
-          auto underlying_type = D->getIntegerType();
-          typeEncoder.VisitQualType(underlying_type);
-          
           encode_entry(D, TagEnumDecl, childIds, underlying_type,
           [D](CborEncoder *local){
               auto name = D->getNameAsString();
@@ -1153,9 +1325,22 @@ class TranslateASTVisitor final
           std::vector<void*> childIds;
           auto typeForDecl = D->getUnderlyingType();
           encode_entry(D, TagTypedefDecl, childIds, typeForDecl,
-                             [D](CborEncoder *array) {
+                             [D, typeForDecl, this](CborEncoder *array) {
                                  auto name = D->getNameAsString();
                                  cbor_encode_string(array, name);
+
+                                 // Integer modes like `mode(DI)` ask for an exact width
+                                 if (D->hasAttr<ModeAttr>() && typeForDecl->isIntegerType())
+                                     cbor_encode_uint(array, Context->getTypeSize(typeForDecl));
+                                 else
+                                     cbor_encode_null(array);
+
+                                 // Alignment requested with `__attribute__((aligned))`, in bytes
+                                 // (0 if none, or if the underlying type is already aligned as much)
+                                 auto manual_alignment = D->hasAttr<AlignedAttr>() ? D->getMaxAlignment() / 8 : 0;
+                                 if (manual_alignment <= Context->getTypeAlignInChars(typeForDecl).getQuantity())
+                                     manual_alignment = 0;
+                                 cbor_encode_uint(array, manual_alignment);
                              });
 
           typeEncoder.VisitQualType(typeForDecl);
@@ -1163,6 +1348,35 @@ class TranslateASTVisitor final
           return true;
       }
       
+      bool VisitStaticAssertDecl(StaticAssertDecl *D)
+      {
+          std::vector<void*> childIds = { D->getAssertExpr() };
+          encode_entry(D, TagStaticAssertDecl, childIds, QualType(),
+                             [D, this](CborEncoder *array) {
+                                 auto message = D->getMessage();
+                                 if (message) {
+                                     cbor_encode_string(array, message->getString().str());
+                                 } else {
+                                     cbor_encode_null(array);
+                                 }
+
+                                 // The condition as written, to point at the failing assertion
+                                 auto& manager = this->Context->getSourceManager();
+                                 auto range = CharSourceRange::getTokenRange(D->getAssertExpr()->getSourceRange());
+                                 auto condition = Lexer::getSourceText(range, manager, this->Context->getLangOpts());
+                                 cbor_encode_string(array, condition.str());
+
+                                 auto presumed = manager.getPresumedLoc(D->getLocation());
+                                 auto filename = string("?");
+                                 if (presumed.isValid()) {
+                                     filename = presumed.getFilename();
+                                 }
+                                 cbor_encode_string(array, filename);
+                             });
+
+          return true;
+      }
+      
       //
       // Literals
       //
@@ -1179,9 +1393,27 @@ class TranslateASTVisitor final
       bool VisitCharacterLiteral(CharacterLiteral *L) {
           std::vector<void*> childIds;
           encode_entry(L, TagCharacterLiteral, childIds,
-                             [L](CborEncoder *array){
+                             [this, L](CborEncoder *array){
                                  auto lit = L->getValue();
                                  cbor_encode_uint(array, lit);
+
+                                 // Narrow and wide character constants are told
+                                 // apart by the width of a single character unit
+                                 switch(L->getKind()) {
+                                     case CharacterLiteral::CharacterKind::Ascii:
+                                     case CharacterLiteral::CharacterKind::UTF8:
+                                         cbor_encode_uint(array, 1);
+                                         break;
+                                     case CharacterLiteral::CharacterKind::Wide:
+                                         cbor_encode_uint(array, Context->getTypeSizeInChars(Context->getWideCharType()).getQuantity());
+                                         break;
+                                     case CharacterLiteral::CharacterKind::UTF16:
+                                         cbor_encode_uint(array, 2);
+                                         break;
+                                     case CharacterLiteral::CharacterKind::UTF32:
+                                         cbor_encode_uint(array, 4);
+                                         break;
+                                 }
                              });
           return true;
       }
@@ -1288,12 +1520,166 @@ void TypeEncoder::VisitVariableArrayType(const VariableArrayType *T) {
     VisitQualType(t);
 }
 
+// Source location and replacement comment text for each region pragma
+typedef std::vector<std::pair<SourceLocation, std::string>> RegionList;
+
+// Clang discards `#pragma region NAME` and `#pragma endregion`, so we record
+// them here as `// region: NAME` and `// endregion` comments to keep files
+// navigable after translation. The `_Pragma("...")` operator form is handled
+// the same way.
+class RegionPragmaCallbacks : public PPCallbacks {
+    SourceManager &SM;
+    RegionList *regions;
+
+public:
+    RegionPragmaCallbacks(SourceManager &SM, RegionList *regions)
+        : SM(SM), regions(regions) { }
+
+    void PragmaDirective(SourceLocation Loc, PragmaIntroducerKind Introducer) override {
+        std::string text;
+        switch (Introducer) {
+            case PIK_HashPragma:
+                if (!readHashPragma(Loc, text))
+                    return;
+                break;
+            case PIK__Pragma:
+                if (!readOperatorPragma(Loc, text))
+                    return;
+                break;
+            default:
+                return;
+        }
+
+        // `_Pragma` may come from a macro, so the comment goes where it was expanded
+        recordPragma(SM.getExpansionLoc(Loc), StringRef(text).trim());
+    }
+
+private:
+    // `#pragma TEXT`
+    bool readHashPragma(SourceLocation Loc, std::string &out) {
+        bool invalid = false;
+        const char *data = SM.getCharacterData(Loc, &invalid);
+        if (invalid)
+            return false;
+
+        StringRef text(data, strcspn(data, "\r\n"));
+        text = text.ltrim();
+        if (!text.consume_front("#"))
+            return false;
+        text = text.ltrim();
+        if (!text.consume_front("pragma"))
+            return false;
+
+        out = text.str();
+        return true;
+    }
+
+    // `_Pragma("TEXT")`, where TEXT is escaped as a string literal
+    bool readOperatorPragma(SourceLocation Loc, std::string &out) {
+        bool invalid = false;
+        const char *data = SM.getCharacterData(SM.getSpellingLoc(Loc), &invalid);
+        if (invalid)
+            return false;
+
+        StringRef text(data);
+        if (!text.consume_front("_Pragma"))
+            return false;
+        text = text.ltrim();
+        if (!text.consume_front("("))
+            return false;
+        text = text.ltrim();
+        text.consume_front("L");
+        if (!text.consume_front("\""))
+            return false;
+
+        for (size_t i = 0; i < text.size() && text[i] != '"'; i++) {
+            if (text[i] == '\\' && i + 1 < text.size())
+                i++;
+            out.push_back(text[i]);
+        }
+        return true;
+    }
+
+    void recordPragma(SourceLocation Loc, StringRef text) {
+        if (text.consume_front("endregion")) {
+            regions->emplace_back(Loc, "// endregion");
+        } else if (text.consume_front("region")) {
+            auto name = text.trim();
+            if (name.empty()) {
+                regions->emplace_back(Loc, "// region");
+            } else {
+                regions->emplace_back(Loc, "// region: " + name.str());
+            }
+        }
+    }
+};
+
+// Source location, severity, and message of each diagnostic the user asked for
+struct UserDiagnostic {
+    SourceLocation loc;
+    bool is_error;
+    std::string message;
+};
+typedef std::vector<UserDiagnostic> UserDiagnosticList;
+
+// Records the diagnostics coming from `#error`, `#warning`, `#pragma message`,
+// and `#pragma GCC warning`/`error` in the active preprocessing path, so that
+// the importer can report them too, and fail on errors. All diagnostics are
+// still passed on to the original consumer.
+class UserDiagnosticConsumer : public DiagnosticConsumer {
+    DiagnosticConsumer *next;
+    std::unique_ptr<DiagnosticConsumer> owned;
+    UserDiagnosticList *diagnostics;
+
+public:
+    UserDiagnosticConsumer(DiagnosticConsumer *next,
+                           std::unique_ptr<DiagnosticConsumer> owned,
+                           UserDiagnosticList *diagnostics)
+        : next(next), owned(std::move(owned)), diagnostics(diagnostics) { }
+
+    void BeginSourceFile(const LangOptions &LangOpts, const Preprocessor *PP) override {
+        next->BeginSourceFile(LangOpts, PP);
+    }
+
+    void EndSourceFile() override {
+        next->EndSourceFile();
+    }
+
+    void finish() override {
+        next->finish();
+    }
+
+    void HandleDiagnostic(DiagnosticsEngine::Level Level, const Diagnostic &Info) override {
+        DiagnosticConsumer::HandleDiagnostic(Level, Info);
+        next->HandleDiagnostic(Level, Info);
+
+        switch (Info.getID()) {
+            case diag::err_pp_hash_error:
+            case diag::pp_hash_warning:
+            case diag::pp_pragma_message:
+            case diag::warn_pragma_message:
+            case diag::err_pragma_message: {
+                SmallString<100> message;
+                Info.FormatDiagnostic(message);
+                auto loc = Info.getSourceManager().getExpansionLoc(Info.getLocation());
+                diagnostics->push_back({loc, Level >= DiagnosticsEngine::Error, message.str()});
+                break;
+            }
+            default:
+                break;
+        }
+    }
+};
+
 class TranslateConsumer : public clang::ASTConsumer {
     const std::string outfile;
+    const RegionList *regions;
+    const UserDiagnosticList *diagnostics;
 
 public:
-    explicit TranslateConsumer(llvm::StringRef InFile) 
-        : outfile(InFile.str().append(".cbor")) { }
+    explicit TranslateConsumer(llvm::StringRef InFile, const RegionList *regions,
+                               const UserDiagnosticList *diagnostics)
+        : outfile(InFile.str().append(".cbor")), regions(regions), diagnostics(diagnostics) { }
     
     virtual void HandleTranslationUnit(clang::ASTContext &Context) {
   
@@ -1305,7 +1691,7 @@ public:
         // type instead.
         std::unordered_map<void*, QualType> sugared;
         
-        auto process = [&encoder, &Context, &sugared](uint8_t *buffer, size_t len)
+        auto process = [this, &encoder, &Context, &sugared](uint8_t *buffer, size_t len)
         {
             cbor_encoder_init(&encoder, buffer, len, 0);
             
@@ -1326,11 +1712,14 @@ public:
             }
             cbor_encoder_close_container(&encoder, &array);
             
-            // Encode all of the visited file names
+            // Encode all of the visited file names, in the order of their file ids
             auto filenames = visitor.getFilenames();
-            cbor_encoder_create_array(&encoder, &array, filenames.size());
+            std::vector<string> ordered_filenames(filenames.size());
             for (auto &kv : filenames) {
-                auto str = kv.first;
+                ordered_filenames[kv.second] = kv.first;
+            }
+            cbor_encoder_create_array(&encoder, &array, ordered_filenames.size());
+            for (auto &str : ordered_filenames) {
                 cbor_encode_string(&array, str);
             }
             cbor_encoder_close_container(&encoder, &array);
@@ -1340,7 +1729,7 @@ public:
             //
             // Getting all comments will require processing the file with -fparse-all-comments !
             auto comments = Context.getRawCommentList().getComments();
-            cbor_encoder_create_array(&encoder, &array, comments.size());
+            cbor_encoder_create_array(&encoder, &array, comments.size() + regions->size());
             for (auto comment : comments) {
                 CborEncoder entry;
                 cbor_encoder_create_array(&array, &entry, 4);
@@ -1348,6 +1737,27 @@ public:
                 cbor_encode_string(&entry, comment->getRawText(Context.getSourceManager()).str());
                 cbor_encoder_close_container(&array, &entry);
             }
+            // Region pragmas are emitted as comments in the same format
+            for (auto &region : *regions) {
+                CborEncoder entry;
+                cbor_encoder_create_array(&array, &entry, 4);
+                visitor.encodeSourcePos(&entry, region.first); // emits 3 values
+                cbor_encode_string(&entry, region.second);
+                cbor_encoder_close_container(&array, &entry);
+            }
+            cbor_encoder_close_container(&encoder, &array);
+
+            // Emit user diagnostics as array of arrays. Each diagnostic is represented
+            // as an array of source position, whether it is an error, and its message.
+            cbor_encoder_create_array(&encoder, &array, diagnostics->size());
+            for (auto &diagnostic : *diagnostics) {
+                CborEncoder entry;
+                cbor_encoder_create_array(&array, &entry, 5);
+                visitor.encodeSourcePos(&entry, diagnostic.loc); // emits 3 values
+                cbor_encode_boolean(&entry, diagnostic.is_error);
+                cbor_encode_string(&entry, diagnostic.message);
+                cbor_encoder_close_container(&array, &entry);
+            }
             cbor_encoder_close_container(&encoder, &array);
         };
         
@@ -1366,11 +1776,21 @@ public:
 };
 
 class TranslateAction : public clang::ASTFrontendAction {
-    
+    RegionList regions;
+    UserDiagnosticList diagnostics;
+
 public:
   virtual std::unique_ptr<clang::ASTConsumer> CreateASTConsumer(
     clang::CompilerInstance &Compiler, llvm::StringRef InFile) {
-    return std::unique_ptr<clang::ASTConsumer>(new TranslateConsumer(InFile));
+    Compiler.getPreprocessor().addPPCallbacks(
+        llvm::make_unique<RegionPragmaCallbacks>(Compiler.getSourceManager(), &regions));
+
+    auto &Diags = Compiler.getDiagnostics();
+    std::unique_ptr<DiagnosticConsumer> owned = Diags.ownsClient() ? Diags.takeClient() : nullptr;
+    DiagnosticConsumer *next = owned ? owned.get() : Diags.getClient();
+    Diags.setClient(new UserDiagnosticConsumer(next, std::move(owned), &diagnostics), true);
+
+    return std::unique_ptr<clang::ASTConsumer>(new TranslateConsumer(InFile, &regions, &diagnostics));
   }
 };
 
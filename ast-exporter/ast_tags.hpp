@@ -20,6 +20,7 @@ enum ASTEntryTag {
     TagEnumConstantDecl,
     TagTypedefDecl,
     TagUnionDecl,
+    TagStaticAssertDecl,
     
     
     TagCompoundStmt = 100,
@@ -65,6 +66,9 @@ enum ASTEntryTag {
     TagPredefinedExpr,
     TagStmtExpr,
     TagVAArgExpr,
+    TagDesignatedInitUpdateExpr,
+    TagNoInitExpr,
+    TagAtomicExpr,
     
     TagIntegerLiteral = 300,
     TagStringLiteral,
@@ -141,6 +141,7 @@ fn parse_cast_kind(kind: &str) -> CastKind {
         "IntegralComplexToFloatingComplex" => CastKind::IntegralComplexToFloatingComplex,
         "BuiltinFnToFnPtr" => CastKind::BuiltinFnToFnPtr,
         "ConstCast" => CastKind::ConstCast,
+        "VectorSplat" => CastKind::VectorSplat,
         k => panic!("Unsupported implicit cast: {}", k),
     }
 }
@@ -287,6 +288,10 @@ impl ConversionContext {
     /// This populates the `typed_context` of the `ConversionContext` it is called on.
     pub fn convert(&mut self, untyped_context: &AstContext) -> () {
 
+        for (fileid, file) in untyped_context.files.iter().enumerate() {
+            self.typed_context.c_files.insert(fileid as u64, file.clone());
+        }
+
         for raw_comment in &untyped_context.comments {
             let comment = Located {
                 loc: Some(SrcLoc {
@@ -587,6 +592,17 @@ impl ConversionContext {
                     self.processed_nodes.insert(new_id, OTHER_TYPE);
                 }
 
+                TypeTag::TagVectorType => {
+                    let element_id = expect_u64(&ty_node.extras[0]).expect("element id");
+                    let element = self.visit_type(element_id);
+
+                    let count = expect_u64(&ty_node.extras[1]).expect("count");
+
+                    let vector_ty = CTypeKind::Vector(element, count as usize);
+                    self.add_type(new_id, not_located(vector_ty));
+                    self.processed_nodes.insert(new_id, OTHER_TYPE);
+                }
+
                 TypeTag::TagIncompleteArrayType => {
                     let element_id = expect_u64(&ty_node.extras[0]).expect("element id");
                     let element = self.visit_type(element_id);
@@ -880,11 +896,12 @@ impl ConversionContext {
 
                 ASTEntryTag::TagCharacterLiteral if expected_ty & (EXPR | STMT) != 0 => {
                     let value = expect_u64(&node.extras[0]).expect("Expected character literal value");
+                    let width = expect_u64(&node.extras[1]).expect("Expected character literal width") as u8;
 
                     let ty_old = node.type_id.expect("Expected expression to have type");
                     let ty = self.visit_qualified_type(ty_old);
 
-                    let character_literal = CExprKind::Literal(ty, CLiteral::Character(value));
+                    let character_literal = CExprKind::Literal(ty, CLiteral::Character(value, width));
 
                     self.expr_possibly_as_stmt(expected_ty, new_id, node, character_literal);
                 }
@@ -900,6 +917,16 @@ impl ConversionContext {
                     self.expr_possibly_as_stmt(expected_ty, new_id, node, floating_literal);
                 }
 
+                // `__extension__` only silences pedantic warnings, so it is as transparent as
+                // parentheses are. It may also wrap lvalues and statement expressions.
+                ASTEntryTag::TagUnaryOperator if expected_ty & (EXPR | STMT) != 0 &&
+                    expect_str(&node.extras[0]).ok() == Some("__extension__") => {
+                    let wrapped = node.children[0].expect("Expected operand");
+
+                    self.id_mapper.merge_old(node_id, wrapped);
+                    self.visit_node_type(wrapped, expected_ty);
+                }
+
                 ASTEntryTag::TagUnaryOperator if expected_ty & (EXPR | STMT) != 0 => {
 
                     let prefix = expect_bool(&node.extras[1]).expect("Expected prefix information");
@@ -1059,6 +1086,10 @@ impl ConversionContext {
                     let opt_res_type_id = expect_opt_u64(&node.extras[2]).expect("Expected compute lhs type");
                     let opt_res_type = opt_res_type_id.map(|x| self.visit_qualified_type(x));
 
+                    if expect_bool(&node.extras[3]).expect("Expected FP contraction flag") {
+                        self.typed_context.fp_contractable.insert(CExprId(new_id));
+                    }
+
                     let binary = CExprKind::Binary(ty, operator, left_operand, right_operand, opt_lhs_type, opt_res_type);
 
                     self.expr_possibly_as_stmt(expected_ty, new_id, node, binary);
@@ -1172,6 +1203,45 @@ impl ConversionContext {
                     self.expr_possibly_as_stmt(expected_ty, new_id, node, CExprKind::ImplicitValueInit(ty))
                 }
 
+                ASTEntryTag::TagDesignatedInitUpdateExpr => {
+                    let base = self.visit_expr(node.children[0].expect("Expected base initializer"));
+                    let updater = self.visit_expr(node.children[1].expect("Expected updater"));
+
+                    let ty_old = node.type_id.expect("Expected expression to have type");
+                    let ty = self.visit_qualified_type(ty_old);
+
+                    let update_expr = CExprKind::DesignatedInitUpdate(ty, base, updater);
+
+                    self.expr_possibly_as_stmt(expected_ty, new_id, node, update_expr)
+                }
+
+                ASTEntryTag::TagNoInitExpr => {
+                    let ty_old = node.type_id.expect("Expected expression to have type");
+                    let ty = self.visit_qualified_type(ty_old);
+
+                    self.expr_possibly_as_stmt(expected_ty, new_id, node, CExprKind::NoInit(ty))
+                }
+
+                ASTEntryTag::TagAtomicExpr if expected_ty & (EXPR | STMT) != 0 => {
+                    let name = expect_str(&node.extras[0])
+                        .expect("Expected atomic builtin name").to_string();
+
+                    let ptr = node.children[0].expect("Expected atomic pointer operand");
+                    let ptr = self.visit_expr(ptr);
+                    let order = node.children[1].map(|x| self.visit_expr(x));
+                    let val1 = node.children[2].map(|x| self.visit_expr(x));
+                    let order_fail = node.children[3].map(|x| self.visit_expr(x));
+                    let val2 = node.children[4].map(|x| self.visit_expr(x));
+                    let weak = node.children[5].map(|x| self.visit_expr(x));
+
+                    let ty_old = node.type_id.expect("Expected expression to have type");
+                    let ty = self.visit_qualified_type(ty_old);
+
+                    let atomic = CExprKind::Atomic { typ: ty, name, ptr, order, val1, order_fail, val2, weak };
+
+                    self.expr_possibly_as_stmt(expected_ty, new_id, node, atomic)
+                }
+
                 ASTEntryTag::TagInitListExpr => {
 
                     let exprs: Vec<CExprId> = node.children
@@ -1228,6 +1298,13 @@ impl ConversionContext {
                         self.typed_context.c_main = Some(CDeclId(new_id));
                     }
 
+                    let is_pure = expect_bool(&node.extras[4]).expect("Expected to find pure attribute");
+                    let is_const = expect_bool(&node.extras[5]).expect("Expected to find const attribute");
+                    let is_must_use = expect_bool(&node.extras[6]).expect("Expected to find warn_unused_result attribute");
+                    let section = expect_opt_str(&node.extras[7]).expect("Expected to find section attribute").map(str::to_string);
+                    let is_overloadable = expect_bool(&node.extras[8]).expect("Expected to find overloadable attribute");
+                    let alias = expect_opt_str(&node.extras[9]).expect("Expected to find alias attribute").map(str::to_string);
+
                     let typ_old = node.type_id.expect("Expected to find a type on a function decl");
                     let typ = CTypeId(self.visit_node_type(typ_old, TYPE));
 
@@ -1244,7 +1321,7 @@ impl ConversionContext {
                         })
                         .collect();
 
-                    let function_decl = CDeclKind::Function { is_extern, is_inline, typ, name, parameters, body };
+                    let function_decl = CDeclKind::Function { is_extern, is_inline, is_pure, is_const, is_must_use, section, is_overloadable, alias, typ, name, parameters, body };
 
                     self.add_decl(new_id, located(node, function_decl));
                     self.processed_nodes.insert(new_id, OTHER_DECL);
@@ -1256,7 +1333,12 @@ impl ConversionContext {
                     let typ_old = node.type_id.expect("Expected to find type on typedef declaration");
                     let typ = self.visit_qualified_type(typ_old);
 
-                    let typdef_decl = CDeclKind::Typedef { name, typ };
+                    let mode_width = node.extras.get(1).and_then(|w| expect_u64(w).ok());
+
+                    let manual_alignment = expect_u64(&node.extras[2]).expect("Expected alignment on typedef");
+                    let manual_alignment = if manual_alignment == 0 { None } else { Some(manual_alignment) };
+
+                    let typdef_decl = CDeclKind::Typedef { name, typ, mode_width, manual_alignment };
 
                     self.add_decl(new_id, located(node, typdef_decl));
                     self.processed_nodes.insert(new_id, TYPDEF_DECL);
@@ -1312,7 +1394,10 @@ impl ConversionContext {
                     let typ_id = node.type_id.expect("Expected to find type on variable declaration");
                     let typ = self.visit_qualified_type(typ_id);
 
-                    let variable_decl = CDeclKind::Variable { is_static, is_extern, is_defn, ident, initializer, typ };
+                    let manual_alignment = expect_u64(&node.extras[4]).expect("Expected alignment on variable");
+                    let manual_alignment = if manual_alignment == 0 { None } else { Some(manual_alignment) };
+
+                    let variable_decl = CDeclKind::Variable { is_static, is_extern, is_defn, ident, initializer, typ, manual_alignment };
 
                     self.add_decl(new_id, located(node, variable_decl));
                     self.processed_nodes.insert(new_id, VAR_DECL);
@@ -1336,7 +1421,13 @@ impl ConversionContext {
                         None
                     };
 
-                    let record = CDeclKind::Struct { name, fields };
+                    let manual_alignment = expect_u64(&node.extras[2]).expect("Expected alignment on record");
+                    let manual_alignment = if manual_alignment == 0 { None } else { Some(manual_alignment) };
+                    let max_field_alignment = expect_u64(&node.extras[3]).expect("Expected packing on record");
+                    let max_field_alignment = if max_field_alignment == 0 { None } else { Some(max_field_alignment) };
+                    let is_must_use = expect_bool(&node.extras[4]).expect("Expected warn_unused_result on record");
+
+                    let record = CDeclKind::Struct { name, fields, manual_alignment, max_field_alignment, is_must_use };
 
                     self.add_decl(new_id, located(node, record));
                     self.processed_nodes.insert(new_id, RECORD_DECL);
@@ -1360,7 +1451,14 @@ impl ConversionContext {
                             None
                         };
 
-                    let record = CDeclKind::Union { name, fields };
+                    let manual_alignment = expect_u64(&node.extras[2]).expect("Expected alignment on record");
+                    let manual_alignment = if manual_alignment == 0 { None } else { Some(manual_alignment) };
+                    let max_field_alignment = expect_u64(&node.extras[3]).expect("Expected packing on record");
+                    let max_field_alignment = if max_field_alignment == 0 { None } else { Some(max_field_alignment) };
+                    let is_must_use = expect_bool(&node.extras[4]).expect("Expected warn_unused_result on record");
+                    let is_transparent = expect_bool(&node.extras[5]).expect("Expected transparent_union on record");
+
+                    let record = CDeclKind::Union { name, fields, manual_alignment, max_field_alignment, is_must_use, is_transparent };
 
                     self.add_decl(new_id, located(node, record));
                     self.processed_nodes.insert(new_id, RECORD_DECL);
@@ -1370,11 +1468,25 @@ impl ConversionContext {
                     let name = expect_str(&node.extras[0]).expect("A field needs a name").to_string();
                     let typ_id = node.type_id.expect("Expected to find type on field declaration");
                     let typ = self.visit_qualified_type(typ_id);
-                    let field = CDeclKind::Field { name, typ };
+                    let bitfield_width = expect_opt_u64(&node.extras[1]).expect("Expected to find bit-field width");
+                    let field = CDeclKind::Field { name, typ, bitfield_width };
                     self.add_decl(new_id, located(node, field));
                     self.processed_nodes.insert(new_id, FIELD_DECL);
                 }
 
+                ASTEntryTag::TagStaticAssertDecl if expected_ty & OTHER_DECL != 0 => {
+                    let assert_id = node.children[0].expect("Expected to find static assertion condition");
+                    let assert_expr = self.visit_expr(assert_id);
+                    let message = expect_opt_str(&node.extras[0]).expect("Expected to find static assertion message").map(str::to_string);
+                    let condition = expect_str(&node.extras[1]).expect("Expected to find static assertion condition text").to_string();
+                    let file = expect_str(&node.extras[2]).expect("Expected to find static assertion file").to_string();
+
+                    let static_assert = CDeclKind::StaticAssert { assert_expr, message, condition, file };
+
+                    self.add_decl(new_id, located(node, static_assert));
+                    self.processed_nodes.insert(new_id, OTHER_DECL);
+                }
+
                 t => panic!("Could not translate node {:?} as type {}", t, expected_ty),
             }
         }
@@ -46,7 +46,7 @@ impl<'context> DFExpr<'context> {
 fn immediate_expr_children(kind: &CExprKind) -> Vec<SomeId> {
     use c_ast::CExprKind::*;
     match *kind {
-        OffsetOf(..) | Literal(..) | ImplicitValueInit(..) => vec![],
+        OffsetOf(..) | Literal(..) | ImplicitValueInit(..) | NoInit(..) => vec![],
         DeclRef(_, _) => vec![], // don't follow references back!
         Unary(_ty, _op, subexpr) => intos![subexpr],
         UnaryType(_ty, _op, opt_expr_id, _) => opt_expr_id.iter().map(|&x| x.into()).collect(),
@@ -57,12 +57,20 @@ fn immediate_expr_children(kind: &CExprKind) -> Vec<SomeId> {
             res
         }
         ArraySubscript(_, l, r) => intos![l,r],
+        DesignatedInitUpdate(_, b, u) => intos![b,u],
         Conditional(_, c, t, e) => intos![c,t,e],
         BinaryConditional(_, c, t) => intos![c,t],
         InitList(_, ref xs, _) => xs.iter().map(|&x| x.into()).collect(),
         ImplicitCast(_, e, _, _) | ExplicitCast(_, e, _, _) |
         Member(_, e, _, _) | CompoundLiteral(_, e) | Predefined(_, e) | VAArg(_,e) => intos![e],
         Statements(_, s) => vec![s.into()],
+        Atomic { ptr, order, val1, order_fail, val2, weak, .. } => {
+            let mut res = intos![ptr];
+            for &x in [order, val1, order_fail, val2, weak].iter() {
+                res.extend(x.map(|x| x.into()))
+            }
+            res
+        }
     }
 }
 
@@ -88,6 +96,7 @@ fn immediate_decl_children(kind: &CDeclKind) -> Vec<SomeId> {
         Union { ref fields, .. } =>
             fields.iter().flat_map(|x| x).map(|&x| x.into()).collect(),
         Field { typ, .. } => intos![typ.ctype],
+        StaticAssert { assert_expr, .. } => intos![assert_expr],
     }
 }
 
@@ -170,7 +179,7 @@ fn immediate_type_children(kind: &CTypeKind) -> Vec<SomeId> {
         Pointer(qtype) | Attributed(qtype, _) | BlockPointer(qtype) => intos![qtype.ctype],
 
         Decayed(ctype) | Paren(ctype) | TypeOf(ctype) | Complex(ctype) |
-        ConstantArray(ctype, _) | IncompleteArray(ctype) => intos![ctype],
+        ConstantArray(ctype, _) | IncompleteArray(ctype) | Vector(ctype, _) => intos![ctype],
 
         Struct(decl_id) | Union(decl_id) | Enum(decl_id) | Typedef(decl_id) => intos![decl_id],
 
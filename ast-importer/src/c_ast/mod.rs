@@ -1,5 +1,6 @@
 use std::collections::{HashMap,HashSet};
 use std::ops::Index;
+use c_ast::iterators::{DFExpr, SomeId};
 
 #[derive(Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Copy, Clone)]
 pub struct CTypeId(pub u64);
@@ -24,7 +25,7 @@ pub type CEnumId = CDeclId;  // Enum types need to point to 'DeclKind::Enum'
 pub type CEnumConstantId = CDeclId;  // Enum's need to point to child 'DeclKind::EnumConstant's
 
 pub use self::conversion::*;
-pub use self::print::Printer;
+pub use self::print::{Printer, c_signature};
 
 mod conversion;
 mod print;
@@ -43,6 +44,10 @@ pub struct TypedAstContext {
     pub c_files: HashMap<u64, String>,
     pub parents: HashMap<CDeclId, CDeclId>, // record fields and enum constants
 
+    /// Binary operators in scope of a `#pragma STDC FP_CONTRACT ON`, which allows a floating
+    /// point multiplication and addition to be fused
+    pub fp_contractable: HashSet<CExprId>,
+
     pub comments: Vec<Located<String>>,
 }
 
@@ -65,11 +70,20 @@ impl TypedAstContext {
             c_main: None,
             c_files: HashMap::new(),
             parents: HashMap::new(),
+            fp_contractable: HashSet::new(),
 
             comments: vec![],
         }
     }
 
+    /// Render a source location as `file:line:column`, for diagnostics
+    pub fn display_loc(&self, loc: &Option<SrcLoc>) -> Option<String> {
+        loc.as_ref().map(|loc| {
+            let file = self.c_files.get(&loc.fileid).map_or("<unknown file>", String::as_str);
+            format!("{}:{}:{}", file, loc.line, loc.column)
+        })
+    }
+
     pub fn is_null_expr(&self, expr_id: CExprId) -> bool {
         match self[expr_id].kind {
             CExprKind::ExplicitCast(_, _, CastKind::NullToPointer, _) |
@@ -114,11 +128,153 @@ impl TypedAstContext {
         }
     }
 
+    /// The record or enum declaration a type spells out directly, looking through elaboration
+    /// but not through typedefs
+    pub fn direct_underlying_decl(&self, typ: CTypeId) -> Option<CDeclId> {
+        match self.index(typ).kind {
+            CTypeKind::Attributed(ty, _) => self.direct_underlying_decl(ty.ctype),
+            CTypeKind::Elaborated(ty) => self.direct_underlying_decl(ty),
+            CTypeKind::Paren(ty) => self.direct_underlying_decl(ty),
+            ref kind => kind.as_underlying_decl(),
+        }
+    }
+
     pub fn resolve_type(&self, typ: CTypeId) -> &CType {
         let resolved_typ_id = self.resolve_type_id(typ);
         self.index(resolved_typ_id)
     }
 
+    /// For a `transparent_union` type, the first field, along with its type. Arguments of such
+    /// types are passed like that field.
+    pub fn transparent_union_member(&self, typ: CTypeId) -> Option<(CFieldId, CQualTypeId)> {
+        let union_id = match self.resolve_type(typ).kind {
+            CTypeKind::Union(union_id) => union_id,
+            _ => return None,
+        };
+        let field_id = match self[union_id].kind {
+            CDeclKind::Union { is_transparent: true, fields: Some(ref fields), .. } => *fields.first()?,
+            _ => return None,
+        };
+        match self[field_id].kind {
+            CDeclKind::Field { typ, .. } => Some((field_id, typ)),
+            _ => None,
+        }
+    }
+
+    /// The functions and variables this translation unit defines with external linkage, with
+    /// their names
+    pub fn exported_symbols(&self) -> Vec<(CDeclId, &str)> {
+        let mut symbols = vec![];
+        for &decl_id in &self.c_decls_top {
+            match self[decl_id].kind {
+                CDeclKind::Function { is_extern: true, is_inline: false, body: Some(_), ref name, .. } =>
+                    symbols.push((decl_id, name.as_str())),
+                CDeclKind::Function { is_extern: true, alias: Some(_), ref name, .. } =>
+                    symbols.push((decl_id, name.as_str())),
+                CDeclKind::Variable { is_extern: true, is_defn: true, ref ident, .. } =>
+                    symbols.push((decl_id, ident.as_str())),
+                _ => {}
+            }
+        }
+        symbols
+    }
+
+    /// Evaluate an integer constant expression, such as the condition of a ternary in the
+    /// initializer of a static. Returns `None` when the value can't be determined here, for
+    /// instance when it depends on the address of an object or on `sizeof`.
+    pub fn eval_const_int(&self, expr: CExprId) -> Option<i128> {
+        match self.index(expr).kind {
+            CExprKind::Literal(ty, CLiteral::Integer(v)) |
+            CExprKind::Literal(ty, CLiteral::Character(v, _)) => self.wrap_const_int(ty.ctype, v as i128),
+
+            CExprKind::DeclRef(_, decl_id) => match self.index(decl_id).kind {
+                CDeclKind::EnumConstant { value: ConstIntExpr::I(v), .. } => Some(v as i128),
+                CDeclKind::EnumConstant { value: ConstIntExpr::U(v), .. } => Some(v as i128),
+                _ => None,
+            },
+
+            CExprKind::ImplicitCast(ty, e, kind, _) | CExprKind::ExplicitCast(ty, e, kind, _) => {
+                match kind {
+                    CastKind::IntegralCast | CastKind::NoOp =>
+                        self.wrap_const_int(ty.ctype, self.eval_const_int(e)?),
+                    CastKind::IntegralToBoolean => Some((self.eval_const_int(e)? != 0) as i128),
+                    _ => None,
+                }
+            }
+
+            CExprKind::Unary(ty, op, e) => {
+                let v = self.eval_const_int(e)?;
+                let v = match op {
+                    UnOp::Plus | UnOp::Extension => v,
+                    UnOp::Negate => -v,
+                    UnOp::Complement => !v,
+                    UnOp::Not => (v == 0) as i128,
+                    _ => return None,
+                };
+                self.wrap_const_int(ty.ctype, v)
+            }
+
+            // Only the operand that's evaluated at runtime needs to be constant
+            CExprKind::Binary(_, BinOp::And, lhs, rhs, _, _) =>
+                Some((self.eval_const_int(lhs)? != 0 && self.eval_const_int(rhs)? != 0) as i128),
+            CExprKind::Binary(_, BinOp::Or, lhs, rhs, _, _) =>
+                Some((self.eval_const_int(lhs)? != 0 || self.eval_const_int(rhs)? != 0) as i128),
+
+            CExprKind::Binary(ty, op, lhs, rhs, _, _) => {
+                let l = self.eval_const_int(lhs)?;
+                let r = self.eval_const_int(rhs)?;
+                let v = match op {
+                    BinOp::Multiply => l.checked_mul(r)?,
+                    BinOp::Divide => l.checked_div(r)?,
+                    BinOp::Modulus => l.checked_rem(r)?,
+                    BinOp::Add => l.checked_add(r)?,
+                    BinOp::Subtract => l.checked_sub(r)?,
+                    BinOp::ShiftLeft if 0 <= r && r < 64 => l.checked_mul(1 << r)?,
+                    BinOp::ShiftRight if 0 <= r && r < 64 => l >> r,
+                    BinOp::Less => (l < r) as i128,
+                    BinOp::Greater => (l > r) as i128,
+                    BinOp::LessEqual => (l <= r) as i128,
+                    BinOp::GreaterEqual => (l >= r) as i128,
+                    BinOp::EqualEqual => (l == r) as i128,
+                    BinOp::NotEqual => (l != r) as i128,
+                    BinOp::BitAnd => l & r,
+                    BinOp::BitXor => l ^ r,
+                    BinOp::BitOr => l | r,
+                    _ => return None,
+                };
+                self.wrap_const_int(ty.ctype, v)
+            }
+
+            CExprKind::Conditional(_, cond, lhs, rhs) =>
+                self.eval_const_int(if self.eval_const_int(cond)? != 0 { lhs } else { rhs }),
+
+            _ => None,
+        }
+    }
+
+    /// Bring a value into the range of an integer type, the way a conversion to it would.
+    /// This assumes an LP64 target.
+    fn wrap_const_int(&self, ty: CTypeId, v: i128) -> Option<i128> {
+        let kind = &self.resolve_type(ty).kind;
+        let bits = match *kind {
+            CTypeKind::Bool => return Some((v != 0) as i128),
+            CTypeKind::Char | CTypeKind::SChar | CTypeKind::UChar => 8,
+            CTypeKind::Short | CTypeKind::UShort => 16,
+            CTypeKind::Int | CTypeKind::UInt => 32,
+            CTypeKind::Long | CTypeKind::ULong | CTypeKind::LongLong | CTypeKind::ULongLong => 64,
+            CTypeKind::Int128 => return Some(v),
+            // Wrapping a negative value gives one above `i128::max_value()`, which this can't hold
+            CTypeKind::UInt128 => return if v < 0 { None } else { Some(v) },
+            _ => return None,
+        };
+        let unsigned = (v as u128) & ((1u128 << bits) - 1);
+        if kind.is_unsigned_integral_type() || unsigned < 1u128 << (bits - 1) {
+            Some(unsigned as i128)
+        } else {
+            Some(unsigned as i128 - (1i128 << bits))
+        }
+    }
+
     /// Pessimistically try to check if an expression has side effects. If it does, or we can't tell
     /// that it doesn't, return `false`.
     pub fn is_expr_pure(&self, expr: CExprId) -> bool {
@@ -131,8 +287,11 @@ impl TypedAstContext {
             CExprKind::Binary(_, BinOp::Assign, _, _, _, _) |
             CExprKind::InitList { .. } |
             CExprKind::ImplicitValueInit { .. } |
+            CExprKind::DesignatedInitUpdate(..) |
+            CExprKind::NoInit(..) |
             CExprKind::Predefined(_, _) |
-            CExprKind::Statements(..) => false, // TODO: more precision
+            CExprKind::Statements(..) |
+            CExprKind::Atomic { .. } => false, // TODO: more precision
 
             CExprKind::Literal(_, _) |
             CExprKind::DeclRef(_, _) |
@@ -183,14 +342,19 @@ impl TypedAstContext {
         // Vector of types that need to be visited which can cause declarations to be live
         let mut type_queue: Vec<CTypeId> = vec![];
 
-        // All variable and function definitions are considered live
+        // All variable and function definitions (aliases included) and static assertions are
+        // considered live, except for overloads, which headers like `<tgmath.h>` define by the
+        // hundred. Those are live when they're called.
         for (&decl_id, decl) in &self.c_decls {
             match decl.kind {
-                CDeclKind::Function { typ, body: Some(_), .. } => {
+                CDeclKind::Function { is_overloadable: true, .. } => {}
+                CDeclKind::Function { typ, body: Some(_), .. } |
+                CDeclKind::Function { typ, alias: Some(_), .. } => {
                     live.insert(decl_id);
                     type_queue.push(typ); // references the return type
                 }
                 CDeclKind::Variable { is_defn: true, .. } => { live.insert(decl_id); }
+                CDeclKind::StaticAssert { .. } => { live.insert(decl_id); }
                 _ => {}
             }
         }
@@ -224,28 +388,65 @@ impl TypedAstContext {
             }
         }
 
-        // All expressions are considered live (this is an overapproximation if an otherwise
-        // unused function declaration uses a VLA and that VLA's size expression mentions
-        // some definitions.
-        for expr in self.c_exprs.values() {
-            type_queue.push(expr.kind.get_type());
-
-            match expr.kind {
-                // Could mention external functions, variables, and enum constants
-                CExprKind::DeclRef(_, decl_id) => {
-                    live.insert(decl_id);
-                    // This declref could refer to an enum constant, so we want to keep the enum
-                    // declaration for that constant live
-                    if let Some(&parent_id) = self.parents.get(&decl_id) {
-                        if live.insert(parent_id) {
-                            if let CDeclKind::Enum { ref variants, .. } = self[parent_id].kind {
-                                live.extend(variants);
+        // Expressions in the body of an overload only count once the overload is live
+        let mut overload_exprs: HashMap<CDeclId, Vec<CExprId>> = HashMap::new();
+        for (&decl_id, decl) in &self.c_decls {
+            if let CDeclKind::Function { is_overloadable: true, body: Some(body), .. } = decl.kind {
+                let exprs = DFExpr::new(self, body.into()).filter_map(|id| match id {
+                    SomeId::Expr(expr_id) => Some(expr_id),
+                    _ => None,
+                }).collect();
+                overload_exprs.insert(decl_id, exprs);
+            }
+        }
+        let in_overloads: HashSet<CExprId> = overload_exprs.values().flat_map(|exprs| exprs.iter().cloned()).collect();
+
+        // `mark_expr` borrows the context, which is pruned below
+        {
+            let mark_expr = |expr: &CExprKind, live: &mut HashSet<CDeclId>, type_queue: &mut Vec<CTypeId>| {
+                type_queue.push(expr.get_type());
+
+                match *expr {
+                    // Could mention external functions, variables, and enum constants
+                    CExprKind::DeclRef(_, decl_id) => {
+                        live.insert(decl_id);
+                        // This declref could refer to an enum constant, so we want to keep the enum
+                        // declaration for that constant live
+                        if let Some(&parent_id) = self.parents.get(&decl_id) {
+                            if live.insert(parent_id) {
+                                if let CDeclKind::Enum { ref variants, .. } = self[parent_id].kind {
+                                    live.extend(variants);
+                                }
                             }
                         }
                     }
+                    CExprKind::UnaryType(_, _, _, type_id) => { type_queue.push(type_id.ctype); }
+                    _ => {}
+                }
+            };
+
+            // All other expressions are considered live (this is an overapproximation if an
+            // otherwise unused function declaration uses a VLA and that VLA's size expression
+            // mentions some definitions.
+            for (expr_id, expr) in &self.c_exprs {
+                if !in_overloads.contains(expr_id) {
+                    mark_expr(&expr.kind, &mut live, &mut type_queue);
+                }
+            }
+
+            // Overloads called from live code, or from overloads that are live themselves
+            loop {
+                let called: Vec<CDeclId> = overload_exprs.keys().filter(|decl_id| live.contains(decl_id)).cloned().collect();
+                if called.is_empty() { break }
+
+                for decl_id in called {
+                    if let CDeclKind::Function { typ, .. } = self[decl_id].kind {
+                        type_queue.push(typ);
+                    }
+                    for expr_id in overload_exprs.remove(&decl_id).unwrap_or_default() {
+                        mark_expr(&self[expr_id].kind, &mut live, &mut type_queue);
+                    }
                 }
-                CExprKind::UnaryType(_, _, _, type_id) => { type_queue.push(type_id.ctype); }
-                _ => {}
             }
         }
 
@@ -255,7 +456,8 @@ impl TypedAstContext {
         self.c_decls.retain(|&decl_id, decl| {
             if live.contains(&decl_id) { return true; }
             match &decl.kind {
-                &CDeclKind::Function { body: None, ref parameters, .. } => {
+                &CDeclKind::Function { body: None, ref parameters, .. } |
+                &CDeclKind::Function { is_overloadable: true, ref parameters, .. } => {
                     bad_variables.extend(parameters);
                     false
                 },
@@ -294,7 +496,8 @@ impl TypedAstContext {
 
                 // Types with CTypeId fields
                 CTypeKind::Complex(type_id) | CTypeKind::Paren(type_id) |
-                CTypeKind::ConstantArray(type_id, _) | CTypeKind::Elaborated(type_id) |
+                CTypeKind::ConstantArray(type_id, _) | CTypeKind::Vector(type_id, _) |
+                CTypeKind::Elaborated(type_id) |
                 CTypeKind::TypeOf(type_id) | CTypeKind::Decayed(type_id) |
                 CTypeKind::IncompleteArray(type_id) | CTypeKind::VariableArray(type_id, _)
                 => type_queue.push(type_id),
@@ -402,7 +605,9 @@ impl CommentContext {
         let empty_vec2 = &vec![];
 
 
-        // Match comments to declarations and statements
+        // Match comments to declarations and statements. Comments are popped off in source order
+        // so that those attached to the same node keep their relative order.
+        ast_context.comments.sort_by(|a, b| b.loc.cmp(&a.loc));
         while let Some(Located { loc, kind: comment_str }) = ast_context.comments.pop() {
             if let Some(loc) = loc {
                 let this_file_decls = decls.get(&loc.fileid).unwrap_or(empty_vec1);
@@ -524,6 +729,16 @@ pub enum CDeclKind {
     Function {
         is_extern: bool,
         is_inline: bool,
+        is_pure: bool,
+        is_const: bool,
+        is_must_use: bool,
+        /// The name given by `__attribute__((section("name")))`
+        section: Option<String>,
+        /// Clang's `__attribute__((overloadable))`, as on the `<tgmath.h>` functions. Overloads
+        /// share a name, and pick up distinct ones in the translation.
+        is_overloadable: bool,
+        /// The target of `__attribute__((alias("target")))`. Aliases have no body of their own.
+        alias: Option<String>,
         typ: CFuncTypeId,
         name: String,
         parameters: Vec<CParamId>,
@@ -538,6 +753,7 @@ pub enum CDeclKind {
         ident: String,
         initializer: Option<CExprId>,
         typ: CQualTypeId,
+        manual_alignment: Option<u64>,
     },
 
     // Enum (http://clang.llvm.org/doxygen/classclang_1_1EnumDecl.html)
@@ -556,24 +772,46 @@ pub enum CDeclKind {
     Typedef {
         name: String,
         typ: CQualTypeId,
+        /// Width in bits requested for an integer type with `__attribute__((mode(...)))`
+        mode_width: Option<u64>,
+        /// Alignment in bytes requested with `__attribute__((aligned(...)))`, beyond that of the
+        /// underlying type
+        manual_alignment: Option<u64>,
     },
 
     // Struct
     Struct {
         name: Option<String>,
         fields: Option<Vec<CFieldId>>,
+        manual_alignment: Option<u64>,
+        max_field_alignment: Option<u64>,
+        is_must_use: bool,
     },
 
     // Union
     Union {
         name: Option<String>,
         fields: Option<Vec<CFieldId>>,
+        manual_alignment: Option<u64>,
+        max_field_alignment: Option<u64>,
+        is_must_use: bool,
+        /// `__attribute__((transparent_union))`: passed as an argument like its first field
+        is_transparent: bool,
     },
 
     // Field
     Field {
         name: String,
         typ: CQualTypeId,
+        bitfield_width: Option<u64>,
+    },
+
+    // http://clang.llvm.org/doxygen/classclang_1_1StaticAssertDecl.html
+    StaticAssert {
+        assert_expr: CExprId,
+        message: Option<String>,
+        condition: String, // source text of `assert_expr`
+        file: String,
     },
 }
 
@@ -648,6 +886,11 @@ pub enum CExprKind {
     // Designated initializer
     ImplicitValueInit(CQualTypeId),
 
+    // Designated initializer updating part of an earlier initializer, as in `{ [0] = s, [0].x =
+    // 1 }`: the base, then an initializer list with `NoInit` for the parts kept from the base
+    DesignatedInitUpdate(CQualTypeId, CExprId, CExprId),
+    NoInit(CQualTypeId),
+
     // Compound literal
     CompoundLiteral(CQualTypeId, CExprId),
 
@@ -659,6 +902,19 @@ pub enum CExprKind {
 
     // Variable argument list
     VAArg(CQualTypeId, CExprId),
+
+    // GNU `__atomic_*` or C11 `__c11_atomic_*` builtin. Operands the builtin doesn't take are
+    // `None`; `order` is only missing for `__c11_atomic_init`.
+    Atomic {
+        typ: CQualTypeId,
+        name: String,
+        ptr: CExprId,
+        order: Option<CExprId>,
+        val1: Option<CExprId>,
+        order_fail: Option<CExprId>,
+        val2: Option<CExprId>,
+        weak: Option<CExprId>,
+    },
 }
 
 #[derive(Copy, Debug, Clone)]
@@ -685,10 +941,13 @@ impl CExprKind {
             CExprKind::BinaryConditional(ty, _, _) |
             CExprKind::InitList(ty, _, _) |
             CExprKind::ImplicitValueInit(ty) |
+            CExprKind::DesignatedInitUpdate(ty, _, _) |
+            CExprKind::NoInit(ty) |
             CExprKind::CompoundLiteral(ty, _) |
             CExprKind::Predefined(ty, _) |
             CExprKind::Statements(ty, _) |
-            CExprKind::VAArg(ty, _) => ty,
+            CExprKind::VAArg(ty, _) |
+            CExprKind::Atomic { typ: ty, .. } => ty,
         }
     }
 
@@ -737,6 +996,7 @@ pub enum CastKind {
     IntegralComplexToFloatingComplex,
     BuiltinFnToFnPtr,
     ConstCast,
+    VectorSplat,
 }
 
 /// Represents a unary operator in C (6.5.3 Unary operators) and GNU C extensions
@@ -840,7 +1100,7 @@ impl BinOp {
 #[derive(Debug, Clone)]
 pub enum CLiteral {
     Integer(u64),
-    Character(u64),
+    Character(u64, u8), // Literal value and unit byte width
     Floating(f64),
     String(Vec<u8>, u8), // Literal bytes and unit byte width
 }
@@ -850,7 +1110,7 @@ impl CLiteral {
     pub fn get_bool(&self) -> bool {
         match *self{
             CLiteral::Integer(x) => x != 0u64,
-            CLiteral::Character(x) => x != 0u64,
+            CLiteral::Character(x, _) => x != 0u64,
             CLiteral::Floating(x) => x != 0f64,
             _ => true
 
@@ -972,6 +1232,14 @@ impl Qualifiers {
             is_volatile: self.is_volatile || other.is_volatile,
         }
     }
+
+    /// Check that these qualifiers include all of the qualifiers in `other`, as the pointee of the
+    /// target of an implicit pointer conversion must (C11 6.5.16.1 para 1).
+    pub fn contains(self, other: Qualifiers) -> bool {
+        (self.is_const || !other.is_const) &&
+        (self.is_restrict || !other.is_restrict) &&
+        (self.is_volatile || !other.is_volatile)
+    }
 }
 
 impl PartialEq for Qualifiers {
@@ -1043,6 +1311,9 @@ pub enum CTypeKind {
     IncompleteArray(CTypeId),
     VariableArray(CTypeId, Option<CExprId>),
 
+    // Vector type (GNU C `__attribute__((vector_size))`), with its element count
+    Vector(CTypeId, usize),
+
     // Type of type or expression (GCC extension)
     TypeOf(CTypeId),
     TypeOfExpr(CExprId),
@@ -1096,6 +1367,22 @@ impl CTypeKind {
         }
     }
 
+    pub fn is_array(&self) -> bool {
+        match *self {
+            CTypeKind::ConstantArray(..) |
+            CTypeKind::IncompleteArray(..) |
+            CTypeKind::VariableArray(..) => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_vector(&self) -> bool {
+        match *self {
+            CTypeKind::Vector(..) => true,
+            _ => false,
+        }
+    }
+
     pub fn is_bool(&self) -> bool {
         match *self {
             CTypeKind::Bool => true,
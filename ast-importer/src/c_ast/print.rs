@@ -176,6 +176,13 @@ impl<W: Write> Printer<W> {
             }
             Some(&CExprKind::ImplicitValueInit{..}) =>
                 self.writer.write_all(b"{}"),
+            Some(&CExprKind::DesignatedInitUpdate(_, base, updater)) => {
+                self.print_expr(base, context)?;
+                self.writer.write_all(b" /* updated with */ ")?;
+                self.print_expr(updater, context)
+            }
+            Some(&CExprKind::NoInit(..)) =>
+                self.writer.write_all(b"/* unchanged */"),
             Some(&CExprKind::CompoundLiteral(ty, val)) => {
                 self.writer.write_all(b"(")?;
                 self.print_qtype(ty, None, context)?;
@@ -188,6 +195,22 @@ impl<W: Write> Printer<W> {
             Some(&CExprKind::VAArg(_,val)) =>
                 self.print_expr(val, context),
 
+            Some(&CExprKind::Atomic { ref name, ptr, order, val1, order_fail, val2, weak, .. }) => {
+                self.writer.write_all(name.as_bytes())?;
+                self.writer.write_all(b"(")?;
+                self.print_expr(ptr, context)?;
+
+                // Same order as in the source
+                for &arg in [val1, val2, weak, order, order_fail].iter() {
+                    if let Some(arg) = arg {
+                        self.writer.write_all(b", ")?;
+                        self.print_expr(arg, context)?;
+                    }
+                }
+
+                self.writer.write_all(b")")
+            }
+
             None => panic!("Could not find expression with ID {:?}", expr_id),
            // _ => unimplemented!("Printer::print_expr"),
         }
@@ -481,7 +504,7 @@ impl<W: Write> Printer<W> {
                 }
             },
 
-            Some(&CDeclKind::Typedef { ref name, ref typ }) => {
+            Some(&CDeclKind::Typedef { ref name, ref typ, .. }) => {
                 self.writer.write_fmt(format_args!("typedef {} = ", name))?;
                 self.print_qtype(*typ, None, context)?;
                 if newline {
@@ -564,7 +587,7 @@ impl<W: Write> Printer<W> {
                 Ok(())
             },
 
-            Some(&CDeclKind::Field { ref name, typ }) => {
+            Some(&CDeclKind::Field { ref name, typ, .. }) => {
                 self.writer.write_fmt(format_args!("{}: ", &name))?;
                 self.print_qtype(typ, None, context)?;
                 if newline {
@@ -574,6 +597,20 @@ impl<W: Write> Printer<W> {
                 Ok(())
             },
 
+            Some(&CDeclKind::StaticAssert { assert_expr, ref message, .. }) => {
+                self.writer.write_all(b"_Static_assert(")?;
+                self.print_expr(assert_expr, context)?;
+                if let Some(ref message) = *message {
+                    self.writer.write_fmt(format_args!(", {:?}", message))?;
+                }
+                self.writer.write_all(b");")?;
+                if newline {
+                    self.writer.write_all(b"\n")?;
+                }
+
+                Ok(())
+            },
+
             None => panic!("Could not find declaration with ID {:?}", decl_id),
 
            // _ => unimplemented!("Printer::print_decl"),
@@ -677,3 +714,186 @@ impl<W: Write> Printer<W> {
 }
 
 
+
+/// Render the C declaration of `declarator` with the type `qtype`, e.g. `const char *name` for
+/// `name` of type pointer to `const char`. An empty `declarator` gives an abstract declarator,
+/// like `const char *`.
+pub fn c_declaration(qtype: CQualTypeId, declarator: &str, context: &TypedAstContext) -> String {
+    let qualifiers = qualifier_words(qtype.qualifiers);
+
+    match context[qtype.ctype].kind {
+        CTypeKind::Pointer(pointee) | CTypeKind::BlockPointer(pointee) => {
+            let sigil = if let CTypeKind::Pointer(_) = context[qtype.ctype].kind { "*" } else { "^" };
+            let mut inner = sigil.to_string();
+            for qualifier in qualifiers {
+                inner.push_str(qualifier);
+                inner.push(' ');
+            }
+            inner.push_str(declarator);
+            let inner = inner.trim_right().to_string();
+
+            // `int (*f)(void)` and `int (*a)[4]` need parentheses to bind the pointer first, unless
+            // the source already had them
+            let binds_tighter = match context[pointee.ctype].kind {
+                CTypeKind::ConstantArray(..) | CTypeKind::IncompleteArray(..) |
+                CTypeKind::VariableArray(..) | CTypeKind::Function(..) => true,
+                _ => false,
+            };
+            let inner = if binds_tighter { format!("({})", inner) } else { inner };
+            c_declaration(pointee, &inner, context)
+        }
+
+        // Qualifiers on an array type apply to its elements
+        CTypeKind::ConstantArray(elt, len) =>
+            c_declaration(CQualTypeId { qualifiers: qtype.qualifiers, ctype: elt }, &format!("{}[{}]", declarator, len), context),
+        CTypeKind::IncompleteArray(elt) =>
+            c_declaration(CQualTypeId { qualifiers: qtype.qualifiers, ctype: elt }, &format!("{}[]", declarator), context),
+        CTypeKind::VariableArray(elt, _) =>
+            c_declaration(CQualTypeId { qualifiers: qtype.qualifiers, ctype: elt }, &format!("{}[*]", declarator), context),
+
+        CTypeKind::Function(ret, ref params, is_variadic, _) => {
+            let mut params: Vec<String> = params.iter().map(|&param| c_declaration(param, "", context)).collect();
+            if is_variadic {
+                params.push("...".to_string());
+            } else if params.is_empty() {
+                params.push("void".to_string());
+            }
+            c_declaration(ret, &format!("{}({})", declarator, params.join(", ")), context)
+        }
+
+        CTypeKind::Paren(ctype) if !declarator.is_empty() => {
+            let qualifiers = qtype.qualifiers;
+            c_declaration(CQualTypeId { qualifiers, ctype }, &format!("({})", declarator), context)
+        }
+        CTypeKind::Elaborated(ctype) | CTypeKind::Paren(ctype) | CTypeKind::Decayed(ctype) | CTypeKind::TypeOf(ctype) => {
+            let qualifiers = qtype.qualifiers;
+            c_declaration(CQualTypeId { qualifiers, ctype }, declarator, context)
+        }
+        CTypeKind::Attributed(inner, _) => {
+            let qualifiers = qtype.qualifiers.and(inner.qualifiers);
+            c_declaration(CQualTypeId { qualifiers, ctype: inner.ctype }, declarator, context)
+        }
+
+        ref kind => {
+            let name = c_type_name(kind, context);
+            let mut words: Vec<&str> = qualifiers;
+            words.push(&name);
+            if !declarator.is_empty() {
+                words.push(declarator);
+            }
+            words.join(" ")
+        }
+    }
+}
+
+/// One-line C rendering of a function prototype, with unnamed parameters, or of a struct or
+/// union definition, e.g. `int foo(const char *, unsigned long)` or
+/// `struct point { int x; int y; }`. Function names carry `name_prefix` when it was added to them
+/// ahead of the translation, which is left out to give the name from the C source.
+pub fn c_signature(decl_id: CDeclId, name_prefix: Option<&str>, context: &TypedAstContext) -> Option<String> {
+    match context[decl_id].kind {
+        CDeclKind::Function { typ, ref name, ref parameters, .. } => {
+            let name = match name_prefix {
+                Some(prefix) if name.starts_with(prefix) => &name[prefix.len()..],
+                _ => name.as_str(),
+            };
+
+            let (ret, is_variadic) = match context.resolve_type(typ).kind {
+                CTypeKind::Function(ret, _, is_variadic, _) => (ret, is_variadic),
+                _ => return None,
+            };
+
+            let mut params = vec![];
+            for &param_id in parameters {
+                match context[param_id].kind {
+                    CDeclKind::Variable { typ, .. } => params.push(c_declaration(typ, "", context)),
+                    _ => return None,
+                }
+            }
+            if is_variadic {
+                params.push("...".to_string());
+            } else if params.is_empty() {
+                params.push("void".to_string());
+            }
+
+            Some(c_declaration(ret, &format!("{}({})", name, params.join(", ")), context))
+        }
+
+        CDeclKind::Struct { ref name, fields: Some(ref fields), .. } |
+        CDeclKind::Union { ref name, fields: Some(ref fields), .. } => {
+            let keyword = if let CDeclKind::Struct { .. } = context[decl_id].kind { "struct" } else { "union" };
+
+            let mut signature = keyword.to_string();
+            if let Some(ref name) = *name {
+                signature.push(' ');
+                signature.push_str(name);
+            }
+            signature.push_str(" {");
+            for &field_id in fields {
+                if let CDeclKind::Field { ref name, typ, bitfield_width } = context[field_id].kind {
+                    signature.push(' ');
+                    signature.push_str(&c_declaration(typ, name, context));
+                    if let Some(width) = bitfield_width {
+                        signature.push_str(&format!(" : {}", width));
+                    }
+                    signature.push(';');
+                }
+            }
+            signature.push_str(" }");
+            Some(signature)
+        }
+
+        _ => None,
+    }
+}
+
+fn qualifier_words(qualifiers: Qualifiers) -> Vec<&'static str> {
+    let mut words = vec![];
+    if qualifiers.is_const { words.push("const") }
+    if qualifiers.is_volatile { words.push("volatile") }
+    if qualifiers.is_restrict { words.push("restrict") }
+    words
+}
+
+/// Name of a type without a declarator: a builtin, a typedef, or a tagged type
+fn c_type_name(kind: &CTypeKind, context: &TypedAstContext) -> String {
+    let tagged = |keyword: &str, decl_id: CDeclId| {
+        match context[decl_id].kind.get_name() {
+            Some(name) => format!("{} {}", keyword, name),
+            None => format!("{} <anonymous>", keyword),
+        }
+    };
+
+    match *kind {
+        CTypeKind::Void => "void".to_string(),
+        CTypeKind::Bool => "_Bool".to_string(),
+        CTypeKind::Char => "char".to_string(),
+        CTypeKind::SChar => "signed char".to_string(),
+        CTypeKind::Short => "short".to_string(),
+        CTypeKind::Int => "int".to_string(),
+        CTypeKind::Long => "long".to_string(),
+        CTypeKind::LongLong => "long long".to_string(),
+        CTypeKind::UChar => "unsigned char".to_string(),
+        CTypeKind::UShort => "unsigned short".to_string(),
+        CTypeKind::UInt => "unsigned int".to_string(),
+        CTypeKind::ULong => "unsigned long".to_string(),
+        CTypeKind::ULongLong => "unsigned long long".to_string(),
+        CTypeKind::Float => "float".to_string(),
+        CTypeKind::Double => "double".to_string(),
+        CTypeKind::LongDouble => "long double".to_string(),
+        CTypeKind::Int128 => "__int128".to_string(),
+        CTypeKind::UInt128 => "unsigned __int128".to_string(),
+        CTypeKind::Complex(elt) => format!("_Complex {}", c_type_name(&context[elt].kind, context)),
+        CTypeKind::Vector(elt, len) => {
+            let elt = c_type_name(&context[elt].kind, context);
+            format!("{} __attribute__((vector_size({} * sizeof({}))))", elt, len, elt)
+        }
+        CTypeKind::Typedef(decl_id) => context[decl_id].kind.get_name().cloned().unwrap_or_default(),
+        CTypeKind::Struct(decl_id) => tagged("struct", decl_id),
+        CTypeKind::Union(decl_id) => tagged("union", decl_id),
+        CTypeKind::Enum(decl_id) => tagged("enum", decl_id),
+        CTypeKind::TypeOfExpr(_) => "__typeof__(...)".to_string(),
+        CTypeKind::BuiltinFn => "<builtin>".to_string(),
+        _ => "<type>".to_string(),
+    }
+}
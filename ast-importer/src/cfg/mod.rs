@@ -9,7 +9,8 @@
 //!
 //!   - given an entry point C statement, translate it into a CFG consisting of `BasicBlock<Label>`
 //!   - simplify this CFG (by eliminating empty blocks that jump unconditionally to the next block)
-//!   - use the _Relooper algorithm_ to convert this CFG into a sequence of `Structure<StmtOrDecl>`s
+//!   - use the _Relooper algorithm_ (or one of the other `CfgStructuring` strategies) to convert
+//!     this CFG into a sequence of `Structure<StmtOrDecl>`s
 //!   - place the declarations in the right place and produce a sequence of `Structure<Stmt>`s
 //!   - simplify that sequence of `Structure<Stmt>`s into another such sequence
 //!   - convert the `Vec<Structure<Stmt>>` back into a `Vec<Stmt>`
@@ -40,6 +41,8 @@ use translator::*;
 use c_ast::*;
 
 pub mod relooper;
+pub mod patterns;
+pub mod switch;
 pub mod structures;
 pub mod loops;
 pub mod multiples;
@@ -47,6 +50,73 @@ pub mod multiples;
 use cfg::loops::*;
 use cfg::multiples::*;
 
+/// Algorithm used to turn a CFG back into structured Rust control flow
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CfgStructuring {
+    /// Relooper algorithm, using `current_block` only where it can't find a better structure
+    Relooper,
+
+    /// Match loops, `if`s, and `match`es directly from the dominator structure of the CFG, falling
+    /// back on the relooper for functions that don't fit those patterns
+    Pattern,
+
+    /// Lower the whole function into one `loop` dispatching on `current_block`
+    Switch,
+}
+
+impl Default for CfgStructuring {
+    fn default() -> Self {
+        CfgStructuring::Relooper
+    }
+}
+
+/// Structure the CFG into a sequence of structures using the requested algorithm
+pub fn structure(
+    cfg: Cfg<Label, StmtOrDecl>,
+    store: DeclStmtStore,
+    structuring: CfgStructuring,
+    simplify_structures: bool,
+    use_c_loop_info: bool,
+    use_c_multiple_info: bool,
+) -> (Vec<Stmt>, Vec<Structure<StmtOrComment>>) {
+    match structuring {
+        CfgStructuring::Relooper => {}
+        CfgStructuring::Pattern => {
+            // Fall back on the relooper if the CFG doesn't match the patterns
+            let matched = patterns::match_patterns(&cfg);
+            if let Ok((structures, lift_me)) = matched {
+                return place_structure_decls(structures, lift_me, store);
+            }
+        }
+        CfgStructuring::Switch => return switch::switch_lower(cfg, store),
+    }
+
+    relooper::reloop(cfg, store, simplify_structures, use_c_loop_info, use_c_multiple_info)
+}
+
+/// Extract the declarations that need to be lifted to the top of the function, and then flatten
+/// the remaining declarations in the structures into statements.
+fn place_structure_decls(
+    structures: Vec<Structure<StmtOrDecl>>,
+    lift_me: HashSet<CDeclId>,
+    mut store: DeclStmtStore,
+) -> (Vec<Stmt>, Vec<Structure<StmtOrComment>>) {
+
+    // These are the statements that emerge from these lifts
+    let lifted_stmts: Vec<Stmt> = lift_me
+        .iter()
+        .flat_map(|&decl: &CDeclId| store.extract_decl(decl).unwrap())
+        .collect();
+
+    // We map over the existing structure and flatten everything to `Stmt`
+    let structures: Vec<Structure<StmtOrComment>> = structures
+        .into_iter()
+        .map(|s| s.place_decls(&lift_me, &mut store))
+        .collect();
+
+    (lifted_stmts, structures)
+}
+
 /// These labels identify basic blocks in a regular CFG.
 #[derive(Copy,Clone,PartialEq,Eq,PartialOrd,Ord,Debug,Hash)]
 pub enum Label {
@@ -1443,7 +1513,9 @@ impl CfgBuilder {
                 let wip_label = wip.label;
                 self.add_wip_block(wip, End); // NOTE: the `End` here is temporary and gets updated
 
-                // Body
+                // Body. Its `case`s and `default` become `Label::FromC` blocks just like C labels
+                // do, so `goto`s into the middle of the body or from one case to another are
+                // ordinary edges of the graph.
                 self.break_labels.push(next_label);
                 self.switch_expr_cases.push(SwitchCases::default());
 
@@ -0,0 +1,445 @@
+//! This modules handles converting a control-flow graph `Cfg` into `Vec<Structure>` by matching
+//! the graph against the usual structured control-flow patterns: straight-line sequences, `if` and
+//! `match` diamonds that rejoin at their immediate post-dominator, and natural loops with a single
+//! exit.
+//!
+//! Unlike the relooper, this never needs a `current_block` variable. Instead, functions that don't
+//! fit the patterns (irreducible loops, loops with several exits, blocks shared between the arms
+//! of a branch, ...) are rejected, in which case the caller is expected to structure the CFG some
+//! other way.
+
+use super::*;
+
+/// Try to structure the CFG by pattern matching. On success, this returns the structures along
+/// with the declarations that have to be lifted to the top of the function.
+pub fn match_patterns(
+    cfg: &Cfg<Label, StmtOrDecl>,
+) -> Result<(Vec<Structure<StmtOrDecl>>, HashSet<CDeclId>), String> {
+
+    if cfg.entries.len() != 1 {
+        Err(format!("Expected exactly one entry, found {:?}", cfg.entries))?
+    }
+    let entry = *cfg.entries.iter().next().expect("match_patterns: expected entry");
+
+    let mut state = PatternState::new(&cfg.nodes, entry)?;
+    let mut structures = vec![];
+    state.region(entry, None, &mut structures)?;
+
+    Ok((structures, state.lifted))
+}
+
+/// A natural loop whose body is being structured
+struct LoopRegion {
+    /// The only block jumped to from outside the loop
+    header: Label,
+
+    /// Where control goes to once the loop is done, if it ever finishes without returning
+    exit: Option<Label>,
+
+    /// Blocks making up the body of the loop
+    body: HashSet<Label>,
+}
+
+/// This is the state we close over while matching patterns.
+struct PatternState<'a> {
+    blocks: &'a HashMap<Label, BasicBlock<Label, StmtOrDecl>>,
+    succs: HashMap<Label, HashSet<Label>>,
+    preds: HashMap<Label, HashSet<Label>>,
+    dominators: HashMap<Label, HashSet<Label>>,
+    post_dominators: HashMap<Label, HashSet<Label>>,
+
+    /// Loops enclosing the region currently being structured, innermost last
+    loops: Vec<LoopRegion>,
+
+    /// Blocks that have already been placed somewhere in the output
+    emitted: HashSet<Label>,
+
+    /// scopes of declarations seen so far
+    scopes: Vec<HashSet<CDeclId>>,
+
+    /// Declarations that will have to be lifted to the top of the output
+    lifted: HashSet<CDeclId>,
+}
+
+/// Compute the dominators of every block, where `incoming` maps a block to the blocks it can be
+/// reached from. Passing in the successors instead of the predecessors yields post-dominators.
+fn dominator_sets(
+    labels: &HashSet<Label>,
+    roots: &HashSet<Label>,
+    incoming: &HashMap<Label, HashSet<Label>>,
+) -> HashMap<Label, HashSet<Label>> {
+    let mut doms: HashMap<Label, HashSet<Label>> = labels
+        .iter()
+        .map(|&lbl| {
+            let doms = if roots.contains(&lbl) { vec![lbl].into_iter().collect() } else { labels.clone() };
+            (lbl, doms)
+        })
+        .collect();
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+
+        for &lbl in labels {
+            if roots.contains(&lbl) {
+                continue
+            }
+
+            let mut new_doms: HashSet<Label> = labels.clone();
+            for from in &incoming[&lbl] {
+                new_doms = new_doms.intersection(&doms[from]).cloned().collect();
+            }
+            new_doms.insert(lbl);
+
+            if new_doms != doms[&lbl] {
+                doms.insert(lbl, new_doms);
+                changed = true;
+            }
+        }
+    }
+
+    doms
+}
+
+impl<'a> PatternState<'a> {
+
+    fn new(blocks: &'a HashMap<Label, BasicBlock<Label, StmtOrDecl>>, entry: Label) -> Result<Self, String> {
+        let labels: HashSet<Label> = blocks.keys().cloned().collect();
+
+        let mut succs: HashMap<Label, HashSet<Label>> = HashMap::new();
+        let mut preds: HashMap<Label, HashSet<Label>> = labels
+            .iter()
+            .map(|&lbl| (lbl, HashSet::new()))
+            .collect();
+        for (&lbl, bb) in blocks {
+            let targets: HashSet<Label> = bb.terminator.get_labels().into_iter().cloned().collect();
+            for target in &targets {
+                preds
+                    .get_mut(target)
+                    .ok_or(format!("{:?} jumps to unknown block {:?}", lbl, target))?
+                    .insert(lbl);
+            }
+            succs.insert(lbl, targets);
+        }
+
+        let exits: HashSet<Label> = labels
+            .iter()
+            .cloned()
+            .filter(|lbl| succs[lbl].is_empty())
+            .collect();
+
+        // Post-dominators only make sense if every block can eventually leave the function
+        let mut finishing: HashSet<Label> = HashSet::new();
+        let mut worklist: Vec<Label> = exits.iter().cloned().collect();
+        while let Some(lbl) = worklist.pop() {
+            if finishing.insert(lbl) {
+                worklist.extend(preds[&lbl].iter().cloned());
+            }
+        }
+        if finishing.len() != labels.len() {
+            Err(format!("Some blocks never reach the end of the function"))?
+        }
+
+        let entries: HashSet<Label> = vec![entry].into_iter().collect();
+        let dominators = dominator_sets(&labels, &entries, &preds);
+        let post_dominators = dominator_sets(&labels, &exits, &succs);
+
+        // The CFG is reducible exactly when it becomes acyclic after removing the back edges, that
+        // is the edges jumping to a block dominating the source of the jump.
+        let mut in_degree: HashMap<Label, usize> = labels.iter().map(|&lbl| (lbl, 0)).collect();
+        for (from, tos) in &succs {
+            for to in tos {
+                if !dominators[from].contains(to) {
+                    *in_degree.get_mut(to).unwrap() += 1;
+                }
+            }
+        }
+        let mut ready: Vec<Label> = in_degree
+            .iter()
+            .filter(|&(_, &degree)| degree == 0)
+            .map(|(&lbl, _)| lbl)
+            .collect();
+        let mut sorted = 0;
+        while let Some(lbl) = ready.pop() {
+            sorted += 1;
+            for to in &succs[&lbl] {
+                if !dominators[&lbl].contains(to) {
+                    let degree = in_degree.get_mut(to).unwrap();
+                    *degree -= 1;
+                    if *degree == 0 {
+                        ready.push(*to);
+                    }
+                }
+            }
+        }
+        if sorted != labels.len() {
+            Err(format!("The control flow graph is irreducible"))?
+        }
+
+        Ok(PatternState {
+            blocks,
+            succs,
+            preds,
+            dominators,
+            post_dominators,
+            loops: vec![],
+            emitted: HashSet::new(),
+            scopes: vec![HashSet::new()],
+            lifted: HashSet::new(),
+        })
+    }
+
+    pub fn in_scope(&self, decl: CDeclId) -> bool {
+        self.scopes.iter().any(|scope| scope.contains(&decl))
+    }
+
+    pub fn add_to_scope(&mut self, decl: CDeclId) {
+        self.scopes
+            .last_mut()
+            .expect("add_to_scope: no scopes found")
+            .insert(decl);
+    }
+
+    pub fn add_to_top_scope(&mut self, decl: CDeclId) {
+        self.scopes
+            .first_mut()
+            .expect("add_to_top_scope: no scopes found")
+            .insert(decl);
+    }
+
+    /// Is this the header or the exit of one of the loops we are in? Jumping to those labels turns
+    /// into a `continue` or a `break`.
+    fn is_loop_label(&self, lbl: Label) -> bool {
+        self.loops.iter().any(|l| l.header == lbl || l.exit == Some(lbl))
+    }
+
+    /// Blocks jumping back to `lbl`, making it the header of a loop
+    fn latches(&self, lbl: Label) -> Vec<Label> {
+        self.preds[&lbl]
+            .iter()
+            .cloned()
+            .filter(|pred| self.dominators[pred].contains(&lbl))
+            .collect()
+    }
+
+    /// Where the branches out of `lbl` meet up again, provided that is still inside the loop we
+    /// are in.
+    fn join(&self, lbl: Label) -> Option<Label> {
+        let mut strict = self.post_dominators[&lbl].clone();
+        strict.remove(&lbl);
+        let join = strict.iter().cloned().find(|d| self.post_dominators[d] == strict)?;
+
+        match self.loops.last() {
+            Some(l) if !l.body.contains(&join) && !self.is_loop_label(join) => None,
+            _ => Some(join),
+        }
+    }
+
+    /// Blocks only reachable by leaving the loop through `exit`, if there are no other ways into
+    /// them. These are typically `return`s in the middle of a loop, and can be moved into the loop
+    /// body.
+    fn dead_end(&self, exit: Label, body: &HashSet<Label>) -> Option<HashSet<Label>> {
+        let mut region: HashSet<Label> = HashSet::new();
+        let mut worklist = vec![exit];
+        while let Some(lbl) = worklist.pop() {
+            if body.contains(&lbl) || self.is_loop_label(lbl) || !region.insert(lbl) {
+                continue
+            }
+            worklist.extend(self.succs[&lbl].iter().cloned());
+        }
+
+        let closed = region
+            .iter()
+            .all(|lbl| self.preds[lbl].iter().all(|pred| body.contains(pred) || region.contains(pred)));
+        if closed { Some(region) } else { None }
+    }
+
+    /// Pick the exit of the loop whose body is `body`, moving the blocks of any other exits into
+    /// the body.
+    fn loop_exit(&self, header: Label, body: &mut HashSet<Label>) -> Result<Option<Label>, String> {
+        let exits: HashSet<Label> = body
+            .iter()
+            .flat_map(|lbl| self.succs[lbl].iter().cloned())
+            .filter(|lbl| !body.contains(lbl) && !self.is_loop_label(*lbl))
+            .collect();
+        let mut exits: Vec<Label> = exits.into_iter().collect();
+        exits.sort();
+
+        let mut dead_ends = vec![];
+        let mut live_exits = vec![];
+        for exit in exits {
+            match self.dead_end(exit, body) {
+                Some(region) => dead_ends.push((exit, region)),
+                None => live_exits.push(exit),
+            }
+        }
+
+        let exit = match live_exits.len() {
+            // Keep one of the dead ends as the exit, preferably one leaving from the header (such
+            // as the condition of a `while` loop)
+            0 if dead_ends.is_empty() => None,
+            0 => {
+                let keep = dead_ends
+                    .iter()
+                    .position(|&(exit, _)| self.succs[&header].contains(&exit))
+                    .unwrap_or(0);
+                Some(dead_ends.remove(keep).0)
+            }
+            1 => Some(live_exits[0]),
+            _ => return Err(format!("The loop at {:?} has several exits: {:?}", header, live_exits)),
+        };
+
+        for (_, region) in dead_ends {
+            body.extend(region);
+        }
+
+        match exit {
+            Some(exit) if body.contains(&exit) => Ok(None),
+            _ => Ok(exit),
+        }
+    }
+
+    /// Structure the blocks starting at `entry` and append them to `result`, stopping when reaching
+    /// `follow` or a label of an enclosing loop.
+    fn region(
+        &mut self,
+        entry: Label,
+        follow: Option<Label>,
+        result: &mut Vec<Structure<StmtOrDecl>>,
+    ) -> Result<(), String> {
+        let mut next = Some(entry);
+        while let Some(lbl) = next {
+            if Some(lbl) == follow || self.is_loop_label(lbl) {
+                break
+            }
+
+            if self.emitted.contains(&lbl) {
+                Err(format!("{:?} is reached from more than one place", lbl))?
+            }
+
+            let latches = self.latches(lbl);
+            next = if latches.is_empty() {
+                self.block(lbl, follow, result)?
+            } else {
+                self.natural_loop(lbl, latches, result)?
+            };
+        }
+
+        Ok(())
+    }
+
+    /// Structure the natural loop with header `header`, returning where control goes next
+    fn natural_loop(
+        &mut self,
+        header: Label,
+        latches: Vec<Label>,
+        result: &mut Vec<Structure<StmtOrDecl>>,
+    ) -> Result<Option<Label>, String> {
+
+        // The body of the loop is everything that can reach a latch without going through the
+        // header
+        let mut body: HashSet<Label> = vec![header].into_iter().collect();
+        let mut worklist = latches;
+        while let Some(lbl) = worklist.pop() {
+            if body.insert(lbl) {
+                worklist.extend(self.preds[&lbl].iter().cloned());
+            }
+        }
+
+        let exit = self.loop_exit(header, &mut body)?;
+        self.loops.push(LoopRegion { header, exit, body });
+        self.scopes.push(HashSet::new());
+
+        let mut loop_body = vec![];
+        if let Some(next) = self.block(header, None, &mut loop_body)? {
+            self.region(next, None, &mut loop_body)?;
+        }
+
+        self.scopes.pop();
+        self.loops.pop();
+
+        let entries = vec![header].into_iter().collect();
+        result.push(Structure::Loop { entries, body: loop_body });
+
+        Ok(exit)
+    }
+
+    /// Structure a single block, returning the block that comes right after it (if any)
+    fn block(
+        &mut self,
+        lbl: Label,
+        follow: Option<Label>,
+        result: &mut Vec<Structure<StmtOrDecl>>,
+    ) -> Result<Option<Label>, String> {
+        if !self.emitted.insert(lbl) {
+            Err(format!("{:?} is reached from more than one place", lbl))?
+        }
+        if let Some(l) = self.loops.last() {
+            if !l.body.contains(&lbl) {
+                Err(format!("{:?} escapes the loop at {:?}", lbl, l.header))?
+            }
+        }
+
+        let blocks = self.blocks;
+        let bb = &blocks[&lbl];
+
+        // Flag declarations for everything that is live going in but not already in scope.
+        for &l in &bb.live {
+            if !self.in_scope(l) {
+                self.add_to_top_scope(l);
+                self.lifted.insert(l);
+            }
+        }
+
+        // Being into scope things that are defined here
+        for &d in &bb.defined {
+            self.add_to_scope(d);
+        }
+
+        let (terminator, next) = match bb.terminator {
+            End => (End, None),
+            Jump(to) => {
+                let to_slbl = if self.is_loop_label(to) {
+                    StructureLabel::ExitTo(to)
+                } else {
+                    StructureLabel::GoTo(to)
+                };
+                (Jump(to_slbl), Some(to))
+            }
+            Branch(ref cond, t, f) => {
+                let join = self.join(lbl);
+                let t = self.edge(t, join)?;
+                let f = self.edge(f, join)?;
+                (Branch(cond.clone(), t, f), join)
+            }
+            Switch { ref expr, ref cases } => {
+                let join = self.join(lbl);
+                let mut new_cases = vec![];
+                for &(ref pats, to) in cases {
+                    new_cases.push((pats.clone(), self.edge(to, join)?));
+                }
+                (Switch { expr: expr.clone(), cases: new_cases }, join)
+            }
+        };
+
+        let entries = vec![lbl].into_iter().collect();
+        result.push(Structure::Simple { entries, body: bb.body.clone(), terminator });
+
+        Ok(next)
+    }
+
+    /// Structure one arm of a branch ending in `join`
+    fn edge(&mut self, to: Label, join: Option<Label>) -> Result<StructureLabel<StmtOrDecl>, String> {
+        if self.is_loop_label(to) {
+            Ok(StructureLabel::ExitTo(to))
+        } else if Some(to) == join {
+            Ok(StructureLabel::GoTo(to))
+        } else {
+            let mut nested = vec![];
+            self.scopes.push(HashSet::new());
+            self.region(to, join, &mut nested)?;
+            self.scopes.pop();
+            Ok(StructureLabel::Nested(nested))
+        }
+    }
+}
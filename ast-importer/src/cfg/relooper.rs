@@ -6,7 +6,7 @@ use super::*;
 /// Convert the CFG into a sequence of structures
 pub fn reloop(
     cfg: Cfg<Label, StmtOrDecl>,  // the control flow graph to reloop
-    mut store: DeclStmtStore,     // store of what to do with declarations
+    store: DeclStmtStore,         // store of what to do with declarations
     simplify_structures: bool,    // simplify the output structure
     use_c_loop_info: bool,        // use the loop information in the CFG (slower, but better)
     use_c_multiple_info: bool,    // use the multiple information in the CFG (slower, but better)
@@ -30,17 +30,7 @@ pub fn reloop(
     // These are declarations we need to lift
     let lift_me: HashSet<CDeclId> = state.lifted;
 
-    // These are the statements that emerge from these lifts
-    let lifted_stmts: Vec<Stmt> = lift_me
-        .iter()
-        .flat_map(|&decl: &CDeclId| store.extract_decl(decl).unwrap())
-        .collect();
-
-    // We map over the existing structure and flatten everything to `Stmt`
-    let mut relooped: Vec<Structure<StmtOrComment>> = relooped_with_decls
-        .into_iter()
-        .map(|s| s.place_decls(&lift_me, &mut store))
-        .collect();
+    let (lifted_stmts, mut relooped) = place_structure_decls(relooped_with_decls, lift_me, store);
 
     if simplify_structures {
         relooped = simplify_structure(relooped)
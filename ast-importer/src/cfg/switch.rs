@@ -0,0 +1,52 @@
+//! This modules handles lowering a control-flow graph `Cfg` into a single state machine: one
+//! `loop` wrapped around a `match` on `current_block` with one arm per basic block. The output is
+//! nowhere near as readable as what the relooper produces, but it is simple enough to always be
+//! correct and its shape doesn't depend on how tangled the `goto`s in the C source are.
+
+use super::*;
+
+/// Convert the CFG into a state machine structure
+pub fn switch_lower(
+    cfg: Cfg<Label, StmtOrDecl>,  // the control flow graph to lower
+    store: DeclStmtStore,         // store of what to do with declarations
+) -> (Vec<Stmt>, Vec<Structure<StmtOrComment>>) {
+
+    let entry = *cfg.entries.iter().next().expect("switch_lower: expected entry");
+    let labels: HashSet<Label> = cfg.nodes.keys().cloned().collect();
+
+    // Every block ends up in its own `match` arm, so no declaration is in scope in any other block.
+    // All of them are lifted to the top of the function.
+    let mut lift_me: HashSet<CDeclId> = HashSet::new();
+
+    let mut branches: HashMap<Label, Vec<Structure<StmtOrDecl>>> = HashMap::new();
+    for (lbl, bb) in cfg.nodes {
+        let BasicBlock { body, terminator, live, defined } = bb;
+        lift_me.extend(live);
+        lift_me.extend(defined);
+
+        // Jumping to any block means setting `current_block` and going around the loop again.
+        // Blocks that end in `End` always finish with a `return`, so they leave the loop.
+        let terminator = terminator.map_labels(|l| StructureLabel::ExitTo(*l));
+        let entries = vec![lbl].into_iter().collect();
+        branches.insert(lbl, vec![Structure::Simple { entries, body, terminator }]);
+    }
+
+    let dispatch = if branches.len() == 1 {
+        branches.remove(&entry).expect("switch_lower: no entry block")
+    } else {
+        let entries = labels.clone();
+        vec![Structure::Multiple { entries, branches, then: vec![] }]
+    };
+
+    let state_machine: Vec<Structure<StmtOrDecl>> = vec![
+        // Start the state machine off at the entry block
+        Structure::Simple {
+            entries: HashSet::new(),
+            body: vec![],
+            terminator: Jump(StructureLabel::GoTo(entry)),
+        },
+        Structure::Loop { entries: labels, body: dispatch },
+    ];
+
+    place_structure_decls(state_machine, lift_me, store)
+}
@@ -33,6 +33,16 @@ pub struct CommentNode {
     pub string: String,
 }
 
+/// A diagnostic from `#error`, `#warning`, or `#pragma message` in the active preprocessing path
+#[derive(Debug,Clone)]
+pub struct DiagnosticNode {
+    pub fileid: u64,
+    pub line: u64,
+    pub column: u64,
+    pub is_error: bool,
+    pub message: String,
+}
+
 impl TypeNode {
     // Masks used to decode the IDs given to type nodes
     pub const ID_MASK: u64 = !0b111;
@@ -47,6 +57,28 @@ pub struct AstContext {
     pub type_nodes: HashMap<u64, TypeNode>,
     pub top_nodes: Vec<u64>,
     pub comments: Vec<CommentNode>,
+    pub diagnostics: Vec<DiagnosticNode>,
+    pub files: Vec<String>, // indexed by file id
+}
+
+impl AstContext {
+    /// Report the `#warning`s and `#pragma message`s of the translation unit on stderr, and fail
+    /// with the message of its first `#error`, since the C wouldn't compile either.
+    pub fn check_diagnostics(&self) -> Result<(), String> {
+        let location = |d: &DiagnosticNode| {
+            let file = self.files.get(d.fileid as usize).map_or("<unknown>", |f| f.as_str());
+            format!("{}:{}:{}", file, d.line, d.column)
+        };
+
+        for diagnostic in self.diagnostics.iter().filter(|d| !d.is_error) {
+            eprintln!("{}: warning: {}", location(diagnostic), diagnostic.message);
+        }
+
+        match self.diagnostics.iter().find(|d| d.is_error) {
+            Some(error) => Err(format!("{}: error: {}", location(error), error.message)),
+            None => Ok(()),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -145,17 +177,22 @@ pub fn process(items: Items<Cursor<Vec<u8>>>) -> Result<AstContext, DecodeError>
     let mut asts: HashMap<u64, AstNode> = HashMap::new();
     let mut types: HashMap<u64, TypeNode> = HashMap::new();
     let mut comments: Vec<CommentNode> = vec![];
+    let mut diagnostics: Vec<DiagnosticNode> = vec![];
 
     let mut top_cbors : Vec<Cbor> = vec![];
     for item in items {
         top_cbors.push(item.unwrap());
     }
 
+    let raw_diagnostics = top_cbors.remove(4);
+    let raw_diagnostics = expect_array(&raw_diagnostics).expect("Bad diagnostic array");
+
     let raw_comments = top_cbors.remove(3);
     let raw_comments = expect_array(&raw_comments).expect("Bad comment array");
 
     let filenames = top_cbors.remove(2);
-    let _filenames = expect_array(&filenames).expect("Bad filename array");
+    let filenames = expect_array(&filenames).expect("Bad filename array");
+    let files = filenames.iter().map(expect_string).collect::<Result<Vec<String>, DecodeError>>()?;
 
     let top_nodes = top_cbors.remove(1);
     let top_nodes = expect_array(&top_nodes).expect("Bad all nodes array");
@@ -176,6 +213,18 @@ pub fn process(items: Items<Cursor<Vec<u8>>>) -> Result<AstContext, DecodeError>
         comments.push(node)
     }
 
+    for x in raw_diagnostics {
+        let entry = expect_array(x).expect("diagnostic entry should be array");
+        let node = DiagnosticNode {
+            fileid: expect_u64(&entry[0])?,
+            line: expect_u64(&entry[1])?,
+            column: expect_u64(&entry[2])?,
+            is_error: expect_bool(&entry[3])?,
+            message: expect_string(&entry[4])?,
+        };
+        diagnostics.push(node)
+    }
+
     for x in all_nodes {
         let entry = expect_array(x).expect("All nodes entry not array");
         let entry_id = expect_u64(&entry[0])?;
@@ -216,5 +265,42 @@ pub fn process(items: Items<Cursor<Vec<u8>>>) -> Result<AstContext, DecodeError>
         ast_nodes: asts,
         type_nodes: types,
         comments,
+        diagnostics,
+        files,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(diagnostics: Vec<DiagnosticNode>) -> AstContext {
+        AstContext {
+            ast_nodes: HashMap::new(),
+            type_nodes: HashMap::new(),
+            top_nodes: vec![],
+            comments: vec![],
+            diagnostics,
+            files: vec!["config.c".to_owned()],
+        }
+    }
+
+    fn diagnostic(is_error: bool, message: &str) -> DiagnosticNode {
+        DiagnosticNode { fileid: 0, line: 3, column: 2, is_error, message: message.to_owned() }
+    }
+
+    #[test]
+    fn active_error_fails_translation() {
+        let cxt = context(vec![
+            diagnostic(false, "falling back to the portable implementation"),
+            diagnostic(true, "unsupported platform"),
+        ]);
+        assert_eq!(cxt.check_diagnostics(), Err("config.c:3:2: error: unsupported platform".to_owned()));
+    }
+
+    #[test]
+    fn warnings_do_not_fail_translation() {
+        let cxt = context(vec![diagnostic(false, "falling back to the portable implementation")]);
+        assert_eq!(cxt.check_diagnostics(), Ok(()));
+    }
+}
@@ -5,12 +5,17 @@ use idiomize::ast_manip::make_ast::*;
 use syntax::ptr::P;
 use std::ops::Index;
 use renamer::*;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use syntax::print::pprust::ty_to_string;
 use c_ast::CDeclId;
 
 pub struct TypeConverter {
     renamer: Renamer<CDeclId>,
     fields: HashMap<CDeclId, Renamer<CFieldId>>,
+    /// Structs standing in for `_Complex` types, keyed by their element type
+    complex_types: BTreeMap<String, (String, P<Ty>)>,
+    /// Over-aligned structs holding variables, keyed by their alignment and the variable's type
+    aligned_types: BTreeMap<(u64, String), (String, P<Ty>)>,
 }
 
 static RESERVED_NAMES: [&str; 100] = [
@@ -52,6 +57,8 @@ impl TypeConverter {
         TypeConverter {
             renamer: Renamer::new(&RESERVED_NAMES),
             fields: HashMap::new(),
+            complex_types: BTreeMap::new(),
+            aligned_types: BTreeMap::new(),
         }
     }
 
@@ -90,6 +97,56 @@ impl TypeConverter {
 
     }
 
+    /// Name of the struct standing in for `_Complex` numbers with elements of type `elt`. The
+    /// struct gets a name the first time it is needed, and is declared by `complex_types`.
+    pub fn complex_type_name(&mut self, ctxt: &TypedAstContext, elt: CTypeId) -> Result<String, String> {
+        let elt_ty = self.convert(ctxt, elt)?;
+        let elt_name = ty_to_string(&elt_ty);
+
+        if let Some(&(ref name, _)) = self.complex_types.get(&elt_name) {
+            return Ok(name.clone())
+        }
+
+        let basename = format!("complex_{}", elt_name.rsplit("::").next().unwrap_or(&elt_name));
+        let name = self.renamer.pick_name(&basename);
+        self.complex_types.insert(elt_name, (name.clone(), elt_ty));
+        Ok(name)
+    }
+
+    /// The names and element types of the `_Complex` structs used so far
+    pub fn complex_types(&self) -> Vec<(String, P<Ty>)> {
+        self.complex_types.values().cloned().collect()
+    }
+
+    /// Name of the struct holding a variable of type `ty` aligned to `align` bytes. Like the
+    /// `_Complex` structs, it gets a name the first time it is needed, and is declared by
+    /// `aligned_types`.
+    pub fn aligned_type_name(&mut self, ty: P<Ty>, align: u64) -> String {
+        let ty_name = ty_to_string(&ty);
+        let key = (align, ty_name);
+
+        if let Some(&(ref name, _)) = self.aligned_types.get(&key) {
+            return name.clone()
+        }
+
+        let basename = {
+            let last = key.1.rsplit("::").next().unwrap_or(&key.1);
+            if last.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                format!("aligned{}_{}", align, last)
+            } else {
+                format!("aligned{}", align)
+            }
+        };
+        let name = self.renamer.pick_name(&basename);
+        self.aligned_types.insert(key, (name.clone(), ty));
+        name
+    }
+
+    /// The names, held types and alignments of the over-aligned structs used so far
+    pub fn aligned_types(&self) -> Vec<(String, P<Ty>, u64)> {
+        self.aligned_types.iter().map(|(&(align, _), &(ref name, ref ty))| (name.clone(), ty.clone(), align)).collect()
+    }
+
     /// Helper function handling conversion of function types in `convert`.
     /// Optional return type excludes a ty when a function doesn't return.
     fn convert_function(
@@ -99,10 +156,12 @@ impl TypeConverter {
       params: &Vec<CQualTypeId>,
       is_variadic: bool
     ) -> Result<P<Ty>, String> {
-        let inputs = params.iter().map(|x|
-            mk().arg(self.convert(ctxt, x.ctype).unwrap(),
+        // A `transparent_union` argument is passed like its first field
+        let inputs = params.iter().map(|x| {
+            let ctype = ctxt.transparent_union_member(x.ctype).map_or(x.ctype, |(_, member)| member.ctype);
+            mk().arg(self.convert(ctxt, ctype).unwrap(),
                                  mk().wild_pat())
-        ).collect();
+        }).collect();
 
         let output = match ret {
             None => mk().never_ty(),
@@ -205,6 +264,13 @@ impl TypeConverter {
                 Ok(mk().array_ty(ty, mk().lit_expr(mk().int_lit(count as u128, LitIntType::Unsuffixed))))
             }
 
+            // There are no portable SIMD types to map vectors to, so they become arrays and their
+            // operations are done element by element
+            CTypeKind::Vector(element, count) => {
+                let ty = self.convert(ctxt, element)?;
+                Ok(mk().array_ty(ty, mk().lit_expr(mk().int_lit(count as u128, LitIntType::Unsuffixed))))
+            }
+
             CTypeKind::IncompleteArray(element) => {
                 let ty = self.convert(ctxt, element)?;
                 let zero_lit = mk().int_lit(0, LitIntType::Unsuffixed);
@@ -230,6 +296,11 @@ impl TypeConverter {
 
             CTypeKind::TypeOf(ty) => self.convert(ctxt, ty),
 
+            CTypeKind::Complex(elt) => {
+                let name = self.complex_type_name(ctxt, elt)?;
+                Ok(mk().path_ty(mk().path(vec![name])))
+            }
+
             ref t => Err(format!("Unsupported type {:?}", t)),
         }
     }
@@ -0,0 +1,228 @@
+//! Differential test harnesses, which call the original C functions and their translations with
+//! the same fuzzed arguments and check that they agree.
+//!
+//! Only functions taking scalars and pointers to scalars, and returning a scalar or nothing, get
+//! a harness. Pointer arguments point to buffers of `BUFFER_LEN` elements. Fuzzed values are
+//! below `BUFFER_LEN` as well, so that scalar arguments used as lengths or indices into those
+//! buffers stay in bounds. Buffers of characters end in a NUL, so that functions taking strings
+//! stop inside them. Other functions are reported as skipped, see `Fuzzability`.
+
+use syntax::abi::Abi;
+use syntax::ast::*;
+use syntax::codemap::DUMMY_SP;
+use syntax::parse::token::{Nonterminal, Token};
+use syntax::ptr::P;
+use syntax::tokenstream::TokenStream;
+use idiomize::ast_manip::make_ast::*;
+
+/// Length of the buffers passed for pointer arguments
+pub const BUFFER_LEN: u128 = 32;
+
+/// Calls to compare per function
+const ITERATIONS: u128 = 100;
+
+pub enum HarnessArg {
+    Scalar(P<Ty>),
+    /// A pointer argument, along with the type of the elements of its buffer and whether they
+    /// are characters
+    Buffer(P<Ty>, P<Ty>, bool),
+}
+
+/// A C function with a fuzzable signature, along with its translation
+pub struct Harness {
+    pub c_name: String,
+    pub rust_name: String,
+    pub args: Vec<HarnessArg>,
+    pub ret: Option<P<Ty>>,
+}
+
+/// Whether a function defined in the translation unit gets a harness
+pub enum Fuzzability {
+    Fuzzable(Harness),
+    /// The C name of a function whose signature can't be fuzzed, along with the reason why
+    Unsupported(String, String),
+}
+
+/// Build the `#[cfg(test)]` module holding one `#[test]` per harness. The module sees the
+/// translated items through `use super::*`, and declares the C functions itself.
+pub fn mk_test_module(harnesses: &[Harness]) -> P<Item> {
+    let mut items = vec![mk().use_glob_item(vec!["super"])];
+
+    let c_fns = harnesses.iter().map(|harness| {
+        let params = harness.args.iter().map(|arg| {
+            let ty = match *arg {
+                HarnessArg::Scalar(ref ty) | HarnessArg::Buffer(ref ty, _, _) => ty,
+            };
+            mk().arg(ty, mk().wild_pat())
+        }).collect();
+        let ret = match harness.ret {
+            Some(ref ty) => FunctionRetTy::Ty(ty.clone()),
+            None => FunctionRetTy::Default(DUMMY_SP),
+        };
+        mk().foreign_fn(&*harness.c_name, mk().fn_decl(params, ret, false))
+    }).collect();
+    items.push(mk().abi(Abi::C).foreign_items(c_fns));
+
+    items.push(mk_fuzz_fn());
+    items.extend(harnesses.iter().map(mk_test_fn));
+
+    mk().call_attr("cfg", vec!["test"]).mod_item("c2rust_differential_tests", items)
+}
+
+/// `fn fuzz(state: &mut u64) -> u64`, a linear congruential generator
+fn mk_fuzz_fn() -> P<Item> {
+    let state = || mk().ident_expr("state");
+    let lit = |i: u128| mk().lit_expr(mk().int_lit(i, ""));
+
+    let step = mk().method_call_expr(
+        mk().method_call_expr(state(), "wrapping_mul", vec![lit(6364136223846793005)]),
+        "wrapping_add",
+        vec![lit(1442695040888963407)],
+    );
+    let high_bits = mk().paren_expr(mk().binary_expr(
+        BinOpKind::Shr,
+        mk().unary_expr(UnOp::Deref, state()),
+        lit(33),
+    ));
+
+    let body = mk().block(vec![
+        mk().semi_stmt(mk().assign_expr(mk().unary_expr(UnOp::Deref, state()), step)),
+        mk().expr_stmt(mk().binary_expr(BinOpKind::Rem, high_bits, lit(BUFFER_LEN))),
+    ]);
+
+    let u64_ty = || mk().path_ty(vec!["u64"]);
+    let decl = mk().fn_decl(
+        vec![mk().arg(mk().set_mutbl(Mutability::Mutable).ref_ty(u64_ty()), mk().ident_pat("state"))],
+        FunctionRetTy::Ty(u64_ty()),
+        false,
+    );
+    mk().fn_item("fuzz", decl, body)
+}
+
+fn mk_test_fn(harness: &Harness) -> P<Item> {
+    let fuzzed = |ty: &P<Ty>| mk().cast_expr(
+        mk().call_expr(mk().path_expr(vec!["fuzz"]), vec![
+            mk().set_mutbl(Mutability::Mutable).addr_of_expr(mk().ident_expr("state")),
+        ]),
+        ty,
+    );
+
+    let mut stmts = vec![];
+    let mut c_args = vec![];
+    let mut rust_args = vec![];
+    let mut buffers = vec![];
+
+    for (i, arg) in harness.args.iter().enumerate() {
+        match *arg {
+            HarnessArg::Scalar(ref ty) => {
+                let name = format!("arg{}", i);
+                stmts.push(mk().local_stmt(P(mk().local(mk().ident_pat(&*name), Some(ty), Some(fuzzed(ty))))));
+                c_args.push(mk().ident_expr(&*name));
+                rust_args.push(mk().ident_expr(&*name));
+            }
+            HarnessArg::Buffer(_, ref elt, is_char) => {
+                let name = format!("buf{}", i);
+                let rust_name = format!("rust_buf{}", i);
+
+                // let mut bufN: [T; BUFFER_LEN] = [0 as T; BUFFER_LEN];
+                let len = || mk().lit_expr(mk().int_lit(BUFFER_LEN, ""));
+                let zero = mk().cast_expr(mk().lit_expr(mk().int_lit(0, "")), elt);
+                stmts.push(mk().local_stmt(P(mk().local(
+                    mk().set_mutbl(Mutability::Mutable).ident_pat(&*name),
+                    Some(mk().array_ty(elt, len())),
+                    Some(mk().repeat_expr(zero, len())),
+                ))));
+
+                // for x in bufN.iter_mut() { *x = fuzz(&mut state) as T; }
+                let fill = mk().assign_expr(mk().unary_expr(UnOp::Deref, mk().ident_expr("x")), fuzzed(elt));
+                stmts.push(mk().semi_stmt(mk().for_expr(
+                    mk().ident_pat("x"),
+                    mk().method_call_expr(mk().ident_expr(&*name), "iter_mut", vec![] as Vec<P<Expr>>),
+                    mk().block(vec![mk().semi_stmt(fill)]),
+                    None as Option<Ident>,
+                )));
+
+                // bufN[BUFFER_LEN - 1] = 0 as T;
+                if is_char {
+                    let last = mk().index_expr(mk().ident_expr(&*name), mk().lit_expr(mk().int_lit(BUFFER_LEN - 1, "")));
+                    let nul = mk().cast_expr(mk().lit_expr(mk().int_lit(0, "")), elt);
+                    stmts.push(mk().semi_stmt(mk().assign_expr(last, nul)));
+                }
+
+                // let mut rust_bufN = bufN;
+                stmts.push(mk().local_stmt(P(mk().local(
+                    mk().set_mutbl(Mutability::Mutable).ident_pat(&*rust_name),
+                    None as Option<P<Ty>>,
+                    Some(mk().ident_expr(&*name)),
+                ))));
+
+                let as_ptr = |buf: &str| mk().method_call_expr(mk().ident_expr(buf), "as_mut_ptr", vec![] as Vec<P<Expr>>);
+                c_args.push(as_ptr(&name));
+                rust_args.push(as_ptr(&rust_name));
+                buffers.push((name, rust_name));
+            }
+        }
+    }
+
+    // let (ret, rust_ret) = unsafe { (f(..), rust_f(..)) };
+    let calls = mk().tuple_expr(vec![
+        mk().call_expr(mk().path_expr(vec![&*harness.c_name]), c_args),
+        mk().call_expr(mk().path_expr(vec![&*harness.rust_name]), rust_args),
+    ]);
+    let calls = mk().block_expr(mk().unsafe_().block(vec![mk().expr_stmt(calls)]));
+    let rets = if harness.ret.is_some() {
+        vec![mk().ident_pat("ret"), mk().ident_pat("rust_ret")]
+    } else {
+        vec![mk().wild_pat(), mk().wild_pat()]
+    };
+    stmts.push(mk().local_stmt(P(mk().local(
+        mk().tuple_pat(rets),
+        None as Option<P<Ty>>,
+        Some(calls),
+    ))));
+
+    if harness.ret.is_some() {
+        stmts.push(mk_assert_eq("ret", "rust_ret"));
+    }
+    for &(ref name, ref rust_name) in &buffers {
+        stmts.push(mk_assert_eq(name, rust_name));
+    }
+
+    // let mut state: u64 = 1; let mut iteration = 0; while iteration < ITERATIONS { ..; iteration += 1; }
+    let iteration = || mk().ident_expr("iteration");
+    stmts.push(mk().semi_stmt(mk().assign_op_expr(
+        BinOpKind::Add,
+        iteration(),
+        mk().lit_expr(mk().int_lit(1, "")),
+    )));
+    let body = vec![
+        mk().local_stmt(P(mk().local(
+            mk().set_mutbl(Mutability::Mutable).ident_pat("state"),
+            Some(mk().path_ty(vec!["u64"])),
+            Some(mk().lit_expr(mk().int_lit(1, ""))),
+        ))),
+        mk().local_stmt(P(mk().local(
+            mk().set_mutbl(Mutability::Mutable).ident_pat("iteration"),
+            None as Option<P<Ty>>,
+            Some(mk().lit_expr(mk().int_lit(0, ""))),
+        ))),
+        mk().expr_stmt(mk().while_expr(
+            mk().binary_expr(BinOpKind::Lt, iteration(), mk().lit_expr(mk().int_lit(ITERATIONS, ""))),
+            mk().block(stmts),
+            None as Option<Ident>,
+        )),
+    ];
+
+    let decl = mk().fn_decl(vec![], FunctionRetTy::Default(DUMMY_SP), false);
+    mk().single_attr("test").fn_item(format!("differential_{}", harness.c_name), decl, mk().block(body))
+}
+
+/// `assert_eq!(lhs, rhs);`
+fn mk_assert_eq(lhs: &str, rhs: &str) -> Stmt {
+    let tokens = vec![
+        Token::interpolated(Nonterminal::NtExpr(mk().ident_expr(lhs))),
+        Token::Comma,
+        Token::interpolated(Nonterminal::NtExpr(mk().ident_expr(rhs))),
+    ].into_iter().collect::<TokenStream>();
+    mk().semi_stmt(mk().mac_expr(mk().mac(vec!["assert_eq"], tokens)))
+}
@@ -15,9 +15,14 @@ pub mod clang_ast;
 pub mod convert_type;
 pub mod loops;
 pub mod comment_store;
+pub mod lints;
 pub mod translator;
 pub mod c_ast;
 pub mod cfg;
+pub mod split_functions;
+pub mod differential_tests;
+pub mod stats;
+pub mod transpile;
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,93 @@
+//! Naming checks mirroring the rustc style lints. These let the translator attach
+//! `#[allow(...)]` attributes only to the items whose C names would trip a lint.
+
+/// Names accepted by the `non_snake_case` lint
+pub fn is_snake_case(name: &str) -> bool {
+    let name = name.trim_matches('_');
+    !name.contains("__") && !name.chars().any(char::is_uppercase)
+}
+
+/// Names accepted by the `non_camel_case_types` lint
+pub fn is_camel_case(name: &str) -> bool {
+    let name = name.trim_matches('_');
+    match name.chars().next() {
+        None => true,
+        Some(c) => !c.is_lowercase() && !name.contains('_'),
+    }
+}
+
+/// Names accepted by the `non_upper_case_globals` lint
+pub fn is_upper_case(name: &str) -> bool {
+    !name.chars().any(char::is_lowercase)
+}
+
+/// Accumulates the set of lints an item needs to be allowed
+#[derive(Debug, Default)]
+pub struct LintAllows {
+    allows: Vec<&'static str>,
+}
+
+impl LintAllows {
+    pub fn new() -> Self {
+        LintAllows::default()
+    }
+
+    fn add(&mut self, lint: &'static str) {
+        if !self.allows.contains(&lint) {
+            self.allows.push(lint)
+        }
+    }
+
+    /// Record a function, argument, or local variable name
+    pub fn value_name(&mut self, name: &str) {
+        if !is_snake_case(name) { self.add("non_snake_case") }
+    }
+
+    /// Record a struct, union, or type alias name
+    pub fn type_name(&mut self, name: &str) {
+        if !is_camel_case(name) { self.add("non_camel_case_types") }
+    }
+
+    /// Record a static or constant name
+    pub fn global_name(&mut self, name: &str) {
+        if !is_upper_case(name) { self.add("non_upper_case_globals") }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.allows.is_empty()
+    }
+
+    pub fn into_vec(self) -> Vec<&'static str> {
+        self.allows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snake_case_function() {
+        let mut allows = LintAllows::new();
+        allows.value_name("add_two_numbers");
+        allows.value_name("_private_helper");
+        assert!(allows.is_empty());
+    }
+
+    #[test]
+    fn camel_case_function() {
+        let mut allows = LintAllows::new();
+        allows.value_name("addTwoNumbers");
+        allows.value_name("anotherOne");
+        assert_eq!(allows.into_vec(), vec!["non_snake_case"]);
+    }
+
+    #[test]
+    fn type_and_global_names() {
+        assert!(is_camel_case("Point"));
+        assert!(!is_camel_case("point_t"));
+        assert!(!is_camel_case("unnamed"));
+        assert!(is_upper_case("BUFFER_SIZE"));
+        assert!(!is_upper_case("buffer_size"));
+    }
+}
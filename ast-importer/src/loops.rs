@@ -1,6 +1,8 @@
 
 use std::cell::{Cell, RefCell, Ref, RefMut};
 
+use c_ast::CStmtId;
+
 pub enum LoopType {
     For,
     While,
@@ -9,6 +11,8 @@ pub enum LoopType {
 
 pub struct Loop {
     pub loop_type: LoopType,
+    /// The C statement of the loop body, which identifies the loop
+    pub body: CStmtId,
     pub label: Option<String>,
     pub body_label: Option<String>,
     pub has_break: bool,
@@ -16,9 +20,10 @@ pub struct Loop {
 }
 
 impl Loop {
-    pub fn new(lt: LoopType) -> Loop {
+    pub fn new(lt: LoopType, body: CStmtId) -> Loop {
         Loop {
             loop_type: lt,
+            body,
             label: None,
             body_label: None,
             has_break: false,
@@ -73,8 +78,8 @@ impl LoopContext {
     }
 
     /// Push a new loop
-    pub fn push_loop(&self, lt: LoopType) {
-        self.loops.borrow_mut().push(Loop::new(lt));
+    pub fn push_loop(&self, lt: LoopType, body: CStmtId) {
+        self.loops.borrow_mut().push(Loop::new(lt, body));
     }
 
     /// Pop the current loop off the stack and return it
@@ -89,4 +94,27 @@ impl LoopContext {
     pub fn current_loop_mut(&self) -> RefMut<Loop> {
         RefMut::map(self.loops.borrow_mut(), |l| l.last_mut().expect("Expected valid loop"))
     }
+
+    /// The enclosing loop with the given body
+    pub fn loop_with_body_mut(&self, body: CStmtId) -> RefMut<Loop> {
+        RefMut::map(self.loops.borrow_mut(), |loops| {
+            loops.iter_mut().rev().find(|l| l.body == body).expect("Expected enclosing loop")
+        })
+    }
+}
+
+/// A `goto` that only leaves an enclosing loop or starts its next iteration, identified by the
+/// loop's body
+#[derive(Copy, Clone, Debug)]
+pub enum LoopJump {
+    Break(CStmtId),
+    Continue(CStmtId),
+}
+
+impl LoopJump {
+    pub fn loop_body(&self) -> CStmtId {
+        match *self {
+            LoopJump::Break(body) | LoopJump::Continue(body) => body,
+        }
+    }
 }
@@ -1,17 +1,15 @@
 #[macro_use]
 extern crate clap;
-extern crate cbor;
 extern crate ast_importer;
 
-use std::io::{Error, stdout, Cursor};
 use std::io::prelude::*;
 use std::fs::File;
-use cbor::Decoder;
-use ast_importer::clang_ast::process;
-use ast_importer::c_ast::*;
-use ast_importer::c_ast::Printer;
-use ast_importer::clang_ast::AstContext;
-use ast_importer::translator::TranslationConfig;
+use std::path::Path;
+use std::process;
+use ast_importer::translator::SignedDivision;
+use ast_importer::transpile::{TranspileOptions, transpile};
+use ast_importer::cfg::CfgStructuring;
+use ast_importer::stats;
 use clap::{Arg, App};
 
 fn main() {
@@ -29,6 +27,11 @@ fn main() {
             .long("translate-entry")
             .help("Creates an entry point that calls the C main function")
             .takes_value(false))
+        .arg(Arg::with_name("emit-differential-tests")
+            .long("emit-differential-tests")
+            .requires("prefix-function-names")
+            .help("Emits a #[cfg(test)] module comparing translated functions with scalar and buffer arguments against the C originals")
+            .takes_value(false))
         .arg(Arg::with_name("fail-on-multiple")
              .long("fail-on-multiple")
              .requires("reloop-cfgs")
@@ -73,6 +76,12 @@ fn main() {
             .long("ignore-c-multiple-info")
             .help("Don't keep/use information about C branches")
             .takes_value(false))
+        .arg(Arg::with_name("cfg-structuring")
+            .requires("reloop-cfgs")
+            .long("cfg-structuring")
+            .help("Algorithm used to turn CFGs back into structured control flow (defaults to relooper)")
+            .possible_values(&["relooper", "pattern", "switch"])
+            .takes_value(true))
         .arg(Arg::with_name("dump-function-cfgs")
             .requires("reloop-cfgs")
             .long("ddump-function-cfgs")
@@ -113,9 +122,14 @@ fn main() {
 
         // End-user
         .arg(Arg::with_name("INPUT")
-            .help("Sets the input CBOR file to use")
+            .help("Sets the input CBOR file(s) to use")
             .required(true)
+            .multiple(true)
             .index(1))
+        .arg(Arg::with_name("output-dir")
+             .long("output-dir")
+             .help("Write one module per input translation unit into the given directory, importing symbols defined by sibling units from their modules")
+             .takes_value(true))
         .arg(Arg::with_name("invalid-code")
             .long("invalid-code")
             .help("How to handle violated invariants or invalid code")
@@ -125,106 +139,146 @@ fn main() {
              .long("emit-module")
              .help("Emit the .rs file as a module instead of a crate, excluding the crate preamble")
              .takes_value(false))
+        .arg(Arg::with_name("ownership-hints")
+             .long("ownership-hints")
+             .help("Annotate pure and const functions, and functions returning restrict pointers, with priors for the ownership analysis")
+             .takes_value(false))
+        .arg(Arg::with_name("destructor")
+             .long("destructor")
+             .help("Release records of type STRUCT through a `Drop` impl calling FUNCTION, given as STRUCT=FUNCTION")
+             .multiple(true)
+             .number_of_values(1)
+             .takes_value(true))
+        .arg(Arg::with_name("split-functions-over")
+             .long("split-functions-over")
+             .help("Outline parts of functions with more than STMTS statements into helper functions, to speed up compiling them")
+             .value_name("STMTS")
+             .takes_value(true))
+        .arg(Arg::with_name("signed-division")
+             .long("signed-division")
+             .help("Whether signed `/` and `%` panic on the overflowing `INT_MIN / -1` or wrap to `INT_MIN` and `0`")
+             .possible_values(&["checked", "wrapping"])
+             .default_value("checked"))
+        .arg(Arg::with_name("contract-fp")
+             .long("contract-fp")
+             .help("Fuse floating point `a * b + c` into `a.mul_add(b, c)` where `#pragma STDC FP_CONTRACT ON` allows it")
+             .takes_value(false))
+        .arg(Arg::with_name("annotate-signatures")
+             .long("annotate-signatures")
+             .help("Precede each translated function and struct with a `// C: ...` comment giving its C signature")
+             .takes_value(false))
+        .arg(Arg::with_name("report-impl-defined")
+             .long("report-impl-defined")
+             .help("Report to stderr each place the translation depends on implementation-defined behavior, like right shifts of signed values")
+             .takes_value(false))
+        .arg(Arg::with_name("restrict-checks")
+             .long("restrict-checks")
+             .help("Check in debug builds that restrict pointer arguments don't point to the same address as other pointer arguments (overlapping but distinct buffers aren't detected)")
+             .takes_value(false))
+        .arg(Arg::with_name("stats")
+             .long("stats")
+             .help("Report the time spent in each phase and the peak memory use to stderr")
+             .takes_value(false))
         .arg(Arg::with_name("fail-on-error")
              .long("fail-on-error")
              .help("Fail to translate a module when a portion is not able to be translated")
              .takes_value(false))
         .get_matches();
 
-    // Build a TranslationConfig from the command line
-    let tcfg = TranslationConfig {
-        fail_on_error:          matches.is_present("fail-on-error"),
-        reloop_cfgs:            matches.is_present("reloop-cfgs"),
-        fail_on_multiple:       matches.is_present("fail-on-multiple"),
-        dump_function_cfgs:     matches.is_present("dump-function-cfgs"),
-        json_function_cfgs:     matches.is_present("json-function-cfgs"),
-        dump_cfg_liveness:      matches.is_present("dump-cfgs-liveness"),
-        dump_structures:        matches.is_present("dump-structures"),
-        debug_relooper_labels:  matches.is_present("debug-labels"),
-        cross_checks:           matches.is_present("cross-checks"),
-        cross_check_configs:    matches.values_of("cross-check-config")
-            .map(|vals| vals.map(String::from).collect::<Vec<_>>())
-            .unwrap_or_default(),
-        prefix_function_names:  matches.value_of("prefix-function-names")
-            .map(String::from),
-        translate_asm:          matches.is_present("translate-asm"),
-        translate_entry:        matches.is_present("translate-entry"),
-        use_c_loop_info:        !matches.is_present("ignore-c-loop-info"),
-        use_c_multiple_info:    !matches.is_present("ignore-c-multiple-info"),
-        simplify_structures:    !matches.is_present("no-simplify-structures"),
-        emit_module:            matches.is_present("emit-module"),
-        panic_on_translator_failure: {
-            match matches.value_of("invalid-code") {
-                Some("panic") => true,
-                Some("compile_error") => false,
-                _ => panic!("Invalid option"),
-            }
-        },
-    };
-    let file = matches.value_of("INPUT").unwrap();
-    let dump_untyped_context = matches.is_present("dump-untyped-clang-ast");
-    let dump_typed_context = matches.is_present("dump-typed-clang-ast");
-    let pretty_typed_context = matches.is_present("pretty-typed-clang-ast");
+    // Build the transpiler options from the command line
+    let mut options = TranspileOptions::new()
+        .fail_on_error(matches.is_present("fail-on-error"))
+        .reloop_cfgs(matches.is_present("reloop-cfgs"))
+        .fail_on_multiple(matches.is_present("fail-on-multiple"))
+        .dump_function_cfgs(matches.is_present("dump-function-cfgs"))
+        .json_function_cfgs(matches.is_present("json-function-cfgs"))
+        .dump_cfg_liveness(matches.is_present("dump-cfgs-liveness"))
+        .dump_structures(matches.is_present("dump-structures"))
+        .debug_relooper_labels(matches.is_present("debug-labels"))
+        .cross_checks(matches.is_present("cross-checks"))
+        .prefix_function_names(matches.value_of("prefix-function-names").map(String::from))
+        .translate_asm(matches.is_present("translate-asm"))
+        .translate_entry(matches.is_present("translate-entry"))
+        .use_c_loop_info(!matches.is_present("ignore-c-loop-info"))
+        .use_c_multiple_info(!matches.is_present("ignore-c-multiple-info"))
+        .simplify_structures(!matches.is_present("no-simplify-structures"))
+        .cfg_structuring(match matches.value_of("cfg-structuring") {
+            Some("relooper") | None => CfgStructuring::Relooper,
+            Some("pattern") => CfgStructuring::Pattern,
+            Some("switch") => CfgStructuring::Switch,
+            _ => panic!("Invalid option"),
+        })
+        .emit_module(matches.is_present("emit-module"))
+        .ownership_hints(matches.is_present("ownership-hints"))
+        .split_functions_over(matches.value_of("split-functions-over")
+            .map(|n| n.parse().expect("Expected a statement count for --split-functions-over")))
+        .signed_division(match matches.value_of("signed-division") {
+            Some("checked") => SignedDivision::Checked,
+            Some("wrapping") => SignedDivision::Wrapping,
+            _ => panic!("Invalid option"),
+        })
+        .emit_differential_tests(matches.is_present("emit-differential-tests"))
+        .contract_fp(matches.is_present("contract-fp"))
+        .annotate_signatures(matches.is_present("annotate-signatures"))
+        .report_impl_defined(matches.is_present("report-impl-defined"))
+        .restrict_checks(matches.is_present("restrict-checks"))
+        .stats(matches.is_present("stats"))
+        .panic_on_translator_failure(match matches.value_of("invalid-code") {
+            Some("panic") => true,
+            Some("compile_error") => false,
+            _ => panic!("Invalid option"),
+        })
+        .dump_untyped_ast(matches.is_present("dump-untyped-clang-ast"))
+        .dump_typed_ast(matches.is_present("dump-typed-clang-ast"))
+        .pretty_typed_ast(matches.is_present("pretty-typed-clang-ast"));
+    for config in matches.values_of("cross-check-config").into_iter().flat_map(|vals| vals) {
+        options = options.cross_check_config(config);
+    }
+    for mapping in matches.values_of("destructor").into_iter().flat_map(|vals| vals) {
+        let (record, destructor) = parse_destructor(mapping);
+        options = options.destructor(record, destructor);
+    }
 
-    // Extract the untyped AST from the CBOR file 
-    let untyped_context = match parse_untyped_ast(file) {
-        Err(e) => panic!("{:#?}", e),
-        Ok(cxt) => cxt,
-    };
+    let files: Vec<&str> = matches.values_of("INPUT").unwrap().collect();
+    let output_dir = matches.value_of("output-dir");
+    let show_stats = options.config().stats;
 
-    if dump_untyped_context {
-        println!("CBOR Clang AST");
-        println!("{:#?}", untyped_context);
+    if files.len() > 1 && output_dir.is_none() {
+        panic!("Translating multiple inputs requires --output-dir");
     }
 
-    // Convert this into a typed AST
-    let typed_context = {
-        let mut conv = ConversionContext::new(&untyped_context);
-        conv.convert(&untyped_context);
-        conv.typed_context
+    // Perform the translation
+    let modules = match transpile(&options, &files) {
+        Ok(modules) => modules,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
     };
 
-    if dump_typed_context {
-        println!("Clang AST");
-        println!("{:#?}", typed_context);
+    match output_dir {
+        None => println!("{}", modules[0].source),
+        Some(output_dir) => {
+            for module in modules {
+                let output_path = Path::new(output_dir).join(format!("{}.rs", module.name));
+                let mut output = File::create(&output_path)
+                    .expect(&format!("Could not create {}", output_path.display()));
+                output.write_all(module.source.as_bytes())
+                    .expect(&format!("Could not write {}", output_path.display()));
+            }
+        }
     }
 
-    if pretty_typed_context {
-        println!("Pretty-printed Clang AST");
-        println!("{:#?}", Printer::new(stdout()).print(&typed_context));
+    if show_stats {
+        stats::report_peak_memory();
     }
-
-
-//    use syn::parse;
-//    use quote::ToTokens;
-//    use quote::Tokens;
-//    if let parse::IResult::Done(_, t) = parse::ty("[u32; 10]") {
-//        let mut tokens = Tokens::new();
-//        t.to_tokens(&mut tokens);
-//        println!("{}", tokens.as_str());
-//    }
-
-    // Perform the translation
-    use ast_importer::translator::translate;
-
-    let mut conv = ConversionContext::new(&untyped_context);
-    conv.convert(&untyped_context);
-
-    println!("{}", translate(conv.typed_context, tcfg));
 }
 
-fn parse_untyped_ast(filename: &str) -> Result<AstContext, Error> {
-    let mut f = File::open(filename)?;
-    let mut buffer = vec![];
-    f.read_to_end(&mut buffer)?;
-
-    let mut cursor: Decoder<Cursor<Vec<u8>>> = Decoder::from_bytes(buffer);
-    let items = cursor.items();
-
-    match process(items) {
-        Ok(cxt) => Ok(cxt),
-        Err(e) => panic!("{:#?}", e),
+/// Split a `--destructor` mapping, ex: `foo=free_foo`
+fn parse_destructor(mapping: &str) -> (String, String) {
+    let mut parts = mapping.splitn(2, '=');
+    match (parts.next(), parts.next()) {
+        (Some(record), Some(destructor)) => (record.to_owned(), destructor.to_owned()),
+        _ => panic!("Expected STRUCT=FUNCTION for --destructor, got '{}'", mapping),
     }
 }
-
-
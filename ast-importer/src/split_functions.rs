@@ -0,0 +1,271 @@
+//! Outlining of oversized translated function bodies into nested helper functions, so that
+//! rustc type checks and borrow checks several small bodies instead of one huge one.
+//!
+//! Only runs of top-level statements of a body are outlined. Such a statement can't `break` or
+//! `continue` out of itself, so a run of them can move into a helper as long as it doesn't
+//! `return` and doesn't declare locals (or items) the rest of the body still sees. The function
+//! arguments and earlier top-level locals it uses are passed in by reference.
+
+use std::collections::{HashMap, HashSet};
+use syntax::ast;
+use syntax::ast::*;
+use syntax::codemap::DUMMY_SP;
+use syntax::fold::{self, Folder};
+use syntax::ptr::P;
+use syntax::symbol::Symbol;
+use syntax::visit::{self, Visitor};
+use idiomize::ast_manip::make_ast::*;
+
+/// Count the statements in a function body, including nested ones
+pub fn count_stmts(stmts: &[Stmt]) -> usize {
+    stmts.iter().map(stmt_weight).sum()
+}
+
+fn stmt_weight(stmt: &Stmt) -> usize {
+    let mut counter = StmtCounter(0);
+    counter.visit_stmt(stmt);
+    counter.0
+}
+
+/// Outline runs of top-level statements of the body of the function `name` into helper
+/// functions of at most `threshold` statements, declared right before they get called. Runs
+/// lighter than half the threshold are not worth a helper and stay inline. `mk_helper` supplies
+/// the attributes of the helper functions.
+pub fn split_function_body(
+    name: &str,
+    args: &[Arg],
+    stmts: Vec<Stmt>,
+    threshold: usize,
+    mk_helper: &Fn() -> Builder,
+) -> Vec<Stmt> {
+    let mut splitter = Splitter {
+        name,
+        threshold,
+        mk_helper,
+        locals: HashMap::new(),
+        helpers: 0,
+        output: vec![],
+        pending: vec![],
+        pending_weight: 0,
+    };
+
+    for arg in args {
+        if let PatKind::Ident(BindingMode::ByValue(mutbl), ident, None) = arg.pat.node {
+            splitter.locals.insert(ident.node.name, Some((arg.ty.clone(), mutbl)));
+        }
+    }
+
+    let last = stmts.len().saturating_sub(1);
+    for (i, stmt) in stmts.into_iter().enumerate() {
+        let uses = StmtUses::of(&stmt);
+        let weight = stmt_weight(&stmt);
+
+        let is_boundary = uses.returns || uses.opaque_macros || match stmt.node {
+            StmtKind::Local(..) | StmtKind::Item(..) | StmtKind::Mac(..) => true,
+            // The tail expression of the body is its value
+            StmtKind::Expr(..) => i == last,
+            StmtKind::Semi(..) => false,
+        };
+
+        if is_boundary || splitter.pending_weight + weight > threshold {
+            splitter.flush();
+        }
+
+        if is_boundary {
+            splitter.declare(&stmt);
+            splitter.output.push(stmt);
+        } else {
+            splitter.pending.push((stmt, uses));
+            splitter.pending_weight += weight;
+        }
+    }
+    splitter.flush();
+
+    splitter.output
+}
+
+struct Splitter<'a> {
+    name: &'a str,
+    threshold: usize,
+    mk_helper: &'a Fn() -> Builder,
+
+    /// Top-level locals in scope, along with their type and mutability when they can be passed
+    /// to a helper
+    locals: HashMap<Symbol, Option<(P<Ty>, Mutability)>>,
+    helpers: usize,
+    output: Vec<Stmt>,
+
+    /// The run of statements that could be outlined next
+    pending: Vec<(Stmt, StmtUses)>,
+    pending_weight: usize,
+}
+
+impl<'a> Splitter<'a> {
+    /// Track the locals introduced by a statement that stays in the function body. Locals
+    /// without a type or an initializer can't be borrowed by a helper.
+    fn declare(&mut self, stmt: &Stmt) {
+        if let StmtKind::Local(ref local) = stmt.node {
+            if let PatKind::Ident(BindingMode::ByValue(mutbl), ident, None) = local.pat.node {
+                let passable = match (&local.ty, &local.init) {
+                    (&Some(ref ty), &Some(_)) => Some((ty.clone(), mutbl)),
+                    _ => None,
+                };
+                self.locals.insert(ident.node.name, passable);
+            }
+        }
+    }
+
+    fn flush(&mut self) {
+        let pending: Vec<(Stmt, StmtUses)> = self.pending.drain(..).collect();
+        let weight = self.pending_weight;
+        self.pending_weight = 0;
+
+        if pending.is_empty() {
+            return
+        }
+
+        let mut uses = StmtUses::default();
+        for &(_, ref stmt_uses) in &pending {
+            uses.names.extend(stmt_uses.names.iter().cloned());
+            uses.bindings.extend(stmt_uses.bindings.iter().cloned());
+        }
+        let stmts = pending.into_iter().map(|(stmt, _)| stmt);
+
+        match self.captures(&uses) {
+            Some(ref captures) if weight * 2 >= self.threshold => {
+                let outlined = self.outline(captures, stmts.collect());
+                self.output.extend(outlined);
+            }
+            _ => self.output.extend(stmts),
+        }
+    }
+
+    /// The locals a run of statements has to borrow, unless one of them can't be borrowed or
+    /// is shadowed within the run
+    fn captures(&self, uses: &StmtUses) -> Option<Vec<(Symbol, P<Ty>, Mutability)>> {
+        let mut captures = vec![];
+        for name in &uses.names {
+            match self.locals.get(name) {
+                None => {}
+                Some(&None) => return None,
+                Some(&Some(_)) if uses.bindings.contains(name) => return None,
+                Some(&Some((ref ty, mutbl))) => captures.push((*name, ty.clone(), mutbl)),
+            }
+        }
+        captures.sort_by_key(|&(name, _, _)| name.as_str().to_string());
+        Some(captures)
+    }
+
+    /// Move statements into a new helper function, returning the helper declaration and the
+    /// statement calling it
+    fn outline(&mut self, captures: &[(Symbol, P<Ty>, Mutability)], stmts: Vec<Stmt>) -> Vec<Stmt> {
+        self.helpers += 1;
+        let helper_name = format!("{}_part{}", self.name, self.helpers);
+
+        let captured: HashSet<Symbol> = captures.iter().map(|&(name, _, _)| name).collect();
+        let mut deref = DerefCaptures(&captured);
+        let body: Vec<Stmt> = stmts.into_iter()
+            .flat_map(|stmt| deref.fold_stmt(stmt).into_iter())
+            .collect();
+
+        let mut params = vec![];
+        let mut args = vec![];
+        for &(name, ref ty, mutbl) in captures {
+            params.push(mk().arg(mk().set_mutbl(mutbl).ref_ty(ty), mk().ident_pat(name)));
+            args.push(mk().set_mutbl(mutbl).addr_of_expr(mk().ident_expr(name)));
+        }
+
+        let decl = mk().fn_decl(params, FunctionRetTy::Default(DUMMY_SP), false);
+        let helper = (self.mk_helper)().unsafe_().fn_item(&*helper_name, decl, mk().block(body));
+        let call = mk().call_expr(mk().ident_expr(&*helper_name), args);
+
+        vec![mk().item_stmt(helper), mk().semi_stmt(call)]
+    }
+}
+
+struct StmtCounter(usize);
+
+impl<'ast> Visitor<'ast> for StmtCounter {
+    fn visit_stmt(&mut self, stmt: &'ast Stmt) {
+        self.0 += 1;
+        visit::walk_stmt(self, stmt)
+    }
+
+    fn visit_mac(&mut self, mac: &'ast Mac) {
+        visit::walk_mac(self, mac)
+    }
+}
+
+/// What a statement refers to and does, as far as outlining it is concerned
+#[derive(Default)]
+struct StmtUses {
+    /// Single-segment paths used as expressions
+    names: HashSet<Symbol>,
+    /// Names bound by patterns
+    bindings: HashSet<Symbol>,
+    returns: bool,
+    /// Macros whose arguments may refer to locals, such as `asm!`
+    opaque_macros: bool,
+}
+
+impl StmtUses {
+    fn of(stmt: &Stmt) -> StmtUses {
+        let mut uses = StmtUses::default();
+        uses.visit_stmt(stmt);
+        uses
+    }
+}
+
+impl<'ast> Visitor<'ast> for StmtUses {
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        match expr.node {
+            ExprKind::Path(None, ref path) if path.segments.len() == 1 => {
+                self.names.insert(path.segments[0].identifier.name);
+            }
+            ExprKind::Ret(..) => self.returns = true,
+            _ => {}
+        }
+        visit::walk_expr(self, expr)
+    }
+
+    fn visit_pat(&mut self, pat: &'ast Pat) {
+        if let PatKind::Ident(_, ident, _) = pat.node {
+            self.bindings.insert(ident.node.name);
+        }
+        visit::walk_pat(self, pat)
+    }
+
+    fn visit_mac(&mut self, mac: &'ast Mac) {
+        let is_diagnostic = mac.node.path.segments.last().map_or(false, |seg| {
+            let name = seg.identifier.name.as_str();
+            &*name == "panic" || &*name == "compile_error"
+        });
+        if !is_diagnostic {
+            self.opaque_macros = true;
+        }
+        visit::walk_mac(self, mac)
+    }
+}
+
+/// Rewrite uses of borrowed locals `x` into `(*x)`
+struct DerefCaptures<'a>(&'a HashSet<Symbol>);
+
+impl<'a> Folder for DerefCaptures<'a> {
+    fn fold_expr(&mut self, expr: P<Expr>) -> P<Expr> {
+        let is_captured = match expr.node {
+            ExprKind::Path(None, ref path) =>
+                path.segments.len() == 1 && self.0.contains(&path.segments[0].identifier.name),
+            _ => false,
+        };
+
+        if is_captured {
+            mk().paren_expr(mk().unary_expr(ast::UnOp::Deref, expr))
+        } else {
+            expr.map(|expr| fold::noop_fold_expr(expr, self))
+        }
+    }
+
+    fn fold_mac(&mut self, mac: Mac) -> Mac {
+        fold::noop_fold_mac(mac, self)
+    }
+}
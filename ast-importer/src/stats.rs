@@ -0,0 +1,37 @@
+//! Timing and memory diagnostics for `--stats`, printed to stderr.
+
+use std::fs::File;
+use std::io::Read;
+use std::time::{Duration, Instant};
+
+/// Print how long a phase of the translation took
+pub fn report_phase(name: &str, time: Duration) {
+    eprintln!("stats: {:<14} {}.{:03}s", name, time.as_secs(), time.subsec_nanos() / 1_000_000);
+}
+
+/// Run `f`, reporting how long it took as the phase `name` when `enabled`
+pub fn time_phase<T, F: FnOnce() -> T>(enabled: bool, name: &str, f: F) -> T {
+    let start = Instant::now();
+    let result = f();
+    if enabled {
+        report_phase(name, start.elapsed());
+    }
+    result
+}
+
+/// Peak resident set size of the process so far, in kB. This is only known on Linux.
+pub fn peak_memory_kb() -> Option<u64> {
+    let mut status = String::new();
+    File::open("/proc/self/status").ok()?.read_to_string(&mut status).ok()?;
+    status.lines()
+        .find(|line| line.starts_with("VmHWM:"))
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|kb| kb.parse().ok())
+}
+
+pub fn report_peak_memory() {
+    match peak_memory_kb() {
+        Some(kb) => eprintln!("stats: {:<14} {} kB", "peak memory", kb),
+        None => eprintln!("stats: {:<14} unknown", "peak memory"),
+    }
+}
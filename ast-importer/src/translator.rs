@@ -2,9 +2,10 @@ use syntax::ast;
 use syntax::ast::*;
 use syntax::codemap::{DUMMY_SP, Span};
 use syntax::tokenstream::{TokenStream};
-use syntax::parse::token::{DelimToken,Token,Nonterminal};
+use syntax::parse::token::{self,DelimToken,Token,Nonterminal};
+use syntax::symbol::Symbol;
 use syntax::abi::Abi;
-use std::collections::{HashMap,HashSet};
+use std::collections::{BTreeSet,HashMap,HashSet};
 use renamer::Renamer;
 use convert_type::TypeConverter;
 use loops::*;
@@ -12,18 +13,24 @@ use idiomize::ast_manip::make_ast::*;
 use c_ast;
 use c_ast::*;
 use comment_store::*;
+use lints::LintAllows;
 use c_ast::iterators::{DFExpr, SomeId};
 use syntax::ptr::*;
 use syntax::print::pprust::*;
 use std::ops::Index;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell, RefMut};
 use std::char;
+use std::mem;
+use std::time::{Duration, Instant};
 use dtoa;
 
 use cfg;
+use split_functions;
+use differential_tests::{self, Harness, HarnessArg, Fuzzability};
+use stats;
 
 /// Configuration settings for the translation process
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
 pub struct TranslationConfig {
     pub reloop_cfgs: bool,
     pub fail_on_multiple: bool,
@@ -40,9 +47,93 @@ pub struct TranslationConfig {
     pub use_c_loop_info: bool,
     pub use_c_multiple_info: bool,
     pub simplify_structures: bool,
+    pub cfg_structuring: cfg::CfgStructuring,
     pub panic_on_translator_failure: bool,
     pub emit_module: bool,
     pub fail_on_error: bool,
+    /// Annotate `pure` and `const` C functions with `#[ownership_pure]`, and functions returning
+    /// `restrict` pointers with `#[ownership_restrict_return]`, as priors for the ownership
+    /// analysis
+    pub ownership_hints: bool,
+    /// Symbols defined by sibling translation units, mapped to the module translated from them
+    /// and to their Rust name in that module, see `exported_names`
+    pub extern_modules: HashMap<String, (String, String)>,
+    /// C destructor functions keyed by the name of the record type they clean up. These records
+    /// get a `Drop` impl calling the destructor in place of the explicit calls, as long as those
+    /// only ever release locals and the records are never copied.
+    pub destructors: HashMap<String, String>,
+    /// Outline parts of function bodies with more statements than this into helper functions
+    pub split_functions_over: Option<usize>,
+    /// How signed integer `/` and `%` handle the overflowing `INT_MIN / -1`
+    pub signed_division: SignedDivision,
+    /// Emit a `#[cfg(test)]` module comparing the prefixed translated functions against the C
+    /// originals on fuzzed arguments
+    pub emit_differential_tests: bool,
+    /// Fuse `a * b + c` into `a.mul_add(b, c)` where `#pragma STDC FP_CONTRACT ON` allows it
+    pub contract_fp: bool,
+    /// Precede each function and record with a `// C: ...` comment giving its C signature
+    pub annotate_signatures: bool,
+    /// Report to stderr each place the translation depends on implementation-defined behavior
+    pub report_impl_defined: bool,
+    /// Start each function with `debug_assert!`s that its `restrict` pointer parameters don't
+    /// point to the same place as its other pointer parameters
+    pub restrict_checks: bool,
+    /// Report the time spent in each phase of the translation to stderr
+    pub stats: bool,
+}
+
+/// Translation of signed integer division and remainder. Both truncate toward zero in C and
+/// Rust alike, and division by zero panics either way, like it traps in C. They only differ on
+/// `INT_MIN / -1` and `INT_MIN % -1`, which are undefined in C.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SignedDivision {
+    /// Translate to `/` and `%`, which panic on overflow, like the division traps on x86
+    Checked,
+
+    /// Translate to `wrapping_div` and `wrapping_rem`, so `INT_MIN / -1` is `INT_MIN` and
+    /// `INT_MIN % -1` is `0`
+    Wrapping,
+}
+
+/// Constructs whose meaning the C standard leaves to the implementation. Their translation
+/// follows the platform the translator runs on, which may not be the one the C code targets.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ImplDefined {
+    /// Right shift of a negative value (C11 6.5.7p5), translated as an arithmetic shift
+    SignedRightShift,
+
+    /// Widening a plain `char`, which sign extends where `char` is signed (C11 6.2.5p15)
+    CharSignedness,
+
+    /// Conversion to a signed type that can't represent every value of the source type
+    /// (C11 6.3.1.3p3), translated as truncation
+    SignedNarrowing,
+
+    /// Value of a multi-character constant, or of a character constant outside the basic
+    /// character set (C11 6.4.4.4p10)
+    CharacterConstant,
+
+    /// Integer type representing an enumerated type (C11 6.7.2.2p4), which matters for enums
+    /// with values outside the range of `int`, a common extension
+    EnumRepresentation,
+}
+
+impl ImplDefined {
+    fn description(&self) -> &'static str {
+        match *self {
+            ImplDefined::SignedRightShift => "right shift of a signed value",
+            ImplDefined::CharSignedness => "widening of a plain `char`",
+            ImplDefined::SignedNarrowing => "conversion to a signed type that cannot represent every value",
+            ImplDefined::CharacterConstant => "value of a character constant",
+            ImplDefined::EnumRepresentation => "integer type of an enum",
+        }
+    }
+}
+
+impl Default for SignedDivision {
+    fn default() -> Self {
+        SignedDivision::Checked
+    }
 }
 
 pub struct Translation {
@@ -55,6 +146,19 @@ pub struct Translation {
     renamer: RefCell<Renamer<CDeclId>>,
     loops: LoopContext,
     zero_inits: RefCell<HashMap<CDeclId, Result<P<Expr>, String>>>,
+    /// Name of the local holding the `alloca` buffers of the function being translated, once
+    /// it calls `alloca`
+    alloca_storage: RefCell<Option<String>>,
+    /// Labels of the function being translated whose `goto`s become `break` or `continue`
+    loop_jumps: RefCell<HashMap<CLabelId, LoopJump>>,
+    /// Sites depending on implementation-defined behavior, when `report_impl_defined` is set
+    impl_defined: RefCell<BTreeSet<(Option<SrcLoc>, ImplDefined)>>,
+    /// Time spent building and structuring control flow graphs, for `stats`
+    cfg_time: Cell<Duration>,
+    /// Records with a configured destructor that don't get a `Drop` impl, because the destructor
+    /// is called other than on the address of a local, their values get copied, or some local
+    /// isn't released on every path
+    undroppable_records: HashSet<CRecordId>,
     pub comment_context: RefCell<CommentContext>,
     pub comment_store: RefCell<CommentStore>,
 }
@@ -118,6 +222,11 @@ fn sequence_option<A,E>(x: Option<Result<A,E>>) -> Result<Option<A>, E> {
     }
 }
 
+/// `::std::sync::atomic::Ordering::<name>`
+fn atomic_ordering(name: &str) -> P<Expr> {
+    mk().path_expr(vec!["", "std", "sync", "atomic", "Ordering", name])
+}
+
 fn cast_int(val: P<Expr>, name: &str) -> P<Expr> {
     let opt_literal_val = match val.node {
         ExprKind::Lit(ref l) => match l.node {
@@ -246,8 +355,12 @@ pub fn signed_int_expr(value: i64) -> P<Expr> {
 fn prefix_names(translation: &mut Translation, prefix: String) {
     for (&decl_id, ref mut decl) in &mut translation.ast_context.c_decls {
         match decl.kind {
-            CDeclKind::Function { ref mut name, ref body, .. } if body.is_some() => {
+            CDeclKind::Function { ref mut name, ref body, ref mut alias, .. } if body.is_some() || alias.is_some() => {
                 name.insert_str(0, &prefix);
+                // Alias targets are definitions, which get the prefix as well
+                if let Some(ref mut target) = *alias {
+                    target.insert_str(0, &prefix);
+                }
 
                 translation.renamer.borrow_mut().insert(decl_id, &name);
             },
@@ -257,6 +370,48 @@ fn prefix_names(translation: &mut Translation, prefix: String) {
     }
 }
 
+/// Name the functions, enum constants, and top-level variables, in `CDeclId` order so that
+/// `exported_names` picks the same names as `translate`
+fn name_values(t: &mut Translation) {
+    // Used for testing; so that we don't overlap with C function names
+    if let Some(prefix) = t.tcfg.prefix_function_names.clone() {
+        prefix_names(t, prefix);
+    }
+
+    let mut decl_ids: Vec<CDeclId> = t.ast_context.c_decls.keys().cloned().collect();
+    decl_ids.sort();
+    for decl_id in decl_ids {
+        let name = match t.ast_context[decl_id].kind {
+            CDeclKind::Function { ref name, .. } => name.clone(),
+            CDeclKind::EnumConstant { ref name, .. } => name.clone(),
+            CDeclKind::Variable { ref ident, .. }
+              if t.ast_context.c_decls_top.contains(&decl_id) => ident.clone(),
+            _ => continue,
+        };
+        t.renamer.borrow_mut().insert(decl_id, &name);
+    }
+}
+
+/// The Rust names `translate` gives to the symbols `ast_context` exports, keyed by their C names.
+/// They differ when a C name is a Rust keyword, or when the test prefix is in use.
+pub fn exported_names(ast_context: TypedAstContext, tcfg: TranslationConfig) -> HashMap<String, String> {
+    let exported = ast_context.exported_symbols().into_iter()
+        .map(|(decl_id, name)| (decl_id, name.to_owned()))
+        .collect::<Vec<_>>();
+
+    let mut t = Translation::new(ast_context, tcfg);
+    if !t.tcfg.translate_entry {
+        t.ast_context.c_main = None;
+    }
+    t.ast_context.simplify();
+    name_values(&mut t);
+
+    let renamer = t.renamer.borrow();
+    exported.into_iter()
+        .filter_map(|(decl_id, name)| Some((name, renamer.get(&decl_id)?)))
+        .collect()
+}
+
 pub fn translate_failure(tcfg: &TranslationConfig, msg: &str) {
     if tcfg.fail_on_error {
         panic!("{}", msg)
@@ -266,6 +421,7 @@ pub fn translate_failure(tcfg: &TranslationConfig, msg: &str) {
 }
 
 pub fn translate(ast_context: TypedAstContext, tcfg: TranslationConfig) -> String {
+    let start = Instant::now();
     let mut t = Translation::new(ast_context, tcfg);
 
     if !t.tcfg.translate_entry {
@@ -275,7 +431,6 @@ pub fn translate(ast_context: TypedAstContext, tcfg: TranslationConfig) -> Strin
     t.ast_context.simplify();
 
     enum Name<'a> {
-        VarName(&'a str),
         TypeName(&'a str),
         AnonymousType,
         NoName,
@@ -288,17 +443,21 @@ pub fn translate(ast_context: TypedAstContext, tcfg: TranslationConfig) -> Strin
         }
     }
 
-    // Used for testing; so that we don't overlap with C function names
-    if let Some(prefix) = t.tcfg.prefix_function_names.clone() {
-        prefix_names(&mut t, prefix);
-    }
+    name_values(&mut t);
 
     // Identify typedefs that name unnamed types and collapse the two declarations
-    // into a single name and declaration, eliminating the typedef altogether.
+    // into a single name and declaration, eliminating the typedef altogether. When
+    // several typedefs name the same type, the first one in the source wins, and
+    // typedefs of such a typedef are left as aliases of it.
+    let mut typedefs: Vec<(&CDeclId, &CDecl)> = t.ast_context.c_decls.iter()
+        .filter(|&(_, decl)| match decl.kind { CDeclKind::Typedef { .. } => true, _ => false })
+        .collect();
+    typedefs.sort_by_key(|&(&decl_id, decl)| (decl.loc, decl_id));
+
     let mut prenamed_decls: HashSet<CDeclId> = HashSet::new();
-    for (&decl_id, decl) in &t.ast_context.c_decls {
-        if let CDeclKind::Typedef { ref name, typ } = decl.kind {
-            if let Some(subdecl_id) = t.ast_context.resolve_type(typ.ctype).kind.as_underlying_decl() {
+    for (&decl_id, decl) in typedefs {
+        if let CDeclKind::Typedef { ref name, typ, .. } = decl.kind {
+            if let Some(subdecl_id) = t.ast_context.direct_underlying_decl(typ.ctype) {
 
                 let is_unnamed = match t.ast_context[subdecl_id].kind {
                     CDeclKind::Struct { name: None, .. } => true,
@@ -318,7 +477,7 @@ pub fn translate(ast_context: TypedAstContext, tcfg: TranslationConfig) -> Strin
         }
     }
 
-        // Populate renamer with top-level names
+    // Name the top-level types
     for (&decl_id, decl) in &t.ast_context.c_decls {
         let decl_name = match decl.kind {
             _ if prenamed_decls.contains(&decl_id) => Name::NoName,
@@ -326,20 +485,17 @@ pub fn translate(ast_context: TypedAstContext, tcfg: TranslationConfig) -> Strin
             CDeclKind::Enum { ref name, .. } => some_type_name(name.as_ref().map(String::as_str)),
             CDeclKind::Union { ref name, .. } => some_type_name(name.as_ref().map(String::as_str)),
             CDeclKind::Typedef { ref name, .. } => Name::TypeName(name),
-            CDeclKind::Function { ref name, .. } => Name::VarName(name),
-            CDeclKind::EnumConstant { ref name, .. } => Name::VarName(name),
-            CDeclKind::Variable { ref ident, .. }
-              if t.ast_context.c_decls_top.contains(&decl_id) => Name::VarName(ident),
             _ => Name::NoName,
         };
         match decl_name {
             Name::NoName => (),
             Name::AnonymousType => { t.type_converter.borrow_mut().declare_decl_name(decl_id, "unnamed"); }
             Name::TypeName(name)=> { t.type_converter.borrow_mut().declare_decl_name(decl_id, name); }
-            Name::VarName(name) => { t.renamer.borrow_mut().insert(decl_id, &name); }
         }
     }
 
+    t.undroppable_records = t.find_undroppable_records();
+
     // Export all types
     for (&decl_id, decl) in &t.ast_context.c_decls {
         let needs_export = match decl.kind {
@@ -360,6 +516,10 @@ pub fn translate(ast_context: TypedAstContext, tcfg: TranslationConfig) -> Strin
                     translate_failure(&t.tcfg, &msg)
                 },
             }
+
+            if let Some(drop_impl) = t.convert_destructor(decl_id) {
+                t.items.push(drop_impl);
+            }
         }
     }
 
@@ -368,9 +528,15 @@ pub fn translate(ast_context: TypedAstContext, tcfg: TranslationConfig) -> Strin
         let needs_export = match t.ast_context.c_decls[top_id].kind {
             CDeclKind::Function { .. } => true,
             CDeclKind::Variable { .. } => true,
+            CDeclKind::StaticAssert { .. } => true,
             _ => false,
         };
         if needs_export {
+            if let Some(item) = t.convert_module_import(*top_id) {
+                t.items.push(item);
+                continue
+            }
+
             match t.convert_decl(true, *top_id) {
                 Ok(ConvertedDecl::Item(mut item)) => t.items.push(item),
                 Ok(ConvertedDecl::ForeignItem(mut item)) => t.foreign_items.push(item),
@@ -383,6 +549,26 @@ pub fn translate(ast_context: TypedAstContext, tcfg: TranslationConfig) -> Strin
         }
     }
 
+    // Add the differential tests of the functions
+    if t.tcfg.emit_differential_tests {
+        let mut harnesses = vec![];
+        for top_id in &t.ast_context.c_decls_top {
+            match t.differential_harness(*top_id) {
+                Ok(Some(Fuzzability::Fuzzable(harness))) => harnesses.push(harness),
+                Ok(Some(Fuzzability::Unsupported(c_name, reason))) =>
+                    eprintln!("Skipping differential test of `{}`: {}", c_name, reason),
+                Ok(None) => {}
+                Err(e) => {
+                    let msg = format!("Skipping differential test due to error: {}", e);
+                    translate_failure(&t.tcfg, &msg)
+                }
+            }
+        }
+        if !harnesses.is_empty() {
+            t.items.push(differential_tests::mk_test_module(&harnesses));
+        }
+    }
+
     // Add the main entry point
     if let Some(main_id) = t.ast_context.c_main {
         match t.convert_main(main_id) {
@@ -394,8 +580,33 @@ pub fn translate(ast_context: TypedAstContext, tcfg: TranslationConfig) -> Strin
         }
     };
 
+    if t.tcfg.report_impl_defined {
+        for line in t.impl_defined_report() {
+            eprintln!("{}", line);
+        }
+    }
+
+    // Declare the structs standing in for the `_Complex` types used by the translation
+    let complex_types = t.type_converter.borrow().complex_types();
+    for (name, elt) in complex_types {
+        let item = t.convert_complex_type(&name, elt);
+        t.items.push(item);
+    }
+
+    // Declare the structs holding over-aligned variables
+    let aligned_types = t.type_converter.borrow().aligned_types();
+    for (name, ty, align) in aligned_types {
+        let item = t.convert_aligned_type(&name, ty, align);
+        t.items.push(item);
+    }
+
+    let show_stats = t.tcfg.stats;
+    if show_stats {
+        stats::report_phase("translate", start.elapsed());
+        stats::report_phase("structure-CFG", t.cfg_time.get());
+    }
 
-    to_string(|s| {
+    stats::time_phase(show_stats, "print", || to_string(|s| {
         s.comments().get_or_insert(vec![]).extend(t.comment_store.into_inner().into_comments());
 
         if t.tcfg.emit_module {
@@ -403,13 +614,14 @@ pub fn translate(ast_context: TypedAstContext, tcfg: TranslationConfig) -> Strin
         } else {
             let mut pragmas: HashMap<&str, Vec<&str>> = HashMap::new();
             pragmas.insert("feature", vec!["libc","i128_type"]);
-            pragmas.insert("allow", vec!["non_upper_case_globals", "non_camel_case_types","non_snake_case",
-                                     "dead_code", "mutable_transmutes", "unused_mut"]);
+            pragmas.insert("allow", vec!["dead_code", "mutable_transmutes", "unused_mut"]);
 
+            if t.tcfg.cross_checks {
+                t.features.borrow_mut().extend(vec!["plugin", "custom_attribute"]);
+            }
             pragmas.get_mut("feature").unwrap().extend(t.features.borrow().iter());
 
             if t.tcfg.cross_checks {
-                pragmas.get_mut("feature").unwrap().append(&mut vec!["plugin", "custom_attribute"]);
                 pragmas.insert("cross_check", vec!["yes"]);
             }
 
@@ -469,7 +681,7 @@ pub fn translate(ast_context: TypedAstContext, tcfg: TranslationConfig) -> Strin
         }
 
         Ok(())
-    })
+    }))
 }
 
 
@@ -541,6 +753,11 @@ impl Translation {
             ])),
             loops: LoopContext::new(),
             zero_inits: RefCell::new(HashMap::new()),
+            alloca_storage: RefCell::new(None),
+            loop_jumps: RefCell::new(HashMap::new()),
+            impl_defined: RefCell::new(BTreeSet::new()),
+            cfg_time: Cell::new(Duration::new(0, 0)),
+            undroppable_records: HashSet::new(),
             comment_context,
             comment_store: RefCell::new(CommentStore::new()),
         }
@@ -562,6 +779,580 @@ impl Translation {
         } else { mk }
     }
 
+    /// Records are laid out as in C, raising their alignment where the C declaration asked for
+    /// more than the fields' natural alignment, and dropping all padding where it asked for
+    /// packed fields. The exporter only reports packing that lowers some field's alignment, so
+    /// the packings rejected here are those that change the layout.
+    fn mk_repr(
+        &self,
+        mk_: Builder,
+        manual_alignment: Option<u64>,
+        max_field_alignment: Option<u64>,
+    ) -> Result<Builder, String> {
+        match (manual_alignment, max_field_alignment) {
+            (None, None) => Ok(mk_.call_attr("repr", vec!["C"])),
+            (None, Some(1)) => Ok(mk_.call_attr("repr", vec!["C", "packed"])),
+            (None, Some(packing)) =>
+                Err(format!("Packing record fields to {} bytes is not supported", packing)),
+            (Some(_), Some(_)) =>
+                Err(format!("Records that are both packed and aligned are not supported")),
+            (Some(alignment), None) => {
+                self.features.borrow_mut().extend(vec!["repr_align", "attr_literals"]);
+                let alignment = Symbol::intern(&alignment.to_string());
+                let tokens: TokenStream = vec![
+                    Token::OpenDelim(DelimToken::Paren),
+                    Token::Ident(mk().ident("C")),
+                    Token::Comma,
+                    Token::Ident(mk().ident("align")),
+                    Token::OpenDelim(DelimToken::Paren),
+                    Token::Literal(token::Lit::Integer(alignment), None),
+                    Token::CloseDelim(DelimToken::Paren),
+                    Token::CloseDelim(DelimToken::Paren),
+                ].into_iter().collect();
+                Ok(mk_.prepared_attr(mk().attribute(AttrStyle::Outer, vec!["repr"], tokens)))
+            }
+        }
+    }
+
+    /// Extern declarations of symbols defined by a sibling translation unit are imported
+    /// from the module translated from that unit rather than declared in an `extern` block.
+    fn convert_module_import(&self, decl_id: CDeclId) -> Option<P<Item>> {
+        let prefix = self.tcfg.prefix_function_names.as_ref().map(String::as_str).unwrap_or("");
+
+        // Extern variables have static storage, so they have been given the test prefix, see
+        // `prefix_names`. The C symbol is the name without it.
+        let name = match self.ast_context[decl_id].kind {
+            CDeclKind::Function { body: None, ref name, .. } => name.as_str(),
+            CDeclKind::Variable { is_extern: true, is_defn: false, ref ident, .. }
+              if ident.starts_with(prefix) => &ident[prefix.len()..],
+            _ => return None,
+        };
+        let &(ref module, ref defined_name) = self.tcfg.extern_modules.get(name)?;
+        let local_name = self.renamer.borrow().get(&decl_id)?;
+        let rename = if local_name == *defined_name { None } else { Some(local_name) };
+
+        Some(mk().pub_().use_item(vec!["super", module.as_str(), defined_name.as_str()], rename))
+    }
+
+    /// The differential test harness of a function defined here, when its signature only
+    /// involves scalars and pointers to scalars, see `differential_tests`. Functions with other
+    /// signatures are `Unsupported`, with the reason why.
+    fn differential_harness(&self, decl_id: CDeclId) -> Result<Option<Fuzzability>, String> {
+        let (name, typ, parameters) = match self.ast_context[decl_id].kind {
+            CDeclKind::Function { is_extern: true, is_inline: false, body: Some(_), ref name, typ, ref parameters, .. } =>
+                (name, typ, parameters),
+            _ => return Ok(None),
+        };
+
+        // Definitions got the test prefix, see `prefix_names`
+        let prefix = self.tcfg.prefix_function_names.as_ref().map(String::as_str).unwrap_or("");
+        let c_name = &name[prefix.len()..];
+        if c_name == "main" {
+            return Ok(None)
+        }
+        let rust_name = self.renamer.borrow().get(&decl_id).expect("Functions should already be renamed");
+        let unsupported = |reason: String| Ok(Some(Fuzzability::Unsupported(c_name.to_string(), reason)));
+
+        let is_fuzzable = |type_id: CTypeId| match self.ast_context.resolve_type(type_id).kind {
+            CTypeKind::Char | CTypeKind::SChar | CTypeKind::UChar |
+            CTypeKind::Short | CTypeKind::UShort | CTypeKind::Int | CTypeKind::UInt |
+            CTypeKind::Long | CTypeKind::ULong | CTypeKind::LongLong | CTypeKind::ULongLong |
+            CTypeKind::Float | CTypeKind::Double => true,
+            _ => false,
+        };
+        let is_opaque = |type_id: CTypeId| match self.ast_context.resolve_type(type_id).kind {
+            CTypeKind::Void => true,
+            CTypeKind::Struct(decl_id) => match self.ast_context[decl_id].kind {
+                CDeclKind::Struct { fields: None, .. } => true,
+                _ => false,
+            },
+            _ => false,
+        };
+
+        let ret = match self.ast_context.resolve_type(typ).kind {
+            CTypeKind::Function(_, _, true, _) => return unsupported("it takes variadic arguments".to_string()),
+            CTypeKind::Function(ret, _, _, _) => ret,
+            ref k => return Err(format!("Type of function {:?} was not a function type, got {:?}", decl_id, k)),
+        };
+        let ret = match self.ast_context.resolve_type(ret.ctype).kind {
+            CTypeKind::Void => None,
+            _ if is_fuzzable(ret.ctype) => Some(self.convert_type(ret.ctype)?),
+            _ => return unsupported("it doesn't return a scalar".to_string()),
+        };
+
+        let mut args = vec![];
+        for param_id in parameters {
+            let (ident, typ) = match self.ast_context.index(*param_id).kind {
+                CDeclKind::Variable { ref ident, typ, .. } => (ident, typ),
+                _ => return Err(format!("Parameter is not variable declaration")),
+            };
+            let arg = match self.ast_context.resolve_type(typ.ctype).kind {
+                _ if is_fuzzable(typ.ctype) => HarnessArg::Scalar(self.convert_type(typ.ctype)?),
+                CTypeKind::Pointer(pointee) if is_fuzzable(pointee.ctype) => {
+                    let is_char = match self.ast_context.resolve_type(pointee.ctype).kind {
+                        CTypeKind::Char | CTypeKind::SChar | CTypeKind::UChar => true,
+                        _ => false,
+                    };
+                    HarnessArg::Buffer(self.convert_type(typ.ctype)?, self.convert_type(pointee.ctype)?, is_char)
+                }
+                CTypeKind::Pointer(pointee) if is_opaque(pointee.ctype) =>
+                    return unsupported(format!("parameter `{}` points to an opaque type", ident)),
+                _ => return unsupported(format!("parameter `{}` is neither a scalar nor a pointer to scalars", ident)),
+            };
+            args.push(arg);
+        }
+
+        Ok(Some(Fuzzability::Fuzzable(Harness { c_name: c_name.to_string(), rust_name, args, ret })))
+    }
+
+    /// `_Complex` numbers are laid out as an array of their real and imaginary parts, which
+    /// translates to a `#[repr(C)]` struct with `re` and `im` fields.
+    fn convert_complex_type(&self, name: &str, elt: P<Ty>) -> P<Item> {
+        let mut allows = LintAllows::new();
+        allows.type_name(name);
+
+        let fields = vec![
+            mk().pub_().struct_field("re", elt.clone()),
+            mk().pub_().struct_field("im", elt),
+        ];
+        let mk_ = self.mk_lint_allows(mk().pub_(), allows);
+        self.mk_cross_check(mk_, vec!["none"])
+            .call_attr("derive", vec!["Copy", "Clone"])
+            .call_attr("repr", vec!["C"])
+            .struct_item(name, fields)
+    }
+
+    /// A struct holding a variable that asks for more alignment than its type has, see
+    /// `aligned_variable`. It doesn't derive `Copy` since the variable is only accessed through
+    /// its `value` field, which might not be `Copy` itself.
+    fn convert_aligned_type(&self, name: &str, ty: P<Ty>, align: u64) -> P<Item> {
+        let mut allows = LintAllows::new();
+        allows.type_name(name);
+
+        let mk_ = self.mk_lint_allows(mk().pub_(), allows);
+        let mk_ = self.mk_cross_check(mk_, vec!["none"]);
+        self.mk_repr(mk_, Some(align), None)
+            .expect("Aligned records are always representable")
+            .struct_item(name, vec![mk().pub_().struct_field("value", ty)])
+    }
+
+    /// Attach an `#[allow(...)]` for the lints this item's names are expected to trigger
+    fn mk_lint_allows(&self, mk: Builder, allows: LintAllows) -> Builder {
+        if allows.is_empty() {
+            mk
+        } else {
+            mk.call_attr("allow", allows.into_vec())
+        }
+    }
+
+    /// Document the `pure`/`const` attributes of a C function and, if requested, pass them on to
+    /// the ownership analysis: neither kind of function can write through its pointer arguments.
+    fn mk_purity(&self, mk: Builder, is_pure: bool, is_const: bool) -> Builder {
+        let doc = if is_const {
+            " Declared `__attribute__((const))`: this function has no side effects and its result \
+             depends only on its arguments."
+        } else if is_pure {
+            " Declared `__attribute__((pure))`: this function has no side effects and its result \
+             depends only on its arguments and global memory."
+        } else {
+            return mk
+        };
+
+        let mk = mk.str_attr("doc", doc);
+        if self.tcfg.ownership_hints {
+            self.features.borrow_mut().insert("custom_attribute");
+            mk.single_attr("ownership_pure")
+        } else {
+            mk
+        }
+    }
+
+    /// Document a `restrict`-qualified return pointer and, if requested, pass it on to the
+    /// ownership analysis: the caller gets the only pointer to the returned data.
+    fn mk_restrict_return(&self, mk: Builder, return_type: Option<CQualTypeId>) -> Builder {
+        let is_restrict_pointer = match return_type {
+            Some(ret) => ret.qualifiers.is_restrict &&
+                self.ast_context.resolve_type(ret.ctype).kind.is_pointer(),
+            None => false,
+        };
+        if !is_restrict_pointer {
+            return mk
+        }
+
+        let mk = mk.str_attr("doc", " Returns a `restrict` pointer: the returned data is not \
+                                     reachable through any other pointer.");
+        // The analysis only has a use for the hint on pointers to mutable data
+        let points_to_const = match self.ast_context.resolve_type(return_type.unwrap().ctype).kind {
+            CTypeKind::Pointer(pointee) => pointee.qualifiers.is_const,
+            _ => false,
+        };
+        if self.tcfg.ownership_hints && !points_to_const {
+            self.features.borrow_mut().insert("custom_attribute");
+            mk.single_attr("ownership_restrict_return")
+        } else {
+            mk
+        }
+    }
+
+    /// `debug_assert!`s that each `restrict` pointer argument differs from every other pointer
+    /// argument, unless it is null. Equal pointers don't always break the promise made by
+    /// `restrict`, which only covers objects modified through one of them, but in practice such
+    /// calls are mistakes. Only the addresses are compared: the extents of the pointed-to buffers
+    /// aren't known here, so distinct pointers into overlapping buffers go unnoticed.
+    fn mk_restrict_checks(&self, pointer_args: &[(String, bool)]) -> Vec<Stmt> {
+        let mut checks = vec![];
+        for (i, &(ref lhs, lhs_restrict)) in pointer_args.iter().enumerate() {
+            for &(ref rhs, rhs_restrict) in &pointer_args[i + 1..] {
+                if !lhs_restrict && !rhs_restrict {
+                    continue
+                }
+
+                // debug_assert!(lhs.is_null() || lhs as usize != rhs as usize, "..");
+                let addr = |var: &str| mk().cast_expr(mk().ident_expr(var), mk().path_ty(vec!["usize"]));
+                let cond = mk().binary_expr(
+                    BinOpKind::Or,
+                    mk().method_call_expr(mk().ident_expr(lhs), "is_null", vec![] as Vec<P<Expr>>),
+                    mk().binary_expr(BinOpKind::Ne, addr(lhs), addr(rhs)),
+                );
+                let msg = format!("`restrict` pointer arguments `{}` and `{}` alias", lhs, rhs);
+                let tokens = vec![
+                    Token::interpolated(Nonterminal::NtExpr(cond)),
+                    Token::Comma,
+                    Token::interpolated(Nonterminal::NtExpr(mk().lit_expr(mk().str_lit(msg)))),
+                ].into_iter().collect::<TokenStream>();
+                checks.push(mk().semi_stmt(mk().mac_expr(mk().mac(vec!["debug_assert"], tokens))));
+            }
+        }
+        checks
+    }
+
+    /// The C destructor configured for a record, looked up by the record's type name, unless the
+    /// record can't get a `Drop` impl
+    fn record_destructor(&self, record_id: CRecordId) -> Option<&String> {
+        if self.undroppable_records.contains(&record_id) {
+            return None
+        }
+        let name = self.type_converter.borrow().resolve_decl_name(record_id)?;
+        self.tcfg.destructors.get(&name)
+    }
+
+    /// Find the records with a configured destructor whose values can't be dropped in its place.
+    /// Only `destructor(&local)` calls can become moves into `drop`: a call through any other
+    /// pointer would release the value a second time once its owner goes out of scope. Copies of
+    /// the value, which `Drop` turns into moves, and assignments over it, which would drop the
+    /// value being overwritten, rule the record out as well, as do locals that some path leaves
+    /// unreleased.
+    fn find_undroppable_records(&self) -> HashSet<CRecordId> {
+        let mut undroppable = HashSet::new();
+        if self.tcfg.destructors.is_empty() {
+            return undroppable
+        }
+
+        // Callees of the calls that `destructor_call_target` rewrites
+        let mut scoped_callees = HashSet::new();
+        for (_, expr) in &self.ast_context.c_exprs {
+            if let CExprKind::Call(_, func, ref args) = expr.kind {
+                if self.destructor_call_target(func, args).is_some() {
+                    if let CExprKind::ImplicitCast(_, fexp, _, _) = self.ast_context[func].kind {
+                        scoped_callees.insert(fexp);
+                    }
+                }
+            }
+        }
+
+        let destructor_records = self.destructor_records();
+        for (&expr_id, expr) in &self.ast_context.c_exprs {
+            match expr.kind {
+                CExprKind::DeclRef(_, decl_id) if !scoped_callees.contains(&expr_id) => {
+                    let name = match self.ast_context[decl_id].kind {
+                        CDeclKind::Function { ref name, .. } => name,
+                        _ => continue,
+                    };
+                    for (&record_id, destructor) in &destructor_records {
+                        if self.is_destructor_name(name, destructor) {
+                            undroppable.insert(record_id);
+                        }
+                    }
+                }
+                CExprKind::ImplicitCast(ty, _, CastKind::LValueToRValue, _) |
+                CExprKind::Binary(ty, BinOp::Assign, _, _, _, _) =>
+                    self.destructor_records_in(ty.ctype, &mut undroppable),
+                _ => {}
+            }
+        }
+
+        // `Drop` runs on every exit from a local's scope, early returns included, so the C has
+        // to release the local on every path as well
+        let released = self.released_locals();
+        for (_, stmt) in &self.ast_context.c_stmts {
+            if let CStmtKind::Decls(ref decls) = stmt.kind {
+                for &decl_id in decls {
+                    match self.ast_context[decl_id].kind {
+                        CDeclKind::Variable { is_static: false, is_extern: false, typ, .. }
+                            if !released.contains(&decl_id) =>
+                            self.destructor_records_in(typ.ctype, &mut undroppable),
+                        _ => {}
+                    }
+                }
+            }
+        }
+        undroppable
+    }
+
+    /// The locals that a `destructor(&local)` statement releases on every path from their
+    /// declaration: one later in the block declaring the local, with no jump out of the block,
+    /// or label to jump into it, in between
+    fn released_locals(&self) -> HashSet<CDeclId> {
+        let mut released = HashSet::new();
+        for (_, stmt) in &self.ast_context.c_stmts {
+            let stmts = match stmt.kind {
+                CStmtKind::Compound(ref stmts) => stmts,
+                _ => continue,
+            };
+            for (i, &decl_stmt) in stmts.iter().enumerate() {
+                let decls = match self.ast_context[decl_stmt].kind {
+                    CStmtKind::Decls(ref decls) => decls,
+                    _ => continue,
+                };
+                for &decl_id in decls {
+                    for &later in &stmts[i + 1..] {
+                        if self.released_local(later) == Some(decl_id) {
+                            released.insert(decl_id);
+                            break
+                        }
+                        if self.may_jump(later) {
+                            break
+                        }
+                    }
+                }
+            }
+        }
+        released
+    }
+
+    /// The local that the statement `destructor(&local);` releases
+    fn released_local(&self, stmt_id: CStmtId) -> Option<CDeclId> {
+        let mut expr_id = match self.ast_context[stmt_id].kind {
+            CStmtKind::Expr(expr_id) => expr_id,
+            _ => return None,
+        };
+        while let CExprKind::ExplicitCast(_, subexpr, CastKind::ToVoid, _) = self.ast_context[expr_id].kind {
+            expr_id = subexpr;
+        }
+        let local = match self.ast_context[expr_id].kind {
+            CExprKind::Call(_, func, ref args) => self.destructor_call_target(func, args)?,
+            _ => return None,
+        };
+        match self.ast_context[local].kind {
+            CExprKind::DeclRef(_, var_id) => Some(var_id),
+            _ => None,
+        }
+    }
+
+    /// Whether control may leave `stmt_id` other than by falling through to what follows, or
+    /// enter it other than at its start. Jumps that stay inside `stmt_id` count too.
+    fn may_jump(&self, stmt_id: CStmtId) -> bool {
+        DFExpr::new(&self.ast_context, stmt_id.into()).any(|id| match id {
+            SomeId::Stmt(stmt_id) => match self.ast_context[stmt_id].kind {
+                CStmtKind::Return(_) | CStmtKind::Goto(_) | CStmtKind::Break | CStmtKind::Continue |
+                CStmtKind::Label(_) | CStmtKind::Case(..) | CStmtKind::Default(_) => true,
+                _ => false,
+            },
+            _ => false,
+        })
+    }
+
+    /// The records with a configured destructor, along with that destructor
+    fn destructor_records(&self) -> HashMap<CRecordId, String> {
+        self.ast_context.c_decls.iter()
+            .filter(|&(_, decl)| match decl.kind { CDeclKind::Struct { .. } => true, _ => false })
+            .filter_map(|(&decl_id, _)| {
+                let name = self.type_converter.borrow().resolve_decl_name(decl_id)?;
+                let destructor = self.tcfg.destructors.get(&name)?;
+                Some((decl_id, destructor.clone()))
+            })
+            .collect()
+    }
+
+    /// Collect the records with a configured destructor that values of `type_id` contain
+    fn destructor_records_in(&self, type_id: CTypeId, records: &mut HashSet<CRecordId>) {
+        match self.ast_context.resolve_type(type_id).kind {
+            CTypeKind::Struct(record_id) => {
+                if self.record_destructor(record_id).is_some() {
+                    records.insert(record_id);
+                }
+                if let CDeclKind::Struct { fields: Some(ref fields), .. } = self.ast_context[record_id].kind {
+                    for &field_id in fields {
+                        if let CDeclKind::Field { typ, .. } = self.ast_context[field_id].kind {
+                            self.destructor_records_in(typ.ctype, records);
+                        }
+                    }
+                }
+            }
+            CTypeKind::ConstantArray(elt, _) => self.destructor_records_in(elt, records),
+            _ => {}
+        }
+    }
+
+    /// Whether `name` is the C function `destructor`, taking the test prefix into account
+    fn is_destructor_name(&self, name: &str, destructor: &str) -> bool {
+        let prefix = self.tcfg.prefix_function_names.as_ref().map(String::as_str).unwrap_or("");
+        name == destructor || (name.starts_with(prefix) && &name[prefix.len()..] == destructor)
+    }
+
+    /// Whether values of a record run a destructor when dropped, either their own or one of a
+    /// field's. Such records can't be `Copy`.
+    fn record_needs_drop(&self, record_id: CRecordId) -> bool {
+        if self.record_destructor(record_id).is_some() {
+            return true
+        }
+
+        match self.ast_context[record_id].kind {
+            CDeclKind::Struct { fields: Some(ref fields), .. } => fields.iter().any(|&field_id| {
+                match self.ast_context[field_id].kind {
+                    CDeclKind::Field { typ, .. } => self.type_needs_drop(typ.ctype),
+                    _ => false,
+                }
+            }),
+            _ => false,
+        }
+    }
+
+    fn type_needs_drop(&self, type_id: CTypeId) -> bool {
+        match self.ast_context.resolve_type(type_id).kind {
+            CTypeKind::Struct(record_id) => self.record_needs_drop(record_id),
+            CTypeKind::ConstantArray(elt, _) => self.type_needs_drop(elt),
+            _ => false,
+        }
+    }
+
+    /// Build `impl Drop` for a record with a configured destructor, which gets passed a pointer
+    /// to the record being dropped.
+    fn convert_destructor(&self, record_id: CRecordId) -> Option<P<Item>> {
+        match self.ast_context[record_id].kind {
+            CDeclKind::Struct { fields: Some(_), .. } => {}
+            _ => return None,
+        }
+        let destructor = self.record_destructor(record_id)?;
+
+        // Prefer the definition over prototypes, which may not carry the test prefix
+        let mut candidates: Vec<CDeclId> = self.ast_context.c_decls_top.iter().cloned()
+            .filter(|&decl_id| match self.ast_context[decl_id].kind {
+                CDeclKind::Function { ref name, .. } => self.is_destructor_name(name, destructor),
+                _ => false,
+            })
+            .collect();
+        candidates.sort_by_key(|&decl_id| match self.ast_context[decl_id].kind {
+            CDeclKind::Function { body: Some(_), .. } => 0,
+            _ => 1,
+        });
+
+        let fn_id = match candidates.first() {
+            Some(&fn_id) => fn_id,
+            None => {
+                let msg = format!("Could not find destructor '{}'", destructor);
+                translate_failure(&self.tcfg, &msg);
+                return None
+            }
+        };
+        let fn_name = self.renamer.borrow().get(&fn_id)?;
+        let record_name = self.type_converter.borrow().resolve_decl_name(record_id)?;
+
+        let call = mk().call_expr(mk().path_expr(vec![fn_name]), vec![mk().path_expr(vec!["self"])]);
+        let body = mk().block(vec![mk().expr_stmt(mk().block_expr(mk().unsafe_().block(vec![mk().expr_stmt(call)])))]);
+        let decl = mk().fn_decl(
+            vec![mk().self_arg(SelfKind::Region(None, Mutability::Mutable))],
+            FunctionRetTy::Default(DUMMY_SP),
+            false,
+        );
+        let drop_fn = mk().fn_impl_item("drop", decl, body);
+
+        Some(mk().trait_impl_item(vec!["Drop"], mk().path_ty(vec![record_name]), vec![drop_fn]))
+    }
+
+    /// Recognize `destructor(&local)` calls to a configured destructor. These are replaced by
+    /// moving the local into `drop`, so the compiler rules out a second release when the local
+    /// would otherwise go out of scope, as well as any later use.
+    fn destructor_call_target(&self, func: CExprId, args: &[CExprId]) -> Option<CExprId> {
+        if self.tcfg.destructors.is_empty() || args.len() != 1 {
+            return None
+        }
+
+        let callee = match self.ast_context[func].kind {
+            CExprKind::ImplicitCast(_, fexp, CastKind::FunctionToPointerDecay, _) =>
+                match self.ast_context[fexp].kind {
+                    CExprKind::DeclRef(_, decl_id) => decl_id,
+                    _ => return None,
+                },
+            _ => return None,
+        };
+        let callee_name = match self.ast_context[callee].kind {
+            CDeclKind::Function { ref name, .. } => name,
+            _ => return None,
+        };
+
+        let mut arg = args[0];
+        while let CExprKind::ImplicitCast(_, subexpr, _, _) = self.ast_context[arg].kind {
+            arg = subexpr;
+        }
+        let local = match self.ast_context[arg].kind {
+            CExprKind::Unary(_, c_ast::UnOp::AddressOf, subexpr) => subexpr,
+            _ => return None,
+        };
+        let (var_id, var_ty) = match self.ast_context[local].kind {
+            CExprKind::DeclRef(ty, var_id) => (var_id, ty),
+            _ => return None,
+        };
+        match self.ast_context[var_id].kind {
+            CDeclKind::Variable { is_static: false, is_extern: false, .. } => {}
+            _ => return None,
+        }
+
+        match self.ast_context.resolve_type(var_ty.ctype).kind {
+            CTypeKind::Struct(record_id) => {
+                let destructor = self.record_destructor(record_id)?;
+                if self.is_destructor_name(callee_name, destructor) { Some(local) } else { None }
+            }
+            _ => None,
+        }
+    }
+
+    /// Callers are warned when ignoring the result of a `warn_unused_result` function
+    fn mk_must_use(&self, mk: Builder, is_must_use: bool) -> Builder {
+        if is_must_use {
+            self.features.borrow_mut().insert("fn_must_use");
+            mk.single_attr("must_use")
+        } else {
+            mk
+        }
+    }
+
+    /// Naming lints triggered by a function definition, its arguments, or its locals
+    fn function_lint_allows(
+        &self,
+        name: &str,
+        arguments: &[(CDeclId, String, CQualTypeId)],
+        body: CStmtId,
+    ) -> LintAllows {
+        let mut allows = LintAllows::new();
+        allows.value_name(name);
+
+        for &(_, ref var, _) in arguments {
+            allows.value_name(var);
+        }
+
+        // Local statics become separate items which get their own attributes
+        for x in DFExpr::new(&self.ast_context, body.into()) {
+            if let SomeId::Decl(decl_id) = x {
+                if let CDeclKind::Variable { is_static: false, ref ident, .. } = self.ast_context[decl_id].kind {
+                    allows.value_name(ident);
+                }
+            }
+        }
+
+        allows
+    }
+
     fn convert_main(&self, main_id: CDeclId) -> Result<P<Item>, String> {
         if let CDeclKind::Function { ref parameters, typ, .. } = self.ast_context.index(main_id).kind {
 
@@ -785,7 +1576,13 @@ impl Translation {
 
     fn convert_decl(&self, toplevel: bool, decl_id: CDeclId) -> Result<ConvertedDecl, String> {
         let s = {
-            let decl_cmt = self.comment_context.borrow_mut().remove_decl_comment(decl_id);
+            let mut decl_cmt = self.comment_context.borrow_mut().remove_decl_comment(decl_id);
+            if self.tcfg.annotate_signatures {
+                let prefix = self.tcfg.prefix_function_names.as_ref().map(String::as_str);
+                if let Some(signature) = c_signature(decl_id, prefix, &self.ast_context) {
+                    decl_cmt.push(format!("// C: {}", signature));
+                }
+            }
             self.comment_store.borrow_mut().add_comment(decl_cmt)
         };
 
@@ -801,15 +1598,21 @@ impl Translation {
                 Ok(ConvertedDecl::ForeignItem(extern_item))
             }
 
-            CDeclKind::Struct { fields: Some(ref fields), .. } => {
+            CDeclKind::Struct { fields: Some(ref fields), manual_alignment, max_field_alignment, is_must_use, .. } => {
                 let name = self.type_converter.borrow().resolve_decl_name(decl_id).unwrap();
 
+                let mut allows = LintAllows::new();
+                allows.type_name(&name);
+
                 // Gather up all the field names and field types
                 let mut field_entries = vec![];
                 for &x in fields {
                     match self.ast_context.index(x).kind {
-                        CDeclKind::Field { ref name, typ } => {
+                        CDeclKind::Field { ref name, typ, .. } => {
+                            self.check_vector_layout(x, typ.ctype)?;
+                            self.check_field_alignment(x, typ.ctype)?;
                             let name = self.type_converter.borrow_mut().declare_field_name(decl_id, x, name);
+                            allows.value_name(&name);
                             let typ = self.convert_type(typ.ctype)?;
                             field_entries.push(mk().span(s).pub_().struct_field(name, typ))
                         }
@@ -817,21 +1620,31 @@ impl Translation {
                     }
                 }
 
-                Ok(ConvertedDecl::Item(self.mk_cross_check(mk().span(s).pub_(), vec!["none"])
-                    .call_attr("derive", vec!["Copy", "Clone"])
-                    .call_attr("repr", vec!["C"])
+                // Records owning a resource released by `Drop` must not be duplicated
+                let derives = if self.record_needs_drop(decl_id) { vec![] } else { vec!["Copy", "Clone"] };
+                let mk_ = self.mk_lint_allows(mk().span(s).pub_(), allows);
+                let mk_ = self.mk_cross_check(mk_, vec!["none"]);
+                let mk_ = if derives.is_empty() { mk_ } else { mk_.call_attr("derive", derives) };
+                let mk_ = if is_must_use { mk_.single_attr("must_use") } else { mk_ };
+                Ok(ConvertedDecl::Item(self.mk_repr(mk_, manual_alignment, max_field_alignment)?
                     .struct_item(name, field_entries)))
             }
 
-            CDeclKind::Union { fields: Some(ref fields), .. } => {
+            CDeclKind::Union { fields: Some(ref fields), manual_alignment, max_field_alignment, is_must_use, .. } => {
                 let name = self.type_converter.borrow().resolve_decl_name(decl_id).unwrap();
 
+                let mut allows = LintAllows::new();
+                allows.type_name(&name);
+
                 let mut field_syns = vec![];
                 for &x in fields {
                     let field_decl = self.ast_context.index(x);
                     match field_decl.kind {
-                        CDeclKind::Field { ref name, typ } => {
+                        CDeclKind::Field { ref name, typ, .. } => {
+                            self.check_vector_layout(x, typ.ctype)?;
+                            self.check_field_alignment(x, typ.ctype)?;
                             let name = self.type_converter.borrow_mut().declare_field_name(decl_id, x, name);
+                            allows.value_name(&name);
                             let typ = self.convert_type(typ.ctype)?;
                             field_syns.push(mk().span(s).struct_field(name, typ))
                         }
@@ -839,26 +1652,39 @@ impl Translation {
                     }
                 }
 
+                let mk_ = self.mk_lint_allows(mk().span(s).pub_(), allows);
+                let mk_ = self.mk_cross_check(mk_, vec!["none"])
+                    .call_attr("derive", vec!["Copy", "Clone"]);
+                let mk_ = if is_must_use { mk_.single_attr("must_use") } else { mk_ };
+                let mk_ = self.mk_repr(mk_, manual_alignment, max_field_alignment)?;
                 Ok(if field_syns.is_empty() {
                     // Empty unions are a GNU extension, but Rust doesn't allow empty unions.
-                    ConvertedDecl::Item(self.mk_cross_check(mk().span(s).pub_(), vec!["none"])
-                        .call_attr("derive", vec!["Copy", "Clone"])
-                        .call_attr("repr", vec!["C"])
-                        .struct_item(name, vec![]))
+                    ConvertedDecl::Item(mk_.struct_item(name, vec![]))
                 } else {
-                    ConvertedDecl::Item(self.mk_cross_check(mk().span(s).pub_(), vec!["none"])
-                        .call_attr("derive", vec!["Copy", "Clone"])
-                        .call_attr("repr", vec!["C"])
-                        .union_item(name, field_syns))
+                    ConvertedDecl::Item(mk_.union_item(name, field_syns))
                 })
             }
 
             CDeclKind::Field { .. } => Err(format!("Field declarations should be handled inside structs/unions")),
 
-            CDeclKind::Enum { integral_type: Some(integral_type), .. } => {
+            CDeclKind::Enum { integral_type: Some(integral_type), ref variants, .. } => {
+                // Enumerators that fit in an `int` are portable whatever type holds them
+                let exceeds_int = variants.iter().any(|&variant_id| match self.ast_context[variant_id].kind {
+                    CDeclKind::EnumConstant { value: ConstIntExpr::I(v), .. } =>
+                        v < i32::min_value() as i64 || v > i32::max_value() as i64,
+                    CDeclKind::EnumConstant { value: ConstIntExpr::U(v), .. } => v > i32::max_value() as u64,
+                    _ => false,
+                });
+                if exceeds_int {
+                    self.note_impl_defined(self.ast_context[decl_id].loc, ImplDefined::EnumRepresentation);
+                }
+
                 let enum_name = &self.type_converter.borrow().resolve_decl_name(decl_id).expect("Enums should already be renamed");
                 let ty = self.convert_type(integral_type.ctype)?;
-                Ok(ConvertedDecl::Item(self.mk_cross_check(mk().span(s).pub_(), vec!["none"])
+                let mut allows = LintAllows::new();
+                allows.type_name(enum_name);
+                let mk_ = self.mk_lint_allows(mk().span(s).pub_(), allows);
+                Ok(ConvertedDecl::Item(self.mk_cross_check(mk_, vec!["none"])
                     .type_item(enum_name, ty)))
             },
 
@@ -872,11 +1698,33 @@ impl Translation {
                     ConstIntExpr::U(value) => mk().lit_expr(mk().int_lit(value as u128, LitIntType::Unsuffixed)),
                 };
 
-                Ok(ConvertedDecl::Item(mk().span(s).pub_().const_item(name, ty, val)))
+                let mut allows = LintAllows::new();
+                allows.global_name(&name);
+                Ok(ConvertedDecl::Item(self.mk_lint_allows(mk().span(s).pub_(), allows)
+                    .const_item(name, ty, val)))
+            }
+
+            CDeclKind::StaticAssert { assert_expr, ref message, ref condition, ref file } => {
+                let line = self.ast_context.c_decls[&decl_id].loc.as_ref().map_or(0, |loc| loc.line);
+                let mut msg = format!("{}:{}: static assertion `{}` failed", file, line, condition);
+                if let Some(ref message) = *message {
+                    msg.push_str(&format!(": {}", message));
+                }
+
+                // The array only has room for the message when the condition holds, otherwise
+                // rustc rejects the constant and quotes the message in its error
+                let cond = self.convert_condition(true, assert_expr, true)?.to_expr();
+                let len = mk().cast_expr(cond, mk().path_ty(vec!["usize"]));
+                let ty = mk().array_ty(mk().ref_lt_ty("'static", mk().path_ty(vec!["str"])), len);
+                let val = mk().array_expr(vec![mk().lit_expr(mk().str_lit(msg))]);
+
+                let name = self.renamer.borrow_mut().pick_name("C2RUST_STATIC_ASSERT");
+                Ok(ConvertedDecl::Item(mk().span(s).call_attr("allow", vec!["dead_code"])
+                    .const_item(name, ty, val)))
             }
 
             CDeclKind::Function { .. } if !toplevel => Err(format!("Function declarations must be top-level")),
-            CDeclKind::Function { is_extern, is_inline, typ, ref name, ref parameters, body, .. } => {
+            CDeclKind::Function { is_extern, is_inline, is_pure, is_const, is_must_use, ref section, ref alias, typ, ref name, ref parameters, body, .. } => {
                 let new_name = &self.renamer.borrow().get(&decl_id).expect("Functions should already be renamed");
 
 
@@ -894,27 +1742,45 @@ impl Translation {
                     }
                 }
 
+                if let (true, Some(ret)) = (is_extern, ret) {
+                    self.check_vector_layout(decl_id, ret.ctype)?;
+                }
+
                 let is_main = self.ast_context.c_main == Some(decl_id);
 
-                self.convert_function(s, is_extern, is_inline, is_main, is_var, new_name, name, &args, ret, body)
+                self.convert_function(s, is_extern, is_inline, is_main, is_var, is_pure, is_const, is_must_use, section.as_ref().map(String::as_str), alias.as_ref().map(String::as_str), new_name, name, &args, ret, body)
             },
 
-            CDeclKind::Typedef { ref typ, .. } => {
+            CDeclKind::Typedef { ref typ, mode_width, .. } => {
                 let new_name = &self.type_converter.borrow().resolve_decl_name(decl_id).unwrap();
 
-                let ty = self.convert_type(typ.ctype)?;
-                Ok(ConvertedDecl::Item(mk().span(s).pub_().type_item(new_name, ty)))
+                // Integers with an explicit mode have a fixed width, unlike the C type clang
+                // picked for them
+                let ty = match mode_width {
+                    Some(width) => {
+                        let signed = !self.ast_context.resolve_type(typ.ctype).kind.is_unsigned_integral_type();
+                        mk().path_ty(vec![format!("{}{}", if signed { "i" } else { "u" }, width)])
+                    }
+                    None => self.convert_type(typ.ctype)?,
+                };
+                let mut allows = LintAllows::new();
+                allows.type_name(new_name);
+                Ok(ConvertedDecl::Item(self.mk_lint_allows(mk().span(s).pub_(), allows)
+                    .type_item(new_name, ty)))
             },
 
             // Extern variable without intializer (definition elsewhere)
-            CDeclKind::Variable { is_extern: true, is_static, is_defn: false, ref ident, initializer, typ } => {
+            CDeclKind::Variable { is_extern: true, is_static, is_defn: false, ref ident, initializer, typ, .. } => {
                 assert!(is_static, "An extern variable must be static");
                 assert!(initializer.is_none(), "An extern variable that isn't a definition can't have an initializer");
 
                 let new_name = self.renamer.borrow().get(&decl_id).expect("Variables should already be renamed");
                 let (ty, mutbl, _) = self.convert_variable(None, typ, is_static)?;
 
-                let extern_item = mk_linkage(true, &new_name, ident)
+                let mut allows = LintAllows::new();
+                allows.global_name(&new_name);
+
+                let extern_item = self.mk_lint_allows(mk_linkage(true, &new_name, ident), allows)
                     .span(s)
                     .set_mutbl(mutbl)
                     .foreign_static(&new_name, ty);
@@ -931,13 +1797,17 @@ impl Translation {
 
                 // Conservatively assume that some aspect of the initializer is unsafe
                 let mut init = init?;
-                init.stmts.push(mk().expr_stmt(init.val));
+                let (ty, val) = self.aligned_storage(decl_id, ty, init.val);
+                init.stmts.push(mk().expr_stmt(val));
                 let init = mk().unsafe_().block(init.stmts);
                 let init = mk().block_expr(init);
 
                 // Force mutability due to the potential for raw pointers occuring in the type
 
-                Ok(ConvertedDecl::Item(mk_linkage(false, new_name, ident)
+                let mut allows = LintAllows::new();
+                allows.global_name(new_name);
+
+                Ok(ConvertedDecl::Item(self.mk_lint_allows(mk_linkage(false, new_name, ident), allows)
                     .span(s)
                     .vis(Visibility::Public)
                     .abi(Abi::C)
@@ -952,12 +1822,17 @@ impl Translation {
 
                 // Conservatively assume that some aspect of the initializer is unsafe
                 let mut init = init?;
-                init.stmts.push(mk().expr_stmt(init.val));
+                let (ty, val) = self.aligned_storage(decl_id, ty, init.val);
+                init.stmts.push(mk().expr_stmt(val));
                 let init = mk().unsafe_().block(init.stmts);
                 let init = mk().block_expr(init);
 
+                let mut allows = LintAllows::new();
+                allows.global_name(new_name);
+
                 // Force mutability due to the potential for raw pointers occurring in the type
-                Ok(ConvertedDecl::Item(mk().span(s).mutbl().static_item(new_name, ty, init)))
+                Ok(ConvertedDecl::Item(self.mk_lint_allows(mk().span(s), allows)
+                    .mutbl().static_item(new_name, ty, init)))
             }
 
             CDeclKind::Variable { .. } => Err(format!("This should be handled in 'convert_decl_stmt'")),
@@ -973,6 +1848,11 @@ impl Translation {
         is_inline: bool,
         is_main: bool,
         is_variadic: bool,
+        is_pure: bool,
+        is_const: bool,
+        is_must_use: bool,
+        section: Option<&str>,
+        alias: Option<&str>,
         new_name: &str,
         name: &str,
         arguments: &[(CDeclId, String, CQualTypeId)],
@@ -981,11 +1861,24 @@ impl Translation {
     ) -> Result<ConvertedDecl, String> {
         self.with_scope(|| {
             let mut args: Vec<Arg> = vec![];
+            // `transparent_union` arguments, received as their first field and rebuilt into the
+            // union at the top of the body
+            let mut transparent_args: Vec<Stmt> = vec![];
+            // Named pointer arguments, and whether each is `restrict`, for `restrict_checks`
+            let mut pointer_args: Vec<(String, bool)> = vec![];
 
             for &(decl_id, ref var, typ) in arguments {
+                if is_extern {
+                    self.check_vector_layout(decl_id, typ.ctype)?;
+                }
 
+                let transparent_member = self.ast_context.transparent_union_member(typ.ctype);
 
                 let (ty, mutbl, _) = self.convert_variable(None, typ, false)?;
+                let ty = match transparent_member {
+                    Some((_, member)) => self.convert_type(member.ctype)?,
+                    None => ty,
+                };
 
                 let pat = if var.is_empty() {
                     mk().wild_pat()
@@ -997,7 +1890,30 @@ impl Translation {
                         .insert(decl_id, var.as_str())
                         .expect(&format!("Failed to insert argument '{}' while converting '{}'", var, name));
 
-                    mk().set_mutbl(mutbl).ident_pat(new_var)
+                    // Function pointers become `Option<fn>`, which has no address to compare
+                    if transparent_member.is_none() &&
+                        self.ast_context.resolve_type(typ.ctype).kind.is_pointer() &&
+                        !self.is_function_pointer(typ.ctype) {
+                        pointer_args.push((new_var.clone(), typ.qualifiers.is_restrict));
+                    }
+
+                    match transparent_member {
+                        Some((field_id, _)) => {
+                            // let mut arg: U = U { field: arg };
+                            let union_ty = self.convert_type(typ.ctype)?;
+                            let union_id = self.ast_context.parents[&field_id];
+                            let union_name = self.type_converter.borrow().resolve_decl_name(union_id).expect("required union name");
+                            let field_name = self.type_converter.borrow().resolve_field_name(Some(union_id), field_id).expect("field name required");
+                            let init = mk().struct_expr(mk().path(vec![union_name]), vec![mk().field(field_name, mk().ident_expr(&new_var))]);
+                            transparent_args.push(mk().local_stmt(P(mk().local(
+                                mk().set_mutbl(mutbl).ident_pat(&new_var),
+                                Some(union_ty),
+                                Some(init),
+                            ))));
+                            mk().ident_pat(new_var)
+                        }
+                        None => mk().set_mutbl(mutbl).ident_pat(new_var),
+                    }
                 };
 
                 args.push(mk().arg(ty, pat))
@@ -1029,7 +1945,10 @@ impl Translation {
                     _ => cfg::ImplicitReturnType::Void,
                 };
 
-                let mut body_stmts = vec![];
+                let mut body_stmts = transparent_args;
+                if self.tcfg.restrict_checks {
+                    body_stmts.append(&mut self.mk_restrict_checks(&pointer_args));
+                }
                 for &(_, _, typ) in arguments {
                     body_stmts.append(&mut self.compute_variable_array_sizes(typ.ctype)?);
                 }
@@ -1039,6 +1958,15 @@ impl Translation {
                     _ => panic!("function body expects to be a compound statement"),
                 };
                 body_stmts.append(&mut self.convert_function_body(name, body_ids, ret)?);
+
+                if let Some(threshold) = self.tcfg.split_functions_over {
+                    if split_functions::count_stmts(&body_stmts) > threshold {
+                        let mk_helper = || self.mk_cross_check(mk(), vec!["none"]);
+                        body_stmts = split_functions::split_function_body(
+                            new_name, &decl.inputs, body_stmts, threshold, &mk_helper);
+                    }
+                }
+
                 let block = stmts_block(body_stmts);
 
                 // Only add linkage attributes if the function is `extern`
@@ -1051,12 +1979,67 @@ impl Translation {
                 } else {
                     mk().abi(Abi::C)
                 };
+                let mk_ = self.mk_lint_allows(mk_, self.function_lint_allows(new_name, arguments, body));
+                let mk_ = self.mk_purity(mk_, is_pure, is_const);
+                let mk_ = self.mk_restrict_return(mk_, return_type);
+                let mk_ = self.mk_must_use(mk_, is_must_use);
+                let mk_ = match section {
+                    Some(section) => mk_.str_attr("link_section", section),
+                    None => mk_,
+                };
+
+                Ok(ConvertedDecl::Item(mk_.span(span).unsafe_().fn_item(new_name, decl, block)))
+            } else if let Some(target) = alias {
+                // Translating an alias, which gets a definition forwarding to its target
+
+                if is_variadic {
+                    return Err(format!("Cannot translate variadic alias {}", name))
+                }
+                let target_name = self.alias_target_name(target)?;
+
+                let mut allows = LintAllows::new();
+                allows.value_name(new_name);
+
+                let mut inputs = vec![];
+                let mut call_args = vec![];
+                for arg in &decl.inputs {
+                    let var = match arg.pat.node {
+                        PatKind::Ident(_, ident, _) => ident.node.name.to_string(),
+                        _ => self.renamer.borrow_mut().pick_name("arg"),
+                    };
+                    allows.value_name(&var);
+                    call_args.push(mk().ident_expr(&var));
+                    inputs.push(mk().arg(arg.ty.clone(), mk().ident_pat(var)));
+                }
+                let decl = mk().fn_decl(inputs, decl.output.clone(), false);
+                let call = mk().call_expr(mk().path_expr(vec![target_name]), call_args);
+                let block = mk().block(vec![mk().expr_stmt(call)]);
+
+                let mk_ = if is_extern {
+                    mk_linkage(false, new_name, name)
+                        .abi(Abi::C)
+                        .vis(Visibility::Public)
+                } else {
+                    mk().abi(Abi::C)
+                };
+                let mk_ = self.mk_lint_allows(mk_, allows);
+                let mk_ = self.mk_purity(mk_, is_pure, is_const);
+                let mk_ = self.mk_must_use(mk_, is_must_use);
 
                 Ok(ConvertedDecl::Item(mk_.span(span).unsafe_().fn_item(new_name, decl, block)))
             } else {
                 // Translating an extern function declaration
 
-                let function_decl = mk_linkage(true, new_name, name)
+                let mut allows = LintAllows::new();
+                allows.value_name(new_name);
+                for &(_, ref var, _) in arguments {
+                    allows.value_name(var);
+                }
+
+                let mk_ = self.mk_lint_allows(mk_linkage(true, new_name, name), allows);
+                let mk_ = self.mk_purity(mk_, is_pure, is_const);
+                let mk_ = self.mk_restrict_return(mk_, return_type);
+                let function_decl = self.mk_must_use(mk_, is_must_use)
                     .span(span)
                     .foreign_fn(new_name, decl);
 
@@ -1065,6 +2048,17 @@ impl Translation {
         })
     }
 
+    /// Name of the translated function that `__attribute__((alias(target)))` refers to
+    fn alias_target_name(&self, target: &str) -> Result<String, String> {
+        self.ast_context.c_decls.iter()
+            .find(|&(_, decl)| match decl.kind {
+                CDeclKind::Function { ref name, body: Some(_), .. } => name == target,
+                _ => false,
+            })
+            .and_then(|(decl_id, _)| self.renamer.borrow().get(decl_id))
+            .ok_or_else(|| format!("Alias target {} is not defined in this translation unit", target))
+    }
+
     fn convert_function_body(
         &self,
         name: &str,
@@ -1072,9 +2066,15 @@ impl Translation {
         ret: cfg::ImplicitReturnType,
     ) -> Result<Vec<Stmt>, String> {
 
+        let outer_alloca_storage = self.alloca_storage.borrow_mut().take();
+
+        let loop_jumps = if self.tcfg.reloop_cfgs { None } else { self.loop_jumps(body_ids) };
+        *self.loop_jumps.borrow_mut() = loop_jumps.unwrap_or_default();
+
         // Function body scope
-        self.with_scope(|| {
-            if self.tcfg.reloop_cfgs {
+        let stmts = self.with_scope(|| {
+            if self.tcfg.reloop_cfgs || self.needs_cfg(body_ids) {
+                let cfg_start = Instant::now();
                 let (graph, store) = cfg::Cfg::from_stmts(self, body_ids, ret)?;
 
                 if self.tcfg.dump_function_cfgs {
@@ -1092,13 +2092,15 @@ impl Translation {
                         .expect("Failed to write CFG .json file");
                 }
 
-                let (lifted_stmts, relooped) = cfg::relooper::reloop(
+                let (lifted_stmts, relooped) = cfg::structure(
                     graph,
                     store,
+                    self.tcfg.cfg_structuring,
                     self.tcfg.simplify_structures,
                     self.tcfg.use_c_loop_info,
                     self.tcfg.use_c_multiple_info,
                 );
+                self.cfg_time.set(self.cfg_time.get() + cfg_start.elapsed());
 
                 if self.tcfg.dump_structures {
                     eprintln!("Relooped structures:");
@@ -1142,9 +2144,180 @@ impl Translation {
                 }
                 Ok(res)
             }
+        });
+
+        let alloca_storage = mem::replace(&mut *self.alloca_storage.borrow_mut(), outer_alloca_storage);
+        let mut stmts = stmts?;
+        if let Some(storage) = alloca_storage {
+            // let mut alloca_storage: Vec<Vec<u8>> = Vec::new();
+            let u8_vec = |ty| mk().path_ty(vec![mk().path_segment_with_params("Vec", mk().angle_bracketed_param_types(vec![ty]))]);
+            let ty = u8_vec(u8_vec(mk().path_ty(vec!["u8"])));
+            let init = mk().call_expr(mk().path_expr(vec!["Vec", "new"]), vec![] as Vec<P<Expr>>);
+            let local = mk().local(mk().mutbl().ident_pat(storage), Some(ty), Some(init));
+            stmts.insert(0, mk().local_stmt(P(local)));
+        }
+        Ok(stmts)
+    }
+
+    /// Whether the statements jump around in ways only the control-flow graph structuring can
+    /// express. Direct translation maps each C loop onto a Rust loop, which can't be entered
+    /// through a label in the middle of its body. The `goto`s and labels of `loop_jumps` are
+    /// translated directly.
+    fn needs_cfg(&self, stmt_ids: &[CStmtId]) -> bool {
+        stmt_ids.iter().any(|&stmt_id| match self.ast_context[stmt_id].kind {
+            CStmtKind::Goto(label) => !self.loop_jumps.borrow().contains_key(&label),
+            CStmtKind::Label(sub) =>
+                !self.loop_jumps.borrow().contains_key(&stmt_id) || self.needs_cfg(&[sub]),
+            CStmtKind::Switch { .. } => true,
+            CStmtKind::Compound(ref stmts) => self.needs_cfg(stmts),
+            CStmtKind::If { true_variant, false_variant, .. } =>
+                self.needs_cfg(&[true_variant]) || false_variant.map_or(false, |s| self.needs_cfg(&[s])),
+            CStmtKind::While { body, .. } | CStmtKind::DoWhile { body, .. } => self.needs_cfg(&[body]),
+            CStmtKind::ForLoop { init, body, .. } =>
+                init.map_or(false, |s| self.needs_cfg(&[s])) || self.needs_cfg(&[body]),
+            _ => false,
         })
     }
 
+    /// Find the labels that `goto`s only use to leave an enclosing loop or to start its next
+    /// iteration, so that the `goto`s can become `break` and `continue` in place of structuring
+    /// the function from its control-flow graph:
+    ///
+    ///   * a label on the statement right after a loop is a `break` from it
+    ///   * an empty label ending a loop body is a `continue` of it
+    ///   * a label on a `while` loop itself is a `continue` of it
+    ///
+    /// Returns `None` if any label is of another kind, or any `goto` jumps to its label from
+    /// outside the loop.
+    fn loop_jumps(&self, stmt_ids: &[CStmtId]) -> Option<HashMap<CLabelId, LoopJump>> {
+        let mut jumps = HashMap::new();
+        let mut ambiguous = HashSet::new();
+        self.collect_loop_labels(stmt_ids, &mut jumps, &mut ambiguous);
+        for label in ambiguous {
+            jumps.remove(&label);
+        }
+
+        if self.gotos_leave_loops(stmt_ids, &jumps, &mut vec![]) { Some(jumps) } else { None }
+    }
+
+    fn collect_loop_labels(
+        &self,
+        stmt_ids: &[CStmtId],
+        jumps: &mut HashMap<CLabelId, LoopJump>,
+        ambiguous: &mut HashSet<CLabelId>,
+    ) {
+        fn add(label: CLabelId, jump: LoopJump, jumps: &mut HashMap<CLabelId, LoopJump>,
+               ambiguous: &mut HashSet<CLabelId>) {
+            if jumps.insert(label, jump).is_some() {
+                ambiguous.insert(label);
+            }
+        }
+
+        for (i, &stmt_id) in stmt_ids.iter().enumerate() {
+            // Look through labels on the statement itself
+            let mut inner = stmt_id;
+            while let CStmtKind::Label(sub) = self.ast_context[inner].kind {
+                if let CStmtKind::While { body, .. } = self.ast_context[sub].kind {
+                    add(inner, LoopJump::Continue(body), jumps, ambiguous);
+                }
+                inner = sub;
+            }
+
+            match self.ast_context[inner].kind {
+                CStmtKind::While { body, .. } | CStmtKind::DoWhile { body, .. } |
+                CStmtKind::ForLoop { body, .. } => {
+                    if let Some(&next) = stmt_ids.get(i + 1) {
+                        if let CStmtKind::Label(_) = self.ast_context[next].kind {
+                            add(next, LoopJump::Break(body), jumps, ambiguous);
+                        }
+                    }
+                    if let CStmtKind::Compound(ref stmts) = self.ast_context[body].kind {
+                        if let Some(&last) = stmts.last() {
+                            if let CStmtKind::Label(sub) = self.ast_context[last].kind {
+                                if let CStmtKind::Empty = self.ast_context[sub].kind {
+                                    add(last, LoopJump::Continue(body), jumps, ambiguous);
+                                }
+                            }
+                        }
+                    }
+                    self.collect_loop_labels(&[body], jumps, ambiguous);
+                }
+                CStmtKind::Compound(ref stmts) => self.collect_loop_labels(stmts, jumps, ambiguous),
+                CStmtKind::If { true_variant, false_variant, .. } => {
+                    self.collect_loop_labels(&[true_variant], jumps, ambiguous);
+                    if let Some(false_variant) = false_variant {
+                        self.collect_loop_labels(&[false_variant], jumps, ambiguous);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Whether every label is one of `jumps`, and every `goto` is inside the loop its label
+    /// leaves or continues. `enclosing` holds the bodies of the loops around `stmt_ids`.
+    fn gotos_leave_loops(
+        &self,
+        stmt_ids: &[CStmtId],
+        jumps: &HashMap<CLabelId, LoopJump>,
+        enclosing: &mut Vec<CStmtId>,
+    ) -> bool {
+        stmt_ids.iter().all(|&stmt_id| match self.ast_context[stmt_id].kind {
+            CStmtKind::Goto(label) =>
+                jumps.get(&label).map_or(false, |jump| enclosing.contains(&jump.loop_body())),
+            CStmtKind::Label(sub) =>
+                jumps.contains_key(&stmt_id) && self.gotos_leave_loops(&[sub], jumps, enclosing),
+            CStmtKind::Switch { .. } => false,
+            CStmtKind::Compound(ref stmts) => self.gotos_leave_loops(stmts, jumps, enclosing),
+            CStmtKind::If { true_variant, false_variant, .. } =>
+                self.gotos_leave_loops(&[true_variant], jumps, enclosing) &&
+                    false_variant.map_or(true, |s| self.gotos_leave_loops(&[s], jumps, enclosing)),
+            CStmtKind::While { body, .. } | CStmtKind::DoWhile { body, .. } |
+            CStmtKind::ForLoop { init: None, body, .. } => {
+                enclosing.push(body);
+                let ok = self.gotos_leave_loops(&[body], jumps, enclosing);
+                enclosing.pop();
+                ok
+            }
+            CStmtKind::ForLoop { init: Some(init), body, .. } => {
+                if !self.gotos_leave_loops(&[init], jumps, enclosing) {
+                    return false;
+                }
+                enclosing.push(body);
+                let ok = self.gotos_leave_loops(&[body], jumps, enclosing);
+                enclosing.pop();
+                ok
+            }
+            _ => true,
+        })
+    }
+
+    /// `break` out of `loop_`
+    fn convert_break(&self, span: Span, mut loop_: RefMut<Loop>) -> Stmt {
+        loop_.has_break = true;
+        let loop_label = loop_.get_or_create_label(&self.loops).to_owned();
+        mk().span(span).expr_stmt(mk().break_expr(Some(loop_label)))
+    }
+
+    /// `continue` with the next iteration of `loop_`
+    fn convert_continue(&self, span: Span, mut loop_: RefMut<Loop>) -> Stmt {
+        loop_.has_continue = true;
+        match loop_.loop_type {
+            LoopType::While => {
+                // We can translate C continue in a while loop
+                // directly to Rust's continue
+                let loop_label = loop_.get_or_create_label(&self.loops).to_owned();
+                mk().span(span).expr_stmt(mk().continue_expr(Some(loop_label)))
+            },
+            _ => {
+                // We translate all other C continue statements
+                // to a break from the inner body loop
+                let body_label = loop_.get_or_create_body_label(&self.loops).to_owned();
+                mk().span(span).expr_stmt(mk().break_expr(Some(body_label)))
+            },
+        }
+    }
+
     fn convert_stmt(&self, stmt_id: CStmtId) -> Result<Vec<Stmt>, String> {
         let s = {
             let stmt_cmt = self.comment_context.borrow_mut().remove_stmt_comment(stmt_id);
@@ -1187,31 +2360,22 @@ impl Translation {
 
             CStmtKind::Expr(expr) => Ok(self.convert_expr(ExprUse::Unused, expr, false)?.stmts),
 
-            CStmtKind::Break => {
-                let mut loop_ = self.loops.current_loop_mut();
-                loop_.has_break = true;
-                let loop_label = loop_.get_or_create_label(&self.loops).to_owned();
-                Ok(vec![mk().span(s).expr_stmt(mk().break_expr(Some(loop_label)))])
-            },
+            CStmtKind::Break => Ok(vec![self.convert_break(s, self.loops.current_loop_mut())]),
 
-            CStmtKind::Continue => {
-                let mut loop_ = self.loops.current_loop_mut();
-                loop_.has_continue = true;
-                match loop_.loop_type {
-                    LoopType::While => {
-                        // We can translate C continue in a while loop
-                        // directly to Rust's continue
-                        let loop_label = loop_.get_or_create_label(&self.loops).to_owned();
-                        Ok(vec![mk().span(s).expr_stmt(mk().continue_expr(Some(loop_label)))])
-                    },
-                    _ => {
-                        // We translate all other C continue statements
-                        // to a break from the inner body loop
-                        let body_label = loop_.get_or_create_body_label(&self.loops).to_owned();
-                        Ok(vec![mk().span(s).expr_stmt(mk().break_expr(Some(body_label)))])
-                    },
+            CStmtKind::Continue => Ok(vec![self.convert_continue(s, self.loops.current_loop_mut())]),
+
+            // Only labels and `goto`s found by `loop_jumps` get here, the others go through the
+            // control-flow graph
+            CStmtKind::Label(sub) => self.convert_stmt(sub),
+
+            CStmtKind::Goto(label) => {
+                let jump = self.loop_jumps.borrow().get(&label).cloned()
+                    .ok_or_else(|| format!("Unexpected goto outside of a control-flow graph"))?;
+                match jump {
+                    LoopJump::Break(body) => Ok(vec![self.convert_break(s, self.loops.loop_with_body_mut(body))]),
+                    LoopJump::Continue(body) => Ok(vec![self.convert_continue(s, self.loops.loop_with_body_mut(body))]),
                 }
-            },
+            }
 
             CStmtKind::Asm{is_volatile, ref asm, ref inputs, ref outputs, ref clobbers} => {
                 self.convert_asm(s, is_volatile, asm, inputs, outputs, clobbers)
@@ -1346,7 +2510,7 @@ impl Translation {
     fn convert_while_stmt(&self, span: Span, cond_id: CExprId, body_id: CStmtId) -> Result<Vec<Stmt>, String> {
         let cond = self.convert_condition(true, cond_id, false)?;
 
-        self.loops.push_loop(LoopType::While);
+        self.loops.push_loop(LoopType::While, body_id);
         let body_res = self.convert_stmt(body_id);
         let loop_ = self.loops.pop_loop();
         let body = body_res?;
@@ -1359,7 +2523,7 @@ impl Translation {
 
     fn convert_do_stmt(&self, span: Span, body_id: CStmtId, cond_id: CExprId) -> Result<Vec<Stmt>, String> {
         let cond = self.convert_condition(false, cond_id, false)?;
-        self.loops.push_loop(LoopType::DoWhile);
+        self.loops.push_loop(LoopType::DoWhile, body_id);
         let body_res = self.convert_stmt(body_id);
         let mut loop_ = self.loops.pop_loop();
         let mut body = body_res?;
@@ -1407,7 +2571,7 @@ impl Translation {
                 None => vec![],
             };
 
-            self.loops.push_loop(LoopType::For);
+            self.loops.push_loop(LoopType::For, body_id);
             let body_res = self.convert_stmt(body_id);
             let loop_ = self.loops.pop_loop();
             let mut body = body_res?;
@@ -1503,9 +2667,20 @@ impl Translation {
         false
     }
 
+    /// Assign the initial value of a variable declared ahead of its initialization. Values that
+    /// run a destructor are written in place, so that the zeroed placeholder isn't dropped.
+    fn mk_init_assign(&self, type_id: CTypeId, name: String, val: P<Expr>) -> P<Expr> {
+        if self.type_needs_drop(type_id) {
+            let var = mk().mutbl().addr_of_expr(mk().ident_expr(name));
+            mk().call_expr(mk().path_expr(vec!["", "std", "ptr", "write"]), vec![var, val])
+        } else {
+            mk().assign_expr(mk().ident_expr(name), val)
+        }
+    }
+
     pub fn convert_decl_stmt_info(&self, decl_id: CDeclId) -> Result<cfg::DeclStmtInfo, String> {
         match self.ast_context.index(decl_id).kind {
-            CDeclKind::Variable { is_static, is_extern, is_defn, ref ident, initializer, typ } if !is_static && !is_extern => {
+            CDeclKind::Variable { is_static, is_extern, is_defn, ref ident, initializer, typ, .. } if !is_static && !is_extern => {
                 assert!(is_defn, "Only local variable definitions should be extracted");
 
                 let has_self_reference =
@@ -1520,17 +2695,28 @@ impl Translation {
                 let rust_name = self.renamer.borrow_mut()
                     .insert(decl_id, &ident)
                     .expect(&format!("Failed to insert variable '{}'", ident));
-                let (ty, mutbl, init) = self.convert_variable(initializer, typ, is_static)?;
+                let (ty, mutbl, init) = match self.aligned_vla(decl_id) {
+                    Some((elt, align)) => self.convert_aligned_vla(typ.ctype, elt, align)?,
+                    None => self.convert_variable(initializer, typ, is_static)?,
+                };
                 let mut init = init?;
 
                 stmts.append(&mut init.stmts);
 
+                let zeroed = if self.aligned_vla(decl_id).is_some() {
+                    mk().call_expr(mk().path_expr(vec!["", "std", "ptr", "null_mut"]), vec![] as Vec<P<Expr>>)
+                } else {
+                    self.implicit_default_expr(typ.ctype, is_static)?
+                };
+                let (_, zeroed) = self.aligned_storage(decl_id, ty.clone(), zeroed);
+                let (ty, val) = self.aligned_storage(decl_id, ty, init.val);
+                init.val = val;
+
                 if has_self_reference {
                     let pat_mut = mk().set_mutbl("mut").ident_pat(rust_name.clone());
-                    let zeroed = self.implicit_default_expr(typ.ctype, is_static)?;
                     let local_mut = mk().local(pat_mut, Some(ty.clone()), Some(zeroed));
 
-                    let assign = mk().assign_expr(mk().ident_expr(rust_name), init.val);
+                    let assign = self.mk_init_assign(typ.ctype, rust_name, init.val);
 
                     let mut assign_stmts = stmts.clone();
                     assign_stmts.push(mk().semi_stmt(assign.clone()));
@@ -1546,13 +2732,12 @@ impl Translation {
                     ))
                 } else {
                     let pat_mut = mk().set_mutbl("mut").ident_pat(rust_name.clone());
-                    let zeroed = self.implicit_default_expr(typ.ctype, is_static)?;
                     let local_mut = mk().local(pat_mut, Some(ty.clone()), Some(zeroed));
 
                     let pat = mk().set_mutbl(mutbl).ident_pat(rust_name.clone());
 
                     let local = mk().local(pat, Some(ty), Some(init.val.clone()));
-                    let assign = mk().assign_expr(mk().ident_expr(rust_name), init.val);
+                    let assign = self.mk_init_assign(typ.ctype, rust_name, init.val);
 
                     let mut assign_stmts = stmts.clone();
                     assign_stmts.push(mk().semi_stmt(assign));
@@ -1637,6 +2822,241 @@ impl Translation {
         Ok((ty, mutbl, init))
     }
 
+    /// The alignment of a variable definition that asks for more than its type has, through an
+    /// alignment attribute on the variable or on a typedef of its type. Rust can only raise the
+    /// alignment of structs and unions, so such a variable is stored in the `value` field of an
+    /// over-aligned struct, see `aligned_storage`. Variable-length arrays are the exception, since
+    /// they get an allocation of their own.
+    fn aligned_variable(&self, decl_id: CDeclId) -> Option<u64> {
+        match self.ast_context[decl_id].kind {
+            CDeclKind::Variable { is_defn: true, manual_alignment: Some(align), .. }
+                if self.aligned_vla(decl_id).is_none() => Some(align),
+            _ => None,
+        }
+    }
+
+    /// The type and a value of a variable, stored in an over-aligned struct if the variable needs
+    /// one, see `aligned_variable`
+    fn aligned_storage(&self, decl_id: CDeclId, ty: P<Ty>, val: P<Expr>) -> (P<Ty>, P<Expr>) {
+        match self.aligned_variable(decl_id) {
+            Some(align) => {
+                let name = self.type_converter.borrow_mut().aligned_type_name(ty, align);
+                let val = mk().struct_expr(vec![name.as_str()], vec![mk().field("value", val)]);
+                (mk().path_ty(vec![name]), val)
+            }
+            None => (ty, val),
+        }
+    }
+
+    /// Fields typed with a typedef that raises the alignment of its type would have to be
+    /// placed further along than Rust places a field of the underlying type, so they are
+    /// rejected rather than silently misplaced.
+    fn check_field_alignment(&self, decl_id: CDeclId, type_id: CTypeId) -> Result<(), String> {
+        let mut type_id = type_id;
+        loop {
+            match self.ast_context[type_id].kind {
+                CTypeKind::ConstantArray(elt, _) | CTypeKind::Elaborated(elt) |
+                CTypeKind::Decayed(elt) | CTypeKind::Paren(elt) => type_id = elt,
+                CTypeKind::Typedef(typedef_id) => match self.ast_context[typedef_id].kind {
+                    CDeclKind::Typedef { manual_alignment: Some(align), .. } => {
+                        let decl = &self.ast_context[decl_id];
+                        let name = decl.kind.get_name().map_or("", String::as_str);
+                        let loc = self.ast_context.display_loc(&decl.loc).unwrap_or_else(|| "<unknown location>".to_string());
+                        return Err(format!("{}: cannot align field `{}` to {} bytes", loc, name, align))
+                    }
+                    CDeclKind::Typedef { typ, .. } => type_id = typ.ctype,
+                    _ => return Ok(()),
+                },
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    /// GNU vectors are aligned to their size, but become arrays aligned like their elements. That
+    /// is only safe where Rust alone decides the layout, so vectors in records and in the
+    /// signatures of functions with external linkage, which C code lays out too, are rejected.
+    fn check_vector_layout(&self, decl_id: CDeclId, type_id: CTypeId) -> Result<(), String> {
+        let mut type_id = type_id;
+        while let CTypeKind::ConstantArray(elt, _) = self.ast_context.resolve_type(type_id).kind {
+            type_id = elt;
+        }
+        if !self.ast_context.resolve_type(type_id).kind.is_vector() {
+            return Ok(())
+        }
+        let decl = &self.ast_context[decl_id];
+        let (kind, name) = match decl.kind {
+            CDeclKind::Field { ref name, .. } => ("field", name.as_str()),
+            CDeclKind::Variable { ref ident, .. } => ("parameter", ident.as_str()),
+            CDeclKind::Function { ref name, .. } => ("return type of", name.as_str()),
+            _ => ("declaration", ""),
+        };
+        let loc = self.ast_context.display_loc(&decl.loc).unwrap_or_else(|| "<unknown location>".to_string());
+        Err(format!("{}: cannot lay out vector {} `{}` as C does", loc, kind, name))
+    }
+
+    /// The base element type and alignment of a local variable-length array declared with an
+    /// alignment attribute. A `Vec` can't be over-aligned, so these arrays are pointers into
+    /// aligned `alloca` storage instead.
+    fn aligned_vla(&self, decl_id: CDeclId) -> Option<(CTypeId, u64)> {
+        match self.ast_context[decl_id].kind {
+            CDeclKind::Variable { is_static: false, typ, manual_alignment: Some(align), .. } =>
+                match self.ast_context.resolve_type(typ.ctype).kind {
+                    CTypeKind::VariableArray(elt, _) => Some((self.variable_array_base_type(elt), align)),
+                    _ => None,
+                },
+            _ => None,
+        }
+    }
+
+    fn convert_aligned_vla(
+        &self,
+        vla_ty: CTypeId,
+        elt: CTypeId,
+        align: u64,
+    ) -> Result<(P<Ty>, Mutability, Result<WithStmts<P<Expr>>,String>), String> {
+        let elt_ty = self.convert_type(elt)?;
+        let ptr_ty = mk().mutbl().ptr_ty(elt_ty);
+
+        let count = self.compute_size_of_expr(vla_ty).ok_or("Expected a variable-length array")?;
+        let WithStmts { mut stmts, val: elt_size } = self.compute_size_of_type(elt)?;
+        let size = mk().binary_expr(BinOpKind::Mul, count, elt_size);
+        let align = mk().lit_expr(mk().int_lit(align as u128, "usize"));
+
+        let mut alloc = self.convert_alloca_storage(size, Some(align));
+        stmts.append(&mut alloc.stmts);
+        let val = mk().cast_expr(alloc.val, ptr_ty.clone());
+
+        Ok((ptr_ty, Mutability::Mutable, Ok(WithStmts { stmts, val })))
+    }
+
+    /// Record that the translation at `loc` depends on implementation-defined behavior
+    fn note_impl_defined(&self, loc: Option<SrcLoc>, kind: ImplDefined) {
+        if self.tcfg.report_impl_defined {
+            self.impl_defined.borrow_mut().insert((loc, kind));
+        }
+    }
+
+    fn note_impl_defined_expr(&self, expr_id: CExprId) {
+        if !self.tcfg.report_impl_defined {
+            return
+        }
+
+        /// Widths in bytes on the platforms the translation targets
+        fn width(kind: &CTypeKind) -> Option<u8> {
+            match *kind {
+                CTypeKind::Bool | CTypeKind::Char | CTypeKind::SChar | CTypeKind::UChar => Some(1),
+                CTypeKind::Short | CTypeKind::UShort => Some(2),
+                CTypeKind::Int | CTypeKind::UInt => Some(4),
+                CTypeKind::Long | CTypeKind::ULong | CTypeKind::LongLong | CTypeKind::ULongLong => Some(8),
+                CTypeKind::Int128 | CTypeKind::UInt128 => Some(16),
+                _ => None,
+            }
+        }
+
+        let expr = &self.ast_context[expr_id];
+        let kind = match expr.kind {
+            CExprKind::Binary(_, c_ast::BinOp::ShiftRight, lhs, _, _, _) |
+            CExprKind::Binary(_, c_ast::BinOp::AssignShiftRight, lhs, _, None, _) => {
+                let lhs_type = self.ast_context[lhs].kind.get_type();
+                if !self.ast_context.resolve_type(lhs_type).kind.is_signed_integral_type() { return }
+                ImplDefined::SignedRightShift
+            }
+            CExprKind::Binary(_, c_ast::BinOp::AssignShiftRight, _, _, Some(compute_type), _) => {
+                if !self.ast_context.resolve_type(compute_type.ctype).kind.is_signed_integral_type() { return }
+                ImplDefined::SignedRightShift
+            }
+
+            CExprKind::ImplicitCast(ty, source_id, CastKind::IntegralCast, _) |
+            CExprKind::ExplicitCast(ty, source_id, CastKind::IntegralCast, _) => {
+                let source = &self.ast_context.resolve_type(self.ast_context[source_id].kind.get_type()).kind;
+                let target = &self.ast_context.resolve_type(ty.ctype).kind;
+                let from_char = match *source { CTypeKind::Char => true, _ => false };
+                match (width(source), width(target)) {
+                    (Some(s), Some(t)) if s < t && from_char => ImplDefined::CharSignedness,
+                    (Some(s), Some(t)) if target.is_signed_integral_type() &&
+                        (s > t || (s == t && source.is_unsigned_integral_type())) => {
+                        // Constants that fit, as in `char c = 'a'`, convert the same everywhere
+                        let (min, max) = if t == 16 {
+                            (i128::min_value(), i128::max_value())
+                        } else {
+                            let bits = 8 * t as u32;
+                            (-(1 << (bits - 1)), (1 << (bits - 1)) - 1)
+                        };
+                        let fits = |v: i128| v >= min && v <= max;
+                        if self.ast_context.eval_const_int(source_id).map_or(false, fits) { return }
+                        ImplDefined::SignedNarrowing
+                    }
+                    _ => return,
+                }
+            }
+
+            CExprKind::Literal(_, CLiteral::Character(val, 1)) if val >= 0x80 => ImplDefined::CharacterConstant,
+
+            _ => return,
+        };
+        self.note_impl_defined(expr.loc, kind);
+    }
+
+    /// Lines of the form `file:line:col: implementation-defined: ...` for the sites recorded
+    /// with `report_impl_defined`, in source order
+    pub fn impl_defined_report(&self) -> Vec<String> {
+        self.impl_defined.borrow().iter().map(|&(ref loc, kind)| {
+            let loc = self.ast_context.display_loc(loc).unwrap_or_else(|| "<unknown location>".to_string());
+            format!("{}: implementation-defined: {}", loc, kind.description())
+        }).collect()
+    }
+
+    /// A diagnostic pointing at the C source when `expr` is a bit-field member, which can't be
+    /// addressed or measured (C11 6.5.3.2 para 1, 6.5.3.4 para 1), and which this translation
+    /// couldn't form a reference to anyway
+    fn bitfield_error(&self, expr: CExprId, action: &str) -> Option<String> {
+        let expr = &self.ast_context[expr];
+        let field = match expr.kind {
+            CExprKind::Member(_, _, field, _) => field,
+            _ => return None,
+        };
+        match self.ast_context[field].kind {
+            CDeclKind::Field { ref name, bitfield_width: Some(_), .. } => {
+                let loc = self.ast_context.display_loc(&expr.loc).unwrap_or_else(|| "<unknown location>".to_string());
+                Some(format!("{}: cannot {} bit-field `{}`", loc, action, name))
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether `field` belongs to a union with `__attribute__((packed))` or `#pragma pack`, whose
+    /// fields may be misaligned
+    fn is_packed_union_field(&self, field: CFieldId) -> bool {
+        self.ast_context.parents.get(&field).map_or(false, |record| {
+            match self.ast_context[*record].kind {
+                CDeclKind::Union { max_field_alignment: Some(_), .. } => true,
+                _ => false,
+            }
+        })
+    }
+
+    /// A raw pointer to the storage of the union `expr`, or the union `expr` points to for
+    /// `->`. Every field of a union starts there, so this is a pointer to each of them too.
+    fn convert_union_storage(
+        &self,
+        expr: CExprId,
+        kind: MemberKind,
+        mutbl: Mutability,
+        is_static: bool,
+    ) -> Result<WithStmts<P<Expr>>, String> {
+        match kind {
+            MemberKind::Arrow => self.convert_expr(ExprUse::RValue, expr, is_static),
+            MemberKind::Dot => {
+                let union_ty = self.convert_type(self.ast_context[expr].kind.get_type())?;
+                let union = self.convert_expr(ExprUse::LValue, expr, is_static)?;
+                Ok(union.map(|u| {
+                    let addr = mk().set_mutbl(mutbl).addr_of_expr(u);
+                    mk().cast_expr(addr, mk().set_mutbl(mutbl).ptr_ty(union_ty))
+                }))
+            }
+        }
+    }
+
     fn convert_type(&self, type_id: CTypeId) -> Result<P<Ty>, String> {
         self.type_converter.borrow_mut().convert(&self.ast_context, type_id)
     }
@@ -1664,19 +3084,54 @@ impl Translation {
         Ok(mk().cast_expr(zero, ty))
     }
 
-    /// Write to a `lhs` that is volatile
+    /// A diagnostic pointing at the C source when `lhs` is `const volatile`, such as a read-only
+    /// hardware register, which may only be read (C11 6.5.16 para 2). Clang rejects such writes,
+    /// but they must not slip through as a `write_volatile` casting away the `const` either.
+    fn const_volatile_write_error(&self, lhs: CExprId) -> Option<String> {
+        let lhs = &self.ast_context[lhs];
+        let qualifiers = lhs.kind.get_qual_type().qualifiers;
+        if qualifiers.is_const && qualifiers.is_volatile {
+            let loc = self.ast_context.display_loc(&lhs.loc).unwrap_or_else(|| "<unknown location>".to_string());
+            Some(format!("{}: cannot write to a `const volatile` lvalue", loc))
+        } else {
+            None
+        }
+    }
+
+    /// A warning pointing at the C source when an implicit conversion of `expr` to the pointer
+    /// type `target` drops qualifiers from the pointee, like passing a `const int *` for an
+    /// `int *`. C only lets implicit conversions add qualifiers (C11 6.5.16.1 para 1), and Clang
+    /// warns about the others, so the translation makes the cast explicit and says so.
+    fn discarded_qualifiers_warning(&self, expr: CExprId, target: CTypeId) -> Option<String> {
+        let pointee_quals = |ty: CTypeId| match self.ast_context.resolve_type(ty).kind {
+            CTypeKind::Pointer(pointee) => Some(pointee.qualifiers),
+            _ => None,
+        };
+
+        let expr = &self.ast_context[expr];
+        let source = pointee_quals(expr.kind.get_type())?;
+        let target = pointee_quals(target)?;
+        if target.contains(source) {
+            return None
+        }
+
+        let dropped = [
+            (source.is_const && !target.is_const, "const"),
+            (source.is_restrict && !target.is_restrict, "restrict"),
+            (source.is_volatile && !target.is_volatile, "volatile"),
+        ].iter().filter(|&&(dropped, _)| dropped).map(|&(_, name)| name).collect::<Vec<_>>().join(" ");
+        let loc = self.ast_context.display_loc(&expr.loc).unwrap_or_else(|| "<unknown location>".to_string());
+        Some(format!("{}: warning: implicit pointer conversion discards `{}` qualifier; translated as an explicit cast", loc, dropped))
+    }
+
+    /// Write to a `lhs` that is volatile, and not `const`
     pub fn volatile_write(&self, lhs: &P<Expr>, lhs_type: CQualTypeId, rhs: P<Expr>) -> Result<P<Expr>, String> {
-        let addr_lhs = match lhs.node {
-            ExprKind::Unary(ast::UnOp::Deref, ref e) => {
-                if lhs_type.qualifiers.is_const {
-                    let lhs_type = self.convert_type(lhs_type.ctype)?;
-                    let ty = mk().mutbl().ptr_ty(lhs_type);
+        if lhs_type.qualifiers.is_const {
+            return Err(format!("Cannot write to a `const volatile` lvalue"))
+        }
 
-                    mk().cast_expr(e, ty)
-                } else {
-                    e.clone()
-                }
-            }
+        let addr_lhs = match lhs.node {
+            ExprKind::Unary(ast::UnOp::Deref, ref e) => e.clone(),
             _ => {
                 let addr_lhs = mk().mutbl().addr_of_expr(lhs);
 
@@ -1885,8 +3340,20 @@ impl Translation {
     /// `stmts` field of the output and it is expected that the `val` field of the output will be
     /// ignored.
     pub fn convert_expr(&self, use_: ExprUse, expr_id: CExprId, is_static: bool) -> Result<WithStmts<P<Expr>>, String> {
+        self.note_impl_defined_expr(expr_id);
+
         match self.ast_context.index(expr_id).kind {
             CExprKind::UnaryType(_ty, kind, opt_expr, arg_ty) => {
+                if let Some(expr) = opt_expr {
+                    let action = match kind {
+                        UnTypeOp::SizeOf => "apply `sizeof` to",
+                        UnTypeOp::AlignOf => "apply `_Alignof` to",
+                    };
+                    if let Some(err) = self.bitfield_error(expr, action) {
+                        return Err(err)
+                    }
+                }
+
                 let result = match kind {
                     UnTypeOp::SizeOf =>
                         match opt_expr {
@@ -1923,9 +3390,16 @@ impl Translation {
 
                 let mut val = mk().path_expr(vec![rustname]);
 
+                // Over-aligned variables are stored in a struct, see `aligned_variable`
+                if self.aligned_variable(decl_id).is_some() {
+                    val = mk().field_expr(val, "value");
+                }
+
                 // If the variable is volatile and used as something that isn't an LValue, this
-                // constitutes a volatile read.
-                if use_ != ExprUse::LValue && qual_ty.qualifiers.is_volatile {
+                // constitutes a volatile read. Volatile arrays are only read element by element,
+                // when they are subscripted.
+                if use_ != ExprUse::LValue && qual_ty.qualifiers.is_volatile &&
+                    !self.ast_context.resolve_type(qual_ty.ctype).kind.is_array() {
                     val = self.volatile_read(&val, qual_ty)?;
                 }
 
@@ -1938,7 +3412,9 @@ impl Translation {
                 }
 
                 if let CTypeKind::VariableArray(..) = self.ast_context.resolve_type(qual_ty.ctype).kind {
-                    val = mk().method_call_expr(val, "as_mut_ptr", vec![] as Vec<P<Expr>>);
+                    if self.aligned_vla(decl_id).is_none() {
+                        val = mk().method_call_expr(val, "as_mut_ptr", vec![] as Vec<P<Expr>>);
+                    }
                 }
 
                 Ok(WithStmts::new(val))
@@ -1958,18 +3434,25 @@ impl Translation {
                 Ok(WithStmts::new(mk().lit_expr(mk().int_lit(val.into(), intty))))
             }
 
-            CExprKind::Literal(_, CLiteral::Character(val)) => {
-                let expr = match char::from_u32(val as u32) {
-                    Some(c) => {
-                        let lit = mk().char_lit(c);
-                        let expr = mk().lit_expr(lit);
-                        let i32_type = mk().path_ty(vec!["i32"]);
-                        mk().cast_expr(expr, i32_type)
+            CExprKind::Literal(ty, CLiteral::Character(val, width)) => {
+                let ty = self.convert_type(ty.ctype)?;
+                let val = val as u32;
+                let expr = if width == 1 {
+                    if val < 0x80 {
+                        mk().cast_expr(mk().lit_expr(mk().char_lit(val as u8 as char)), ty)
+                    } else {
+                        // Multi-character constants and narrow characters with the high bit set
+                        // keep the implementation-defined `int` value clang computed for them
+                        mk().cast_expr(signed_int_expr(val as i32 as i64), ty)
                     }
-                    None => {
-                        // Fallback for characters outside of the valid Unicode range
-                        let lit = mk().int_lit(val as u128, LitIntType::Signed(IntTy::I32));
-                        mk().lit_expr(lit)
+                } else {
+                    match char::from_u32(val) {
+                        Some(c) => mk().cast_expr(mk().lit_expr(mk().char_lit(c)), ty),
+                        None => {
+                            // Fallback for characters outside of the valid Unicode range
+                            let lit = mk().int_lit(val as u128, LitIntType::Unsigned(UintTy::U32));
+                            mk().cast_expr(mk().lit_expr(lit), ty)
+                        }
                     }
                 };
                 Ok(WithStmts::new(expr))
@@ -1999,10 +3482,11 @@ impl Translation {
                     _ => for _ in 0..width { val.push(0); },
                 };
                 if is_static {
-                    let mut vals: Vec<P<Expr>> = vec![];
-                    for c in val {
-                        vals.push(mk().lit_expr(mk().int_lit(c as u128, LitIntType::Unsuffixed)));
-                    }
+                    // Wide literals hold one character per `width` bytes, in little-endian order
+                    let vals: Vec<P<Expr>> = val.chunks(width as usize).map(|c| {
+                        let c = c.iter().rev().fold(0u128, |acc, &b| (acc << 8) | b as u128);
+                        mk().lit_expr(mk().int_lit(c, LitIntType::Unsuffixed))
+                    }).collect();
                     let array = mk().array_expr(vals);
                     Ok(WithStmts::new(array))
                 } else {
@@ -2031,6 +3515,14 @@ impl Translation {
                 self.convert_unary_operator(use_, op, type_id, arg, is_static),
 
             CExprKind::Conditional(_, cond, lhs, rhs) => {
+                // Initializers of statics can't contain `if`, so pick the branch here when the
+                // condition is constant
+                if is_static {
+                    if let Some(c) = self.ast_context.eval_const_int(cond) {
+                        return self.convert_expr(use_, if c != 0 { lhs } else { rhs }, is_static)
+                    }
+                }
+
                 let cond = self.convert_condition(true, cond, is_static)?;
 
                 let lhs = self.convert_expr(use_, lhs, is_static)?;
@@ -2044,6 +3536,14 @@ impl Translation {
                         stmts: vec![mk().semi_stmt(mk().ifte_expr(c, then, Some(els)))],
                         val: self.panic("Conditional expression is not supposed to be used"),
                     }))
+                } else if use_ == ExprUse::LValue {
+                    // GNU C's `(c ? a : b) = v` assigns to whichever place `c` selects. Borrow
+                    // the selected place, so the other branch isn't evaluated, and go through
+                    // the borrow: `*if c { &mut a } else { &mut b } = v`
+                    let then: P<Block> = lhs.map(|x| mk().mutbl().addr_of_expr(x)).to_block();
+                    let els: P<Expr> = rhs.map(|x| mk().mutbl().addr_of_expr(x)).to_expr();
+
+                    Ok(cond.map(|c| mk().unary_expr(ast::UnOp::Deref, mk().ifte_expr(c, then, Some(els)))))
                 } else {
                     let then: P<Block> = lhs.to_block();
                     let els: P<Expr> = rhs.to_expr();
@@ -2067,16 +3567,40 @@ impl Translation {
                         val: self.panic("Binary conditional expression is not supposed to be used"),
                     })
                 } else {
-                    self.name_reference_write_read(lhs)?.result_map(|(_, lhs_val)| {
-                        let cond = self.match_bool(true, ty.ctype, lhs_val.clone());
-                        let ite = mk().ifte_expr(cond,
-                                       mk().block(vec![mk().expr_stmt(lhs_val)]),
-                                       Some(self.convert_expr(use_, rhs, is_static)?.to_expr()));
-                        Ok(ite)
-                    })
+                    // Bind the common operand to a temporary so it is evaluated (and, if volatile,
+                    // read) exactly once, even though its value is used both as the condition
+                    // and as the result.
+                    let lhs_ty = self.ast_context.index(lhs).kind.get_qual_type();
+                    let WithStmts { mut stmts, val: (_, lhs_val) } = self.name_reference_write_read(lhs)?;
+
+                    let lhs_name = self.renamer.borrow_mut().fresh();
+                    stmts.push(mk().local_stmt(P(mk().local(mk().ident_pat(&lhs_name),
+                                                            None as Option<P<Ty>>,
+                                                            Some(lhs_val)))));
+
+                    let mut lhs_val = mk().ident_expr(&lhs_name);
+                    let cond = self.match_bool(true, lhs_ty.ctype, lhs_val.clone());
+                    if self.ast_context.resolve_type_id(lhs_ty.ctype) != self.ast_context.resolve_type_id(ty.ctype) {
+                        lhs_val = mk().cast_expr(lhs_val, self.convert_type(ty.ctype)?);
+                    }
+
+                    let rhs = self.convert_expr(use_, rhs, is_static)?.to_expr();
+                    let ite = mk().ifte_expr(cond,
+                                   mk().block(vec![mk().expr_stmt(lhs_val)]),
+                                   Some(rhs));
+                    Ok(WithStmts { stmts, val: ite })
                 }
             },
 
+            CExprKind::Binary(_, c_ast::BinOp::And, _, _, _, _) |
+            CExprKind::Binary(_, c_ast::BinOp::Or, _, _, _, _) if is_static &&
+                self.ast_context.eval_const_int(expr_id).is_some() => {
+                // Short-circuiting operators are control flow, which the initializers of statics
+                // can't contain either
+                let val = self.ast_context.eval_const_int(expr_id).unwrap() != 0;
+                Ok(WithStmts::new(bool_to_int(mk().lit_expr(mk().bool_lit(val)))))
+            }
+
             CExprKind::Binary(type_id, op, lhs, rhs, opt_lhs_type_id, opt_res_type_id) => {
                 match op {
                     c_ast::BinOp::Comma => {
@@ -2120,6 +3644,20 @@ impl Translation {
                     },
 
                     _ => {
+                        if let Some(fused) = self.convert_contracted_fp(expr_id, op, type_id, lhs, rhs, is_static)? {
+                            return Ok(fused);
+                        }
+
+                        if let (c_ast::BinOp::Divide, false) = (op, is_static) {
+                            if let Some(array) = self.array_element_count(lhs, rhs) {
+                                let array = self.convert_expr(ExprUse::RValue, array, is_static)?;
+                                return Ok(array.map(|a| {
+                                    let len = mk().method_call_expr(a, "len", vec![] as Vec<P<Expr>>);
+                                    mk().cast_expr(len, mk().path_ty(vec!["libc", "c_ulong"]))
+                                }));
+                            }
+                        }
+
                         let ty = self.convert_type(type_id.ctype)?;
 
                         let lhs_type = self.ast_context.index(lhs).kind.get_qual_type();
@@ -2139,21 +3677,21 @@ impl Translation {
                 }
             }
 
-            CExprKind::ArraySubscript(_, ref lhs, ref rhs) => {
+            CExprKind::ArraySubscript(qual_ty, ref lhs, ref rhs) => {
                 let lhs_node = &self.ast_context.index(*lhs).kind;
                 let rhs_node = &self.ast_context.index(*rhs).kind;
 
-                let lhs_is_pointer = self.ast_context.resolve_type(lhs_node.get_type()).kind.is_pointer();
+                let lhs_is_indexed = {
+                    let lhs_type = &self.ast_context.resolve_type(lhs_node.get_type()).kind;
+                    lhs_type.is_pointer() || lhs_type.is_vector()
+                };
 
-                // From here on in, the LHS is the pointer/array and the RHS the index
+                // From here on in, the LHS is the pointer/array/vector and the RHS the index
                 let (lhs, rhs, lhs_node) =
-                    if lhs_is_pointer { (lhs, rhs, lhs_node) } else { (rhs, lhs, rhs_node) };
+                    if lhs_is_indexed { (lhs, rhs, lhs_node) } else { (rhs, lhs, rhs_node) };
 
                 let mut stmts = vec![];
 
-                let mut rhs = self.convert_expr(ExprUse::RValue, *rhs, is_static)?;
-                stmts.extend(rhs.stmts);
-
                 let simple_index_array =
                 match lhs_node {
                     &CExprKind::ImplicitCast(_, arr, CastKind::ArrayToPointerDecay, _) => {
@@ -2164,6 +3702,8 @@ impl Translation {
                             _ => Some(arr),
                         }
                     }
+                    // Vectors are subscripted directly, without decaying to a pointer first
+                    _ if self.ast_context.resolve_type(lhs_node.get_type()).kind.is_vector() => Some(*lhs),
                     _ => None,
                 };
 
@@ -2175,13 +3715,19 @@ impl Translation {
                     let var_elt_type_id = match self.ast_context.resolve_type(t).kind {
                         CTypeKind::ConstantArray(..) => None,
                         CTypeKind::IncompleteArray(..) => None,
+                        CTypeKind::Vector(..) => None,
                         CTypeKind::VariableArray(elt, _) => Some(elt),
                         ref other => panic!("Unexpected array type {:?}", other),
                     };
 
+                    // The array is evaluated before the index, so that the side-effects of the
+                    // indices in a chain like `a[i].b->c[j]` happen from left to right
                     let lhs = self.convert_expr(use_, arr, is_static)?;
                     stmts.extend(lhs.stmts);
 
+                    let rhs = self.convert_expr(ExprUse::RValue, *rhs, is_static)?;
+                    stmts.extend(rhs.stmts);
+
                     // Don't dereference the offset if we're still within the variable portion
                     if let Some(elt_type_id) = var_elt_type_id {
                         match self.compute_size_of_expr(elt_type_id) {
@@ -2196,6 +3742,9 @@ impl Translation {
                     let lhs = self.convert_expr(ExprUse::RValue, *lhs, is_static)?;
                     stmts.extend(lhs.stmts);
 
+                    let rhs = self.convert_expr(ExprUse::RValue, *rhs, is_static)?;
+                    stmts.extend(rhs.stmts);
+
                     let lhs_type_id = lhs_node.get_type();
 
                     // Determine the type of element being indexed
@@ -2213,26 +3762,58 @@ impl Translation {
                     }
                 };
 
+                // Reading an element of a volatile array, or through a pointer to volatile. As for
+                // variables, an element that is itself an array (as in `m[i]` of a volatile
+                // `m[2][3]`) is only read when it is subscripted in turn.
+                let val = if use_ != ExprUse::LValue && qual_ty.qualifiers.is_volatile &&
+                    !self.ast_context.resolve_type(qual_ty.ctype).kind.is_array() {
+                    self.volatile_read(&val, qual_ty)?
+                } else {
+                    val
+                };
+
                 Ok(WithStmts { stmts, val })
             }
 
             CExprKind::Call(_, func, ref args) => {
-                let WithStmts { mut stmts, val: func } = match self.ast_context.index(func).kind {
-                    CExprKind::ImplicitCast(_, fexp, CastKind::FunctionToPointerDecay, _) =>
-                        self.convert_expr(ExprUse::RValue, fexp, is_static)?,
-                    _ =>
-                        self.convert_expr(ExprUse::RValue, func, is_static)?.map(|x|
-                            unwrap_function_pointer(x)),
-                };
-
-                let mut args_new: Vec<P<Expr>> = vec![];
-                for arg in args {
-                    let WithStmts { stmts: ss, val } = self.convert_expr(ExprUse::RValue, *arg, is_static)?;
-                    stmts.extend(ss);
-                    args_new.push(val);
+                if let Some(builtin) = self.convert_builtin_call(use_, func, args, is_static)? {
+                    return Ok(builtin)
                 }
 
-                let call_expr = mk().call_expr(func, args_new);
+                let (mut stmts, call_expr) = if let Some(local) = self.destructor_call_target(func, args) {
+                    let drop_fn = mk().path_expr(vec!["", "std", "mem", "drop"]);
+                    let WithStmts { stmts, val: local } = self.convert_expr(ExprUse::RValue, local, is_static)?;
+                    (stmts, mk().call_expr(drop_fn, vec![local]))
+                } else {
+                    let WithStmts { mut stmts, val: func } = match self.ast_context.index(func).kind {
+                        CExprKind::ImplicitCast(_, fexp, CastKind::FunctionToPointerDecay, _) =>
+                            self.convert_expr(ExprUse::RValue, fexp, is_static)?,
+                        _ =>
+                            self.convert_expr(ExprUse::RValue, func, is_static)?.map(|x|
+                                unwrap_function_pointer(x)),
+                    };
+
+                    let params = self.callee_param_types(func);
+                    let mut args_new: Vec<P<Expr>> = vec![];
+                    for (i, arg) in args.iter().enumerate() {
+                        let WithStmts { stmts: ss, val } = self.convert_expr(ExprUse::RValue, *arg, is_static)?;
+                        stmts.extend(ss);
+
+                        // A `transparent_union` argument is passed as its first field
+                        let transparent_member = params.get(i)
+                            .and_then(|param| self.ast_context.transparent_union_member(param.ctype));
+                        let val = match transparent_member {
+                            Some((field_id, _)) => {
+                                let field_name = self.type_converter.borrow().resolve_field_name(None, field_id).unwrap();
+                                mk().field_expr(val, field_name)
+                            }
+                            None => val,
+                        };
+                        args_new.push(val);
+                    }
+
+                    (stmts, mk().call_expr(func, args_new))
+                };
 
                 if use_ == ExprUse::Unused {
                     // Recall that if `used` is false, the `stmts` field of the output must contain
@@ -2246,10 +3827,18 @@ impl Translation {
                 }
             }
 
-            CExprKind::Member(_, expr, decl, kind) => {
+            CExprKind::Member(qual_ty, expr, decl, kind) => {
 
                 if use_ == ExprUse::Unused {
                     self.convert_expr(use_, expr, is_static)
+                } else if use_ == ExprUse::RValue && !is_static && self.is_packed_union_field(decl) {
+                    // The field may be misaligned, so it is read without a reference to it
+                    let ty = self.convert_type(qual_ty.ctype)?;
+                    let storage = self.convert_union_storage(expr, kind, Mutability::Immutable, is_static)?;
+                    Ok(storage.map(|ptr| {
+                        let ptr = mk().cast_expr(ptr, mk().ptr_ty(ty));
+                        mk().call_expr(mk().path_expr(vec!["", "std", "ptr", "read_unaligned"]), vec![ptr])
+                    }))
                 } else {
                     let field_name = self.type_converter.borrow().resolve_field_name(None, decl).unwrap();
                     match kind {
@@ -2263,7 +3852,8 @@ impl Translation {
                                 let val = self.convert_expr(use_, subexpr_id, is_static)?;
                                 Ok(val.map(|v| mk().field_expr(v, field_name)))
                             } else {
-                                let val = self.convert_expr(use_, expr, is_static)?;
+                                // The pointer itself is only read, whatever the use of the member
+                                let val = self.convert_expr(ExprUse::RValue, expr, is_static)?;
                                 Ok(val.map(|v| mk().field_expr(mk().unary_expr(ast::UnOp::Deref, v), field_name)))
                             }
                         }
@@ -2277,7 +3867,7 @@ impl Translation {
             CExprKind::InitList(ty, ref ids, opt_union_field_id) => {
 
                 match self.ast_context.resolve_type(ty.ctype).kind {
-                    CTypeKind::ConstantArray(ty, n) => {
+                    CTypeKind::ConstantArray(ty, n) | CTypeKind::Vector(ty, n) => {
                         // Convert all of the provided initializer values
 
                         // Need to check to see if the next item is a string literal,
@@ -2334,6 +3924,30 @@ impl Translation {
             CExprKind::ImplicitValueInit(ty) =>
                 Ok(WithStmts::new(self.implicit_default_expr(ty.ctype, is_static)?)),
 
+            // `{ let mut init = base; init.x = 1; init }`
+            CExprKind::DesignatedInitUpdate(ty, base, updater) => {
+                if is_static {
+                    return Err(format!("Cannot update part of an initializer in a static"))
+                }
+
+                let WithStmts { stmts, val: base } = self.convert_expr(ExprUse::RValue, base, is_static)?;
+
+                let init = self.renamer.borrow_mut().fresh();
+                let local = mk().local(
+                    mk().set_mutbl(Mutability::Mutable).ident_pat(&init),
+                    Some(self.convert_type(ty.ctype)?),
+                    Some(base),
+                );
+                let mut block = vec![mk().local_stmt(P(local))];
+                block.append(&mut self.convert_init_update(mk().ident_expr(&init), updater)?);
+                block.push(mk().expr_stmt(mk().ident_expr(&init)));
+
+                Ok(WithStmts { stmts, val: mk().block_expr(mk().block(block)) })
+            }
+
+            CExprKind::NoInit(..) =>
+                Err(format!("Found an unchanged part of an initializer outside of its update")),
+
             CExprKind::Predefined(_, val_id) =>
                 self.convert_expr(use_, val_id, is_static),
 
@@ -2342,6 +3956,9 @@ impl Translation {
 
             CExprKind::VAArg(..) =>
                 Err(format!("Variable argument lists are not supported")),
+
+            CExprKind::Atomic { ref name, ptr, order, val1, order_fail, val2, weak, .. } =>
+                self.convert_atomic(use_, name, ptr, order, val1, order_fail, val2, weak, is_static),
         }
     }
 
@@ -2372,7 +3989,7 @@ impl Translation {
                     ref s => Err(format!("Statement expression didn't end in an expression: {:?}", s)),
                 }?;
 
-                if self.tcfg.reloop_cfgs {
+                if self.tcfg.reloop_cfgs || self.needs_cfg(&substmt_ids[0 .. (n-1)]) {
                     let name = format!("<stmt-expr_{:?}>", compound_stmt_id);
                     let ret = cfg::ImplicitReturnType::StmtExpr(use_, expr_id, is_static);
                     let mut stmts = self.convert_function_body(&name, &substmt_ids[0 .. (n-1)], ret)?;
@@ -2427,6 +4044,15 @@ impl Translation {
         is_static: bool,
     ) -> Result<WithStmts<P<Expr>>, String> {
 
+        if !is_explicit {
+            if let Some(warning) = self.discarded_qualifiers_warning(expr, ty.ctype) {
+                eprintln!("{}", warning);
+                let target_ty = self.convert_type(ty.ctype)?;
+                let val = self.convert_expr(use_, expr, is_static)?;
+                return Ok(val.map(|x| mk().cast_expr(x, target_ty)))
+            }
+        }
+
         let val = if is_explicit {
             let mut stmts = self.compute_variable_array_sizes(ty.ctype)?;
             let mut val = self.convert_expr(use_, expr, is_static)?;
@@ -2440,8 +4066,6 @@ impl Translation {
         match kind {
             CastKind::BitCast => {
                 val.result_map(|x| {
-                    // TODO: Detect cast from mutable to constant pointer to same type
-
                     // Special cases
                     if let Ok((source_quals, source_ty_id)) = self.get_declref_type(expr) {
                         let source_ty = self.convert_type(source_ty_id)?;
@@ -2451,26 +4075,19 @@ impl Translation {
 
                             // Detect a quirk where the bitcast is superfluous.
                             // See this issue: https://github.com/GaloisInc/C2Rust/issues/32
-                            if target_ty == source_ty {
-                                // Don't skip the cast if we're going from const to mutable
-                                if source_quals.map_or(true, |x| !x.is_const) || ty.qualifiers.is_const {
-                                    return Ok(x)
-                                }
+                            // Only a conversion that keeps or adds qualifiers can skip the cast.
+                            // Going from const to mutable still needs one.
+                            let adds_quals = source_quals.map_or(true, |sq| qual_type_id.qualifiers.contains(sq));
+
+                            if target_ty == source_ty && adds_quals {
+                                return Ok(x)
                             }
 
-                            let quals_agree = if let Some(sq) = source_quals {
-                                sq == qual_type_id.qualifiers
-                            } else { false };
                             // Detect bitcasts from array-of-T to slice-of-T
                             if let TyKind::Slice(ref tgt_elem_ty) = target_ty.node {
                                 if let TyKind::Array(ref src_elem_ty, _) = source_ty.node {
-                                    if tgt_elem_ty == src_elem_ty {
-                                        if quals_agree {
-                                            return Ok(x)
-                                        } else {
-                                            // FIXME: handle mismatched qualifiers
-                                            panic!("Cannot handle mismatched qualifiers yet.")
-                                        }
+                                    if tgt_elem_ty == src_elem_ty && adds_quals {
+                                        return Ok(x)
                                     }
                                 }
                             }
@@ -2491,12 +4108,27 @@ impl Translation {
                 })
             }
 
+            // Integers and pointers convert into each other with `as` casts, which expose the
+            // provenance of the pointer and pick it back up, so that a pointer round-tripped
+            // through an `intptr_t` can still access its allocation. Function pointers can't be
+            // cast to and from integers directly, so they go through `*const c_void` rather than
+            // transmuting an integer, which would make a pointer without provenance.
             CastKind::IntegralToPointer if self.is_function_pointer(ty.ctype) => {
                 let target_ty = self.convert_type(ty.ctype)?;
                 Ok(val.map(|x| {
                     let intptr_t = mk().path_ty(vec!["libc","intptr_t"]);
-                    let intptr = mk().cast_expr(x, intptr_t.clone());
-                    transmute_expr(intptr_t, target_ty, intptr)
+                    let void_ptr = mk().ptr_ty(mk().path_ty(vec!["libc","c_void"]));
+                    let ptr = mk().cast_expr(mk().cast_expr(x, intptr_t), void_ptr.clone());
+                    transmute_expr(void_ptr, target_ty, ptr)
+                }))
+            }
+
+            CastKind::PointerToIntegral if self.is_function_pointer(self.ast_context[expr].kind.get_type()) => {
+                let source_ty = self.convert_type(self.ast_context[expr].kind.get_type())?;
+                let target_ty = self.convert_type(ty.ctype)?;
+                Ok(val.map(|x| {
+                    let void_ptr = mk().ptr_ty(mk().path_ty(vec!["libc","c_void"]));
+                    mk().cast_expr(transmute_expr(source_ty, void_ptr, x), target_ty)
                 }))
             }
 
@@ -2588,12 +4220,40 @@ impl Translation {
                 Err(format!("TODO boolean to signed integral not supported")),
 
 
-            CastKind::FloatingRealToComplex | CastKind::FloatingComplexToIntegralComplex |
-            CastKind::FloatingComplexCast | CastKind::FloatingComplexToReal |
-            CastKind::IntegralComplexToReal | CastKind::IntegralRealToComplex |
+            CastKind::FloatingRealToComplex | CastKind::IntegralRealToComplex => {
+                let elt = match self.ast_context.resolve_type(ty.ctype).kind {
+                    CTypeKind::Complex(elt) => elt,
+                    _ => return Err(format!("Casts to complex should produce a complex type")),
+                };
+                let name = self.type_converter.borrow_mut().complex_type_name(&self.ast_context, elt)?;
+                let elt_ty = self.convert_type(elt)?;
+                let zero = self.implicit_default_expr(elt, is_static)?;
+                Ok(val.map(|x| mk().struct_expr(vec![name], vec![
+                    mk().field("re", mk().cast_expr(x, elt_ty)),
+                    mk().field("im", zero),
+                ])))
+            }
+
+            CastKind::FloatingComplexToReal | CastKind::IntegralComplexToReal => {
+                let target_ty = self.convert_type(ty.ctype)?;
+                Ok(val.map(|x| mk().cast_expr(mk().field_expr(x, "re"), target_ty)))
+            }
+
+            CastKind::FloatingComplexToIntegralComplex | CastKind::FloatingComplexCast |
             CastKind::IntegralComplexCast | CastKind::IntegralComplexToFloatingComplex |
             CastKind::IntegralComplexToBoolean =>
                 Err(format!("TODO casts with complex numbers not supported")),
+
+            // `v + 1` on a vector `v` adds `1` to every element
+            CastKind::VectorSplat => {
+                match self.ast_context.resolve_type(ty.ctype).kind {
+                    CTypeKind::Vector(_, len) => {
+                        let len = mk().lit_expr(mk().int_lit(len as u128, LitIntType::Unsuffixed));
+                        Ok(val.map(|x| mk().repeat_expr(x, len)))
+                    }
+                    ref t => Err(format!("Vector splat to non-vector type {:?}", t)),
+                }
+            }
         }
     }
 
@@ -2631,70 +4291,558 @@ impl Translation {
 
         let target_ty = self.convert_type(enum_type_id).unwrap();
 
-        mk().cast_expr(value, target_ty)
+        mk().cast_expr(value, target_ty)
+    }
+
+    /// This handles translating casts when the target type in an `enum` type.
+    ///
+    /// When translating variable references to `EnumConstant`'s, we always insert casts to the
+    /// expected type. In C, `EnumConstants` have some integral type, _not_ the enum type. However,
+    /// if we then immediately have a cast to convert this variable back into an enum type, we would
+    /// like to produce Rust with _no_ casts. This function handles this simplification.
+    fn enum_cast(
+        &self,
+        enum_type: CTypeId,
+        enum_decl: CEnumId,      // ID of the enum declaration corresponding to the target type
+        expr: CExprId,           // ID of initial C argument to cast
+        val: WithStmts<P<Expr>>, // translated Rust argument to cast
+        _source_ty: P<Ty>,        // source type of cast
+        target_ty: P<Ty>,        // target type of cast
+    ) -> WithStmts<P<Expr>> {
+
+        // Extract the IDs of the `EnumConstant` decls underlying the enum.
+        let variants = match self.ast_context.index(enum_decl).kind {
+            CDeclKind::Enum { ref variants, .. } => variants,
+            _ => panic!("{:?} does not point to an `enum` declaration")
+        };
+
+        match self.ast_context.index(expr).kind {
+            // This is the case of finding a variable which is an `EnumConstant` of the same enum
+            // we are casting to. Here, we can just remove the extraneous cast instead of generating
+            // a new one.
+            CExprKind::DeclRef(_, decl_id) if variants.contains(&decl_id) =>
+                return val.map(|x| match x.node {
+                    ast::ExprKind::Cast(ref e, _) => e.clone(),
+                    _ => panic!(format!("DeclRef {:?} of enum {:?} is not cast", expr, enum_decl)),
+                }),
+
+            CExprKind::Literal(_, CLiteral::Integer(i)) => {
+                let new_val = self.enum_for_i64(enum_type, i as i64);
+                return WithStmts { stmts: val.stmts, val: new_val }
+            }
+
+            CExprKind::Unary(_, c_ast::UnOp::Negate, subexpr_id) => {
+                if let &CExprKind::Literal(_, CLiteral::Integer(i)) = &self.ast_context[subexpr_id].kind {
+                    let new_val = self.enum_for_i64(enum_type, -(i as i64));
+                    return WithStmts { stmts: val.stmts, val: new_val }
+                }
+            }
+
+            // In all other cases, the value is cast to the integral type the enum is an alias of.
+            _ => {},
+        }
+
+        val.map(|x| mk().cast_expr(x, target_ty))
+    }
+
+    /// Translate calls to compiler builtins that have a direct Rust equivalent. Returns `None`
+    /// when `func` does not refer to such a builtin.
+    fn convert_builtin_call(
+        &self,
+        use_: ExprUse,
+        func: CExprId,
+        args: &[CExprId],
+        is_static: bool,
+    ) -> Result<Option<WithStmts<P<Expr>>>, String> {
+        let decl_id = match self.ast_context[func].kind {
+            CExprKind::ImplicitCast(_, fexp, CastKind::BuiltinFnToFnPtr, _) =>
+                match self.ast_context[fexp].kind {
+                    CExprKind::DeclRef(_, decl_id) => decl_id,
+                    _ => return Ok(None),
+                },
+            _ => return Ok(None),
+        };
+
+        let name = match self.ast_context[decl_id].kind {
+            CDeclKind::Function { ref name, .. } => name.as_str(),
+            _ => return Ok(None),
+        };
+
+        if name == "__builtin_alloca" || name == "__builtin_alloca_with_align" {
+            return self.convert_alloca(use_, name, args, is_static).map(Some)
+        }
+
+        if name.starts_with("__sync_") {
+            return self.convert_sync_builtin(use_, name, args, is_static).map(Some)
+        }
+
+        let swapped_ty = match name {
+            "__builtin_bswap16" => "u16",
+            "__builtin_bswap32" => "u32",
+            "__builtin_bswap64" => "u64",
+            _ => return Ok(None),
+        };
+
+        if args.len() != 1 {
+            return Err(format!("{} expects exactly one argument", name))
+        }
+
+        let arg = args[0];
+        let arg_ty = self.ast_context[arg].kind.get_type();
+        let is_unsigned = self.ast_context.resolve_type(arg_ty).kind.is_unsigned_integral_type();
+
+        let val = self.convert_expr(ExprUse::RValue, arg, is_static)?.map(|x| {
+            // Byte swapping is only defined on the unsigned type of the right width
+            let x = if is_unsigned { x } else { mk().cast_expr(x, mk().path_ty(vec![swapped_ty])) };
+            mk().method_call_expr(x, "swap_bytes", vec![] as Vec<P<Expr>>)
+        });
+
+        if use_ == ExprUse::Unused {
+            let WithStmts { mut stmts, val } = val;
+            stmts.push(mk().semi_stmt(val));
+            let val = self.panic("Builtin call expression is not supposed to be used");
+            Ok(Some(WithStmts { stmts, val }))
+        } else {
+            Ok(Some(val))
+        }
+    }
+
+    /// Translate `__builtin_alloca(size)` and `__builtin_alloca_with_align(size, align)`, where
+    /// the alignment is given in bits
+    fn convert_alloca(
+        &self,
+        use_: ExprUse,
+        name: &str,
+        args: &[CExprId],
+        is_static: bool,
+    ) -> Result<WithStmts<P<Expr>>, String> {
+        let expected_args = if name == "__builtin_alloca" { 1 } else { 2 };
+        if args.len() != expected_args {
+            return Err(format!("{} expects exactly {} argument(s)", name, expected_args))
+        }
+        if is_static {
+            return Err(format!("{} can only be called in a function", name))
+        }
+
+        let WithStmts { mut stmts, val: size } = self.convert_expr(ExprUse::RValue, args[0], is_static)?;
+        let size = cast_int(size, "usize");
+
+        let align = match args.get(1) {
+            Some(&align) => {
+                let mut align = self.convert_expr(ExprUse::RValue, align, is_static)?;
+                stmts.append(&mut align.stmts);
+                let bits = cast_int(align.val, "usize");
+                Some(mk().binary_expr(BinOpKind::Div, bits, mk().lit_expr(mk().int_lit(8, "usize"))))
+            }
+            None => None,
+        };
+
+        let mut alloc = self.convert_alloca_storage(size, align);
+        stmts.append(&mut alloc.stmts);
+
+        let void_ptr = mk().mutbl().ptr_ty(mk().path_ty(vec!["libc", "c_void"]));
+        let val = mk().cast_expr(alloc.val, void_ptr);
+
+        if use_ == ExprUse::Unused {
+            stmts.push(mk().semi_stmt(val));
+            let val = self.panic("Builtin call expression is not supposed to be used");
+            Ok(WithStmts { stmts, val })
+        } else {
+            Ok(WithStmts { stmts, val })
+        }
+    }
+
+    /// Allocate `size` zeroed bytes aligned to `align` (a power of two) that live until the
+    /// current function returns, like `alloca` memory does. The buffers are kept in a vector
+    /// declared at the start of the function body. Rust type: `*mut u8`
+    fn convert_alloca_storage(&self, size: P<Expr>, align: Option<P<Expr>>) -> WithStmts<P<Expr>> {
+        let storage = self.alloca_storage.borrow_mut()
+            .get_or_insert_with(|| self.renamer.borrow_mut().pick_name("alloca_storage"))
+            .clone();
+        let base = self.renamer.borrow_mut().pick_name("alloca");
+        let usize_lit = |i: u128| mk().lit_expr(mk().int_lit(i, "usize"));
+
+        // Leave room to move the start of the buffer up to the next multiple of `align`
+        let len = match align {
+            Some(ref align) => mk().binary_expr(
+                BinOpKind::Sub,
+                mk().binary_expr(BinOpKind::Add, size, align.clone()),
+                usize_lit(1),
+            ),
+            None => size,
+        };
+
+        // alloca_storage.push(vec![0u8; len]);
+        let from_elem = mk().path_expr(vec!["", "std", "vec", "from_elem"]);
+        let buffer = mk().call_expr(from_elem, vec![mk().lit_expr(mk().int_lit(0, "u8")), len]);
+        let push = mk().method_call_expr(mk().ident_expr(&storage), "push", vec![buffer]);
+
+        // let alloca = alloca_storage.last_mut().unwrap().as_mut_ptr();
+        let last = mk().method_call_expr(mk().ident_expr(&storage), "last_mut", vec![] as Vec<P<Expr>>);
+        let last = mk().method_call_expr(last, "unwrap", vec![] as Vec<P<Expr>>);
+        let ptr = mk().method_call_expr(last, "as_mut_ptr", vec![] as Vec<P<Expr>>);
+        let local = mk().local(mk().ident_pat(&base), None as Option<P<Ty>>, Some(ptr));
+
+        let stmts = vec![mk().semi_stmt(push), mk().local_stmt(P(local))];
+
+        // alloca.offset(((align - alloca as usize % align) % align) as isize)
+        let val = match align {
+            Some(align) => {
+                let addr = mk().cast_expr(mk().ident_expr(&base), mk().path_ty(vec!["usize"]));
+                let misalignment = mk().binary_expr(BinOpKind::Rem, addr, align.clone());
+                let padding = mk().paren_expr(mk().binary_expr(BinOpKind::Sub, align.clone(), misalignment));
+                let padding = mk().paren_expr(mk().binary_expr(BinOpKind::Rem, padding, align));
+                let padding = mk().cast_expr(padding, mk().path_ty(vec!["isize"]));
+                mk().method_call_expr(mk().ident_expr(&base), "offset", vec![padding])
+            }
+            None => mk().ident_expr(&base),
+        };
+
+        WithStmts { stmts, val }
+    }
+
+    /// Translate the legacy GCC `__sync_*` builtins. These are sequentially consistent, except
+    /// for `__sync_lock_test_and_set`, which only acquires, and `__sync_lock_release`, which only
+    /// releases.
+    fn convert_sync_builtin(
+        &self,
+        use_: ExprUse,
+        name: &str,
+        args: &[CExprId],
+        is_static: bool,
+    ) -> Result<WithStmts<P<Expr>>, String> {
+        if is_static {
+            return Err(format!("{} can only be called in a function", name))
+        }
+
+        // Clang calls the variant for the size of the operand, like `__sync_fetch_and_add_4`
+        let op = name.trim_left_matches("__sync_");
+        let op = match op.rfind('_') {
+            Some(i) if op[i + 1..].parse::<u32>().is_ok() => &op[..i],
+            _ => op,
+        };
+
+        if op == "synchronize" {
+            let fence = mk().path_expr(vec!["", "std", "sync", "atomic", "fence"]);
+            let val = mk().call_expr(fence, vec![atomic_ordering("SeqCst")]);
+            return Ok(self.unused_builtin_result(use_, WithStmts::new(val)))
+        }
+
+        let (&ptr, args) = args.split_first()
+            .ok_or_else(|| format!("{} expects a pointer argument", name))?;
+        let value_ty = self.atomic_value_type(ptr)?;
+        let WithStmts { mut stmts, val: ptr } = self.convert_expr(ExprUse::RValue, ptr, is_static)?;
+        let atomic = self.atomic_ref(ptr, value_ty)?;
+        let WithStmts { stmts: mut arg_stmts, val: args } = self.convert_exprs(args, is_static)?;
+        stmts.append(&mut arg_stmts);
+
+        let num_args = args.len();
+        let expect_args = |n: usize| if num_args == n {
+            Ok(())
+        } else {
+            Err(format!("{} expects exactly {} argument(s)", name, n + 1))
+        };
+        let mut args = args.into_iter();
+        let mut arg = || args.next().unwrap();
+        let seq_cst = || atomic_ordering("SeqCst");
+
+        let val = match op {
+            "val_compare_and_swap" => {
+                expect_args(2)?;
+                let (old, new) = (arg(), arg());
+                WithStmts::new(mk().method_call_expr(atomic, "compare_and_swap", vec![old, new, seq_cst()]))
+            }
+            "bool_compare_and_swap" => {
+                expect_args(2)?;
+                let (old, new) = (arg(), arg());
+                let exchange = mk().method_call_expr(atomic, "compare_exchange", vec![old, new, seq_cst(), seq_cst()]);
+                WithStmts::new(mk().method_call_expr(exchange, "is_ok", vec![] as Vec<P<Expr>>))
+            }
+            "lock_test_and_set" => {
+                expect_args(1)?;
+                WithStmts::new(mk().method_call_expr(atomic, "swap", vec![arg(), atomic_ordering("Acquire")]))
+            }
+            "lock_release" => {
+                expect_args(0)?;
+                // `AtomicBool` stores a `bool`, and `0 as bool` isn't a valid cast
+                let zero = match self.ast_context.resolve_type(value_ty).kind {
+                    CTypeKind::Bool => mk().lit_expr(mk().bool_lit(false)),
+                    _ => mk().cast_expr(mk().lit_expr(mk().int_lit(0, "")), self.convert_type(value_ty)?),
+                };
+                WithStmts::new(mk().method_call_expr(atomic, "store", vec![zero, atomic_ordering("Release")]))
+            }
+            _ if op.starts_with("fetch_and_") => {
+                expect_args(1)?;
+                self.convert_atomic_rmw(atomic, value_ty, &op["fetch_and_".len()..], arg(), seq_cst(), false)?
+            }
+            _ if op.ends_with("_and_fetch") => {
+                expect_args(1)?;
+                self.convert_atomic_rmw(atomic, value_ty, &op[..op.len() - "_and_fetch".len()], arg(), seq_cst(), true)?
+            }
+            _ => return Err(format!("Unsupported builtin {}", name)),
+        };
+
+        let WithStmts { stmts: mut op_stmts, val } = val;
+        stmts.append(&mut op_stmts);
+        Ok(self.unused_builtin_result(use_, WithStmts { stmts, val }))
+    }
+
+    /// Translate the GNU `__atomic_*` builtins, which take explicit memory orders. The generic
+    /// forms, like `__atomic_load`, pass values through pointers, while the `_n` forms pass them
+    /// directly.
+    fn convert_atomic(
+        &self,
+        use_: ExprUse,
+        name: &str,
+        ptr: CExprId,
+        order: Option<CExprId>,
+        val1: Option<CExprId>,
+        order_fail: Option<CExprId>,
+        val2: Option<CExprId>,
+        weak: Option<CExprId>,
+        is_static: bool,
+    ) -> Result<WithStmts<P<Expr>>, String> {
+        if is_static {
+            return Err(format!("{} can only be called in a function", name))
+        }
+        if !name.starts_with("__atomic_") {
+            return Err(format!("Unsupported builtin {}", name))
+        }
+
+        let missing = || format!("Missing operand to {}", name);
+        let order = self.convert_memory_order(order.ok_or_else(&missing)?)?;
+
+        let value_ty = self.atomic_value_type(ptr)?;
+        let WithStmts { mut stmts, val: ptr } = self.convert_expr(ExprUse::RValue, ptr, is_static)?;
+        let atomic = self.atomic_ref(ptr, value_ty)?;
+        let operands = val1.iter().chain(val2.iter()).cloned().collect::<Vec<_>>();
+        let WithStmts { stmts: mut operand_stmts, val: operands } = self.convert_exprs(&operands, is_static)?;
+        stmts.append(&mut operand_stmts);
+
+        let mut operands = operands.into_iter();
+        let mut operand = || operands.next().ok_or_else(&missing);
+        let deref = |e: P<Expr>| mk().unary_expr(ast::UnOp::Deref, e);
+
+        let op = name.trim_left_matches("__atomic_");
+        let val = match op {
+            "load_n" => WithStmts::new(mk().method_call_expr(atomic, "load", vec![order])),
+            "load" => {
+                let ret = operand()?;
+                let load = mk().method_call_expr(atomic, "load", vec![order]);
+                WithStmts::new(mk().assign_expr(deref(ret), load))
+            }
+            "store_n" => WithStmts::new(mk().method_call_expr(atomic, "store", vec![operand()?, order])),
+            "store" => WithStmts::new(mk().method_call_expr(atomic, "store", vec![deref(operand()?), order])),
+            "exchange_n" => WithStmts::new(mk().method_call_expr(atomic, "swap", vec![operand()?, order])),
+            "exchange" => {
+                let (val, ret) = (operand()?, operand()?);
+                let swap = mk().method_call_expr(atomic, "swap", vec![deref(val), order]);
+                WithStmts::new(mk().assign_expr(deref(ret), swap))
+            }
+            "compare_exchange" | "compare_exchange_n" => {
+                let (expected, desired) = (operand()?, operand()?);
+                let desired = if op == "compare_exchange" { deref(desired) } else { desired };
+                let order_fail = self.convert_memory_order(order_fail.ok_or_else(&missing)?)?;
+                // A strong exchange is also a valid implementation of a weak one
+                let is_weak = weak.and_then(|w| self.ast_context.eval_const_int(w)).map_or(false, |w| w != 0);
+                self.convert_compare_exchange(atomic, expected, desired, order, order_fail, is_weak)
+            }
+            _ if op.starts_with("fetch_") =>
+                self.convert_atomic_rmw(atomic, value_ty, &op["fetch_".len()..], operand()?, order, false)?,
+            _ if op.ends_with("_fetch") =>
+                self.convert_atomic_rmw(atomic, value_ty, &op[..op.len() - "_fetch".len()], operand()?, order, true)?,
+            _ => return Err(format!("Unsupported builtin {}", name)),
+        };
+
+        let WithStmts { stmts: mut op_stmts, val } = val;
+        stmts.append(&mut op_stmts);
+        Ok(self.unused_builtin_result(use_, WithStmts { stmts, val }))
+    }
+
+    /// `atomic.fetch_<op>(val, order)`, giving the old value, or the new value when `new_value`
+    /// is set. `value_ty` is the type of the values in the atomic.
+    fn convert_atomic_rmw(
+        &self,
+        atomic: P<Expr>,
+        value_ty: CTypeId,
+        op: &str,
+        val: P<Expr>,
+        order: P<Expr>,
+        new_value: bool,
+    ) -> Result<WithStmts<P<Expr>>, String> {
+        match op {
+            "add" | "sub" | "and" | "or" | "xor" => {}
+            _ => return Err(format!("Atomic `{}` is not supported", op)),
+        }
+        // `AtomicPtr` has no `fetch_` methods, and `AtomicBool` has only the bitwise ones
+        match self.ast_context.resolve_type(value_ty).kind {
+            CTypeKind::Pointer(..) =>
+                return Err(format!("Atomic `{}` is not supported on pointers", op)),
+            CTypeKind::Bool if op == "add" || op == "sub" =>
+                return Err(format!("Atomic `{}` is not supported on `_Bool`", op)),
+            _ => {}
+        }
+        let method = format!("fetch_{}", op);
+
+        if !new_value {
+            return Ok(WithStmts::new(mk().method_call_expr(atomic, &*method, vec![val, order])))
+        }
+
+        // The operand is needed again to compute the new value, so evaluate it only once
+        let name = self.renamer.borrow_mut().pick_name("val");
+        let local = mk().local(mk().ident_pat(&name), None as Option<P<Ty>>, Some(val));
+        let val = || mk().ident_expr(&name);
+
+        let old = mk().method_call_expr(atomic, &*method, vec![val(), order]);
+        let new = match op {
+            "add" => mk().method_call_expr(old, "wrapping_add", vec![val()]),
+            "sub" => mk().method_call_expr(old, "wrapping_sub", vec![val()]),
+            "and" => mk().binary_expr(BinOpKind::BitAnd, old, val()),
+            "or" => mk().binary_expr(BinOpKind::BitOr, old, val()),
+            _ => mk().binary_expr(BinOpKind::BitXor, old, val()),
+        };
+        Ok(WithStmts { stmts: vec![mk().local_stmt(P(local))], val: new })
     }
 
-    /// This handles translating casts when the target type in an `enum` type.
-    ///
-    /// When translating variable references to `EnumConstant`'s, we always insert casts to the
-    /// expected type. In C, `EnumConstants` have some integral type, _not_ the enum type. However,
-    /// if we then immediately have a cast to convert this variable back into an enum type, we would
-    /// like to produce Rust with _no_ casts. This function handles this simplification.
-    fn enum_cast(
+    /// Compare `*expected` with the atomic and exchange it for `desired` if they're equal.
+    /// Otherwise, write the current value to `*expected`. The result is whether the exchange
+    /// happened.
+    fn convert_compare_exchange(
         &self,
-        enum_type: CTypeId,
-        enum_decl: CEnumId,      // ID of the enum declaration corresponding to the target type
-        expr: CExprId,           // ID of initial C argument to cast
-        val: WithStmts<P<Expr>>, // translated Rust argument to cast
-        _source_ty: P<Ty>,        // source type of cast
-        target_ty: P<Ty>,        // target type of cast
+        atomic: P<Expr>,
+        expected: P<Expr>,
+        desired: P<Expr>,
+        success: P<Expr>,
+        failure: P<Expr>,
+        is_weak: bool,
     ) -> WithStmts<P<Expr>> {
+        // `expected` is used twice, so evaluate it only once
+        let expected_name = self.renamer.borrow_mut().pick_name("expected");
+        let actual_name = self.renamer.borrow_mut().pick_name("actual");
+        let local = mk().local(mk().ident_pat(&expected_name), None as Option<P<Ty>>, Some(expected));
+        let expected = || mk().unary_expr(ast::UnOp::Deref, mk().ident_expr(&expected_name));
+
+        let method = if is_weak { "compare_exchange_weak" } else { "compare_exchange" };
+        let exchange = mk().method_call_expr(atomic, method, vec![expected(), desired, success, failure]);
+
+        // match exchange { Ok(_) => true, Err(actual) => { *expected = actual; false } }
+        let ok = mk().arm(
+            vec![mk().tuple_struct_pat(vec!["Ok"], vec![mk().wild_pat()])],
+            None as Option<P<Expr>>,
+            mk().lit_expr(mk().bool_lit(true)),
+        );
+        let write_back = mk().semi_stmt(mk().assign_expr(expected(), mk().ident_expr(&actual_name)));
+        let err = mk().arm(
+            vec![mk().tuple_struct_pat(vec!["Err"], vec![mk().ident_pat(&actual_name)])],
+            None as Option<P<Expr>>,
+            mk().block_expr(mk().block(vec![write_back, mk().expr_stmt(mk().lit_expr(mk().bool_lit(false)))])),
+        );
 
-        // Extract the IDs of the `EnumConstant` decls underlying the enum.
-        let variants = match self.ast_context.index(enum_decl).kind {
-            CDeclKind::Enum { ref variants, .. } => variants,
-            _ => panic!("{:?} does not point to an `enum` declaration")
-        };
+        WithStmts {
+            stmts: vec![mk().local_stmt(P(local))],
+            val: mk().match_expr(exchange, vec![ok, err]),
+        }
+    }
 
-        match self.ast_context.index(expr).kind {
-            // This is the case of finding a variable which is an `EnumConstant` of the same enum
-            // we are casting to. Here, we can just remove the extraneous cast instead of generating
-            // a new one.
-            CExprKind::DeclRef(_, decl_id) if variants.contains(&decl_id) =>
-                return val.map(|x| match x.node {
-                    ast::ExprKind::Cast(ref e, _) => e.clone(),
-                    _ => panic!(format!("DeclRef {:?} of enum {:?} is not cast", expr, enum_decl)),
-                }),
+    /// The type of the values an atomic builtin operates on, given its pointer operand
+    fn atomic_value_type(&self, ptr: CExprId) -> Result<CTypeId, String> {
+        let ptr_ty = self.ast_context[ptr].kind.get_type();
+        match self.ast_context.resolve_type(ptr_ty).kind {
+            CTypeKind::Pointer(pointee) => Ok(pointee.ctype),
+            _ => Err(format!("Expected a pointer operand to an atomic builtin")),
+        }
+    }
 
-            CExprKind::Literal(_, CLiteral::Integer(i)) => {
-                let new_val = self.enum_for_i64(enum_type, i as i64);
-                return WithStmts { stmts: val.stmts, val: new_val }
-            }
+    /// Reinterpret `ptr`, a pointer to values of type `ctype`, as a reference to the
+    /// `std::sync::atomic` type with the same representation: `(*(ptr as *mut AtomicI32))`
+    fn atomic_ref(&self, ptr: P<Expr>, ctype: CTypeId) -> Result<P<Expr>, String> {
+        let int_atomic = |name: &str| {
+            // Only the pointer-sized integer atomics are stable
+            self.features.borrow_mut().insert("integer_atomics");
+            mk().path_segment(name)
+        };
 
-            CExprKind::Unary(_, c_ast::UnOp::Negate, subexpr_id) => {
-                if let &CExprKind::Literal(_, CLiteral::Integer(i)) = &self.ast_context[subexpr_id].kind {
-                    let new_val = self.enum_for_i64(enum_type, -(i as i64));
-                    return WithStmts { stmts: val.stmts, val: new_val }
-                }
+        let atomic = match self.ast_context.resolve_type(ctype).kind {
+            CTypeKind::Bool => mk().path_segment("AtomicBool"),
+            CTypeKind::Char | CTypeKind::SChar => int_atomic("AtomicI8"),
+            CTypeKind::UChar => int_atomic("AtomicU8"),
+            CTypeKind::Short => int_atomic("AtomicI16"),
+            CTypeKind::UShort => int_atomic("AtomicU16"),
+            CTypeKind::Int => int_atomic("AtomicI32"),
+            CTypeKind::UInt => int_atomic("AtomicU32"),
+            CTypeKind::Long | CTypeKind::LongLong => int_atomic("AtomicI64"),
+            CTypeKind::ULong | CTypeKind::ULongLong => int_atomic("AtomicU64"),
+            // `AtomicPtr<T>` holds `*mut T`, which is not how pointers to const are translated
+            CTypeKind::Pointer(pointee) if !pointee.qualifiers.is_const => {
+                let pointee = self.convert_type(pointee.ctype)?;
+                mk().path_segment_with_params("AtomicPtr", mk().angle_bracketed_param_types(vec![pointee]))
             }
+            _ => return Err(format!("Atomic operations are only supported on integers, `_Bool`, and pointers to non-const data")),
+        };
 
-            // In all other cases, a cast to an enum requires a `transmute` - Rust enums cannot be
-            // converted into integral types as easily as C ones.
-            _ => {},
+        let path = vec![
+            mk().path_segment(""),
+            mk().path_segment("std"),
+            mk().path_segment("sync"),
+            mk().path_segment("atomic"),
+            atomic,
+        ];
+        let ptr = mk().paren_expr(mk().cast_expr(ptr, mk().mutbl().ptr_ty(mk().path_ty(path))));
+        Ok(mk().paren_expr(mk().unary_expr(ast::UnOp::Deref, ptr)))
+    }
+
+    /// The `Ordering` for a C11 memory order, which must be a constant like `__ATOMIC_SEQ_CST`
+    fn convert_memory_order(&self, order: CExprId) -> Result<P<Expr>, String> {
+        let name = match self.ast_context.eval_const_int(order) {
+            Some(0) => "Relaxed",
+            // Rust has no consume ordering, so use the stronger acquire ordering
+            Some(1) | Some(2) => "Acquire",
+            Some(3) => "Release",
+            Some(4) => "AcqRel",
+            Some(5) => "SeqCst",
+            _ => return Err(format!("Atomic memory orders must be constants")),
+        };
+        Ok(atomic_ordering(name))
+    }
+
+    /// Convert expressions in order, collecting their statements
+    fn convert_exprs(&self, exprs: &[CExprId], is_static: bool) -> Result<WithStmts<Vec<P<Expr>>>, String> {
+        let mut stmts = vec![];
+        let mut vals = vec![];
+        for &expr in exprs {
+            let WithStmts { stmts: mut expr_stmts, val } = self.convert_expr(ExprUse::RValue, expr, is_static)?;
+            stmts.append(&mut expr_stmts);
+            vals.push(val);
         }
+        Ok(WithStmts { stmts, val: vals })
+    }
 
-        val.map(|x| mk().cast_expr(x, target_ty))
+    /// The value of a builtin call that isn't used becomes a statement
+    fn unused_builtin_result(&self, use_: ExprUse, val: WithStmts<P<Expr>>) -> WithStmts<P<Expr>> {
+        if use_ == ExprUse::Unused {
+            let WithStmts { mut stmts, val } = val;
+            stmts.push(mk().semi_stmt(val));
+            let val = self.panic("Builtin call expression is not supposed to be used");
+            WithStmts { stmts, val }
+        } else {
+            val
+        }
     }
 
     fn convert_union_literal(
         &self,
         union_id: CRecordId,
         ids: &[CExprId],
-        _ty: CQualTypeId,
+        ty: CQualTypeId,
         opt_union_field_id: Option<CFieldId>,
         is_static: bool,
     ) -> Result<WithStmts<P<Expr>>, String> {
-        let union_field_id = opt_union_field_id.expect("union field ID");
+        // An initializer list that doesn't select a member (ex: `{}`) zero initializes the union,
+        // which activates only its first member
+        let union_field_id = match opt_union_field_id {
+            Some(union_field_id) => union_field_id,
+            None => return Ok(WithStmts::new(self.zero_initializer(union_id, ty.ctype, is_static)?)),
+        };
 
         match self.ast_context.index(union_id).kind {
             CDeclKind::Union { .. } => {
@@ -2766,7 +4914,7 @@ impl Translation {
         }
 
         // Pad out remaining omitted record fields
-        for i in ids.len()..fields.len() {
+        for i in ids.len()..field_decls.len() {
             let &(ref field_name, ty) = &field_decls[i];
             fields.push(mk().field(field_name, self.implicit_default_expr(ty.ctype, is_static)?));
         }
@@ -2777,6 +4925,55 @@ impl Translation {
         })
     }
 
+    /// Assignments applying the initializer list `updater` to `place`, leaving the parts marked
+    /// `NoInit` as they are
+    fn convert_init_update(&self, place: P<Expr>, updater: CExprId) -> Result<Vec<Stmt>, String> {
+        let mut stmts = vec![];
+
+        match self.ast_context[updater].kind {
+            CExprKind::NoInit(..) => {}
+
+            CExprKind::InitList(ty, ref ids, opt_union_field_id) => {
+                match self.ast_context.resolve_type(ty.ctype).kind {
+                    CTypeKind::ConstantArray(..) => {
+                        for (i, &id) in ids.iter().enumerate() {
+                            let index = mk().lit_expr(mk().int_lit(i as u128, LitIntType::Unsuffixed));
+                            stmts.append(&mut self.convert_init_update(mk().index_expr(place.clone(), index), id)?);
+                        }
+                    }
+                    CTypeKind::Struct(struct_id) => {
+                        let fields = match self.ast_context[struct_id].kind {
+                            CDeclKind::Struct { fields: Some(ref fields), .. } => fields,
+                            _ => return Err(format!("Updated initializer for an incomplete struct")),
+                        };
+                        for (&field_id, &id) in fields.iter().zip(ids) {
+                            let name = self.type_converter.borrow().resolve_field_name(Some(struct_id), field_id)
+                                .ok_or("Unknown field name")?;
+                            stmts.append(&mut self.convert_init_update(mk().field_expr(place.clone(), name), id)?);
+                        }
+                    }
+                    CTypeKind::Union(union_id) => {
+                        let field_id = opt_union_field_id.ok_or("Updated union initializer without a field")?;
+                        let name = self.type_converter.borrow().resolve_field_name(Some(union_id), field_id)
+                            .ok_or("Unknown field name")?;
+                        for &id in ids {
+                            stmts.append(&mut self.convert_init_update(mk().field_expr(place.clone(), &name), id)?);
+                        }
+                    }
+                    ref t => return Err(format!("Updated initializer for {:?}", t)),
+                }
+            }
+
+            _ => {
+                let WithStmts { stmts: mut val_stmts, val } = self.convert_expr(ExprUse::RValue, updater, false)?;
+                stmts.append(&mut val_stmts);
+                stmts.push(mk().semi_stmt(mk().assign_expr(place, val)));
+            }
+        }
+
+        Ok(stmts)
+    }
+
     pub fn implicit_default_expr(&self, ty_id: CTypeId, is_static: bool) -> Result<P<Expr>, String> {
         let resolved_ty_id = self.ast_context.resolve_type_id(ty_id);
         let resolved_ty = &self.ast_context.index(resolved_ty_id).kind;
@@ -2792,6 +4989,16 @@ impl Translation {
         } else if let &CTypeKind::ConstantArray(elt, sz) = resolved_ty {
             let sz = mk().lit_expr(mk().int_lit(sz as u128, LitIntType::Unsuffixed));
             Ok(mk().repeat_expr(self.implicit_default_expr(elt, is_static)?, sz))
+        } else if let &CTypeKind::Vector(elt, sz) = resolved_ty {
+            let sz = mk().lit_expr(mk().int_lit(sz as u128, LitIntType::Unsuffixed));
+            Ok(mk().repeat_expr(self.implicit_default_expr(elt, is_static)?, sz))
+        } else if let &CTypeKind::Complex(elt) = resolved_ty {
+            let name = self.type_converter.borrow_mut().complex_type_name(&self.ast_context, elt)?;
+            let zero = self.implicit_default_expr(elt, is_static)?;
+            Ok(mk().struct_expr(vec![name], vec![
+                mk().field("re", zero.clone()),
+                mk().field("im", zero),
+            ]))
         } else if let &CTypeKind::IncompleteArray(_) = resolved_ty {
             // Incomplete arrays are translated to zero length arrays
             Ok(mk().array_expr(vec![] as Vec<P<Expr>>))
@@ -2951,8 +5158,19 @@ impl Translation {
                 ExprKind::Path(..) => true,
                 ExprKind::Unary(ast::UnOp::Deref, ref e) |
                 ExprKind::Field(ref e, _) |
-                ExprKind::TupField(ref e, _) |
-                ExprKind::Index(ref e, _) => is_simple_lvalue(e),
+                ExprKind::TupField(ref e, _) => is_simple_lvalue(e),
+                ExprKind::Index(ref e, ref i) => is_simple_lvalue(e) && is_simple_index(i),
+                _ => false,
+            }
+        }
+
+        // Check if an index is side-effect free, so that `a[i]` can be evaluated twice.
+        fn is_simple_index(e: &Expr) -> bool {
+            match e.node {
+                ExprKind::Path(..) |
+                ExprKind::Lit(..) => true,
+                ExprKind::Cast(ref e, _) |
+                ExprKind::Paren(ref e) => is_simple_index(e),
                 _ => false,
             }
         }
@@ -3009,6 +5227,9 @@ impl Translation {
         }
 
         let ty = self.ast_context.index(arg).kind.get_qual_type();
+        if let Some(err) = self.const_volatile_write_error(arg) {
+            return Err(err)
+        }
 
         let WithStmts { val: (write, read), stmts: mut lhs_stmts } = self.name_reference_write_read(arg)?;
 
@@ -3073,11 +5294,34 @@ impl Translation {
         match name {
             c_ast::UnOp::AddressOf => {
 
+                if let Some(err) = self.bitfield_error(arg, "take the address of") {
+                    return Err(err)
+                }
+
                 // C99 6.5.3.2 para 4
                 if let CExprKind::Unary(_, c_ast::UnOp::Deref, target) = self.ast_context[arg].kind {
                     return self.convert_expr(use_, target, is_static)
                 }
 
+                // The fields of packed unions may be misaligned, so their address is taken
+                // without a reference to them
+                if let CExprKind::Member(_, union, field, kind) = self.ast_context[arg].kind {
+                    if !is_static && self.is_packed_union_field(field) {
+                        let storage = self.convert_union_storage(union, kind, Mutability::Mutable, is_static)?;
+                        return Ok(storage.map(|ptr| mk().cast_expr(ptr, ty)))
+                    }
+                }
+
+                // A variable-length array is already represented by a pointer to its first
+                // element, which is also the address of the whole array. Arithmetic on the result
+                // still strides over whole arrays, since its pointee is the variable array type.
+                if let CTypeKind::Pointer(pointee) = resolved_ctype.kind {
+                    if let CTypeKind::VariableArray(..) = self.ast_context.resolve_type(pointee.ctype).kind {
+                        let arg = self.convert_expr(ExprUse::RValue, arg, is_static)?;
+                        return Ok(arg.map(|a| mk().cast_expr(a, ty)))
+                    }
+                }
+
                 // In this translation, there are only pointers to functions and
                 // & becomes a no-op when applied to a function.
 
@@ -3170,7 +5414,32 @@ impl Translation {
                 let arg = self.convert_expr(use_, arg, is_static)?;
                 Ok(arg)
             },
-            c_ast::UnOp::Real | c_ast::UnOp::Imag | c_ast::UnOp::Coawait =>
+            c_ast::UnOp::Real | c_ast::UnOp::Imag => {
+                let arg_ty = self.ast_context[arg].kind.get_type();
+                let is_complex = match self.ast_context.resolve_type(arg_ty).kind {
+                    CTypeKind::Complex(_) => true,
+                    _ => false,
+                };
+
+                if is_complex {
+                    // The parts of a complex lvalue are lvalues themselves
+                    let field = match name { c_ast::UnOp::Real => "re", _ => "im" };
+                    let val = self.convert_expr(use_, arg, is_static)?;
+                    Ok(val.map(|x| mk().field_expr(x, field)))
+                } else if let c_ast::UnOp::Real = name {
+                    // GNU C: the real part of a real number is the number itself
+                    self.convert_expr(use_, arg, is_static)
+                } else {
+                    // GNU C: the imaginary part of a real number is zero, after evaluating it
+                    let val = self.convert_expr(ExprUse::RValue, arg, is_static)?;
+                    let zero = self.implicit_default_expr(ctype, is_static)?;
+                    Ok(val.and_then(|x| WithStmts {
+                        stmts: vec![mk().semi_stmt(x)],
+                        val: zero,
+                    }))
+                }
+            }
+            c_ast::UnOp::Coawait =>
                 panic!("Unsupported extension operator"),
         }
     }
@@ -3194,17 +5463,15 @@ impl Translation {
             Ok(mk().assign_op_expr(bin_op_kind, write, rhs))
         } else {
             let lhs_type = self.convert_type(compute_lhs_ty.ctype)?;
-            let lhs = mk().cast_expr(read, lhs_type.clone());
+            let lhs = mk().cast_expr(read, lhs_type);
             let ty = self.convert_type(compute_res_ty.ctype)?;
             let val = self.convert_binary_operator(bin_op, ty, compute_res_ty.ctype, compute_lhs_ty, rhs_ty, lhs, rhs);
 
-            let is_enum_result = self.ast_context[self.ast_context.resolve_type_id(lhs_ty.ctype)].kind.is_enum();
+            // Enums are aliases of their underlying integral type, so converting the result
+            // back into an enum is a plain cast too, even when that type is narrower than the
+            // type the operation was computed in.
             let result_type = self.convert_type(lhs_ty.ctype)?;
-            let val = if is_enum_result {
-                transmute_expr(lhs_type, result_type, val)
-            } else {
-                mk().cast_expr(val, result_type)
-            };
+            let val = mk().cast_expr(val, result_type);
             Ok(mk().assign_expr(write.clone(), val))
         }
     }
@@ -3244,6 +5511,9 @@ impl Translation {
         let initial_lhs_type_id = self.ast_context.index(lhs).kind.get_qual_type();
 
         let is_volatile = initial_lhs_type_id.qualifiers.is_volatile;
+        if let Some(err) = self.const_volatile_write_error(lhs) {
+            return Err(err)
+        }
         let is_volatile_compound_assign = op.underlying_assignment().is_some() && is_volatile;
 
         let qtype_kind = &self.ast_context.resolve_type(qtype.ctype).kind;
@@ -3254,20 +5524,28 @@ impl Translation {
             _ => None,
         };
 
-        let is_unsigned_arith = match op {
+        let is_wrapping_arith = match op {
             c_ast::BinOp::AssignAdd | c_ast::BinOp::AssignSubtract |
             c_ast::BinOp::AssignMultiply | c_ast::BinOp::AssignDivide |
             c_ast::BinOp::AssignModulus
              => compute_type_kind.is_unsigned_integral_type(),
             _ => false,
+        } || match op {
+            c_ast::BinOp::AssignDivide | c_ast::BinOp::AssignModulus
+             => self.is_wrapping_division(compute_lhs_type_id.ctype),
+            _ => false,
         };
 
+        // Vectors are arrays in Rust, without arithmetic operators of their own
+        let is_vector_arith = op.underlying_assignment().is_some() && qtype_kind.is_vector();
+
         let (write, read, lhs_stmts) =
             if initial_lhs_type_id.ctype != compute_lhs_type_id.ctype ||
                 use_ == ExprUse::RValue ||
                 pointer_lhs.is_some() ||
                 is_volatile_compound_assign ||
-                is_unsigned_arith {
+                is_wrapping_arith ||
+                is_vector_arith {
             let WithStmts { val: (write, read), stmts: lhs_stmts } = self.name_reference_write_read(lhs)?;
             (write, read, lhs_stmts)
         } else {
@@ -3289,7 +5567,7 @@ impl Translation {
             c_ast::BinOp::Assign => self.volatile_write(&write, initial_lhs_type_id, rhs)?,
 
             // Anything volatile needs to be desugared into explicit reads and writes
-            op if is_volatile || is_unsigned_arith => {
+            op if is_volatile || is_wrapping_arith || is_vector_arith => {
                 let op = op.underlying_assignment().expect("Cannot convert non-assignment operator");
 
                 let val = if compute_lhs_type_id.ctype == initial_lhs_type_id.ctype {
@@ -3297,17 +5575,10 @@ impl Translation {
                 } else {
                     let lhs_type = self.convert_type(compute_type.unwrap().ctype)?;
                     let write_type = self.convert_type(qtype.ctype)?;
-                    let lhs = mk().cast_expr(read.clone(), lhs_type.clone());
+                    let lhs = mk().cast_expr(read.clone(), lhs_type);
                     let ty = self.convert_type(result_type_id.ctype)?;
                     let val = self.convert_binary_operator(op, ty, result_type_id.ctype, compute_lhs_type_id, rhs_type_id, lhs, rhs);
 
-                    let is_enum_result = self.ast_context[self.ast_context.resolve_type_id(qtype.ctype)].kind.is_enum();
-                    let result_type = self.convert_type(qtype.ctype)?;
-                    let val = if is_enum_result {
-                        transmute_expr(lhs_type, result_type, val)
-                    } else {
-                        mk().cast_expr(val, result_type)
-                    };
                     mk().cast_expr(val, write_type)
                 };
 
@@ -3361,6 +5632,128 @@ impl Translation {
         Ok(WithStmts { stmts, val: read })
     }
 
+    /// The declared parameter types of the function a call expression calls, empty for calls
+    /// through functions without a prototype
+    fn callee_param_types(&self, func: CExprId) -> Vec<CQualTypeId> {
+        let fn_ty = match self.ast_context.resolve_type(self.ast_context[func].kind.get_type()).kind {
+            CTypeKind::Pointer(pointee) => pointee.ctype,
+            _ => return vec![],
+        };
+        match self.ast_context.resolve_type(fn_ty).kind {
+            CTypeKind::Function(_, ref params, _, _) => params.clone(),
+            _ => vec![],
+        }
+    }
+
+    /// Recognize `sizeof(a) / sizeof(a[0])` and `sizeof(a) / sizeof(*a)` for a fixed size array
+    /// `a`, the idiom for its element count, and return `a`. The count then translates to
+    /// `a.len()`, which keeps the length tied to the array for later rewriting into slices.
+    fn array_element_count(&self, lhs: CExprId, rhs: CExprId) -> Option<CExprId> {
+        let array = match self.ast_context[lhs].kind {
+            CExprKind::UnaryType(_, UnTypeOp::SizeOf, Some(array), arg_ty) => {
+                match self.ast_context.resolve_type(arg_ty.ctype).kind {
+                    CTypeKind::ConstantArray(..) => array,
+                    _ => return None,
+                }
+            }
+            _ => return None,
+        };
+
+        let element = match self.ast_context[rhs].kind {
+            CExprKind::UnaryType(_, UnTypeOp::SizeOf, Some(element), _) => element,
+            _ => return None,
+        };
+        let base = match self.ast_context[element].kind {
+            CExprKind::ArraySubscript(_, base, index) => {
+                match self.ast_context[index].kind {
+                    CExprKind::Literal(_, CLiteral::Integer(0)) => base,
+                    _ => return None,
+                }
+            }
+            CExprKind::Unary(_, c_ast::UnOp::Deref, base) => base,
+            _ => return None,
+        };
+        let base = match self.ast_context[base].kind {
+            CExprKind::ImplicitCast(_, base, CastKind::ArrayToPointerDecay, _) => base,
+            _ => return None,
+        };
+
+        if self.same_array_lvalue(array, base) { Some(array) } else { None }
+    }
+
+    /// Whether two expressions name the same variable or the same member of it, without any
+    /// side effects that would make evaluating one of them differ from the other
+    fn same_array_lvalue(&self, a: CExprId, b: CExprId) -> bool {
+        match (&self.ast_context[a].kind, &self.ast_context[b].kind) {
+            (&CExprKind::DeclRef(_, a), &CExprKind::DeclRef(_, b)) => a == b,
+            (&CExprKind::Member(_, a, a_field, MemberKind::Dot), &CExprKind::Member(_, b, b_field, MemberKind::Dot)) |
+            (&CExprKind::Member(_, a, a_field, MemberKind::Arrow), &CExprKind::Member(_, b, b_field, MemberKind::Arrow)) =>
+                a_field == b_field && self.same_array_lvalue(a, b),
+            (&CExprKind::ImplicitCast(_, a, CastKind::LValueToRValue, _), &CExprKind::ImplicitCast(_, b, CastKind::LValueToRValue, _)) =>
+                self.same_array_lvalue(a, b),
+            _ => false,
+        }
+    }
+
+    /// Fuse `a * b + c`, `c + a * b`, `a * b - c`, and `c - a * b` into a `mul_add`, when
+    /// `--contract-fp` is given and `#pragma STDC FP_CONTRACT ON` allows the contraction. The
+    /// operations otherwise stay separate, and Rust never contracts those on its own.
+    fn convert_contracted_fp(
+        &self,
+        expr_id: CExprId,
+        op: c_ast::BinOp,
+        ty: CQualTypeId,
+        lhs: CExprId,
+        rhs: CExprId,
+        is_static: bool,
+    ) -> Result<Option<WithStmts<P<Expr>>>, String> {
+        if !self.tcfg.contract_fp || is_static || !self.ast_context.fp_contractable.contains(&expr_id) {
+            return Ok(None)
+        }
+
+        match self.ast_context.resolve_type(ty.ctype).kind {
+            CTypeKind::Float | CTypeKind::Double => {}
+            _ => return Ok(None),
+        }
+
+        let as_product = |id: CExprId| match self.ast_context.index(id).kind {
+            CExprKind::Binary(_, c_ast::BinOp::Multiply, a, b, _, _) => Some((a, b)),
+            _ => None,
+        };
+
+        // The factors and addend, and which of the product and addend get negated
+        let (a, b, c, negate_product, negate_addend) = match (op, as_product(lhs), as_product(rhs)) {
+            (c_ast::BinOp::Add, Some((a, b)), _) => (a, b, rhs, false, false),
+            (c_ast::BinOp::Add, None, Some((a, b))) => (a, b, lhs, false, false),
+            (c_ast::BinOp::Subtract, Some((a, b)), _) => (a, b, rhs, false, true),
+            (c_ast::BinOp::Subtract, None, Some((a, b))) => (a, b, lhs, true, false),
+            _ => return Ok(None),
+        };
+
+        let a = self.convert_expr(ExprUse::RValue, a, is_static)?;
+        let b = self.convert_expr(ExprUse::RValue, b, is_static)?;
+        let c = self.convert_expr(ExprUse::RValue, c, is_static)?;
+
+        let mut stmts = vec![];
+        stmts.extend(a.stmts);
+        stmts.extend(b.stmts);
+        stmts.extend(c.stmts);
+
+        // Operands of `-` and receivers of method calls bind tighter than most expressions
+        let parenthesize = |e: P<Expr>| match e.node {
+            ExprKind::Binary(..) | ExprKind::Unary(..) | ExprKind::Cast(..) | ExprKind::Assign(..) |
+            ExprKind::AssignOp(..) | ExprKind::If(..) | ExprKind::Lit(..) => mk().paren_expr(e),
+            _ => e,
+        };
+        let negate = |e: P<Expr>| mk().unary_expr(ast::UnOp::Neg, parenthesize(e));
+
+        let factor = if negate_product { negate(a.val) } else { a.val };
+        let addend = if negate_addend { negate(c.val) } else { c.val };
+        let val = mk().method_call_expr(parenthesize(factor), "mul_add", vec![b.val, addend]);
+
+        Ok(Some(WithStmts { stmts, val }))
+    }
+
     /// Translate a non-assignment binary operator. It is expected that the `lhs` and `rhs`
     /// arguments be usable as rvalues.
     fn convert_binary_operator(
@@ -3373,7 +5766,12 @@ impl Translation {
         lhs: P<Expr>,
         rhs: P<Expr>,
     ) -> P<Expr> {
+        if let CTypeKind::Vector(elt, len) = self.ast_context.resolve_type(ctype).kind {
+            return self.convert_vector_operator(op, ty, elt, len, lhs_type, rhs_type, lhs, rhs)
+        }
+
         let is_unsigned_integral_type = self.ast_context.index(ctype).kind.is_unsigned_integral_type();
+        let is_wrapping_division = self.is_wrapping_division(ctype);
 
         match op {
             c_ast::BinOp::Add => self.convert_addition(lhs_type, rhs_type, lhs, rhs),
@@ -3383,11 +5781,11 @@ impl Translation {
                 mk().method_call_expr(lhs, mk().path_segment("wrapping_mul"), vec![rhs]),
             c_ast::BinOp::Multiply => mk().binary_expr(BinOpKind::Mul, lhs, rhs),
 
-            c_ast::BinOp::Divide if is_unsigned_integral_type =>
+            c_ast::BinOp::Divide if is_unsigned_integral_type || is_wrapping_division =>
                 mk().method_call_expr(lhs, mk().path_segment("wrapping_div"), vec![rhs]),
             c_ast::BinOp::Divide => mk().binary_expr(BinOpKind::Div, lhs, rhs),
 
-            c_ast::BinOp::Modulus if is_unsigned_integral_type =>
+            c_ast::BinOp::Modulus if is_unsigned_integral_type || is_wrapping_division =>
                 mk().method_call_expr(lhs, mk().path_segment("wrapping_rem"), vec![rhs]),
             c_ast::BinOp::Modulus => mk().binary_expr(BinOpKind::Rem, lhs, rhs),
 
@@ -3410,6 +5808,59 @@ impl Translation {
         }
     }
 
+    /// Translate a binary operator on GNU C vectors, which applies it to each pair of elements.
+    /// The operands are bound first so that they are evaluated once:
+    /// `{ let (lhs, rhs) = (a, b); [lhs[0] + rhs[0], lhs[1] + rhs[1]] }`
+    fn convert_vector_operator(
+        &self,
+        op: c_ast::BinOp,
+        ty: P<Ty>,
+        elt: CTypeId,
+        len: usize,
+        lhs_type: CQualTypeId,
+        rhs_type: CQualTypeId,
+        lhs: P<Expr>,
+        rhs: P<Expr>,
+    ) -> P<Expr> {
+        let elt_ty = match ty.node {
+            TyKind::Array(ref elt_ty, _) => elt_ty.clone(),
+            _ => panic!("Vector types are translated to arrays"),
+        };
+
+        // Element `i` of a vector operand, or the operand itself if it's a scalar
+        let element = |operand: &str, qtype: CQualTypeId, i: usize| {
+            match self.ast_context.resolve_type(qtype.ctype).kind {
+                CTypeKind::Vector(ctype, _) => {
+                    let index = mk().lit_expr(mk().int_lit(i as u128, LitIntType::Unsuffixed));
+                    let qtype = CQualTypeId { qualifiers: Qualifiers::default(), ctype };
+                    (mk().index_expr(mk().ident_expr(operand), index), qtype)
+                }
+                _ => (mk().ident_expr(operand), qtype),
+            }
+        };
+
+        let elements: Vec<P<Expr>> = (0..len).map(|i| {
+            let (lhs, lhs_type) = element("lhs", lhs_type, i);
+            let (rhs, rhs_type) = element("rhs", rhs_type, i);
+            let val = self.convert_binary_operator(op, elt_ty.clone(), elt, lhs_type, rhs_type, lhs, rhs);
+
+            match op {
+                // Vector comparisons are `-1` where they hold, rather than `1`
+                c_ast::BinOp::EqualEqual | c_ast::BinOp::NotEqual | c_ast::BinOp::Less |
+                c_ast::BinOp::Greater | c_ast::BinOp::GreaterEqual | c_ast::BinOp::LessEqual =>
+                    mk().cast_expr(mk().unary_expr(ast::UnOp::Neg, val), elt_ty.clone()),
+                _ => val,
+            }
+        }).collect();
+
+        let operands = mk().local_stmt(P(mk().local(
+            mk().tuple_pat(vec![mk().ident_pat("lhs"), mk().ident_pat("rhs")]),
+            None as Option<P<Ty>>,
+            Some(mk().tuple_expr(vec![lhs, rhs])),
+        )));
+        mk().block_expr(mk().block(vec![operands, mk().expr_stmt(mk().array_expr(elements))]))
+    }
+
     fn convert_addition(
         &self,
         lhs_type_id: CQualTypeId,
@@ -3515,21 +5966,32 @@ impl Translation {
 
             // One simplification we can make at the cost of inspecting `val` more closely: if `val`
             // is already in the form `(x <op> y) as <ty>` where `<op>` is a Rust operator
-            // that returns a boolean, we can simple output `x <op> y` or `!(x <op> y)`.
+            // that returns a boolean, we can simple output `x <op> y` or `!(x <op> y)`. The same
+            // goes for `p.is_null() as <ty>` from pointer comparisons. Anything else, notably
+            // `!x as <ty>` which is a bitwise complement on integers, is compared against zero.
             if let ExprKind::Cast(ref arg, _) = val.node {
-                if let ExprKind::Binary(op, _, _) = arg.node {
-                    match op.node {
+                let is_bool = match arg.node {
+                    ExprKind::Binary(op, _, _) => match op.node {
                         BinOpKind::Or | BinOpKind::And |
                         BinOpKind::Eq | BinOpKind::Ne |
                         BinOpKind::Lt | BinOpKind::Le |
-                        BinOpKind::Gt | BinOpKind::Ge => if target {
-                            // If target == true, just return the argument
-                            return arg.clone();
-                        } else {
-                            // If target == false, return !arg
-                            return mk().unary_expr(ast::UnOp::Not, arg.clone());
-                        },
-                        _ => {}
+                        BinOpKind::Gt | BinOpKind::Ge => true,
+                        _ => false,
+                    },
+                    ExprKind::MethodCall(ref seg, ref args) if args.len() == 1 => {
+                        let method = seg.identifier.name.as_str();
+                        &*method == "is_null" || &*method == "is_some" || &*method == "is_none"
+                    }
+                    _ => false,
+                };
+
+                if is_bool {
+                    if target {
+                        // If target == true, just return the argument
+                        return arg.clone();
+                    } else {
+                        // If target == false, return !arg
+                        return mk().unary_expr(ast::UnOp::Not, arg.clone());
                     }
                 }
             }
@@ -3545,6 +6007,12 @@ impl Translation {
         }
     }
 
+    /// Whether signed division and remainder in `typ` should use the wrapping methods
+    fn is_wrapping_division(&self, typ: CTypeId) -> bool {
+        self.tcfg.signed_division == SignedDivision::Wrapping &&
+            self.ast_context.resolve_type(typ).kind.is_signed_integral_type()
+    }
+
     fn is_function_pointer(&self, typ: CTypeId) -> bool {
         let resolved_ctype = self.ast_context.resolve_type(typ);
         if let CTypeKind::Pointer(p) = resolved_ctype.kind {
@@ -3575,3 +6043,343 @@ impl Translation {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn qual(ctype: CTypeId) -> CQualTypeId {
+        CQualTypeId { qualifiers: Qualifiers::default(), ctype }
+    }
+
+    /// Builds a `TypedAstContext` for a single file by hand, for code that Clang rejects or only
+    /// warns about. Every node gets a fresh id, and variables made with `var` are already named
+    /// in the `Translation` returned by `translation`.
+    struct AstBuilder {
+        context: TypedAstContext,
+        vars: Vec<(CDeclId, String)>,
+        next_id: u64,
+    }
+
+    impl AstBuilder {
+        fn new(file: &str) -> AstBuilder {
+            let mut context = TypedAstContext::new();
+            context.c_files.insert(0, file.to_string());
+            AstBuilder { context, vars: vec![], next_id: 1 }
+        }
+
+        fn located<T>(&mut self, line: u64, column: u64, kind: T) -> (u64, Located<T>) {
+            let id = self.next_id;
+            self.next_id += 1;
+            (id, Located { loc: Some(SrcLoc { fileid: 0, line, column }), kind })
+        }
+
+        fn ty(&mut self, kind: CTypeKind) -> CTypeId {
+            let (id, ty) = self.located(1, 1, kind);
+            self.context.c_types.insert(CTypeId(id), ty);
+            CTypeId(id)
+        }
+
+        fn decl(&mut self, line: u64, column: u64, kind: CDeclKind) -> CDeclId {
+            let (id, decl) = self.located(line, column, kind);
+            self.context.c_decls.insert(CDeclId(id), decl);
+            CDeclId(id)
+        }
+
+        fn expr(&mut self, line: u64, column: u64, kind: CExprKind) -> CExprId {
+            let (id, expr) = self.located(line, column, kind);
+            self.context.c_exprs.insert(CExprId(id), expr);
+            CExprId(id)
+        }
+
+        /// A variable definition without an initializer
+        fn var(&mut self, line: u64, column: u64, is_static: bool, ident: &str, typ: CQualTypeId) -> CDeclId {
+            let var = self.decl(line, column, CDeclKind::Variable {
+                is_static,
+                is_extern: false,
+                is_defn: true,
+                ident: ident.to_string(),
+                initializer: None,
+                typ,
+                manual_alignment: None,
+            });
+            self.vars.push((var, ident.to_string()));
+            var
+        }
+
+        /// The value of `var`, as in `var + 1`
+        fn rvalue(&mut self, line: u64, column: u64, var: CDeclId, typ: CQualTypeId) -> CExprId {
+            let lvalue = self.expr(line, column, CExprKind::DeclRef(typ, var));
+            self.expr(line, column, CExprKind::ImplicitCast(typ, lvalue, CastKind::LValueToRValue, None))
+        }
+
+        fn int(&mut self, line: u64, column: u64, typ: CTypeId, value: u64) -> CExprId {
+            self.expr(line, column, CExprKind::Literal(qual(typ), CLiteral::Integer(value)))
+        }
+
+        fn translation(self, tcfg: TranslationConfig) -> Translation {
+            let t = Translation::new(self.context, tcfg);
+            for (var, ident) in self.vars {
+                t.renamer.borrow_mut().insert(var, &ident);
+            }
+            t
+        }
+    }
+
+    /// `struct flags { unsigned ready : 1; } f;` in `flags.c`, with `&f.ready` and
+    /// `sizeof(f.ready)` on line 3. Clang already rejects both, so they are built by hand.
+    fn bitfield_context() -> (Translation, CExprId, CExprId) {
+        let mut b = AstBuilder::new("flags.c");
+        let uint = b.ty(CTypeKind::UInt);
+        let uint_ptr = b.ty(CTypeKind::Pointer(qual(uint)));
+        let ulong = b.ty(CTypeKind::ULong);
+
+        let ready = b.decl(1, 25, CDeclKind::Field {
+            name: "ready".to_string(),
+            typ: qual(uint),
+            bitfield_width: Some(1),
+        });
+        let flags = b.decl(1, 1, CDeclKind::Struct {
+            name: Some("flags".to_string()),
+            fields: Some(vec![ready]),
+            manual_alignment: None,
+            max_field_alignment: None,
+            is_must_use: false,
+        });
+        b.context.parents.insert(ready, flags);
+        let record = b.ty(CTypeKind::Struct(flags));
+        let f = b.var(1, 38, true, "f", qual(record));
+
+        let base = b.expr(3, 12, CExprKind::DeclRef(qual(record), f));
+        let member = b.expr(3, 12, CExprKind::Member(qual(uint), base, ready, MemberKind::Dot));
+        let address_of = b.expr(3, 11, CExprKind::Unary(qual(uint_ptr), c_ast::UnOp::AddressOf, member));
+        let size_of = b.expr(3, 4, CExprKind::UnaryType(qual(ulong), UnTypeOp::SizeOf, Some(member), qual(uint)));
+
+        (b.translation(TranslationConfig::default()), address_of, size_of)
+    }
+
+    #[test]
+    fn address_of_bitfield() {
+        let (t, address_of, _) = bitfield_context();
+
+        let err = t.convert_expr(ExprUse::RValue, address_of, false).unwrap_err();
+        assert_eq!(err, "flags.c:3:12: cannot take the address of bit-field `ready`");
+    }
+
+    #[test]
+    fn size_of_bitfield() {
+        let (t, _, size_of) = bitfield_context();
+
+        let err = t.convert_expr(ExprUse::RValue, size_of, false).unwrap_err();
+        assert_eq!(err, "flags.c:3:12: cannot apply `sizeof` to bit-field `ready`");
+    }
+
+    /// `int a, b, c;` with `(c ? a : b) = 5` and `(c ? a : b) += 1`, which GNU C used to accept.
+    /// Clang rejects both, so they are built by hand.
+    fn conditional_lvalue_context() -> (Translation, CExprId, CExprId) {
+        let mut b = AstBuilder::new("select.c");
+        let int = b.ty(CTypeKind::Int);
+        let vars = ["a", "b", "c"].iter()
+            .map(|ident| b.var(1, 5, false, ident, qual(int)))
+            .collect::<Vec<_>>();
+
+        let conditional = |b: &mut AstBuilder, line: u64| {
+            let cond = b.rvalue(line, 6, vars[2], qual(int));
+            let lhs = b.expr(line, 10, CExprKind::DeclRef(qual(int), vars[0]));
+            let rhs = b.expr(line, 14, CExprKind::DeclRef(qual(int), vars[1]));
+            b.expr(line, 6, CExprKind::Conditional(qual(int), cond, lhs, rhs))
+        };
+
+        let target = conditional(&mut b, 3);
+        let five = b.int(3, 20, int, 5);
+        let assign = b.expr(3, 5, CExprKind::Binary(qual(int), c_ast::BinOp::Assign, target, five, None, None));
+
+        let target = conditional(&mut b, 4);
+        let one = b.int(4, 21, int, 1);
+        let add_assign = b.expr(4, 5, CExprKind::Binary(qual(int), c_ast::BinOp::AssignAdd, target, one, Some(qual(int)), Some(qual(int))));
+
+        (b.translation(TranslationConfig::default()), assign, add_assign)
+    }
+
+    fn translate_stmts(t: &Translation, expr: CExprId) -> String {
+        let translated = t.convert_expr(ExprUse::Unused, expr, false).unwrap();
+        translated.stmts.iter().map(stmt_to_string).collect::<Vec<_>>().join("\n")
+    }
+
+    #[test]
+    fn assign_to_conditional() {
+        let (t, assign, _) = conditional_lvalue_context();
+
+        let translated = translate_stmts(&t, assign);
+        assert!(translated.contains("*if c != 0 { &mut a } else { &mut b } = 5"), "{}", translated);
+    }
+
+    #[test]
+    fn compound_assign_to_conditional() {
+        let (t, _, add_assign) = conditional_lvalue_context();
+
+        // The condition is evaluated once, when binding the selected place
+        let translated = translate_stmts(&t, add_assign);
+        assert_eq!(translated.matches("c != 0").count(), 1, "{}", translated);
+        assert!(translated.contains("&mut a"), "{}", translated);
+    }
+
+    /// `const volatile unsigned *status;` in `regs.c`, with `*status = 1` on line 4. Clang
+    /// already rejects the write, so it is built by hand.
+    #[test]
+    fn write_to_const_volatile() {
+        let mut b = AstBuilder::new("regs.c");
+        let uint = b.ty(CTypeKind::UInt);
+        let read_only = Qualifiers { is_const: true, is_volatile: true, ..Qualifiers::default() };
+        let read_only_uint = CQualTypeId { qualifiers: read_only, ctype: uint };
+        let uint_ptr = b.ty(CTypeKind::Pointer(read_only_uint));
+        let status = b.var(1, 26, true, "status", qual(uint_ptr));
+
+        let ptr = b.rvalue(4, 6, status, qual(uint_ptr));
+        let deref = b.expr(4, 5, CExprKind::Unary(read_only_uint, c_ast::UnOp::Deref, ptr));
+        let one = b.int(4, 15, uint, 1);
+        let assign = b.expr(4, 5, CExprKind::Binary(read_only_uint, c_ast::BinOp::Assign, deref, one, None, None));
+
+        let t = b.translation(TranslationConfig::default());
+
+        let err = t.convert_expr(ExprUse::Unused, assign, false).unwrap_err();
+        assert_eq!(err, "regs.c:4:5: cannot write to a `const volatile` lvalue");
+    }
+
+    /// `const int *view; int *data;` in `view.c`, with `data = view` on line 3 and `view = data`
+    /// on line 4. Clang only warns about the first, so both are built by hand.
+    fn pointer_conversion_context() -> (Translation, CExprId, CExprId) {
+        let mut b = AstBuilder::new("view.c");
+        let int = b.ty(CTypeKind::Int);
+        let const_int = CQualTypeId { qualifiers: Qualifiers { is_const: true, ..Qualifiers::default() }, ctype: int };
+        let int_ptr = b.ty(CTypeKind::Pointer(qual(int)));
+        let const_int_ptr = b.ty(CTypeKind::Pointer(const_int));
+        let view = b.var(1, 12, false, "view", qual(const_int_ptr));
+        let data = b.var(1, 12, false, "data", qual(int_ptr));
+
+        let view_value = b.rvalue(3, 12, view, qual(const_int_ptr));
+        let discards = b.expr(3, 12, CExprKind::ImplicitCast(qual(int_ptr), view_value, CastKind::BitCast, None));
+        let data_value = b.rvalue(4, 12, data, qual(int_ptr));
+        let adds = b.expr(4, 12, CExprKind::ImplicitCast(qual(const_int_ptr), data_value, CastKind::BitCast, None));
+
+        (b.translation(TranslationConfig::default()), discards, adds)
+    }
+
+    #[test]
+    fn implicit_conversion_discards_const() {
+        let (t, discards, _) = pointer_conversion_context();
+
+        let source = match t.ast_context[discards].kind {
+            CExprKind::ImplicitCast(_, source, _, _) => source,
+            _ => unreachable!(),
+        };
+        let warning = t.discarded_qualifiers_warning(source, t.ast_context[discards].kind.get_type());
+        assert_eq!(warning.unwrap(), "view.c:3:12: warning: implicit pointer conversion discards `const` qualifier; translated as an explicit cast");
+        let translated = t.convert_expr(ExprUse::RValue, discards, false).unwrap();
+        assert_eq!(expr_to_string(&translated.val), "view as *mut libc::c_int");
+    }
+
+    #[test]
+    fn implicit_conversion_adds_const() {
+        let (t, _, adds) = pointer_conversion_context();
+
+        let translated = t.convert_expr(ExprUse::RValue, adds, false).unwrap();
+        assert_eq!(expr_to_string(&translated.val), "data as *const libc::c_int");
+    }
+
+    /// `struct lanes { v4si lanes[2]; int count; };`, with `v4si` a 16-byte vector of `int`s
+    #[test]
+    fn reject_vector_fields() {
+        let mut b = AstBuilder::new("simd.c");
+        let int = b.ty(CTypeKind::Int);
+        let v4si = b.ty(CTypeKind::Vector(int, 4));
+        let v4si_pair = b.ty(CTypeKind::ConstantArray(v4si, 2));
+        let lanes = b.decl(2, 10, CDeclKind::Field {
+            name: "lanes".to_string(),
+            typ: qual(v4si_pair),
+            bitfield_width: None,
+        });
+        let count = b.decl(3, 9, CDeclKind::Field {
+            name: "count".to_string(),
+            typ: qual(int),
+            bitfield_width: None,
+        });
+
+        let t = b.translation(TranslationConfig::default());
+
+        assert!(t.check_vector_layout(count, int).is_ok());
+        let err = t.check_vector_layout(lanes, v4si_pair).unwrap_err();
+        assert_eq!(err, "simd.c:2:10: cannot lay out vector field `lanes` as C does");
+    }
+
+    /// `typedef int wide_int __attribute__((aligned(8)));` in `counter.c`, and `struct counter {
+    /// int count; wide_int total; };` on lines 2 to 4
+    #[test]
+    fn reject_aligned_typedef_fields() {
+        let mut b = AstBuilder::new("counter.c");
+        let int = b.ty(CTypeKind::Int);
+        let wide_int = b.decl(1, 13, CDeclKind::Typedef {
+            name: "wide_int".to_string(),
+            typ: qual(int),
+            mode_width: None,
+            manual_alignment: Some(8),
+        });
+        let wide_int_ty = b.ty(CTypeKind::Typedef(wide_int));
+        let count = b.decl(3, 9, CDeclKind::Field {
+            name: "count".to_string(),
+            typ: qual(int),
+            bitfield_width: None,
+        });
+        let total = b.decl(4, 14, CDeclKind::Field {
+            name: "total".to_string(),
+            typ: qual(wide_int_ty),
+            bitfield_width: None,
+        });
+
+        let t = b.translation(TranslationConfig::default());
+
+        assert!(t.check_field_alignment(count, int).is_ok());
+        let err = t.check_field_alignment(total, wide_int_ty).unwrap_err();
+        assert_eq!(err, "counter.c:4:14: cannot align field `total` to 8 bytes");
+    }
+
+    /// `(unsigned __int128)-1`, `(__int128)-1`, and `(unsigned long long)-1` in `wide.c`
+    #[test]
+    fn wrap_constants_to_128_bits() {
+        let mut b = AstBuilder::new("wide.c");
+        let int = b.ty(CTypeKind::Int);
+        let int128 = b.ty(CTypeKind::Int128);
+        let uint128 = b.ty(CTypeKind::UInt128);
+        let ulonglong = b.ty(CTypeKind::ULongLong);
+
+        let one = b.int(1, 24, int, 1);
+        let minus_one = b.expr(1, 23, CExprKind::Unary(qual(int), c_ast::UnOp::Negate, one));
+        let cast = |ty| CExprKind::ExplicitCast(qual(ty), minus_one, CastKind::IntegralCast, None);
+        let to_uint128 = b.expr(1, 1, cast(uint128));
+        let to_int128 = b.expr(1, 1, cast(int128));
+        let to_ulonglong = b.expr(1, 1, cast(ulonglong));
+        let big = b.int(2, 1, uint128, u64::max_value());
+
+        assert_eq!(b.context.eval_const_int(to_int128), Some(-1));
+        assert_eq!(b.context.eval_const_int(to_ulonglong), Some(u64::max_value() as i128));
+        assert_eq!(b.context.eval_const_int(big), Some(u64::max_value() as i128));
+        // 2^128 - 1 doesn't fit the `i128` the values are computed in
+        assert_eq!(b.context.eval_const_int(to_uint128), None);
+    }
+
+    #[test]
+    fn reject_atomic_arithmetic_on_bool_and_pointers() {
+        let mut b = AstBuilder::new("atomics.c");
+        let boolean = b.ty(CTypeKind::Bool);
+        let int = b.ty(CTypeKind::Int);
+        let int_ptr = b.ty(CTypeKind::Pointer(qual(int)));
+        let t = b.translation(TranslationConfig::default());
+
+        let rmw = |ty, op| t.convert_atomic_rmw(mk().ident_expr("a"), ty, op, mk().ident_expr("v"), atomic_ordering("SeqCst"), false);
+        assert!(rmw(int, "add").is_ok());
+        assert!(rmw(boolean, "or").is_ok());
+        assert_eq!(rmw(boolean, "add").unwrap_err(), "Atomic `add` is not supported on `_Bool`");
+        assert_eq!(rmw(int_ptr, "sub").unwrap_err(), "Atomic `sub` is not supported on pointers");
+        assert_eq!(rmw(int_ptr, "or").unwrap_err(), "Atomic `or` is not supported on pointers");
+    }
+}
@@ -0,0 +1,460 @@
+//! Entry point for running the whole translation from other tools.
+//!
+//! `TranspileOptions` collects the settings the command line exposes, and `transpile` reads the
+//! CBOR files produced by the AST exporter, lowers them, and translates each one into a module:
+//!
+//! ```text
+//! let options = TranspileOptions::new()
+//!     .reloop_cfgs(true)
+//!     .signed_division(SignedDivision::Wrapping);
+//! for module in transpile(&options, &["foo.c.cbor", "bar.c.cbor"])? {
+//!     println!("// {}.rs\n{}", module.name, module.source);
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Cursor, Read, stdout};
+use std::path::Path;
+
+use cbor::Decoder;
+
+use c_ast::{ConversionContext, Printer, TypedAstContext};
+use cfg::CfgStructuring;
+use clang_ast::{process, AstContext};
+use stats;
+use translator::{exported_names, translate, SignedDivision, TranslationConfig};
+
+/// Settings for `transpile`. Each setter takes the builder by value so calls can be chained, and
+/// anything left unset keeps the default of the corresponding command line flag.
+#[derive(Debug, Clone)]
+pub struct TranspileOptions {
+    tcfg: TranslationConfig,
+    dump_untyped_context: bool,
+    dump_typed_context: bool,
+    pretty_typed_context: bool,
+}
+
+/// Setters for the `TranslationConfig` fields with the same names
+macro_rules! config_setters {
+    ($($(#[$doc:meta])* $field:ident: $ty:ty,)*) => {
+        $(
+            $(#[$doc])*
+            pub fn $field(mut self, $field: $ty) -> Self {
+                self.tcfg.$field = $field;
+                self
+            }
+        )*
+    }
+}
+
+impl TranspileOptions {
+    pub fn new() -> Self {
+        TranspileOptions {
+            tcfg: TranslationConfig {
+                use_c_loop_info: true,
+                use_c_multiple_info: true,
+                simplify_structures: true,
+                ..TranslationConfig::default()
+            },
+            dump_untyped_context: false,
+            dump_typed_context: false,
+            pretty_typed_context: false,
+        }
+    }
+
+    /// The configuration each translation unit is translated with
+    pub fn config(&self) -> &TranslationConfig {
+        &self.tcfg
+    }
+
+    config_setters! {
+        /// Translate function bodies using a CFG/Relooper approach
+        reloop_cfgs: bool,
+        /// Fail to translate if we ever need `current_block`
+        fail_on_multiple: bool,
+        /// Dump into files DOT visualizations of the CFGs of every function
+        dump_function_cfgs: bool,
+        /// Dump into files JSON visualizations of the CFGs of every function
+        json_function_cfgs: bool,
+        /// Include liveness information in the dumped CFGs
+        dump_cfg_liveness: bool,
+        /// Dump to stderr the intermediate structures produced by relooper
+        dump_structures: bool,
+        /// Generate readable `current_block` values in relooper
+        debug_relooper_labels: bool,
+        /// Enable cross-checks
+        cross_checks: bool,
+        /// Add a prefix to all function names
+        prefix_function_names: Option<String>,
+        /// Translate inline assembly without translating the assembly fragment
+        translate_asm: bool,
+        /// Create an entry point that calls the C `main` function
+        translate_entry: bool,
+        /// Keep and use information about C loops
+        use_c_loop_info: bool,
+        /// Keep and use information about C branches
+        use_c_multiple_info: bool,
+        /// Run a pass to simplify structures
+        simplify_structures: bool,
+        /// Algorithm used to turn CFGs back into structured control flow
+        cfg_structuring: CfgStructuring,
+        /// Panic on violated invariants or invalid code instead of emitting `compile_error!`
+        panic_on_translator_failure: bool,
+        /// Emit each translation unit as a module instead of a crate
+        emit_module: bool,
+        /// Fail to translate a module when a portion is not able to be translated
+        fail_on_error: bool,
+        /// Annotate pure and const functions, and functions returning restrict pointers, with
+        /// priors for the ownership analysis
+        ownership_hints: bool,
+        /// Outline parts of functions with more statements than this into helper functions
+        split_functions_over: Option<usize>,
+        /// How signed `/` and `%` handle the overflowing `INT_MIN / -1`
+        signed_division: SignedDivision,
+        /// Emit a `#[cfg(test)]` module comparing translated functions against the C originals
+        emit_differential_tests: bool,
+        /// Fuse `a * b + c` into `a.mul_add(b, c)` where `#pragma STDC FP_CONTRACT ON` allows it
+        contract_fp: bool,
+        /// Precede each function and record with a `// C: ...` comment giving its C signature
+        annotate_signatures: bool,
+        /// Report to stderr each place the translation depends on implementation-defined behavior
+        report_impl_defined: bool,
+        /// Check in debug builds that restrict pointer arguments don't alias other arguments
+        restrict_checks: bool,
+        /// Report the time spent in each phase of the translation to stderr
+        stats: bool,
+    }
+
+    /// Add a configuration file to the top-level `#[cross_check(...)]` attribute
+    pub fn cross_check_config<S: Into<String>>(mut self, path: S) -> Self {
+        self.tcfg.cross_check_configs.push(path.into());
+        self
+    }
+
+    /// Release records of type `record` through a `Drop` impl calling `destructor`
+    pub fn destructor<S: Into<String>, T: Into<String>>(mut self, record: S, destructor: T) -> Self {
+        self.tcfg.destructors.insert(record.into(), destructor.into());
+        self
+    }
+
+    /// Print the CBOR based Clang AST of each input to stdout
+    pub fn dump_untyped_ast(mut self, dump: bool) -> Self {
+        self.dump_untyped_context = dump;
+        self
+    }
+
+    /// Print the typed Clang AST of each input to stdout
+    pub fn dump_typed_ast(mut self, dump: bool) -> Self {
+        self.dump_typed_context = dump;
+        self
+    }
+
+    /// Pretty-print the typed Clang AST of each input to stdout
+    pub fn pretty_typed_ast(mut self, dump: bool) -> Self {
+        self.pretty_typed_context = dump;
+        self
+    }
+}
+
+impl Default for TranspileOptions {
+    fn default() -> Self {
+        TranspileOptions::new()
+    }
+}
+
+/// The translation of one input
+#[derive(Debug, Clone)]
+pub struct TranslatedModule {
+    /// Module name, ex: `foo` for `dir/foo.c.cbor`
+    pub name: String,
+    pub source: String,
+}
+
+/// Translate each CBOR file in `inputs`, in order. Symbols one input defines are imported from its
+/// module by the others.
+pub fn transpile<P: AsRef<Path>>(options: &TranspileOptions, inputs: &[P])
+                                 -> Result<Vec<TranslatedModule>, String> {
+    let show_stats = options.tcfg.stats;
+
+    let mut typed_contexts = vec![];
+    for input in inputs {
+        let input = input.as_ref();
+
+        // Extract the untyped AST from the CBOR file
+        let untyped_context = stats::time_phase(show_stats, "parse", || parse_untyped_ast(input))?;
+
+        if options.dump_untyped_context {
+            println!("CBOR Clang AST");
+            println!("{:#?}", untyped_context);
+        }
+
+        // An active `#error` fails the translation like it fails the compilation
+        untyped_context.check_diagnostics()?;
+
+        // Convert this into a typed AST
+        let typed_context = stats::time_phase(show_stats, "lower", || {
+            let mut conv = ConversionContext::new(&untyped_context);
+            conv.convert(&untyped_context);
+            conv.typed_context
+        });
+
+        if options.dump_typed_context {
+            println!("Clang AST");
+            println!("{:#?}", typed_context);
+        }
+
+        if options.pretty_typed_context {
+            println!("Pretty-printed Clang AST");
+            println!("{:#?}", Printer::new(stdout()).print(&typed_context));
+        }
+
+        typed_contexts.push((module_name(input), typed_context));
+    }
+
+    Ok(transpile_contexts(options, typed_contexts))
+}
+
+/// Translate already lowered translation units, each paired with its module name
+pub fn transpile_contexts(options: &TranspileOptions,
+                          typed_contexts: Vec<(String, TypedAstContext)>) -> Vec<TranslatedModule> {
+    // Record which module defines each exported symbol, and the Rust name it has there
+    let mut symbol_modules: HashMap<String, (String, String)> = HashMap::new();
+    for &(ref module, ref typed_context) in &typed_contexts {
+        for (symbol, name) in exported_names(typed_context.clone(), options.tcfg.clone()) {
+            symbol_modules.insert(symbol, (module.clone(), name));
+        }
+    }
+
+    typed_contexts.into_iter().map(|(module, typed_context)| {
+        let mut module_tcfg = options.tcfg.clone();
+        module_tcfg.extern_modules = symbol_modules
+            .iter()
+            .filter(|&(_, &(ref m, _))| *m != module)
+            .map(|(s, m)| (s.clone(), m.clone()))
+            .collect();
+
+        TranslatedModule {
+            source: translate(typed_context, module_tcfg),
+            name: module,
+        }
+    }).collect()
+}
+
+/// The module name of a translation unit, ex: `dir/foo.c.cbor` becomes `foo`. Characters that
+/// can't appear in an identifier, as in `foo-bar.c.cbor`, become `_`.
+fn module_name(path: &Path) -> String {
+    // Strip `.cbor`, then the extension of the source file
+    let source = Path::new(path.file_stem().unwrap());
+    let stem = source.file_stem().unwrap().to_string_lossy();
+    stem.chars().map(|c| if c.is_alphanumeric() || c == '_' { c } else { '_' }).collect()
+}
+
+fn parse_untyped_ast(path: &Path) -> Result<AstContext, String> {
+    let mut buffer = vec![];
+    File::open(path)
+        .and_then(|mut f| f.read_to_end(&mut buffer))
+        .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+
+    let mut cursor: Decoder<Cursor<Vec<u8>>> = Decoder::from_bytes(buffer);
+    process(cursor.items()).map_err(|e| format!("{:#?}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use c_ast::*;
+    use clang_ast::{ASTEntryTag, TypeTag};
+    use std::env;
+    use std::fs;
+    use std::io::Write;
+
+    /// The CBOR values the exporter writes
+    enum Item {
+        Unsigned(u64),
+        Bool(bool),
+        Text(&'static str),
+        Array(Vec<Item>),
+    }
+
+    fn encode(item: &Item, out: &mut Vec<u8>) {
+        // The major type, followed by the argument in the fewest bytes up to eight
+        fn head(major: u8, arg: u64, out: &mut Vec<u8>) {
+            if arg < 24 {
+                out.push(major << 5 | arg as u8);
+            } else {
+                out.push(major << 5 | 27);
+                out.extend((0..8).rev().map(|i| (arg >> (8 * i)) as u8));
+            }
+        }
+
+        match *item {
+            Item::Unsigned(n) => head(0, n, out),
+            Item::Bool(b) => out.push(if b { 0xf5 } else { 0xf4 }),
+            Item::Text(s) => {
+                head(3, s.len() as u64, out);
+                out.extend(s.bytes());
+            }
+            Item::Array(ref items) => {
+                head(4, items.len() as u64, out);
+                for item in items {
+                    encode(item, out);
+                }
+            }
+        }
+    }
+
+    /// The exporter's output for `int answer = 42;` in `answer.c`: the nodes, the top-level
+    /// declarations, the files, the comments, and the diagnostics
+    fn exported_answer() -> Vec<u8> {
+        use self::Item::*;
+
+        let (int, answer, init) = (8, 16, 24);
+        let nodes = Array(vec![
+            Array(vec![Unsigned(int), Unsigned(TypeTag::TagInt as u64)]),
+            Array(vec![
+                Unsigned(answer), Unsigned(ASTEntryTag::TagVarDecl as u64), Array(vec![Unsigned(init)]),
+                Unsigned(0), Unsigned(1), Unsigned(5), Unsigned(int),
+                Text("answer"), Bool(true), Bool(true), Bool(true), Unsigned(0),
+            ]),
+            Array(vec![
+                Unsigned(init), Unsigned(ASTEntryTag::TagIntegerLiteral as u64), Array(vec![]),
+                Unsigned(0), Unsigned(1), Unsigned(14), Unsigned(int),
+                Unsigned(42),
+            ]),
+        ]);
+
+        let mut out = vec![];
+        for item in &[nodes, Array(vec![Unsigned(answer)]), Array(vec![Text("answer.c")]),
+                      Array(vec![]), Array(vec![])] {
+            encode(item, &mut out);
+        }
+        out
+    }
+
+    /// `int answer = 42;` in `answer.c`
+    fn answer_context() -> TypedAstContext {
+        let mut context = TypedAstContext::new();
+        context.c_files.insert(0, "answer.c".to_string());
+
+        let int = CTypeId(1);
+        let qual_int = CQualTypeId { qualifiers: Qualifiers::default(), ctype: int };
+        let loc = Some(SrcLoc { fileid: 0, line: 1, column: 5 });
+        context.c_types.insert(int, Located { loc, kind: CTypeKind::Int });
+
+        let (answer, init) = (CDeclId(10), CExprId(20));
+        context.c_exprs.insert(init, Located { loc, kind: CExprKind::Literal(qual_int, CLiteral::Integer(42)) });
+        context.c_decls.insert(answer, Located { loc, kind: CDeclKind::Variable {
+            is_static: false,
+            is_extern: false,
+            is_defn: true,
+            ident: "answer".to_string(),
+            initializer: Some(init),
+            typ: qual_int,
+            manual_alignment: None,
+        }});
+        context.c_decls_top.push(answer);
+
+        context
+    }
+
+    /// `int type = 1;` in `defs.c` if `is_defn`, otherwise `extern int type;` in `uses.c`
+    fn keyword_context(is_defn: bool) -> TypedAstContext {
+        let mut context = TypedAstContext::new();
+        context.c_files.insert(0, if is_defn { "defs.c" } else { "uses.c" }.to_string());
+
+        let int = CTypeId(1);
+        let qual_int = CQualTypeId { qualifiers: Qualifiers::default(), ctype: int };
+        let loc = Some(SrcLoc { fileid: 0, line: 1, column: 5 });
+        context.c_types.insert(int, Located { loc, kind: CTypeKind::Int });
+
+        let (var, init) = (CDeclId(10), CExprId(20));
+        if is_defn {
+            context.c_exprs.insert(init, Located { loc, kind: CExprKind::Literal(qual_int, CLiteral::Integer(1)) });
+        }
+        context.c_decls.insert(var, Located { loc, kind: CDeclKind::Variable {
+            is_static: true,
+            is_extern: true,
+            is_defn,
+            ident: "type".to_string(),
+            initializer: if is_defn { Some(init) } else { None },
+            typ: qual_int,
+            manual_alignment: None,
+        }});
+        context.c_decls_top.push(var);
+
+        context
+    }
+
+    #[test]
+    fn module_names() {
+        assert_eq!(module_name(Path::new("dir/foo.c.cbor")), "foo");
+        assert_eq!(module_name(Path::new("dir.d/foo.c.cbor")), "foo");
+        assert_eq!(module_name(Path::new("foo.bar.c.cbor")), "foo_bar");
+        assert_eq!(module_name(Path::new("foo-bar.c.cbor")), "foo_bar");
+    }
+
+    #[test]
+    fn import_renamed_symbol() {
+        let options = TranspileOptions::new().emit_module(true);
+        let contexts = vec![
+            ("defs".to_string(), keyword_context(true)),
+            ("uses".to_string(), keyword_context(false)),
+        ];
+
+        // `type` is a keyword, so the module defining it renames it, and the other imports it
+        // under that name
+        let modules = transpile_contexts(&options, contexts);
+        assert!(modules[0].source.contains("static mut type_0: libc::c_int"), "{}", modules[0].source);
+        assert!(modules[1].source.contains("pub use super::defs::type_0;"), "{}", modules[1].source);
+    }
+
+    #[test]
+    fn options_set_config() {
+        let options = TranspileOptions::new()
+            .reloop_cfgs(true)
+            .signed_division(SignedDivision::Wrapping)
+            .split_functions_over(Some(50))
+            .destructor("list", "free_list");
+
+        let tcfg = options.config();
+        assert!(tcfg.reloop_cfgs);
+        assert!(tcfg.use_c_loop_info && tcfg.simplify_structures);
+        assert_eq!(tcfg.signed_division, SignedDivision::Wrapping);
+        assert_eq!(tcfg.split_functions_over, Some(50));
+        assert_eq!(tcfg.destructors["list"], "free_list");
+    }
+
+    #[test]
+    fn transpile_through_options() {
+        let crate_options = TranspileOptions::new();
+        let module_options = TranspileOptions::new().emit_module(true);
+
+        let modules = transpile_contexts(&crate_options, vec![("answer".to_string(), answer_context())]);
+        assert_eq!(modules.len(), 1);
+        assert_eq!(modules[0].name, "answer");
+        assert!(modules[0].source.contains("#![feature(libc)]"));
+        assert!(modules[0].source.contains("static mut answer: libc::c_int = 42"));
+
+        let modules = transpile_contexts(&module_options, vec![("answer".to_string(), answer_context())]);
+        assert!(!modules[0].source.contains("#![feature"));
+        assert!(modules[0].source.contains("static mut answer: libc::c_int = 42"));
+    }
+
+    #[test]
+    fn transpile_exported_file() {
+        let dir = env::temp_dir().join("ast-importer-transpile-exported-file");
+        fs::create_dir_all(&dir).unwrap();
+        let cbor = dir.join("answer.c.cbor");
+        File::create(&cbor).and_then(|mut f| f.write_all(&exported_answer())).unwrap();
+
+        let options = TranspileOptions::new().emit_module(true);
+        let modules = transpile(&options, &[&cbor]);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let modules = modules.unwrap();
+        assert_eq!(modules.len(), 1);
+        assert_eq!(modules[0].name, "answer");
+        assert!(modules[0].source.contains("static mut answer: libc::c_int = 42"), "{}", modules[0].source);
+    }
+}
@@ -1,8 +1,12 @@
 //! Analysis passes used to drive various transformations.
 
 use std::collections::HashSet;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::str::FromStr;
 
-use command::{Registry, DriverCommand};
+use command::{Registry, DriverCommand, FuncCommand, RefactorState};
 use driver::Phase;
 use util::IntoSymbol;
 
@@ -20,10 +24,211 @@ pub fn register_commands(reg: &mut Registry) {
         }))
     });
 
-    reg.register("test_analysis_ownership", |_args| {
+    reg.register("test_analysis_ownership", |args| {
+        let path = args.get(0).cloned();
+        let config = ownership::MonoFilterConfig::from_args(args.get(1..).unwrap_or(&[]))
+            .unwrap_or_else(|e| panic!("{}", e));
         Box::new(DriverCommand::new(Phase::Phase3, move |st, cx| {
-            let results = ownership::analyze(&st, &cx);
-            ownership::dump_results(&cx, &results);
+            let results = ownership::analyze_with_config(&st, &cx, &config);
+            match path {
+                Some(ref path) => {
+                    let mut f = File::create(path).unwrap();
+                    ownership::dump_results_to(&mut f, &cx, &results).unwrap();
+                },
+                None => ownership::dump_results(&cx, &results),
+            }
+        }))
+    });
+
+    reg.register("test_analysis_ownership_cached", |args| {
+        let cache_path = args.get(0).map_or("ownership_cache.json", |x| x).to_owned();
+        let path = args.get(1).cloned();
+        let log_path = args.get(2).cloned();
+        Box::new(DriverCommand::new(Phase::Phase3, move |st, cx| {
+            let mut cache = if Path::new(&cache_path).exists() {
+                ownership::cache::IntraCache::load(&cache_path).unwrap_or_else(|e| panic!("{}", e))
+            } else {
+                ownership::cache::IntraCache::new()
+            };
+
+            let results = ownership::analyze_with_cache(
+                &st, &cx, &ownership::MonoFilterConfig::default(), &mut cache);
+            cache.save(&cache_path).unwrap_or_else(|e| panic!("{}", e));
+
+            match path {
+                Some(ref path) => {
+                    let mut f = File::create(path).unwrap();
+                    ownership::dump_results_to(&mut f, &cx, &results).unwrap();
+                },
+                None => ownership::dump_results(&cx, &results),
+            }
+
+            if let Some(ref log_path) = log_path {
+                let mut f = File::create(log_path).unwrap();
+                for def_path in cache.analyzed() {
+                    writeln!(f, "analyzed {}", def_path).unwrap();
+                }
+                for def_path in cache.reused() {
+                    writeln!(f, "reused {}", def_path).unwrap();
+                }
+            }
+        }))
+    });
+
+    reg.register("test_analysis_ownership_fn", |args| {
+        let label = args.get(0).map_or("target", |x| x).into_symbol();
+        let path = args.get(1).cloned();
+        Box::new(DriverCommand::new(Phase::Phase3, move |st, cx| {
+            let mut ids = st.marks().iter()
+                .filter(|&&(_, l)| l == label)
+                .filter_map(|&(id, _)| cx.hir_map().opt_local_def_id(id))
+                .collect::<Vec<_>>();
+            ids.sort();
+
+            let mut out = Vec::new();
+            for def_id in ids {
+                let def_path = cx.ty_ctxt().def_path(def_id).to_string_no_crate();
+                match ownership::analyze_function(&st, &cx, def_id) {
+                    Some(fr) => {
+                        writeln!(out, "func {}: {}",
+                                 def_path, ownership::format_poly_sig(fr.sig)).unwrap();
+                        for &(a, b) in fr.cset.iter() {
+                            writeln!(out, "  {:?} <= {:?}", a, b).unwrap();
+                        }
+                    },
+                    None => writeln!(out, "func {}: not a local fn", def_path).unwrap(),
+                }
+            }
+
+            match path {
+                Some(ref path) => File::create(path).unwrap().write_all(&out).unwrap(),
+                None => info!("{}", String::from_utf8_lossy(&out)),
+            }
+        }))
+    });
+
+    reg.register("test_analysis_ownership_filtered", |args| {
+        let prefix = args.get(0).map_or("", |x| x).to_owned();
+        let path = args.get(1).cloned();
+        Box::new(DriverCommand::new(Phase::Phase3, move |st, cx| {
+            let include = ownership::include_by_path_prefix(&cx, &prefix);
+            let results = ownership::analyze_filtered(&st, &cx, &*include);
+            match path {
+                Some(ref path) => {
+                    let mut f = File::create(path).unwrap();
+                    ownership::dump_results_to(&mut f, &cx, &results).unwrap();
+                },
+                None => ownership::dump_results(&cx, &results),
+            }
+        }))
+    });
+
+    reg.register("ownership_foreign_summary", |args| {
+        let name = args.get(0).expect("usage: ownership_foreign_summary <name> <perms...>")
+            .to_owned();
+        let perms = args[1..].iter()
+            .map(|s| ownership::json::parse_perm(s))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap_or_else(|e| panic!("{}", e));
+        Box::new(FuncCommand(move |rs: &mut RefactorState| {
+            rs.foreign_summaries_mut().insert(name.clone(), perms.clone());
+        }))
+    });
+
+    reg.register("ownership_dump_json", |args| {
+        let path = args.get(0).map_or("ownership.json", |x| x).to_owned();
+        let config = ownership::MonoFilterConfig::from_args(args.get(1..).unwrap_or(&[]))
+            .unwrap_or_else(|e| panic!("{}", e));
+        Box::new(DriverCommand::new(Phase::Phase3, move |st, cx| {
+            let results = ownership::analyze_with_config(&st, &cx, &config);
+            let json = ownership::json::results_to_json(&cx, &results);
+            let mut f = File::create(&path).unwrap();
+            f.write_all(json.pretty(4).as_bytes()).unwrap();
+        }))
+    });
+
+    reg.register("test_analysis_ownership_json", |args| {
+        let path = args.get(0).map_or("ownership.json", |x| x).to_owned();
+        let config = ownership::MonoFilterConfig::from_args(args.get(1..).unwrap_or(&[]))
+            .unwrap_or_else(|e| panic!("{}", e));
+        Box::new(DriverCommand::new(Phase::Phase3, move |st, cx| {
+            let mut text = String::new();
+            File::open(&path).and_then(|mut f| f.read_to_string(&mut text))
+                .unwrap_or_else(|e| panic!("{}: {}", path, e));
+            let loaded = ownership::json::parse_results(&text)
+                .unwrap_or_else(|e| panic!("{}: {}", path, e));
+
+            let results = ownership::analyze_with_config(&st, &cx, &config);
+            let funcs = ownership::json::func_results(&cx, &results);
+            assert_eq!(loaded.len(), funcs.len(), "{}: wrong number of functions", path);
+            for (old, new) in loaded.iter().zip(funcs.iter()) {
+                assert_eq!(old, new, "{}: results differ for {}", path, new.def_path);
+            }
+        }))
+    });
+
+    reg.register("ownership_dump_dot", |args| {
+        let path = args.get(0).map_or("ownership.dot", |x| x).to_owned();
+        let config = ownership::MonoFilterConfig::from_args(args.get(1..).unwrap_or(&[]))
+            .unwrap_or_else(|e| panic!("{}", e));
+        Box::new(DriverCommand::new(Phase::Phase3, move |st, cx| {
+            let results = ownership::analyze_with_config(&st, &cx, &config);
+            let dot = ownership::dot::results_to_dot(&cx, &results);
+            let mut f = File::create(&path).unwrap();
+            f.write_all(dot.as_bytes()).unwrap();
+        }))
+    });
+
+    reg.register("ownership_explain", |args| {
+        let path = args.get(0).map_or("ownership_explain.txt", |x| x).to_owned();
+        let config = ownership::MonoFilterConfig::from_args(args.get(1..).unwrap_or(&[]))
+            .unwrap_or_else(|e| panic!("{}", e));
+        Box::new(DriverCommand::new(Phase::Phase3, move |st, cx| {
+            let results = ownership::analyze_with_provenance(&st, &cx, &config);
+            let text = ownership::explain::results_to_explanations(&cx, &results);
+            let mut f = File::create(&path).unwrap();
+            f.write_all(text.as_bytes()).unwrap();
+        }))
+    });
+
+    reg.register("ownership_diff", |args| {
+        let path = args.get(0).map_or("ownership_diff.txt", |x| x).to_owned();
+        let config = ownership::MonoFilterConfig::from_args(args.get(1..).unwrap_or(&[]))
+            .unwrap_or_else(|e| panic!("{}", e));
+        Box::new(DriverCommand::new(Phase::Phase3, move |st, cx| {
+            let old = ownership::analyze(&st, &cx);
+            let new = ownership::analyze_with_config(&st, &cx, &config);
+            let text = ownership::diff::diff_results(&cx, &old, &new);
+            let mut f = File::create(&path).unwrap();
+            f.write_all(text.as_bytes()).unwrap();
+        }))
+    });
+
+    reg.register("ownership_dead_monos", |args| {
+        let path = args.get(0).map_or("ownership_dead_monos.txt", |x| x).to_owned();
+        let config = ownership::MonoFilterConfig::from_args(args.get(1..).unwrap_or(&[]))
+            .unwrap_or_else(|e| panic!("{}", e));
+        Box::new(DriverCommand::new(Phase::Phase3, move |st, cx| {
+            let results = ownership::analyze_with_config(&st, &cx, &config);
+            let is_entry = ownership::default_entry_points(&cx);
+            let mut dead = ownership::find_dead_monos(&results, &*is_entry).into_iter()
+                .collect::<Vec<_>>();
+            dead.sort();
+
+            let mut f = File::create(&path).unwrap();
+            for (def_id, idx) in dead {
+                writeln!(f, "{} #{}", cx.ty_ctxt().def_path(def_id).to_string_no_crate(), idx)
+                    .unwrap();
+            }
+        }))
+    });
+
+    reg.register("test_constraint_minimize", |args| {
+        let cases = args.get(0).map_or(1000, |x| u32::from_str(x).unwrap());
+        Box::new(DriverCommand::new(Phase::Phase3, move |_st, cx| {
+            if let Err(msg) = ownership::constraint::check_minimize(cx.ty_arena(), cases) {
+                panic!("{}", msg);
+            }
         }))
     });
 
@@ -6,7 +6,9 @@ use std::collections::HashMap;
 use std::str::FromStr;
 
 use arena::DroplessArena;
+use rustc::hir;
 use rustc::hir::def_id::DefId;
+use rustc::ty::TypeVariants;
 use rustc_data_structures::indexed_vec::IndexVec;
 use syntax::ast;
 use syntax::symbol::Symbol;
@@ -150,6 +152,19 @@ impl<'ast> Visitor<'ast> for AttrVisitor<'ast> {
         visit::walk_impl_item(self, i);
     }
 
+    fn visit_foreign_item(&mut self, i: &'ast ast::ForeignItem) {
+        match i.node {
+            ast::ForeignItemKind::Fn(..) => {
+                if i.attrs.len() > 0 {
+                    self.def_attrs.push((i.id, &i.attrs));
+                }
+            },
+            _ => {},
+        }
+
+        visit::walk_foreign_item(self, i);
+    }
+
     fn visit_struct_field(&mut self, sf: &'ast ast::StructField) {
         if sf.attrs.len() > 0 {
             self.def_attrs.push((sf.id, &sf.attrs));
@@ -229,6 +244,69 @@ pub fn handle_attrs<'a, 'hir, 'tcx>(cx: &mut Ctxt<'a, 'tcx>,
                     func.monos_provided = true;
                 },
 
+                "ownership_pure" => {
+                    meta_item_word(&meta)
+                        .unwrap_or_else(|e| panic!("bad #[ownership_pure] for {:?}: {}",
+                                                   def_id, e));
+
+                    // A function without side effects can't write through any pointer it's
+                    // given, so every pointer in its arguments is at most READ.
+                    let sig = cx.variant_func_sig(def_id);
+                    let (_func, var) = cx.variant_summ(def_id);
+                    for &input in sig.inputs {
+                        input.for_each_label(&mut |p| {
+                            if let Some(p @ PermVar::Sig(_)) = *p {
+                                var.inst_cset.add(Perm::var(p), Perm::read());
+                            }
+                        });
+                    }
+                },
+
+                "ownership_restrict_return" => {
+                    meta_item_word(&meta)
+                        .unwrap_or_else(|e| panic!("bad #[ownership_restrict_return] for {:?}: {}",
+                                                   def_id, e));
+
+                    // Nothing else can reach the data behind a `restrict` return pointer, so it
+                    // doesn't borrow from the arguments.  This says nothing about what the caller
+                    // does with it, so the permissions are left to inference, and a pointer to
+                    // `const` data is not affected at all.
+                    let sig = cx.variant_func_sig(def_id);
+                    let is_mut_ptr = match sig.output.ty.sty {
+                        TypeVariants::TyRawPtr(tm) => tm.mutbl == hir::MutMutable,
+                        _ => false,
+                    };
+                    if is_mut_ptr {
+                        let (func, _var) = cx.variant_summ(def_id);
+                        func.restrict_return = true;
+                    }
+                },
+
+                "ownership_pin" => {
+                    let (idx, perm) = parse_pin(&meta)
+                        .unwrap_or_else(|e| panic!("bad #[ownership_pin] for {:?}: {}",
+                                                   def_id, e));
+
+                    // Bound the outermost pointer of the argument from both sides.  The
+                    // constraints become part of the function's signature constraints, so the
+                    // interprocedural phase passes them on to its callers.
+                    let sig = cx.variant_func_sig(def_id);
+                    let input = *sig.inputs.get(idx).unwrap_or_else(|| panic!(
+                            "bad #[ownership_pin] for {:?}: no argument {}", def_id, idx));
+                    let p = match input.label {
+                        Some(p @ PermVar::Sig(_)) => p,
+                        _ => panic!("bad #[ownership_pin] for {:?}: argument {} is not a pointer",
+                                    def_id, idx),
+                    };
+                    let (_func, var) = cx.variant_summ(def_id);
+                    if perm > ConcretePerm::Read {
+                        var.inst_cset.add(Perm::Concrete(perm), Perm::var(p));
+                    }
+                    if perm < ConcretePerm::Move {
+                        var.inst_cset.add(Perm::var(p), Perm::Concrete(perm));
+                    }
+                },
+
                 "ownership_static" => {
                     let assign = parse_static_assign(&meta)
                         .unwrap_or_else(|e| panic!("bad #[ownership_static] for {:?}: {}",
@@ -396,6 +474,26 @@ fn parse_static_assign(meta: &ast::MetaItem) -> Result<Vec<ConcretePerm>, &'stat
     Ok(assign)
 }
 
+fn parse_pin(meta: &ast::MetaItem) -> Result<(usize, ConcretePerm), &'static str> {
+    let args = meta_item_list(meta)?;
+    if args.len() != 2 {
+        return Err("expected an argument index and a permission in #[ownership_pin]");
+    }
+
+    let idx = match args[0].node {
+        ast::NestedMetaItemKind::Literal(ref lit) => {
+            match lit.node {
+                ast::LitKind::Int(i, _) => i as usize,
+                _ => return Err("expected argument index"),
+            }
+        },
+        _ => return Err("expected argument index"),
+    };
+    let perm = parse_concrete(nested_meta_item(&args[1])?)?;
+
+    Ok((idx, perm))
+}
+
 fn parse_variant_of(meta: &ast::MetaItem) -> Result<Symbol, &'static str> {
     let args = meta_item_list(meta)?;
     if args.len() != 1 {
@@ -0,0 +1,627 @@
+//! Cache of intraprocedural summaries, so that re-running the analysis after an edit only redoes
+//! `analyze_intra` for the items whose bodies changed.  The interprocedural steps always rerun,
+//! since the summaries of callees may have changed.
+//!
+//! Entries are keyed by def path and hold a stable hash of the item's optimized MIR, along with
+//! the summary computed from it.  Spans are hashed relative to the start of the body, so moving a
+//! function around in its file doesn't invalidate its entry, but editing inside it does, even if
+//! only a comment changes: the summary records the spans of calls.  The cache is saved as JSON:
+//!
+//! ```text
+//! {
+//!     "entries": [
+//!         {
+//!             "def_path": "::reset[0]",
+//!             "mir_hash": "3f0c9a1e27d4b865",
+//!             "preset": "[]",
+//!             "refs": [{ "path": "old::clear[0]", "ty": "...", "fn": true }],
+//!             "constraints": [["sig 0", "inst 0"], ["WRITE", "inst 0"]],
+//!             "insts": [{ "callee": 0, "span": [48, 53], "first_inst_var": 0 }],
+//!             ...
+//!         }
+//!     ]
+//! }
+//! ```
+//!
+//! A summary refers to other items (callees, statics, and struct fields) by path.  When an entry
+//! is reused, these are looked up again among the items its MIR mentions, and the entry is dropped
+//! if one is missing or its type has changed.  Static vars are recorded as `static I J`: the `J`th
+//! pointer in the type of `refs[I]`.
+//!
+//! Warnings printed by the intraprocedural step, such as for uses after `realloc`, only appear
+//! for the items that are analyzed again.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::hash::Hash;
+use std::io::{Read, Write};
+
+use json::{self, JsonValue};
+use rustc::hir::def_id::DefId;
+use rustc::mir::{Location, Mir, Static};
+use rustc::mir::visit::{PlaceContext, TyContext, Visitor};
+use rustc::ty::{Ty, TyCtxt, TypeVariants};
+use rustc_data_structures::stable_hasher::{HashStable, StableHasher};
+use syntax::codemap::Span;
+use syntax_pos::BytePos;
+
+use super::{ConcretePerm, PermVar, Var};
+use super::constraint::{ConstraintSet, Perm};
+use super::context::{Ctxt, Instantiation};
+use super::intra::FnSummary;
+use super::json::{parse_perm, perm_str, get_str, get_usize, get_array};
+
+
+/// A permission in a cached constraint.
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum PermJson {
+    Concrete(ConcretePerm),
+    /// The static var at the given position in the type of `refs[.0]`.
+    Static(usize, usize),
+    Sig(u32),
+    Inst(u32),
+    Min(Vec<PermJson>),
+}
+
+/// An item referred to by a cached summary.
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct RefJson {
+    /// Def path, including the crate name.
+    path: String,
+    /// Signature of a function, or type of anything else.  The entry is stale if this changes.
+    ty: String,
+    is_fn: bool,
+}
+
+/// A cached instantiation.  See `context::Instantiation`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct InstJson {
+    /// Index of the callee in `refs`.
+    callee: usize,
+    /// Span of the reference, relative to the start of the body.
+    span: Option<(u32, u32)>,
+    first_inst_var: u32,
+}
+
+/// The cached summary of one function variant or static initializer.
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct CacheEntry {
+    mir_hash: String,
+    /// Constraints set on the variant by annotations and marks before the body was analyzed.
+    /// They end up in the summary, so the entry is stale if they change.
+    preset: String,
+    refs: Vec<RefJson>,
+    constraints: Vec<(PermJson, PermJson)>,
+    insts: Vec<InstJson>,
+    nullable: Vec<u32>,
+    array_ptrs: Vec<u32>,
+    bound_args: Vec<usize>,
+    /// As in `FnSummary::alias_args`, but with callees as indices in `refs`.
+    alias_args: Vec<(usize, usize, usize)>,
+}
+
+/// Summaries saved by an earlier run of the analysis.
+pub struct IntraCache {
+    entries: HashMap<String, CacheEntry>,
+    /// The items looked up since `start_run`, and whether their entries were reused.
+    used: HashMap<String, bool>,
+}
+
+impl IntraCache {
+    pub fn new() -> IntraCache {
+        IntraCache {
+            entries: HashMap::new(),
+            used: HashMap::new(),
+        }
+    }
+
+    /// Read a cache saved with `save`.
+    pub fn load(path: &str) -> Result<IntraCache, String> {
+        let mut text = String::new();
+        File::open(path).and_then(|mut f| f.read_to_string(&mut text))
+            .map_err(|e| format!("{}: {}", path, e))?;
+        let doc = json::parse(&text).map_err(|e| format!("{}: {}", path, e))?;
+        IntraCache::from_json(&doc)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        File::create(path).and_then(|mut f| f.write_all(self.to_json().pretty(4).as_bytes()))
+            .map_err(|e| format!("{}: {}", path, e))
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        let mut paths = self.entries.keys().collect::<Vec<_>>();
+        paths.sort();
+        let entries = paths.into_iter()
+            .map(|path| self.entries[path].to_json(path))
+            .collect::<Vec<_>>();
+        object! {
+            "entries" => entries
+        }
+    }
+
+    pub fn from_json(doc: &JsonValue) -> Result<IntraCache, String> {
+        let mut cache = IntraCache::new();
+        for obj in get_array(doc, "entries")?.members() {
+            let path = get_str(obj, "def_path")?;
+            cache.entries.insert(path, CacheEntry::from_json(obj)?);
+        }
+        Ok(cache)
+    }
+
+    /// Forget which entries were used by the previous run.
+    pub fn start_run(&mut self) {
+        self.used.clear();
+    }
+
+    /// Drop the entries not used since `start_run`.  After a run over the whole crate, these
+    /// belong to items that no longer exist.
+    pub fn finish_run(&mut self) {
+        let used = &self.used;
+        self.entries.retain(|path, _| used.contains_key(path));
+    }
+
+    /// The items whose cached summaries were reused since `start_run`, sorted by def path.
+    pub fn reused(&self) -> Vec<String> {
+        self.used_paths(true)
+    }
+
+    /// The items analyzed again since `start_run`, sorted by def path.
+    pub fn analyzed(&self) -> Vec<String> {
+        self.used_paths(false)
+    }
+
+    fn used_paths(&self, reused: bool) -> Vec<String> {
+        let mut paths = self.used.iter()
+            .filter(|&(_, &r)| r == reused)
+            .map(|(path, _)| path.clone())
+            .collect::<Vec<_>>();
+        paths.sort();
+        paths
+    }
+
+    /// Get the summary of `def_id` from the cache if its MIR is unchanged, and otherwise compute
+    /// it with `analyze` and store it.
+    pub fn get_or_analyze<'a, 'tcx, F>(&mut self,
+                                       cx: &mut Ctxt<'a, 'tcx>,
+                                       def_id: DefId,
+                                       is_static: bool,
+                                       mir: &Mir<'tcx>,
+                                       analyze: F) -> FnSummary<'tcx>
+            where F: FnOnce(&mut Ctxt<'a, 'tcx>) -> FnSummary<'tcx> {
+        let path = cx.tcx.def_path(def_id).to_string_no_crate();
+        let mir_hash = mir_hash(cx.tcx, mir);
+        let preset = if is_static {
+            String::new()
+        } else {
+            let preset = cx.variant_summ(def_id).1.inst_cset.iter().collect::<Vec<_>>();
+            format!("{:?}", preset)
+        };
+
+        let cached = match self.entries.get(&path) {
+            Some(entry) if entry.mir_hash == mir_hash && entry.preset == preset =>
+                entry.decode(cx, def_id, mir),
+            _ => None,
+        };
+        if let Some(summ) = cached {
+            self.used.insert(path, true);
+            return summ;
+        }
+
+        let summ = analyze(cx);
+        match CacheEntry::encode(cx, mir, &summ, mir_hash, preset) {
+            Some(entry) => { self.entries.insert(path.clone(), entry); },
+            None => { self.entries.remove(&path); },
+        }
+        self.used.insert(path, false);
+        summ
+    }
+}
+
+
+/// Hash the MIR of a body.  Spans are hashed relative to the start of the body.
+fn mir_hash<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, mir: &Mir<'tcx>) -> String {
+    let mut hasher = StableHasher::<u64>::new();
+    let mut hcx = tcx.create_stable_hashing_context();
+    hcx.while_hashing_spans(false, |hcx| mir.hash_stable(hcx, &mut hasher));
+
+    let mut spans = SpanCollector { spans: Vec::new() };
+    spans.visit_mir(mir);
+    let base = mir.span.lo().0;
+    for span in spans.spans {
+        (span.lo().0.wrapping_sub(base), span.hi().0.wrapping_sub(base)).hash(&mut hasher);
+    }
+
+    format!("{:016x}", hasher.finish())
+}
+
+struct SpanCollector {
+    spans: Vec<Span>,
+}
+
+impl<'tcx> Visitor<'tcx> for SpanCollector {
+    fn visit_span(&mut self, span: &Span) {
+        self.spans.push(*span);
+    }
+}
+
+/// Collects the items a body may refer to: the functions it names, the statics it uses, and the
+/// fields of the structs it works with.
+struct RefCollector<'a, 'tcx: 'a> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    fns: Vec<DefId>,
+    others: Vec<DefId>,
+    seen_adts: Vec<DefId>,
+}
+
+impl<'a, 'tcx> RefCollector<'a, 'tcx> {
+    fn add_ty(&mut self, ty: Ty<'tcx>) {
+        for ty in ty.walk() {
+            match ty.sty {
+                TypeVariants::TyFnDef(def_id, _) => self.fns.push(def_id),
+                TypeVariants::TyAdt(adt, _) => {
+                    if self.seen_adts.contains(&adt.did) {
+                        continue;
+                    }
+                    self.seen_adts.push(adt.did);
+                    for variant in &adt.variants {
+                        for field in &variant.fields {
+                            self.others.push(field.did);
+                            let field_ty = self.tcx.type_of(field.did);
+                            self.add_ty(field_ty);
+                        }
+                    }
+                },
+                _ => {},
+            }
+        }
+    }
+}
+
+impl<'a, 'tcx> Visitor<'tcx> for RefCollector<'a, 'tcx> {
+    fn visit_ty(&mut self, ty: &Ty<'tcx>, _: TyContext) {
+        self.add_ty(*ty);
+    }
+
+    fn visit_static(&mut self,
+                    static_: &Static<'tcx>,
+                    context: PlaceContext<'tcx>,
+                    location: Location) {
+        self.others.push(static_.def_id);
+        self.super_static(static_, context, location);
+    }
+}
+
+fn ref_path<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, def_id: DefId) -> String {
+    format!("{}{}", tcx.crate_name(def_id.krate), tcx.def_path(def_id).to_string_no_crate())
+}
+
+fn ref_ty<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, def_id: DefId, is_fn: bool) -> String {
+    if is_fn { format!("{:?}", tcx.fn_sig(def_id)) }
+    else { format!("{:?}", tcx.type_of(def_id)) }
+}
+
+/// The static vars in the type of a static or field, in preorder.
+fn static_vars<'a, 'tcx>(cx: &mut Ctxt<'a, 'tcx>, def_id: DefId) -> Vec<Var> {
+    let mut vars = Vec::new();
+    cx.static_ty(def_id).for_each_label(&mut |l| {
+        if let Some(PermVar::Static(v)) = *l {
+            vars.push(v);
+        }
+    });
+    vars
+}
+
+/// The items referred to by an entry being built, each with its index in `refs`.
+struct RefTable<'a, 'tcx: 'a> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    refs: Vec<RefJson>,
+    idxs: HashMap<DefId, usize>,
+}
+
+impl<'a, 'tcx> RefTable<'a, 'tcx> {
+    fn add(&mut self, def_id: DefId, is_fn: bool) -> usize {
+        let tcx = self.tcx;
+        let refs = &mut self.refs;
+        *self.idxs.entry(def_id).or_insert_with(|| {
+            refs.push(RefJson {
+                path: ref_path(tcx, def_id),
+                ty: ref_ty(tcx, def_id, is_fn),
+                is_fn: is_fn,
+            });
+            refs.len() - 1
+        })
+    }
+}
+
+fn encode_perm<'a, 'tcx>(p: Perm<'tcx>,
+                         static_locs: &HashMap<Var, (DefId, usize)>,
+                         refs: &mut RefTable<'a, 'tcx>) -> Option<PermJson> {
+    Some(match p {
+        Perm::Concrete(c) => PermJson::Concrete(c),
+        Perm::Min(ps) => PermJson::Min(ps.iter()
+            .map(|&p| encode_perm(p, static_locs, refs))
+            .collect::<Option<Vec<_>>>()?),
+        Perm::StaticVar(v) => {
+            let (def_id, pos) = *static_locs.get(&v)?;
+            PermJson::Static(refs.add(def_id, false), pos)
+        },
+        Perm::SigVar(v) => PermJson::Sig(v.0),
+        Perm::InstVar(v) => PermJson::Inst(v.0),
+        // Local vars are gone from finished summaries.
+        Perm::LocalVar(_) => return None,
+    })
+}
+
+fn decode_perm<'a, 'tcx>(cx: &mut Ctxt<'a, 'tcx>,
+                         p: &PermJson,
+                         refs: &[DefId]) -> Option<Perm<'tcx>> {
+    Some(match *p {
+        PermJson::Concrete(c) => Perm::Concrete(c),
+        PermJson::Min(ref ps) => {
+            let mut perms = Vec::with_capacity(ps.len());
+            for p in ps {
+                perms.push(decode_perm(cx, p, refs)?);
+            }
+            if perms.len() == 0 { Perm::Min(&[]) }
+            else { Perm::Min(cx.arena.alloc_slice(&perms)) }
+        },
+        PermJson::Static(idx, pos) => {
+            let def_id = *refs.get(idx)?;
+            Perm::StaticVar(*static_vars(cx, def_id).get(pos)?)
+        },
+        PermJson::Sig(v) => Perm::SigVar(Var(v)),
+        PermJson::Inst(v) => Perm::InstVar(Var(v)),
+    })
+}
+
+impl CacheEntry {
+    /// Build the entry for `summ`.  Returns `None` if some part of it can't be cached.
+    fn encode<'a, 'tcx>(cx: &mut Ctxt<'a, 'tcx>,
+                        mir: &Mir<'tcx>,
+                        summ: &FnSummary<'tcx>,
+                        mir_hash: String,
+                        preset: String) -> Option<CacheEntry> {
+        // Find the position of each static var in its type.  The vars shared by the fields of a
+        // union can go with any of them.
+        let mut static_locs = HashMap::new();
+        let mut static_ids = cx.static_summ.keys().cloned().collect::<Vec<_>>();
+        static_ids.sort();
+        for def_id in static_ids {
+            for (pos, v) in static_vars(cx, def_id).into_iter().enumerate() {
+                static_locs.entry(v).or_insert((def_id, pos));
+            }
+        }
+
+        let mut refs = RefTable {
+            tcx: cx.tcx,
+            refs: Vec::new(),
+            idxs: HashMap::new(),
+        };
+
+        let mut constraints = Vec::new();
+        for &(a, b) in summ.inst_cset.iter() {
+            constraints.push((encode_perm(a, &static_locs, &mut refs)?,
+                              encode_perm(b, &static_locs, &mut refs)?));
+        }
+
+        let base = mir.span;
+        let insts = summ.insts.iter().map(|inst| {
+            // Spans from macros, or outside the body, are dropped.
+            let span = inst.span.and_then(|s| {
+                if s.ctxt() == base.ctxt() && s.lo() >= base.lo() && s.hi() <= base.hi() {
+                    Some(((s.lo() - base.lo()).0, (s.hi() - base.lo()).0))
+                } else {
+                    None
+                }
+            });
+            InstJson {
+                callee: refs.add(inst.callee, true),
+                span: span,
+                first_inst_var: inst.first_inst_var,
+            }
+        }).collect();
+
+        let alias_args = summ.alias_args.iter()
+            .map(|&(callee, i, j)| (refs.add(callee, true), i, j))
+            .collect();
+
+        Some(CacheEntry {
+            mir_hash: mir_hash,
+            preset: preset,
+            refs: refs.refs,
+            constraints: constraints,
+            insts: insts,
+            nullable: summ.nullable.iter().map(|v| v.0).collect(),
+            array_ptrs: summ.array_ptrs.iter().map(|v| v.0).collect(),
+            bound_args: summ.bound_args.clone(),
+            alias_args: alias_args,
+        })
+    }
+
+    /// Rebuild the summary of `def_id` from this entry.  Returns `None` if some item it refers to
+    /// can no longer be found or has changed type.
+    fn decode<'a, 'tcx>(&self,
+                        cx: &mut Ctxt<'a, 'tcx>,
+                        def_id: DefId,
+                        mir: &Mir<'tcx>) -> Option<FnSummary<'tcx>> {
+        let tcx = cx.tcx;
+
+        let mut collector = RefCollector {
+            tcx: tcx,
+            fns: Vec::new(),
+            others: vec![def_id],
+            seen_adts: Vec::new(),
+        };
+        collector.visit_mir(mir);
+
+        let mut by_path = HashMap::new();
+        for &did in &collector.others {
+            by_path.insert(ref_path(tcx, did), did);
+        }
+        for &did in &collector.fns {
+            // Instantiations refer to the function, not the variant the body names.
+            let func_id = cx.variant_summ(did).1.func_id;
+            by_path.insert(ref_path(tcx, did), did);
+            by_path.insert(ref_path(tcx, func_id), func_id);
+        }
+
+        let mut refs = Vec::with_capacity(self.refs.len());
+        for r in &self.refs {
+            let did = *by_path.get(&r.path)?;
+            if ref_ty(tcx, did, r.is_fn) != r.ty {
+                return None;
+            }
+            refs.push(did);
+        }
+
+        let mut inst_cset = ConstraintSet::new();
+        for &(ref a, ref b) in &self.constraints {
+            let a = decode_perm(cx, a, &refs)?;
+            let b = decode_perm(cx, b, &refs)?;
+            inst_cset.add(a, b);
+        }
+
+        let base = mir.span;
+        let mut insts = Vec::with_capacity(self.insts.len());
+        for inst in &self.insts {
+            insts.push(Instantiation {
+                callee: *refs.get(inst.callee)?,
+                span: inst.span.map(|(lo, hi)| {
+                    base.with_lo(base.lo() + BytePos(lo)).with_hi(base.lo() + BytePos(hi))
+                }),
+                first_inst_var: inst.first_inst_var,
+            });
+        }
+
+        let mut alias_args = Vec::with_capacity(self.alias_args.len());
+        for &(idx, i, j) in &self.alias_args {
+            alias_args.push((*refs.get(idx)?, i, j));
+        }
+
+        Some(FnSummary {
+            inst_cset: inst_cset,
+            insts: insts,
+            nullable: self.nullable.iter().map(|&v| Var(v)).collect(),
+            array_ptrs: self.array_ptrs.iter().map(|&v| Var(v)).collect(),
+            bound_args: self.bound_args.clone(),
+            alias_args: alias_args,
+        })
+    }
+
+    fn to_json(&self, path: &str) -> JsonValue {
+        let refs = self.refs.iter().map(|r| object! {
+            "path" => r.path.clone(),
+            "ty" => r.ty.clone(),
+            "fn" => r.is_fn
+        }).collect::<Vec<_>>();
+        let constraints = self.constraints.iter()
+            .map(|&(ref a, ref b)| JsonValue::from(vec![perm_to_json(a), perm_to_json(b)]))
+            .collect::<Vec<_>>();
+        let insts = self.insts.iter().map(|inst| object! {
+            "callee" => inst.callee,
+            "span" => inst.span.map(|(lo, hi)| vec![lo, hi]),
+            "first_inst_var" => inst.first_inst_var
+        }).collect::<Vec<_>>();
+        let alias_args = self.alias_args.iter()
+            .map(|&(callee, i, j)| vec![callee, i, j])
+            .collect::<Vec<_>>();
+        object! {
+            "def_path" => path,
+            "mir_hash" => self.mir_hash.clone(),
+            "preset" => self.preset.clone(),
+            "refs" => refs,
+            "constraints" => constraints,
+            "insts" => insts,
+            "nullable" => self.nullable.clone(),
+            "array_ptrs" => self.array_ptrs.clone(),
+            "bound_args" => self.bound_args.clone(),
+            "alias_args" => alias_args
+        }
+    }
+
+    fn from_json(obj: &JsonValue) -> Result<CacheEntry, String> {
+        let refs = get_array(obj, "refs")?.members().map(|r| {
+            Ok(RefJson {
+                path: get_str(r, "path")?,
+                ty: get_str(r, "ty")?,
+                is_fn: r["fn"].as_bool().ok_or_else(|| "expected bool field `fn`".to_owned())?,
+            })
+        }).collect::<Result<Vec<_>, String>>()?;
+
+        let constraints = get_array(obj, "constraints")?.members().map(|c| {
+            if c.len() != 2 {
+                return Err(format!("expected a pair of permissions, got {}", c));
+            }
+            Ok((perm_from_json(&c[0])?, perm_from_json(&c[1])?))
+        }).collect::<Result<Vec<_>, String>>()?;
+
+        let insts = get_array(obj, "insts")?.members().map(|inst| {
+            let span = if inst["span"].is_null() {
+                None
+            } else {
+                Some((get_index(&inst["span"][0])?, get_index(&inst["span"][1])?))
+            };
+            Ok(InstJson {
+                callee: get_usize(inst, "callee")?,
+                span: span,
+                first_inst_var: get_index(&inst["first_inst_var"])?,
+            })
+        }).collect::<Result<Vec<_>, String>>()?;
+
+        let alias_args = get_array(obj, "alias_args")?.members().map(|a| {
+            let idx = |i: usize| a[i].as_usize()
+                .ok_or_else(|| format!("expected a triple of indices, got {}", a));
+            Ok((idx(0)?, idx(1)?, idx(2)?))
+        }).collect::<Result<Vec<_>, String>>()?;
+
+        Ok(CacheEntry {
+            mir_hash: get_str(obj, "mir_hash")?,
+            preset: get_str(obj, "preset")?,
+            refs: refs,
+            constraints: constraints,
+            insts: insts,
+            nullable: get_indices(obj, "nullable")?,
+            array_ptrs: get_indices(obj, "array_ptrs")?,
+            bound_args: get_indices(obj, "bound_args")?
+                .into_iter().map(|i| i as usize).collect(),
+            alias_args: alias_args,
+        })
+    }
+}
+
+fn perm_to_json(p: &PermJson) -> JsonValue {
+    match *p {
+        PermJson::Concrete(c) => perm_str(c).into(),
+        PermJson::Static(idx, pos) => format!("static {} {}", idx, pos).into(),
+        PermJson::Sig(v) => format!("sig {}", v).into(),
+        PermJson::Inst(v) => format!("inst {}", v).into(),
+        PermJson::Min(ref ps) => JsonValue::from(ps.iter().map(perm_to_json).collect::<Vec<_>>()),
+    }
+}
+
+fn perm_from_json(obj: &JsonValue) -> Result<PermJson, String> {
+    if obj.is_array() {
+        return Ok(PermJson::Min(obj.members().map(perm_from_json)
+                                .collect::<Result<Vec<_>, _>>()?));
+    }
+
+    let s = obj.as_str().ok_or_else(|| format!("expected permission, got {}", obj))?;
+    let words = s.split_whitespace().collect::<Vec<_>>();
+    let num = |i: usize| words.get(i).and_then(|w| w.parse::<u32>().ok())
+        .ok_or_else(|| format!("bad permission `{}`", s));
+    match (words.get(0).cloned().unwrap_or(""), words.len()) {
+        ("static", 3) => Ok(PermJson::Static(num(1)? as usize, num(2)? as usize)),
+        ("sig", 2) => Ok(PermJson::Sig(num(1)?)),
+        ("inst", 2) => Ok(PermJson::Inst(num(1)?)),
+        _ => Ok(PermJson::Concrete(parse_perm(s)?)),
+    }
+}
+
+fn get_index(obj: &JsonValue) -> Result<u32, String> {
+    obj.as_u32().ok_or_else(|| format!("expected integer, got {}", obj))
+}
+
+fn get_indices(obj: &JsonValue, key: &str) -> Result<Vec<u32>, String> {
+    get_array(obj, key)?.members().map(get_index).collect()
+}
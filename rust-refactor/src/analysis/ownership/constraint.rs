@@ -2,10 +2,11 @@
 use std::cmp;
 use std::collections::Bound;
 use std::collections::btree_set::{self, BTreeSet};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::collections::VecDeque;
 
 use arena::DroplessArena;
+use syntax::codemap::Span;
 
 use super::{ConcretePerm, PermVar, Var};
 
@@ -198,16 +199,39 @@ impl<'tcx> Perm<'tcx> {
 }
 
 
+/// A source-level statement that gave rise to a constraint.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Origin {
+    pub span: Span,
+    /// The source text of the statement, or its MIR if the source is unavailable.
+    pub desc: String,
+}
+
 /// A set of constraints over permission expressions, of the form `p1 <= p2`.
 ///
 /// Note that most of the more complex operations are imprecise (unsound) in certain cases
 /// involving `Min`.  Fortunately, these cases seem not to come up often in practice.
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, Debug)]
 pub struct ConstraintSet<'tcx> {
     less: BTreeSet<(Perm<'tcx>, Perm<'tcx>)>,
     greater: BTreeSet<(Perm<'tcx>, Perm<'tcx>)>,
+
+    /// The statements each constraint comes from, when tracking provenance (see
+    /// `track_provenance`).  Constraints derived from others by simplification inherit their
+    /// origins.  Entries of removed constraints are kept, since simplification may still derive
+    /// new constraints from them.
+    origins: Option<HashMap<(Perm<'tcx>, Perm<'tcx>), Vec<Origin>>>,
 }
 
+// Provenance doesn't affect the meaning of a constraint set.
+impl<'tcx> PartialEq for ConstraintSet<'tcx> {
+    fn eq(&self, other: &ConstraintSet<'tcx>) -> bool {
+        self.less == other.less
+    }
+}
+
+impl<'tcx> Eq for ConstraintSet<'tcx> {}
+
 /// Return a pair of bounds, suitable for use with `BTreeSet::range`, covering all pairs of
 /// permissions whose first element is `p`.
 fn perm_range(p: Perm) -> (Bound<(Perm, Perm)>, Bound<(Perm, Perm)>) {
@@ -220,6 +244,49 @@ impl<'tcx> ConstraintSet<'tcx> {
         ConstraintSet {
             less: BTreeSet::new(),
             greater: BTreeSet::new(),
+            origins: None,
+        }
+    }
+
+    /// An empty constraint set, which tracks provenance if `self` does.
+    fn new_like(&self) -> ConstraintSet<'tcx> {
+        let mut cset = ConstraintSet::new();
+        if self.origins.is_some() {
+            cset.track_provenance();
+        }
+        cset
+    }
+
+    /// Start recording the origins of constraints added from now on.  This is off by default,
+    /// since the origins take far more memory than the constraints themselves.
+    pub fn track_provenance(&mut self) {
+        if self.origins.is_none() {
+            self.origins = Some(HashMap::new());
+        }
+    }
+
+    pub fn tracks_provenance(&self) -> bool {
+        self.origins.is_some()
+    }
+
+    /// The origins recorded for the constraint `a <= b`.
+    pub fn origins_of(&self, a: Perm<'tcx>, b: Perm<'tcx>) -> &[Origin] {
+        self.origins.as_ref()
+            .and_then(|origins| origins.get(&(a, b)))
+            .map_or(&[][..], |v| &v[..])
+    }
+
+    fn add_origins(&mut self, a: Perm<'tcx>, b: Perm<'tcx>, new: &[Origin]) {
+        if new.len() == 0 {
+            return;
+        }
+        if let Some(ref mut origins) = self.origins {
+            let entry = origins.entry((a, b)).or_insert_with(Vec::new);
+            for origin in new {
+                if !entry.contains(origin) {
+                    entry.push(origin.clone());
+                }
+            }
         }
     }
 
@@ -232,6 +299,12 @@ impl<'tcx> ConstraintSet<'tcx> {
         self.greater.insert((b, a));
     }
 
+    /// Add a constraint, recording that it comes from `origin` if tracking provenance.
+    pub fn add_with_origin(&mut self, a: Perm<'tcx>, b: Perm<'tcx>, origin: Origin) {
+        self.add(a, b);
+        self.add_origins(a, b, &[origin]);
+    }
+
     /// Add all constraints from `other` to `self`.
     pub fn import(&mut self, other: &ConstraintSet<'tcx>) {
         debug!("IMPORT {} constraints", other.less.len());
@@ -240,6 +313,12 @@ impl<'tcx> ConstraintSet<'tcx> {
             true
         }));
         self.greater.extend(other.greater.iter().cloned());
+
+        if self.tracks_provenance() {
+            for &(a, b) in other.less.iter() {
+                self.add_origins(a, b, other.origins_of(a, b));
+            }
+        }
     }
 
     /// For each constraint in `other`, substitute all atomic permissions using the callback `f`,
@@ -272,6 +351,7 @@ impl<'tcx> ConstraintSet<'tcx> {
             debug!("IMPORT CONSTRANT: {:?} <= {:?} (substituted from {:?} <= {:?})",
                    a2, b2, a, b);
             self.add(a2, b2);
+            self.add_origins(a2, b2, other.origins_of(a, b));
         }
     }
 
@@ -280,7 +360,7 @@ impl<'tcx> ConstraintSet<'tcx> {
                                 arena: &'tcx DroplessArena,
                                 f: F) -> ConstraintSet<'tcx>
             where F: Fn(Perm<'tcx>) -> Perm<'tcx> {
-        let mut new_cset = ConstraintSet::new();
+        let mut new_cset = self.new_like();
         new_cset.import_substituted(self, arena, f);
         new_cset
     }
@@ -379,6 +459,67 @@ impl<'tcx> ConstraintSet<'tcx> {
         bound
     }
 
+    /// Explain `lower_bound(p)`: collect the origins of the constraints on the chains leading up
+    /// to `p` from that bound, nearest to `p` first.  The result is empty if the bound is READ or
+    /// provenance is not tracked.
+    pub fn explain(&self, p: Perm<'tcx>) -> Vec<Origin> {
+        let lower = self.lower_bound(p);
+        if !self.tracks_provenance() || lower == ConcretePerm::Read {
+            return Vec::new();
+        }
+
+        // Collect the constraints `q <= r` below `p`, breadth-first.
+        let mut edges = Vec::new();
+        let mut bounded = HashSet::new();
+        let mut seen = HashSet::new();
+        let mut queue = VecDeque::new();
+        seen.insert(p);
+        queue.push_back(p);
+        while let Some(cur) = queue.pop_front() {
+            for &(_, q) in self.greater.range(perm_range(cur)) {
+                edges.push((q, cur));
+                match q {
+                    Perm::Concrete(c) => {
+                        if c >= lower {
+                            bounded.insert(q);
+                        }
+                    },
+                    _ => {
+                        if seen.insert(q) {
+                            queue.push_back(q);
+                        }
+                    },
+                }
+            }
+        }
+
+        // Keep only those that carry the bound.
+        loop {
+            let old_len = bounded.len();
+            for &(q, r) in &edges {
+                if bounded.contains(&q) {
+                    bounded.insert(r);
+                }
+            }
+            if bounded.len() == old_len {
+                break;
+            }
+        }
+
+        let mut result: Vec<Origin> = Vec::new();
+        for &(q, r) in &edges {
+            if !bounded.contains(&q) {
+                continue;
+            }
+            for origin in self.origins_of(q, r) {
+                if !result.contains(origin) {
+                    result.push(origin.clone());
+                }
+            }
+        }
+        result
+    }
+
     /// Given an assignment of concrete permission values to a subset of the variables, check
     /// whether any constraints are violated under the partial assignment.  Returns `false` if a
     /// constraint is violated, or `true` if all constraints appear to be satisfiable.
@@ -460,6 +601,47 @@ impl<'tcx> ConstraintSet<'tcx> {
             b.for_each_atom(&mut f);
         }
     }
+
+    /// Iterate over the constraints `lhs <= rhs` as pairs of `PermExpr`s, which don't borrow from
+    /// the analysis, for feeding them to an external solver.
+    pub fn constraints<'a>(&'a self) -> Constraints<'a, 'tcx> {
+        Constraints { inner: self.less.iter() }
+    }
+}
+
+/// A permission expression detached from the analysis arena.  This is the public form of `Perm`
+/// produced by `ConstraintSet::constraints`.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
+pub enum PermExpr {
+    /// A concrete permission.
+    Concrete(ConcretePerm),
+    /// A permission variable.
+    Var(PermVar),
+    /// The minimum of some set of atomic permissions.
+    Min(Vec<PermExpr>),
+}
+
+impl<'tcx> From<Perm<'tcx>> for PermExpr {
+    fn from(p: Perm<'tcx>) -> PermExpr {
+        match p {
+            Perm::Concrete(c) => PermExpr::Concrete(c),
+            Perm::Min(ps) => PermExpr::Min(ps.iter().map(|&p| PermExpr::from(p)).collect()),
+            _ => PermExpr::Var(p.as_var().unwrap()),
+        }
+    }
+}
+
+/// Iterator over the constraints of a `ConstraintSet`.  See `ConstraintSet::constraints`.
+pub struct Constraints<'a, 'tcx: 'a> {
+    inner: btree_set::Iter<'a, (Perm<'tcx>, Perm<'tcx>)>,
+}
+
+impl<'a, 'tcx> Iterator for Constraints<'a, 'tcx> {
+    type Item = (PermExpr, PermExpr);
+
+    fn next(&mut self) -> Option<(PermExpr, PermExpr)> {
+        self.inner.next().map(|&(a, b)| (PermExpr::from(a), PermExpr::from(b)))
+    }
 }
 
 /// Editing cursor, for visiting every constraint while adding/removing as you go.
@@ -502,6 +684,18 @@ impl<'a, 'tcx> EditConstraintSet<'a, 'tcx> {
         self.cset.greater.insert((b, a));
     }
 
+    /// Record that the constraint `a <= b` follows from the constraints `sources`, so it inherits
+    /// their origins.
+    pub fn derive_origins(&mut self, a: Perm<'tcx>, b: Perm<'tcx>, sources: &[(Perm<'tcx>, Perm<'tcx>)]) {
+        if !self.cset.tracks_provenance() {
+            return;
+        }
+        let origins = sources.iter()
+            .flat_map(|&(x, y)| self.cset.origins_of(x, y).iter().cloned())
+            .collect::<Vec<_>>();
+        self.cset.add_origins(a, b, &origins);
+    }
+
     /// Remove a constraint.
     pub fn remove(&mut self, a: Perm<'tcx>, b: Perm<'tcx>) {
         self.cset.less.remove(&(a, b));
@@ -541,6 +735,7 @@ impl<'tcx> ConstraintSet<'tcx> {
                     edit.remove(a, b);
                     for &p in ps {
                         edit.add(a, p);
+                        edit.derive_origins(a, p, &[(a, b)]);
                     }
                 },
                 _ => {},
@@ -625,6 +820,7 @@ impl<'tcx> ConstraintSet<'tcx> {
                     .filter(|&(i, _)| !to_remove.contains(&i)).next().unwrap();
                 debug!("replace {:?} <= {:?} with {:?} <= {:?}", a, b, p, b);
                 edit.add(p, b);
+                edit.derive_origins(p, b, &[(a, b)]);
             } else if to_remove.len() > 0 {
                 edit.remove(a, b);
                 let ps = ps.iter().cloned().enumerate()
@@ -633,6 +829,7 @@ impl<'tcx> ConstraintSet<'tcx> {
                 let new_min = Perm::Min(arena.alloc_slice(&ps));
                 debug!("replace {:?} <= {:?} with {:?} <= {:?}", a, b, new_min, b);
                 edit.add(new_min, b);
+                edit.derive_origins(new_min, b, &[(a, b)]);
             }
             // Otherwise, to_remove == 0, meaning we don't have any changes to apply.
         }
@@ -699,10 +896,24 @@ impl<'tcx> ConstraintSet<'tcx> {
                 // and only delete the whole constraint if the `Min` winds up empty.
                 debug!("  remove {:?} <= {:?}", a, b);
                 edit.remove(a, b);
-                a.for_each_replacement(arena, p, &less, |a| {
-                    b.for_each_replacement(arena, p, &greater, |b| {
-                        debug!("    replacement: {:?} <= {:?}", a, b);
-                        edit.add_no_visit(a, b);
+                a.for_each_replacement(arena, p, &less, |a2| {
+                    b.for_each_replacement(arena, p, &greater, |b2| {
+                        debug!("    replacement: {:?} <= {:?}", a2, b2);
+                        edit.add_no_visit(a2, b2);
+
+                        // The replacement stands for the chain through `p`.
+                        if edit.cset.tracks_provenance() {
+                            let mut sources = vec![(a, b)];
+                            if a.contains(p) {
+                                sources.extend(less.iter().filter(|&&l| a2.contains(l))
+                                               .map(|&l| (l, p)));
+                            }
+                            if b.contains(p) {
+                                sources.extend(greater.iter().filter(|&&g| b2.contains(g))
+                                               .map(|&g| (p, g)));
+                            }
+                            edit.derive_origins(a2, b2, &sources);
+                        }
                     });
                 });
             }
@@ -710,3 +921,221 @@ impl<'tcx> ConstraintSet<'tcx> {
     }
 }
 
+
+impl<'tcx> ConstraintSet<'tcx> {
+    /// Compute a minimal equivalent of this constraint set over the signature variables
+    /// `SigVar(0) .. SigVar(num_vars)`.  The result mentions no other variables, contains no
+    /// constraint implied by the others, and replaces each group of variables that are forced
+    /// equal with a single representative, related to the rest of the group by a pair of
+    /// constraints in each direction.
+    ///
+    /// Invariant: an assignment of concrete permissions to the first `num_vars` signature
+    /// variables satisfies the result if and only if it extends to an assignment satisfying
+    /// `self`.  This holds exactly for constraint sets without `Min`, and with the imprecision
+    /// documented on `retain_perms` otherwise.  An unsatisfiable set minimizes to
+    /// `MOVE <= READ`.
+    pub fn minimize(&self, arena: &'tcx DroplessArena, num_vars: u32) -> ConstraintSet<'tcx> {
+        let mut cset = self.clone();
+        // `remove_useless` drops all constraints between concrete permissions, including the
+        // violated ones, so look for those before each step that may discard them.
+        if cset.has_violated_concrete() {
+            return ConstraintSet::unsatisfiable();
+        }
+
+        cset.simplify(arena);
+        cset.retain_perms(arena, |p| {
+            match p {
+                Perm::Concrete(_) => true,
+                Perm::SigVar(v) => v.0 < num_vars,
+                _ => false,
+            }
+        });
+        if cset.has_violated_concrete() {
+            return ConstraintSet::unsatisfiable();
+        }
+
+        cset.simplify(arena);
+        cset.collapse_equal_vars(arena);
+        cset.simplify(arena);
+        cset.remove_implied();
+        cset
+    }
+
+    /// The constraint set `MOVE <= READ`, which no assignment satisfies.
+    fn unsatisfiable() -> ConstraintSet<'tcx> {
+        let mut cset = ConstraintSet::new();
+        cset.add(Perm::move_(), Perm::read());
+        cset
+    }
+
+    fn has_violated_concrete(&self) -> bool {
+        self.less.iter().any(|&(a, b)| {
+            match (a, b) {
+                (Perm::Concrete(a), Perm::Concrete(b)) => a > b,
+                _ => false,
+            }
+        })
+    }
+
+    /// Replace every variable with the least variable it is forced equal to (through a cycle of
+    /// constraints), then add constraints in both directions between each replaced variable and
+    /// its representative.
+    fn collapse_equal_vars(&mut self, arena: &'tcx DroplessArena) {
+        let mut vars = BTreeSet::new();
+        self.for_each_perm(|p| {
+            match p {
+                Perm::Concrete(_) => {},
+                p => { vars.insert(p); },
+            }
+        });
+
+        let mut greater_sets = HashMap::new();
+        for &p in &vars {
+            let mut greater = HashSet::new();
+            self.for_each_greater_than(p, |q| {
+                greater.insert(q);
+                true
+            });
+            greater_sets.insert(p, greater);
+        }
+
+        // `vars` is sorted, so the first variable of each group becomes its representative.
+        let mut rep = HashMap::new();
+        for &p in &vars {
+            if rep.contains_key(&p) {
+                continue;
+            }
+            for &q in &vars {
+                if greater_sets[&p].contains(&q) && greater_sets[&q].contains(&p) {
+                    rep.entry(q).or_insert(p);
+                }
+            }
+        }
+
+        if rep.iter().all(|(&q, &p)| p == q) {
+            return;
+        }
+
+        let mut new_cset = self.clone_substituted(arena, |p| *rep.get(&p).unwrap_or(&p));
+        for (&q, &p) in &rep {
+            if p != q {
+                debug!("collapse {:?} into {:?}", q, p);
+                new_cset.add(p, q);
+                new_cset.add(q, p);
+            }
+        }
+        new_cset.remove_useless();
+        *self = new_cset;
+    }
+
+    /// Remove each constraint between atomic permissions that is implied by the remaining ones,
+    /// either through a chain of other constraints or through the ordering of concrete
+    /// permissions.
+    fn remove_implied(&mut self) {
+        let candidates = self.less.iter().cloned().filter(|&(a, b)| {
+            match (a, b) {
+                (Perm::Min(_), _) | (_, Perm::Min(_)) => false,
+                _ => true,
+            }
+        }).collect::<Vec<_>>();
+
+        for (a, b) in candidates {
+            self.less.remove(&(a, b));
+            self.greater.remove(&(b, a));
+
+            let implied = match (a, b) {
+                (Perm::Concrete(c), _) => self.lower_bound(b) >= c,
+                (_, Perm::Concrete(c)) => self.upper_bound(a) <= c,
+                _ => {
+                    let mut found = false;
+                    self.for_each_greater_than(a, |p| {
+                        found |= p == b;
+                        !found
+                    });
+                    found
+                },
+            };
+
+            if implied {
+                debug!("remove implied: {:?} <= {:?}", a, b);
+            } else {
+                self.add(a, b);
+            }
+        }
+    }
+}
+
+
+/// Check the invariant of `ConstraintSet::minimize` on `cases` pseudo-random constraint sets over
+/// a few signature and instantiation variables, by comparing the satisfying assignments of each
+/// set and its minimized form.  Returns a description of the first mismatch found.
+pub fn check_minimize<'tcx>(arena: &'tcx DroplessArena, cases: u32) -> Result<(), String> {
+    const NUM_VARS: u32 = 3;
+    const NUM_SIG_VARS: u32 = 5;
+    const NUM_INST_VARS: u32 = 2;
+    const PERMS: [ConcretePerm; 3] = [ConcretePerm::Read, ConcretePerm::Write, ConcretePerm::Move];
+
+    let mut state = 1_u64;
+    let mut next = |n: u32| {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        ((state >> 33) % n as u64) as u32
+    };
+
+    // The satisfying assignments of `cset` over the first `NUM_VARS` sig vars, each encoded as a
+    // base-3 number.
+    fn satisfying<'tcx>(cset: &ConstraintSet<'tcx>, num_free: u32) -> BTreeSet<u32> {
+        let mut result = BTreeSet::new();
+        for code in 0 .. 3_u32.pow(NUM_SIG_VARS + NUM_INST_VARS) {
+            let digit = |i: u32| PERMS[(code / 3_u32.pow(i) % 3) as usize];
+            let ok = cset.check_partial_assignment(|p| {
+                match p {
+                    Perm::SigVar(v) => Some(digit(v.0)),
+                    Perm::InstVar(v) => Some(digit(NUM_SIG_VARS + v.0)),
+                    _ => None,
+                }
+            });
+            if ok {
+                result.insert(code % 3_u32.pow(num_free));
+            }
+        }
+        result
+    }
+
+    for case in 0 .. cases {
+        let mut cset = ConstraintSet::new();
+        for _ in 0 .. next(8) {
+            let mut perm = || {
+                match next(10) {
+                    0 => Perm::Concrete(PERMS[next(3) as usize]),
+                    1 | 2 => Perm::InstVar(Var(next(NUM_INST_VARS))),
+                    _ => Perm::SigVar(Var(next(NUM_SIG_VARS))),
+                }
+            };
+            let a = perm();
+            let b = perm();
+            cset.add(a, b);
+        }
+
+        let minimized = cset.minimize(arena, NUM_VARS);
+
+        let mut other_var = None;
+        minimized.for_each_perm(|p| {
+            match p {
+                Perm::Concrete(_) => {},
+                Perm::SigVar(v) if v.0 < NUM_VARS => {},
+                p => other_var = Some(p),
+            }
+        });
+        if let Some(p) = other_var {
+            return Err(format!("case {}: {:?} minimized to {:?}, which mentions {:?}",
+                               case, cset, minimized, p));
+        }
+
+        if satisfying(&cset, NUM_VARS) != satisfying(&minimized, NUM_VARS) {
+            return Err(format!("case {}: {:?} and its minimized form {:?} have different \
+                                solutions", case, cset, minimized));
+        }
+    }
+
+    Ok(())
+}
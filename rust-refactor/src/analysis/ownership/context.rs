@@ -23,6 +23,7 @@
 use std::collections::hash_map::{self, HashMap, Entry};
 
 use arena::DroplessArena;
+use rustc::hir::def::Def;
 use rustc::hir::def_id::DefId;
 use rustc::ty::{Ty, TyCtxt, TypeVariants};
 use rustc_data_structures::indexed_vec::IndexVec;
@@ -31,8 +32,10 @@ use syntax::codemap::Span;
 use analysis::labeled_ty::LabeledTyCtxt;
 
 use super::{Var, FnSig, LTy, LFnSig, ConcretePerm, PermVar};
+use super::cache::IntraCache;
 use super::constraint::ConstraintSet;
 use super::constraint::Perm;
+use super::inter::{ForeignSummaries, builtin_foreign_summaries};
 
 
 // The following structures describe the functions, variants, and monomorphizations relevant to the
@@ -55,6 +58,30 @@ pub struct FuncSumm<'tcx> {
 
     pub monos_provided: bool,
 
+    /// Is the signature visible to C, through `#[no_mangle]`, `#[export_name]`, or a non-Rust
+    /// ABI?  Its pointers must stay raw, so it gets a single mono.
+    ///
+    /// Populated by `intra`.
+    pub sig_frozen: bool,
+
+    /// Did the function have more monos than `MonoLimits` allow, and so get only the most
+    /// permissive one?
+    ///
+    /// Populated by `mono`.
+    pub monos_truncated: bool,
+
+    /// Pairs of signature variables labeling pointer arguments that some caller passes the same
+    /// pointer to.  Where both get WRITE, neither can become `&mut`.
+    ///
+    /// Populated by `find_alias_conflicts`, after `inter`.
+    pub alias_conflicts: Vec<(Var, Var)>,
+
+    /// Does the function return a `restrict` pointer to mutable data?  Then the returned pointer
+    /// is the only way to reach that data, and doesn't borrow from any argument.
+    ///
+    /// Populated by `annot`.
+    pub restrict_return: bool,
+
     pub variant_ids: Vec<DefId>,
     pub num_monos: usize,
 }
@@ -72,6 +99,26 @@ pub struct VariantSumm<'tcx> {
     ///
     /// Populated by `intra`.
     pub insts: Vec<Instantiation>,
+
+    /// Signature variables labeling pointers that may be null in this variant.
+    ///
+    /// Populated by `intra`.
+    pub nullable: Vec<Var>,
+
+    /// Signature variables labeling pointers that this variant indexes as arrays.
+    ///
+    /// Populated by `intra`.
+    pub array_ptrs: Vec<Var>,
+
+    /// Indices of the integer arguments that may be array lengths in this variant.
+    ///
+    /// Populated by `intra`.
+    pub bound_args: Vec<usize>,
+
+    /// Calls in this variant passing the same pointer for two arguments, as `(callee, i, j)`.
+    ///
+    /// Populated by `intra`.
+    pub alias_args: Vec<(DefId, usize, usize)>,
 }
 
 pub struct MonoSumm {
@@ -90,6 +137,20 @@ pub struct MonoSumm {
     pub suffix: String,
 }
 
+/// Constraints from the initializer of a `static` or `const`.  The initializer's return place is
+/// the item itself, so these relate the item's static vars to those of anything it refers to.
+pub struct StaticInitSumm<'tcx> {
+    /// Constraints over static and instantiation variables.
+    ///
+    /// Populated by `intra`.
+    pub inst_cset: ConstraintSet<'tcx>,
+
+    /// List of instantiations, or references to functions.
+    ///
+    /// Populated by `intra`.
+    pub insts: Vec<Instantiation>,
+}
+
 pub struct Instantiation {
     pub callee: DefId,
     pub span: Option<Span>,
@@ -109,6 +170,22 @@ pub struct Ctxt<'a, 'tcx: 'a> {
     /// convenient way to communicate it from `annot` to `inter`.
     pub static_assign: IndexVec<Var, ConcretePerm>,
 
+    /// Summaries of `static` and `const` initializers, keyed by the item's `DefId`.
+    pub static_inits: HashMap<DefId, StaticInitSumm<'tcx>>,
+
+    /// The static var shared by the pointer-typed fields of each union, keyed by the union's
+    /// `DefId`.
+    union_vars: HashMap<DefId, Var>,
+
+    /// Record which statements give rise to each constraint, for `FunctionResult::explain`.
+    pub track_provenance: bool,
+
+    /// Summaries of foreign functions.  See `inter::ForeignSummaries`.
+    pub foreign_summs: ForeignSummaries,
+
+    /// Intraprocedural summaries from an earlier run, reused where the MIR hasn't changed.
+    pub intra_cache: Option<IntraCache>,
+
     funcs: HashMap<DefId, FuncSumm<'tcx>>,
     variants: HashMap<DefId, VariantSumm<'tcx>>,
     monos: HashMap<(DefId, usize), MonoSumm>,
@@ -124,6 +201,14 @@ impl<'a, 'tcx> Ctxt<'a, 'tcx> {
 
             static_summ: HashMap::new(),
             static_assign: IndexVec::new(),
+            static_inits: HashMap::new(),
+            union_vars: HashMap::new(),
+
+            track_provenance: false,
+
+            foreign_summs: builtin_foreign_summaries(),
+
+            intra_cache: None,
 
             funcs: HashMap::new(),
             variants: HashMap::new(),
@@ -132,14 +217,36 @@ impl<'a, 'tcx> Ctxt<'a, 'tcx> {
     }
 
     pub fn static_ty(&mut self, did: DefId) -> LTy<'tcx> {
+        let tcx = self.tcx;
+        let ty = tcx.type_of(did);
+        let is_ptr = match ty.sty {
+            TypeVariants::TyRef(_, _) | TypeVariants::TyRawPtr(_) => true,
+            _ => false,
+        };
+        let union_id = tcx.parent_def_id(did).and_then(|parent| {
+            match tcx.describe_def(parent) {
+                Some(Def::Union(_)) if is_ptr => Some(parent),
+                _ => None,
+            }
+        });
+
         let assign = &mut self.static_assign;
+        let union_vars = &mut self.union_vars;
         match self.static_summ.entry(did) {
             Entry::Vacant(e) => {
-                *e.insert(self.lcx.label(self.tcx.type_of(did), &mut |ty| {
+                // The fields of a union overlap, so writing a pointer to one field and reading it
+                // from another copies it.  Pointer-typed fields share a single var, so they all
+                // get the same permission.
+                let mut shared = union_id.map(|union_id| {
+                    *union_vars.entry(union_id)
+                        .or_insert_with(|| assign.push(ConcretePerm::Read))
+                });
+                *e.insert(self.lcx.label(ty, &mut |ty| {
                     match ty.sty {
                         TypeVariants::TyRef(_, _) |
                         TypeVariants::TyRawPtr(_) => {
-                            let v = assign.push(ConcretePerm::Read);
+                            let v = shared.take()
+                                .unwrap_or_else(|| assign.push(ConcretePerm::Read));
                             Some(PermVar::Static(v))
                         },
                         _ => None,
@@ -163,6 +270,8 @@ impl<'a, 'tcx> Ctxt<'a, 'tcx> {
                 let sig = tcx.fn_sig(did);
                 let mut counter = 0;
 
+                // Every pointer in the signature gets its own variable, including each level of
+                // a pointer-to-pointer type.
                 let l_sig = {
                     let mut f = |ty: Ty<'tcx>| {
                         match ty.sty {
@@ -194,6 +303,10 @@ impl<'a, 'tcx> Ctxt<'a, 'tcx> {
                     variant_idx: 0,
                     inst_cset: ConstraintSet::new(),
                     insts: Vec::new(),
+                    nullable: Vec::new(),
+                    array_ptrs: Vec::new(),
+                    bound_args: Vec::new(),
+                    alias_args: Vec::new(),
                 });
 
                 e.insert(FuncSumm {
@@ -202,6 +315,10 @@ impl<'a, 'tcx> Ctxt<'a, 'tcx> {
                     sig_cset: cset,
                     cset_provided: provided,
                     monos_provided: false,
+                    sig_frozen: false,
+                    monos_truncated: false,
+                    alias_conflicts: Vec::new(),
+                    restrict_return: false,
 
                     variant_ids: vec![did],
                     num_monos: 0,
@@ -269,6 +386,10 @@ impl<'a, 'tcx> Ctxt<'a, 'tcx> {
             variant_idx: v_idx,
             inst_cset: ConstraintSet::new(),
             insts: Vec::new(),
+            nullable: Vec::new(),
+            array_ptrs: Vec::new(),
+            bound_args: Vec::new(),
+            alias_args: Vec::new(),
         });
         let variant = variants.get_mut(&variant_did).unwrap();
         (func, variant)
@@ -0,0 +1,85 @@
+//! Differences between the results of two runs of the analysis.
+//!
+//! Functions are matched up by def path.  For each one whose polymorphic signature or mono
+//! signatures changed, the lines only in the old run are marked `-` and those only in the new run
+//! `+`.  Functions only in one of the runs are marked `removed` or `added`:
+//!
+//! ```text
+//! func ::point_x[0]:
+//! - mono ("take"): [*mut MOVE Point] -> *mut MOVE i32
+//! func ::helper[0]: added
+//! + sig: ...
+//! + mono (""): ...
+//! ```
+//!
+//! Monos are compared by suffix and signature, not by index, so renumbering them is not a change.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use driver;
+
+use super::{AnalysisResult, def_paths, format_sig};
+
+
+/// The lines describing each function of `results`, keyed by def path.
+fn func_lines(dcx: &driver::Ctxt, results: &AnalysisResult) -> BTreeMap<String, Vec<String>> {
+    let paths = def_paths(dcx, results);
+
+    let mut funcs = BTreeMap::new();
+    for (&id, fr) in &results.funcs {
+        let mut monos = (0 .. fr.num_monos)
+            .map(|i| {
+                let mr = &results.monos[&(id, i)];
+                format!("mono ({:?}): {}", mr.suffix, format_sig(fr.sig, &mr.assign))
+            })
+            .collect::<Vec<_>>();
+        monos.sort();
+
+        let mut lines = vec![format!("sig: {:?}", fr.sig)];
+        lines.extend(monos);
+        funcs.insert(paths[&id].clone(), lines);
+    }
+    funcs
+}
+
+/// Describe how the signatures in `new` differ from those in `old`.  Returns an empty string if
+/// they are the same.
+pub fn diff_results(dcx: &driver::Ctxt, old: &AnalysisResult, new: &AnalysisResult) -> String {
+    let old_funcs = func_lines(dcx, old);
+    let new_funcs = func_lines(dcx, new);
+
+    let paths = old_funcs.keys().chain(new_funcs.keys()).collect::<BTreeSet<_>>();
+
+    let mut out = String::new();
+    for path in paths {
+        match (old_funcs.get(path), new_funcs.get(path)) {
+            (Some(old_lines), Some(new_lines)) => {
+                if old_lines == new_lines {
+                    continue;
+                }
+                writeln!(out, "func {}:", path).unwrap();
+                for line in old_lines.iter().filter(|l| !new_lines.contains(l)) {
+                    writeln!(out, "- {}", line).unwrap();
+                }
+                for line in new_lines.iter().filter(|l| !old_lines.contains(l)) {
+                    writeln!(out, "+ {}", line).unwrap();
+                }
+            },
+            (Some(old_lines), None) => {
+                writeln!(out, "func {}: removed", path).unwrap();
+                for line in old_lines {
+                    writeln!(out, "- {}", line).unwrap();
+                }
+            },
+            (None, Some(new_lines)) => {
+                writeln!(out, "func {}: added", path).unwrap();
+                for line in new_lines {
+                    writeln!(out, "+ {}", line).unwrap();
+                }
+            },
+            (None, None) => unreachable!(),
+        }
+    }
+    out
+}
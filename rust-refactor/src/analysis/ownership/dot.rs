@@ -0,0 +1,74 @@
+//! Graphviz export of the mono call graph.
+//!
+//! Each mono of each function is a node, labeled with the function's def path, the mono index
+//! and suffix, and its concrete signature.  Each call in a mono is an edge to the callee mono it
+//! selects:
+//!
+//! ```text
+//! digraph monos {
+//!     "::set_x[0] #0" [label="::set_x[0] #0 (\"\")\n[*mut WRITE Point, i32] -> ()"];
+//!     "::set_x[0] #0" -> "::point_x[0] #1";
+//! }
+//! ```
+//!
+//! Monos dropped by the mono filter are not part of the results, so they have no node.
+
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+use rustc::hir::def_id::DefId;
+
+use driver;
+
+use super::{AnalysisResult, def_paths, format_sig};
+
+
+/// Escape `s` for use inside a double-quoted DOT string.
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Render the mono call graph of `results` in DOT format.  Nodes and edges are listed in `DefId`
+/// order, so the output is the same from run to run.
+pub fn results_to_dot(dcx: &driver::Ctxt, results: &AnalysisResult) -> String {
+    let paths = def_paths(dcx, results);
+    let path_str = |def_id: DefId| paths[&def_id].as_str();
+    let node = |def_id: DefId, idx: usize| escape(&format!("{} #{}", path_str(def_id), idx));
+
+    let mut ids = results.funcs.keys().cloned().collect::<Vec<_>>();
+    ids.sort();
+
+    let mut out = String::new();
+    writeln!(out, "digraph monos {{").unwrap();
+
+    for &id in &ids {
+        let fr = &results.funcs[&id];
+        for i in 0 .. fr.num_monos {
+            let mr = &results.monos[&(id, i)];
+            let label = format!("{} #{} ({:?})", path_str(id), i, mr.suffix);
+            writeln!(out, "    \"{}\" [label=\"{}\\n{}\"];",
+                     node(id, i), escape(&label), escape(&format_sig(fr.sig, &mr.assign)))
+                .unwrap();
+        }
+    }
+
+    for &id in &ids {
+        let fr = &results.funcs[&id];
+        for i in 0 .. fr.num_monos {
+            let mr = &results.monos[&(id, i)];
+            let var_id = fr.variants.as_ref().map_or(id, |vars| vars[i]);
+            let vr = &results.variants[&var_id];
+
+            // Several calls to the same callee mono share one edge.
+            let callees = vr.func_refs.iter().zip(mr.callee_mono_idxs.iter())
+                .map(|(func_ref, &mono_idx)| (func_ref.def_id, mono_idx))
+                .collect::<BTreeSet<_>>();
+            for (callee, mono_idx) in callees {
+                writeln!(out, "    \"{}\" -> \"{}\";", node(id, i), node(callee, mono_idx)).unwrap();
+            }
+        }
+    }
+
+    writeln!(out, "}}").unwrap();
+    out
+}
@@ -0,0 +1,51 @@
+//! Explanations of the inferred permissions.
+//!
+//! For each signature variable forced above READ, lists the statements responsible, as found by
+//! `FunctionResult::explain`:
+//!
+//! ```text
+//! func ::release:
+//!   _0 >= MOVE:
+//!     old.rs:9:5: 9:31: free(p as *mut c_void)
+//! ```
+//!
+//! The results must come from `analyze_with_provenance`, or there is nothing to explain.
+
+use std::fmt::Write;
+
+use driver;
+
+use super::{AnalysisResult, ConcretePerm, Var, def_paths};
+use super::constraint::Perm;
+
+
+/// Explain the signature permissions of every function in `results`, in `DefId` order.
+pub fn results_to_explanations(dcx: &driver::Ctxt, results: &AnalysisResult) -> String {
+    let paths = def_paths(dcx, results);
+    let codemap = dcx.session().codemap();
+
+    let mut ids = results.funcs.keys().cloned().collect::<Vec<_>>();
+    ids.sort();
+
+    let mut out = String::new();
+    for id in ids {
+        let fr = &results.funcs[&id];
+        writeln!(out, "func {}:", paths[&id]).unwrap();
+
+        for i in 0 .. fr.num_sig_vars {
+            let var = Var(i);
+            let bound = fr.cset.lower_bound(Perm::SigVar(var));
+            if bound == ConcretePerm::Read {
+                continue;
+            }
+
+            writeln!(out, "  _{} >= {}:", i, format!("{:?}", bound).to_uppercase()).unwrap();
+            for (span, desc) in fr.explain(var) {
+                // Keep each explanation on one line.
+                let desc = desc.split_whitespace().collect::<Vec<_>>().join(" ");
+                writeln!(out, "    {}: {}", codemap.span_to_string(span), desc).unwrap();
+            }
+        }
+    }
+    out
+}
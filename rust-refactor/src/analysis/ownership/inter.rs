@@ -5,12 +5,76 @@ use std::collections::HashSet;
 use std::collections::VecDeque;
 
 use rustc::hir::def_id::DefId;
+use rustc::ty::TyCtxt;
 
-use super::Var;
+use super::{ConcretePerm, PermVar, Var};
 use super::context::Ctxt;
 use super::constraint::{ConstraintSet, Perm};
 
 
+/// C library functions whose bodies the analysis can't see, but whose ownership behavior is known.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Intrinsic {
+    /// Returns a fresh allocation, like `malloc`.
+    Alloc,
+    /// Releases its argument, like `free`.
+    Free,
+    /// `realloc` releases its argument (or moves it into the result) and returns a fresh
+    /// allocation in its place.  The old pointer is dangling afterwards.
+    Realloc,
+}
+
+/// Handwritten summaries of foreign functions, keyed by name.  Each gives the permission of each
+/// pointer argument of the function, in order, skipping arguments that aren't pointers.  Without
+/// a summary, nothing constrains the arguments of a foreign function, so they end up READ.
+pub type ForeignSummaries = HashMap<String, Vec<ConcretePerm>>;
+
+/// The C library functions the analysis knows about: the intrinsic each one is, if any, and the
+/// built-in summary of its pointer arguments.  Consuming an argument is a move out of the
+/// caller's pointer, so the intrinsics that release their argument give it MOVE here.
+static FOREIGN_FNS: [(&str, Option<Intrinsic>, &[ConcretePerm]); 12] = [
+    ("malloc", Some(Intrinsic::Alloc), &[]),
+    ("calloc", Some(Intrinsic::Alloc), &[]),
+    ("free", Some(Intrinsic::Free), &[ConcretePerm::Move]),
+    ("realloc", Some(Intrinsic::Realloc), &[ConcretePerm::Move]),
+    ("strlen", None, &[ConcretePerm::Read]),
+    ("strcmp", None, &[ConcretePerm::Read, ConcretePerm::Read]),
+    ("strncmp", None, &[ConcretePerm::Read, ConcretePerm::Read]),
+    ("strcpy", None, &[ConcretePerm::Write, ConcretePerm::Read]),
+    ("memcmp", None, &[ConcretePerm::Read, ConcretePerm::Read]),
+    ("memcpy", None, &[ConcretePerm::Write, ConcretePerm::Read]),
+    ("memmove", None, &[ConcretePerm::Write, ConcretePerm::Read]),
+    ("memset", None, &[ConcretePerm::Write]),
+];
+
+/// Look up the intrinsic a foreign function corresponds to, if any.
+pub fn intrinsic<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, def_id: DefId) -> Option<Intrinsic> {
+    let name = foreign_name(tcx, def_id)?;
+    FOREIGN_FNS.iter()
+        .find(|&&(fn_name, _, _)| fn_name == name)
+        .and_then(|&(_, intrinsic, _)| intrinsic)
+}
+
+/// The name of a foreign function, without its module path.
+fn foreign_name<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, def_id: DefId) -> Option<String> {
+    if !tcx.is_foreign_item(def_id) {
+        return None;
+    }
+
+    let path = tcx.absolute_item_path_str(def_id);
+    let name = path.rsplit("::").next().unwrap_or(&path).to_owned();
+    Some(name)
+}
+
+/// The built-in summaries of common C library functions.
+pub fn builtin_foreign_summaries() -> ForeignSummaries {
+    FOREIGN_FNS.iter()
+        .filter(|&&(_, _, perms)| !perms.is_empty())
+        .map(|&(name, _, perms)| (name.to_owned(), perms.to_owned()))
+        .collect()
+}
+
+
 struct WorkList {
     queue: VecDeque<DefId>,
     in_queue: HashSet<DefId>,
@@ -40,10 +104,15 @@ impl WorkList {
     }
 }
 
+/// Default for the number of times `InterCtxt` processes a single function or static before
+/// giving up on it.
+pub const DEFAULT_MAX_VISITS: usize = 1000;
+
 pub struct InterCtxt<'c, 'a: 'c, 'tcx: 'a> {
     cx: &'c mut Ctxt<'a, 'tcx>,
 
-    // Note: all IDs here are function IDs.  Variants are ignored.
+    // Note: all IDs here are function IDs, or IDs of statics with initializers.  Variants are
+    // ignored.
 
     complete_cset: HashMap<DefId, ConstraintSet<'tcx>>,
 
@@ -51,22 +120,32 @@ pub struct InterCtxt<'c, 'a: 'c, 'tcx: 'a> {
     rev_deps: HashMap<DefId, HashSet<DefId>>,
 
     static_rev_deps: HashMap<Var, HashSet<DefId>>,
+
+    /// Number of times each ID has been processed.  IDs that reach `max_visits` are reported
+    /// and added to `diverged`, and keep the last result they had from then on.
+    visits: HashMap<DefId, usize>,
+    max_visits: usize,
+    diverged: HashSet<DefId>,
 }
 
 impl<'c, 'a, 'tcx> InterCtxt<'c, 'a, 'tcx> {
-    pub fn new(cx: &'c mut Ctxt<'a, 'tcx>) -> InterCtxt<'c, 'a, 'tcx> {
+    /// Create a context that processes each function or static at most `max_visits` times.
+    /// Since constraints only grow, this is only reached if there's a bug in the analysis.
+    pub fn new(cx: &'c mut Ctxt<'a, 'tcx>, max_visits: usize) -> InterCtxt<'c, 'a, 'tcx> {
         InterCtxt {
             cx: cx,
             complete_cset: HashMap::new(),
             work_list: WorkList::new(),
             rev_deps: HashMap::new(),
             static_rev_deps: HashMap::new(),
+            visits: HashMap::new(),
+            max_visits: max_visits,
+            diverged: HashSet::new(),
         }
     }
 
     /// Recompute the `complete_cset` of one function.  Returns the new cset.
     fn compute_one_cset(&mut self, def_id: DefId) -> ConstraintSet<'tcx> {
-        let dummy_cset = ConstraintSet::new();
         let arena = self.cx.arena;
 
         let mut cset = {
@@ -77,7 +156,36 @@ impl<'c, 'a, 'tcx> InterCtxt<'c, 'a, 'tcx> {
             var.inst_cset.clone()
         };
 
-        // Add constraints for all used static vars.
+        self.constrain_foreign(def_id, &mut cset);
+
+        let used_statics = self.import_statics(def_id, &mut cset);
+
+        let insts = self.cx.first_variant_summ(def_id).1.insts.iter()
+            .map(|inst| (inst.callee, inst.first_inst_var))
+            .collect::<Vec<_>>();
+        self.import_insts(def_id, &insts, &mut cset);
+
+        self.constrain_owned_result(def_id, &mut cset);
+
+        self.simplify_inst_vars(&mut cset);
+        self.update_statics(&used_statics, &cset);
+
+        // Simplify away static vars too.
+        cset.retain_perms(arena, |p| {
+            match p {
+                Perm::LocalVar(_) | Perm::InstVar(_) | Perm::StaticVar(_) => false,
+                _ => true,
+            }
+        });
+
+        cset.simplify(arena);
+
+        cset
+    }
+
+    /// Add constraints for all static vars used in `cset`, fixing them at their current
+    /// assignments.  Returns the set of static vars used, and records that `id` depends on them.
+    fn import_statics(&mut self, id: DefId, cset: &mut ConstraintSet<'tcx>) -> HashSet<Var> {
         let mut used_statics = HashSet::new();
         cset.for_each_perm(|p| {
             match p {
@@ -90,24 +198,37 @@ impl<'c, 'a, 'tcx> InterCtxt<'c, 'a, 'tcx> {
         for &v in &used_statics {
             eprintln!("  import static: {:?} = {:?}", v, self.cx.static_assign[v]);
             cset.add(Perm::Concrete(self.cx.static_assign[v]), Perm::StaticVar(v));
-            self.static_rev_deps.entry(v).or_insert_with(HashSet::new).insert(def_id);
+            self.static_rev_deps.entry(v).or_insert_with(HashSet::new).insert(id);
         }
+        used_statics
+    }
+
+    /// Copy in complete csets for all instantiations, given as `(callee, first_inst_var)` pairs.
+    fn import_insts(&mut self,
+                    id: DefId,
+                    insts: &[(DefId, u32)],
+                    cset: &mut ConstraintSet<'tcx>) {
+        let dummy_cset = ConstraintSet::new();
+        let arena = self.cx.arena;
 
-        // Copy in complete csets for all instantiations.
-        for inst in &self.cx.first_variant_summ(def_id).1.insts {
-            let complete = self.complete_cset.get(&inst.callee).unwrap_or(&dummy_cset);
-            eprintln!("  instantiate {:?} for vars {}..", inst.callee, inst.first_inst_var);
+        for &(callee, first_inst_var) in insts {
+            let complete = self.complete_cset.get(&callee).unwrap_or(&dummy_cset);
+            eprintln!("  instantiate {:?} for vars {}..", callee, first_inst_var);
             cset.import_substituted(complete, arena, |p| {
                 match p {
-                    Perm::SigVar(v) => Perm::InstVar(Var(v.0 + inst.first_inst_var)),
+                    Perm::SigVar(v) => Perm::InstVar(Var(v.0 + first_inst_var)),
                     p => p,
                 }
             });
 
-            self.rev_deps.entry(inst.callee).or_insert_with(HashSet::new).insert(def_id);
+            self.rev_deps.entry(callee).or_insert_with(HashSet::new).insert(id);
         }
+    }
+
+    /// Simplify away local and inst vars, leaving constraints over sig and static vars.
+    fn simplify_inst_vars(&mut self, cset: &mut ConstraintSet<'tcx>) {
+        let arena = self.cx.arena;
 
-        // Simplify away inst vars to produce a new complete cset for this fn.
         eprintln!("  original constraints:");
         for &(a, b) in cset.iter() {
             eprintln!("    {:?} <= {:?}", a, b);
@@ -127,9 +248,13 @@ impl<'c, 'a, 'tcx> InterCtxt<'c, 'a, 'tcx> {
         for &(a, b) in cset.iter() {
             eprintln!("    {:?} <= {:?}", a, b);
         }
+    }
 
-        // Update `cx.static_assign`
-        for &v in &used_statics {
+    /// Raise `cx.static_assign` to the lower bounds `cset` requires, and requeue everything that
+    /// uses a static whose assignment changed.  Assignments only ever increase, so cycles
+    /// between statics (or between statics and functions) still reach a fixed point.
+    fn update_statics(&mut self, used_statics: &HashSet<Var>, cset: &ConstraintSet<'tcx>) {
+        for &v in used_statics {
             let old = self.cx.static_assign[v];
             let new = cset.lower_bound(Perm::StaticVar(v));
             eprintln!("  static {:?}: {:?} -> {:?}", v, old, new);
@@ -142,23 +267,125 @@ impl<'c, 'a, 'tcx> InterCtxt<'c, 'a, 'tcx> {
                 }
             }
         }
+    }
 
-        // Simplify away static vars too.
-        cset.retain_perms(arena, |p| {
-            match p {
-                Perm::LocalVar(_) | Perm::InstVar(_) | Perm::StaticVar(_) => false,
-                _ => true,
+    /// Propagate the constraints from the initializer of a `static` or `const` into the
+    /// assignments of the static vars it mentions.  Unlike functions, initializers have no
+    /// signature, so nothing is saved in `complete_cset`.
+    fn process_static(&mut self, def_id: DefId) {
+        let (mut cset, insts) = {
+            let init = &self.cx.static_inits[&def_id];
+            let insts = init.insts.iter()
+                .map(|inst| (inst.callee, inst.first_inst_var))
+                .collect::<Vec<_>>();
+            (init.inst_cset.clone(), insts)
+        };
+
+        let used_statics = self.import_statics(def_id, &mut cset);
+        self.import_insts(def_id, &insts, &mut cset);
+        self.simplify_inst_vars(&mut cset);
+        self.update_statics(&used_statics, &cset);
+    }
+
+    /// Fix the pointer arguments of a foreign function at the permissions its summary gives, if
+    /// it has one, and the result of an allocating intrinsic at MOVE, since a fresh allocation is
+    /// owned by the caller.  Nothing relates the two for `realloc`: its result is a new
+    /// allocation, not a borrow of its argument.
+    fn constrain_foreign(&mut self, def_id: DefId, cset: &mut ConstraintSet<'tcx>) {
+        let sig = self.cx.first_variant_summ(def_id).0.sig;
+
+        let perms = foreign_name(self.cx.tcx, def_id)
+            .and_then(|name| self.cx.foreign_summs.get(&name))
+            .cloned()
+            .unwrap_or_else(Vec::new);
+        let labels = sig.inputs.iter().filter_map(|arg| arg.label);
+        for (label, perm) in labels.zip(perms.into_iter()) {
+            if let PermVar::Sig(v) = label {
+                eprintln!("  foreign {:?}: {:?} is {:?}", def_id, v, perm);
+                if perm > ConcretePerm::Read {
+                    cset.add(Perm::Concrete(perm), Perm::SigVar(v));
+                }
+                if perm < ConcretePerm::Move {
+                    cset.add(Perm::SigVar(v), Perm::Concrete(perm));
+                }
             }
-        });
+        }
 
-        cset.simplify(arena);
+        match intrinsic(self.cx.tcx, def_id) {
+            Some(Intrinsic::Alloc) | Some(Intrinsic::Realloc) => {
+                if let Some(PermVar::Sig(v)) = sig.output.label {
+                    eprintln!("  intrinsic {:?}: {:?} is MOVE", def_id, v);
+                    cset.add(Perm::move_(), Perm::SigVar(v));
+                }
+            }
+            _ => {}
+        }
+    }
 
-        cset
+    /// Pair allocators with the functions handing out what they allocate.  An allocator is a
+    /// function whose result is always MOVE, such as a `malloc` marked `box`.  A function
+    /// returning the result of an allocator call passes on ownership of it, so its own result
+    /// must be MOVE too, which makes it an allocator for its callers in turn.  Together with
+    /// `free`-like consumers requiring MOVE arguments, this keeps every pointer on the way from
+    /// an allocation to its release owned.
+    fn constrain_owned_result(&mut self, def_id: DefId, cset: &mut ConstraintSet<'tcx>) {
+        let ret = match self.cx.first_variant_summ(def_id).0.sig.output.label {
+            Some(PermVar::Sig(v)) => Perm::SigVar(v),
+            _ => return,
+        };
+
+        let insts = self.cx.first_variant_summ(def_id).1.insts.iter()
+            .map(|inst| (inst.callee, inst.first_inst_var))
+            .collect::<Vec<_>>();
+
+        for (callee, first_inst_var) in insts {
+            let callee_ret = match self.cx.func_summ(callee).sig.output.label {
+                Some(PermVar::Sig(v)) => Perm::InstVar(Var(v.0 + first_inst_var)),
+                _ => continue,
+            };
+            if cset.lower_bound(callee_ret) != ConcretePerm::Move {
+                continue;
+            }
+
+            let mut returned = false;
+            cset.for_each_less_than(callee_ret, |p| {
+                returned |= p == ret;
+                true
+            });
+            if returned {
+                eprintln!("  owned result from {:?}", callee);
+                cset.add(Perm::move_(), ret);
+                return;
+            }
+        }
     }
 
     fn process_one(&mut self, def_id: DefId) {
+        if self.diverged.contains(&def_id) {
+            return;
+        }
+        let exceeded = {
+            let visits = self.visits.entry(def_id).or_insert(0);
+            *visits += 1;
+            *visits > self.max_visits
+        };
+
+        if self.cx.static_inits.contains_key(&def_id) {
+            if exceeded {
+                self.report_divergence(def_id, None);
+                return;
+            }
+            self.process_static(def_id);
+            return;
+        }
+
         let cset = self.compute_one_cset(def_id);
 
+        if exceeded && self.complete_cset.get(&def_id) != Some(&cset) {
+            self.report_divergence(def_id, Some(&cset));
+            return;
+        }
+
         eprintln!("save cset for {:?}", def_id);
         for &(a, b) in cset.iter() {
             eprintln!("  {:?} <= {:?}", a, b);
@@ -189,11 +416,70 @@ impl<'c, 'a, 'tcx> InterCtxt<'c, 'a, 'tcx> {
         }
     }
 
+    /// Report that `def_id` was still changing after `max_visits` visits, along with the
+    /// strongly connected component of the dependency graph it belongs to, and stop processing
+    /// it.  Its last `complete_cset` is kept as the result.
+    fn report_divergence(&mut self, def_id: DefId, new_cset: Option<&ConstraintSet<'tcx>>) {
+        eprintln!("error: ownership constraints for {:?} did not converge after {} visits; \
+                   keeping the last result",
+                  def_id, self.max_visits);
+
+        if let Some(new_cset) = new_cset {
+            let empty = ConstraintSet::new();
+            let old_cset = self.complete_cset.get(&def_id).unwrap_or(&empty);
+            for &(a, b) in old_cset.iter() {
+                if !new_cset.iter().any(|&c| c == (a, b)) {
+                    eprintln!("  removed: {:?} <= {:?}", a, b);
+                }
+            }
+            for &(a, b) in new_cset.iter() {
+                if !old_cset.iter().any(|&c| c == (a, b)) {
+                    eprintln!("  added: {:?} <= {:?}", a, b);
+                }
+            }
+        }
+
+        let mut scc = self.cycle_of(def_id);
+        scc.sort();
+        eprintln!("  in the cycle: {:?}", scc);
+
+        self.diverged.insert(def_id);
+    }
+
+    /// Collect the IDs in the same strongly connected component of the dependency graph as
+    /// `def_id`: those reachable from `def_id` along `rev_deps` that can also reach it.
+    fn cycle_of(&self, def_id: DefId) -> Vec<DefId> {
+        let reachable = |start: DefId, edges: &HashMap<DefId, HashSet<DefId>>| {
+            let mut seen = HashSet::new();
+            let mut stack = vec![start];
+            while let Some(id) = stack.pop() {
+                if seen.insert(id) {
+                    if let Some(next) = edges.get(&id) {
+                        stack.extend(next.iter().cloned());
+                    }
+                }
+            }
+            seen
+        };
+
+        let mut deps: HashMap<DefId, HashSet<DefId>> = HashMap::new();
+        for (&callee, callers) in &self.rev_deps {
+            for &caller in callers {
+                deps.entry(caller).or_insert_with(HashSet::new).insert(callee);
+            }
+        }
+
+        let users = reachable(def_id, &self.rev_deps);
+        let used = reachable(def_id, &deps);
+        users.intersection(&used).cloned().collect()
+    }
+
     pub fn process(&mut self) {
         let mut idx = 0;
 
-        let ids = self.cx.func_ids().collect::<Vec<_>>();
-        eprintln!("\ninterprocedural analysis: process {} fns", ids.len());
+        let mut ids = self.cx.func_ids().collect::<Vec<_>>();
+        ids.extend(self.cx.static_inits.keys().cloned());
+        eprintln!("\ninterprocedural analysis: process {} fns and statics", ids.len());
         for id in ids {
             eprintln!("process {} (init): {:?}", idx, id);
             idx += 1;
@@ -1,15 +1,24 @@
 //! Intraprocedural step of the analysis.
 
+use std::collections::{HashMap, HashSet};
+
 use rustc::hir::def_id::DefId;
+use rustc::middle::const_val::ConstVal;
 use rustc::mir::*;
-use rustc::ty::{Ty, TypeVariants};
+use rustc::mir::visit::{PlaceContext, Visitor};
+use rustc::ty::{Ty, TyCtxt, TypeVariants};
 use rustc_data_structures::indexed_vec::{IndexVec, Idx};
+use syntax::abi::Abi;
+use syntax::ast::Name;
+use syntax::attr;
+use syntax::codemap::Span;
 
 use analysis::labeled_ty::{LabeledTy, LabeledTyCtxt};
 
 use super::{Var, PermVar, LTy, LFnSig, FnSig};
-use super::constraint::{ConstraintSet, Perm};
+use super::constraint::{ConstraintSet, Origin, Perm};
 use super::context::{Ctxt, Instantiation};
+use super::inter::{self, Intrinsic};
 
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -46,6 +55,25 @@ type ITy<'tcx> = LabeledTy<'tcx, Label<'tcx>>;
 type IFnSig<'tcx> = FnSig<'tcx, Label<'tcx>>;
 
 
+/// The result of running `IntraCtxt` on one function variant or static initializer.  This is
+/// owned by the caller rather than written into the `Ctxt`, so summaries can be produced
+/// independently and then merged in a fixed order.
+pub struct FnSummary<'tcx> {
+    /// Constraints over static, instantiation, and (for variants) signature variables.
+    pub inst_cset: ConstraintSet<'tcx>,
+    /// List of instantiations, or references to functions.
+    pub insts: Vec<Instantiation>,
+    /// Signature variables labeling pointers that may be null.
+    pub nullable: Vec<Var>,
+    /// Signature variables labeling pointers to arrays.
+    pub array_ptrs: Vec<Var>,
+    /// Indices of the integer arguments that may be array lengths.
+    pub bound_args: Vec<usize>,
+    /// Calls passing the same pointer for two arguments, as `(callee, i, j)` with `i < j`.
+    pub alias_args: Vec<(DefId, usize, usize)>,
+}
+
+
 /// Variant-local analysis context.  We run one of these for each function variant to produce the
 /// initial (incomplete) summary.
 pub struct IntraCtxt<'c, 'a: 'c, 'tcx: 'a> {
@@ -54,6 +82,8 @@ pub struct IntraCtxt<'c, 'a: 'c, 'tcx: 'a> {
 
     /// ID of the variant being processed.
     def_id: DefId,
+    /// Is `def_id` a `static` or `const` rather than a function variant?
+    is_static: bool,
     mir: &'a Mir<'tcx>,
     bbid: BasicBlock,
     stmt_idx: usize,
@@ -77,6 +107,32 @@ pub struct IntraCtxt<'c, 'a: 'c, 'tcx: 'a> {
     /// `Label::FnDef` above), and we do that by adding a new entry to `insts`.
     insts: Vec<Instantiation>,
     next_inst_var: u32,
+
+    /// Assignments between pointers, as `(lhs, rhs)` pairs of permissions.  Null values flow
+    /// forward along these, and null checks flow backward: checking a copy of a pointer says the
+    /// original may be null too.  Array offsets flow backward the same way.
+    null_flows: Vec<(Perm<'tcx>, Perm<'tcx>)>,
+    /// Pointers produced by a null constant or a fallible allocation.
+    null_values: Vec<Perm<'tcx>>,
+    /// Pointers checked with `is_null`.
+    null_checks: Vec<Perm<'tcx>>,
+    /// Pairs of pointers compared with `==` or `!=`.  Comparing against a null value counts as a
+    /// null check.
+    null_compares: Vec<(Perm<'tcx>, Perm<'tcx>)>,
+
+    /// Pointers offset by an amount not known to be zero, as when indexing an array.
+    array_offsets: Vec<Perm<'tcx>>,
+    /// Integer locals compared with `<`, `<=`, `>`, or `>=`, which may be array lengths.
+    bound_locals: Vec<Local>,
+    /// The operand each local was copied or cast from.  Compared values and offsets usually reach
+    /// the comparison or call through temporaries, and are traced back with this.
+    local_srcs: HashMap<Local, Operand<'tcx>>,
+    /// Locals assigned exactly once, and never mutably borrowed.  Only these are traced through
+    /// `local_srcs`: the others may hold a different value at each use.
+    single_def_locals: HashSet<Local>,
+
+    /// Calls passing copies of the same pointer for two arguments.
+    alias_args: Vec<(DefId, usize, usize)>,
 }
 
 impl<'c, 'a, 'tcx> IntraCtxt<'c, 'a, 'tcx> {
@@ -89,6 +145,7 @@ impl<'c, 'a, 'tcx> IntraCtxt<'c, 'a, 'tcx> {
             ilcx: ilcx,
 
             def_id: def_id,
+            is_static: false,
             mir: mir,
             bbid: START_BLOCK,
             stmt_idx: !0,
@@ -99,9 +156,30 @@ impl<'c, 'a, 'tcx> IntraCtxt<'c, 'a, 'tcx> {
 
             insts: Vec::new(),
             next_inst_var: 0,
+
+            null_flows: Vec::new(),
+            null_values: Vec::new(),
+            null_checks: Vec::new(),
+            null_compares: Vec::new(),
+
+            array_offsets: Vec::new(),
+            bound_locals: Vec::new(),
+            local_srcs: HashMap::new(),
+            single_def_locals: single_def_locals(mir),
+
+            alias_args: Vec::new(),
         }
     }
 
+    /// Build a context for the initializer of a `static` or `const`.
+    pub fn new_static(cx: &'c mut Ctxt<'a, 'tcx>,
+                      def_id: DefId,
+                      mir: &'a Mir<'tcx>) -> IntraCtxt<'c, 'a, 'tcx> {
+        let mut icx = IntraCtxt::new(cx, def_id, mir);
+        icx.is_static = true;
+        icx
+    }
+
     fn enter_block(&mut self, bbid: BasicBlock) {
         self.bbid = bbid;
         // Obviously bogus statement index
@@ -114,8 +192,17 @@ impl<'c, 'a, 'tcx> IntraCtxt<'c, 'a, 'tcx> {
 
 
     pub fn init(&mut self) {
-        let sig = self.cx.variant_func_sig(self.def_id);
-        let sig = self.relabel_sig(sig);
+        let sig = if self.is_static {
+            // An initializer takes no arguments, and its return place is the static itself.
+            let output = self.static_ty(self.def_id);
+            FnSig { inputs: &[], output: output }
+        } else {
+            if is_abi_visible(self.cx.tcx, self.def_id) {
+                self.cx.variant_summ(self.def_id).0.sig_frozen = true;
+            }
+            let sig = self.cx.variant_func_sig(self.def_id);
+            self.relabel_sig(sig)
+        };
         for (l, decl) in self.mir.local_decls.iter_enumerated() {
             let lty =
                 if l.index() == 0 { sig.output }
@@ -125,7 +212,12 @@ impl<'c, 'a, 'tcx> IntraCtxt<'c, 'a, 'tcx> {
         }
 
         // Pick up any preset constraints for this variant.
-        self.cset = self.cx.variant_summ(self.def_id).1.inst_cset.clone();
+        if !self.is_static {
+            self.cset = self.cx.variant_summ(self.def_id).1.inst_cset.clone();
+        }
+        if self.cx.track_provenance {
+            self.cset.track_provenance();
+        }
     }
 
     fn relabel_ty(&mut self, lty: LTy<'tcx>) -> ITy<'tcx> {
@@ -150,7 +242,7 @@ impl<'c, 'a, 'tcx> IntraCtxt<'c, 'a, 'tcx> {
         }
     }
 
-    pub fn finish(mut self) {
+    pub fn finish(mut self) -> FnSummary<'tcx> {
         eprintln!("  original constraints:");
         for &(a, b) in self.cset.iter() {
             eprintln!("    {:?} <= {:?}", a, b);
@@ -173,9 +265,145 @@ impl<'c, 'a, 'tcx> IntraCtxt<'c, 'a, 'tcx> {
             eprintln!("    {:?} <= {:?}", a, b);
         }
 
-        let (_func, var) = self.cx.variant_summ(self.def_id);
-        var.inst_cset = self.cset;
-        var.insts = self.insts;
+        let nullable = self.nullable_sig_vars();
+        let array_ptrs = self.array_sig_vars();
+        let bound_args = self.bound_args();
+
+        FnSummary {
+            inst_cset: self.cset,
+            insts: self.insts,
+            nullable: nullable,
+            array_ptrs: array_ptrs,
+            bound_args: bound_args,
+            alias_args: self.alias_args,
+        }
+    }
+
+    /// Find the signature variables labeling pointers that may be null: those that may hold a
+    /// null value, and those whose value is checked for null.
+    ///
+    /// This is purely intraprocedural: a pointer passed to a callee that checks it for null is
+    /// not flagged here.  It is also flow-insensitive, so a pointer that is null-checked and then
+    /// dereferenced on every path is still reported as nullable.
+    fn nullable_sig_vars(&self) -> Vec<Var> {
+        let mut forward = HashMap::new();
+        let mut backward = HashMap::new();
+        for &(lhs, rhs) in &self.null_flows {
+            forward.entry(rhs).or_insert_with(Vec::new).push(lhs);
+            backward.entry(lhs).or_insert_with(Vec::new).push(rhs);
+        }
+
+        let values = reachable(&forward, self.null_values.iter().cloned());
+
+        let mut checks = self.null_checks.clone();
+        for &(a, b) in &self.null_compares {
+            if values.contains(&a) {
+                checks.push(b);
+            }
+            if values.contains(&b) {
+                checks.push(a);
+            }
+        }
+        let checked = reachable(&backward, checks.into_iter());
+
+        let mut vars = values.iter().chain(checked.iter())
+            .filter_map(|&p| match p {
+                Perm::SigVar(v) => Some(v),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        vars.sort();
+        vars.dedup();
+        vars
+    }
+
+    /// Find the signature variables labeling pointers to arrays: those offset, directly or
+    /// through a copy, by an amount not known to be zero.  A pointer only offset by zero, as in
+    /// `*p.offset(0)`, points to a single value.
+    fn array_sig_vars(&self) -> Vec<Var> {
+        let mut backward = HashMap::new();
+        for &(lhs, rhs) in &self.null_flows {
+            backward.entry(lhs).or_insert_with(Vec::new).push(rhs);
+        }
+
+        let offset = reachable(&backward, self.array_offsets.iter().cloned());
+
+        let mut vars = offset.iter()
+            .filter_map(|&p| match p {
+                Perm::SigVar(v) => Some(v),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        vars.sort();
+        vars.dedup();
+        vars
+    }
+
+    /// Find the integer arguments that are compared with `<`, `<=`, `>`, or `>=`, directly or
+    /// through copies and casts, as the length is in `while i < len`.
+    fn bound_args(&self) -> Vec<usize> {
+        let mut args = self.bound_locals.iter()
+            .filter_map(|&l| self.arg_source(l))
+            .collect::<Vec<_>>();
+        args.sort();
+        args.dedup();
+        args
+    }
+
+    /// Follow `op` back through the locals it was copied or cast from, as long as each has that
+    /// one definition.  Any other definition, like an arithmetic result or a call destination,
+    /// ends the trace.
+    fn trace_operand<'o>(&'o self, mut op: &'o Operand<'tcx>) -> &'o Operand<'tcx> {
+        // Copies can form a cycle, but never a longer one than there are locals.
+        for _ in 0 .. self.mir.local_decls.len() {
+            let l = match *op {
+                Operand::Copy(Place::Local(l)) |
+                Operand::Move(Place::Local(l)) => l,
+                _ => break,
+            };
+            if self.is_arg(l) || !self.single_def_locals.contains(&l) {
+                break;
+            }
+            op = match self.local_srcs.get(&l) {
+                Some(src) => src,
+                None => break,
+            };
+        }
+        op
+    }
+
+    fn is_arg(&self, l: Local) -> bool {
+        l.index() >= 1 && l.index() <= self.mir.arg_count
+    }
+
+    /// The index of the argument that local `l` was copied or cast from, if any.
+    fn arg_source(&self, l: Local) -> Option<usize> {
+        match *self.trace_operand(&Operand::Copy(Place::Local(l))) {
+            Operand::Copy(Place::Local(src)) |
+            Operand::Move(Place::Local(src)) if self.is_arg(src) => Some(src.index() - 1),
+            _ => None,
+        }
+    }
+
+    /// Whether `op` is the constant zero, possibly cast, as in `p.offset(0 as isize)`.
+    fn is_const_zero(&self, op: &Operand<'tcx>) -> bool {
+        match *self.trace_operand(op) {
+            Operand::Constant(ref c) => match c.literal {
+                Literal::Value { value } => match value.val {
+                    ConstVal::Integral(i) => i.to_u128_unchecked() == 0,
+                    _ => false,
+                },
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    fn add_null_flow(&mut self, lhs: Perm<'tcx>, rhs: Perm<'tcx>) {
+        // `Min`s come from taking the address of a place, which never gives null.
+        if lhs.as_var().is_some() && rhs.as_var().is_some() {
+            self.null_flows.push((lhs, rhs));
+        }
     }
 
     fn local_ty(&mut self, ty: Ty<'tcx>) -> ITy<'tcx> {
@@ -300,16 +528,48 @@ impl<'c, 'a, 'tcx> IntraCtxt<'c, 'a, 'tcx> {
                 let cast_ty = self.local_ty(cast_raw_ty);
                 let (op_ty, op_perm) = self.operand_lty(op);
                 self.propagate(cast_ty, op_ty, Perm::move_());
+                // Casting zero to a pointer is how translated code spells `NULL`.
+                if let Label::Ptr(p) = cast_ty.label {
+                    if self.is_const_zero(op) {
+                        self.null_values.push(p);
+                    }
+                }
                 (cast_ty, op_perm)
             },
-            Rvalue::BinaryOp(op, ref a, ref _b) |
-            Rvalue::CheckedBinaryOp(op, ref a, ref _b) => match op {
+            Rvalue::BinaryOp(op, ref a, ref b) |
+            Rvalue::CheckedBinaryOp(op, ref a, ref b) => match op {
+                BinOp::Eq | BinOp::Ne if self.is_raw_ptr(a) && self.is_raw_ptr(b) => {
+                    let (a_ty, _) = self.operand_lty(a);
+                    let (b_ty, _) = self.operand_lty(b);
+                    if let (Label::Ptr(a_perm), Label::Ptr(b_perm)) = (a_ty.label, b_ty.label) {
+                        self.null_compares.push((a_perm, b_perm));
+                    }
+                    (self.local_ty(ty), Perm::move_())
+                },
+
+                BinOp::Lt | BinOp::Le | BinOp::Ge | BinOp::Gt => {
+                    for op in &[a, b] {
+                        match **op {
+                            Operand::Copy(Place::Local(l)) |
+                            Operand::Move(Place::Local(l))
+                                    if op.ty(self.mir, self.cx.tcx).is_integral() =>
+                                self.bound_locals.push(l),
+                            _ => {},
+                        }
+                    }
+                    (self.local_ty(ty), Perm::move_())
+                },
+
                 BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div | BinOp::Rem |
                 BinOp::BitXor | BinOp::BitAnd | BinOp::BitOr | BinOp::Shl | BinOp::Shr |
-                BinOp::Eq | BinOp::Lt | BinOp::Le | BinOp::Ne | BinOp::Ge | BinOp::Gt =>
+                BinOp::Eq | BinOp::Ne =>
                     (self.local_ty(ty), Perm::move_()),
 
-                BinOp::Offset => self.operand_lty(a),
+                BinOp::Offset => {
+                    let (a_ty, a_perm) = self.operand_lty(a);
+                    self.add_array_offset(a_ty, b);
+                    (a_ty, a_perm)
+                },
             },
             Rvalue::NullaryOp(_op, _ty) => unimplemented!(),
             Rvalue::UnaryOp(op, ref _a) => match op {
@@ -363,6 +623,13 @@ impl<'c, 'a, 'tcx> IntraCtxt<'c, 'a, 'tcx> {
         }
     }
 
+    fn is_raw_ptr(&self, op: &Operand<'tcx>) -> bool {
+        match op.ty(self.mir, self.cx.tcx).sty {
+            TypeVariants::TyRawPtr(_) => true,
+            _ => false,
+        }
+    }
+
     fn operand_lty(&mut self, op: &Operand<'tcx>) -> (ITy<'tcx>, Perm<'tcx>) {
         match *op {
             Operand::Copy(ref lv) => self.place_lty(lv),
@@ -387,6 +654,7 @@ impl<'c, 'a, 'tcx> IntraCtxt<'c, 'a, 'tcx> {
     fn propagate(&mut self, lhs: ITy<'tcx>, rhs: ITy<'tcx>, path_perm: Perm<'tcx>) {
         if let (Label::Ptr(l_perm), Label::Ptr(r_perm)) = (lhs.label, rhs.label) {
             self.propagate_perm(l_perm, r_perm);
+            self.add_null_flow(l_perm, r_perm);
 
             // This is the "collection hack".
             //
@@ -410,6 +678,8 @@ impl<'c, 'a, 'tcx> IntraCtxt<'c, 'a, 'tcx> {
         if let (Label::Ptr(l_perm), Label::Ptr(r_perm)) = (lhs.label, rhs.label) {
             self.propagate_perm(l_perm, r_perm);
             self.propagate_perm(r_perm, l_perm);
+            self.add_null_flow(l_perm, r_perm);
+            self.add_null_flow(r_perm, l_perm);
         } else if let (Label::FnDef(l_inst), Label::FnDef(r_inst)) = (lhs.label, rhs.label) {
             self.unify_inst_vars(l_inst, r_inst);
         }
@@ -421,9 +691,70 @@ impl<'c, 'a, 'tcx> IntraCtxt<'c, 'a, 'tcx> {
         }
     }
 
+    /// Require MOVE for every pointer in `ty`.
+    fn require_move(&mut self, ty: ITy<'tcx>) {
+        if let Label::Ptr(perm) = ty.label {
+            self.propagate_perm(Perm::move_(), perm);
+        }
+        for &arg in ty.args {
+            self.require_move(arg);
+        }
+    }
+
+    /// Pointers stored into a `static mut` outlive the function storing them, and any function can
+    /// reach them later, so the global owns them.  Require MOVE for the pointers in `lv`'s storage
+    /// if `lv` is part of a `static mut`.  This raises the static's permissions (see
+    /// `InterCtxt::update_statics`), so the stored values must be MOVE too, and pointers read
+    /// back out of the static may be as well.
+    fn store_to_static_mut(&mut self, lv: &Place<'tcx>, lv_ty: ITy<'tcx>) {
+        if self.is_static_mut_storage(lv) {
+            self.require_move(lv_ty);
+        }
+    }
+
+    /// Check if `lv` is part of the storage of a `static mut` itself, such as one of its fields
+    /// or elements, rather than data reached through a pointer stored there.
+    fn is_static_mut_storage(&self, lv: &Place<'tcx>) -> bool {
+        match *lv {
+            Place::Local(_) => false,
+            Place::Static(ref s) => self.cx.tcx.is_static_mut(s.def_id),
+            Place::Projection(ref p) => {
+                match p.elem {
+                    ProjectionElem::Deref => false,
+                    _ => self.is_static_mut_storage(&p.base),
+                }
+            },
+        }
+    }
+
     fn propagate_perm(&mut self, p1: Perm<'tcx>, p2: Perm<'tcx>) {
         eprintln!("ADD: {:?} <= {:?}", p1, p2);
-        self.cset.add(p1, p2);
+        match self.origin() {
+            Some(origin) => self.cset.add_with_origin(p1, p2, origin),
+            None => self.cset.add(p1, p2),
+        }
+    }
+
+    /// The statement being processed, as the origin of the constraints it adds.  `None` unless
+    /// tracking provenance, and outside of statements (e.g. for constraints from the signature).
+    fn origin(&self) -> Option<Origin> {
+        if !self.cx.track_provenance || self.stmt_idx == !0 {
+            return None;
+        }
+
+        let loc = Location { block: self.bbid, statement_index: self.stmt_idx };
+        let span = self.mir.source_info(loc).span;
+        let desc = match self.cx.tcx.sess.codemap().span_to_snippet(span) {
+            Ok(snippet) => snippet,
+            Err(_) => {
+                let bb = &self.mir[self.bbid];
+                match bb.statements.get(self.stmt_idx) {
+                    Some(s) => format!("{:?}", s),
+                    None => format!("{:?}", bb.terminator().kind),
+                }
+            },
+        };
+        Some(Origin { span: span, desc: desc })
     }
 
     fn unify_inst_vars(&mut self, idx1: usize, idx2: usize) {
@@ -501,10 +832,20 @@ impl<'c, 'a, 'tcx> IntraCtxt<'c, 'a, 'tcx> {
             self.enter_stmt(idx);
             match s.kind {
                 StatementKind::Assign(ref lv, ref rv) => {
+                    if let Place::Local(l) = *lv {
+                        match *rv {
+                            Rvalue::Use(ref op) |
+                            Rvalue::Cast(_, ref op, _) => {
+                                self.local_srcs.insert(l, op.clone());
+                            },
+                            _ => {},
+                        }
+                    }
                     let (lv_ty, lv_perm) = self.place_lty(lv);
                     let (rv_ty, rv_perm) = self.rvalue_lty(rv);
                     self.propagate(lv_ty, rv_ty, rv_perm);
                     self.propagate_perm(Perm::write(), lv_perm);
+                    self.store_to_static_mut(lv, lv_ty);
                     eprintln!("    {:?}: {:?}", lv, lv_ty);
                     eprintln!("    ^-- {:?}: {:?}", rv, rv_ty);
                 },
@@ -520,6 +861,7 @@ impl<'c, 'a, 'tcx> IntraCtxt<'c, 'a, 'tcx> {
             }
         }
 
+        self.enter_stmt(bb.statements.len());
         match bb.terminator().kind {
             TerminatorKind::Goto { .. } |
             TerminatorKind::FalseEdges { .. } |
@@ -538,6 +880,7 @@ impl<'c, 'a, 'tcx> IntraCtxt<'c, 'a, 'tcx> {
                 let (val_ty, val_perm) = self.operand_lty(value);
                 self.propagate(loc_ty, val_ty, val_perm);
                 self.propagate_perm(Perm::write(), loc_perm);
+                self.store_to_static_mut(location, loc_ty);
                 eprintln!("    {:?}: {:?}", location, loc_ty);
                 eprintln!("    ^-- {:?}: {:?}", value, val_ty);
             },
@@ -555,6 +898,14 @@ impl<'c, 'a, 'tcx> IntraCtxt<'c, 'a, 'tcx> {
                     eprintln!("    (arg): {:?}", sig_ty);
                     eprintln!("    ^-- {:?}: {:?}", arg, arg_ty);
                 }
+                // The target of a call through a function pointer is unknown, so it may take
+                // ownership of anything passed to it.
+                if let TypeVariants::TyFnPtr(_) = func_ty.ty.sty {
+                    for &sig_ty in sig.inputs {
+                        self.require_move(sig_ty);
+                    }
+                }
+
                 if let Some((ref dest, _)) = *destination {
                     let sig_ty = sig.output;
                     let (dest_ty, dest_perm) = self.place_lty(dest);
@@ -563,7 +914,271 @@ impl<'c, 'a, 'tcx> IntraCtxt<'c, 'a, 'tcx> {
                     eprintln!("    {:?}: {:?}", dest, dest_ty);
                     eprintln!("    ^-- (return): {:?}", sig_ty);
                 }
+
+                if let TypeVariants::TyFnDef(callee, _) = func_ty.ty.sty {
+                    self.handle_null_call(callee, args, destination.as_ref().map(|&(ref d, _)| d));
+                    self.handle_offset_call(callee, args);
+                    self.handle_alias_call(callee, args);
+
+                    let is_realloc = inter::intrinsic(self.cx.tcx, callee) == Some(Intrinsic::Realloc);
+                    if let (true, Some(arg), Some(&(_, target))) =
+                            (is_realloc, args.get(0), destination.as_ref()) {
+                        self.check_uses_after_realloc(arg, target);
+                    }
+                }
             },
         }
     }
+
+    /// Record the nullability facts implied by a call: `p.is_null()` says `p` may be null, and
+    /// `ptr::null()`, `ptr::null_mut()`, and fallible allocations may return null.
+    fn handle_null_call(&mut self,
+                        callee: DefId,
+                        args: &[Operand<'tcx>],
+                        dest: Option<&Place<'tcx>>) {
+        let path = self.cx.tcx.absolute_item_path_str(callee);
+        let name = path.rsplit("::").next().unwrap_or(&path).to_owned();
+
+        if name == "is_null" && self.is_raw_ptr_method(callee) {
+            if let Some(arg) = args.get(0) {
+                let (arg_ty, _) = self.operand_lty(arg);
+                if let Label::Ptr(p) = arg_ty.label {
+                    self.null_checks.push(p);
+                }
+            }
+            return;
+        }
+
+        let returns_null = match inter::intrinsic(self.cx.tcx, callee) {
+            Some(Intrinsic::Alloc) | Some(Intrinsic::Realloc) => true,
+            _ => path.ends_with("ptr::null") || path.ends_with("ptr::null_mut"),
+        };
+        if let (true, Some(dest)) = (returns_null, dest) {
+            let (dest_ty, _) = self.place_lty(dest);
+            if let Label::Ptr(p) = dest_ty.label {
+                self.null_values.push(p);
+            }
+        }
+    }
+
+    /// Whether `def_id` is an inherent method of raw pointers, like `<*const T>::is_null`, rather
+    /// than a function or method of some other type that happens to have the same name.
+    fn is_raw_ptr_method(&self, def_id: DefId) -> bool {
+        let tcx = self.cx.tcx;
+        let impl_id = match tcx.impl_of_method(def_id) {
+            Some(id) => id,
+            None => return false,
+        };
+        if tcx.impl_trait_ref(impl_id).is_some() {
+            return false;
+        }
+        match tcx.type_of(impl_id).sty {
+            TypeVariants::TyRawPtr(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Record the array indexing done by a call: `p.offset(n)` indexes `p` unless `n` is zero.
+    fn handle_offset_call(&mut self, callee: DefId, args: &[Operand<'tcx>]) {
+        let path = self.cx.tcx.absolute_item_path_str(callee);
+        let name = path.rsplit("::").next().unwrap_or(&path);
+        if name != "offset" && name != "wrapping_offset" {
+            return;
+        }
+
+        if let (Some(ptr), Some(count)) = (args.get(0), args.get(1)) {
+            if self.is_raw_ptr(ptr) {
+                let (ptr_ty, _) = self.operand_lty(ptr);
+                self.add_array_offset(ptr_ty, count);
+            }
+        }
+    }
+
+    /// Record the pairs of pointer arguments of a call that are copies of the same pointer, as in
+    /// `f(p, p)`.  Borrows of the same place, as in `f(&mut x, &mut x)`, are not detected.
+    fn handle_alias_call(&mut self, callee: DefId, args: &[Operand<'tcx>]) {
+        let roots = args.iter()
+            .map(|op| {
+                if !self.is_raw_ptr(op) {
+                    return None;
+                }
+                match *self.trace_operand(op) {
+                    Operand::Copy(Place::Local(l)) |
+                    Operand::Move(Place::Local(l)) => Some(l),
+                    _ => None,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        for (i, &root1) in roots.iter().enumerate() {
+            for (j, &root2) in roots.iter().enumerate().skip(i + 1) {
+                if root1.is_some() && root1 == root2 {
+                    self.alias_args.push((callee, i, j));
+                }
+            }
+        }
+    }
+
+    fn add_array_offset(&mut self, ptr_ty: ITy<'tcx>, count: &Operand<'tcx>) {
+        if let Label::Ptr(p) = ptr_ty.label {
+            if !self.is_const_zero(count) {
+                self.array_offsets.push(p);
+            }
+        }
+    }
+
+    /// `realloc` may free its argument, so warn about uses of the old pointer after the call,
+    /// on paths where it hasn't been assigned again first.  These are only warnings: when
+    /// `realloc` fails, it leaves the old pointer alone, and code handling the failure can keep
+    /// using it.
+    fn check_uses_after_realloc(&self, arg: &Operand<'tcx>, target: BasicBlock) {
+        let mut old = match *arg {
+            Operand::Copy(Place::Local(l)) |
+            Operand::Move(Place::Local(l)) => l,
+            _ => return,
+        };
+
+        // The pointer usually reaches the call through a cast into a temporary.
+        for s in self.mir[self.bbid].statements.iter().rev() {
+            if let StatementKind::Assign(Place::Local(l), ref rv) = s.kind {
+                if l != old {
+                    continue;
+                }
+                match *rv {
+                    Rvalue::Use(Operand::Copy(Place::Local(src))) |
+                    Rvalue::Use(Operand::Move(Place::Local(src))) |
+                    Rvalue::Cast(_, Operand::Copy(Place::Local(src)), _) |
+                    Rvalue::Cast(_, Operand::Move(Place::Local(src)), _) => old = src,
+                    _ => break,
+                }
+            }
+        }
+
+        // Only user variables can be mentioned again after the call.
+        let name = match self.mir.local_decls[old].name {
+            Some(name) => name,
+            None => return,
+        };
+
+        let mut visited = HashSet::new();
+        let mut pending = vec![target];
+        while let Some(bbid) = pending.pop() {
+            if !visited.insert(bbid) {
+                continue;
+            }
+
+            let bb = &self.mir[bbid];
+            let mut finder = LocalUseFinder { local: old, used: false, assigned: false };
+            for (idx, s) in bb.statements.iter().enumerate() {
+                finder.visit_statement(bbid, s, Location { block: bbid, statement_index: idx });
+                if finder.used {
+                    self.warn_use_after_realloc(s.source_info.span, name);
+                    return;
+                }
+                if finder.assigned {
+                    break;
+                }
+            }
+            if finder.assigned {
+                continue;
+            }
+
+            let term = bb.terminator();
+            let loc = Location { block: bbid, statement_index: bb.statements.len() };
+            finder.visit_terminator(bbid, term, loc);
+            if finder.used {
+                self.warn_use_after_realloc(term.source_info.span, name);
+                return;
+            }
+            if !finder.assigned {
+                pending.extend(term.successors().iter().cloned());
+            }
+        }
+    }
+
+    fn warn_use_after_realloc(&self, span: Span, name: Name) {
+        self.cx.tcx.sess.span_warn(
+            span, &format!("`{}` is used after being passed to `realloc`, which may free it", name));
+    }
+}
+
+
+/// Finds the first use or assignment of a local.
+struct LocalUseFinder {
+    local: Local,
+    used: bool,
+    assigned: bool,
+}
+
+impl<'tcx> Visitor<'tcx> for LocalUseFinder {
+    fn visit_local(&mut self, local: &Local, context: PlaceContext<'tcx>, _location: Location) {
+        if *local != self.local || self.used || self.assigned {
+            return;
+        }
+        match context {
+            PlaceContext::Store |
+            PlaceContext::Call => self.assigned = true,
+            PlaceContext::StorageLive |
+            PlaceContext::StorageDead |
+            PlaceContext::Validate => {},
+            _ => self.used = true,
+        }
+    }
+}
+
+/// Counts the definitions of each local.  A mutable borrow or a write through a projection counts
+/// as any number of them.
+struct LocalDefCounter {
+    defs: HashMap<Local, usize>,
+}
+
+impl<'tcx> Visitor<'tcx> for LocalDefCounter {
+    fn visit_local(&mut self, local: &Local, context: PlaceContext<'tcx>, _location: Location) {
+        let n = match context {
+            PlaceContext::Store |
+            PlaceContext::Call |
+            PlaceContext::AsmOutput => 1,
+            PlaceContext::Borrow { kind: BorrowKind::Shared, .. } => 0,
+            PlaceContext::Borrow { .. } |
+            PlaceContext::Projection(Mutability::Mut) => 2,
+            _ => 0,
+        };
+        *self.defs.entry(*local).or_insert(0) += n;
+    }
+}
+
+/// The locals of `mir` that are assigned exactly once.
+fn single_def_locals<'tcx>(mir: &Mir<'tcx>) -> HashSet<Local> {
+    let mut counter = LocalDefCounter { defs: HashMap::new() };
+    counter.visit_mir(mir);
+    counter.defs.into_iter()
+        .filter(|&(_, n)| n == 1)
+        .map(|(l, _)| l)
+        .collect()
+}
+
+/// Find everything reachable from `roots` along `edges`.
+fn reachable<'tcx, I>(edges: &HashMap<Perm<'tcx>, Vec<Perm<'tcx>>>,
+                      roots: I) -> HashSet<Perm<'tcx>>
+        where I: Iterator<Item=Perm<'tcx>> {
+    let mut seen = HashSet::new();
+    let mut stack = roots.collect::<Vec<_>>();
+    while let Some(p) = stack.pop() {
+        if !seen.insert(p) {
+            continue;
+        }
+        if let Some(next) = edges.get(&p) {
+            stack.extend(next.iter().cloned());
+        }
+    }
+    seen
+}
+
+/// Can C code call this variant directly?  Its body is still analyzed as usual, but C callers
+/// depend on the raw pointers in its signature.
+pub fn is_abi_visible<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, def_id: DefId) -> bool {
+    let attrs = tcx.get_attrs(def_id);
+    attr::contains_name(&attrs, "no_mangle") ||
+        attr::contains_name(&attrs, "export_name") ||
+        tcx.fn_sig(def_id).abi() != Abi::Rust
 }
@@ -0,0 +1,240 @@
+//! Machine-readable export of analysis results.
+//!
+//! The JSON document has one entry per analysis-level function, sorted by `DefId`:
+//!
+//! ```text
+//! {
+//!     "functions": [
+//!         {
+//!             "def_path": "::f[0]",
+//!             "num_sig_vars": 2,
+//!             "inputs": [{ "ty": "*mut i32", "vars": [0] }],
+//!             "output": { "ty": "*mut i32", "vars": [1] },
+//!             "monos": [
+//!                 {
+//!                     "suffix": "mut",
+//!                     "assign": ["WRITE", "WRITE"],
+//!                     "callees": [{ "def_path": "::g[0]", "mono_idx": 1 }]
+//!                 }
+//!             ]
+//!         }
+//!     ]
+//! }
+//! ```
+//!
+//! `vars` lists the `SigVar` index of each pointer in the type, in preorder.  The `callees` of a
+//! mono correspond to the function references of its variant, each with the index of the callee
+//! mono chosen for it.  `FuncJson` and friends can also be read back with `from_json`, so tools
+//! can load and compare the output of several runs.
+
+use json::{self, JsonValue};
+use rustc::hir::def_id::DefId;
+
+use driver;
+
+use super::{AnalysisResult, ConcretePerm, VTy};
+
+
+/// A type from a polymorphic signature.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TyJson {
+    pub ty: String,
+    /// `SigVar` indices of the pointers in `ty`, in preorder.
+    pub vars: Vec<u32>,
+}
+
+/// A monomorphization, along with the callee monos it uses.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct MonoJson {
+    pub suffix: String,
+    pub assign: Vec<ConcretePerm>,
+    /// Def path of each referenced function, with the index of the mono chosen for it.
+    pub callees: Vec<(String, usize)>,
+}
+
+/// An analysis-level function.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct FuncJson {
+    pub def_path: String,
+    pub num_sig_vars: u32,
+    pub inputs: Vec<TyJson>,
+    pub output: TyJson,
+    pub monos: Vec<MonoJson>,
+}
+
+pub fn perm_str(p: ConcretePerm) -> &'static str {
+    match p {
+        ConcretePerm::Read => "READ",
+        ConcretePerm::Write => "WRITE",
+        ConcretePerm::Move => "MOVE",
+    }
+}
+
+pub fn parse_perm(s: &str) -> Result<ConcretePerm, String> {
+    match s {
+        "READ" => Ok(ConcretePerm::Read),
+        "WRITE" => Ok(ConcretePerm::Write),
+        "MOVE" => Ok(ConcretePerm::Move),
+        _ => Err(format!("unknown permission `{}`", s)),
+    }
+}
+
+pub fn get_str(obj: &JsonValue, key: &str) -> Result<String, String> {
+    obj[key].as_str().map(|s| s.to_owned())
+        .ok_or_else(|| format!("expected string field `{}`", key))
+}
+
+pub fn get_usize(obj: &JsonValue, key: &str) -> Result<usize, String> {
+    obj[key].as_usize().ok_or_else(|| format!("expected integer field `{}`", key))
+}
+
+pub fn get_array<'a>(obj: &'a JsonValue, key: &str) -> Result<&'a JsonValue, String> {
+    if obj[key].is_array() {
+        Ok(&obj[key])
+    } else {
+        Err(format!("expected array field `{}`", key))
+    }
+}
+
+impl TyJson {
+    fn from_lty(lty: VTy) -> TyJson {
+        let mut vars = Vec::new();
+        lty.for_each_label(&mut |l| {
+            if let Some(v) = *l {
+                vars.push(v.0);
+            }
+        });
+        TyJson {
+            ty: format!("{:?}", lty.ty),
+            vars: vars,
+        }
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        object! {
+            "ty" => self.ty.clone(),
+            "vars" => self.vars.clone()
+        }
+    }
+
+    pub fn from_json(obj: &JsonValue) -> Result<TyJson, String> {
+        let vars = get_array(obj, "vars")?.members()
+            .map(|v| v.as_u32().ok_or_else(|| format!("expected var index, got {}", v)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(TyJson {
+            ty: get_str(obj, "ty")?,
+            vars: vars,
+        })
+    }
+}
+
+impl MonoJson {
+    pub fn to_json(&self) -> JsonValue {
+        let callees = self.callees.iter().map(|&(ref def_path, mono_idx)| object! {
+            "def_path" => def_path.clone(),
+            "mono_idx" => mono_idx
+        }).collect::<Vec<_>>();
+        object! {
+            "suffix" => self.suffix.clone(),
+            "assign" => self.assign.iter().map(|&p| perm_str(p)).collect::<Vec<_>>(),
+            "callees" => callees
+        }
+    }
+
+    pub fn from_json(obj: &JsonValue) -> Result<MonoJson, String> {
+        let assign = get_array(obj, "assign")?.members()
+            .map(|p| p.as_str().ok_or_else(|| format!("expected permission, got {}", p))
+                 .and_then(parse_perm))
+            .collect::<Result<Vec<_>, _>>()?;
+        let callees = get_array(obj, "callees")?.members()
+            .map(|c| Ok((get_str(c, "def_path")?, get_usize(c, "mono_idx")?)))
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(MonoJson {
+            suffix: get_str(obj, "suffix")?,
+            assign: assign,
+            callees: callees,
+        })
+    }
+}
+
+impl FuncJson {
+    pub fn to_json(&self) -> JsonValue {
+        object! {
+            "def_path" => self.def_path.clone(),
+            "num_sig_vars" => self.num_sig_vars,
+            "inputs" => self.inputs.iter().map(|t| t.to_json()).collect::<Vec<_>>(),
+            "output" => self.output.to_json(),
+            "monos" => self.monos.iter().map(|m| m.to_json()).collect::<Vec<_>>()
+        }
+    }
+
+    pub fn from_json(obj: &JsonValue) -> Result<FuncJson, String> {
+        let inputs = get_array(obj, "inputs")?.members()
+            .map(TyJson::from_json)
+            .collect::<Result<Vec<_>, _>>()?;
+        let monos = get_array(obj, "monos")?.members()
+            .map(MonoJson::from_json)
+            .collect::<Result<Vec<_>, _>>()?;
+        let num_sig_vars = obj["num_sig_vars"].as_u32()
+            .ok_or_else(|| "expected integer field `num_sig_vars`".to_owned())?;
+        Ok(FuncJson {
+            def_path: get_str(obj, "def_path")?,
+            num_sig_vars: num_sig_vars,
+            inputs: inputs,
+            output: TyJson::from_json(&obj["output"])?,
+            monos: monos,
+        })
+    }
+}
+
+/// Collect the results for each function, sorted by `DefId`.
+pub fn func_results(dcx: &driver::Ctxt, results: &AnalysisResult) -> Vec<FuncJson> {
+    let path_str = |def_id: DefId| dcx.ty_ctxt().def_path(def_id).to_string_no_crate();
+
+    let mut ids = results.funcs.keys().cloned().collect::<Vec<_>>();
+    ids.sort();
+    ids.into_iter().map(|id| {
+        let fr = &results.funcs[&id];
+
+        let monos = (0 .. fr.num_monos).map(|i| {
+            let mr = &results.monos[&(id, i)];
+            let var_id = fr.variants.as_ref().map_or(id, |vars| vars[i]);
+            let vr = &results.variants[&var_id];
+
+            MonoJson {
+                suffix: mr.suffix.clone(),
+                assign: mr.assign.iter().cloned().collect(),
+                callees: vr.func_refs.iter().zip(mr.callee_mono_idxs.iter())
+                    .map(|(func_ref, &mono_idx)| (path_str(func_ref.def_id), mono_idx))
+                    .collect(),
+            }
+        }).collect();
+
+        FuncJson {
+            def_path: path_str(id),
+            num_sig_vars: fr.num_sig_vars,
+            inputs: fr.sig.inputs.iter().map(|&lty| TyJson::from_lty(lty)).collect(),
+            output: TyJson::from_lty(fr.sig.output),
+            monos: monos,
+        }
+    }).collect()
+}
+
+/// Build the JSON document describing the analysis results.
+pub fn results_to_json(dcx: &driver::Ctxt, results: &AnalysisResult) -> JsonValue {
+    let funcs = func_results(dcx, results).iter().map(|f| f.to_json()).collect::<Vec<_>>();
+    object! {
+        "functions" => funcs
+    }
+}
+
+/// Read back a document produced by `results_to_json`.
+pub fn results_from_json(doc: &JsonValue) -> Result<Vec<FuncJson>, String> {
+    get_array(doc, "functions")?.members().map(FuncJson::from_json).collect()
+}
+
+/// Parse the text of a document produced by `results_to_json`.
+pub fn parse_results(text: &str) -> Result<Vec<FuncJson>, String> {
+    let doc = json::parse(text).map_err(|e| format!("{}", e))?;
+    results_from_json(&doc)
+}
@@ -14,15 +14,20 @@
 //! runs interprocedurally to a fixed point, on each function plugging in the complete summaries of
 //! its callees and simplifying to produce a complete summary for the current function.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::io::{self, Write};
+use std::mem;
 use std::u32;
 
 use arena::DroplessArena;
 use rustc::hir;
 use rustc::hir::def_id::{DefId, LOCAL_CRATE};
-use rustc::ty::TyCtxt;
+use rustc::mir::Mir;
+use rustc::ty::{Ty, TyCtxt, TypeVariants};
+use rustc_data_structures::bitvec::BitVector;
 use rustc_data_structures::indexed_vec::{IndexVec, Idx};
+use syntax::ast::{IntTy, UintTy};
 use syntax::codemap::Span;
 
 use analysis::labeled_ty::{LabeledTy, LabeledTyCtxt};
@@ -38,21 +43,26 @@ mod intra;
 mod inter;
 mod mono;
 mod inst;
-/*
 mod mono_filter;
-*/
 mod debug;
+pub mod json;
+pub mod dot;
+pub mod explain;
+pub mod diff;
+pub mod cache;
 
 use self::constraint::*;
-use self::context::Ctxt;
+use self::context::{Ctxt, StaticInitSumm};
+use self::cache::IntraCache;
 use self::annot::{handle_marks, handle_attrs};
-use self::intra::IntraCtxt;
+use self::intra::{IntraCtxt, FnSummary};
 use self::inter::InterCtxt;
-use self::mono::compute_all_mono_sigs;
+pub use self::inter::{Intrinsic, intrinsic, ForeignSummaries, builtin_foreign_summaries};
+use self::mono::{compute_all_mono_sigs, MonoLimits};
 use self::inst::find_instantiations;
-/*
 use self::mono_filter::filter_suspicious_monos;
-*/
+pub use self::mono::MonoLimits;
+pub use self::mono_filter::{MonoFilterConfig, find_dead_monos, default_entry_points};
 use self::debug::*;
 
 
@@ -75,7 +85,7 @@ impl Idx for Var {
 }
 
 /// A permission variable.
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
 pub enum PermVar {
     /// "Static" variables appear in the types of non-function items.  This includes `static` items
     /// as well as `struct`s and other ADTs.  Constraints on static vars are inferred from their
@@ -163,58 +173,347 @@ fn is_fn(hir_map: &hir::map::Map, def_id: DefId) -> bool {
     }
 }
 
+/// Check if a definition is a `static` or `const` item, whose initializer has its own MIR.
+fn is_static(hir_map: &hir::map::Map, def_id: DefId) -> bool {
+    use rustc::hir::map::Node::*;
+
+    match hir_map.get_if_local(def_id) {
+        Some(NodeItem(i)) => match i.node {
+            hir::ItemStatic(..) | hir::ItemConst(..) => true,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
 /// Run the intraprocedural step of polymorphic signature inference.  Results are written back into
 /// the `Ctxt`.
+///
+/// The summaries are built one function at a time and then merged in `DefId` order.  Building a
+/// summary also creates the summaries of the functions it refers to, on first use, so the `Ctxt`
+/// is shared state and the summaries must be produced sequentially.
 fn analyze_intra<'a, 'tcx>(cx: &mut Ctxt<'a, 'tcx>,
                            hir_map: &hir::map::Map<'tcx>,
                            tcx: TyCtxt<'a, 'tcx, 'tcx>) {
-    for &def_id in tcx.mir_keys(LOCAL_CRATE).iter() {
-        // We currently don't process `static` bodies, even though they do have MIR.
-        if !is_fn(hir_map, def_id) {
+    let ids = tcx.mir_keys(LOCAL_CRATE).iter()
+        .map(|&def_id| (def_id, is_static(hir_map, def_id)))
+        .filter(|&(def_id, is_static)| is_fn(hir_map, def_id) || is_static)
+        .collect::<Vec<_>>();
+
+    let mut summs = ids.into_iter()
+        .map(|(def_id, is_static)| {
+            (def_id, is_static, analyze_intra_one(cx, tcx, def_id, is_static))
+        })
+        .collect::<Vec<_>>();
+
+    summs.sort_by_key(|&(def_id, _, _)| def_id);
+    for (def_id, is_static, summ) in summs {
+        merge_intra_summ(cx, def_id, is_static, summ);
+    }
+}
+
+/// Run the intraprocedural step on the functions `roots`, and on every local function reachable
+/// from them through their references to other functions.
+fn analyze_intra_reachable<'a, 'tcx>(cx: &mut Ctxt<'a, 'tcx>,
+                                     hir_map: &hir::map::Map<'tcx>,
+                                     tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                     roots: Vec<DefId>) {
+    let has_body = |def_id| is_fn(hir_map, def_id) && !tcx.is_foreign_item(def_id);
+
+    let mut seen = HashSet::new();
+    let mut queue = roots;
+    while let Some(def_id) = queue.pop() {
+        if !seen.insert(def_id) {
             continue;
         }
 
-        let mir = tcx.optimized_mir(def_id);
+        // All variants of a function share its results, so they all need to be analyzed.
+        queue.extend(cx.variant_summ(def_id).0.variant_ids.iter().cloned());
 
-        let mut local_cx = IntraCtxt::new(cx, def_id, mir);
-        local_cx.init();
+        let summ = analyze_intra_one(cx, tcx, def_id, false);
+        let callees = summ.insts.iter()
+            .map(|inst| inst.callee)
+            .collect::<Vec<_>>();
+        merge_intra_summ(cx, def_id, false, summ);
 
-        for (bbid, bb) in mir.basic_blocks().iter_enumerated() {
-            local_cx.handle_basic_block(bbid, bb);
+        for callee in callees {
+            let variant_ids = cx.func_summ(callee).variant_ids.clone();
+            queue.extend(variant_ids.into_iter().filter(|&id| has_body(id)));
         }
+    }
+}
 
-        local_cx.finish();
+/// Run the intraprocedural step on a single function variant or static initializer.  The `Ctxt`
+/// is only used to look up (or create) the summaries of other items; the result is returned
+/// rather than stored.  If the `Ctxt` has a cache, the summary comes from there when the item's
+/// MIR is unchanged.
+fn analyze_intra_one<'a, 'tcx>(cx: &mut Ctxt<'a, 'tcx>,
+                               tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                               def_id: DefId,
+                               is_static: bool) -> FnSummary<'tcx> {
+    let mir = tcx.optimized_mir(def_id);
+
+    let mut cache = match cx.intra_cache.take() {
+        Some(cache) => cache,
+        None => return build_intra_summ(cx, def_id, is_static, mir),
+    };
+    let summ = cache.get_or_analyze(cx, def_id, is_static, mir, |cx| {
+        build_intra_summ(cx, def_id, is_static, mir)
+    });
+    cx.intra_cache = Some(cache);
+
+    // `IntraCtxt` freezes the signatures C can call, which a cached summary skips.
+    if !is_static && intra::is_abi_visible(tcx, def_id) {
+        cx.variant_summ(def_id).0.sig_frozen = true;
     }
+    summ
 }
 
-/// Run the interprocedural step of polymorphic signature inference.  Results are written back into
-/// the `Ctxt`.
-fn analyze_inter(cx: &mut Ctxt) {
-    let mut inter_cx = InterCtxt::new(cx);
+fn build_intra_summ<'a, 'tcx>(cx: &mut Ctxt<'a, 'tcx>,
+                              def_id: DefId,
+                              is_static: bool,
+                              mir: &'a Mir<'tcx>) -> FnSummary<'tcx> {
+    let mut local_cx =
+        if is_static { IntraCtxt::new_static(cx, def_id, mir) }
+        else { IntraCtxt::new(cx, def_id, mir) };
+    local_cx.init();
+
+    for (bbid, bb) in mir.basic_blocks().iter_enumerated() {
+        local_cx.handle_basic_block(bbid, bb);
+    }
+
+    local_cx.finish()
+}
+
+/// Store the intraprocedural summary of a function variant or static initializer in the `Ctxt`.
+fn merge_intra_summ<'a, 'tcx>(cx: &mut Ctxt<'a, 'tcx>,
+                              def_id: DefId,
+                              is_static: bool,
+                              summ: FnSummary<'tcx>) {
+    if is_static {
+        cx.static_inits.insert(def_id, StaticInitSumm {
+            inst_cset: summ.inst_cset,
+            insts: summ.insts,
+        });
+        return;
+    }
+
+    let (_func, var) = cx.variant_summ(def_id);
+    var.inst_cset = summ.inst_cset;
+    var.insts = summ.insts;
+    var.nullable = summ.nullable;
+    var.array_ptrs = summ.array_ptrs;
+    var.bound_args = summ.bound_args;
+    var.alias_args = summ.alias_args;
+}
+
+/// Run the interprocedural step of polymorphic signature inference, processing each function or
+/// static at most `max_visits` times.  Results are written back into the `Ctxt`.
+fn analyze_inter(cx: &mut Ctxt, max_visits: usize) {
+    let mut inter_cx = InterCtxt::new(cx, max_visits);
     inter_cx.process();
     inter_cx.finish();
 }
 
+/// Record on each callee the pairs of pointer arguments that some call passes the same pointer to.
+/// Both can't become `&mut` without breaking that caller, however much they are written through.
+fn find_alias_conflicts(cx: &mut Ctxt) {
+    let mut calls = Vec::new();
+    for id in cx.variant_ids() {
+        calls.extend(cx.get_variant_summ(id).alias_args.iter().cloned());
+    }
+    calls.sort();
+
+    for (callee, i, j) in calls {
+        let func = cx.variant_summ(callee).0;
+        let pair = {
+            let var = |idx: usize| match func.sig.inputs.get(idx).and_then(|lty| lty.label) {
+                Some(PermVar::Sig(v)) => Some(v),
+                _ => None,
+            };
+            match (var(i), var(j)) {
+                (Some(v1), Some(v2)) => (v1, v2),
+                _ => continue,
+            }
+        };
+        if !func.alias_conflicts.contains(&pair) {
+            func.alias_conflicts.push(pair);
+        }
+    }
+}
+
 /// Run the analysis.
 pub fn analyze<'a, 'tcx>(st: &CommandState,
                          dcx: &driver::Ctxt<'a, 'tcx>)
                          -> AnalysisResult<'tcx> {
+    analyze_with_config(st, dcx, &MonoFilterConfig::default())
+}
+
+/// Run the analysis, dropping the monomorphizations rejected by `filter_config`.
+pub fn analyze_with_config<'a, 'tcx>(st: &CommandState,
+                                     dcx: &driver::Ctxt<'a, 'tcx>,
+                                     filter_config: &MonoFilterConfig)
+                                     -> AnalysisResult<'tcx> {
+    analyze_impl(st, dcx, filter_config, false, None)
+}
+
+/// Like `analyze_with_config`, but reuse the intraprocedural summaries in `cache` for the items
+/// whose MIR hasn't changed, and store those of the rest.  Afterward, the cache holds entries for
+/// exactly the items of the current crate.
+pub fn analyze_with_cache<'a, 'tcx>(st: &CommandState,
+                                    dcx: &driver::Ctxt<'a, 'tcx>,
+                                    filter_config: &MonoFilterConfig,
+                                    cache: &mut IntraCache)
+                                    -> AnalysisResult<'tcx> {
+    analyze_impl(st, dcx, filter_config, false, Some(cache))
+}
+
+/// Like `analyze_with_config`, but also record which statements give rise to each constraint, so
+/// the results can be explained with `FunctionResult::explain`.  This makes the analysis slower
+/// and the results larger.
+pub fn analyze_with_provenance<'a, 'tcx>(st: &CommandState,
+                                         dcx: &driver::Ctxt<'a, 'tcx>,
+                                         filter_config: &MonoFilterConfig)
+                                         -> AnalysisResult<'tcx> {
+    analyze_impl(st, dcx, filter_config, true, None)
+}
+
+fn analyze_impl<'a, 'tcx>(st: &CommandState,
+                          dcx: &driver::Ctxt<'a, 'tcx>,
+                          filter_config: &MonoFilterConfig,
+                          track_provenance: bool,
+                          mut cache: Option<&mut IntraCache>)
+                          -> AnalysisResult<'tcx> {
     let mut cx = Ctxt::new(dcx.ty_ctxt(), dcx.ty_arena());
+    cx.track_provenance = track_provenance;
+    if let Some(ref mut cache) = cache {
+        cache.start_run();
+        cx.intra_cache = Some(mem::replace(&mut **cache, IntraCache::new()));
+    }
 
-    // Process the annotations and marks provided by the user.
+    // Process the foreign summaries, annotations, and marks provided by the user.
+    cx.foreign_summs.extend(st.foreign_summaries().clone());
     handle_attrs(&mut cx, st, dcx);
     handle_marks(&mut cx, st, dcx);
 
     // Compute polymorphic signatures / constraint sets for each function
     analyze_intra(&mut cx, dcx.hir_map(), dcx.ty_ctxt());
-    analyze_inter(&mut cx);
+    if let Some(cache) = cache {
+        *cache = cx.intra_cache.take().unwrap();
+        cache.finish_run();
+    }
+    analyze_inter(&mut cx, filter_config.max_visits);
+    find_alias_conflicts(&mut cx);
 
     // Compute monomorphic signatures and select instantiations in each function
-    compute_all_mono_sigs(&mut cx);
+    compute_all_mono_sigs(&mut cx, &filter_config.limits);
     find_instantiations(&mut cx);
+    let filter = filter_suspicious_monos(&cx, filter_config);
 
     // Convert results to a more usable format.
-    convert_results(&cx)
+    convert_results(&cx, &filter)
+}
+
+/// Run the analysis for a single function, without analyzing the rest of the crate.  Only the
+/// function itself and the local functions reachable from it are analyzed, so the results match
+/// those of `analyze` except where they depend on other code, such as callers of the function or
+/// initializers of the statics it uses.
+///
+/// Returns `None` if `def_id` is not a local `fn` with a body.
+pub fn analyze_function<'a, 'tcx>(st: &CommandState,
+                                  dcx: &driver::Ctxt<'a, 'tcx>,
+                                  def_id: DefId)
+                                  -> Option<FunctionResult<'tcx>> {
+    let hir_map = dcx.hir_map();
+    let tcx = dcx.ty_ctxt();
+    if !is_fn(hir_map, def_id) || tcx.is_foreign_item(def_id) {
+        return None;
+    }
+
+    let mut cx = Ctxt::new(tcx, dcx.ty_arena());
+
+    cx.foreign_summs.extend(st.foreign_summaries().clone());
+    handle_attrs(&mut cx, st, dcx);
+    handle_marks(&mut cx, st, dcx);
+
+    analyze_intra_reachable(&mut cx, hir_map, tcx, vec![def_id]);
+    analyze_inter(&mut cx, inter::DEFAULT_MAX_VISITS);
+    find_alias_conflicts(&mut cx);
+
+    compute_all_mono_sigs(&mut cx, &MonoLimits::default());
+    find_instantiations(&mut cx);
+
+    let mut results = convert_results(&cx, &HashSet::new());
+    let func_id = results.variants.get(&def_id)?.func_id;
+    results.funcs.remove(&func_id)
+}
+
+/// Run the analysis on the local functions for which `include` holds.  The functions they call
+/// are analyzed too, so the results are the same as those of `analyze` except where they depend
+/// on other code, as for `analyze_function`.  Only the included functions appear in the results,
+/// so unlike those of `analyze`, the results may refer to callees with no entry in `funcs`.
+///
+/// A multi-variant function is included if any of its variants is.
+pub fn analyze_filtered<'a, 'tcx>(st: &CommandState,
+                                  dcx: &driver::Ctxt<'a, 'tcx>,
+                                  include: &Fn(DefId) -> bool)
+                                  -> AnalysisResult<'tcx> {
+    let hir_map = dcx.hir_map();
+    let tcx = dcx.ty_ctxt();
+
+    let mut cx = Ctxt::new(tcx, dcx.ty_arena());
+
+    cx.foreign_summs.extend(st.foreign_summaries().clone());
+    handle_attrs(&mut cx, st, dcx);
+    handle_marks(&mut cx, st, dcx);
+
+    let mut roots = tcx.mir_keys(LOCAL_CRATE).iter().cloned()
+        .filter(|&def_id| is_fn(hir_map, def_id) && include(def_id))
+        .collect::<Vec<_>>();
+    roots.sort();
+    analyze_intra_reachable(&mut cx, hir_map, tcx, roots);
+    analyze_inter(&mut cx, inter::DEFAULT_MAX_VISITS);
+    find_alias_conflicts(&mut cx);
+
+    compute_all_mono_sigs(&mut cx, &MonoLimits::default());
+    find_instantiations(&mut cx);
+
+    let mut results = convert_results(&cx, &HashSet::new());
+    let included = results.funcs.iter()
+        .filter(|&(&id, fr)| {
+            match fr.variants {
+                Some(ref var_ids) => var_ids.iter().any(|&var_id| include(var_id)),
+                None => include(id),
+            }
+        })
+        .map(|(&id, _)| id)
+        .collect::<HashSet<_>>();
+    results.funcs.retain(|id, _| included.contains(id));
+    results.variants.retain(|_, vr| included.contains(&vr.func_id));
+    results.monos.retain(|&(id, _), _| included.contains(&id));
+    results
+}
+
+/// A predicate for `analyze_filtered` that holds for the items at or below the path `prefix`,
+/// such as `"foo::bar"` (no leading `::`).  Disambiguators like the `[0]` in `::foo[0]` are
+/// ignored.
+pub fn include_by_path_prefix<'a, 'tcx: 'a>(dcx: &driver::Ctxt<'a, 'tcx>,
+                                            prefix: &str)
+                                            -> Box<Fn(DefId) -> bool + 'a> {
+    let tcx = dcx.ty_ctxt();
+    let prefix = prefix.split("::")
+        .filter(|seg| seg.len() > 0)
+        .map(|seg| seg.to_owned())
+        .collect::<Vec<_>>();
+    Box::new(move |def_id| {
+        let path = tcx.def_path(def_id).to_string_no_crate();
+        let segs = path.split("::")
+            .filter(|seg| seg.len() > 0)
+            .map(|seg| seg.find('[').map_or(seg, |i| &seg[.. i]))
+            .collect::<Vec<_>>();
+        segs.len() >= prefix.len() &&
+            segs.iter().zip(prefix.iter()).all(|(&seg, pre)| seg == pre)
+    })
 }
 
 
@@ -265,6 +564,43 @@ pub struct FunctionResult<'tcx> {
     /// Number of monomorphizations.  If `self.variants` is not `None`, `num_monos` is equal to the
     /// length of that `Vec`.
     pub num_monos: usize,
+
+    /// Pairs of pointer arguments that a caller could point at the same data.  Where one gets
+    /// WRITE and the other READ or WRITE, at most one of them can become a reference.
+    pub potential_alias_pairs: Vec<(Var, Var)>,
+
+    /// Pairs of pointer arguments that some caller actually passes the same pointer to, as in
+    /// `f(p, p)`.  Where either gets WRITE, neither can become a reference, since the caller
+    /// couldn't borrow `p` mutably while it is borrowed for the other.
+    pub alias_conflicts: Vec<(Var, Var)>,
+
+    /// `SigVar`s labeling pointers that may be null in some variant, because they are compared
+    /// against null or assigned from a null constant or a fallible allocation.  These are
+    /// candidates for `Option<Box<T>>` (if owned) or `Option<&T>` (if borrowed).  Nullability is
+    /// inferred within each function only, and without regard to control flow.
+    pub nullable: BitVector,
+
+    /// The signature is visible to C, so its pointers must stay raw.  The function has a single
+    /// mono, whose permissions constrain callers and select callee monos as usual.
+    pub sig_frozen: bool,
+
+    /// Indices of the arguments that the returned pointer may borrow from, in order.  If there
+    /// are several (say, the function returns one argument or another depending on a condition),
+    /// the borrow must be valid for all of them, as in `fn f<'a>(x: &'a T, y: &'a T) -> &'a T`.
+    /// Only meaningful in monos where the return is a borrow rather than MOVE.  Empty for a
+    /// `#[ownership_restrict_return]` function, whose result is unaliased.
+    pub return_borrows_from: Vec<usize>,
+
+    /// The function had more monos than the `MonoLimits` allow, so it got only the one with the
+    /// most permissive outputs, as if its signature were frozen.
+    pub truncated: bool,
+
+    /// Pointer arguments indexed as arrays, by index, each with the index of the integer argument
+    /// that seems to hold its length, if any: the one right after the pointer, as in
+    /// `sum(arr, len)`, or else the only one compared with `<`, `<=`, `>`, or `>=`.  A pointer
+    /// only ever offset by zero is not an array.  Arrays borrowed as READ or WRITE are candidates
+    /// for `&[T]` and `&mut [T]`.
+    pub array_pointers: Vec<(usize, Option<usize>)>,
 }
 
 /// Results specific to a variant `fn`.
@@ -325,10 +661,36 @@ impl<'tcx> AnalysisResult<'tcx> {
         let fr = &self.funcs[&vr.func_id];
         (fr, vr)
     }
+
+    /// Iterate over the constraints on the signature variables of a `fn` item-like.  Each
+    /// `PermExpr::Var` is a `PermVar::Sig` labeling a pointer in the function's `sig`.
+    pub fn fn_constraints<'a>(&'a self, id: DefId) -> Constraints<'a, 'tcx> {
+        let (fr, _) = self.fn_results(id);
+        fr.cset.constraints()
+    }
+}
+
+impl<'tcx> FunctionResult<'tcx> {
+    /// Explain the lower bound on `var`: the statements whose constraints force it above READ,
+    /// each with its source text.  The constraints of callees are followed into their bodies, so
+    /// this may include statements of other functions.  Empty unless the analysis was run with
+    /// `analyze_with_provenance`.
+    pub fn explain(&self, var: Var) -> Vec<(Span, String)> {
+        self.cset.explain(Perm::SigVar(var)).into_iter()
+            .map(|origin| (origin.span, origin.desc))
+            .collect()
+    }
+}
+
+/// Index of mono `idx` of `func` once the monos in `filter` are removed.
+fn filtered_mono_idx(filter: &HashSet<(DefId, usize)>, func: DefId, idx: usize) -> usize {
+    (0 .. idx).filter(|&i| !filter.contains(&(func, i))).count()
 }
 
-/// Extract the useful information from the `Ctxt`, and collect it into an `AnalysisResult`.
-fn convert_results<'a, 'tcx>(cx: &Ctxt<'a, 'tcx>) -> AnalysisResult<'tcx> {
+/// Extract the useful information from the `Ctxt`, and collect it into an `AnalysisResult`.  The
+/// monos in `filter` are left out, and the remaining monos of each function are renumbered.
+fn convert_results<'a, 'tcx>(cx: &Ctxt<'a, 'tcx>,
+                             filter: &HashSet<(DefId, usize)>) -> AnalysisResult<'tcx> {
     let mut r = AnalysisResult {
         statics: HashMap::new(),
         funcs: HashMap::new(),
@@ -374,12 +736,42 @@ fn convert_results<'a, 'tcx>(cx: &Ctxt<'a, 'tcx>) -> AnalysisResult<'tcx> {
             if func.variant_ids.len() == 1 { None }
             else { Some(func.variant_ids.clone()) };
 
+        let mut nullable = BitVector::new(func.num_sig_vars as usize);
+        for &var_id in &func.variant_ids {
+            for &v in &cx.get_variant_summ(var_id).nullable {
+                nullable.insert(v.index());
+            }
+        }
+
+        let mut array_vars = HashSet::new();
+        let mut bound_args = Vec::new();
+        for &var_id in &func.variant_ids {
+            let variant = cx.get_variant_summ(var_id);
+            array_vars.extend(variant.array_ptrs.iter().cloned());
+            bound_args.extend(variant.bound_args.iter().cloned());
+        }
+        bound_args.sort();
+        bound_args.dedup();
+
+        let kept_monos = (0 .. func.num_monos)
+            .filter(|&idx| !filter.contains(&(def_id, idx)))
+            .collect::<Vec<_>>();
+
         r.funcs.insert(def_id, FunctionResult {
             sig: sig,
             num_sig_vars: func.num_sig_vars,
             cset: func.sig_cset.clone(),
             variants: variant_ids,
-            num_monos: func.num_monos,
+            num_monos: kept_monos.len(),
+            potential_alias_pairs: potential_alias_pairs(sig),
+            alias_conflicts: func.alias_conflicts.clone(),
+            nullable: nullable,
+            sig_frozen: func.sig_frozen,
+            return_borrows_from:
+                if func.restrict_return { Vec::new() }
+                else { return_borrows_from(sig, &func.sig_cset) },
+            truncated: func.monos_truncated,
+            array_pointers: array_pointers(sig, &array_vars, &bound_args),
         });
 
 
@@ -408,50 +800,33 @@ fn convert_results<'a, 'tcx>(cx: &Ctxt<'a, 'tcx>) -> AnalysisResult<'tcx> {
         let mut suffixes = Vec::new();
         if func.monos_provided {
             // Do nothing. If monos were provided, we'll use their provided names.
-        } else if func.num_monos == 1 {
+        } else if kept_monos.len() == 1 {
             // Use the original name.
             suffixes.push(String::new());
         } else {
-            /// Default suffixes corresponding to the three concrete permissions.
-            static SUFFIX_BASE: [&'static str; 3] = ["", "mut", "take"];
-            // If more than one mono tries to use the same default suffix, we need to append a
-            // number to disambiguate.
-            let mut suffix_count = [0, 0, 0];
-
             let is_output = mono::infer_outputs(func);
-
-            // Guess a suffix for each mono depending on its output types.  Automatic suffixes look
-            // like "", "mut", "take", "2", "mut3", "take4", etc.
-            for idx in 0 .. func.num_monos {
-                let mono = cx.get_mono_summ(def_id, idx);
-
-                let max_perm = is_output.iter_enumerated()
-                    .filter(|&(_, &out)| out)
-                    .map(|(v, _)| mono.assign[v])
-                    .max().unwrap_or(ConcretePerm::Read);
-
-                let idx = max_perm as usize;
-                suffix_count[idx] += 1;
-                let suffix = if suffix_count[idx] == 1 {
-                    SUFFIX_BASE[idx].to_owned()
-                } else {
-                    format!("{}{}", SUFFIX_BASE[idx], suffix_count[idx])
-                };
-                suffixes.push(suffix);
-            }
+            let assigns = kept_monos.iter()
+                .map(|&idx| &cx.get_mono_summ(def_id, idx).assign)
+                .collect::<Vec<_>>();
+            suffixes = mono_suffixes(&assigns, &is_output);
         }
 
-        for idx in 0 .. func.num_monos {
+        for (new_idx, &idx) in kept_monos.iter().enumerate() {
             let mono = cx.get_mono_summ(def_id, idx);
+            let variant = cx.get_mono_variant_summ(def_id, idx);
 
             let suffix = 
                 if func.monos_provided { mono.suffix.clone() }
-                else { suffixes[idx].clone() };
+                else { suffixes[new_idx].clone() };
 
-            r.monos.insert((def_id, idx), MonoResult {
+            let callee_mono_idxs = variant.insts.iter().zip(mono.callee_mono_idxs.iter())
+                .map(|(inst, &callee_idx)| filtered_mono_idx(filter, inst.callee, callee_idx))
+                .collect();
+
+            r.monos.insert((def_id, new_idx), MonoResult {
                 suffix: suffix,
                 assign: mono.assign.clone(),
-                callee_mono_idxs: mono.callee_mono_idxs.clone(),
+                callee_mono_idxs: callee_mono_idxs,
             });
         }
     }
@@ -459,28 +834,213 @@ fn convert_results<'a, 'tcx>(cx: &Ctxt<'a, 'tcx>) -> AnalysisResult<'tcx> {
     r
 }
 
-/// Print the analysis results to stderr, for debugging.
-pub fn dump_results(dcx: &driver::Ctxt,
-                    results: &AnalysisResult) {
-    eprintln!("\n === summary ===");
+/// Suffix for a mono whose most permissive output has the given permission.
+fn perm_suffix(perm: ConcretePerm) -> &'static str {
+    match perm {
+        ConcretePerm::Read => "",
+        ConcretePerm::Write => "mut",
+        ConcretePerm::Move => "take",
+    }
+}
+
+/// Tag for a single variable's permission in a multi-variable suffix.
+fn perm_tag(perm: ConcretePerm) -> &'static str {
+    match perm {
+        ConcretePerm::Read => "read",
+        ConcretePerm::Write => "mut",
+        ConcretePerm::Move => "take",
+    }
+}
+
+/// Choose a distinct suffix for each mono of a function.
+///
+/// Monos are first named after their most permissive output: "", "mut", or "take".  If two monos
+/// would get the same name, each is instead named by the permissions of the variables that differ
+/// between monos, like "mut_read", preferring output variables over inputs.  Any remaining
+/// collisions get a number appended.
+fn mono_suffixes(assigns: &[&IndexVec<Var, ConcretePerm>],
+                 is_output: &IndexVec<Var, bool>) -> Vec<String> {
+    let outputs = is_output.iter_enumerated()
+        .filter(|&(_, &out)| out)
+        .map(|(v, _)| v)
+        .collect::<Vec<_>>();
+
+    let by_output = assigns.iter().map(|assign| {
+        let max_perm = outputs.iter().map(|&v| assign[v]).max().unwrap_or(ConcretePerm::Read);
+        perm_suffix(max_perm).to_owned()
+    }).collect::<Vec<_>>();
+    if by_output.iter().collect::<HashSet<_>>().len() == by_output.len() {
+        return by_output;
+    }
+
+    // Monos are distinguished by their output permissions, so name them by those when possible.
+    // Only variables whose permission actually varies contribute a tag.  If no output varies,
+    // fall back to the inputs, and keep the output name if nothing varies at all.
+    let varies = |&v: &Var| assigns.iter().any(|assign| assign[v] != assigns[0][v]);
+    let mut varying = outputs.into_iter().filter(&varies).collect::<Vec<_>>();
+    if varying.is_empty() {
+        varying = assigns.get(0)
+            .map_or(vec![], |assign| assign.indices().filter(&varies).collect());
+    }
+    let by_var = if varying.is_empty() {
+        by_output
+    } else {
+        assigns.iter().map(|assign| {
+            varying.iter().map(|&v| perm_tag(assign[v])).collect::<Vec<_>>().join("_")
+        }).collect::<Vec<_>>()
+    };
+
+    // Append a number to any suffix that is already taken.
+    let mut used = HashSet::new();
+    by_var.into_iter().map(|base| {
+        let mut suffix = base.clone();
+        let mut count = 1;
+        while !used.insert(suffix.clone()) {
+            count += 1;
+            suffix = format!("{}{}", base, count);
+        }
+        suffix
+    }).collect()
+}
+
+/// Find the pairs of pointer arguments that may alias.  Rust signatures don't carry C's
+/// `restrict`, so any two pointers to the same type could.  A C `char *` may point into an
+/// object of any type, so pointers to `u8` or `i8` may alias any other pointer.
+fn potential_alias_pairs(sig: VFnSig) -> Vec<(Var, Var)> {
+    let ptrs = sig.inputs.iter()
+        .filter_map(|lty| match (lty.label, lty.args.get(0)) {
+            (Some(v), Some(pointee)) => Some((v, pointee.ty)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let is_char = |ty: Ty| match ty.sty {
+        TypeVariants::TyInt(IntTy::I8) | TypeVariants::TyUint(UintTy::U8) => true,
+        _ => false,
+    };
+
+    let mut pairs = Vec::new();
+    for (i, &(v1, ty1)) in ptrs.iter().enumerate() {
+        for &(v2, ty2) in &ptrs[i + 1 ..] {
+            if ty1 == ty2 || is_char(ty1) || is_char(ty2) {
+                pairs.push((v1, v2));
+            }
+        }
+    }
+    pairs
+}
 
+/// Find the arguments whose outermost pointer the outermost pointer of the return value may have
+/// been copied from, possibly through field projections or calls.  Each copy `q = p` adds a
+/// constraint `q <= p`, so these are the arguments above the return value in `cset`.
+fn return_borrows_from(sig: VFnSig, cset: &ConstraintSet) -> Vec<usize> {
+    let ret = match sig.output.label {
+        Some(v) => Perm::SigVar(v),
+        None => return Vec::new(),
+    };
+
+    let mut above = HashSet::new();
+    cset.for_each_greater_than(ret, |p| {
+        match p {
+            Perm::SigVar(_) => above.insert(p),
+            _ => false,
+        }
+    });
+
+    sig.inputs.iter().enumerate()
+        .filter(|&(_, lty)| lty.label.map_or(false, |v| above.contains(&Perm::SigVar(v))))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Find the pointer arguments labeled by one of `array_vars`, and pair each with its length
+/// argument among `bound_args`, as described on `FunctionResult::array_pointers`.
+fn array_pointers(sig: VFnSig,
+                  array_vars: &HashSet<Var>,
+                  bound_args: &[usize]) -> Vec<(usize, Option<usize>)> {
+    sig.inputs.iter().enumerate()
+        .filter(|&(_, lty)| lty.label.map_or(false, |v| array_vars.contains(&v)))
+        .map(|(i, _)| {
+            let len =
+                if bound_args.contains(&(i + 1)) { Some(i + 1) }
+                else if bound_args.len() == 1 && bound_args[0] != i { Some(bound_args[0]) }
+                else { None };
+            (i, len)
+        })
+        .collect()
+}
+
+/// Render the signature of a mono, with the sig variables of `sig` replaced by their values in
+/// `assign`, as in `[*mut WRITE Point, i32] -> ()`.
+pub fn format_sig(sig: VFnSig, assign: &IndexVec<Var, ConcretePerm>) -> String {
     let arena = DroplessArena::new();
     let new_lcx = LabeledTyCtxt::new(&arena);
-    let format_sig = |sig: VFnSig, assign: &IndexVec<Var, ConcretePerm>| {
-        let mut func = |p: &Option<_>| p.as_ref().map(|&v| assign[v]);
+    let mut func = |p: &Option<_>| p.as_ref().map(|&v| assign[v]);
 
-        let inputs = new_lcx.relabel_slice(sig.inputs, &mut func);
-        let output = new_lcx.relabel(sig.output, &mut func);
-        format!("{:?} -> {:?}", pretty_slice(inputs), Pretty(output))
-    };
+    let inputs = new_lcx.relabel_slice(sig.inputs, &mut func);
+    let output = new_lcx.relabel(sig.output, &mut func);
+    format!("{:?} -> {:?}", pretty_slice(inputs), Pretty(output))
+}
 
-    let path_str = |def_id| dcx.ty_ctxt().def_path(def_id).to_string_no_crate();
+/// Format the polymorphic signature `sig`, naming the `SigVar` of each pointer, as in
+/// `[*mut #f0 i32] -> ()`.
+pub fn format_poly_sig(sig: VFnSig) -> String {
+    let arena = DroplessArena::new();
+    let new_lcx = LabeledTyCtxt::new(&arena);
+    let mut func = |p: &Option<Var>| p.map(|v| PrintVar(Perm::SigVar(v)));
+
+    let inputs = new_lcx.relabel_slice(sig.inputs, &mut func);
+    let output = new_lcx.relabel(sig.output, &mut func);
+    format!("{:?} -> {:?}", pretty_slice(inputs), Pretty(output))
+}
+
+/// The def path of every static, function, variant, and callee in `results`.  Printing the
+/// results names the callee of every call of every mono, so each path is only computed once.
+fn def_paths(dcx: &driver::Ctxt, results: &AnalysisResult) -> HashMap<DefId, String> {
+    let mut paths = HashMap::new();
+    {
+        let mut add = |def_id: DefId| {
+            paths.entry(def_id)
+                .or_insert_with(|| dcx.ty_ctxt().def_path(def_id).to_string_no_crate());
+        };
+        results.statics.keys().cloned().for_each(&mut add);
+        results.funcs.keys().cloned().for_each(&mut add);
+        for (&var_id, vr) in &results.variants {
+            add(var_id);
+            vr.func_refs.iter().map(|func_ref| func_ref.def_id).for_each(&mut add);
+        }
+    }
+    paths
+}
+
+/// Print the analysis results to stderr, for debugging.
+pub fn dump_results(dcx: &driver::Ctxt,
+                    results: &AnalysisResult) {
+    dump_results_to(&mut io::stderr(), dcx, results).unwrap();
+}
+
+/// Write the analysis results to `w`.  Statics, functions, and the calls of each function are
+/// listed in `DefId` order, so the output is the same from run to run.
+pub fn dump_results_to<W: Write>(w: &mut W,
+                                 dcx: &driver::Ctxt,
+                                 results: &AnalysisResult) -> io::Result<()> {
+    writeln!(w, "\n === summary ===")?;
+
+    let paths = def_paths(dcx, results);
+    let path_str = |def_id| paths[&def_id].as_str();
+
+    // Calls keep their index in `func_refs`, which is how monos refer to them.
+    let sorted_calls = |vr: &VariantResult| {
+        let mut calls = vr.func_refs.iter().enumerate().collect::<Vec<_>>();
+        calls.sort_by_key(|&(j, func_ref)| (func_ref.def_id, j));
+        calls
+    };
 
     let mut ids = results.statics.keys().cloned().collect::<Vec<_>>();
     ids.sort();
     for id in ids {
         let ty = results.statics[&id];
-        eprintln!("static {} :: {:?}", path_str(id), Pretty(ty));
+        writeln!(w, "static {} :: {:?}", path_str(id), Pretty(ty))?;
     }
 
     let mut ids = results.funcs.keys().cloned().collect::<Vec<_>>();
@@ -488,34 +1048,66 @@ pub fn dump_results(dcx: &driver::Ctxt,
     for id in ids {
         let fr = &results.funcs[&id];
 
-        eprintln!("func {}:", path_str(id));
+        writeln!(w, "func {}:", path_str(id))?;
 
-        eprintln!("  sig constraints:");
+        writeln!(w, "  sig constraints:")?;
         for &(a, b) in fr.cset.iter() {
-            eprintln!("    {:?} <= {:?}", a, b);
+            writeln!(w, "    {:?} <= {:?}", a, b)?;
+        }
+
+        let nullable = (0 .. fr.num_sig_vars as usize)
+            .filter(|&i| fr.nullable.contains(i))
+            .collect::<Vec<_>>();
+        if nullable.len() > 0 {
+            writeln!(w, "  nullable: {:?}", nullable)?;
+        }
+
+        if fr.return_borrows_from.len() > 0 {
+            writeln!(w, "  return borrows from: {:?}", fr.return_borrows_from)?;
+        }
+
+        if fr.array_pointers.len() > 0 {
+            writeln!(w, "  array pointers: {:?}", fr.array_pointers)?;
+        }
+
+        if fr.alias_conflicts.len() > 0 {
+            let conflicts = fr.alias_conflicts.iter()
+                .map(|&(v1, v2)| (v1.0, v2.0))
+                .collect::<Vec<_>>();
+            writeln!(w, "  alias conflicts: {:?}", conflicts)?;
+        }
+
+        if fr.truncated {
+            writeln!(w, "  monos truncated")?;
         }
 
         if let Some(ref var_ids) = fr.variants {
             for (i, &var_id) in var_ids.iter().enumerate() {
-                eprintln!("  variant {}: {}", i, path_str(var_id));
+                writeln!(w, "  variant {}: {}", i, path_str(var_id))?;
                 let vr = &results.variants[&var_id];
 
-                for (j, func_ref) in vr.func_refs.iter().enumerate() {
-                    let callee_fr = &results.funcs[&func_ref.def_id];
-                    eprintln!("    call #{}: {:?} :: {:?}",
-                              j, path_str(func_ref.def_id), callee_fr.sig);
-                    eprintln!("      (at {:?})", func_ref.span);
+                for (j, func_ref) in sorted_calls(vr) {
+                    match results.funcs.get(&func_ref.def_id) {
+                        Some(callee_fr) => writeln!(w, "    call #{}: {:?} :: {:?}",
+                                                    j, path_str(func_ref.def_id), callee_fr.sig)?,
+                        None => writeln!(w, "    call #{}: {:?} (excluded)",
+                                         j, path_str(func_ref.def_id))?,
+                    }
+                    writeln!(w, "      (at {:?})", func_ref.span)?;
                 }
             }
         } else {
-            eprintln!("  single variant");
+            writeln!(w, "  single variant")?;
             let vr = &results.variants[&id];
 
-            for (j, func_ref) in vr.func_refs.iter().enumerate() {
-                let callee_fr = &results.funcs[&func_ref.def_id];
-                eprintln!("    call #{}: {:?} :: {:?}",
-                          j, path_str(func_ref.def_id), callee_fr.sig);
-                eprintln!("      (at {:?})", func_ref.span);
+            for (j, func_ref) in sorted_calls(vr) {
+                match results.funcs.get(&func_ref.def_id) {
+                    Some(callee_fr) => writeln!(w, "    call #{}: {:?} :: {:?}",
+                                                j, path_str(func_ref.def_id), callee_fr.sig)?,
+                    None => writeln!(w, "    call #{}: {:?} (excluded)",
+                                     j, path_str(func_ref.def_id))?,
+                }
+                writeln!(w, "      (at {:?})", func_ref.span)?;
             }
         }
 
@@ -525,16 +1117,24 @@ pub fn dump_results(dcx: &driver::Ctxt,
             let var_id = fr.variants.as_ref().map_or(id, |vars| vars[i]);
             let vr = &results.variants[&var_id];
 
-            eprintln!("  mono #{} ({:?}): {}", i, mr.suffix, format_sig(fr.sig, &mr.assign));
-            for (j, (func_ref, &mono_idx)) in
-                    vr.func_refs.iter().zip(mr.callee_mono_idxs.iter()).enumerate() {
-                let callee_fr = &results.funcs[&func_ref.def_id];
-                eprintln!("    call #{}: {:?} #{} :: {}",
-                          j, path_str(func_ref.def_id), mono_idx,
-                          format_sig(callee_fr.sig,
-                                     &results.monos[&(func_ref.def_id, mono_idx)].assign));
-                eprintln!("      (at {:?})", func_ref.span);
+            writeln!(w, "  mono #{} ({:?}): {}", i, mr.suffix, format_sig(fr.sig, &mr.assign))?;
+            for (j, func_ref) in sorted_calls(vr) {
+                let mono_idx = mr.callee_mono_idxs[j];
+                // Callees left out by `analyze_filtered` have no results to show.
+                match results.funcs.get(&func_ref.def_id) {
+                    Some(callee_fr) => {
+                        let callee_mr = &results.monos[&(func_ref.def_id, mono_idx)];
+                        writeln!(w, "    call #{}: {:?} #{} :: {}",
+                                 j, path_str(func_ref.def_id), mono_idx,
+                                 format_sig(callee_fr.sig, &callee_mr.assign))?;
+                    },
+                    None => writeln!(w, "    call #{}: {:?} #{} (excluded)",
+                                     j, path_str(func_ref.def_id), mono_idx)?,
+                }
+                writeln!(w, "      (at {:?})", func_ref.span)?;
             }
         }
     }
+
+    Ok(())
 }
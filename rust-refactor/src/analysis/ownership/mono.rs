@@ -1,3 +1,5 @@
+use std::cmp;
+
 use rustc_data_structures::indexed_vec::IndexVec;
 
 use super::{ConcretePerm, PermVar, Var, LTy};
@@ -60,11 +62,13 @@ fn upper_bounded_vars(summ: &FuncSumm) -> IndexVec<Var, bool> {
     bounded
 }
 
+/// Call `callback` on each valid assignment to the output variables, stopping early if it returns
+/// `false`.
 fn for_each_output_assignment<F>(summ: &FuncSumm,
                                  is_out: &IndexVec<Var, bool>,
                                  is_bounded: &IndexVec<Var, bool>,
                                  mut callback: F)
-        where F: FnMut(&IndexVec<Var, Option<ConcretePerm>>) {
+        where F: FnMut(&IndexVec<Var, Option<ConcretePerm>>) -> bool {
 
     struct State<'a, 'tcx: 'a, F: 'a> {
         max: Var,
@@ -76,17 +80,16 @@ fn for_each_output_assignment<F>(summ: &FuncSumm,
     }
 
     impl<'a, 'tcx, F> State<'a, 'tcx, F>
-            where F: FnMut(&IndexVec<Var, Option<ConcretePerm>>) {
-        fn walk_vars(&mut self, cur: Var) {
+            where F: FnMut(&IndexVec<Var, Option<ConcretePerm>>) -> bool {
+        /// Returns `false` once the callback asks to stop.
+        fn walk_vars(&mut self, cur: Var) -> bool {
             if cur >= self.max {
-                (self.callback)(&mut self.assignment);
-                return;
+                return (self.callback)(&mut self.assignment);
             }
 
             let next = Var(cur.0 + 1);
             if !self.is_out[cur] {
-                self.walk_vars(next);
-                return;
+                return self.walk_vars(next);
             }
 
             for &p in &[ConcretePerm::Move, ConcretePerm::Write, ConcretePerm::Read] {
@@ -101,13 +104,16 @@ fn for_each_output_assignment<F>(summ: &FuncSumm,
                     continue;
                 }
 
-                self.walk_vars(next);
+                if !self.walk_vars(next) {
+                    return false;
+                }
 
                 // For unbounded output variables, try only the highest valid assignment.
                 if !self.is_bounded[cur] {
                     break;
                 }
             }
+            true
         }
     }
 
@@ -195,7 +201,9 @@ fn find_input_assignment(summ: &FuncSumm,
     }
 }
 
-pub fn get_mono_sigs(summ: &FuncSumm) -> Vec<IndexVec<Var, ConcretePerm>> {
+/// Compute the mono sigs of a function, stopping after `limit` of them.  The first one has the
+/// most permissive outputs.
+pub fn get_mono_sigs(summ: &FuncSumm, limit: usize) -> Vec<IndexVec<Var, ConcretePerm>> {
     let is_out = infer_outputs(&summ);
     let is_bounded = upper_bounded_vars(&summ);
 
@@ -205,24 +213,65 @@ pub fn get_mono_sigs(summ: &FuncSumm) -> Vec<IndexVec<Var, ConcretePerm>> {
         if let Some(assign) = find_input_assignment(summ, assign) {
             assigns.push(assign);
         }
+        assigns.len() < limit
     });
 
     assigns
 }
 
-pub fn compute_all_mono_sigs(cx: &mut Ctxt) {
-    let ids = cx.variant_ids().collect::<Vec<_>>();
+/// Limits on the number of monos.  A function whose output permissions can vary independently has
+/// exponentially many of them.
+#[derive(Clone, Copy, Debug)]
+pub struct MonoLimits {
+    /// Most monos a single function can have.
+    pub max_per_fn: usize,
+    /// Most monos all functions can have together.
+    pub budget: usize,
+}
+
+impl Default for MonoLimits {
+    fn default() -> MonoLimits {
+        MonoLimits {
+            max_per_fn: 64,
+            budget: 100000,
+        }
+    }
+}
+
+pub fn compute_all_mono_sigs(cx: &mut Ctxt, limits: &MonoLimits) {
+    // Sorted, so the budget runs out at the same place from run to run.
+    let mut ids = cx.variant_ids().collect::<Vec<_>>();
+    ids.sort();
+
+    let mut budget = limits.budget;
     for &id in &ids {
-        let assigns = {
+        let (assigns, truncated) = {
             let (func, _var) = cx.variant_summ(id);
             if func.monos_provided {
                 // No work for us to do in this pass.
                 continue;
             }
-            get_mono_sigs(func)
+            if func.sig_frozen {
+                // Keep only the mono with the most permissive outputs, which serves every caller.
+                (get_mono_sigs(func, 1), false)
+            } else {
+                let limit = cmp::max(1, cmp::min(limits.max_per_fn, budget));
+                // Ask for one extra, to tell whether the function has too many.
+                let mut assigns = get_mono_sigs(func, limit + 1);
+                if assigns.len() > limit {
+                    // Too many to keep.  Fall back to the most permissive mono, as for frozen
+                    // signatures.
+                    assigns.truncate(1);
+                    (assigns, true)
+                } else {
+                    (assigns, false)
+                }
+            }
         };
         assert!(assigns.len() > 0, "found no mono sigs for {:?}", id);
 
+        budget = budget.saturating_sub(assigns.len());
+        cx.variant_summ(id).0.monos_truncated = truncated;
         for assign in assigns {
             let mono = cx.add_mono(id).2;
             mono.assign = assign;
@@ -5,21 +5,97 @@
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::collections::VecDeque;
+use std::str::FromStr;
 
 use rustc::hir::def_id::DefId;
-use rustc_data_structures::indexed_vec::IndexVec;
+use rustc::ty;
+use syntax::attr;
 
-use super::{Var, ConcretePerm, Perm};
+use driver;
+
+use super::{AnalysisResult, ConcretePerm};
 use super::context::Ctxt;
+use super::inter::DEFAULT_MAX_VISITS;
+use super::mono::MonoLimits;
+
+
+/// Knobs for `filter_suspicious_monos`, and for how many monos are computed in the first place.
+/// The default keeps every mono, as the analysis always has, up to the default `MonoLimits`.
+#[derive(Clone, Debug)]
+pub struct MonoFilterConfig {
+    /// Suspicious monos with fewer call sites than this (counting only calls from monos that are
+    /// kept) are dropped.  Callers keep the callee monos they selected, so only 0 and 1 are
+    /// accepted: at 1, suspicious monos are dropped if nothing calls them.
+    pub min_call_sites: usize,
 
+    /// Drop monos that can't be reached through calls from a mono of an exported function, one
+    /// with `#[no_mangle]` or `#[export_name]`.
+    pub exported_only: bool,
 
-pub fn filter_suspicious_monos(
-    cx: &Ctxt,
-    all_mono_sigs: &HashMap<DefId, Vec<IndexVec<Var, ConcretePerm>>>,
-    all_inst_sels: &HashMap<(DefId, usize), Vec<usize>>)
-    -> HashSet<(DefId, usize)> {
+    /// Drop uncalled monos of functions with more monos than this, suspicious and more permissive
+    /// ones first.  Monos that are still called are kept, so a function can stay above the limit.
+    pub max_monos: Option<usize>,
 
+    /// Limits on the monos computed before filtering.  A function over them gets only its most
+    /// permissive mono.
+    pub limits: MonoLimits,
+
+    /// How many times the interprocedural step may process each function or static before it is
+    /// reported as diverging.
+    pub max_visits: usize,
+}
+
+impl Default for MonoFilterConfig {
+    fn default() -> MonoFilterConfig {
+        MonoFilterConfig {
+            min_call_sites: 0,
+            exported_only: false,
+            max_monos: None,
+            limits: MonoLimits::default(),
+            max_visits: DEFAULT_MAX_VISITS,
+        }
+    }
+}
 
+impl MonoFilterConfig {
+    /// Parse options of the form `min_call_sites=N`, `exported_only`, `max_monos=N`,
+    /// `mono_cap=N`, `mono_budget=N`, and `max_visits=N`.
+    pub fn from_args(args: &[String]) -> Result<MonoFilterConfig, String> {
+        let mut config = MonoFilterConfig::default();
+        for arg in args {
+            let mut parts = arg.splitn(2, '=');
+            let key = parts.next().unwrap();
+            let value = parts.next();
+            let count = || -> Result<usize, String> {
+                let value = value.ok_or_else(|| format!("`{}` needs a value", key))?;
+                usize::from_str(value).map_err(|e| format!("bad value for `{}`: {}", key, e))
+            };
+            match key {
+                "min_call_sites" => {
+                    config.min_call_sites = count()?;
+                    if config.min_call_sites > 1 {
+                        return Err(format!("`min_call_sites` must be 0 or 1, since monos that \
+                                            are still called are never dropped"));
+                    }
+                },
+                "exported_only" if value.is_none() => config.exported_only = true,
+                "max_monos" => config.max_monos = Some(count()?),
+                "mono_cap" => config.limits.max_per_fn = count()?,
+                "mono_budget" => config.limits.budget = count()?,
+                "max_visits" => config.max_visits = count()?,
+                _ => return Err(format!("unknown mono filter option `{}`", arg)),
+            }
+        }
+        Ok(config)
+    }
+}
+
+
+/// Compute the set of monos to drop, according to `config`.  Monos provided by attributes, and
+/// those of functions split into variants, are never dropped, and every function keeps at least
+/// one mono.  A dropped mono is never called from a kept one, so callers keep their choice of
+/// callee mono.
+pub fn filter_suspicious_monos(cx: &Ctxt, config: &MonoFilterConfig) -> HashSet<(DefId, usize)> {
     // (1) Find suspicious mono sigs.
     //
     // It's common for accessor functions to get inferred signatures `READ -> READ`, `WRITE ->
@@ -33,82 +109,226 @@ pub fn filter_suspicious_monos(
 
     let mut suspicious = HashSet::new();
     let mut all_monos = Vec::new();
+    // Number of monos that each function has left, for functions whose monos may be dropped.
+    let mut num_kept = HashMap::new();
 
-    for (&def_id, mono_sigs) in all_mono_sigs {
-        let summ = cx.get_fn_summ_imm(def_id).unwrap();
+    let mut func_ids = cx.func_ids().collect::<Vec<_>>();
+    func_ids.sort();
+    for &def_id in &func_ids {
+        let summ = cx.get_func_summ(def_id);
+        for i in 0 .. summ.num_monos {
+            all_monos.push((def_id, i));
+        }
 
         // Only look at inferred monos - ones provided by attributes are exempt.
-        if summ.attr_monos.is_none() {
-            let outputs = super::mono::infer_outputs(summ);
+        if summ.monos_provided || summ.variant_ids.len() > 1 {
+            continue;
+        }
+        num_kept.insert(def_id, summ.num_monos);
 
-            for (v, is_output) in outputs.iter_enumerated() {
-                if !is_output {
-                    continue;
-                }
+        let outputs = super::mono::infer_outputs(summ);
+        let assign = |i| &cx.get_mono_summ(def_id, i).assign;
+        for (v, &is_output) in outputs.iter_enumerated() {
+            if !is_output {
+                continue;
+            }
 
-                if !mono_sigs.iter().any(|assign| assign[v] == ConcretePerm::Read) {
-                    continue;
-                }
+            if !(0 .. summ.num_monos).any(|i| assign(i)[v] == ConcretePerm::Read) {
+                continue;
+            }
 
-                for (i, assign) in mono_sigs.iter().enumerate() {
-                    if assign[v] == ConcretePerm::Move {
-                        suspicious.insert((def_id, i));
-                        eprintln!("found suspicious mono: {:?} #{}", def_id, i);
-                    }
+            for i in 0 .. summ.num_monos {
+                if assign(i)[v] == ConcretePerm::Move {
+                    suspicious.insert((def_id, i));
                 }
             }
         }
-
-        for i in 0 .. mono_sigs.len() {
-            all_monos.push((def_id, i));
-        }
     }
 
 
-    // (2) Build the call graph.  Suspicious monos that have no calls from non-suspicious monos
-    // will be filtered out.
+    // (2) Build the call graph, with an edge for each call site.
 
     let mut call_graph = HashMap::new();
     let mut caller_count = HashMap::new();
 
-    for (&(def_id, src_idx), inst_sel) in all_inst_sels {
-        let summ = cx.get_fn_summ_imm(def_id).unwrap();
+    for &src in &all_monos {
+        let variant = cx.get_mono_variant_summ(src.0, src.1);
+        let mono = cx.get_mono_summ(src.0, src.1);
 
-        for (inst, &dest_idx) in summ.insts.iter().zip(inst_sel.iter()) {
-            let src = (def_id, src_idx);
+        let mut dests = Vec::with_capacity(variant.insts.len());
+        for (inst, &dest_idx) in variant.insts.iter().zip(mono.callee_mono_idxs.iter()) {
             let dest = (inst.callee, dest_idx);
-            call_graph.entry(src).or_insert_with(HashSet::new).insert(dest);
+            dests.push(dest);
             *caller_count.entry(dest).or_insert(0) += 1;
         }
+        call_graph.insert(src, dests);
     }
 
+    let mut filter = FilterState {
+        call_graph: call_graph,
+        caller_count: caller_count,
+        num_kept: num_kept,
+        queue: VecDeque::new(),
+        filter: HashSet::new(),
+    };
 
-    // (3) Search the callgraph for suspicious monos with only suspicious callers.
 
-    let mut queue = VecDeque::new();
-    let mut filter = HashSet::new();
+    // (3) Drop suspicious monos without enough callers, and monos that exported functions can't
+    // reach.
 
     for &src in &all_monos {
-        let count = caller_count.get(&src).map(|&x| x).unwrap_or(0);
-        eprintln!("{:?}: {} callers, suspicious? {}", src, count, suspicious.contains(&src));
-        if suspicious.contains(&src) && count == 0 {
-            queue.push_back(src);
+        if suspicious.contains(&src) && filter.callers(src) < config.min_call_sites {
+            filter.queue.push_back(src);
+        }
+    }
+
+    if config.exported_only {
+        let reachable = reachable_from_exports(cx, &filter.call_graph, &all_monos);
+        for &src in &all_monos {
+            if !reachable.contains(&src) {
+                filter.queue.push_back(src);
+            }
+        }
+    }
+
+    filter.process();
+
+
+    // (4) Trim functions with too many monos, one uncalled mono at a time.
+
+    if let Some(max_monos) = config.max_monos {
+        loop {
+            let next = all_monos.iter().cloned()
+                .filter(|&(def_id, _)| filter.num_kept.get(&def_id).map_or(false, |&n| n > max_monos))
+                .filter(|&src| !filter.filter.contains(&src) && filter.callers(src) == 0)
+                // Suspicious first, then the most permissive, which come first in each function
+                .min_by_key(|&(def_id, i)| (!suspicious.contains(&(def_id, i)), def_id, i));
+            match next {
+                Some(src) => {
+                    filter.queue.push_back(src);
+                    filter.process();
+                },
+                None => break,
+            }
+        }
+    }
+
+    filter.filter
+}
+
+struct FilterState {
+    /// The callee of each call site in each mono.
+    call_graph: HashMap<(DefId, usize), Vec<(DefId, usize)>>,
+    /// The number of call sites in kept monos that call each mono.
+    caller_count: HashMap<(DefId, usize), usize>,
+    num_kept: HashMap<DefId, usize>,
+    queue: VecDeque<(DefId, usize)>,
+    filter: HashSet<(DefId, usize)>,
+}
+
+impl FilterState {
+    fn callers(&self, mono: (DefId, usize)) -> usize {
+        self.caller_count.get(&mono).cloned().unwrap_or(0)
+    }
+
+    /// Drop the queued monos, along with any monos left without callers as a result.
+    fn process(&mut self) {
+        while let Some(src) = self.queue.pop_front() {
+            if self.filter.contains(&src) || self.callers(src) > 0 {
+                continue;
+            }
+            match self.num_kept.get_mut(&src.0) {
+                Some(n) if *n > 1 => *n -= 1,
+                _ => continue,
+            }
+            self.filter.insert(src);
+
+            // `src` has been filtered out.  Decrement the caller count for each of its callees.
+            for &dest in &self.call_graph[&src] {
+                let count = self.caller_count.get_mut(&dest).unwrap();
+                *count -= 1;
+                if *count == 0 {
+                    self.queue.push_back(dest);
+                }
+            }
         }
     }
+}
+
+/// The monos reachable through calls from the monos of exported functions.
+fn reachable_from_exports(cx: &Ctxt,
+                          call_graph: &HashMap<(DefId, usize), Vec<(DefId, usize)>>,
+                          all_monos: &[(DefId, usize)]) -> HashSet<(DefId, usize)> {
+    let is_exported = |def_id: DefId| {
+        let attrs = cx.tcx.get_attrs(def_id);
+        attr::contains_name(&attrs, "no_mangle") || attr::contains_name(&attrs, "export_name")
+    };
 
+    let mut reachable = HashSet::new();
+    let mut queue = all_monos.iter().cloned()
+        .filter(|&(def_id, _)| is_exported(def_id))
+        .collect::<VecDeque<_>>();
     while let Some(src) = queue.pop_front() {
-        filter.insert(src);
-
-        // `src` has been filtered out.  Decrement the caller count for each of its callees.
-        for &dest in call_graph.entry(src).or_insert_with(HashSet::new).iter() {
-            let count = caller_count.get_mut(&dest).unwrap();
-            *count -= 1;
-            if *count == 0 {
-                queue.push_back(dest);
+        if reachable.insert(src) {
+            queue.extend(call_graph[&src].iter().cloned());
+        }
+    }
+    reachable
+}
+
+
+/// Find the monos in `results` that no entry point can reach.  Every mono of an entry point
+/// function (or variant) is live, and so is each mono selected by a call in a live mono; the rest
+/// are dead, and need not be generated.  Unlike `filter_suspicious_monos`, this may report every
+/// mono of a function.
+pub fn find_dead_monos(results: &AnalysisResult,
+                       is_entry: &Fn(DefId) -> bool) -> HashSet<(DefId, usize)> {
+    let mut live = HashSet::new();
+    let mut queue = VecDeque::new();
+    for (&id, fr) in &results.funcs {
+        for i in 0 .. fr.num_monos {
+            let var_id = fr.variants.as_ref().map_or(id, |vars| vars[i]);
+            if is_entry(var_id) {
+                queue.push_back((id, i));
             }
         }
     }
 
+    while let Some(src) = queue.pop_front() {
+        if !live.insert(src) {
+            continue;
+        }
+        let (id, i) = src;
+        // Callees left out by `analyze_filtered` have no monos to visit.
+        let fr = match results.funcs.get(&id) {
+            Some(fr) => fr,
+            None => continue,
+        };
+        let var_id = fr.variants.as_ref().map_or(id, |vars| vars[i]);
+        let vr = &results.variants[&var_id];
+        let mr = &results.monos[&src];
+        for (func_ref, &mono_idx) in vr.func_refs.iter().zip(mr.callee_mono_idxs.iter()) {
+            queue.push_back((func_ref.def_id, mono_idx));
+        }
+    }
+
+    results.monos.keys().cloned()
+        .filter(|src| !live.contains(src))
+        .collect()
+}
 
-    filter
+/// The default entry points for `find_dead_monos`: `pub` functions, those callable from C through
+/// `#[no_mangle]` or `#[export_name]`, and the crate's `main`.
+pub fn default_entry_points<'a, 'tcx: 'a>(dcx: &driver::Ctxt<'a, 'tcx>)
+                                          -> Box<Fn(DefId) -> bool + 'a> {
+    let tcx = dcx.ty_ctxt();
+    let hir_map = dcx.hir_map();
+    let main_id = tcx.sess.entry_fn.borrow().map(|(node_id, _)| hir_map.local_def_id(node_id));
+    Box::new(move |def_id| {
+        let attrs = tcx.get_attrs(def_id);
+        Some(def_id) == main_id ||
+            tcx.visibility(def_id) == ty::Visibility::Public ||
+            attr::contains_name(&attrs, "no_mangle") ||
+            attr::contains_name(&attrs, "export_name")
+    })
 }
@@ -114,6 +114,14 @@ gen_fold_node_impl! {
     map = (self.callback)(l);
 }
 
+gen_fold_node_impl! {
+    node = StructField;
+    folder = StructFieldNodeFolder;
+    fn fold_struct_field(&mut self, sf: StructField) -> StructField;
+    walk = fold::noop_fold_struct_field(sf, self);
+    map = (self.callback)(sf);
+}
+
 gen_fold_node_impl! {
     node = ForeignMod;
     folder = ForeignModNodeFolder;
@@ -334,6 +334,15 @@ impl Builder {
         }
     }
 
+    pub fn prepared_attr(self, attr: Attribute) -> Self {
+        let mut attrs = self.attrs;
+        attrs.push(attr);
+        Builder {
+            attrs: attrs,
+            ..self
+        }
+    }
+
     pub fn call_attr<K,V>(self, func: K, arguments: Vec<V>) -> Self
         where K: Make<PathSegment>, V: Make<Ident> {
 
@@ -928,6 +937,17 @@ impl Builder {
         })
     }
 
+    pub fn tuple_struct_pat<Pa, P1>(self, path: Pa, pats: Vec<P1>) -> P<Pat>
+        where Pa: Make<Path>, P1: Make<P<Pat>> {
+        let path = path.make(&self);
+        let pats: Vec<P<Pat>> = pats.into_iter().map(|x| x.make(&self)).collect();
+        P(Pat {
+            id: DUMMY_NODE_ID,
+            node: PatKind::TupleStruct(path, pats, None),
+            span: DUMMY_SP,
+        })
+    }
+
     pub fn qpath_pat<Pa>(self, qself: Option<QSelf>, path: Pa) -> P<Pat>
         where Pa: Make<Path> {
         let path = path.make(&self);
@@ -1288,6 +1308,48 @@ impl Builder {
                                   items))
     }
 
+    pub fn trait_impl_item<Pa, T>(self, trait_path: Pa, ty: T, items: Vec<ImplItem>) -> P<Item>
+        where Pa: Make<Path>, T: Make<P<Ty>>
+    {
+        let trait_ref = TraitRef {
+            path: trait_path.make(&self),
+            ref_id: DUMMY_NODE_ID,
+        };
+        let ty = ty.make(&self);
+        Self::item(keywords::Invalid.ident(), self.attrs, self.vis, self.span,
+                   ItemKind::Impl(self.unsafety,
+                                  ImplPolarity::Positive,
+                                  Defaultness::Final,
+                                  self.generics,
+                                  Some(trait_ref),
+                                  ty,
+                                  items))
+    }
+
+    pub fn fn_impl_item<I, D, B>(self, name: I, decl: D, block: B) -> ImplItem
+        where I: Make<Ident>, D: Make<P<FnDecl>>, B: Make<P<Block>> {
+        let name = name.make(&self);
+        let decl = decl.make(&self);
+        let block = block.make(&self);
+        let sig = MethodSig {
+            unsafety: self.unsafety,
+            constness: Spanned { span: DUMMY_SP, node: self.constness },
+            abi: self.abi,
+            decl: decl,
+        };
+        ImplItem {
+            id: DUMMY_NODE_ID,
+            ident: name,
+            vis: self.vis,
+            defaultness: Defaultness::Final,
+            attrs: self.attrs,
+            generics: self.generics,
+            node: ImplItemKind::Method(sig, block),
+            span: self.span,
+            tokens: None,
+        }
+    }
+
     pub fn extern_crate_item<I>(self, name: I, rename: Option<I>) -> P<Item>
         where I: Make<Ident>
     {
@@ -1296,10 +1358,17 @@ impl Builder {
         Self::item(name, self.attrs, self.vis, self.span, ItemKind::ExternCrate(rename))
     }
 
+    pub fn mod_item<I>(self, name: I, items: Vec<P<Item>>) -> P<Item>
+        where I: Make<Ident> {
+        let name = name.make(&self);
+        let module = Mod { inner: self.span, items };
+        Self::item(name, self.attrs, self.vis, self.span, ItemKind::Mod(module))
+    }
+
     // `use <path>;` item
-    // TODO: for now, we only support simple paths with an optional rename;
-    // if we ever need them, we should add support for globs and nested trees,
-    // e.g., `use foo::*;` and `use foo::{a, b, c};`
+    // TODO: for now, we only support simple paths with an optional rename and
+    // globs; if we ever need them, we should add support for nested trees,
+    // e.g., `use foo::{a, b, c};`
     pub fn use_item<Pa, I>(self, path: Pa, rename: Option<I>) -> P<Item>
         where Pa: Make<Path>, I: Make<Ident>
     {
@@ -1316,6 +1385,20 @@ impl Builder {
                    ItemKind::Use(P(use_tree)))
     }
 
+    // `use <path>::*;` item
+    pub fn use_glob_item<Pa>(self, path: Pa) -> P<Item>
+        where Pa: Make<Path>
+    {
+        let path = path.make(&self);
+        let use_tree = UseTree {
+            span: DUMMY_SP,
+            prefix: path,
+            kind: UseTreeKind::Glob,
+        };
+        Self::item(keywords::Invalid.ident(), self.attrs, self.vis, self.span,
+                   ItemKind::Use(P(use_tree)))
+    }
+
     pub fn foreign_items(self, items: Vec<ForeignItem>) -> P<Item>
     {
         let fgn_mod = ForeignMod { abi: self.abi, items };
@@ -115,6 +115,14 @@ gen_visit_node_impl! {
     walk = visit::walk_local(self, l);
 }
 
+gen_visit_node_impl! {
+    node = StructField;
+    visitor = StructFieldNodeVisitor;
+    visitor_post = StructFieldNodeVisitorPost;
+    fn visit_struct_field(&mut self, sf: &'ast StructField);
+    walk = visit::walk_struct_field(self, sf);
+}
+
 gen_visit_node_impl! {
     node = ForeignItem;
     visitor = ForeignItemNodeVisitor;
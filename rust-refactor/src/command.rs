@@ -10,6 +10,7 @@ use syntax::codemap::FileLoader;
 use syntax::codemap::FileMap;
 use syntax::symbol::Symbol;
 
+use analysis::ownership::ForeignSummaries;
 use driver::{self, Phase};
 use rewrite;
 use rewrite::files;
@@ -41,6 +42,7 @@ pub struct RefactorState {
     rewrite_handler: Option<Box<FnMut(Rc<FileMap>, &str)>>,
     cmd_reg: Registry,
     marks: HashSet<(NodeId, Symbol)>,
+    foreign_summaries: ForeignSummaries,
 }
 
 impl RefactorState {
@@ -53,6 +55,7 @@ impl RefactorState {
             rewrite_handler: None,
             cmd_reg: cmd_reg,
             marks: marks,
+            foreign_summaries: ForeignSummaries::new(),
         }
     }
 
@@ -71,6 +74,7 @@ impl RefactorState {
     pub fn with_context_at_phase<F, R>(&mut self, phase: Phase, f: F) -> R
             where F: FnOnce(&CommandState, &driver::Ctxt) -> R{
         let marks = &mut self.marks;
+        let foreign_summaries = &mut self.foreign_summaries;
         let mut rewrite_handler = self.rewrite_handler.as_mut();
 
         let file_loader = self.make_file_loader.as_ref().map(|f| f());
@@ -78,7 +82,8 @@ impl RefactorState {
             let krate = span_fix::fix_spans(cx.session(), krate);
 
             let cmd_state = CommandState::new(krate.clone(),
-                                              marks.clone());
+                                              marks.clone(),
+                                              foreign_summaries.clone());
 
             let r = f(&cmd_state, &cx);
 
@@ -86,6 +91,10 @@ impl RefactorState {
                 *marks = cmd_state.marks().clone();
             }
 
+            if cmd_state.foreign_summaries_changed() {
+                *foreign_summaries = cmd_state.foreign_summaries().clone();
+            }
+
             if cmd_state.krate_changed() {
                 let rws = rewrite::rewrite(cx.session(), &krate, &cmd_state.krate());
                 if rws.len() == 0 {
@@ -128,6 +137,16 @@ impl RefactorState {
     pub fn marks_mut(&mut self) -> &mut HashSet<(NodeId, Symbol)> {
         &mut self.marks
     }
+
+    /// User-provided summaries of foreign functions for the ownership analysis, which add to or
+    /// replace the built-in ones.
+    pub fn foreign_summaries(&self) -> &ForeignSummaries {
+        &self.foreign_summaries
+    }
+
+    pub fn foreign_summaries_mut(&mut self) -> &mut ForeignSummaries {
+        &mut self.foreign_summaries
+    }
 }
 
 
@@ -136,20 +155,25 @@ impl RefactorState {
 pub struct CommandState {
     krate: RefCell<Crate>,
     marks: RefCell<HashSet<(NodeId, Symbol)>>,
+    foreign_summaries: RefCell<ForeignSummaries>,
 
     krate_changed: Cell<bool>,
     marks_changed: Cell<bool>,
+    foreign_summaries_changed: Cell<bool>,
 }
 
 impl CommandState {
     pub fn new(krate: Crate,
-               marks: HashSet<(NodeId, Symbol)>) -> CommandState {
+               marks: HashSet<(NodeId, Symbol)>,
+               foreign_summaries: ForeignSummaries) -> CommandState {
         CommandState {
             krate: RefCell::new(krate),
             marks: RefCell::new(marks),
+            foreign_summaries: RefCell::new(foreign_summaries),
 
             krate_changed: Cell::new(false),
             marks_changed: Cell::new(false),
+            foreign_summaries_changed: Cell::new(false),
         }
     }
 
@@ -206,6 +230,20 @@ impl CommandState {
     pub fn marks_changed(&self) -> bool {
         self.marks_changed.get()
     }
+
+
+    pub fn foreign_summaries(&self) -> cell::Ref<ForeignSummaries> {
+        self.foreign_summaries.borrow()
+    }
+
+    pub fn foreign_summaries_mut(&self) -> cell::RefMut<ForeignSummaries> {
+        self.foreign_summaries_changed.set(true);
+        self.foreign_summaries.borrow_mut()
+    }
+
+    pub fn foreign_summaries_changed(&self) -> bool {
+        self.foreign_summaries_changed.get()
+    }
 }
 
 
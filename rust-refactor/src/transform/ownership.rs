@@ -3,25 +3,31 @@ use std::collections::HashSet;
 
 use arena::DroplessArena;
 use rustc::hir;
+use rustc::hir::def::Def;
 use rustc::hir::def_id::DefId;
+use rustc::ty::{Ty as RustTy, TypeVariants};
 use rustc_data_structures::indexed_vec::IndexVec;
 use syntax::ast::*;
 use syntax::codemap::DUMMY_SP;
 use syntax::fold::{self, Folder};
+use syntax::print::pprust;
 use syntax::parse::token::{self, Token, DelimToken};
 use syntax::ptr::P;
 use syntax::symbol::Symbol;
 use syntax::tokenstream::{TokenTree, TokenStream, Delimited};
 use syntax::util::small_vector::SmallVector;
+use syntax::visit::{self, Visitor};
 
 use analysis::labeled_ty::LabeledTyCtxt;
-use analysis::ownership::{self, ConcretePerm, Var, PTy};
-use analysis::ownership::constraint::{ConstraintSet, Perm};
+use analysis::ownership::{self, ConcretePerm, PermVar, Var, PTy};
+use analysis::ownership::constraint::{ConstraintSet, PermExpr};
 use api::*;
+use ast_manip::fn_edit::{visit_fns, FnKind};
 use command::{CommandState, Registry, DriverCommand};
 use driver::{self, Phase};
 use type_map;
 use util::IntoSymbol;
+use util::cursor::Cursor;
 
 pub fn register_commands(reg: &mut Registry) {
     reg.register("ownership_annotate", |args| {
@@ -32,6 +38,26 @@ pub fn register_commands(reg: &mut Registry) {
         }))
     });
 
+    reg.register("ownership_annotate_params", |args| {
+        let label = args.get(0).map_or("target", |x| x).into_symbol();
+
+        Box::new(DriverCommand::new(Phase::Phase3, move |st, cx| {
+            do_annotate_params(st, cx, label);
+        }))
+    });
+
+    reg.register("ownership_box_allocs", |_args| {
+        Box::new(DriverCommand::new(Phase::Phase3, move |st, cx| {
+            do_box_allocs(st, cx);
+        }))
+    });
+
+    reg.register("ownership_box_pointers", |_args| {
+        Box::new(DriverCommand::new(Phase::Phase3, move |st, cx| {
+            do_box_pointers(st, cx);
+        }))
+    });
+
     reg.register("ownership_split_variants", |args| {
         let label = args.get(0).map_or("target", |x| x).into_symbol();
 
@@ -45,6 +71,28 @@ pub fn register_commands(reg: &mut Registry) {
             do_mark_pointers(st, cx);
         }))
     });
+
+    reg.register("ownership_mark_array_lens", |args| {
+        let label = args.get(0).map_or("target", |x| x).into_symbol();
+
+        Box::new(DriverCommand::new(Phase::Phase3, move |st, cx| {
+            do_mark_array_lens(st, cx, label);
+        }))
+    });
+
+    reg.register("ownership_ref_params", |_args| {
+        Box::new(DriverCommand::new(Phase::Phase3, move |st, cx| {
+            do_ref_params(st, cx);
+        }))
+    });
+
+    reg.register("ownership_to_methods", |args| {
+        let label = args.get(0).map_or("target", |x| x).into_symbol();
+
+        Box::new(DriverCommand::new(Phase::Phase3, move |st, cx| {
+            do_to_methods(st, cx, label);
+        }))
+    });
 }
 
 fn do_annotate(st: &CommandState,
@@ -168,6 +216,897 @@ fn do_annotate(st: &CommandState,
     });
 }
 
+/// Give each marked function an `#[ownership_param(IDX, PERM)]` attribute for each of its pointer
+/// arguments, where `IDX` is the index of the argument and `PERM` the permission of its outermost
+/// pointer.  Functions with several monomorphizations get the argument's signature variable
+/// (`_0`, `_1`, ...) in place of a concrete permission, as in `#[ownership_constraints]`.
+/// Arguments that aren't pointers get no attribute.
+fn do_annotate_params(st: &CommandState,
+                      cx: &driver::Ctxt,
+                      label: Symbol) {
+    let ana = ownership::analyze(&st, &cx);
+
+    let param_attrs = |def_id: DefId| -> Vec<Attribute> {
+        if !ana.variants.contains_key(&def_id) {
+            return Vec::new();
+        }
+        let (fr, vr) = ana.fn_results(def_id);
+
+        let polymorphic = fr.variants.is_none() && fr.num_monos > 1;
+        let mono_idx =
+            if fr.variants.is_none() { 0 }
+            else { vr.index };
+
+        fr.sig.inputs.iter().enumerate().filter_map(|(idx, lty)| {
+            let v = lty.label?;
+            let perm =
+                if polymorphic { ident_token(&format!("_{}", v.0)) }
+                else { perm_token(ana.monos[&(vr.func_id, mono_idx)].assign[v]) };
+            let tokens = parens(vec![int_token(idx), token(Token::Comma), perm]).into();
+            Some(make_attr("ownership_param", tokens))
+        }).collect()
+    };
+
+    st.map_krate(|krate| {
+        fold_fns(krate, |mut fl| {
+            if !st.marked(fl.id, label) {
+                return fl;
+            }
+            let def_id = match_or!([cx.hir_map().opt_local_def_id(fl.id)]
+                                   Some(x) => x; return fl);
+
+            fl.attrs.retain(|a| a.name().map_or(true, |name| name != "ownership_param"));
+            fl.attrs.extend(param_attrs(def_id));
+            fl
+        })
+    });
+}
+
+
+/// Replace local allocations `let p = malloc(size_of::<T>()) as *mut T;` that are freed later in
+/// the same block with `let mut p = Box::new(T::default());`, and array allocations
+/// `malloc(n * size_of::<T>())` with `vec![T::default(); n]`.  The `free` is removed: the `Box` or
+/// `Vec` is dropped at the end of the block instead.
+///
+/// The rewrite only applies when the analysis shows the allocation stays with `p`.  `p` may only
+/// be dereferenced (or, for arrays, `offset`), and passed to local functions that get READ or
+/// WRITE for that argument in every monomorphization.  Passing `p` to anything else, copying it,
+/// or reassigning it leaves the allocation alone.  `T` must be a path type implementing
+/// `Default`: a primitive type, or a type of this crate with an `impl Default`.
+fn do_box_allocs(st: &CommandState, cx: &driver::Ctxt) {
+    let ana = ownership::analyze(&st, &cx);
+
+    st.map_krate(|krate| {
+        fold_nodes(krate, |b: P<Block>| {
+            b.map(|mut b| {
+                // Each rewrite changes the statements after it, so restart after every one.
+                while let Some(alloc) = find_box_alloc(cx, &ana, &b.stmts) {
+                    b.stmts = rewrite_box_alloc(cx, b.stmts, alloc);
+                }
+                b
+            })
+        })
+    });
+}
+
+/// An allocation that `ownership_box_allocs` can turn into a `Box` or `Vec`.
+struct BoxAlloc {
+    /// Index of the `let` statement holding the allocation.
+    let_idx: usize,
+    /// Index of the statement calling `free`.
+    free_idx: usize,
+    /// The binding of the pointer.
+    binding: NodeId,
+    ident: Ident,
+    elem_ty: P<Ty>,
+    /// The element count, for array allocations.
+    count: Option<P<Expr>>,
+}
+
+/// The `NodeId` of the binding a path expression refers to, if it names a local variable.
+fn local_binding(cx: &driver::Ctxt, e: &Expr) -> Option<NodeId> {
+    let node = match_or!([cx.hir_map().find(e.id)] Some(x) => x; return None);
+    let e = match_or!([node] hir::map::NodeExpr(e) => e; return None);
+    let qpath = match_or!([e.node] hir::ExprPath(ref q) => q; return None);
+    let path = match_or!([*qpath] hir::QPath::Resolved(_, ref path) => path; return None);
+    match path.def {
+        Def::Local(id) => Some(id),
+        _ => None,
+    }
+}
+
+/// If `e` is a call to one of the allocator intrinsics, return which one, along with the args.
+fn intrinsic_call<'e>(cx: &driver::Ctxt, e: &'e Expr)
+                      -> Option<(ownership::Intrinsic, &'e [P<Expr>])> {
+    let args = match_or!([e.node] ExprKind::Call(_, ref args) => args; return None);
+    let def_id = cx.opt_callee(e)?;
+    ownership::intrinsic(cx.ty_ctxt(), def_id).map(|i| (i, &args[..]))
+}
+
+/// If `e` is `mem::size_of::<T>()`, return `T`.
+fn size_of_ty(cx: &driver::Ctxt, e: &Expr) -> Option<P<Ty>> {
+    let func = match e.node {
+        ExprKind::Call(ref func, ref args) if args.is_empty() => func,
+        _ => return None,
+    };
+    let def_id = cx.try_resolve_expr(func)?;
+    let def_path = cx.ty_ctxt().absolute_item_path_str(def_id);
+    if def_path != "std::mem::size_of" && def_path != "core::mem::size_of" {
+        return None;
+    }
+
+    let path = match_or!([func.node] ExprKind::Path(None, ref path) => path; return None);
+    let params = path.segments.last()?.parameters.as_ref()?;
+    match **params {
+        PathParameters::AngleBracketed(ref abpd) if abpd.types.len() == 1 =>
+            Some(abpd.types[0].clone()),
+        _ => None,
+    }
+}
+
+/// Whether `ty` implements `Default`.  Primitive types do, and so do types of this crate with an
+/// `impl Default`, including a derived one.
+fn implements_default<'a, 'tcx>(cx: &driver::Ctxt<'a, 'tcx>, ty: RustTy<'tcx>) -> bool {
+    match ty.sty {
+        TypeVariants::TyBool | TypeVariants::TyChar | TypeVariants::TyInt(_) |
+        TypeVariants::TyUint(_) | TypeVariants::TyFloat(_) => return true,
+        _ => {},
+    }
+    has_local_impl(cx, ty, "default::Default")
+}
+
+/// Whether this crate has an `impl` of the `std` (or `core`) trait at `path` for `ty`, including
+/// a derived one.
+fn has_local_impl<'a, 'tcx>(cx: &driver::Ctxt<'a, 'tcx>, ty: RustTy<'tcx>, path: &str) -> bool {
+    let tcx = cx.ty_ctxt();
+    tcx.hir.krate().trait_impls.iter().any(|(&trait_id, impls)| {
+        let trait_path = tcx.absolute_item_path_str(trait_id);
+        (trait_path == format!("std::{}", path) || trait_path == format!("core::{}", path)) &&
+            impls.iter().any(|&id| {
+                let impl_id = tcx.hir.local_def_id(id);
+                tcx.impl_trait_ref(impl_id).map_or(false, |tr| tr.self_ty() == ty)
+            })
+    })
+}
+
+/// If `e` is `malloc(size) as *mut T`, where `size` is `size_of::<T>()` or `n * size_of::<T>()`,
+/// return `T`, along with `n` for array allocations.  `T` must implement `Default`, which builds
+/// the value of the `Box` or `Vec` replacing the allocation.
+fn alloc_elem_ty(cx: &driver::Ctxt, e: &Expr) -> Option<(P<Ty>, Option<P<Expr>>)> {
+    let (call, ptr_ty) = match_or!([e.node] ExprKind::Cast(ref call, ref ty) => (call, ty);
+                                   return None);
+    let elem_ty = match ptr_ty.node {
+        TyKind::Ptr(MutTy { ref ty, mutbl: Mutability::Mutable }) => ty,
+        _ => return None,
+    };
+    match elem_ty.node {
+        TyKind::Path(None, _) => {},
+        _ => return None,
+    }
+    let size = match intrinsic_call(cx, call) {
+        Some((ownership::Intrinsic::Alloc, args)) if args.len() == 1 => &args[0],
+        _ => return None,
+    };
+    let (size_ty, count) = match size.node {
+        ExprKind::Binary(op, ref a, ref b) if op.node == BinOpKind::Mul => {
+            match (size_of_ty(cx, a), size_of_ty(cx, b)) {
+                (Some(ty), None) => (ty, Some(b.clone())),
+                (None, Some(ty)) => (ty, Some(a.clone())),
+                _ => return None,
+            }
+        },
+        _ => (size_of_ty(cx, size)?, None),
+    };
+    if pprust::ty_to_string(&size_ty) != pprust::ty_to_string(elem_ty) {
+        return None;
+    }
+    match cx.node_type(e.id).sty {
+        TypeVariants::TyRawPtr(tm) if implements_default(cx, tm.ty) => {},
+        _ => return None,
+    }
+    Some((elem_ty.clone(), count))
+}
+
+fn strip_casts(e: &Expr) -> &Expr {
+    match e.node {
+        ExprKind::Cast(ref inner, _) => strip_casts(inner),
+        _ => e,
+    }
+}
+
+fn find_box_alloc<'a, 'tcx>(cx: &driver::Ctxt<'a, 'tcx>,
+                            ana: &ownership::AnalysisResult<'tcx>,
+                            stmts: &[Stmt]) -> Option<BoxAlloc> {
+    for (let_idx, stmt) in stmts.iter().enumerate() {
+        let l = match_or!([stmt.node] StmtKind::Local(ref l) => l; continue);
+        let ident = match l.pat.node {
+            PatKind::Ident(BindingMode::ByValue(_), ident, None) => ident.node,
+            _ => continue,
+        };
+        let init = match_or!([l.init] Some(ref x) => x; continue);
+        let (elem_ty, count) = match_or!([alloc_elem_ty(cx, init)] Some(x) => x; continue);
+
+        // A later `free(p)` in the same block
+        let free_idx = stmts.iter().enumerate().skip(let_idx + 1).filter_map(|(i, s)| {
+            let e = match s.node {
+                StmtKind::Semi(ref e) | StmtKind::Expr(ref e) => e,
+                _ => return None,
+            };
+            match intrinsic_call(cx, e) {
+                Some((ownership::Intrinsic::Free, args)) if args.len() == 1 &&
+                        local_binding(cx, strip_casts(&args[0])) == Some(l.pat.id) => Some(i),
+                _ => None,
+            }
+        }).next();
+        let free_idx = match_or!([free_idx] Some(x) => x; continue);
+
+        let mut checker = UseChecker {
+            cx: cx,
+            ana: ana,
+            binding: l.pat.id,
+            is_array: count.is_some(),
+            escapes: false,
+        };
+        for (i, s) in stmts.iter().enumerate().skip(let_idx + 1) {
+            if i != free_idx {
+                checker.visit_stmt(s);
+            }
+        }
+        if checker.escapes {
+            continue;
+        }
+
+        return Some(BoxAlloc {
+            let_idx: let_idx,
+            free_idx: free_idx,
+            binding: l.pat.id,
+            ident: ident,
+            elem_ty: elem_ty,
+            count: count,
+        });
+    }
+    None
+}
+
+/// Checks that every use of a pointer to an allocation leaves the allocation with the pointer.
+struct UseChecker<'a, 'b: 'a, 'tcx: 'b> {
+    cx: &'a driver::Ctxt<'b, 'tcx>,
+    ana: &'a ownership::AnalysisResult<'tcx>,
+    binding: NodeId,
+    is_array: bool,
+    escapes: bool,
+}
+
+impl<'a, 'b, 'tcx> UseChecker<'a, 'b, 'tcx> {
+    fn is_ptr(&self, e: &Expr) -> bool {
+        local_binding(self.cx, e) == Some(self.binding)
+    }
+}
+
+/// Whether the argument `idx` of the call `e` is a pointer that no monomorphization of the callee
+/// takes ownership of.
+fn borrows_arg(cx: &driver::Ctxt, ana: &ownership::AnalysisResult, e: &Expr, idx: usize) -> bool {
+    let def_id = match_or!([cx.opt_callee(e)] Some(x) => x; return false);
+    if !ana.variants.contains_key(&def_id) {
+        return false;
+    }
+    let (fr, vr) = ana.fn_results(def_id);
+    let v = match fr.sig.inputs.get(idx).and_then(|lty| lty.label) {
+        Some(v) => v,
+        None => return false,
+    };
+    (0 .. fr.num_monos).all(|i| {
+        ana.monos[&(vr.func_id, i)].assign[v] != ConcretePerm::Move
+    })
+}
+
+impl<'a, 'b, 'tcx, 'ast> Visitor<'ast> for UseChecker<'a, 'b, 'tcx> {
+    fn visit_expr(&mut self, e: &'ast Expr) {
+        match e.node {
+            ExprKind::Unary(UnOp::Deref, ref inner) if self.is_ptr(inner) => return,
+            ExprKind::MethodCall(ref seg, ref args)
+                    if self.is_array && seg.identifier.name == "offset" &&
+                       self.is_ptr(&args[0]) => {
+                for arg in &args[1..] {
+                    self.visit_expr(arg);
+                }
+                return;
+            },
+            ExprKind::Call(ref func, ref args) => {
+                self.visit_expr(func);
+                for (idx, arg) in args.iter().enumerate() {
+                    if self.is_ptr(arg) {
+                        if !borrows_arg(self.cx, self.ana, e, idx) {
+                            self.escapes = true;
+                        }
+                    } else {
+                        self.visit_expr(arg);
+                    }
+                }
+                return;
+            },
+            _ => {},
+        }
+
+        if self.is_ptr(e) {
+            self.escapes = true;
+        }
+        visit::walk_expr(self, e);
+    }
+
+    fn visit_mac(&mut self, mac: &'ast Mac) {
+        visit::walk_mac(self, mac);
+    }
+}
+
+fn rewrite_box_alloc(cx: &driver::Ctxt, stmts: Vec<Stmt>, alloc: BoxAlloc) -> Vec<Stmt> {
+    let elem_ty = pprust::ty_to_string(&alloc.elem_ty);
+    let init = match alloc.count {
+        None => format!("Box::new({}::default())", elem_ty),
+        Some(ref count) => {
+            let count = match count.node {
+                ExprKind::Path(..) | ExprKind::Lit(..) => pprust::expr_to_string(count),
+                _ => format!("({})", pprust::expr_to_string(count)),
+            };
+            format!("vec![{}::default(); {} as usize]", elem_ty, count)
+        },
+    };
+    let new_let = parse_stmts(cx.session(), &format!("let mut {} = {};", alloc.ident, init));
+
+    // Box: pass `&mut *p` where the raw pointer was passed.
+    // Vec: use `p.as_mut_ptr()` in place of the raw pointer everywhere.
+    let ptr_repl = if alloc.count.is_none() {
+        format!("&mut *{}", alloc.ident)
+    } else {
+        format!("{}.as_mut_ptr()", alloc.ident)
+    };
+    let ptr_repl = parse_expr(cx.session(), &ptr_repl);
+    let is_array = alloc.count.is_some();
+
+    let mut new_stmts = Vec::with_capacity(stmts.len());
+    for (i, stmt) in stmts.into_iter().enumerate() {
+        if i == alloc.let_idx {
+            new_stmts.extend(new_let.iter().cloned());
+        } else if i == alloc.free_idx {
+            continue;
+        } else if i < alloc.let_idx {
+            new_stmts.push(stmt);
+        } else {
+            new_stmts.extend(fold_nodes(stmt, |e: P<Expr>| {
+                let replace_args = match e.node {
+                    ExprKind::Call(..) => true,
+                    _ => is_array,
+                };
+                if !replace_args {
+                    return e;
+                }
+                e.map(|mut e| {
+                    let replace = |arg: &mut P<Expr>| {
+                        if local_binding(cx, arg) == Some(alloc.binding) {
+                            *arg = ptr_repl.clone();
+                        }
+                    };
+                    match e.node {
+                        ExprKind::Call(_, ref mut args) => args.iter_mut().for_each(replace),
+                        ExprKind::MethodCall(_, ref mut args) => replace(&mut args[0]),
+                        ExprKind::Unary(UnOp::Deref, ref mut inner) => replace(inner),
+                        _ => {},
+                    }
+                    e
+                })
+            }));
+        }
+    }
+    new_stmts
+}
+
+/// Turn raw pointers that own their target into `Box`es: parameters whose permission is MOVE in
+/// every monomorphization, struct fields whose permission is MOVE, and locals
+/// `let p = malloc(size_of::<T>()) as *mut T;` that are moved into one of those.  Their `*mut T`
+/// type becomes `Box<T>`, allocations moved into them become `Box::new(T::default())`, and
+/// `free(p)` becomes `drop(p)`.
+///
+/// Each pointer must be used the way a `Box` can be.  It may be dereferenced, passed as `&mut *p`
+/// to functions that only borrow it, and moved exactly once, outside of any loop, into a boxed
+/// parameter or field or into `free`.  Boxed parameters and fields must only be given fresh
+/// allocations or other boxed pointers, so when one pointer stays raw, the ones it is passed to
+/// stay raw as well, until all call chains agree.  Fields are read out with `ptr::read` and written
+/// with `ptr::write`, since the structs holding them are still freed by hand.  `T` must be a path
+/// type implementing `Default`, or the allocation and the pointers it is moved into stay raw.
+/// Fields of `Copy` structs, like those translated from C with `#[derive(Copy, Clone)]`, stay raw
+/// too, since a `Box` can't be copied.
+fn do_box_pointers(st: &CommandState, cx: &driver::Ctxt) {
+    let ana = ownership::analyze(&st, &cx);
+
+    let mut bcx = BoxCtxt {
+        cx: cx,
+        ana: &ana,
+        binding_owners: HashMap::new(),
+        boxed: HashSet::new(),
+    };
+    bcx.collect_candidates(&st.krate());
+
+    loop {
+        let rejected = {
+            let mut checker = BoxUseChecker {
+                bcx: &bcx,
+                loop_depth: 0,
+                decl_depths: HashMap::new(),
+                moved: HashSet::new(),
+                rejected: HashSet::new(),
+            };
+            st.krate().visit(&mut checker);
+            checker.finish()
+        };
+        if rejected.is_empty() {
+            break;
+        }
+        for owner in &rejected {
+            bcx.boxed.remove(owner);
+        }
+    }
+
+    st.map_krate(|krate| bcx.rewrite(krate));
+}
+
+/// A raw pointer that `ownership_box_pointers` may turn into a `Box`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+enum BoxOwner {
+    /// Argument `idx` of a function
+    Param(DefId, usize),
+    /// A local initialized with a fresh allocation, by the `NodeId` of its binding
+    Local(NodeId),
+    Field(DefId),
+}
+
+struct BoxCtxt<'a, 'b: 'a, 'tcx: 'b> {
+    cx: &'a driver::Ctxt<'b, 'tcx>,
+    ana: &'a ownership::AnalysisResult<'tcx>,
+    /// The owner for the binding of each candidate parameter or local.
+    binding_owners: HashMap<NodeId, BoxOwner>,
+    /// The candidates that still become `Box`es.
+    boxed: HashSet<BoxOwner>,
+}
+
+impl<'a, 'b, 'tcx> BoxCtxt<'a, 'b, 'tcx> {
+    fn collect_candidates(&mut self, krate: &Crate) {
+        let cx = self.cx;
+        let ana = self.ana;
+        let mut binding_owners = HashMap::new();
+        let mut boxed = HashSet::new();
+
+        visit_fns(krate, |fl| {
+            if fl.block.is_none() {
+                return;
+            }
+            let def_id = match_or!([cx.hir_map().opt_local_def_id(fl.id)] Some(x) => x; return);
+            if !ana.variants.contains_key(&def_id) {
+                return;
+            }
+            let (fr, vr) = ana.fn_results(def_id);
+            // C callers pass raw pointers to frozen functions, so their params stay raw.
+            if fr.sig_frozen {
+                return;
+            }
+            let mono_idxs =
+                if fr.variants.is_none() { 0 .. fr.num_monos }
+                else { vr.index .. vr.index + 1 };
+
+            for (idx, arg) in fl.decl.inputs.iter().enumerate() {
+                if mut_ptr_pointee(&arg.ty).is_none() || binding_ident(&arg.pat).is_none() {
+                    continue;
+                }
+                let v = match_or!([fr.sig.inputs.get(idx).and_then(|lty| lty.label)]
+                                  Some(v) => v; continue);
+                if mono_idxs.clone().all(|i| {
+                    ana.monos[&(vr.func_id, i)].assign[v] == ConcretePerm::Move
+                }) {
+                    binding_owners.insert(arg.pat.id, BoxOwner::Param(def_id, idx));
+                    boxed.insert(BoxOwner::Param(def_id, idx));
+                }
+            }
+        });
+
+        visit_nodes(krate, |sf: &StructField| {
+            if mut_ptr_pointee(&sf.ty).is_none() {
+                return;
+            }
+            // A `Box` field would make the struct impossible to copy.
+            let struct_id = cx.node_def_id(cx.hir_map().get_parent(sf.id));
+            if has_local_impl(cx, cx.ty_ctxt().type_of(struct_id), "marker::Copy") {
+                return;
+            }
+            let def_id = cx.node_def_id(sf.id);
+            if ana.statics.get(&def_id).map_or(false, |pty| pty.label == Some(ConcretePerm::Move)) {
+                boxed.insert(BoxOwner::Field(def_id));
+            }
+        });
+
+        visit_nodes(krate, |l: &Local| {
+            let fresh = l.init.as_ref().map_or(false, |init| is_single_alloc(cx, init));
+            if fresh && binding_ident(&l.pat).is_some() {
+                binding_owners.insert(l.pat.id, BoxOwner::Local(l.pat.id));
+                boxed.insert(BoxOwner::Local(l.pat.id));
+            }
+        });
+
+        self.binding_owners = binding_owners;
+        self.boxed = boxed;
+    }
+
+    /// The boxed pointer that `e` names, if any.
+    fn owner(&self, e: &Expr) -> Option<BoxOwner> {
+        let owner = match e.node {
+            ExprKind::Path(..) => local_binding(self.cx, e)
+                .and_then(|id| self.binding_owners.get(&id).cloned()),
+            ExprKind::Field(ref obj, ref name) =>
+                field_def_id(self.cx, obj, name.node).map(BoxOwner::Field),
+            _ => None,
+        };
+        match owner {
+            Some(owner) if self.boxed.contains(&owner) => Some(owner),
+            _ => None,
+        }
+    }
+
+    /// The boxed parameter that argument `idx` of the call `e` is passed to, if any.
+    fn boxed_param(&self, e: &Expr, idx: usize) -> Option<BoxOwner> {
+        let owner = BoxOwner::Param(self.cx.opt_callee(e)?, idx);
+        if self.boxed.contains(&owner) { Some(owner) } else { None }
+    }
+
+    /// The `Box` to move into a boxed parameter or field in place of `e`.
+    fn box_source(&self, e: &Expr) -> P<Expr> {
+        match self.owner(e) {
+            Some(BoxOwner::Field(_)) => {
+                let read = parse_expr(self.cx.session(), "::std::ptr::read");
+                return mk().call_expr(read, vec![mk().addr_of_expr(P(e.clone()))]);
+            },
+            Some(_) => return P(e.clone()),
+            None => {},
+        }
+        match alloc_elem_ty(self.cx, e) {
+            Some((elem_ty, None)) => {
+                let src = format!("Box::new({}::default())", pprust::ty_to_string(&elem_ty));
+                parse_expr(self.cx.session(), &src)
+            },
+            _ => P(e.clone()),
+        }
+    }
+
+    /// If `e` is `free(p)` for a boxed `p`, return what to drop in its place.
+    fn freed_box(&self, e: &Expr) -> Option<P<Expr>> {
+        match intrinsic_call(self.cx, e) {
+            Some((ownership::Intrinsic::Free, args)) if args.len() == 1 => {
+                let arg = strip_casts(&args[0]);
+                self.owner(arg)?;
+                Some(self.box_source(arg))
+            },
+            _ => None,
+        }
+    }
+
+    /// If `e` assigns to a boxed field, return the field and the `Box` to write to it.
+    fn boxed_field_write(&self, e: &Expr) -> Option<(P<Expr>, P<Expr>)> {
+        let (lhs, rhs) = match_or!([e.node] ExprKind::Assign(ref lhs, ref rhs) => (lhs, rhs);
+                                   return None);
+        match self.owner(lhs) {
+            Some(BoxOwner::Field(_)) => Some((lhs.clone(), self.box_source(rhs))),
+            _ => None,
+        }
+    }
+
+    /// The new arguments of a call `e`: `Box`es for boxed parameters, and borrows of boxed
+    /// pointers for the rest.
+    fn call_args(&self, e: &Expr) -> Vec<Option<P<Expr>>> {
+        let args = match_or!([e.node] ExprKind::Call(_, ref args) => args; return Vec::new());
+        args.iter().enumerate().map(|(idx, arg)| {
+            if self.boxed_param(e, idx).is_some() {
+                Some(self.box_source(arg))
+            } else if self.owner(arg).is_some() {
+                Some(mk().mutbl().addr_of_expr(mk().unary_expr(UnOp::Deref, arg.clone())))
+            } else {
+                None
+            }
+        }).collect()
+    }
+
+    /// The new values of the boxed fields initialized by a struct expression `e`.
+    fn struct_fields(&self, e: &Expr) -> Vec<Option<P<Expr>>> {
+        let fields = match_or!([e.node] ExprKind::Struct(_, ref fields, _) => fields;
+                               return Vec::new());
+        fields.iter().map(|f| {
+            let owner = BoxOwner::Field(field_def_id(self.cx, e, f.ident.node)?);
+            if self.boxed.contains(&owner) { Some(self.box_source(&f.expr)) } else { None }
+        }).collect()
+    }
+
+    fn rewrite(&self, krate: Crate) -> Crate {
+        let cx = self.cx;
+
+        // Rewrite expressions first, while every other node still has its `NodeId`.
+        let krate = fold_nodes(krate, |e: P<Expr>| {
+            if let Some(freed) = self.freed_box(&e) {
+                return mk().call_expr(mk().ident_expr("drop"), vec![freed]);
+            }
+            if let Some((field, val)) = self.boxed_field_write(&e) {
+                let write = parse_expr(cx.session(), "::std::ptr::write");
+                return mk().call_expr(write, vec![mk().mutbl().addr_of_expr(field), val]);
+            }
+
+            let call_args = self.call_args(&e);
+            let struct_fields = self.struct_fields(&e);
+            e.map(|mut e| {
+                match e.node {
+                    ExprKind::Call(_, ref mut args) => {
+                        for (arg, new_arg) in args.iter_mut().zip(call_args) {
+                            if let Some(new_arg) = new_arg {
+                                *arg = new_arg;
+                            }
+                        }
+                    },
+                    ExprKind::Struct(_, ref mut fields, _) => {
+                        for (f, new_expr) in fields.iter_mut().zip(struct_fields) {
+                            if let Some(new_expr) = new_expr {
+                                f.expr = new_expr;
+                                f.is_shorthand = false;
+                            }
+                        }
+                    },
+                    _ => {},
+                }
+                e
+            })
+        });
+
+        let krate = fold_nodes(krate, |l: P<Local>| {
+            if !self.boxed.contains(&BoxOwner::Local(l.pat.id)) {
+                return l;
+            }
+            let ident = binding_ident(&l.pat).unwrap();
+            let init = l.init.as_ref().map(|init| self.box_source(init));
+            l.map(|l| Local {
+                pat: mk().mutbl().ident_pat(ident),
+                ty: None,
+                init: init,
+                .. l
+            })
+        });
+
+        let krate = fold_nodes(krate, |mut sf: StructField| {
+            if self.boxed.contains(&BoxOwner::Field(cx.node_def_id(sf.id))) {
+                sf.ty = box_ty(cx, &sf.ty);
+            }
+            sf
+        });
+
+        fold_fns(krate, |mut fl| {
+            let def_id = match_or!([cx.hir_map().opt_local_def_id(fl.id)] Some(x) => x; return fl);
+            fl.decl = fl.decl.map(|mut decl| {
+                for (idx, arg) in decl.inputs.iter_mut().enumerate() {
+                    if self.boxed.contains(&BoxOwner::Param(def_id, idx)) {
+                        arg.ty = box_ty(cx, &arg.ty);
+                        arg.pat = mk().mutbl().ident_pat(binding_ident(&arg.pat).unwrap());
+                    }
+                }
+                decl
+            });
+            fl
+        })
+    }
+}
+
+/// Finds the candidates of `ownership_box_pointers` that are used in ways a `Box` can't be.
+struct BoxUseChecker<'c, 'a: 'c, 'b: 'a, 'tcx: 'b> {
+    bcx: &'c BoxCtxt<'a, 'b, 'tcx>,
+    /// The number of loops and closures around the current expression.
+    loop_depth: usize,
+    /// The `loop_depth` of each boxed local's `let`.  Parameters are declared at depth 0.
+    decl_depths: HashMap<NodeId, usize>,
+    /// The boxed bindings that were moved already.
+    moved: HashSet<NodeId>,
+    rejected: HashSet<BoxOwner>,
+}
+
+impl<'c, 'a, 'b, 'tcx> BoxUseChecker<'c, 'a, 'b, 'tcx> {
+    fn reject_params(&mut self, def_id: DefId) {
+        let params = self.bcx.boxed.iter().filter(|&&owner| match owner {
+            BoxOwner::Param(id, _) => id == def_id,
+            _ => false,
+        });
+        self.rejected.extend(params.cloned().collect::<Vec<_>>());
+    }
+
+    /// Record a use of the boxed pointer `e`, which moves it if `is_move`.
+    fn use_owner<'ast>(&mut self, e: &'ast Expr, owner: BoxOwner, is_move: bool) {
+        if let ExprKind::Field(ref obj, _) = e.node {
+            // Fields are read out with `ptr::read`, which doesn't move the struct.
+            self.visit_expr(obj);
+            return;
+        }
+
+        let id = local_binding(self.bcx.cx, e).unwrap();
+        if self.moved.contains(&id) {
+            self.rejected.insert(owner);
+        }
+        if is_move {
+            if self.decl_depths.get(&id).cloned().unwrap_or(0) != self.loop_depth {
+                self.rejected.insert(owner);
+            }
+            self.moved.insert(id);
+        }
+    }
+
+    /// Check the value `e` that is moved into the boxed parameter or field `dest`.
+    fn move_into<'ast>(&mut self, dest: BoxOwner, e: &'ast Expr) {
+        match self.bcx.owner(e) {
+            Some(owner) => self.use_owner(e, owner, true),
+            None => {
+                if !is_single_alloc(self.bcx.cx, e) {
+                    self.rejected.insert(dest);
+                }
+                self.visit_expr(e);
+            },
+        }
+    }
+
+    /// The rejected candidates.  A boxed binding that is never moved would be dropped in a
+    /// different place than the C code frees it, so it is rejected as well.
+    fn finish(mut self) -> HashSet<BoxOwner> {
+        for (id, &owner) in &self.bcx.binding_owners {
+            if self.bcx.boxed.contains(&owner) && !self.moved.contains(id) {
+                self.rejected.insert(owner);
+            }
+        }
+        self.rejected
+    }
+}
+
+impl<'c, 'a, 'b, 'tcx, 'ast> Visitor<'ast> for BoxUseChecker<'c, 'a, 'b, 'tcx> {
+    fn visit_item(&mut self, i: &'ast Item) {
+        let depth = self.loop_depth;
+        self.loop_depth = 0;
+        visit::walk_item(self, i);
+        self.loop_depth = depth;
+    }
+
+    fn visit_local(&mut self, l: &'ast Local) {
+        if self.bcx.binding_owners.contains_key(&l.pat.id) {
+            self.decl_depths.insert(l.pat.id, self.loop_depth);
+        }
+        visit::walk_local(self, l);
+    }
+
+    fn visit_expr(&mut self, e: &'ast Expr) {
+        let bcx = self.bcx;
+        match e.node {
+            ExprKind::Unary(UnOp::Deref, ref inner) => {
+                if let Some(owner) = bcx.owner(inner) {
+                    self.use_owner(inner, owner, false);
+                    return;
+                }
+            },
+
+            ExprKind::Call(ref func, ref args) => {
+                let is_free = matches!([intrinsic_call(bcx.cx, e)]
+                                       Some((ownership::Intrinsic::Free, _)));
+                // A callee path is fine, unlike other uses of functions with boxed parameters.
+                if !matches!([func.node] ExprKind::Path(..)) {
+                    self.visit_expr(func);
+                }
+                for (idx, arg) in args.iter().enumerate() {
+                    let arg = if is_free { strip_casts(arg) } else { &**arg };
+                    match (bcx.boxed_param(e, idx), bcx.owner(arg)) {
+                        (Some(param), _) => self.move_into(param, arg),
+                        (None, Some(owner)) if is_free => self.use_owner(arg, owner, true),
+                        (None, Some(owner)) => {
+                            if !borrows_arg(bcx.cx, bcx.ana, e, idx) {
+                                self.rejected.insert(owner);
+                            }
+                            self.use_owner(arg, owner, false);
+                        },
+                        (None, None) => self.visit_expr(arg),
+                    }
+                }
+                return;
+            },
+
+            ExprKind::MethodCall(..) => {
+                if let Some(def_id) = bcx.cx.opt_callee(e) {
+                    self.reject_params(def_id);
+                }
+            },
+
+            ExprKind::Assign(ref lhs, ref rhs) => {
+                if let Some(field @ BoxOwner::Field(_)) = bcx.owner(lhs) {
+                    self.use_owner(lhs, field, false);
+                    self.move_into(field, rhs);
+                    return;
+                }
+            },
+
+            ExprKind::Struct(_, ref fields, ref base) => {
+                for f in fields {
+                    match field_def_id(bcx.cx, e, f.ident.node).map(BoxOwner::Field) {
+                        Some(owner) if bcx.boxed.contains(&owner) => self.move_into(owner, &f.expr),
+                        _ => self.visit_expr(&f.expr),
+                    }
+                }
+                if let Some(ref base) = *base {
+                    self.visit_expr(base);
+                }
+                return;
+            },
+
+            ExprKind::While(..) | ExprKind::WhileLet(..) | ExprKind::Loop(..) |
+            ExprKind::ForLoop(..) | ExprKind::Closure(..) => {
+                self.loop_depth += 1;
+                visit::walk_expr(self, e);
+                self.loop_depth -= 1;
+                return;
+            },
+
+            ExprKind::Path(..) => {
+                if let Some(def_id) = bcx.cx.try_resolve_expr(e) {
+                    self.reject_params(def_id);
+                }
+            },
+
+            _ => {},
+        }
+
+        // Any other use of a boxed pointer copies it.
+        if let Some(owner) = bcx.owner(e) {
+            self.rejected.insert(owner);
+        }
+        visit::walk_expr(self, e);
+    }
+
+    fn visit_mac(&mut self, mac: &'ast Mac) {
+        visit::walk_mac(self, mac);
+    }
+}
+
+/// If `ty` is `*mut T` for a path type `T`, return `T`.
+fn mut_ptr_pointee(ty: &Ty) -> Option<&P<Ty>> {
+    match ty.node {
+        TyKind::Ptr(MutTy { ref ty, mutbl: Mutability::Mutable }) => match ty.node {
+            TyKind::Path(None, _) => Some(ty),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// `Box<T>`, for `ty` of the form `*mut T`
+fn box_ty(cx: &driver::Ctxt, ty: &Ty) -> P<Ty> {
+    let pointee = mut_ptr_pointee(ty).unwrap();
+    parse_ty(cx.session(), &format!("Box<{}>", pprust::ty_to_string(pointee)))
+}
+
+/// The identifier bound by a plain by-value binding pattern.
+fn binding_ident(pat: &Pat) -> Option<Ident> {
+    match pat.node {
+        PatKind::Ident(BindingMode::ByValue(_), ident, None) => Some(ident.node),
+        _ => None,
+    }
+}
+
+/// Whether `e` allocates a single `T`, as in `malloc(size_of::<T>()) as *mut T`.
+fn is_single_alloc(cx: &driver::Ctxt, e: &Expr) -> bool {
+    alloc_elem_ty(cx, e).map_or(false, |(_, count)| count.is_none())
+}
+
+/// The `DefId` of the field `name` of the struct that `obj` evaluates to.
+fn field_def_id(cx: &driver::Ctxt, obj: &Expr, name: Ident) -> Option<DefId> {
+    match cx.adjusted_node_type(obj.id).sty {
+        TypeVariants::TyAdt(adt, _) if adt.is_struct() => {
+            adt.struct_variant().fields.iter().find(|f| f.name == name.name).map(|f| f.did)
+        },
+        _ => None,
+    }
+}
+
 fn build_static_attr(ty: PTy) -> Option<Attribute> {
     let mut args = Vec::new();
     ty.for_each_label(&mut |p| {
@@ -182,13 +1121,13 @@ fn build_static_attr(ty: PTy) -> Option<Attribute> {
 fn build_constraints_attr(cset: &ConstraintSet) -> Attribute {
     let mut args = Vec::new();
 
-    fn push_perm_tokens(p: Perm, dest: &mut Vec<TokenTree>) {
-        match p {
-            Perm::Concrete(p) => dest.push(perm_token(p)),
-            Perm::SigVar(v) => dest.push(ident_token(&format!("_{}", v.0))),
-            Perm::Min(ps) => {
+    fn push_perm_tokens(p: &PermExpr, dest: &mut Vec<TokenTree>) {
+        match *p {
+            PermExpr::Concrete(p) => dest.push(perm_token(p)),
+            PermExpr::Var(PermVar::Sig(v)) => dest.push(ident_token(&format!("_{}", v.0))),
+            PermExpr::Min(ref ps) => {
                 let mut ts = Vec::new();
-                for (i, &p) in ps.iter().enumerate() {
+                for (i, p) in ps.iter().enumerate() {
                     if i > 0 {
                         ts.push(token(Token::Comma));
                     }
@@ -201,16 +1140,16 @@ fn build_constraints_attr(cset: &ConstraintSet) -> Attribute {
         }
     }
 
-    for (i, &(a, b)) in cset.iter().enumerate() {
+    for (i, (a, b)) in cset.constraints().enumerate() {
         if i > 0 {
             args.push(token(Token::Comma));
         }
         args.push(ident_token("le"));
 
         let mut le_args = Vec::new();
-        push_perm_tokens(a, &mut le_args);
+        push_perm_tokens(&a, &mut le_args);
         le_args.push(token(Token::Comma));
-        push_perm_tokens(b, &mut le_args);
+        push_perm_tokens(&b, &mut le_args);
 
         args.push(parens(le_args));
     }
@@ -245,6 +1184,10 @@ fn ident_token(name: &str) -> TokenTree {
     token(Token::Ident(mk().ident(name)))
 }
 
+fn int_token(i: usize) -> TokenTree {
+    token(Token::Literal(token::Lit::Integer(i.to_string().into_symbol()), None))
+}
+
 fn str_token(s: &str) -> TokenTree {
     token(Token::Literal(token::Lit::Str_(s.into_symbol()), None))
 }
@@ -488,6 +1431,10 @@ fn do_mark_pointers(st: &CommandState, cx: &driver::Ctxt) {
             if fr.variants.is_none() && fr.num_monos > 1 {
                 return None;
             }
+            // C callers depend on the raw pointers in frozen signatures.
+            if fr.sig_frozen {
+                return None;
+            }
 
             // Only one variant?  Use mono #0 (which is the only one, by the check above).
             // Multiple variants?  Use the mono for the current variant.
@@ -496,15 +1443,15 @@ fn do_mark_pointers(st: &CommandState, cx: &driver::Ctxt) {
                 else { vr.index };
 
             let mr = &self.ana.monos[&(vr.func_id, mono_idx)];
+            let raw = aliased_vars(fr, mr);
 
             let lcx = LabeledTyCtxt::new(self.arena);
 
             let sig = {
                 let mut f = |l: &Option<_>| {
-                    if let Some(v) = *l {
-                        Some(mr.assign[v])
-                    } else {
-                        None
+                    match *l {
+                        Some(v) if !raw.contains(&v) => Some(mr.assign[v]),
+                        _ => None,
                     }
                 };
                 ownership::FnSig {
@@ -543,3 +1490,408 @@ fn do_mark_pointers(st: &CommandState, cx: &driver::Ctxt) {
         st.add_mark(ast_ty.id, label);
     });
 }
+
+
+/// Mark with `label` the length arguments of the array pointers the analysis finds, where the
+/// length comes right after the pointer.  This is what `slice_argument` expects, so
+/// `ownership_mark_pointers; ownership_mark_array_lens; slice_argument` turns each READ or WRITE
+/// array pointer and its length into a `&[T]` or `&mut [T]`.
+fn do_mark_array_lens(st: &CommandState, cx: &driver::Ctxt, label: Symbol) {
+    let ana = ownership::analyze(&st, &cx);
+
+    visit_fns(&*st.krate(), |fl| {
+        let def_id = match_or!([cx.hir_map().opt_local_def_id(fl.id)] Some(x) => x; return);
+        if !ana.variants.contains_key(&def_id) {
+            return;
+        }
+        let (fr, _) = ana.fn_results(def_id);
+        for &(ptr_idx, len_idx) in &fr.array_pointers {
+            if len_idx == Some(ptr_idx + 1) {
+                st.add_mark(fl.decl.inputs[ptr_idx + 1].id, label);
+            }
+        }
+    });
+}
+
+
+/// The `SigVar`s of arguments that must stay raw pointers in `mr`.  A `&mut` argument must not
+/// alias any other reference, so of each pair of arguments that could alias, where one is WRITE
+/// and the other READ or WRITE, one stays raw: the READ one, since writes through the other would
+/// break its `&` borrow, or else the second one.  Where some caller actually passes the same
+/// pointer for both, both stay raw.
+fn aliased_vars(fr: &ownership::FunctionResult,
+                mr: &ownership::MonoResult) -> HashSet<Var> {
+    let mut raw = HashSet::new();
+    for &(v1, v2) in &fr.potential_alias_pairs {
+        let keep_raw = match (mr.assign[v1], mr.assign[v2]) {
+            (ConcretePerm::Write, ConcretePerm::Write) |
+            (ConcretePerm::Write, ConcretePerm::Read) => v2,
+            (ConcretePerm::Read, ConcretePerm::Write) => v1,
+            _ => continue,
+        };
+        if !raw.contains(&v1) && !raw.contains(&v2) {
+            raw.insert(keep_raw);
+        }
+    }
+    // A caller passes the same pointer for both, so it can't borrow it for both at once.
+    for &(v1, v2) in &fr.alias_conflicts {
+        match (mr.assign[v1], mr.assign[v2]) {
+            (ConcretePerm::Write, ConcretePerm::Write) |
+            (ConcretePerm::Write, ConcretePerm::Read) |
+            (ConcretePerm::Read, ConcretePerm::Write) => {
+                raw.insert(v1);
+                raw.insert(v2);
+            },
+            _ => {},
+        }
+    }
+    raw
+}
+
+
+/// Turn raw pointer parameters into references: `&T` where every monomorphization of the
+/// function gives the pointer READ, and `&mut T` where every one gives it WRITE.  Functions whose
+/// monomorphizations disagree keep their pointers; split them with `ownership_split_variants`
+/// first, and each variant gets the permissions of its own monomorphization.  As in
+/// `ownership_mark_pointers`, parameters of frozen signatures stay raw, and so does one of two
+/// parameters that could point at the same object where either is WRITE.  Nullable pointers and
+/// `mut` bindings, which the function could reassign, stay raw as well.
+///
+/// Callers pass `&mut *p` or `&*p` in place of the pointer `p`, or borrow directly where they
+/// passed `&mut x`.  In the function, dereferences work on the reference as they did on the
+/// pointer, and other uses of the parameter get a cast back to its old type.  Only plain
+/// functions called by name are changed; one that is used as a value keeps its signature.
+fn do_ref_params(st: &CommandState, cx: &driver::Ctxt) {
+    let ana = ownership::analyze(&st, &cx);
+
+    // The converted parameters of each function, by index: the mutability of the new reference,
+    // the `NodeId` of the binding, and the old pointer type.
+    let mut ref_params: HashMap<DefId, HashMap<usize, (Mutability, NodeId, MutTy)>> =
+        HashMap::new();
+
+    visit_fns(&*st.krate(), |fl| {
+        if fl.kind != FnKind::Normal || fl.block.is_none() {
+            return;
+        }
+        let def_id = match_or!([cx.hir_map().opt_local_def_id(fl.id)] Some(x) => x; return);
+        if !ana.variants.contains_key(&def_id) {
+            return;
+        }
+        let (fr, vr) = ana.fn_results(def_id);
+        if fr.sig_frozen {
+            return;
+        }
+        let mono_idxs =
+            if fr.variants.is_none() { 0 .. fr.num_monos }
+            else { vr.index .. vr.index + 1 };
+        let monos = mono_idxs.map(|i| &ana.monos[&(vr.func_id, i)]).collect::<Vec<_>>();
+        let raw = monos.iter().flat_map(|mr| aliased_vars(fr, mr)).collect::<HashSet<_>>();
+
+        for (idx, arg) in fl.decl.inputs.iter().enumerate() {
+            let ptr = match_or!([arg.ty.node] TyKind::Ptr(ref mt) => mt; continue);
+            if !matches!([arg.pat.node]
+                         PatKind::Ident(BindingMode::ByValue(Mutability::Immutable), _, None)) {
+                continue;
+            }
+            let v = match_or!([fr.sig.inputs.get(idx).and_then(|lty| lty.label)]
+                              Some(v) => v; continue);
+            if raw.contains(&v) || fr.nullable.contains(v.0 as usize) {
+                continue;
+            }
+            let perm = monos[0].assign[v];
+            if monos.iter().any(|mr| mr.assign[v] != perm) {
+                continue;
+            }
+            let mutbl = match (perm, ptr.mutbl) {
+                (ConcretePerm::Read, _) => Mutability::Immutable,
+                (ConcretePerm::Write, Mutability::Mutable) => Mutability::Mutable,
+                _ => continue,
+            };
+            ref_params.entry(def_id).or_insert_with(HashMap::new)
+                .insert(idx, (mutbl, arg.pat.id, ptr.clone()));
+        }
+    });
+
+    // Callers of a function used as a value would still pass it pointers.
+    let mut callee_paths = HashSet::new();
+    visit_nodes(&*st.krate(), |e: &Expr| {
+        if let ExprKind::Call(ref func, _) = e.node {
+            callee_paths.insert(func.id);
+        }
+    });
+    visit_nodes(&*st.krate(), |e: &Expr| {
+        if matches!([e.node] ExprKind::Path(..)) && !callee_paths.contains(&e.id) {
+            if let Some(def_id) = cx.try_resolve_expr(e) {
+                ref_params.remove(&def_id);
+            }
+        }
+    });
+
+    let bindings = ref_params.values()
+        .flat_map(|params| params.values())
+        .map(|&(mutbl, id, ref ptr)| (id, (mutbl, ptr.clone())))
+        .collect::<HashMap<_, _>>();
+    let ref_param = |e: &Expr| {
+        local_binding(cx, e).and_then(|id| bindings.get(&id))
+    };
+
+    st.map_krate(|krate| {
+        // `fold_nodes` does a preorder traversal, so parameters inside the expressions built here
+        // are visited again.  Those that are dereferenced, or were already cast, are left alone.
+        let mut done = HashSet::new();
+        let krate = fold_nodes(krate, |e: P<Expr>| {
+            if done.contains(&e.id) {
+                return e;
+            }
+
+            if let ExprKind::Unary(UnOp::Deref, ref inner) = e.node {
+                if ref_param(inner).is_some() {
+                    done.insert(inner.id);
+                }
+            }
+
+            if let Some(&(mutbl, ref ptr)) = ref_param(&e) {
+                done.insert(e.id);
+                return match mutbl {
+                    Mutability::Mutable => {
+                        let reborrow = mk().mutbl().addr_of_expr(
+                            mk().unary_expr(UnOp::Deref, e.clone()));
+                        mk().cast_expr(reborrow, mk().set_mutbl(ptr.mutbl).ptr_ty(ptr.ty.clone()))
+                    },
+                    Mutability::Immutable => {
+                        let cast = mk().cast_expr(e.clone(), mk().ptr_ty(ptr.ty.clone()));
+                        if ptr.mutbl == Mutability::Mutable {
+                            mk().cast_expr(cast, mk().mutbl().ptr_ty(ptr.ty.clone()))
+                        } else {
+                            cast
+                        }
+                    },
+                };
+            }
+
+            if !matches!([e.node] ExprKind::Call(..)) {
+                return e;
+            }
+            let params = match_or!([cx.opt_callee(&e).and_then(|id| ref_params.get(&id))]
+                                   Some(x) => x; return e);
+            e.map(|mut e| {
+                if let ExprKind::Call(_, ref mut args) = e.node {
+                    for (&idx, &(mutbl, _, _)) in params {
+                        let new_arg = match args[idx].node {
+                            // Borrow directly instead of reborrowing the coerced pointer.
+                            ExprKind::AddrOf(arg_mutbl, ref inner)
+                                    if arg_mutbl == mutbl || mutbl == Mutability::Immutable =>
+                                mk().set_mutbl(mutbl).addr_of_expr(inner.clone()),
+                            _ => mk().set_mutbl(mutbl).addr_of_expr(
+                                mk().unary_expr(UnOp::Deref, args[idx].clone())),
+                        };
+                        args[idx] = new_arg;
+                    }
+                }
+                e
+            })
+        });
+
+        fold_fns(krate, |mut fl| {
+            let def_id = match_or!([cx.hir_map().opt_local_def_id(fl.id)] Some(x) => x; return fl);
+            let params = match_or!([ref_params.get(&def_id)] Some(x) => x; return fl);
+            fl.decl = fl.decl.map(|mut decl| {
+                for (&idx, &(mutbl, _, ref ptr)) in params {
+                    decl.inputs[idx].ty = mk().set_mutbl(mutbl).ref_ty(ptr.ty.clone());
+                }
+                decl
+            });
+            fl
+        })
+    });
+}
+
+
+/// Turn marked free functions taking a pointer to a local struct into methods of an `impl` of
+/// that struct, taking `&self` or `&mut self` in place of the first such pointer.  The receiver
+/// borrows only as much as the analysis says the function needs, which every monomorphization
+/// must agree on.  Functions whose monomorphizations disagree, that need MOVE, or that are used
+/// as values are left alone.
+///
+/// Method names drop the `struct_name_` prefix where the function has one, so `list_push` on a
+/// `List` becomes `List::push`.  The body rebinds `self` to the original raw pointer argument,
+/// and calls become method calls on the dereferenced pointer.
+fn do_to_methods(st: &CommandState,
+                 cx: &driver::Ctxt,
+                 label: Symbol) {
+    let ana = ownership::analyze(&st, &cx);
+
+    // A function used as a value can't become a method.
+    let mut callee_paths = HashSet::new();
+    visit_nodes(&*st.krate(), |e: &Expr| {
+        if let ExprKind::Call(ref func, _) = e.node {
+            callee_paths.insert(func.id);
+        }
+    });
+    let mut fn_values = HashSet::new();
+    visit_nodes(&*st.krate(), |e: &Expr| {
+        if matches!([e.node] ExprKind::Path(..)) && !callee_paths.contains(&e.id) {
+            if let Some(def_id) = cx.try_resolve_expr(e) {
+                fn_values.insert(def_id);
+            }
+        }
+    });
+
+    struct MethodInfo {
+        /// The struct item receiving the method
+        struct_id: NodeId,
+        struct_ident: Ident,
+        method_ident: Ident,
+        /// Index of the argument replaced with `self`
+        arg_idx: usize,
+        mutbl: Mutability,
+    }
+
+    let receiver_info = |i: &Item| -> Option<MethodInfo> {
+        let decl = match_or!([i.node] ItemKind::Fn(ref decl, ..) => decl; return None);
+        let def_id = cx.hir_map().opt_local_def_id(i.id)?;
+        if !ana.variants.contains_key(&def_id) || fn_values.contains(&def_id) {
+            return None;
+        }
+        let (fr, vr) = ana.fn_results(def_id);
+        // Methods can't keep the symbol that C calls a frozen function by.
+        if fr.variants.is_some() || fr.sig_frozen {
+            return None;
+        }
+
+        // The first argument pointing to a struct of this crate
+        let (arg_idx, struct_id) = decl.inputs.iter().enumerate().filter_map(|(idx, arg)| {
+            let pointee = match_or!([cx.node_type(arg.pat.id).sty]
+                                    TypeVariants::TyRawPtr(tm) => tm.ty; return None);
+            let adt = match_or!([pointee.sty] TypeVariants::TyAdt(adt, _) => adt; return None);
+            if !adt.is_struct() {
+                return None;
+            }
+            cx.hir_map().as_local_node_id(adt.did).map(|id| (idx, id))
+        }).next()?;
+        if !matches!([decl.inputs[arg_idx].pat.node] PatKind::Ident(..)) {
+            return None;
+        }
+
+        let var = fr.sig.inputs[arg_idx].label?;
+        let perm = ana.monos[&(vr.func_id, 0)].assign[var];
+        if (1 .. fr.num_monos).any(|idx| ana.monos[&(vr.func_id, idx)].assign[var] != perm) {
+            return None;
+        }
+        let mutbl = match perm {
+            ConcretePerm::Read => Mutability::Immutable,
+            ConcretePerm::Write => Mutability::Mutable,
+            ConcretePerm::Move => return None,
+        };
+
+        let struct_ident = mk().ident(cx.hir_map().name(struct_id));
+        let prefix = format!("{}_", struct_ident.name.as_str().to_lowercase());
+        let fn_name = i.ident.name.as_str();
+        let method_ident = match fn_name.get(prefix.len() ..) {
+            Some(rest) if fn_name.starts_with(&prefix) && is_plain_ident(rest) => mk().ident(rest),
+            _ => i.ident,
+        };
+
+        Some(MethodInfo { struct_id, struct_ident, method_ident, arg_idx, mutbl })
+    };
+
+    st.map_krate(|krate| {
+        // (1) Remove the marked functions that can become methods.
+        let mut methods: HashMap<NodeId, Vec<ImplItem>> = HashMap::new();
+        let mut fn_infos: HashMap<DefId, MethodInfo> = HashMap::new();
+        let mut struct_idents: HashMap<NodeId, Ident> = HashMap::new();
+
+        let krate = fold_modules(krate, |curs: &mut Cursor<P<Item>>| {
+            while let Some(info) = curs.advance_until_match(|i| {
+                if st.marked(i.id, label) { receiver_info(&**i) } else { None }
+            }) {
+                let i = curs.remove();
+                unpack!([i.node.clone()]
+                        ItemKind::Fn(decl, unsafety, constness, abi, generics, block));
+
+                // `fn f(.., l: *mut S, ..)` becomes `fn m(&mut self, ..)`, starting with
+                // `let l: *mut S = self;`
+                let mut inputs = decl.inputs.clone();
+                let arg = inputs.remove(info.arg_idx);
+                inputs.insert(0, mk().self_arg(SelfKind::Region(None, info.mutbl)));
+                let decl = decl.map(|fd| FnDecl { inputs: inputs, .. fd });
+
+                let mut self_ptr = mk().ident_expr("self");
+                if info.mutbl == Mutability::Immutable {
+                    if let TyKind::Ptr(ref mty) = arg.ty.node {
+                        if mty.mutbl == Mutability::Mutable {
+                            self_ptr = mk().cast_expr(self_ptr, mk().ptr_ty(mty.ty.clone()));
+                            self_ptr = mk().cast_expr(self_ptr, arg.ty.clone());
+                        }
+                    }
+                }
+                let rebind = mk().local_stmt(P(mk().local(arg.pat.clone(), Some(arg.ty.clone()),
+                                                          Some(self_ptr))));
+                let block = block.map(|mut b| {
+                    b.stmts.insert(0, rebind);
+                    b
+                });
+
+                let sig = MethodSig { unsafety, constness, abi, decl };
+                methods.entry(info.struct_id).or_insert_with(Vec::new).push(ImplItem {
+                    id: DUMMY_NODE_ID,
+                    ident: info.method_ident,
+                    vis: i.vis.clone(),
+                    defaultness: Defaultness::Final,
+                    attrs: i.attrs.clone(),
+                    generics: generics,
+                    node: ImplItemKind::Method(sig, block),
+                    span: i.span,
+                    tokens: None,
+                });
+                struct_idents.insert(info.struct_id, info.struct_ident);
+                fn_infos.insert(cx.node_def_id(i.id), info);
+            }
+        });
+
+        // (2) Add an `impl` with the methods of each struct right after its definition.
+        let krate = fold_modules(krate, |curs: &mut Cursor<P<Item>>| {
+            while let Some(struct_id) = curs.advance_until_match(|i| {
+                if methods.contains_key(&i.id) { Some(i.id) } else { None }
+            }) {
+                let items = methods.remove(&struct_id).unwrap();
+                curs.advance();
+                curs.insert(mk().impl_item(mk().path_ty(vec![struct_idents[&struct_id]]), items));
+            }
+        });
+
+        // (3) Turn calls to the converted functions into method calls on the pointed-to struct.
+        fold_nodes(krate, |e: P<Expr>| {
+            if !matches!([e.node] ExprKind::Call(..)) {
+                return e;
+            }
+
+            unpack!([e.node.clone()] ExprKind::Call(func, args));
+            let def_id = match_or!([cx.try_resolve_expr(&func)] Some(x) => x; return e);
+            let info = match_or!([fn_infos.get(&def_id)] Some(x) => x; return e);
+
+            let mut args = args;
+            let ptr = args.remove(info.arg_idx);
+            let receiver = match ptr.node {
+                ExprKind::AddrOf(_, ref place) => place.clone(),
+                _ => mk().paren_expr(mk().unary_expr(UnOp::Deref, ptr.clone())),
+            };
+            args.insert(0, receiver);
+
+            e.map(|e| {
+                Expr {
+                    node: ExprKind::MethodCall(mk().path_segment(&info.method_ident), args),
+                    .. e
+                }
+            })
+        })
+    });
+}
+
+/// Whether `name` can be used as an identifier as-is
+fn is_plain_ident(name: &str) -> bool {
+    let starts_ok = name.chars().next().map_or(false, |c| c.is_alphabetic() || c == '_');
+    starts_ok &&
+        name.chars().all(|c| c.is_alphanumeric() || c == '_') &&
+        !Token::Ident(mk().ident(name)).is_reserved_ident()
+}
@@ -116,6 +116,161 @@ impl Transform for RetypeArgument {
 }
 
 
+/// Merge a pointer argument and the length argument following it into a single slice argument.
+/// Length arguments are marked `target`.  The pointer argument's type must be marked `ref` or
+/// `mut` by `ownership_mark_pointers`, which picks between `&[T]` and `&mut [T]`; pointers the
+/// ownership analysis finds to be owning, or can't give a single permission, are left alone.
+///
+/// Inside the function, the old pointer and length are rebound from the slice.  At call sites,
+/// `a.as_ptr(), a.len() as _` becomes `&a[..]`, `a.as_ptr(), n` becomes `&a[..n as usize]`, and
+/// any other pointer is passed through `slice::from_raw_parts`.
+pub struct SliceArgument;
+
+impl Transform for SliceArgument {
+    fn transform(&self, krate: Crate, st: &CommandState, cx: &driver::Ctxt) -> Crate {
+        // (1) Change argument types and rebind the old arguments in function bodies.
+
+        // Modified functions, by DefId.  For each one, we track the index of the pointer argument
+        // (the removed length argument followed it) and the mutability of the new slice.
+        let mut mod_fns: HashMap<DefId, (usize, Mutability)> = HashMap::new();
+
+        let krate = fold_fns(krate, |mut fl| {
+            // Functions without bodies, like foreign functions, must keep their signatures.
+            if fl.block.is_none() {
+                return fl;
+            }
+            let fn_id = fl.id;
+            let fn_ident = fl.ident;
+
+            let mut merged = None;
+            fl.decl = fl.decl.map(|mut decl| {
+                let len_idx = match_or!([decl.inputs.iter().position(|arg| st.marked(arg.id, "target"))]
+                                        Some(x) => x; return decl);
+                if len_idx == 0 {
+                    warn!("length argument of {:?} has no pointer argument before it", fn_ident);
+                    return decl;
+                }
+                let ptr_idx = len_idx - 1;
+
+                let mutbl = {
+                    let ptr_ty = &decl.inputs[ptr_idx].ty;
+                    if st.marked(ptr_ty.id, "mut") {
+                        Mutability::Mutable
+                    } else if st.marked(ptr_ty.id, "ref") {
+                        Mutability::Immutable
+                    } else {
+                        warn!("pointer argument of {:?} is not a `ref` or `mut` pointer", fn_ident);
+                        return decl;
+                    }
+                };
+                let elt_ty = match_or!([decl.inputs[ptr_idx].ty.node]
+                                       TyKind::Ptr(ref mty) => mty.ty.clone(); return decl);
+                let name = match_or!([decl.inputs[ptr_idx].pat.node]
+                                     PatKind::Ident(_, ref ident, None) => ident.node; return decl);
+
+                let len_arg = decl.inputs.remove(len_idx);
+                let slice_ty = mk().set_mutbl(mutbl).ref_ty(mk().slice_ty(elt_ty));
+                let ptr_arg = mem::replace(&mut decl.inputs[ptr_idx],
+                                           mk().arg(slice_ty, mk().ident_pat(name)));
+
+                mod_fns.insert(cx.node_def_id(fn_id), (ptr_idx, mutbl));
+                merged = Some((ptr_arg, len_arg, name, mutbl));
+                decl
+            });
+
+            let (ptr_arg, len_arg, name, mutbl) = match_or!([merged] Some(x) => x; return fl);
+
+            // let len: L = slice.len() as L;
+            // let ptr: *mut T = slice.as_mut_ptr() as *mut T;
+            let as_ptr = if mutbl == Mutability::Mutable { "as_mut_ptr" } else { "as_ptr" };
+            let no_args = || Vec::<P<Expr>>::new();
+            let len = mk().cast_expr(mk().method_call_expr(mk().ident_expr(name), "len", no_args()),
+                                     len_arg.ty.clone());
+            let ptr = mk().cast_expr(mk().method_call_expr(mk().ident_expr(name), as_ptr, no_args()),
+                                     ptr_arg.ty.clone());
+            let rebinds = vec![
+                mk().local_stmt(P(mk().local(len_arg.pat, Some(len_arg.ty), Some(len)))),
+                mk().local_stmt(P(mk().local(ptr_arg.pat, Some(ptr_arg.ty), Some(ptr)))),
+            ];
+            fl.block = fl.block.map(|b| b.map(|mut b| {
+                b.stmts.splice(0..0, rebinds);
+                b
+            }));
+
+            fl
+        });
+
+        // (2) Rewrite callsites of modified functions.
+
+        let krate = fold_nodes(krate, |e: P<Expr>| {
+            let callee = match_or!([cx.opt_callee(&e)] Some(x) => x; return e);
+            let (ptr_idx, mutbl) = match_or!([mod_fns.get(&callee)] Some(&x) => x; return e);
+            e.map(|mut e| {
+                {
+                    let args: &mut Vec<P<Expr>> =
+                        match e.node {
+                            ExprKind::Call(_, ref mut args) => args,
+                            ExprKind::MethodCall(_, ref mut args) => args,
+                            _ => panic!("expected Call or MethodCall"),
+                        };
+                    let len = args.remove(ptr_idx + 1);
+                    let ptr = args[ptr_idx].clone();
+                    args[ptr_idx] = slice_expr(st, cx, ptr, len, mutbl);
+                }
+                e
+            })
+        });
+
+        krate
+    }
+
+    fn min_phase(&self) -> Phase {
+        Phase::Phase3
+    }
+}
+
+/// The slice passed in place of a pointer and length
+fn slice_expr(st: &CommandState,
+              cx: &driver::Ctxt,
+              ptr: P<Expr>,
+              len: P<Expr>,
+              mutbl: Mutability) -> P<Expr> {
+    let (borrow, as_ptr, from_raw_parts) = match mutbl {
+        Mutability::Mutable => ("&mut ", "as_mut_ptr", "from_raw_parts_mut"),
+        Mutability::Immutable => ("&", "as_ptr", "from_raw_parts"),
+    };
+
+    let mut bnd = Bindings::new();
+    bnd.add_expr("__ptr", ptr.clone());
+    bnd.add_expr("__len", len.clone());
+
+    let template = match method_receiver(&ptr, as_ptr) {
+        Some(array) => {
+            bnd.add_expr("__array", array.clone());
+            let count = match len.node {
+                ExprKind::Cast(ref count, _) => count,
+                _ => &len,
+            };
+            match method_receiver(count, "len") {
+                Some(len_of) if len_of.ast_equiv(array) => format!("{}__array[..]", borrow),
+                _ => format!("{}__array[..__len as usize]", borrow),
+            }
+        }
+        None => format!("::std::slice::{}(__ptr, __len as usize)", from_raw_parts),
+    };
+    parse_expr(cx.session(), &template).subst(st, cx, &bnd)
+}
+
+/// The receiver of `e` if it is a call to the method `name` without arguments
+fn method_receiver<'a>(e: &'a P<Expr>, name: &str) -> Option<&'a P<Expr>> {
+    match e.node {
+        ExprKind::MethodCall(ref seg, ref args) if &*seg.identifier.name.as_str() == name && args.len() == 1 =>
+            Some(&args[0]),
+        _ => None,
+    }
+}
+
+
 /// Rewrite types in the crate to types that are transmute-compatible with the original.
 /// Automatically inserts `transmute` calls as needed to make the types line up after rewriting.
 ///
@@ -409,6 +564,8 @@ pub fn register_commands(reg: &mut Registry) {
         unwrap: args[2].clone(),
     }));
 
+    reg.register("slice_argument", |_args| mk(SliceArgument));
+
     reg.register("bitcast_retype", |args| mk(BitcastRetype {
         pat: args[0].clone(),
         repl: args[1].clone(),
@@ -0,0 +1,27 @@
+// Both arguments are written through.  A caller could pass the same pointer twice, so only one
+// of them may become `&mut`.
+unsafe fn swap(a: &mut i32, b: *mut i32) {
+    let tmp = *a;
+    *a = *b;
+    *b = tmp;
+}
+
+// `src` is only read, but a caller could pass the same pointer as `dst`.  Writes through `dst`
+// would then break a `&` borrow of `src`, so `src` stays raw.
+unsafe fn copy(dst: &mut i32, src: *mut i32) {
+    *dst = *src;
+}
+
+// A `u8` pointer may point into an object of any type, as a C `char *` may.
+unsafe fn store_byte(a: &mut i32, b: *mut u8) {
+    *a = 1;
+    *b = 2;
+}
+
+// Pointers to other different types can't alias.
+unsafe fn store(a: &mut i32, b: &mut i64) {
+    *a = 1;
+    *b = 2;
+}
+
+fn main() {}
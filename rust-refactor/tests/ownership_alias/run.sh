@@ -0,0 +1,12 @@
+#!/bin/sh
+
+# work around System Integrity Protection on macOS
+if [ `uname` = 'Darwin' ]; then
+    export LD_LIBRARY_PATH=$not_LD_LIBRARY_PATH
+fi
+
+$refactor \
+    ownership_mark_pointers \; \
+    rewrite_ty 'marked!(*mut __t, mut)' '&mut __t' \; \
+    rewrite_ty 'marked!(*mut __t, ref)' '&__t' \
+    -- old.rs $rustflags
@@ -0,0 +1,29 @@
+// `main` passes the same pointer for both arguments, so neither may become `&mut`.
+unsafe fn add_to(dst: *mut i32, src: *mut i32) {
+    *dst = *dst + *src;
+    *src = 0;
+}
+
+// `src` is only read, but `main` passes the same pointer for both arguments, so `dst` can't
+// become `&mut` while `src` is borrowed either.
+unsafe fn accumulate(dst: *mut i32, src: *mut i32) {
+    *dst = *dst + *src;
+}
+
+// Called with distinct pointers, so only the possible alias keeps one of them raw.
+unsafe fn add_from(dst: *mut i32, src: *mut i32) {
+    *dst = *dst + *src;
+    *src = 0;
+}
+
+fn main() {
+    let mut x = 1;
+    let mut y = 2;
+    let p = &mut x as *mut i32;
+    let q = &mut y as *mut i32;
+    unsafe {
+        add_to(p, p);
+        accumulate(p, p);
+        add_from(p, q);
+    }
+}
@@ -0,0 +1,40 @@
+#![feature(custom_attribute, attr_literals)]
+
+use std::mem;
+use std::os::raw::c_void;
+
+extern "C" {
+    fn malloc(size: usize) -> *mut c_void;
+    fn free(ptr: *mut c_void);
+}
+
+// Returns what `malloc` returned, so it hands out ownership too.
+#[ownership_constraints(le(MOVE, _0))]
+#[ownership_mono("", MOVE)]
+unsafe fn counter_new() -> *mut i32 {
+    let c = malloc(mem::size_of::<i32>()) as *mut i32;
+    *c = 0;
+    c
+}
+
+#[ownership_constraints(le(WRITE, _0))]
+#[ownership_mono("", WRITE)]
+unsafe fn counter_bump(c: *mut i32) {
+    *c += 1;
+}
+
+#[ownership_constraints(le(MOVE, _0))]
+#[ownership_mono("", MOVE)]
+unsafe fn counter_delete(c: *mut i32) {
+    free(c as *mut c_void);
+}
+
+#[ownership_constraints()]
+unsafe fn counter_run() {
+    let c = counter_new();
+    counter_bump(c);
+    counter_delete(c);
+}
+
+#[ownership_constraints()]
+fn main() {}
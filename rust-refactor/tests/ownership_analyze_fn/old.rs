@@ -0,0 +1,17 @@
+use std::os::raw::c_void;
+
+extern "C" {
+    fn free(ptr: *mut c_void);
+}
+
+unsafe fn set(p: *mut i32) {
+    *p = 1;
+}
+
+// Only this function is marked, but `set` is analyzed too, so `p` still needs `WRITE`.
+unsafe fn set_twice(p: *mut i32, q: *const i32) {
+    set(p);
+    set(p.offset(*q as isize));
+}
+
+fn main() {}
@@ -0,0 +1,22 @@
+#!/bin/sh
+set -e
+
+# work around System Integrity Protection on macOS
+if [ `uname` = 'Darwin' ]; then
+    export LD_LIBRARY_PATH=$not_LD_LIBRARY_PATH
+fi
+
+$refactor \
+    select target 'crate; desc(fn && name("set_twice"));' \; \
+    select foreign 'crate; desc(foreign_item && fn && name("free"));' \; \
+    test_analysis_ownership_fn target old.txt \; \
+    test_analysis_ownership_fn foreign foreign.txt \
+    -- old.rs $rustflags
+
+diff -u new.txt old.txt
+
+# Foreign functions have no body to analyze
+grep -q '^func .*free\[0\]: not a local fn$' foreign.txt
+
+# The command only writes the summary, so the source is unchanged
+cp old.rs old.rs.new
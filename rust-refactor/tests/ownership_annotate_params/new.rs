@@ -0,0 +1,20 @@
+#![feature(custom_attribute, attr_literals)]
+
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+// Has a mono for each permission of the result, so `p` is annotated with its signature variable.
+#[ownership_param(0, _0)]
+unsafe fn point_x(p: *mut Point) -> *mut i32 {
+    &mut (*p).x
+}
+
+// `v` is not a pointer, so only `p` is annotated.
+#[ownership_param(0, WRITE)]
+unsafe fn set(p: *mut i32, v: i32) {
+    *p = v;
+}
+
+fn main() {}
@@ -0,0 +1,47 @@
+// The usual C convention: the length follows the array.
+unsafe fn sum(arr: *const i32, len: usize) -> i32 {
+    let mut total = 0;
+    let mut i = 0;
+    while i < len {
+        total += *arr.offset(i as isize);
+        i += 1;
+    }
+    total
+}
+
+// The only bound is the length, wherever it is.
+unsafe fn fill(n: usize, value: i32, arr: *mut i32) {
+    let mut i = 0;
+    while i < n {
+        *arr.offset(i as isize) = value;
+        i += 1;
+    }
+}
+
+// The index starts at zero, but is redefined by the call in the loop, so the offset isn't
+// always zero.
+unsafe fn scale(arr: *mut i32, len: usize, factor: i32) {
+    let mut i = 0;
+    while i < len {
+        *arr.offset(i as isize) *= factor;
+        i = i.wrapping_add(1);
+    }
+}
+
+// Walked through a copy, up to a terminator rather than a length.
+unsafe fn count_nonzero(arr: *const i32) -> usize {
+    let mut p = arr;
+    let mut count = 0;
+    while *p != 0 {
+        count += 1;
+        p = p.offset(1);
+    }
+    count
+}
+
+// Only ever looks at the first element, so this is a pointer to a single value.
+unsafe fn first(p: *const i32) -> i32 {
+    *p.offset(0)
+}
+
+fn main() {}
@@ -0,0 +1,26 @@
+#!/bin/sh
+set -e
+
+# work around System Integrity Protection on macOS
+if [ `uname` = 'Darwin' ]; then
+    export LD_LIBRARY_PATH=$not_LD_LIBRARY_PATH
+fi
+
+$refactor \
+    test_analysis_ownership old.txt \
+    -- old.rs $rustflags
+
+summary() {
+    sed -n "/^func ::$1\[0\]:/,/^func/p" old.txt
+}
+
+summary sum | grep -qF 'array pointers: [(0, Some(1))]'
+summary fill | grep -qF 'array pointers: [(2, Some(0))]'
+summary scale | grep -qF 'array pointers: [(0, Some(1))]'
+summary count_nonzero | grep -qF 'array pointers: [(0, None)]'
+if summary first | grep -qF 'array pointers'; then
+    exit 1
+fi
+
+# The command only writes the summary, so the source is unchanged
+cp old.rs old.rs.new
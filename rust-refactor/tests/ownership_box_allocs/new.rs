@@ -0,0 +1,59 @@
+#![feature(custom_attribute, attr_literals)]
+
+use std::mem;
+use std::os::raw::c_void;
+
+extern "C" {
+    fn malloc(size: usize) -> *mut c_void;
+    fn free(ptr: *mut c_void);
+}
+
+#[derive(Clone, Copy, Default)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+unsafe fn point_sum(p: *mut Point) -> i32 {
+    (*p).x + (*p).y
+}
+
+unsafe fn point_keep(p: *mut Point) -> *mut Point {
+    p
+}
+
+// Allocated, borrowed by `point_sum`, and freed: becomes a `Box`.
+unsafe fn boxed() -> i32 {
+    let mut p = Box::new(Point::default());
+    (*p).x = 1;
+    (*p).y = 2;
+    let sum = point_sum(&mut *p);
+    sum
+}
+
+// `point_keep` may take ownership of its argument, so the allocation stays as it is.
+unsafe fn escapes() -> *mut Point {
+    let p = malloc(mem::size_of::<Point>()) as *mut Point;
+    let q = point_keep(p);
+    free(p as *mut c_void);
+    q
+}
+
+// An array allocation becomes a `Vec`.
+unsafe fn array_sum(n: usize) -> i32 {
+    let mut a = vec![i32::default(); n as usize];
+    let mut i = 0;
+    while i < n {
+        *a.as_mut_ptr().offset(i as isize) = i as i32;
+        i += 1;
+    }
+    let mut sum = 0;
+    i = 0;
+    while i < n {
+        sum += *a.as_mut_ptr().offset(i as isize);
+        i += 1;
+    }
+    sum
+}
+
+fn main() {}
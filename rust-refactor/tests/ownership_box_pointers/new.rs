@@ -0,0 +1,122 @@
+#![feature(custom_attribute, attr_literals)]
+
+use std::mem;
+use std::os::raw::c_void;
+use std::ptr;
+
+extern "C" {
+    fn malloc(size: usize) -> *mut c_void;
+    fn free(ptr: *mut c_void);
+}
+
+#[derive(Default)]
+struct Payload {
+    value: i32,
+}
+
+// A single-owner list node.  Each node owns its payload, which becomes a `Box`.  `next` is also
+// freed through the node, but it is copied and compared with null, so it stays a raw pointer.
+struct Node {
+    payload: Box<Payload>,
+    next: *mut Node,
+}
+
+unsafe fn payload_get(p: *mut Payload) -> i32 {
+    (*p).value
+}
+
+unsafe fn payload_free(mut p: Box<Payload>) {
+    drop(p);
+}
+
+// Borrows `p`, then hands it on to `payload_free`.
+unsafe fn payload_take(mut p: Box<Payload>) -> i32 {
+    let v = payload_get(&mut *p);
+    payload_free(p);
+    v
+}
+
+// MOVE in its "take" mono, but READ in the default one: stays a raw pointer.
+unsafe fn payload_id(p: *mut Payload) -> *mut Payload {
+    p
+}
+
+unsafe fn node_set_payload(n: *mut Node, value: i32) {
+    let mut p = Box::new(Payload::default());
+    (*p).value = value;
+    ::std::ptr::write(&mut (*n).payload, p);
+}
+
+unsafe fn node_value(n: *mut Node) -> i32 {
+    payload_get(&mut *(*n).payload)
+}
+
+unsafe fn node_take(n: *mut Node) -> i32 {
+    payload_take(::std::ptr::read(&(*n).payload))
+}
+
+// `list_free` passes a raw pointer, so `n` stays one too.
+unsafe fn node_free(n: *mut Node) {
+    payload_free(::std::ptr::read(&(*n).payload));
+    free(n as *mut c_void);
+}
+
+unsafe fn list_push(head: *mut Node, value: i32) -> *mut Node {
+    let n = malloc(mem::size_of::<Node>()) as *mut Node;
+    node_set_payload(n, value);
+    (*n).next = head;
+    n
+}
+
+unsafe fn list_free(mut n: *mut Node) {
+    while n != ptr::null_mut() {
+        let next = (*n).next;
+        node_free(n);
+        n = next;
+    }
+}
+
+// `Handle` doesn't implement `Default`, so there is no `Box::new(Handle::default())` to replace
+// the allocation, and both pointers stay raw.
+struct Handle {
+    fd: i32,
+}
+
+unsafe fn handle_close(h: *mut Handle) {
+    free(h as *mut c_void);
+}
+
+unsafe fn handle_use(fd: i32) {
+    let h = malloc(mem::size_of::<Handle>()) as *mut Handle;
+    (*h).fd = fd;
+    handle_close(h);
+}
+
+// Translated C structs derive `Copy`, which a `Box` field would break, so `blob` stays raw, and
+// so do the pointers moved into or out of it.
+#[derive(Default)]
+struct Blob {
+    size: i32,
+}
+
+#[derive(Copy, Clone)]
+#[repr(C)]
+struct Holder {
+    blob: *mut Blob,
+}
+
+unsafe fn blob_free(b: *mut Blob) {
+    free(b as *mut c_void);
+}
+
+unsafe fn holder_fill(h: *mut Holder, size: i32) {
+    let b = malloc(mem::size_of::<Blob>()) as *mut Blob;
+    (*b).size = size;
+    (*h).blob = b;
+}
+
+unsafe fn holder_clear(h: *mut Holder) {
+    blob_free((*h).blob);
+}
+
+fn main() {}
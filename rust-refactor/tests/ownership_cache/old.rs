@@ -0,0 +1,19 @@
+struct Buf {
+    data: *mut i32,
+    len: usize,
+}
+
+unsafe fn first(b: *const Buf) -> i32 {
+    *(*b).data
+}
+
+unsafe fn clear(b: *mut Buf) {
+    *(*b).data = 0;
+    (*b).len = 0;
+}
+
+unsafe fn reset(b: *mut Buf) {
+    clear(b);
+}
+
+fn main() {}
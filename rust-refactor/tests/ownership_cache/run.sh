@@ -0,0 +1,49 @@
+#!/bin/sh
+set -e
+
+# work around System Integrity Protection on macOS
+if [ `uname` = 'Darwin' ]; then
+    export LD_LIBRARY_PATH=$not_LD_LIBRARY_PATH
+fi
+
+rm -f cache.json
+
+# The first run analyzes everything.
+$refactor \
+    test_analysis_ownership_cached cache.json old.txt first.txt \
+    -- old.rs $rustflags
+
+# Edit `first` only, in a copy with the same crate name.
+mkdir -p edited
+sed 's/^    \*(\*b)\.data$/    *(*b).data + 1/' old.rs >edited/old.rs
+if cmp -s old.rs edited/old.rs; then
+    exit 1
+fi
+
+# The second run analyzes `first` again and reuses the rest.  The third reuses everything, and
+# gets the same results as a run without the cache.
+$refactor \
+    test_analysis_ownership_cached cache.json edited.txt second.txt \; \
+    test_analysis_ownership_cached cache.json cached.txt third.txt \; \
+    test_analysis_ownership fresh.txt \
+    -- edited/old.rs $rustflags
+
+grep -qxF 'analyzed ::first[0]' first.txt
+grep -qxF 'analyzed ::reset[0]' first.txt
+if grep -q '^reused' first.txt; then
+    exit 1
+fi
+
+grep -qxF 'analyzed ::first[0]' second.txt
+grep -qxF 'reused ::clear[0]' second.txt
+grep -qxF 'reused ::reset[0]' second.txt
+grep -qxF 'reused ::main[0]' second.txt
+test `grep -c '^analyzed' second.txt` = 1
+
+if grep -q '^analyzed' third.txt; then
+    exit 1
+fi
+diff -u fresh.txt cached.txt
+
+# The commands only write the summaries, so the source is unchanged
+cp old.rs old.rs.new
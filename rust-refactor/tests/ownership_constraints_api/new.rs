@@ -0,0 +1,22 @@
+#![feature(custom_attribute, attr_literals)]
+
+// `ownership_constraints` is printed from the public `ConstraintSet::constraints` iterator.  A
+// function only reading through its pointer has no constraints at all.
+#[ownership_constraints()]
+#[ownership_mono("take", MOVE)]
+#[ownership_mono("mut", WRITE)]
+#[ownership_mono("", READ)]
+unsafe fn get(p: *mut i32) -> i32 {
+    *p
+}
+
+// Writing through the pointer needs at least WRITE.
+#[ownership_constraints(le(WRITE, _0))]
+#[ownership_mono("take", MOVE)]
+#[ownership_mono("mut", WRITE)]
+unsafe fn set(p: *mut i32) {
+    *p = 1;
+}
+
+#[ownership_constraints()]
+fn main() {}
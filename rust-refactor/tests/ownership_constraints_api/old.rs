@@ -0,0 +1,14 @@
+#![feature(custom_attribute, attr_literals)]
+
+// `ownership_constraints` is printed from the public `ConstraintSet::constraints` iterator.  A
+// function only reading through its pointer has no constraints at all.
+unsafe fn get(p: *mut i32) -> i32 {
+    *p
+}
+
+// Writing through the pointer needs at least WRITE.
+unsafe fn set(p: *mut i32) {
+    *p = 1;
+}
+
+fn main() {}
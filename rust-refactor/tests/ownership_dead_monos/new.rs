@@ -0,0 +1,19 @@
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+// `point_x` gets a `take`, a `mut`, and a plain mono, but its callers only need the last two.
+unsafe fn point_x(p: *mut Point) -> *mut i32 {
+    &mut (*p).x
+}
+
+pub unsafe fn get_x(p: *mut Point) -> i32 {
+    *point_x(p)
+}
+
+pub unsafe fn set_x(p: *mut Point, x: i32) {
+    *point_x(p) = x;
+}
+
+fn main() {}
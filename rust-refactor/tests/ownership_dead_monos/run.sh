@@ -0,0 +1,15 @@
+#!/bin/sh
+set -e
+
+# work around System Integrity Protection on macOS
+if [ `uname` = 'Darwin' ]; then
+    export LD_LIBRARY_PATH=$not_LD_LIBRARY_PATH
+fi
+
+$refactor \
+    ownership_dead_monos old.txt \
+    -- old.rs $rustflags
+
+# The command only writes the list of dead monos, so the source is unchanged
+diff -u new.txt old.txt
+cp old.rs old.rs.new
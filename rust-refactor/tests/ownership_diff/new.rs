@@ -0,0 +1,22 @@
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+// Nothing calls the suspicious `MOVE -> MOVE` mono of `point_x`, so it is dropped at
+// `min_call_sites=1`.
+unsafe fn point_x(p: *mut Point) -> *mut i32 {
+    &mut (*p).x
+}
+
+// Only reads through `p`, until `mono_cap=2` leaves `point_x` with nothing but its `MOVE` mono.
+unsafe fn read_x(p: *mut Point) -> i32 {
+    *point_x(p)
+}
+
+// Has a single mono whatever the options.
+unsafe fn get_y(p: *mut Point) -> i32 {
+    (*p).y
+}
+
+fn main() {}
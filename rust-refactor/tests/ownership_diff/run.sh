@@ -0,0 +1,27 @@
+#!/bin/sh
+set -e
+
+# work around System Integrity Protection on macOS
+if [ `uname` = 'Darwin' ]; then
+    export LD_LIBRARY_PATH=$not_LD_LIBRARY_PATH
+fi
+
+$refactor \
+    ownership_diff old_same.txt \; \
+    ownership_diff old.txt min_call_sites=1 \; \
+    ownership_diff old_cap.txt mono_cap=2 \
+    -- old.rs $rustflags
+
+# With no options, there is nothing to compare against.
+test ! -s old_same.txt
+
+# Only the dropped mono of `point_x` shows up.  The other two are renumbered, which doesn't count.
+diff -u new.txt old.txt
+
+# A permission that changes within a mono shows up as a pair of lines.
+sed -n '/^func ::read_x\[0\]:/,/^func/p' old_cap.txt > read_x.txt
+grep -qxF -- '- mono (""): [*mut READ Point] -> i32' read_x.txt
+grep -qxF -- '+ mono (""): [*mut MOVE Point] -> i32' read_x.txt
+
+# The command only writes the diff, so the source is unchanged
+cp old.rs old.rs.new
@@ -0,0 +1,18 @@
+#!/bin/sh
+set -e
+
+# work around System Integrity Protection on macOS
+if [ `uname` = 'Darwin' ]; then
+    export LD_LIBRARY_PATH=$not_LD_LIBRARY_PATH
+fi
+
+$refactor \
+    ownership_dump_dot old.dot \
+    -- old.rs $rustflags
+
+# `set_x` writes through the result of `point_x`, so it calls the `mut` mono
+grep -qF '"::set_x[0] #0" -> "::point_x[0] #1";' old.dot
+grep -qF '"::point_x[0] #1" [label="::point_x[0] #1 (\"mut\")\n[*mut WRITE Point] -> *mut WRITE i32"];' old.dot
+
+# The command only writes the DOT file, so the source is unchanged
+cp old.rs old.rs.new
@@ -0,0 +1,5 @@
+unsafe fn set(p: *mut i32) {
+    *p = 1;
+}
+
+fn main() {}
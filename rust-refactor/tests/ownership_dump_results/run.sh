@@ -0,0 +1,15 @@
+#!/bin/sh
+set -e
+
+# work around System Integrity Protection on macOS
+if [ `uname` = 'Darwin' ]; then
+    export LD_LIBRARY_PATH=$not_LD_LIBRARY_PATH
+fi
+
+$refactor \
+    test_analysis_ownership old.txt \
+    -- old.rs $rustflags
+
+# The command only writes the summary, so the source is unchanged
+diff -u new.txt old.txt
+cp old.rs old.rs.new
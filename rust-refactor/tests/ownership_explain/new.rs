@@ -0,0 +1,23 @@
+#![feature(custom_attribute, attr_literals)]
+
+use std::os::raw::c_void;
+
+extern "C" {
+    fn free(ptr: *mut c_void);
+}
+
+unsafe fn counter_bump(c: *mut i32) {
+    *c += 1;
+}
+
+unsafe fn counter_delete(c: *mut i32) {
+    free(c as *mut c_void);
+}
+
+// Only the call to `counter_delete` needs `MOVE`; the bump needs no more than `WRITE`.
+unsafe fn counter_finish(c: *mut i32) {
+    counter_bump(c);
+    counter_delete(c);
+}
+
+fn main() {}
@@ -0,0 +1,29 @@
+#!/bin/sh
+set -e
+
+# work around System Integrity Protection on macOS
+if [ `uname` = 'Darwin' ]; then
+    export LD_LIBRARY_PATH=$not_LD_LIBRARY_PATH
+fi
+
+$refactor \
+    ownership_explain old.txt \
+    -- old.rs $rustflags
+
+# `counter_finish` needs `MOVE` for the call to `counter_delete`, which in turn passes its
+# argument to `free`
+sed -n '/^func ::counter_finish/,/^func/p' old.txt >counter_finish.txt
+grep -q '^  _0 >= MOVE:$' counter_finish.txt
+grep -q ': counter_delete(c)$' counter_finish.txt
+grep -q ': free(c as \*mut c_void)$' counter_finish.txt
+if grep -q 'counter_bump' counter_finish.txt; then
+    exit 1
+fi
+
+# `counter_bump` only writes through its argument
+sed -n '/^func ::counter_bump/,/^func/p' old.txt >counter_bump.txt
+grep -q '^  _0 >= WRITE:$' counter_bump.txt
+grep -q ': \*c += 1$' counter_bump.txt
+
+# The command only writes the explanations, so the source is unchanged
+cp old.rs old.rs.new
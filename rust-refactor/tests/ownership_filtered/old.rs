@@ -0,0 +1,34 @@
+#![feature(custom_attribute, attr_literals)]
+
+use std::os::raw::c_void;
+
+extern "C" {
+    fn free(ptr: *mut c_void);
+}
+
+mod counter {
+    use super::*;
+
+    pub unsafe fn bump(c: *mut i32) {
+        *c += 1;
+    }
+
+    pub unsafe fn delete(c: *mut i32) {
+        free(c as *mut c_void);
+    }
+}
+
+// Only this module is analyzed, but its calls into `counter` still need `WRITE` and `MOVE`.
+mod app {
+    use counter;
+
+    pub unsafe fn update(c: *mut i32) {
+        counter::bump(c);
+    }
+
+    pub unsafe fn finish(c: *mut i32) {
+        counter::delete(c);
+    }
+}
+
+fn main() {}
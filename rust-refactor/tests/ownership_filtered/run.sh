@@ -0,0 +1,25 @@
+#!/bin/sh
+set -e
+
+# work around System Integrity Protection on macOS
+if [ `uname` = 'Darwin' ]; then
+    export LD_LIBRARY_PATH=$not_LD_LIBRARY_PATH
+fi
+
+$refactor \
+    test_analysis_ownership_filtered app old.txt \
+    -- old.rs $rustflags
+
+# Only the functions in `app` are in the results
+if grep -q '^func ::counter\|^func ::main' old.txt; then
+    exit 1
+fi
+
+# ... but the callees in `counter` still constrain them
+grep -qF 'mono #0 (""): [*mut WRITE i32] -> ()' old.txt
+grep -qF 'mono #0 (""): [*mut MOVE i32] -> ()' old.txt
+grep -qF 'call #0: "::counter[0]::bump[0]" #0 (excluded)' old.txt
+grep -qF 'call #0: "::counter[0]::delete[0]" #0 (excluded)' old.txt
+
+# The command only writes the summary, so the source is unchanged
+cp old.rs old.rs.new
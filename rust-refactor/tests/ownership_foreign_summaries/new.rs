@@ -0,0 +1,22 @@
+extern "C" {
+    fn strlen(s: *const u8) -> usize;
+    fn memcpy(dest: *mut u8, src: *const u8, n: usize) -> *mut u8;
+    fn fill(buf: *mut u8, n: usize);
+}
+
+// `strlen` only reads its argument.
+unsafe fn name_len(name: *mut u8) -> usize {
+    strlen(name)
+}
+
+// `memcpy` writes to `dest` and reads from `src`.
+unsafe fn copy_name(dest: *mut u8, src: *mut u8) {
+    memcpy(dest, src, strlen(src) + 1);
+}
+
+// `fill` has no built-in summary; the test provides one.
+unsafe fn reset(buf: *mut u8) {
+    fill(buf, 16);
+}
+
+fn main() {}
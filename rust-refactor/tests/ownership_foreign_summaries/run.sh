@@ -0,0 +1,23 @@
+#!/bin/sh
+set -e
+
+# work around System Integrity Protection on macOS
+if [ `uname` = 'Darwin' ]; then
+    export LD_LIBRARY_PATH=$not_LD_LIBRARY_PATH
+fi
+
+$refactor \
+    ownership_foreign_summary fill WRITE \; \
+    test_analysis_ownership old.txt \
+    -- old.rs $rustflags
+
+summary() {
+    sed -n "/^func ::$1\[0\]:/,/^func/p" old.txt
+}
+
+summary name_len | grep -qF 'mono #0 (""): [*mut READ u8] -> usize'
+summary copy_name | grep -qF 'mono #0 (""): [*mut WRITE u8, *mut READ u8] -> ()'
+summary reset | grep -qF 'mono #0 (""): [*mut WRITE u8] -> ()'
+
+# The command only writes the summary, so the source is unchanged
+cp old.rs old.rs.new
@@ -0,0 +1,38 @@
+#![feature(custom_attribute, attr_literals)]
+
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[ownership_constraints(le(_1, _0))]
+#[ownership_mono("take", MOVE, MOVE)]
+#[ownership_mono("mut", WRITE, WRITE)]
+#[ownership_mono("", READ, READ)]
+unsafe fn point_x(p: *mut Point) -> *mut i32 {
+    &mut (*p).x
+}
+
+// C calls these by their symbols, so each keeps a single mono and its raw pointers.  `point_x`
+// is still specialized for them.
+#[no_mangle]
+#[ownership_constraints(le(_1, _0))]
+#[ownership_mono("", MOVE, MOVE)]
+pub unsafe fn entry(p: *mut Point) -> *mut i32 {
+    point_x(p)
+}
+
+#[ownership_constraints(le(_1, _0))]
+#[ownership_mono("", MOVE, MOVE)]
+pub unsafe extern "C" fn entry_c(p: *mut Point) -> *mut i32 {
+    point_x(p)
+}
+
+#[ownership_constraints()]
+#[ownership_mono("", READ)]
+unsafe fn read_x(p: *mut Point) -> i32 {
+    *point_x(p)
+}
+
+#[ownership_constraints()]
+fn main() {}
@@ -0,0 +1,27 @@
+#![feature(custom_attribute, attr_literals)]
+
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+unsafe fn point_x(p: *mut Point) -> *mut i32 {
+    &mut (*p).x
+}
+
+// C calls these by their symbols, so each keeps a single mono and its raw pointers.  `point_x`
+// is still specialized for them.
+#[no_mangle]
+pub unsafe fn entry(p: *mut Point) -> *mut i32 {
+    point_x(p)
+}
+
+pub unsafe extern "C" fn entry_c(p: *mut Point) -> *mut i32 {
+    point_x(p)
+}
+
+unsafe fn read_x(p: *mut Point) -> i32 {
+    *point_x(p)
+}
+
+fn main() {}
@@ -0,0 +1,29 @@
+#![feature(custom_attribute, attr_literals)]
+
+#[ownership_constraints()]
+#[ownership_mono("take", MOVE)]
+#[ownership_mono("mut", WRITE)]
+#[ownership_mono("", READ)]
+unsafe fn read_first(p: *mut i32) -> i32 {
+    *p
+}
+
+#[ownership_constraints(le(WRITE, _0))]
+#[ownership_mono("", WRITE)]
+unsafe fn clear_first(p: *mut i32) -> i32 {
+    *p = 0;
+    0
+}
+
+static HANDLERS: [unsafe fn(*mut i32) -> i32; 2] = [read_first, clear_first];
+
+// The callee is only known at runtime, so `p` is assumed to be passed to something that takes
+// ownership of it.
+#[ownership_constraints(le(MOVE, _0))]
+#[ownership_mono("", MOVE)]
+unsafe fn dispatch(which: usize, p: *mut i32) -> i32 {
+    HANDLERS[which](p)
+}
+
+#[ownership_constraints()]
+fn main() {}
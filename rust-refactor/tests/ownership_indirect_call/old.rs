@@ -0,0 +1,20 @@
+#![feature(custom_attribute, attr_literals)]
+
+unsafe fn read_first(p: *mut i32) -> i32 {
+    *p
+}
+
+unsafe fn clear_first(p: *mut i32) -> i32 {
+    *p = 0;
+    0
+}
+
+static HANDLERS: [unsafe fn(*mut i32) -> i32; 2] = [read_first, clear_first];
+
+// The callee is only known at runtime, so `p` is assumed to be passed to something that takes
+// ownership of it.
+unsafe fn dispatch(which: usize, p: *mut i32) -> i32 {
+    HANDLERS[which](p)
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+#![feature(custom_attribute, attr_literals)]
+
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+unsafe fn point_x(p: *mut Point) -> *mut i32 {
+    &mut (*p).x
+}
+
+unsafe fn set_x(p: *mut Point, v: i32) {
+    *point_x(p) = v;
+}
+
+fn main() {}
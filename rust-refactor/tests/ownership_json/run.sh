@@ -0,0 +1,27 @@
+#!/bin/sh
+set -e
+
+# work around System Integrity Protection on macOS
+if [ `uname` = 'Darwin' ]; then
+    export LD_LIBRARY_PATH=$not_LD_LIBRARY_PATH
+fi
+
+$refactor \
+    select box 'crate;
+        desc(foreign_item && fn && name("malloc|free|realloc"));
+        desc(match_ty(*mut __t));' \; \
+    ownership_dump_json old.json \
+    -- old.rs $rustflags
+
+# The command only writes the JSON file, so the source is unchanged
+diff -u new.json old.json
+
+# Reading the file back gives the same results as a fresh analysis
+$refactor \
+    select box 'crate;
+        desc(foreign_item && fn && name("malloc|free|realloc"));
+        desc(match_ty(*mut __t));' \; \
+    test_analysis_ownership_json old.json \
+    -- old.rs $rustflags
+
+cp old.rs old.rs.new
@@ -0,0 +1,14 @@
+#!/bin/sh
+set -e
+
+# work around System Integrity Protection on macOS
+if [ `uname` = 'Darwin' ]; then
+    export LD_LIBRARY_PATH=$not_LD_LIBRARY_PATH
+fi
+
+# Panics if some random constraint set and its minimized form have different solutions
+$refactor \
+    test_constraint_minimize 2000 \
+    -- old.rs $rustflags
+
+cp old.rs old.rs.new
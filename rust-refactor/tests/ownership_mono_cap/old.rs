@@ -0,0 +1,19 @@
+// Each of the three outputs can independently be MOVE, WRITE, or READ, so `spread` has 27 monos.
+unsafe fn spread(n: i32,
+                 a: *mut *mut i32, src_a: *mut i32,
+                 b: *mut *mut i32, src_b: *mut i32,
+                 c: *mut *mut i32, src_c: *mut i32) {
+    *a = src_a;
+    *b = src_b;
+    *c = src_c;
+    if n > 0 {
+        spread(n - 1, a, src_a, b, src_b, c, src_c);
+    }
+}
+
+// Only 3 monos, which fit under the cap.
+unsafe fn store(a: *mut *mut i32, src_a: *mut i32) {
+    *a = src_a;
+}
+
+fn main() {}
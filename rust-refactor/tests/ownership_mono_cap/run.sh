@@ -0,0 +1,27 @@
+#!/bin/sh
+set -e
+
+# work around System Integrity Protection on macOS
+if [ `uname` = 'Darwin' ]; then
+    export LD_LIBRARY_PATH=$not_LD_LIBRARY_PATH
+fi
+
+$refactor \
+    test_analysis_ownership old.txt mono_cap=8 \
+    -- old.rs $rustflags
+
+summary() {
+    sed -n "/^func ::$1\[0\]:/,/^func/p" old.txt
+}
+
+# `spread` is over the cap, so it gets only its most permissive mono.
+summary spread | grep -qF 'monos truncated'
+test `summary spread | grep -c '^  mono #'` -eq 1
+
+if summary store | grep -qF 'monos truncated'; then
+    exit 1
+fi
+test `summary store | grep -c '^  mono #'` -eq 3
+
+# The command only writes the summary, so the source is unchanged
+cp old.rs old.rs.new
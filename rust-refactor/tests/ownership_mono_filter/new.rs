@@ -0,0 +1,11 @@
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+// Nothing calls `point_x`, so its suspicious `MOVE -> MOVE` mono has no call sites backing it.
+unsafe fn point_x(p: *mut Point) -> *mut i32 {
+    &mut (*p).x
+}
+
+fn main() {}
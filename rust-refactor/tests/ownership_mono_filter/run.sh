@@ -0,0 +1,28 @@
+#!/bin/sh
+set -e
+
+# work around System Integrity Protection on macOS
+if [ `uname` = 'Darwin' ]; then
+    export LD_LIBRARY_PATH=$not_LD_LIBRARY_PATH
+fi
+
+$refactor \
+    test_analysis_ownership old_all.txt \; \
+    test_analysis_ownership old_filtered.txt min_call_sites=1 \; \
+    test_analysis_ownership old_capped.txt max_monos=1 \
+    -- old.rs $rustflags
+
+# The default config keeps every mono.  Requiring a call site drops the suspicious `take` mono,
+# and capping the count keeps only the least permissive one.
+diff -u new_all.txt old_all.txt
+diff -u new_filtered.txt old_filtered.txt
+diff -u new_capped.txt old_capped.txt
+
+# Called monos are never dropped, so requiring more than one call site is an error rather than
+# the same as requiring one.
+if $refactor test_analysis_ownership old_two.txt min_call_sites=2 \
+        -- old.rs $rustflags 2>old_two.log; then
+    exit 1
+fi
+grep -qF '`min_call_sites` must be 0 or 1' old_two.log
+cp old.rs old.rs.new
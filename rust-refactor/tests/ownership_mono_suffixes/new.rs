@@ -0,0 +1,35 @@
+#![feature(custom_attribute, attr_literals)]
+
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+// Callers may read `x` through the result, write it, or take ownership of it, so each mono is
+// named after the permission it hands back.
+#[ownership_constraints(le(_1, _0))]
+#[ownership_mono("take", MOVE, MOVE)]
+#[ownership_mono("mut", WRITE, WRITE)]
+#[ownership_mono("", READ, READ)]
+unsafe fn point_x(p: *mut Point) -> *mut i32 {
+    &mut (*p).x
+}
+
+// Both fields of the result vary independently, so the monos are named by the permission of each,
+// first `x` then `y`.
+#[ownership_constraints(le(_1, _0), le(_2, _0))]
+#[ownership_mono("take_take", MOVE, MOVE, MOVE)]
+#[ownership_mono("take_mut", MOVE, MOVE, WRITE)]
+#[ownership_mono("take_read", MOVE, MOVE, READ)]
+#[ownership_mono("mut_take", MOVE, WRITE, MOVE)]
+#[ownership_mono("mut_mut", WRITE, WRITE, WRITE)]
+#[ownership_mono("mut_read", WRITE, WRITE, READ)]
+#[ownership_mono("read_take", MOVE, READ, MOVE)]
+#[ownership_mono("read_mut", WRITE, READ, WRITE)]
+#[ownership_mono("read_read", READ, READ, READ)]
+unsafe fn point_fields(p: *mut Point) -> (*mut i32, *mut i32) {
+    (&mut (*p).x, &mut (*p).y)
+}
+
+#[ownership_constraints()]
+fn main() {}
@@ -0,0 +1,20 @@
+#![feature(custom_attribute, attr_literals)]
+
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+// Callers may read `x` through the result, write it, or take ownership of it, so each mono is
+// named after the permission it hands back.
+unsafe fn point_x(p: *mut Point) -> *mut i32 {
+    &mut (*p).x
+}
+
+// Both fields of the result vary independently, so the monos are named by the permission of each,
+// first `x` then `y`.
+unsafe fn point_fields(p: *mut Point) -> (*mut i32, *mut i32) {
+    (&mut (*p).x, &mut (*p).y)
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+#![feature(custom_attribute, attr_literals)]
+
+use std::os::raw::c_void;
+
+extern "C" {
+    fn free(ptr: *mut c_void);
+}
+
+// Each level of indirection gets its own variable: the array `argv` points to is freed, so `_0`
+// must be MOVE, while the strings in it (`_1`) are only read.
+unsafe fn free_args(argv: *mut *mut i8) -> i8 {
+    let first = **argv;
+    free(argv as *mut c_void);
+    first
+}
+
+fn main() {}
@@ -0,0 +1,30 @@
+// Not the raw pointer method, just a function that happens to have the same name.
+fn is_null(p: *mut i32) -> bool {
+    p as usize == 1
+}
+
+unsafe fn get_unless_one(p: *mut i32) -> i32 {
+    if is_null(p) {
+        return 0;
+    }
+    *p
+}
+
+// The raw pointer method does make `p` nullable.
+unsafe fn get_checked(p: *mut i32) -> i32 {
+    if p.is_null() {
+        return 0;
+    }
+    *p
+}
+
+// Only zero is null.  Other constants cast to pointers are sentinels.
+unsafe fn mark(pp: *mut *mut i32) {
+    *pp = 1 as *mut i32;
+}
+
+unsafe fn clear(pp: *mut *mut i32) {
+    *pp = 0 as *mut i32;
+}
+
+fn main() {}
@@ -0,0 +1,28 @@
+#!/bin/sh
+set -e
+
+# work around System Integrity Protection on macOS
+if [ `uname` = 'Darwin' ]; then
+    export LD_LIBRARY_PATH=$not_LD_LIBRARY_PATH
+fi
+
+$refactor \
+    test_analysis_ownership old.txt \
+    -- old.rs $rustflags
+
+summary() {
+    sed -n "/^func ::$1\[0\]:/,/^func/p" old.txt
+}
+
+summary get_checked | grep -qxF '  nullable: [0]'
+summary clear | grep -qxF '  nullable: [1]'
+
+for f in is_null get_unless_one mark; do
+    if summary $f | grep -qF 'nullable'; then
+        echo "$f should have no nullable pointers"
+        exit 1
+    fi
+done
+
+# The command only writes the summary, so the source is unchanged
+cp old.rs old.rs.new
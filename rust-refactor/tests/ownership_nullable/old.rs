@@ -0,0 +1,15 @@
+// `p` is compared against null before it is dereferenced, so it's nullable even though the
+// dereference itself is unconditional on the remaining path.
+unsafe fn get_or_zero(p: *mut i32) -> i32 {
+    if p == 0 as *mut i32 {
+        return 0;
+    }
+    *p
+}
+
+// Only the inner pointer (`_1`) is assigned null.
+unsafe fn clear(pp: *mut *mut i32) {
+    *pp = 0 as *mut i32;
+}
+
+fn main() {}
@@ -0,0 +1,30 @@
+#![feature(custom_attribute, attr_literals)]
+
+// Nothing in the body needs more than `READ`, and neither does the caller.
+#[ownership_constraints()]
+#[ownership_mono("", READ)]
+unsafe fn release_unpinned(p: *mut i32) {
+}
+
+#[ownership_constraints()]
+#[ownership_mono("", READ)]
+unsafe fn drop_counter_unpinned(c: *mut i32) {
+    release_unpinned(c);
+}
+
+// The real release happens out of the analysis' sight, so the argument is pinned to `MOVE`,
+// and the caller has to hand over ownership too.
+#[ownership_pin(0, MOVE)]
+#[ownership_constraints(le(MOVE, _0))]
+#[ownership_mono("", MOVE)]
+unsafe fn release(p: *mut i32) {
+}
+
+#[ownership_constraints(le(MOVE, _0))]
+#[ownership_mono("", MOVE)]
+unsafe fn drop_counter(c: *mut i32) {
+    release(c);
+}
+
+#[ownership_constraints()]
+fn main() {}
@@ -0,0 +1,21 @@
+#![feature(custom_attribute, attr_literals)]
+
+// Nothing in the body needs more than `READ`, and neither does the caller.
+unsafe fn release_unpinned(p: *mut i32) {
+}
+
+unsafe fn drop_counter_unpinned(c: *mut i32) {
+    release_unpinned(c);
+}
+
+// The real release happens out of the analysis' sight, so the argument is pinned to `MOVE`,
+// and the caller has to hand over ownership too.
+#[ownership_pin(0, MOVE)]
+unsafe fn release(p: *mut i32) {
+}
+
+unsafe fn drop_counter(c: *mut i32) {
+    release(c);
+}
+
+fn main() {}
@@ -0,0 +1,22 @@
+#![feature(custom_attribute, attr_literals)]
+
+// Without `ownership_pure`, the result can be used for writing or freeing, so the argument
+// needs matching permissions.
+#[ownership_constraints(le(_1, _0))]
+#[ownership_mono("take", MOVE, MOVE)]
+#[ownership_mono("mut", WRITE, WRITE)]
+#[ownership_mono("", READ, READ)]
+unsafe fn element_ptr(arr: *mut i32, idx: isize) -> *mut i32 {
+    arr.offset(idx)
+}
+
+// The attribute rules out writes through `arr`, leaving only the `READ` signature.
+#[ownership_pure]
+#[ownership_constraints(le(_0, READ), le(_1, _0))]
+#[ownership_mono("", READ, READ)]
+unsafe fn element_ptr_pure(arr: *mut i32, idx: isize) -> *mut i32 {
+    arr.offset(idx)
+}
+
+#[ownership_constraints()]
+fn main() {}
@@ -0,0 +1,15 @@
+#![feature(custom_attribute, attr_literals)]
+
+// Without `ownership_pure`, the result can be used for writing or freeing, so the argument
+// needs matching permissions.
+unsafe fn element_ptr(arr: *mut i32, idx: isize) -> *mut i32 {
+    arr.offset(idx)
+}
+
+// The attribute rules out writes through `arr`, leaving only the `READ` signature.
+#[ownership_pure]
+unsafe fn element_ptr_pure(arr: *mut i32, idx: isize) -> *mut i32 {
+    arr.offset(idx)
+}
+
+fn main() {}
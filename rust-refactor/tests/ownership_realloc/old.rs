@@ -0,0 +1,35 @@
+#![feature(custom_attribute, attr_literals)]
+
+use std::mem;
+use std::os::raw::c_void;
+
+extern "C" {
+    fn malloc(size: usize) -> *mut c_void;
+    fn realloc(ptr: *mut c_void, size: usize) -> *mut c_void;
+    fn free(ptr: *mut c_void);
+}
+
+// Consumes the old buffer and hands out the new one, with no `box` marks needed.
+unsafe fn buffer_grow(buf: *mut i32, len: usize) -> *mut i32 {
+    let new_buf = realloc(buf as *mut c_void, 2 * len * mem::size_of::<i32>()) as *mut i32;
+    *new_buf.offset(len as isize) = 0;
+    new_buf
+}
+
+// Reads through the old pointer, which `realloc` may have freed.
+unsafe fn buffer_grow_stale(buf: *mut i32, len: usize) -> i32 {
+    let new_buf = realloc(buf as *mut c_void, 2 * len * mem::size_of::<i32>()) as *mut i32;
+    let first = *buf;
+    free(new_buf as *mut c_void);
+    first
+}
+
+unsafe fn buffer_run() {
+    let mut buf = malloc(mem::size_of::<i32>()) as *mut i32;
+    *buf = 1;
+    buf = buffer_grow(buf, 1);
+    *buf.offset(1) = 2;
+    free(buf as *mut c_void);
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+#!/bin/sh
+
+# work around System Integrity Protection on macOS
+if [ `uname` = 'Darwin' ]; then
+    export LD_LIBRARY_PATH=$not_LD_LIBRARY_PATH
+fi
+
+# No `box` marks: the analysis knows `malloc`, `realloc`, and `free` already.
+$refactor \
+    select ann 'crate; desc(fn || field);' \; \
+    ownership_annotate ann \
+    -- old.rs $rustflags 2>old.rs.log
+
+# Only `buffer_grow_stale` uses a pointer after passing it to `realloc`.
+[ `grep -c 'after being passed to .realloc.' old.rs.log` = 1 ]
@@ -0,0 +1,49 @@
+use std::mem::size_of;
+use std::os::raw::c_void;
+
+extern "C" {
+    fn memset(s: *mut c_void, c: i32, n: usize) -> *mut c_void;
+}
+
+struct Counter {
+    count: i32,
+    step: i32,
+}
+
+// Mutates the counter in place, and only reads `times`.
+unsafe fn advance(c: *mut Counter, times: *const i32) {
+    (*c).count += (*c).step * *times;
+}
+
+unsafe fn get_count(c: *mut Counter) -> i32 {
+    (*c).count
+}
+
+unsafe fn advance_twice(c: *mut Counter) -> i32 {
+    let two = 2;
+    advance(c, &two);
+    get_count(c)
+}
+
+// `memset` still takes a pointer.
+unsafe fn clear(c: *mut Counter) {
+    memset(c as *mut c_void, 0, size_of::<Counter>());
+}
+
+// A caller could pass the same counter twice, so only `a` becomes `&mut`.
+unsafe fn swap_counts(a: *mut Counter, b: *mut Counter) {
+    let tmp = (*a).count;
+    (*a).count = (*b).count;
+    (*b).count = tmp;
+}
+
+fn main() {
+    let mut c = Counter { count: 0, step: 1 };
+    let mut d = Counter { count: 5, step: 1 };
+    unsafe {
+        advance_twice(&mut c);
+        swap_counts(&mut c, &mut d);
+        get_count(&mut c);
+        clear(&mut d);
+    }
+}
@@ -0,0 +1,36 @@
+#![feature(custom_attribute, attr_literals)]
+
+// Without `ownership_restrict_return`, the result may only ever be read, so there is a `READ`
+// variant alongside the `WRITE` and `MOVE` ones.
+#[ownership_constraints(le(_1, _0))]
+#[ownership_mono("take", MOVE, MOVE)]
+#[ownership_mono("mut", WRITE, WRITE)]
+#[ownership_mono("", READ, READ)]
+unsafe fn first_slot(arr: *mut i32) -> *mut i32 {
+    arr
+}
+
+// The result doesn't borrow from `arr`, but what the caller does with it is still inferred, so
+// the variants are the same.
+#[ownership_restrict_return]
+#[ownership_constraints(le(_1, _0))]
+#[ownership_mono("take", MOVE, MOVE)]
+#[ownership_mono("mut", WRITE, WRITE)]
+#[ownership_mono("", READ, READ)]
+unsafe fn first_slot_restrict(arr: *mut i32) -> *mut i32 {
+    arr
+}
+
+// `restrict` says nothing about data behind a `const` pointer, so the result still borrows from
+// `arr`.
+#[ownership_restrict_return]
+#[ownership_constraints(le(_1, _0))]
+#[ownership_mono("take", MOVE, MOVE)]
+#[ownership_mono("mut", WRITE, WRITE)]
+#[ownership_mono("", READ, READ)]
+unsafe fn first_slot_const(arr: *const i32) -> *const i32 {
+    arr
+}
+
+#[ownership_constraints()]
+fn main() {}
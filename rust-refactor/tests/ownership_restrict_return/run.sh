@@ -0,0 +1,29 @@
+#!/bin/sh
+set -e
+
+# work around System Integrity Protection on macOS
+if [ `uname` = 'Darwin' ]; then
+    export LD_LIBRARY_PATH=$not_LD_LIBRARY_PATH
+fi
+
+$refactor \
+    test_analysis_ownership old.txt \
+    -- old.rs $rustflags
+
+summary() {
+    sed -n "/^func ::$1\[0\]:/,/^func/p" old.txt
+}
+
+summary first_slot | grep -qF 'return borrows from: [0]'
+summary first_slot_const | grep -qF 'return borrows from: [0]'
+if summary first_slot_restrict | grep -qF 'return borrows from'; then
+    exit 1
+fi
+
+$refactor \
+    select box 'crate;
+        desc(foreign_item && fn && name("malloc|free|realloc"));
+        desc(match_ty(*mut __t));' \; \
+    select ann 'crate; desc(fn || field);' \; \
+    ownership_annotate ann \
+    -- old.rs $rustflags
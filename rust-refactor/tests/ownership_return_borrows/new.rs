@@ -0,0 +1,25 @@
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+unsafe fn point_x(p: *mut Point) -> *mut i32 {
+    &mut (*p).x
+}
+
+// Borrows from `p` through the call to `point_x`.
+unsafe fn x_of(_n: i32, p: *mut Point) -> *mut i32 {
+    point_x(p)
+}
+
+// Borrows from either `a` or `b`, so the result can't outlive either of them.
+unsafe fn pick(c: bool, a: *mut i32, b: *mut i32) -> *mut i32 {
+    if c { a } else { b }
+}
+
+// The result doesn't come from `p` at all.
+unsafe fn null_like(p: *mut i32) -> *mut i32 {
+    0 as *mut i32
+}
+
+fn main() {}
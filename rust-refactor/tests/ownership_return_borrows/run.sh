@@ -0,0 +1,25 @@
+#!/bin/sh
+set -e
+
+# work around System Integrity Protection on macOS
+if [ `uname` = 'Darwin' ]; then
+    export LD_LIBRARY_PATH=$not_LD_LIBRARY_PATH
+fi
+
+$refactor \
+    test_analysis_ownership old.txt \
+    -- old.rs $rustflags
+
+summary() {
+    sed -n "/^func ::$1\[0\]:/,/^func/p" old.txt
+}
+
+summary point_x | grep -qF 'return borrows from: [0]'
+summary x_of | grep -qF 'return borrows from: [1]'
+summary pick | grep -qF 'return borrows from: [1, 2]'
+if summary null_like | grep -qF 'return borrows from'; then
+    exit 1
+fi
+
+# The command only writes the summary, so the source is unchanged
+cp old.rs old.rs.new
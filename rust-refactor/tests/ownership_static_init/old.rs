@@ -0,0 +1,24 @@
+#![feature(custom_attribute, attr_literals)]
+
+use std::os::raw::c_void;
+
+extern "C" {
+    fn free(ptr: *mut c_void);
+}
+
+static mut DATA: *mut i32 = 0 as *mut i32;
+
+// The initializer ties the pointee of `DATA_REF` to `DATA` itself, so freeing through `DATA_REF`
+// makes `DATA` an owning pointer.
+static mut DATA_REF: *mut *mut i32 = unsafe { &DATA as *const *mut i32 as *mut *mut i32 };
+
+unsafe fn release() {
+    free(*DATA_REF as *mut c_void);
+}
+
+// Storing into `DATA` hands over ownership of `p`.
+unsafe fn store(p: *mut i32) {
+    DATA = p;
+}
+
+fn main() {}
@@ -0,0 +1,24 @@
+struct Handler {
+    id: i32,
+}
+
+static mut HANDLERS: [*mut Handler; 4] = [0 as *mut Handler; 4];
+static mut NUM_HANDLERS: usize = 0;
+
+// The registry keeps the handler after `register` returns, so the caller hands over ownership.
+unsafe fn register(h: *mut Handler) {
+    HANDLERS[NUM_HANDLERS] = h;
+    NUM_HANDLERS += 1;
+}
+
+// Handlers read back out of the registry get the registry's permission.
+unsafe fn lookup(i: usize) -> *mut Handler {
+    HANDLERS[i]
+}
+
+// Writing through a pointer stored in the registry doesn't store into the registry itself.
+unsafe fn set_id(i: usize, id: i32) {
+    (*HANDLERS[i]).id = id;
+}
+
+fn main() {}
@@ -0,0 +1,18 @@
+#!/bin/sh
+set -e
+
+# work around System Integrity Protection on macOS
+if [ `uname` = 'Darwin' ]; then
+    export LD_LIBRARY_PATH=$not_LD_LIBRARY_PATH
+fi
+
+$refactor \
+    test_analysis_ownership old.txt \
+    -- old.rs $rustflags
+
+grep -qF 'mono #0 (""): [*mut MOVE Handler] -> ()' old.txt
+grep -qF 'mono #0 (""): [usize] -> *mut MOVE Handler' old.txt
+grep -qF 'mono #0 (""): [usize, i32] -> ()' old.txt
+
+# The command only writes the summary, so the source is unchanged
+cp old.rs old.rs.new
@@ -0,0 +1,51 @@
+pub struct List {
+    pub data: [i32; 8],
+    pub len: i32,
+}
+impl List {
+    // Writes through `l`, so it becomes `&mut self`.
+    pub unsafe fn push(&mut self, x: i32) {
+        let l: *mut List = self;
+        (*l).data[(*l).len as usize] = x;
+        (*l).len += 1;
+    }
+    // Only reads through `l`, so it becomes `&self`.
+    pub unsafe fn len(&self) -> i32 {
+        let l: *mut List = self as *const List as *mut List;
+        (*l).len
+    }
+    pub unsafe fn sum(&self) -> i32 {
+        let l: *mut List = self as *const List as *mut List;
+        let mut sum = 0;
+        let mut i = 0;
+        while i < (*l).len() {
+            sum += (*l).data[i as usize];
+            i += 1;
+        }
+        sum
+    }
+}
+
+// Monomorphizations give `l` READ or WRITE depending on the use of the result, so it stays a
+// function.
+pub unsafe fn list_first(l: *mut List) -> *mut i32 {
+    &mut (*l).data[0]
+}
+
+// Used as a value, so it stays a function.
+pub unsafe fn list_clear(l: *mut List) {
+    (*l).len = 0;
+}
+
+fn main() {
+    let mut list = List { data: [0; 8], len: 0 };
+    unsafe {
+        list.push(1);
+        list.push(2);
+        assert_eq!(list.sum(), 3);
+        *list_first(&mut list) = 5;
+        assert_eq!(*list_first(&mut list), 5);
+        let clear: unsafe fn(*mut List) = list_clear;
+        clear(&mut list);
+    }
+}
@@ -0,0 +1,49 @@
+pub struct List {
+    pub data: [i32; 8],
+    pub len: i32,
+}
+
+// Writes through `l`, so it becomes `&mut self`.
+pub unsafe fn list_push(l: *mut List, x: i32) {
+    (*l).data[(*l).len as usize] = x;
+    (*l).len += 1;
+}
+
+// Only reads through `l`, so it becomes `&self`.
+pub unsafe fn list_len(l: *mut List) -> i32 {
+    (*l).len
+}
+
+pub unsafe fn list_sum(l: *mut List) -> i32 {
+    let mut sum = 0;
+    let mut i = 0;
+    while i < list_len(l) {
+        sum += (*l).data[i as usize];
+        i += 1;
+    }
+    sum
+}
+
+// Monomorphizations give `l` READ or WRITE depending on the use of the result, so it stays a
+// function.
+pub unsafe fn list_first(l: *mut List) -> *mut i32 {
+    &mut (*l).data[0]
+}
+
+// Used as a value, so it stays a function.
+pub unsafe fn list_clear(l: *mut List) {
+    (*l).len = 0;
+}
+
+fn main() {
+    let mut list = List { data: [0; 8], len: 0 };
+    unsafe {
+        list_push(&mut list, 1);
+        list_push(&mut list, 2);
+        assert_eq!(list_sum(&mut list), 3);
+        *list_first(&mut list) = 5;
+        assert_eq!(*list_first(&mut list), 5);
+        let clear: unsafe fn(*mut List) = list_clear;
+        clear(&mut list);
+    }
+}
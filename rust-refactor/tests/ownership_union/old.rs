@@ -0,0 +1,22 @@
+use std::os::raw::c_void;
+
+extern "C" {
+    fn free(ptr: *mut c_void);
+}
+
+// `p` and `q` overlap, so freeing through `q` makes `p` owning too.
+union Slot {
+    p: *mut i32,
+    q: *mut c_void,
+    len: usize,
+}
+
+unsafe fn slot_get(s: *mut Slot) -> i32 {
+    *(*s).p
+}
+
+unsafe fn slot_release(s: *mut Slot) {
+    free((*s).q);
+}
+
+fn main() {}
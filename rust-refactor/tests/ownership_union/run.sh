@@ -0,0 +1,18 @@
+#!/bin/sh
+set -e
+
+# work around System Integrity Protection on macOS
+if [ `uname` = 'Darwin' ]; then
+    export LD_LIBRARY_PATH=$not_LD_LIBRARY_PATH
+fi
+
+$refactor \
+    test_analysis_ownership old.txt \
+    -- old.rs $rustflags
+
+# Both pointer fields of the union get the permission `free` needs
+grep -qE '^static ::Slot(\[0\])?::p(\[0\])? :: \*mut MOVE i32$' old.txt
+grep -qE '^static ::Slot(\[0\])?::q(\[0\])? :: \*mut MOVE .*c_void$' old.txt
+
+# The command only writes the summary, so the source is unchanged
+cp old.rs old.rs.new
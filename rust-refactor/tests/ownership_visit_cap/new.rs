@@ -0,0 +1,17 @@
+// `ping` writes through `p` and `pong` through `q`, and each passes both on to the other.
+// Whichever is processed first misses the other's write, so it needs a second visit.
+unsafe fn ping(p: *mut i32, q: *mut i32, n: i32) {
+    *p = n;
+    if n > 0 {
+        pong(p, q, n - 1);
+    }
+}
+
+unsafe fn pong(p: *mut i32, q: *mut i32, n: i32) {
+    *q = n;
+    if n > 0 {
+        ping(p, q, n - 1);
+    }
+}
+
+fn main() {}
@@ -0,0 +1,32 @@
+unsafe fn sum(arr: &[i32]) -> i32 {
+    let len: usize = arr.len() as usize;
+    let arr: *const i32 = arr.as_ptr() as *const i32;
+    let mut total = 0;
+    let mut i = 0;
+    while i < len {
+        total += *arr.offset(i as isize);
+        i += 1;
+    }
+    total
+}
+
+unsafe fn fill(arr: &mut [i32], value: i32) {
+    let len: usize = arr.len() as usize;
+    let arr: *mut i32 = arr.as_mut_ptr() as *mut i32;
+    let mut i = 0;
+    while i < len {
+        *arr.offset(i as isize) = value;
+        i += 1;
+    }
+}
+
+fn main() {
+    let mut values = [0; 4];
+    let mut rest = [0; 4];
+    unsafe {
+        fill(&mut values[..], 3);
+        fill(&mut values[..2 as usize], 5);
+        fill(::std::slice::from_raw_parts_mut(rest.as_mut_ptr().offset(1), 3 as usize), 7);
+        let total = sum(&values[..]);
+    }
+}
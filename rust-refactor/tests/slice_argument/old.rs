@@ -0,0 +1,28 @@
+unsafe fn sum(arr: *const i32, len: usize) -> i32 {
+    let mut total = 0;
+    let mut i = 0;
+    while i < len {
+        total += *arr.offset(i as isize);
+        i += 1;
+    }
+    total
+}
+
+unsafe fn fill(arr: *mut i32, len: usize, value: i32) {
+    let mut i = 0;
+    while i < len {
+        *arr.offset(i as isize) = value;
+        i += 1;
+    }
+}
+
+fn main() {
+    let mut values = [0; 4];
+    let mut rest = [0; 4];
+    unsafe {
+        fill(values.as_mut_ptr(), values.len() as usize, 3);
+        fill(values.as_mut_ptr(), 2, 5);
+        fill(rest.as_mut_ptr().offset(1), 3, 7);
+        let total = sum(values.as_ptr(), values.len() as usize);
+    }
+}
@@ -0,0 +1,12 @@
+#!/bin/sh
+
+# work around System Integrity Protection on macOS
+if [ `uname` = 'Darwin' ]; then
+    export LD_LIBRARY_PATH=$not_LD_LIBRARY_PATH
+fi
+
+$refactor \
+    ownership_mark_pointers \; \
+    select target 'crate; desc(arg && any_child(match_pat(len)));' \; \
+    slice_argument \
+    -- old.rs $rustflags
@@ -25,8 +25,8 @@ class RustFile:
     def __init__(self, path: str) -> None:
         self.path = path
 
-    def compile(self, crate_type: CrateType,
-                save_output: bool = False) -> Optional[LocalCommand]:
+    def compile(self, crate_type: CrateType, save_output: bool = False,
+                test: bool = False) -> Optional[LocalCommand]:
         current_dir, _ = os.path.split(self.path)
         extensionless_file, _ = os.path.splitext(self.path)
 
@@ -37,10 +37,15 @@ class RustFile:
             current_dir,
         ]
 
+        # build a runner for the `#[test]` functions instead
+        if test:
+            args.append("--test")
+            extensionless_file += "_tests"
+
         if save_output:
             args.append('-o')
 
-            if crate_type == CrateType.Binary:
+            if crate_type == CrateType.Binary or test:
                 args.append(extensionless_file)
             else:
                 # REVIEW: Not sure if ext is correct
@@ -59,7 +64,7 @@ class RustFile:
             raise NonZeroReturn(stderr)
 
         if save_output:
-            if crate_type == CrateType.Binary:
+            if crate_type == CrateType.Binary or test:
                 return get_cmd_or_die(extensionless_file)
             # TODO: Support saving lib file
 
@@ -0,0 +1,140 @@
+#!/usr/bin/env python3
+# -*- coding: utf-8 -*-
+
+import os
+import json
+import shutil
+import tempfile
+import unittest
+from unittest import mock
+
+import transpile
+from translation_cache import hash_compile_command
+
+
+class FakeExporter:
+    """stands in for ast-exporter, writing an empty .cbor file."""
+
+    def __init__(self, path: str) -> None:
+        self.executable = path
+        self.exports = 0
+
+    def export(self, ast_expo, cc_db_path, sys_incl_dirs, **cmd) -> str:
+        self.exports += 1
+        cbor_file = os.path.join(cmd['directory'], cmd['file'] + ".cbor")
+        open(cbor_file, "w").close()
+        return cbor_file
+
+
+class FakeImporter:
+    """stands in for ast-importer, numbering the translations it makes."""
+
+    def __init__(self, path: str) -> None:
+        self.executable = path
+        self.imports = 0
+
+    def __getitem__(self, args):
+        return self
+
+    def run(self):
+        self.imports += 1
+        return 0, "// translation #{}\n".format(self.imports), ""
+
+
+@unittest.skipUnless(shutil.which("cc"), "needs a C compiler")
+class TranslationCacheTests(unittest.TestCase):
+    def setUp(self):
+        self.dir = tempfile.mkdtemp()
+        self.write_file("tu.c", '#include "tu.h"\nint f(void) { return N; }\n')
+        self.write_file("tu.h", "#define N 1\n")
+        self.write_file("ast-exporter", "v1")
+        self.write_file("ast-importer", "v1")
+        self.cmd = {
+            'directory': self.dir,
+            'file': "tu.c",
+            'arguments': ["cc", "-c", "-o", "tu.o", "tu.c"],
+        }
+        self.write_file("compile_commands.json", json.dumps([self.cmd]))
+        self.exporter = FakeExporter(os.path.join(self.dir, "ast-exporter"))
+        self.importer = FakeImporter(os.path.join(self.dir, "ast-importer"))
+
+    def tearDown(self):
+        shutil.rmtree(self.dir)
+
+    def write_file(self, name, contents):
+        with open(os.path.join(self.dir, name), "w") as fh:
+            fh.write(contents)
+
+    def read_file(self, name):
+        with open(os.path.join(self.dir, name)) as fh:
+            return fh.read()
+
+    def transpile(self, extra_impo_args=[], use_cache=True):
+        """run the transpiler over the compile commands, with fake tools."""
+        tools = {transpile.c.AST_EXPO: self.exporter,
+                 transpile.c.AST_IMPO: self.importer}
+        with mock.patch.object(transpile, 'get_cmd_or_die', tools.get), \
+                mock.patch.object(transpile, 'export_ast_from',
+                                  self.exporter.export), \
+                mock.patch.object(transpile, 'ensure_code_compiled_with_clang'), \
+                mock.patch.object(transpile, 'get_system_include_dirs',
+                                  return_value=[]), \
+                mock.patch.object(transpile, 'get_rust_toolchain_libpath',
+                                  return_value=""), \
+                open(os.path.join(self.dir, "compile_commands.json")) as cc_db:
+            ok = transpile.transpile_files(cc_db, jobs=1,
+                                           extra_impo_args=extra_impo_args,
+                                           emit_build_files=False,
+                                           use_cache=use_cache)
+        self.assertTrue(ok)
+        return self.read_file("tu.rs")
+
+    def test_unchanged_tu_is_cached(self):
+        first = self.transpile()
+        second = self.transpile()
+        self.assertEqual(first, second)
+        self.assertEqual(self.exporter.exports, 1)
+        self.assertEqual(self.importer.imports, 1)
+
+    def test_changed_header_invalidates(self):
+        self.transpile()
+        # the TU itself is untouched, but its preprocessed output changes
+        self.write_file("tu.h", "#define N 2\n")
+        self.assertEqual(self.transpile(), "// translation #2\n")
+
+    def test_changed_comment_invalidates(self):
+        self.transpile()
+        # comments are carried over into the translation
+        self.write_file("tu.c", '#include "tu.h"\n// f\nint f(void) { return N; }\n')
+        self.transpile()
+        self.assertEqual(self.importer.imports, 2)
+
+    def test_importer_args_invalidate(self):
+        self.transpile()
+        self.transpile(extra_impo_args=["--reloop-cfgs"])
+        self.assertEqual(self.importer.imports, 2)
+        # the stale entry is gone, even for the original configuration
+        self.transpile()
+        self.assertEqual(self.importer.imports, 3)
+
+    def test_rebuilt_importer_invalidates(self):
+        self.transpile()
+        self.write_file("ast-importer", "v1, rebuilt")
+        self.transpile()
+        self.assertEqual(self.importer.imports, 2)
+
+    def test_no_cache(self):
+        self.transpile()
+        self.transpile(use_cache=False)
+        self.assertEqual(self.importer.imports, 2)
+
+    def test_unpreprocessable_tu_is_not_cached(self):
+        self.write_file("tu.c", '#include "missing.h"\n')
+        self.assertIsNone(hash_compile_command(self.cmd, []))
+        self.transpile()
+        self.transpile()
+        self.assertEqual(self.importer.imports, 2)
+
+
+if __name__ == '__main__':
+    unittest.main()
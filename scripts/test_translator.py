@@ -54,12 +54,36 @@ class TestOutcome(Enum):
 
 class CborFile:
     def __init__(self, path: str, enable_relooper: bool = False,
-                 disallow_current_block: bool = False) -> None:
+                 disallow_current_block: bool = False,
+                 link_modules: bool = False,
+                 cfg_structuring: Optional[str] = None,
+                 destructors: List[str] = None,
+                 split_functions_over: Optional[str] = None,
+                 signed_division: Optional[str] = None,
+                 differential_tests: bool = False,
+                 contract_fp: bool = False,
+                 annotate_signatures: bool = False,
+                 restrict_checks: bool = False,
+                 report_impl_defined: bool = False,
+                 stats: bool = False,
+                 translation_error: bool = False) -> None:
         self.path = path
         self.enable_relooper = enable_relooper
         self.disallow_current_block = disallow_current_block
-
-    def translate(self) -> RustFile:
+        self.link_modules = link_modules
+        self.cfg_structuring = cfg_structuring
+        self.destructors = destructors or []
+        self.split_functions_over = split_functions_over
+        self.signed_division = signed_division
+        self.differential_tests = differential_tests
+        self.contract_fp = contract_fp
+        self.annotate_signatures = annotate_signatures
+        self.restrict_checks = restrict_checks
+        self.report_impl_defined = report_impl_defined
+        self.stats = stats
+        self.translation_error = translation_error
+
+    def translate(self) -> Optional[RustFile]:
         c_file_path, _ = os.path.splitext(self.path)
         extensionless_file, _ = os.path.splitext(c_file_path)
         rust_src = extensionless_file + ".rs"
@@ -84,6 +108,30 @@ class CborFile:
             #  args.append("--use-c-multiple-info")
         if self.disallow_current_block:
             args.append("--fail-on-multiple")
+        if self.cfg_structuring:
+            args.append("--cfg-structuring")
+            args.append(self.cfg_structuring)
+        for destructor in self.destructors:
+            args.append("--destructor")
+            args.append(destructor)
+        if self.split_functions_over:
+            args.append("--split-functions-over")
+            args.append(self.split_functions_over)
+        if self.signed_division:
+            args.append("--signed-division")
+            args.append(self.signed_division)
+        if self.differential_tests:
+            args.append("--emit-differential-tests")
+        if self.contract_fp:
+            args.append("--contract-fp")
+        if self.annotate_signatures:
+            args.append("--annotate-signatures")
+        if self.restrict_checks:
+            args.append("--restrict-checks")
+        if self.report_impl_defined:
+            args.append("--report-impl-defined")
+        if self.stats:
+            args.append("--stats")
 
         with pb.local.env(RUST_BACKTRACE='1', LD_LIBRARY_PATH=ld_lib_path):
             # log the command in a format that's easy to re-run
@@ -95,11 +143,81 @@ class CborFile:
 
         logging.debug("stdout:\n%s", stdout)
 
+        # keep the error for the tests to inspect, instead of a translation
+        if self.translation_error:
+            if retcode == 0:
+                raise NonZeroReturn("translation was expected to fail")
+            with open(self.translation_error_path(), "w") as error_file:
+                error_file.write(stderr)
+            return None
+
         if retcode != 0:
             raise NonZeroReturn(stderr)
 
+        # keep the reports for the tests to inspect
+        if self.stats:
+            with open(self.stats_path(), "w") as stats_file:
+                stats_file.write(stderr)
+        if self.report_impl_defined:
+            with open(self.impl_defined_path(), "w") as report_file:
+                report_file.write(stderr)
+
         return RustFile(extensionless_file + ".rs")
 
+    def stats_path(self) -> str:
+        c_file_path, _ = os.path.splitext(self.path)
+        extensionless_file, _ = os.path.splitext(c_file_path)
+        return extensionless_file + ".stats"
+
+    def impl_defined_path(self) -> str:
+        c_file_path, _ = os.path.splitext(self.path)
+        extensionless_file, _ = os.path.splitext(c_file_path)
+        return extensionless_file + ".impl_defined"
+
+    def translation_error_path(self) -> str:
+        c_file_path, _ = os.path.splitext(self.path)
+        extensionless_file, _ = os.path.splitext(c_file_path)
+        return extensionless_file + ".translation_error"
+
+
+def translate_modules(cbor_files: List[CborFile]) -> List[RustFile]:
+    """
+    Translate several CBOR files in one invocation so that each becomes a
+    module which imports the symbols defined by its siblings.
+    """
+    output_dir, _ = os.path.split(cbor_files[0].path)
+
+    ld_lib_path = get_rust_toolchain_libpath(c.CUSTOM_RUST_NAME)
+    if 'LD_LIBRARY_PATH' in pb.local.env:
+        ld_lib_path += ':' + pb.local.env['LD_LIBRARY_PATH']
+
+    ast_importer = get_cmd_or_die(c.AST_IMPO)
+
+    args = [cbor_file.path for cbor_file in cbor_files]
+    args += [
+        "--prefix-function-names",
+        "rust_",
+        "--output-dir",
+        output_dir,
+    ]
+
+    with pb.local.env(RUST_BACKTRACE='1', LD_LIBRARY_PATH=ld_lib_path):
+        logging.debug("translation command:\n %s", str(ast_importer[args]))
+        retcode, stdout, stderr = ast_importer[args].run(retcode=None)
+
+    logging.debug("stdout:\n%s", stdout)
+
+    if retcode != 0:
+        raise NonZeroReturn(stderr)
+
+    rust_files = []
+    for cbor_file in cbor_files:
+        c_file_path, _ = os.path.splitext(cbor_file.path)
+        extensionless_file, _ = os.path.splitext(c_file_path)
+        rust_files.append(RustFile(extensionless_file + ".rs"))
+
+    return rust_files
+
 
 class CStaticLibrary:
     def __init__(self, path: str, link_name: str,
@@ -117,6 +235,32 @@ class CFile:
         self.path = path
         self.enable_relooper = "enable_relooper" in flags
         self.disallow_current_block = "disallow_current_block" in flags
+        self.link_modules = "link_modules" in flags
+        self.differential_tests = "differential_tests" in flags
+        self.contract_fp = "contract_fp" in flags
+        self.annotate_signatures = "annotate_signatures" in flags
+        self.restrict_checks = "restrict_checks" in flags
+        self.report_impl_defined = "report_impl_defined" in flags
+        self.stats = "stats" in flags
+        # the C doesn't compile either, so it's only exported and translated
+        self.translation_error = "translation_error" in flags
+        self.cfg_structuring = None
+        self.destructors = []
+        self.split_functions_over = None
+        self.signed_division = None
+
+        # e.g. `cfg_structuring=switch`, `destructor=foo=free_foo`,
+        # `split_functions_over=20`, or `signed_division=wrapping`
+        for flag in flags:
+            if flag.startswith("cfg_structuring="):
+                self.cfg_structuring = flag[len("cfg_structuring="):]
+            elif flag.startswith("destructor="):
+                self.destructors.append(flag[len("destructor="):])
+            elif flag.startswith("split_functions_over="):
+                self.split_functions_over = \
+                    flag[len("split_functions_over="):]
+            elif flag.startswith("signed_division="):
+                self.signed_division = flag[len("signed_division="):]
 
     def export(self) -> CborFile:
         ast_exporter = get_cmd_or_die(c.AST_EXPO)
@@ -136,11 +280,19 @@ class CFile:
 
         logging.debug("stdout:\n%s", stdout)
 
-        if retcode != 0:
+        # the exporter reports the error, but still writes out the AST
+        if retcode != 0 and not (self.translation_error and
+                                 os.path.isfile(self.path + ".cbor")):
             raise NonZeroReturn(stderr)
 
         return CborFile(self.path + ".cbor", self.enable_relooper,
-                        self.disallow_current_block)
+                        self.disallow_current_block, self.link_modules,
+                        self.cfg_structuring, self.destructors,
+                        self.split_functions_over, self.signed_division,
+                        self.differential_tests, self.contract_fp,
+                        self.annotate_signatures, self.restrict_checks,
+                        self.report_impl_defined, self.stats,
+                        self.translation_error)
 
 
 def build_static_library(c_files: Iterable[CFile],
@@ -340,7 +492,9 @@ class TestDirectory:
         self.print_status(Colors.WARNING, "RUNNING", description)
 
         try:
-            static_library = build_static_library(self.c_files, self.full_path)
+            static_library = build_static_library(
+                [c_file for c_file in self.c_files
+                 if not c_file.translation_error], self.full_path)
         except NonZeroReturn as exception:
             self.print_status(Colors.FAIL, "FAILED", "create libtest.a")
             sys.stdout.write('\n')
@@ -378,10 +532,41 @@ class TestDirectory:
             self.generated_files["cbor"].append(cbor_file)
 
         rust_file_builder = RustFileBuilder()
-        rust_file_builder.add_features(["libc", "i128_type", "extern_types"])
+        rust_file_builder.add_features(["libc", "i128_type", "extern_types", "repr_align",
+                                       "attr_literals", "integer_atomics"])
+
+        # .cbor -> .rs, translating files which reference each other as modules
+        linked_cbor_files = [cbor_file for cbor_file in self.generated_files["cbor"]
+                             if cbor_file.link_modules]
+
+        if linked_cbor_files:
+            description = "translate the CBOR as linked modules..."
+
+            self.print_status(Colors.WARNING, "RUNNING", description)
+
+            try:
+                translated_rust_files = translate_modules(linked_cbor_files)
+            except NonZeroReturn as exception:
+                self.print_status(Colors.FAIL, "FAILED", "translate modules")
+                sys.stdout.write('\n')
+                sys.stdout.write(str(exception))
+
+                outcomes.append(TestOutcome.UnexpectedFailure)
+                translated_rust_files = []
+
+            for translated_rust_file in translated_rust_files:
+                self.generated_files["rust_src"].append(translated_rust_file)
+
+                _, rust_file_short = os.path.split(translated_rust_file.path)
+                extensionless_rust_file, _ = os.path.splitext(rust_file_short)
+
+                rust_file_builder.add_mod(RustMod(extensionless_rust_file,
+                                                  RustVisibility.Public))
 
-        # .cbor -> .rs
         for cbor_file in self.generated_files["cbor"]:
+            if cbor_file.link_modules:
+                continue
+
             _, cbor_file_short = os.path.split(cbor_file.path)
             description = "{}: translate the CBOR...".format(cbor_file_short)
 
@@ -398,7 +583,17 @@ class TestDirectory:
                 outcomes.append(TestOutcome.UnexpectedFailure)
                 continue
 
+            if cbor_file.translation_error:
+                self.generated_files["rust_src"].append(
+                    cbor_file.translation_error_path())
+                continue
+
             self.generated_files["rust_src"].append(translated_rust_file)
+            if cbor_file.stats:
+                self.generated_files["rust_src"].append(cbor_file.stats_path())
+            if cbor_file.report_impl_defined:
+                self.generated_files["rust_src"].append(
+                    cbor_file.impl_defined_path())
 
             _, rust_file_short = os.path.split(translated_rust_file.path)
             extensionless_rust_file, _ = os.path.splitext(rust_file_short)
@@ -515,6 +710,36 @@ class TestDirectory:
 
                         outcomes.append(TestOutcome.Failure)
 
+        # Run the `#[test]`s generated by `--emit-differential-tests`
+        if any(cbor_file.differential_tests
+               for cbor_file in self.generated_files["cbor"]):
+            description = "differential tests: comparing C and Rust..."
+
+            self.print_status(Colors.WARNING, "RUNNING", description)
+
+            try:
+                tests = main_file.compile(CrateType.Binary, save_output=True,
+                                          test=True)
+                self.generated_files["rust_test_exec"].append(str(tests.executable))
+
+                retcode, stdout, stderr = tests.run(retcode=None)
+
+                logging.debug("stdout:%s\n", stdout)
+
+                if retcode != 0:
+                    raise NonZeroReturn(stdout + stderr)
+
+                self.print_status(Colors.OKGREEN, "OK", "    differential tests")
+                sys.stdout.write('\n')
+
+                outcomes.append(TestOutcome.Success)
+            except NonZeroReturn as exception:
+                self.print_status(Colors.FAIL, "FAILED", "differential tests")
+                sys.stdout.write('\n')
+                sys.stdout.write(str(exception))
+
+                outcomes.append(TestOutcome.UnexpectedFailure)
+
         if not outcomes:
             display_text = "   No rust file(s) matching " + self.files.pattern
             display_text += " within this folder\n"
@@ -0,0 +1,90 @@
+#!/usr/bin/env python3
+# -*- coding: utf-8 -*-
+
+import os
+import shutil
+import tempfile
+import unittest
+
+from common import config as c
+from verify import verify
+
+C_SOURCE = """\
+struct handle;
+
+struct point {
+    int x;
+    int y;
+};
+
+int twice(int x) {
+    return x + x;
+}
+
+void increment(int *buf, int len) {
+    for (int i = 0; i < len; i++) {
+        buf[i] += 1;
+    }
+}
+
+int manhattan(struct point *p) {
+    return p->x + p->y;
+}
+
+int handle_id(struct handle *h) {
+    return h != 0;
+}
+"""
+
+
+@unittest.skipUnless(os.path.isfile(c.AST_EXPO) and
+                     os.path.isfile(c.AST_IMPO),
+                     "needs the translator; run build_translator.py first")
+class VerifyTests(unittest.TestCase):
+    def setUp(self):
+        self.dir = tempfile.mkdtemp()
+        self.c_path = os.path.join(self.dir, "funcs.c")
+        with open(self.c_path, "w") as fh:
+            fh.write(C_SOURCE)
+
+    def tearDown(self):
+        shutil.rmtree(self.dir)
+
+    def build_dir(self, name):
+        path = os.path.join(self.dir, name)
+        os.mkdir(path)
+        return path
+
+    def test_translation_passes(self):
+        report = verify(self.c_path, build_dir=self.build_dir("fresh"))
+        self.assertTrue(report.ok(), report.output)
+        self.assertEqual(sorted(report.passed), ["increment", "twice"])
+
+    def test_unsupported_signatures_are_skipped(self):
+        report = verify(self.c_path, build_dir=self.build_dir("fresh"))
+        skipped = dict(report.skipped)
+        self.assertEqual(sorted(skipped), ["handle_id", "manhattan"])
+        self.assertIn("neither a scalar", skipped["manhattan"])
+        self.assertIn("opaque", skipped["handle_id"])
+
+    def test_miscompiled_function_fails(self):
+        first = verify(self.c_path, build_dir=self.build_dir("fresh"))
+        with open(os.path.join(self.dir, "fresh", "translation.rs")) as fh:
+            translation = fh.read()
+
+        # break `twice`, and only `twice`
+        self.assertEqual(translation.count("x + x"), 1)
+        miscompiled_path = os.path.join(self.dir, "miscompiled.rs")
+        with open(miscompiled_path, "w") as fh:
+            fh.write(translation.replace("x + x", "x + 1"))
+
+        report = verify(self.c_path, rust_path=miscompiled_path,
+                        build_dir=self.build_dir("miscompiled"))
+        self.assertTrue(first.ok(), first.output)
+        self.assertFalse(report.ok())
+        self.assertEqual(report.failed, ["twice"])
+        self.assertEqual(report.passed, ["increment"])
+
+
+if __name__ == '__main__':
+    unittest.main()
@@ -0,0 +1,125 @@
+"""
+On-disk cache of translated Rust code, keyed by a hash of the fully
+preprocessed translation unit. Translation units whose preprocessed output
+(and translator configuration) hasn't changed since the last run are served
+from the cache instead of being exported and imported again.
+"""
+
+import os
+import json
+import shlex
+import hashlib
+import logging
+import subprocess
+from typing import List, Optional
+
+
+CACHE_DIR_NAME = ".c2rust-cache"
+
+
+def preprocess_command(cmd: dict) -> List[str]:
+    """
+    turn a compile_commands.json entry into a command that writes the
+    preprocessed translation unit to stdout.
+    """
+    if "arguments" in cmd:
+        args = list(cmd['arguments'])
+    else:
+        args = shlex.split(cmd['command'])
+
+    # comments are kept (`-C`) since the translator carries them over
+    pp_args = [args[0], "-E", "-C"]
+    skip_next = False
+    for arg in args[1:]:
+        if skip_next:
+            skip_next = False
+        elif arg == "-o":
+            skip_next = True
+        elif arg == "-c" or arg.startswith("-o"):
+            continue
+        else:
+            pp_args.append(arg)
+    return pp_args
+
+
+def tool_fingerprint(path: str) -> str:
+    """changes whenever the given tool is rebuilt."""
+    st = os.stat(path)
+    return "{}:{}:{}".format(path, st.st_size, st.st_mtime)
+
+
+def hash_translation_unit(preprocessed: bytes, config: List[str]) -> str:
+    """
+    hash the preprocessed source together with everything else that affects
+    the generated Rust, such as the importer arguments and binaries.
+    """
+    h = hashlib.sha256()
+    for item in config:
+        h.update(item.encode('utf-8'))
+        h.update(b'\0')
+    h.update(preprocessed)
+    return h.hexdigest()
+
+
+def hash_compile_command(cmd: dict, config: List[str]) -> Optional[str]:
+    """
+    preprocess the translation unit described by `cmd` and hash it; returns
+    None (i.e., don't cache) if the preprocessor fails.
+    """
+    try:
+        preprocessed = subprocess.check_output(preprocess_command(cmd),
+                                               cwd=cmd['directory'],
+                                               stderr=subprocess.DEVNULL)
+    except (OSError, subprocess.CalledProcessError) as e:
+        logging.debug("couldn't preprocess %s: %s", cmd['file'], e)
+        return None
+    return hash_translation_unit(preprocessed, config)
+
+
+class TranslationCache:
+    """
+    maps each translation unit to the hash it was last translated with and
+    the Rust code that translation produced.
+    """
+
+    def __init__(self, cache_dir: str) -> None:
+        self.cache_dir = cache_dir
+        os.makedirs(cache_dir, exist_ok=True)
+
+    def _entry_path(self, tu_path: str) -> str:
+        tu_path = os.path.abspath(tu_path)
+        name = hashlib.sha1(tu_path.encode('utf-8')).hexdigest()
+        return os.path.join(self.cache_dir, name + ".json")
+
+    def lookup(self, tu_path: str, tu_hash: str) -> Optional[str]:
+        """
+        get the cached Rust for `tu_path` if it was translated from
+        preprocessed source with hash `tu_hash`. Stale entries are removed.
+        """
+        entry_path = self._entry_path(tu_path)
+        try:
+            with open(entry_path, "r") as entry_fh:
+                entry = json.load(entry_fh)
+        except (OSError, ValueError):
+            return None
+
+        if entry.get('hash') != tu_hash:
+            logging.debug("cache entry for %s is stale", tu_path)
+            os.remove(entry_path)
+            return None
+        return entry.get('rust')
+
+    def store(self, tu_path: str, tu_hash: str, rust: str) -> None:
+        entry = {
+            'file': os.path.abspath(tu_path),
+            'hash': tu_hash,
+            'rust': rust,
+        }
+        # write to a temporary file first so that concurrent or interrupted
+        # runs never observe a partially written entry
+        entry_path = self._entry_path(tu_path)
+        tmp_path = entry_path + ".tmp"
+        with open(tmp_path, "w") as entry_fh:
+            json.dump(entry, entry_fh)
+        os.replace(tmp_path, entry_path)
+
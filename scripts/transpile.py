@@ -27,6 +27,12 @@ from common import (
     get_rust_toolchain_libpath,
     setup_logging,
 )
+from translation_cache import (
+    CACHE_DIR_NAME,
+    TranslationCache,
+    hash_compile_command,
+    tool_fingerprint,
+)
 
 
 # Template for the contents of the Cargo.toml file
@@ -193,10 +199,13 @@ def transpile_files(cc_db: TextIO,
                     verbose: bool = False,
                     emit_build_files: bool = True,
                     cross_checks: bool = False,
-                    cross_check_config: List[str] = []) -> bool:
+                    cross_check_config: List[str] = [],
+                    use_cache: bool = True) -> bool:
     """
     run the ast-exporter and ast-importer on all C files
-    in a compile commands database.
+    in a compile commands database. unless `use_cache` is
+    false, files whose preprocessed source hasn't changed
+    since their last translation are not translated again.
     """
     ast_expo = get_cmd_or_die(c.AST_EXPO)
     ast_impo = get_cmd_or_die(c.AST_IMPO)
@@ -219,8 +228,30 @@ def transpile_files(cc_db: TextIO,
             impo_args.append('--cross-check-config')
             impo_args.append(ccc)
 
+    cache = None
+    if use_cache:
+        cc_db_dir = os.path.dirname(os.path.abspath(cc_db_name))
+        cache = TranslationCache(os.path.join(cc_db_dir, CACHE_DIR_NAME))
+    # anything besides the preprocessed source that affects the output
+    cache_config = [tool_fingerprint(str(ast_expo.executable)),
+                    tool_fingerprint(str(ast_impo.executable))]
+    cache_config += impo_args + extra_impo_args
+
     def transpile_single(cmd) -> Tuple[str, int, str, str, str]:
 
+        tu_hash = None
+        if cache is not None:
+            tu_path = os.path.join(cmd['directory'], cmd['file'])
+            tu_hash = hash_compile_command(cmd, cache_config)
+            rust = cache.lookup(tu_path, tu_hash) if tu_hash else None
+            if rust is not None:
+                logging.info(" using cached translation of %s", cmd['file'])
+                rust_file = os.path.splitext(tu_path)[0] + ".rs"
+                with open(rust_file, "w") as rust_fh:
+                    rust_fh.write(rust)
+                return (os.path.basename(cmd['file']), 0, rust, "",
+                        os.path.abspath(rust_file))
+
         if import_only:
             cbor_file = os.path.join(cmd['directory'], cmd['file'] + ".cbor")
         else:
@@ -256,6 +287,10 @@ def transpile_files(cc_db: TextIO,
                     rust_fh.writelines(stdout)
                     logging.debug("wrote output rust to %s", rust_file)
 
+                if tu_hash is not None and retcode == 0:
+                    tu_path = os.path.join(cmd['directory'], cmd['file'])
+                    cache.store(tu_path, tu_hash, stdout)
+
                 return (file_basename, retcode, stdout, stderr,
                         os.path.abspath(rust_file))
             except pb.ProcessExecutionError as pee:
@@ -324,6 +359,10 @@ def parse_args() -> argparse.Namespace:
     parser.add_argument('-X', '--cross-check-config',
                         default=[], action='append',
                         help='cross-check configuration file(s)')
+    parser.add_argument('--no-cache', default=True, action='store_false',
+                        dest='use_cache',
+                        help='retranslate every file, even if its '
+                             'preprocessed source is unchanged')
     c.add_args(parser)
     return parser.parse_args()
 
@@ -342,7 +381,8 @@ def main():
                     args.verbose,
                     args.emit_build_files,
                     args.cross_checks,
-                    args.cross_check_config)
+                    args.cross_check_config,
+                    args.use_cache)
 
     logging.info(u"success 👍")
 
@@ -0,0 +1,268 @@
+#!/usr/bin/env python3
+
+"""
+Check a translation against the C it came from. Both are compiled, the C is
+linked in as the reference implementation, and each function with a fuzzable
+signature is called with the same random scalars and buffers on both sides by
+the differential tests the translator emits, see
+`ast-importer/src/differential_tests.rs`. Functions with other signatures,
+e.g. taking variadic arguments or pointers to opaque types, are reported as
+skipped.
+"""
+
+import errno
+import os
+import re
+import sys
+import logging
+import argparse
+import tempfile
+
+from common import (
+    config as c,
+    pb,
+    Colors,
+    get_cmd_or_die,
+    get_rust_toolchain_libpath,
+    NonZeroReturn,
+    setup_logging,
+    die,
+    ensure_rustc_version,
+)
+from typing import List, Optional, Tuple
+
+# Tools we will need
+clang = get_cmd_or_die("clang")
+rustc = get_cmd_or_die("rustc")
+ar = get_cmd_or_die("ar")
+
+# Printed by the translator for functions without a harness
+SKIPPED_RE = re.compile(
+    r"^Skipping differential test of `(\w+)`: (.*)$", re.MULTILINE)
+
+# Printed by the test runner for each harness
+RESULT_RE = re.compile(
+    r"^test c2rust_differential_tests::differential_(\w+) \.\.\. (\w+)$",
+    re.MULTILINE)
+
+# Name of the static library holding the reference C
+REFERENCE_LIB = "verify"
+
+
+class VerifyReport:
+    def __init__(self) -> None:
+        self.passed = []  # type: List[str]
+        self.failed = []  # type: List[str]
+        self.skipped = []  # type: List[Tuple[str, str]]
+        self.output = ""
+
+    def ok(self) -> bool:
+        return not self.failed
+
+
+def export(c_path: str) -> str:
+    """
+    Export the Clang AST of a C file, returning the path of the CBOR file.
+    """
+    directory, c_file = os.path.split(c_path)
+
+    compile_commands = """ \
+    [
+      {{
+        "arguments": [ "cc", "-D_FORTIFY_SOURCE=0", "-c", "{0}" ],
+        "directory": "{1}",
+        "file": "{0}"
+      }}
+    ]
+    """.format(c_file, directory)
+
+    with open(os.path.join(directory, "compile_commands.json"), 'w') as fh:
+        fh.write(compile_commands)
+
+    ast_exporter = get_cmd_or_die(c.AST_EXPO)
+    logging.debug("export command:\n %s", str(ast_exporter[c_path]))
+    retcode, stdout, stderr = ast_exporter[c_path].run(retcode=None)
+
+    logging.debug("stdout:\n%s", stdout)
+
+    if retcode != 0:
+        raise NonZeroReturn(stderr)
+
+    return c_path + ".cbor"
+
+
+def translate(cbor_path: str, rust_path: str) -> List[Tuple[str, str]]:
+    """
+    Translate a CBOR file along with its differential tests, returning the
+    functions that got no test and why.
+    """
+    ld_lib_path = get_rust_toolchain_libpath(c.CUSTOM_RUST_NAME)
+    if 'LD_LIBRARY_PATH' in pb.local.env:
+        ld_lib_path += ':' + pb.local.env['LD_LIBRARY_PATH']
+
+    ast_importer = get_cmd_or_die(c.AST_IMPO)
+
+    args = [
+        cbor_path,
+        "--prefix-function-names",
+        "rust_",
+        "--emit-differential-tests",
+    ]
+
+    with pb.local.env(RUST_BACKTRACE='1', LD_LIBRARY_PATH=ld_lib_path):
+        logging.debug("translation command:\n %s",
+                      str(ast_importer[args] > rust_path))
+        retcode, stdout, stderr = (ast_importer[args] > rust_path).run(
+            retcode=None)
+
+    if retcode != 0:
+        raise NonZeroReturn(stderr)
+
+    return SKIPPED_RE.findall(stderr)
+
+
+def build_reference(c_path: str, build_dir: str) -> None:
+    """
+    Compile the C into the static library the differential tests call into.
+    """
+    obj_path = os.path.join(build_dir, "reference.o")
+    lib_path = os.path.join(build_dir, "lib{}.a".format(REFERENCE_LIB))
+
+    args = ["-c", "-fPIC", c_path, "-o", obj_path]
+    logging.debug("compilation command:\n %s", str(clang[args]))
+    retcode, stdout, stderr = clang[args].run(retcode=None)
+
+    if retcode != 0:
+        raise NonZeroReturn(stderr)
+
+    args = ["-rv", lib_path, obj_path]
+    logging.debug("combination command:\n %s", str(ar[args]))
+    retcode, stdout, stderr = ar[args].run(retcode=None)
+
+    if retcode != 0:
+        raise NonZeroReturn(stderr)
+
+
+def build_tests(rust_path: str, build_dir: str) -> pb.commands.BaseCommand:
+    """
+    Build the runner of the `#[test]`s of the translation, linked against
+    the reference C.
+    """
+    exe_path = os.path.join(build_dir, "verify_tests")
+
+    args = [
+        "--test",
+        "-L", build_dir,
+        "-l", "static=" + REFERENCE_LIB,
+        "-o", exe_path,
+        rust_path,
+    ]
+    logging.debug("rustc compile command: %s", str(rustc[args]))
+    retcode, stdout, stderr = rustc[args].run(retcode=None)
+
+    if retcode != 0:
+        raise NonZeroReturn(stderr)
+
+    return get_cmd_or_die(exe_path)
+
+
+def verify(c_path: str, rust_path: Optional[str] = None,
+           build_dir: Optional[str] = None) -> VerifyReport:
+    """
+    Verify the translation of `c_path`. By default, that's a fresh
+    translation; `rust_path` instead checks an existing one, which must have
+    been translated with `--emit-differential-tests` and
+    `--prefix-function-names rust_`.
+    """
+    c_path = os.path.abspath(c_path)
+    build_dir = build_dir or tempfile.mkdtemp(prefix="c2rust_verify")
+
+    report = VerifyReport()
+
+    # The translation is done even when checking an existing one, to find
+    # out which functions have no harness
+    fresh_path = os.path.join(build_dir, "translation.rs")
+    report.skipped = translate(export(c_path), fresh_path)
+    rust_path = os.path.abspath(rust_path) if rust_path else fresh_path
+
+    build_reference(c_path, build_dir)
+    tests = build_tests(rust_path, build_dir)
+
+    # Failing harnesses make the runner exit with an error, but we
+    # report them per function
+    retcode, stdout, stderr = tests.run(retcode=None)
+    logging.debug("stdout:\n%s", stdout)
+    report.output = stdout + stderr
+
+    for name, result in RESULT_RE.findall(stdout):
+        if result == "ok":
+            report.passed.append(name)
+        else:
+            report.failed.append(name)
+
+    return report
+
+
+def print_report(report: VerifyReport) -> None:
+    for name in report.passed:
+        print(Colors.OKGREEN + " [ OK ] " + Colors.NO_COLOR + name)
+    for name in report.failed:
+        print(Colors.FAIL + " [ FAILED ] " + Colors.NO_COLOR + name)
+    for name, reason in report.skipped:
+        print(Colors.OKBLUE + " [ SKIPPED ] " + Colors.NO_COLOR +
+              "{}: {}".format(name, reason))
+
+    if report.failed:
+        print(report.output)
+
+    print("{} passed, {} failed, {} skipped".format(
+        len(report.passed), len(report.failed), len(report.skipped)))
+
+
+def main() -> None:
+    desc = 'check translated Rust against the original C with random inputs.'
+    parser = argparse.ArgumentParser(description=desc)
+    parser.add_argument('c_file', help="C file to check the translation of")
+    parser.add_argument(
+        '--rust', dest='rust_file', default=None,
+        help="Existing translation to check, translated with "
+             "--emit-differential-tests and --prefix-function-names rust_"
+    )
+    parser.add_argument(
+        '--build-dir', dest='build_dir', default=None,
+        help="Where to keep the intermediate files (default: a temporary "
+             "directory)"
+    )
+    parser.add_argument(
+        '--log', dest='logLevel',
+        choices=['DEBUG', 'INFO', 'WARNING', 'ERROR', 'CRITICAL'],
+        default='CRITICAL', help="Set the logging level"
+    )
+    c.add_args(parser)
+
+    args = parser.parse_args()
+    c.update_args(args)
+    setup_logging(args.logLevel)
+
+    logging.debug("args: %s", " ".join(sys.argv))
+
+    # check that the binaries have been built first
+    for b in [c.AST_EXPO, c.AST_IMPO]:
+        if not os.path.isfile(b):
+            die(b + " not found; run build_translator.py first?", errno.ENOENT)
+
+    ensure_rustc_version(c.CUSTOM_RUST_RUSTC_VERSION)
+
+    try:
+        report = verify(args.c_file, args.rust_file, args.build_dir)
+    except NonZeroReturn as exception:
+        die(str(exception))
+
+    print_report(report)
+
+    if not report.ok():
+        sys.exit(1)
+
+
+if __name__ == "__main__":
+    main()
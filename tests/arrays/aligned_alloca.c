@@ -0,0 +1,25 @@
+#include <stdint.h>
+
+void aligned_alloca(const unsigned buffer_size, int buffer[const]) {
+    unsigned n = buffer_size;
+    int i = 0;
+
+    // The alignment is in bits
+    char *bytes = __builtin_alloca_with_align(n * 3, 64 * 8);
+    buffer[i++] = (uintptr_t)bytes % 64 == 0;
+    for (unsigned j = 0; j < n * 3; j++)
+        bytes[j] = j;
+    buffer[i++] = bytes[n * 3 - 1];
+
+    __attribute__((aligned(32))) double halves[n];
+    buffer[i++] = (uintptr_t)halves % 32 == 0;
+    for (unsigned j = 0; j < n; j++)
+        halves[j] = j * 0.5;
+    buffer[i++] = halves[n - 1] * 2;
+    buffer[i++] = sizeof(halves) == n * sizeof(double);
+
+    int *ints = __builtin_alloca(n * sizeof(int));
+    for (unsigned j = 0; j < n; j++)
+        ints[j] = j;
+    buffer[i++] = ints[0] + ints[n - 1];
+}
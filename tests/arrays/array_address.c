@@ -0,0 +1,27 @@
+// `&arr` points to the whole array, while `arr` and `&arr[0]` point to its first element, so
+// pointer arithmetic on them strides over whole arrays and single elements respectively.
+
+void array_address(const unsigned buffer_size, int buffer[const]) {
+    if (buffer_size < 8) return;
+
+    int arr[4] = {1, 2, 3, 4};
+    int (*whole)[4] = &arr;
+    int *first = &arr[0];
+    int *decayed = arr;
+
+    buffer[0] = (int)((int *)(whole + 1) - first);
+    buffer[1] = (int)(decayed + 1 - first);
+    buffer[2] = (*whole)[2];
+    buffer[3] = (int *)whole == first;
+
+    // the same for a variable-length array, whose length is only known at runtime
+    int n = 3;
+    int vla[n];
+    for (int i = 0; i < n; i++) vla[i] = 10 * (i + 1);
+    int (*vla_whole)[n] = &vla;
+
+    buffer[4] = (int)((int *)(vla_whole + 1) - vla);
+    buffer[5] = (int)(vla + 1 - vla);
+    buffer[6] = (*vla_whole)[1];
+    buffer[7] = (int *)vla_whole == &vla[0];
+}
@@ -0,0 +1,13 @@
+int sum_elements(unsigned n, const int a[]) {
+        int sum = 0;
+        for (unsigned i = 0; i < n; i++) {
+                sum += a[i];
+        }
+        return sum;
+}
+
+void fill_elements(unsigned n, int a[const]) {
+        for (unsigned i = 0; i < n; i++) {
+                a[i] = sum_elements(i, a) + 1;
+        }
+}
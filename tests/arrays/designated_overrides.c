@@ -0,0 +1,22 @@
+struct point {
+    int x, y;
+};
+
+void designated_overrides(int *out) {
+    // `[0] = 9` overrides the `1`, and `8` continues from there to override the `2`. `[2] = 7`
+    // comes last, so it wins over the `3`.
+    int a[] = { 1, 2, 3, [0] = 9, 8, [4] = 5, [2] = 7 };
+
+    // `[0].y` updates part of the copy of `p`, and `6` continues with `ps[1].x`
+    struct point p = { 1, 2 };
+    struct point ps[2] = { [0] = p, [0].y = 4, 6 };
+
+    int i = 0;
+    for (int j = 0; j < 5; j++) {
+        out[i++] = a[j];
+    }
+    for (int j = 0; j < 2; j++) {
+        out[i++] = ps[j].x;
+        out[i++] = ps[j].y;
+    }
+}
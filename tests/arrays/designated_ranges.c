@@ -0,0 +1,19 @@
+#define BASE 2
+enum { WIDTH = 3 };
+
+void designated_ranges(int *out) {
+    // `[BASE ... BASE + WIDTH]` sets elements 2 through 5. `[4] = 9` comes later, so it wins over
+    // the `5` there, and the second range covers 6 and 7.
+    int a[10] = { 1, [BASE ... BASE + WIDTH] = 5, [4] = 9, [BASE + WIDTH + 1 ... 2 * 4 - 1] = -1 };
+
+    // A later range overrides part of an earlier one
+    int b[6] = { [0 ... 5] = 3, [1 ... sizeof(short)] = 4 };
+
+    int i = 0;
+    for (int j = 0; j < 10; j++) {
+        out[i++] = a[j];
+    }
+    for (int j = 0; j < 6; j++) {
+        out[i++] = b[j];
+    }
+}
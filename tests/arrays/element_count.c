@@ -0,0 +1,23 @@
+#include <stddef.h>
+
+int add_elements(const int *values, size_t len) {
+    int total = 0;
+    for (size_t i = 0; i < len; i++) {
+        total += values[i];
+    }
+    return total;
+}
+
+struct samples {
+    int readings[5];
+};
+
+int sum_by_element_count(void) {
+    int values[] = { 1, 2, 3, 4, 5, 6 };
+    struct samples s = { { 10, 20, 30, 40, 50 } };
+    struct samples *p = &s;
+
+    return add_elements(values, sizeof(values) / sizeof(values[0]))
+         + add_elements(s.readings, sizeof s.readings / sizeof *s.readings)
+         + add_elements(p->readings, sizeof(p->readings) / sizeof(p->readings[0]));
+}
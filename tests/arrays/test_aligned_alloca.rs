@@ -0,0 +1,26 @@
+extern crate libc;
+
+use aligned_alloca::rust_aligned_alloca;
+use self::libc::{c_int, c_uint};
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn aligned_alloca(_: c_uint, _: *mut c_int);
+}
+
+const BUFFER_SIZE: usize = 8;
+
+pub fn test_aligned_alloca() {
+    let mut buffer = [0; BUFFER_SIZE];
+    let mut rust_buffer = [0; BUFFER_SIZE];
+    let expected_buffer = [1, 23, 1, 7, 1, 7, 0, 0];
+
+    unsafe {
+        aligned_alloca(BUFFER_SIZE as c_uint, buffer.as_mut_ptr());
+        rust_aligned_alloca(BUFFER_SIZE as c_uint, rust_buffer.as_mut_ptr());
+    }
+
+    assert_eq!(buffer, rust_buffer);
+    assert_eq!(buffer, expected_buffer);
+}
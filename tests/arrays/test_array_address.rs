@@ -0,0 +1,26 @@
+extern crate libc;
+
+use array_address::rust_array_address;
+use self::libc::{c_int, c_uint};
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn array_address(_: c_uint, _: *mut c_int);
+}
+
+const BUFFER_SIZE: usize = 8;
+
+pub fn test_array_address() {
+    let mut buffer = [0; BUFFER_SIZE];
+    let mut rust_buffer = [0; BUFFER_SIZE];
+    let expected_buffer = [4, 1, 3, 1, 3, 1, 20, 1];
+
+    unsafe {
+        array_address(BUFFER_SIZE as u32, buffer.as_mut_ptr());
+        rust_array_address(BUFFER_SIZE as u32, rust_buffer.as_mut_ptr());
+    }
+
+    assert_eq!(buffer, expected_buffer);
+    assert_eq!(buffer, rust_buffer);
+}
@@ -0,0 +1,41 @@
+extern crate libc;
+
+use const_array_params::{rust_sum_elements, rust_fill_elements};
+use self::libc::{c_int, c_uint};
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn sum_elements(_: c_uint, _: *const c_int) -> c_int;
+
+    #[no_mangle]
+    fn fill_elements(_: c_uint, _: *mut c_int);
+}
+
+const BUFFER_SIZE: usize = 6;
+
+// `const int a[]` has read-only elements, so it must accept a `*const c_int`
+pub fn test_const_elements() {
+    let buffer: [c_int; BUFFER_SIZE] = [1, 2, 3, 4, 5, 6];
+
+    let ret = unsafe { sum_elements(BUFFER_SIZE as u32, buffer.as_ptr()) };
+    let rust_ret = unsafe { rust_sum_elements(BUFFER_SIZE as u32, buffer.as_ptr()) };
+
+    assert_eq!(ret, rust_ret);
+    assert_eq!(ret, 21);
+}
+
+// `int a[const]` is a const pointer to mutable elements
+pub fn test_const_pointer() {
+    let mut buffer = [0; BUFFER_SIZE];
+    let mut rust_buffer = [0; BUFFER_SIZE];
+    let expected_buffer = [1, 2, 4, 8, 16, 32];
+
+    unsafe {
+        fill_elements(BUFFER_SIZE as u32, buffer.as_mut_ptr());
+        rust_fill_elements(BUFFER_SIZE as u32, rust_buffer.as_mut_ptr());
+    }
+
+    assert_eq!(buffer, rust_buffer);
+    assert_eq!(buffer, expected_buffer);
+}
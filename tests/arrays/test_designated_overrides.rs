@@ -0,0 +1,26 @@
+extern crate libc;
+
+use designated_overrides::rust_designated_overrides;
+use self::libc::c_int;
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn designated_overrides(_: *mut c_int);
+}
+
+const BUFFER_SIZE: usize = 9;
+
+pub fn test_designated_overrides() {
+    let mut buffer = [0; BUFFER_SIZE];
+    let mut rust_buffer = [0; BUFFER_SIZE];
+    let expected_buffer = [9, 8, 7, 0, 5, 1, 4, 6, 0];
+
+    unsafe {
+        designated_overrides(buffer.as_mut_ptr());
+        rust_designated_overrides(rust_buffer.as_mut_ptr());
+    }
+
+    assert_eq!(buffer, expected_buffer);
+    assert_eq!(buffer, rust_buffer);
+}
@@ -0,0 +1,26 @@
+extern crate libc;
+
+use designated_ranges::rust_designated_ranges;
+use self::libc::c_int;
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn designated_ranges(_: *mut c_int);
+}
+
+const BUFFER_SIZE: usize = 16;
+
+pub fn test_designated_ranges() {
+    let mut buffer = [0; BUFFER_SIZE];
+    let mut rust_buffer = [0; BUFFER_SIZE];
+    let expected_buffer = [1, 0, 5, 5, 9, 5, -1, -1, 0, 0, 3, 4, 4, 3, 3, 3];
+
+    unsafe {
+        designated_ranges(buffer.as_mut_ptr());
+        rust_designated_ranges(rust_buffer.as_mut_ptr());
+    }
+
+    assert_eq!(buffer, expected_buffer);
+    assert_eq!(buffer, rust_buffer);
+}
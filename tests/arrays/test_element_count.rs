@@ -0,0 +1,25 @@
+extern crate libc;
+
+use element_count::rust_sum_by_element_count;
+use self::libc::c_int;
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn sum_by_element_count() -> c_int;
+}
+
+// `sizeof(a) / sizeof(a[0])` becomes `a.len()`, for variables and struct members alike
+pub fn test_element_count_idiom() {
+    let translated = include_str!("element_count.rs");
+
+    assert_eq!(translated.matches(".len() as libc::c_ulong").count(), 3);
+}
+
+pub fn test_sum_by_element_count() {
+    let ret = unsafe { sum_by_element_count() };
+    let rust_ret = unsafe { rust_sum_by_element_count() };
+
+    assert_eq!(ret, rust_ret);
+    assert_eq!(ret, 321);
+}
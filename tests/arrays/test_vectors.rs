@@ -0,0 +1,28 @@
+extern crate libc;
+
+use vectors::rust_add_vectors;
+use self::libc::c_int;
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn add_vectors(a: *const c_int, b: *const c_int, out: *mut c_int);
+}
+
+const BUFFER_SIZE: usize = 4;
+
+pub fn test_add_vectors() {
+    let a: [c_int; BUFFER_SIZE] = [1, 2, 3, 4];
+    let b: [c_int; BUFFER_SIZE] = [10, 20, 30, 40];
+    let mut buffer = [0; BUFFER_SIZE];
+    let mut rust_buffer = [0; BUFFER_SIZE];
+    let expected_buffer = [13, 26, 39, -1];
+
+    unsafe {
+        add_vectors(a.as_ptr(), b.as_ptr(), buffer.as_mut_ptr());
+        rust_add_vectors(a.as_ptr(), b.as_ptr(), rust_buffer.as_mut_ptr());
+    }
+
+    assert_eq!(buffer, rust_buffer);
+    assert_eq!(buffer, expected_buffer);
+}
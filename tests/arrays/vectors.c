@@ -0,0 +1,14 @@
+typedef int v4si __attribute__((vector_size(16)));
+
+void add_vectors(const int *a, const int *b, int *out) {
+    v4si x = { a[0], a[1], a[2], a[3] };
+    v4si y = { b[0], b[1], b[2], b[3] };
+
+    v4si sum = x + y;
+    sum += x * 2;
+    sum[3] = -1;
+
+    for (int i = 0; i < 4; i++) {
+        out[i] = sum[i];
+    }
+}
@@ -0,0 +1,20 @@
+#include <stdint.h>
+
+static int twice(int i) { return 2 * i; }
+
+// Pointers stashed in integers have to keep pointing into their buffer, and to their function,
+// once cast back.
+void round_trip(const unsigned sz, int buffer[const]) {
+        if (sz < 4) return;
+
+        intptr_t stashed = (intptr_t)buffer;
+        int *p = (int *)(stashed + sizeof(int));
+        *p = 7;
+
+        uintptr_t unsigned_stashed = (uintptr_t)&buffer[2];
+        *(int *)unsigned_stashed += 5;
+
+        intptr_t stashed_fn = (intptr_t)twice;
+        int (*f)(int) = (int (*)(int))stashed_fn;
+        buffer[3] = f(buffer[1]);
+}
@@ -2,6 +2,7 @@ extern crate libc;
 
 use casts::rust_cast_stuff;
 use cast_funptr::{rust_identity, rust_get_identity, rust_entry};
+use provenance::rust_round_trip;
 
 use self::libc::{c_int, c_uint, c_void};
 
@@ -20,6 +21,9 @@ extern "C" {
 
     #[no_mangle]
     fn entry(_: c_uint, _: *mut c_int);
+
+    #[no_mangle]
+    fn round_trip(_: c_uint, _: *mut c_int);
 }
 
 const BUFFER_SIZE: usize = 1;
@@ -77,3 +81,17 @@ pub fn test_identity() {
         assert_eq!(rust_id, i);
     }
 }
+
+pub fn test_pointer_integer_round_trip() {
+    let mut buffer = [0; 4];
+    let mut rust_buffer = [0; 4];
+    let expected_buffer = [0, 7, 5, 14];
+
+    unsafe {
+        round_trip(4, buffer.as_mut_ptr());
+        rust_round_trip(4, rust_buffer.as_mut_ptr());
+    }
+
+    assert_eq!(buffer, rust_buffer);
+    assert_eq!(buffer, expected_buffer);
+}
@@ -9,3 +9,22 @@ void entry3(const unsigned sz, int buf[const])
         (void) (add(buf+2, 2, 0) ?: add(buf+3, 3, 0));
         (void) (add(buf+4, 4, 1) ?: add(buf+5, 5, 0));
 }
+
+static int bump(int *counter) { return ++*counter; }
+
+void entry4(const unsigned sz, int buf[const])
+{
+        int calls = 0;
+        int zero = 0;
+
+        // The common operand must be evaluated exactly once
+        buf[0] = 10 + (bump(&calls) ?: 100);
+        buf[1] = calls;
+
+        // ... including when it is itself a conditional
+        buf[2] = (zero ? bump(&calls) : zero) ?: bump(&calls);
+        buf[3] = calls;
+
+        buf[4] = calls++ ?: 50;
+        buf[5] = calls;
+}
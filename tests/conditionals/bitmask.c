@@ -0,0 +1,62 @@
+#include <stdbool.h>
+
+#define FLAG_READ  0x1
+#define FLAG_WRITE 0x2
+#define FLAG_EXEC  0x4
+#define FLAG_HIGH  0x80000000u
+
+static const unsigned samples[] = { 0, 1, 2, 3, 4, 6, 7, FLAG_HIGH, FLAG_HIGH | FLAG_EXEC };
+
+/* Each result packs the outcome of one bitmask test per bit */
+static int classify(unsigned flags, const int *owner) {
+    int result = 0;
+    int bit = 0;
+
+    if (flags & FLAG_READ) result |= 1 << bit;
+    bit++;
+
+    if (!(flags & FLAG_WRITE)) result |= 1 << bit;
+    bit++;
+
+    if ((flags & FLAG_READ) && (flags & FLAG_WRITE)) result |= 1 << bit;
+    bit++;
+
+    if ((flags & FLAG_EXEC) || !(flags & (FLAG_READ | FLAG_WRITE))) result |= 1 << bit;
+    bit++;
+
+    result |= ((flags & FLAG_HIGH) ? 1 : 0) << bit;
+    bit++;
+
+    result |= !(flags & FLAG_EXEC) << bit;
+    bit++;
+
+    result |= !!(flags & FLAG_WRITE) << bit;
+    bit++;
+
+    bool writable = flags & FLAG_WRITE;
+    if (writable && owner) result |= 1 << bit;
+    bit++;
+
+    if (!owner || flags & FLAG_HIGH) result |= 1 << bit;
+    bit++;
+
+    unsigned remaining = flags & (FLAG_READ | FLAG_WRITE | FLAG_EXEC);
+    int cleared = 0;
+    while (remaining & (FLAG_READ | FLAG_WRITE | FLAG_EXEC)) {
+        remaining &= remaining - 1;
+        cleared++;
+    }
+    result |= cleared << bit;
+
+    return result;
+}
+
+void entry5(const unsigned sz, int buf[const]) {
+    int owner = 0;
+    unsigned n = sizeof(samples) / sizeof(samples[0]);
+
+    for (unsigned i = 0; i < n && 2 * i + 1 < sz; i++) {
+        buf[2 * i] = classify(samples[i], &owner);
+        buf[2 * i + 1] = classify(samples[i], 0);
+    }
+}
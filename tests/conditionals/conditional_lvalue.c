@@ -0,0 +1,27 @@
+union __attribute__((packed)) cell {
+    char tag;
+    int word;
+};
+
+static int conditions;
+
+static int pick(int c) {
+    conditions++;
+    return c;
+}
+
+// Clang rejects GNU C's `(c ? a : b) = v`, but a field of a packed union may be misaligned, so
+// reading one through a conditional also goes through the address of the selected union
+void conditional_lvalue(unsigned buffer_size, int buffer[]) {
+    union cell a, b;
+    a.word = 1;
+    b.word = 2;
+
+    if (buffer_size < 3) return;
+
+    buffer[0] = (pick(1) ? a : b).word;
+    buffer[1] = (pick(0) ? a : b).word;
+
+    // Each condition is evaluated once
+    buffer[2] = conditions;
+}
@@ -0,0 +1,30 @@
+extern crate libc;
+
+use conditional_lvalue::rust_conditional_lvalue;
+use self::libc::{c_int, c_uint};
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn conditional_lvalue(_: c_uint, _: *mut c_int);
+}
+
+const BUFFER_SIZE: usize = 3;
+
+pub fn test_conditional_lvalue() {
+    let mut buffer = [0; BUFFER_SIZE];
+    let mut rust_buffer = [0; BUFFER_SIZE];
+    let expected_buffer = [1, 2, 2];
+
+    unsafe {
+        conditional_lvalue(BUFFER_SIZE as c_uint, buffer.as_mut_ptr());
+        rust_conditional_lvalue(BUFFER_SIZE as c_uint, rust_buffer.as_mut_ptr());
+    }
+
+    assert_eq!(buffer, rust_buffer);
+    assert_eq!(buffer, expected_buffer);
+
+    // The field is read through a borrow of the selected union, not a copy of it
+    let translated = include_str!("conditional_lvalue.rs");
+    assert!(translated.contains("&mut a } else { &mut b }"));
+}
@@ -2,7 +2,8 @@ extern crate libc;
 
 use conditional::rust_entry;
 use conditionals::rust_entry2;
-use binary_conditional::rust_entry3;
+use binary_conditional::{rust_entry3, rust_entry4};
+use bitmask::rust_entry5;
 use self::libc::{c_int, c_uint};
 
 #[link(name = "test")]
@@ -15,11 +16,18 @@ extern "C" {
 
     #[no_mangle]
     fn entry3(_: c_uint, _: *mut c_int);
+
+    #[no_mangle]
+    fn entry4(_: c_uint, _: *mut c_int);
+
+    #[no_mangle]
+    fn entry5(_: c_uint, _: *mut c_int);
 }
 
 const BUFFER_SIZE: usize = 4;
 const BUFFER_SIZE2: usize = 30;
 const BUFFER_SIZE3: usize = 6;
+const BUFFER_SIZE5: usize = 18;
 
 pub fn test_buffer() {
     let mut buffer = [0; BUFFER_SIZE];
@@ -66,3 +74,35 @@ pub fn test_binary_conditionals() {
     assert_eq!(buffer, rust_buffer);
     assert_eq!(buffer, expected_buffer);
 }
+
+pub fn test_binary_conditional_single_eval() {
+    let mut buffer = [0; BUFFER_SIZE3];
+    let mut rust_buffer = [0; BUFFER_SIZE3];
+    let expected_buffer = [11, 1, 2, 2, 2, 3];
+
+    unsafe {
+        entry4(BUFFER_SIZE3 as u32, buffer.as_mut_ptr());
+        rust_entry4(BUFFER_SIZE3 as u32, rust_buffer.as_mut_ptr());
+    }
+
+    assert_eq!(buffer, rust_buffer);
+    assert_eq!(buffer, expected_buffer);
+}
+
+// Integer and pointer values used as conditions are compared against zero and null
+pub fn test_bitmask_conditions() {
+    let mut buffer = [0; BUFFER_SIZE5];
+    let mut rust_buffer = [0; BUFFER_SIZE5];
+    let expected_buffer = [
+        42, 298, 547, 803, 736, 864, 1253, 1381, 522,
+        778, 1224, 1352, 1741, 1869, 314, 314, 794, 794,
+    ];
+
+    unsafe {
+        entry5(BUFFER_SIZE5 as u32, buffer.as_mut_ptr());
+        rust_entry5(BUFFER_SIZE5 as u32, rust_buffer.as_mut_ptr());
+    }
+
+    assert_eq!(buffer, rust_buffer);
+    assert_eq!(buffer, expected_buffer);
+}
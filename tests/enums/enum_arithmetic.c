@@ -0,0 +1,41 @@
+enum Level { LOW = 1, MID = 2, HIGH = 3 };
+
+enum Offset { BEHIND = -2, LEVEL = 0, AHEAD = 2 };
+
+// Packed enums are narrower than the `int` their arithmetic is computed in
+enum __attribute__((packed)) Small { SMALL_ONE = 1, SMALL_TWO = 2 };
+
+void entry6(const unsigned buffer_size, int buffer[]) {
+  if (buffer_size < 10) { return; }
+
+  enum Level l = LOW;
+
+  // Comparisons mixing enum values and integer arithmetic
+  buffer[0] = l + 1 == MID;
+  buffer[1] = HIGH - l > 1;
+  buffer[2] = l < MID + 1;
+
+  // Integer arithmetic assigned back to an enum variable
+  l = l + 1;
+  buffer[3] = l;
+  l = HIGH * 2 - l;
+  buffer[4] = l;
+
+  // Compound assignment and increments on enum variables
+  l -= 2;
+  l++;
+  ++l;
+  buffer[5] = l;
+
+  enum Offset o = BEHIND;
+  o *= -1;
+  buffer[6] = o == AHEAD;
+  o = o - 4;
+  buffer[7] = o;
+
+  enum Small s = SMALL_ONE;
+  s += 1;
+  s <<= 1;
+  buffer[8] = s;
+  buffer[9] = s / SMALL_TWO == SMALL_TWO;
+}
@@ -5,6 +5,7 @@ use enum_ret::{Color, rust_entry2};
 use enum_duplicate::{e, rust_entry3};
 use top_enum::{E as otherE, rust_entry4};
 use big_enum::{E1, E2, E3, rust_entry5};
+use enum_arithmetic::rust_entry6;
 
 use self::libc::{c_int, c_uint};
 
@@ -24,6 +25,9 @@ extern "C" {
 
     #[no_mangle]
     fn entry5(_: c_uint, _: *mut c_int);
+
+    #[no_mangle]
+    fn entry6(_: c_uint, _: *mut c_int);
 }
 
 const BUFFER_SIZE: usize = 10;
@@ -31,6 +35,7 @@ const BUFFER_SIZE2: usize = 7;
 const BUFFER_SIZE3: usize = 4;
 const BUFFER_SIZE4: usize = 1;
 const BUFFER_SIZE5: usize = 6;
+const BUFFER_SIZE6: usize = 10;
 
 
 pub fn test_variants() {
@@ -108,3 +113,17 @@ pub fn test_buffer5() {
     assert_eq!(buffer, rust_buffer);
     assert_eq!(buffer, expected_buffer);
 }
+
+pub fn test_buffer6() {
+    let mut buffer = [0; BUFFER_SIZE6];
+    let mut rust_buffer = [0; BUFFER_SIZE6];
+    let expected_buffer = [1, 1, 1, 2, 4, 4, 1, -2, 4, 1];
+
+    unsafe {
+        entry6(BUFFER_SIZE6 as u32, buffer.as_mut_ptr());
+        rust_entry6(BUFFER_SIZE6 as u32, rust_buffer.as_mut_ptr());
+    }
+
+    assert_eq!(buffer, rust_buffer);
+    assert_eq!(buffer, expected_buffer);
+}
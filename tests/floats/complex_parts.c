@@ -0,0 +1,17 @@
+void complex_parts(unsigned buffer_size, double buffer[]) {
+    double _Complex z = 1.5;
+
+    // Reading the parts
+    buffer[0] = __real__ z;
+    buffer[1] = __imag__ z;
+
+    // Assigning the parts
+    __imag__ z = 2.5;
+    __real__ z *= 2;
+    buffer[2] = __real__ z;
+    buffer[3] = __imag__ z;
+
+    // The imaginary part of a real number is zero
+    double r = 3.0;
+    buffer[4] = __imag__ r;
+}
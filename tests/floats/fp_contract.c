@@ -0,0 +1,28 @@
+//! contract_fp
+
+#pragma STDC FP_CONTRACT ON
+
+// Translated to `mul_add`
+void fused(const unsigned buffer_size, double buffer[const]) {
+    double a = 1.5, b = 2.0, c = 0.25;
+    float x = 3.0f, y = 0.5f;
+
+    int i = 0;
+    buffer[i++] = a * b + c;
+    buffer[i++] = c + a * b;
+    buffer[i++] = a * b - c;
+    buffer[i++] = c - a * b;
+    buffer[i++] = (a + c) * b + -c;
+    buffer[i++] = x * y + x;
+}
+
+#pragma STDC FP_CONTRACT OFF
+
+// Kept as separate operations
+void unfused(const unsigned buffer_size, double buffer[const]) {
+    double a = 1.5, b = 2.0, c = 0.25;
+
+    int i = 0;
+    buffer[i++] = a * b + c;
+    buffer[i++] = c - a * b;
+}
@@ -0,0 +1,26 @@
+extern crate libc;
+
+use complex_parts::rust_complex_parts;
+use self::libc::{c_double, c_uint};
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn complex_parts(_: c_uint, _: *mut c_double);
+}
+
+const BUFFER_SIZE: usize = 5;
+
+pub fn test_complex_parts() {
+    let mut buffer = [0.; BUFFER_SIZE];
+    let mut rust_buffer = [0.; BUFFER_SIZE];
+    let expected_buffer = [1.5, 0., 3., 2.5, 0.];
+
+    unsafe {
+        complex_parts(BUFFER_SIZE as c_uint, buffer.as_mut_ptr());
+        rust_complex_parts(BUFFER_SIZE as c_uint, rust_buffer.as_mut_ptr());
+    }
+
+    assert_eq!(buffer, expected_buffer);
+    assert_eq!(buffer, rust_buffer);
+}
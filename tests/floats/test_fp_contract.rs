@@ -0,0 +1,57 @@
+extern crate libc;
+
+use fp_contract::{rust_fused, rust_unfused};
+use self::libc::{c_double, c_uint};
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn fused(_: c_uint, _: *mut c_double);
+
+    #[no_mangle]
+    fn unfused(_: c_uint, _: *mut c_double);
+}
+
+const BUFFER_SIZE: usize = 6;
+const BUFFER_SIZE2: usize = 2;
+
+pub fn test_fused() {
+    let mut buffer = [0.0; BUFFER_SIZE];
+    let mut rust_buffer = [0.0; BUFFER_SIZE];
+    let expected_buffer = [3.25, 3.25, 2.75, -2.75, 3.25, 4.5];
+
+    unsafe {
+        fused(BUFFER_SIZE as c_uint, buffer.as_mut_ptr());
+        rust_fused(BUFFER_SIZE as c_uint, rust_buffer.as_mut_ptr());
+    }
+
+    assert_eq!(buffer, rust_buffer);
+    assert_eq!(buffer, expected_buffer);
+}
+
+pub fn test_unfused() {
+    let mut buffer = [0.0; BUFFER_SIZE2];
+    let mut rust_buffer = [0.0; BUFFER_SIZE2];
+    let expected_buffer = [3.25, -2.75];
+
+    unsafe {
+        unfused(BUFFER_SIZE2 as c_uint, buffer.as_mut_ptr());
+        rust_unfused(BUFFER_SIZE2 as c_uint, rust_buffer.as_mut_ptr());
+    }
+
+    assert_eq!(buffer, rust_buffer);
+    assert_eq!(buffer, expected_buffer);
+}
+
+pub fn test_fused_translation() {
+    let translated = include_str!("fp_contract.rs");
+    let fused_start = translated.find("fn rust_fused(").expect("fused not found");
+    let unfused_start = translated.find("fn rust_unfused(").expect("unfused not found");
+    assert!(fused_start < unfused_start);
+
+    let fused = &translated[fused_start..unfused_start];
+    let unfused = &translated[unfused_start..];
+
+    assert_eq!(fused.matches("mul_add").count(), 6, "{}", fused);
+    assert!(!unfused.contains("mul_add"), "{}", unfused);
+}
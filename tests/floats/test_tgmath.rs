@@ -0,0 +1,34 @@
+extern crate libc;
+
+use tgmath::rust_tgmath_roots;
+use self::libc::c_double;
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn tgmath_roots(_: *mut c_double);
+}
+
+const BUFFER_SIZE: usize = 2;
+
+// Only the two `__tg_sqrt` overloads called are translated, out of the hundreds in `<tgmath.h>`
+pub fn test_tgmath_overloads() {
+    let translated = include_str!("tgmath.rs");
+
+    assert_eq!(translated.matches("fn rust___tg_").count(), 2);
+    assert!(translated.contains("sqrtf("));
+}
+
+pub fn test_tgmath_roots() {
+    let mut buffer = [0.; BUFFER_SIZE];
+    let mut rust_buffer = [0.; BUFFER_SIZE];
+    let expected_buffer = [2f32.sqrt() as f64, 2f64.sqrt()];
+
+    unsafe {
+        tgmath_roots(buffer.as_mut_ptr());
+        rust_tgmath_roots(rust_buffer.as_mut_ptr());
+    }
+
+    assert_eq!(buffer, expected_buffer);
+    assert_eq!(buffer, rust_buffer);
+}
@@ -0,0 +1,10 @@
+#include <tgmath.h>
+
+void tgmath_roots(double *out) {
+    float f = 2.0f;
+    double d = 2.0;
+
+    // `sqrtf` for the float, `sqrt` for the double
+    out[0] = sqrt(f);
+    out[1] = sqrt(d);
+}
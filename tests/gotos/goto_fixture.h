@@ -0,0 +1,62 @@
+// Shared by the `goto_*.c` tests, each of which defines `GOTO_FIXTURE` and
+// `GOTO_FIXTURE_TWO_ENTRIES` to different function names and translates them with a different
+// `--cfg-structuring` algorithm.
+
+void GOTO_FIXTURE(const unsigned buffer_size, int buffer[const]) {
+    if (buffer_size < 14) return;
+
+    int i = 0, n = 0;
+
+    // loop made out of a backwards `goto`
+top:
+    buffer[i] += i;
+    i++;
+    if (i < 4) goto top;
+
+    // jump out of nested loops
+    for (int a = 0; a < 4; a++) {
+        for (int b = 0; b < 4; b++) {
+            if (a * b == 6) goto done;
+            n++;
+        }
+    }
+done:
+    buffer[4] = n;
+
+    // jump into the middle of a loop
+    i = 0;
+    goto inside;
+again:
+    buffer[5 + i] = i * 3;
+inside:
+    i++;
+    if (i < 6) goto again;
+    buffer[11] = i;
+
+    // early exit from a loop
+    for (i = 0; i < 10; i++) {
+        if (i == buffer_size - 8) {
+            buffer[12] = i;
+            return;
+        }
+        buffer[13] += i;
+    }
+    buffer[12] = -1;
+}
+
+// Neither of the blocks in this loop dominates the other one
+int GOTO_FIXTURE_TWO_ENTRIES(int n) {
+    int total = 0, steps = 0;
+
+    if (n & 1) goto odd;
+even:
+    total += 2;
+    if (total < n) goto odd;
+    goto out;
+odd:
+    total += 1;
+    steps++;
+    if (total < n) goto even;
+out:
+    return total * 100 + steps;
+}
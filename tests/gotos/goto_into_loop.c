@@ -0,0 +1,46 @@
+// No `enable_relooper` here: functions with `goto`s have to be structured from their control-flow
+// graph anyway, since a Rust loop can't be entered in the middle of its body.
+
+void goto_into_loop(const unsigned buffer_size, int buffer[const]) {
+    if (buffer_size < 13) return;
+
+    int i = 0;
+
+    // enter a `while` loop partway through its body
+    goto inside_while;
+    while (i < 4) {
+        buffer[i] += 10;
+inside_while:
+        buffer[i] += i;
+        i++;
+    }
+
+    // enter a `for` loop either at the top or partway through its body
+    for (int round = 0; round < 2; round++) {
+        int n = 0;
+        i = 1;
+        if (round == 1) goto inside_for;
+        for (i = 0; i < 5; i++) {
+            n += 100;
+inside_for:
+            n += i;
+        }
+        buffer[4 + 2 * round] = n;
+        buffer[5 + 2 * round] = i;
+    }
+
+    // jump backward within a loop body
+    int retries = 0;
+    i = 0;
+    do {
+        buffer[8 + i] += 1;
+again:
+        buffer[8 + i] *= 2;
+        if (buffer[8 + i] < 8) {
+            retries++;
+            goto again;
+        }
+        i++;
+    } while (i < 4);
+    buffer[12] = retries;
+}
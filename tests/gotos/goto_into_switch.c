@@ -0,0 +1,65 @@
+// Interpreter-style mixes of `switch` and `goto`: jumps into the middle of a `switch`, between
+// its cases, and back to a label in front of it to dispatch again.
+
+void goto_into_switch(const unsigned buffer_size, int buffer[const]) {
+    if (buffer_size < 10) return;
+
+    for (int i = 0; i < 5; i++) {
+        int acc = i;
+
+        // skip the dispatch and land in the shared tail of the cases
+        if (i == 4) goto shared_tail;
+
+        switch (i) {
+        case 0:
+            acc += 10;
+            // fall through
+        default:
+            acc += 100;
+            break;
+        case 1:
+            acc += 20;
+            goto twice;
+        case 2:
+        twice:
+            acc *= 2;
+        shared_tail:
+            acc += 1000;
+            break;
+        }
+        buffer[i] = acc;
+    }
+
+    // a leading `default` falls through into the case after it
+    for (int i = 0; i < 3; i++) {
+        int acc = 0;
+        switch (i) {
+        default:
+            acc += 1;
+        case 1:
+            acc += 10;
+            break;
+        case 2:
+            acc += 100;
+        }
+        buffer[5 + i] = acc;
+    }
+
+    // leave the `switch` for a label in front of it and dispatch on the new state
+    int state = 0;
+    int steps = 0;
+next:
+    steps++;
+    switch (state) {
+    case 0:
+        state = 2;
+        goto next;
+    case 2:
+        state = 1;
+        goto next;
+    case 1:
+        break;
+    }
+    buffer[8] = steps;
+    buffer[9] = state;
+}
@@ -0,0 +1,57 @@
+//! disallow_current_block
+
+// No `enable_relooper` here: `goto`s that only leave a loop or start its next iteration become
+// labeled `break`s and `continue`s, without going through the control-flow graph.
+
+void goto_loop_control(const unsigned buffer_size, int buffer[const]) {
+    if (buffer_size < 12) return;
+
+    // `goto` as `continue` of an outer `for` loop
+    for (int i = 0; i < 4; i++) {
+        for (int j = 0; j < 4; j++) {
+            if (j > i) goto next_row;
+            buffer[i] += j + 1;
+        }
+        buffer[i] *= 10;
+next_row:
+        ;
+    }
+
+    // `goto` as `break` out of nested loops
+    int found = -1;
+    for (int i = 0; i < 4; i++) {
+        int j = 0;
+        while (j < 4) {
+            if (i * j == 6) {
+                found = 10 * i + j;
+                goto done;
+            }
+            j++;
+        }
+    }
+done:
+    buffer[4] = found;
+
+    // `goto` back to the top of a `while` loop
+    int n = 0, skipped = 0;
+top:
+    while (n < 10) {
+        n++;
+        if (n % 3 == 0) {
+            skipped++;
+            goto top;
+        }
+        buffer[5] += n;
+    }
+    buffer[6] = skipped;
+
+    // `goto` as `continue` of a `do`-`while` loop
+    int k = 0;
+    do {
+        k++;
+        if (k % 2) goto next;
+        buffer[6 + k / 2] = k * k;
+next:
+        ;
+    } while (k < 10);
+}
@@ -0,0 +1,5 @@
+//! enable_relooper, cfg_structuring=pattern
+
+#define GOTO_FIXTURE goto_pattern
+#define GOTO_FIXTURE_TWO_ENTRIES goto_two_entries_pattern
+#include "goto_fixture.h"
@@ -0,0 +1,5 @@
+//! enable_relooper, cfg_structuring=relooper
+
+#define GOTO_FIXTURE goto_relooper
+#define GOTO_FIXTURE_TWO_ENTRIES goto_two_entries_relooper
+#include "goto_fixture.h"
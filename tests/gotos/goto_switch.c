@@ -0,0 +1,5 @@
+//! enable_relooper, cfg_structuring=switch
+
+#define GOTO_FIXTURE goto_switch
+#define GOTO_FIXTURE_TWO_ENTRIES goto_two_entries_switch
+#include "goto_fixture.h"
@@ -0,0 +1,26 @@
+extern crate libc;
+
+use goto_into_loop::rust_goto_into_loop;
+use self::libc::{c_int, c_uint};
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn goto_into_loop(_: c_uint, _: *mut c_int);
+}
+
+const BUFFER_SIZE: usize = 13;
+
+pub fn test_goto_into_loop() {
+    let mut buffer = [0; BUFFER_SIZE];
+    let mut rust_buffer = [0; BUFFER_SIZE];
+    let expected_buffer = [0, 11, 12, 13, 510, 5, 310, 5, 8, 8, 8, 8, 8];
+
+    unsafe {
+        goto_into_loop(BUFFER_SIZE as u32, buffer.as_mut_ptr());
+        rust_goto_into_loop(BUFFER_SIZE as u32, rust_buffer.as_mut_ptr());
+    }
+
+    assert_eq!(buffer, expected_buffer);
+    assert_eq!(buffer, rust_buffer);
+}
@@ -0,0 +1,26 @@
+extern crate libc;
+
+use goto_into_switch::rust_goto_into_switch;
+use self::libc::{c_int, c_uint};
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn goto_into_switch(_: c_uint, _: *mut c_int);
+}
+
+const BUFFER_SIZE: usize = 10;
+
+pub fn test_goto_into_switch() {
+    let mut buffer = [0; BUFFER_SIZE];
+    let mut rust_buffer = [0; BUFFER_SIZE];
+    let expected_buffer = [110, 1042, 1004, 103, 1004, 11, 10, 100, 3, 1];
+
+    unsafe {
+        goto_into_switch(BUFFER_SIZE as u32, buffer.as_mut_ptr());
+        rust_goto_into_switch(BUFFER_SIZE as u32, rust_buffer.as_mut_ptr());
+    }
+
+    assert_eq!(buffer, expected_buffer);
+    assert_eq!(buffer, rust_buffer);
+}
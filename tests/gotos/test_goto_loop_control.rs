@@ -0,0 +1,33 @@
+extern crate libc;
+
+use goto_loop_control::rust_goto_loop_control;
+use self::libc::{c_int, c_uint};
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn goto_loop_control(_: c_uint, _: *mut c_int);
+}
+
+const BUFFER_SIZE: usize = 12;
+
+pub fn test_goto_loop_control() {
+    let mut buffer = [0; BUFFER_SIZE];
+    let mut rust_buffer = [0; BUFFER_SIZE];
+    let expected_buffer = [1, 3, 6, 100, 23, 37, 3, 4, 16, 36, 64, 100];
+
+    unsafe {
+        goto_loop_control(BUFFER_SIZE as u32, buffer.as_mut_ptr());
+        rust_goto_loop_control(BUFFER_SIZE as u32, rust_buffer.as_mut_ptr());
+    }
+
+    assert_eq!(buffer, expected_buffer);
+    assert_eq!(buffer, rust_buffer);
+}
+
+pub fn test_gotos_become_break_and_continue() {
+    let translated = include_str!("goto_loop_control.rs");
+
+    assert!(translated.contains("continue '"));
+    assert!(translated.contains("break '"));
+}
@@ -0,0 +1,62 @@
+extern crate libc;
+
+use goto_relooper::{rust_goto_relooper, rust_goto_two_entries_relooper};
+use goto_pattern::{rust_goto_pattern, rust_goto_two_entries_pattern};
+use goto_switch::{rust_goto_switch, rust_goto_two_entries_switch};
+use self::libc::{c_int, c_uint};
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn goto_relooper(_: c_uint, _: *mut c_int);
+
+    #[no_mangle]
+    fn goto_two_entries_relooper(_: c_int) -> c_int;
+}
+
+const BUFFER_SIZE: usize = 14;
+
+pub fn test_goto_structuring() {
+    let mut buffer = [0; BUFFER_SIZE];
+    let expected_buffer = [0, 1, 2, 3, 11, 0, 3, 6, 9, 12, 15, 6, 6, 15];
+
+    unsafe {
+        goto_relooper(BUFFER_SIZE as u32, buffer.as_mut_ptr());
+    }
+
+    assert_eq!(buffer, expected_buffer);
+
+    let structured: [unsafe extern "C" fn(c_uint, *mut c_int); 3] = [
+        rust_goto_relooper,
+        rust_goto_pattern,
+        rust_goto_switch,
+    ];
+
+    for &rust_goto in structured.iter() {
+        let mut rust_buffer = [0; BUFFER_SIZE];
+
+        unsafe {
+            rust_goto(BUFFER_SIZE as u32, rust_buffer.as_mut_ptr());
+        }
+
+        assert_eq!(buffer, rust_buffer);
+    }
+}
+
+pub fn test_goto_two_entries() {
+    let structured: [unsafe extern "C" fn(c_int) -> c_int; 3] = [
+        rust_goto_two_entries_relooper,
+        rust_goto_two_entries_pattern,
+        rust_goto_two_entries_switch,
+    ];
+
+    for n in 0..8 {
+        let ret = unsafe { goto_two_entries_relooper(n) };
+
+        for &rust_goto_two_entries in structured.iter() {
+            let rust_ret = unsafe { rust_goto_two_entries(n) };
+
+            assert_eq!(ret, rust_ret);
+        }
+    }
+}
@@ -0,0 +1,38 @@
+int sync_fetch_and_add(int *counter, int amount) {
+    int old = __sync_fetch_and_add(counter, amount);
+    __sync_add_and_fetch(counter, 1);
+    return old;
+}
+
+int sync_compare_and_swap(unsigned *flag) {
+    int swapped = __sync_bool_compare_and_swap(flag, 0u, 1u);
+    unsigned previous = __sync_val_compare_and_swap(flag, 1u, 2u);
+    __sync_synchronize();
+    return swapped + (int)previous;
+}
+
+// Releasing stores zero, which is `false` for a `_Bool`
+int sync_lock_release(_Bool *flag) {
+    __sync_lock_release(flag);
+    return *flag;
+}
+
+int atomic_fetch_add(int *counter) {
+    int old = __atomic_fetch_add(counter, 2, __ATOMIC_RELAXED);
+    int now = __atomic_add_fetch(counter, 3, __ATOMIC_ACQ_REL);
+    __atomic_store_n(counter, now * 2, __ATOMIC_RELEASE);
+    return old + __atomic_load_n(counter, __ATOMIC_ACQUIRE);
+}
+
+// On failure, `expected` is updated to the value that was found instead
+int atomic_compare_exchange(long *value, long expected, long desired) {
+    int exchanged = __atomic_compare_exchange(value, &expected, &desired, 0,
+                                              __ATOMIC_SEQ_CST, __ATOMIC_SEQ_CST);
+    return exchanged + 2 * (int)expected;
+}
+
+int atomic_compare_exchange_n(long *value, long expected, long desired) {
+    int exchanged = __atomic_compare_exchange_n(value, &expected, desired, 1,
+                                                __ATOMIC_ACQ_REL, __ATOMIC_ACQUIRE);
+    return exchanged + 2 * (int)expected;
+}
@@ -0,0 +1,17 @@
+#include <stdint.h>
+
+uint16_t bswap16(uint16_t x) {
+        return __builtin_bswap16(x);
+}
+
+uint32_t bswap32(uint32_t x) {
+        return __builtin_bswap32(x);
+}
+
+uint64_t bswap64(uint64_t x) {
+        return __builtin_bswap64(x);
+}
+
+uint32_t bswap32_signed(int32_t x) {
+        return __builtin_bswap32(x);
+}
@@ -0,0 +1,25 @@
+#include <wchar.h>
+
+int multichar_two(void) {
+        return 'AB';
+}
+
+int multichar_four(void) {
+        return 'ABCD';
+}
+
+int narrow_high_bit(void) {
+        return '\xff';
+}
+
+int narrow_plain(void) {
+        return 'a';
+}
+
+wchar_t wide_char(void) {
+        return L'x';
+}
+
+wchar_t wide_non_ascii(void) {
+        return L'é';
+}
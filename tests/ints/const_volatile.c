@@ -0,0 +1,18 @@
+// A read-only status register, with both orderings of the qualifiers
+typedef struct {
+    const volatile unsigned status;
+    volatile const unsigned count;
+    volatile unsigned control;
+} device;
+
+void poll_device(const unsigned buffer_size, int buffer[], device *dev) {
+    if (buffer_size < 3) { return; }
+
+    const volatile unsigned *status = &dev->status;
+    buffer[0] = *status & 0xff;
+    buffer[1] = dev->count;
+
+    // Only the control register is written
+    dev->control = *status >> 8;
+    buffer[2] = dev->control;
+}
@@ -0,0 +1,19 @@
+void division(const unsigned buffer_size, int buffer[]) {
+  if (buffer_size < 12) { return; }
+
+  int operands[][2] = { { 7, 2 }, { -7, 2 }, { 7, -2 }, { -7, -2 }, { -8, 3 }, { 1, -5 } };
+
+  for (int i = 0; i < 6; i++) {
+    int a = operands[i][0], b = operands[i][1];
+    buffer[2 * i] = a / b;
+    buffer[2 * i + 1] = a % b;
+  }
+}
+
+long compound_division(long a, long b) {
+  long q = a;
+  q /= b;
+  long r = a;
+  r %= b;
+  return q * 1000 + r;
+}
@@ -0,0 +1,26 @@
+// `mode` asks for an integer of an exact width, whatever the width of its base type
+typedef int mode_qi __attribute__((mode(QI)));
+typedef unsigned mode_uqi __attribute__((mode(QI)));
+typedef int mode_hi __attribute__((mode(HI)));
+typedef int mode_si __attribute__((mode(SI)));
+typedef int mode_di __attribute__((mode(DI)));
+typedef unsigned mode_udi __attribute__((mode(DI)));
+
+void int_modes(const unsigned buffer_size, int buffer[const]) {
+    if (buffer_size < 7) return;
+
+    mode_di big = 1;
+    big <<= 40;
+    mode_udi all_ones = -1;
+    mode_uqi small = 255;
+    small++;
+    mode_qi tiny = -128;
+
+    buffer[0] = sizeof(mode_qi);
+    buffer[1] = sizeof(mode_hi);
+    buffer[2] = sizeof(mode_si);
+    buffer[3] = sizeof(mode_di);
+    buffer[4] = (int)(big >> 32);
+    buffer[5] = (int)(all_ones >> 60);
+    buffer[6] = small + tiny;
+}
@@ -0,0 +1,109 @@
+extern crate libc;
+
+use atomics::{rust_sync_fetch_and_add, rust_sync_compare_and_swap, rust_sync_lock_release,
+              rust_atomic_fetch_add, rust_atomic_compare_exchange, rust_atomic_compare_exchange_n};
+use self::libc::{c_int, c_uint, c_long};
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn sync_fetch_and_add(_: *mut c_int, _: c_int) -> c_int;
+
+    #[no_mangle]
+    fn sync_compare_and_swap(_: *mut c_uint) -> c_int;
+
+    #[no_mangle]
+    fn sync_lock_release(_: *mut bool) -> c_int;
+
+    #[no_mangle]
+    fn atomic_fetch_add(_: *mut c_int) -> c_int;
+
+    #[no_mangle]
+    fn atomic_compare_exchange(_: *mut c_long, _: c_long, _: c_long) -> c_int;
+
+    #[no_mangle]
+    fn atomic_compare_exchange_n(_: *mut c_long, _: c_long, _: c_long) -> c_int;
+}
+
+pub fn test_sync_fetch_and_add() {
+    let mut counter = 10;
+    let mut rust_counter = 10;
+    let ret = unsafe { sync_fetch_and_add(&mut counter, 5) };
+    let rust_ret = unsafe { rust_sync_fetch_and_add(&mut rust_counter, 5) };
+
+    assert_eq!(ret, rust_ret);
+    assert_eq!(counter, rust_counter);
+    assert_eq!((ret, counter), (10, 16));
+}
+
+pub fn test_sync_compare_and_swap() {
+    let mut flag = 0;
+    let mut rust_flag = 0;
+    let ret = unsafe { sync_compare_and_swap(&mut flag) };
+    let rust_ret = unsafe { rust_sync_compare_and_swap(&mut rust_flag) };
+
+    assert_eq!(ret, rust_ret);
+    assert_eq!(flag, rust_flag);
+    assert_eq!((ret, flag), (2, 2));
+}
+
+pub fn test_sync_lock_release() {
+    let mut flag = true;
+    let mut rust_flag = true;
+    let ret = unsafe { sync_lock_release(&mut flag) };
+    let rust_ret = unsafe { rust_sync_lock_release(&mut rust_flag) };
+
+    assert_eq!(ret, rust_ret);
+    assert_eq!(flag, rust_flag);
+    assert_eq!((ret, flag), (0, false));
+}
+
+pub fn test_atomic_fetch_add() {
+    let mut counter = 1;
+    let mut rust_counter = 1;
+    let ret = unsafe { atomic_fetch_add(&mut counter) };
+    let rust_ret = unsafe { rust_atomic_fetch_add(&mut rust_counter) };
+
+    assert_eq!(ret, rust_ret);
+    assert_eq!(counter, rust_counter);
+    assert_eq!((ret, counter), (13, 12));
+}
+
+pub fn test_atomic_compare_exchange() {
+    let mut value = 5;
+    let mut rust_value = 5;
+
+    // Succeeds, leaving `expected` alone
+    let ret = unsafe { atomic_compare_exchange(&mut value, 5, 7) };
+    let rust_ret = unsafe { rust_atomic_compare_exchange(&mut rust_value, 5, 7) };
+    assert_eq!(ret, rust_ret);
+    assert_eq!((ret, value, rust_value), (11, 7, 7));
+
+    // Fails, reporting the current value through `expected`
+    let ret = unsafe { atomic_compare_exchange(&mut value, 5, 9) };
+    let rust_ret = unsafe { rust_atomic_compare_exchange(&mut rust_value, 5, 9) };
+    assert_eq!(ret, rust_ret);
+    assert_eq!((ret, value, rust_value), (14, 7, 7));
+}
+
+pub fn test_atomic_compare_exchange_n() {
+    let mut value = 3;
+    let mut rust_value = 3;
+
+    // A weak exchange may fail spuriously, so only the failing case is deterministic
+    let ret = unsafe { atomic_compare_exchange_n(&mut value, 4, 9) };
+    let rust_ret = unsafe { rust_atomic_compare_exchange_n(&mut rust_value, 4, 9) };
+    assert_eq!(ret, rust_ret);
+    assert_eq!((ret, value, rust_value), (6, 3, 3));
+}
+
+// The pointer operands become references to `std::sync::atomic` types
+pub fn test_translation() {
+    let translated = include_str!("atomics.rs");
+
+    assert!(translated.contains("::std::sync::atomic::AtomicI32"));
+    assert!(translated.contains("fetch_add"));
+    assert!(translated.contains("compare_exchange("));
+    assert!(translated.contains("compare_exchange_weak("));
+    assert!(translated.contains("::std::sync::atomic::Ordering::Relaxed"));
+}
@@ -0,0 +1,58 @@
+extern crate libc;
+
+use bswap::{rust_bswap16, rust_bswap32, rust_bswap64, rust_bswap32_signed};
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn bswap16(_: u16) -> u16;
+
+    #[no_mangle]
+    fn bswap32(_: u32) -> u32;
+
+    #[no_mangle]
+    fn bswap64(_: u64) -> u64;
+
+    #[no_mangle]
+    fn bswap32_signed(_: i32) -> u32;
+}
+
+pub fn test_bswap16() {
+    for &x in &[0u16, 1, 0x1234, 0xff00, 0xffff] {
+        let ret = unsafe { bswap16(x) };
+        let rust_ret = unsafe { rust_bswap16(x) };
+
+        assert_eq!(ret, rust_ret);
+        assert_eq!(ret, x.swap_bytes());
+    }
+}
+
+pub fn test_bswap32() {
+    for &x in &[0u32, 1, 0x12345678, 0xff000000, 0xffffffff] {
+        let ret = unsafe { bswap32(x) };
+        let rust_ret = unsafe { rust_bswap32(x) };
+
+        assert_eq!(ret, rust_ret);
+        assert_eq!(ret, x.swap_bytes());
+    }
+}
+
+pub fn test_bswap64() {
+    for &x in &[0u64, 1, 0x123456789abcdef0, 0xff00000000000000, 0xffffffffffffffff] {
+        let ret = unsafe { bswap64(x) };
+        let rust_ret = unsafe { rust_bswap64(x) };
+
+        assert_eq!(ret, rust_ret);
+        assert_eq!(ret, x.swap_bytes());
+    }
+}
+
+pub fn test_bswap32_signed() {
+    for &x in &[0i32, 1, -1, 0x12345678, -0x12345678] {
+        let ret = unsafe { bswap32_signed(x) };
+        let rust_ret = unsafe { rust_bswap32_signed(x) };
+
+        assert_eq!(ret, rust_ret);
+        assert_eq!(ret, (x as u32).swap_bytes());
+    }
+}
@@ -0,0 +1,51 @@
+extern crate libc;
+
+use char_constants::{rust_multichar_two, rust_multichar_four, rust_narrow_high_bit,
+                     rust_narrow_plain, rust_wide_char, rust_wide_non_ascii};
+
+use self::libc::{c_int, wchar_t};
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn multichar_two() -> c_int;
+
+    #[no_mangle]
+    fn multichar_four() -> c_int;
+
+    #[no_mangle]
+    fn narrow_high_bit() -> c_int;
+
+    #[no_mangle]
+    fn narrow_plain() -> c_int;
+
+    #[no_mangle]
+    fn wide_char() -> wchar_t;
+
+    #[no_mangle]
+    fn wide_non_ascii() -> wchar_t;
+}
+
+pub fn test_multichar_constants() {
+    unsafe {
+        assert_eq!(multichar_two(), rust_multichar_two());
+        assert_eq!(multichar_four(), rust_multichar_four());
+        assert_eq!(rust_multichar_two(), 0x4142);
+    }
+}
+
+pub fn test_narrow_constants() {
+    unsafe {
+        assert_eq!(narrow_high_bit(), rust_narrow_high_bit());
+        assert_eq!(narrow_plain(), rust_narrow_plain());
+        assert_eq!(rust_narrow_plain(), 'a' as c_int);
+    }
+}
+
+pub fn test_wide_constants() {
+    unsafe {
+        assert_eq!(wide_char(), rust_wide_char());
+        assert_eq!(wide_non_ascii(), rust_wide_non_ascii());
+        assert_eq!(rust_wide_non_ascii(), 0xe9);
+    }
+}
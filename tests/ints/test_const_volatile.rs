@@ -0,0 +1,35 @@
+extern crate libc;
+
+use const_volatile::{rust_poll_device, device};
+use self::libc::{c_int, c_uint};
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn poll_device(_: c_uint, _: *mut c_int, _: *mut device);
+}
+
+const BUFFER_SIZE: usize = 3;
+
+pub fn test_poll_device() {
+    let mut buffer = [0; BUFFER_SIZE];
+    let mut rust_buffer = [0; BUFFER_SIZE];
+    let expected_buffer = [0x34, 7, 0x12];
+    let mut dev = device { status: 0x1234, count: 7, control: 0 };
+    let mut rust_dev = device { status: 0x1234, count: 7, control: 0 };
+
+    unsafe {
+        poll_device(BUFFER_SIZE as u32, buffer.as_mut_ptr(), &mut dev);
+        rust_poll_device(BUFFER_SIZE as u32, rust_buffer.as_mut_ptr(), &mut rust_dev);
+    }
+
+    assert_eq!(buffer, rust_buffer);
+    assert_eq!(buffer, expected_buffer);
+}
+
+pub fn test_read_only_registers_are_only_read() {
+    let translated = include_str!("const_volatile.rs");
+
+    assert!(translated.contains("read_volatile"));
+    assert_eq!(translated.matches("write_volatile").count(), 1);
+}
@@ -0,0 +1,69 @@
+extern crate libc;
+
+use division::{rust_division, rust_compound_division};
+use wrapping_division::{rust_wrapping_divide, rust_wrapping_remainder, rust_wrapping_compound,
+                        rust_int_min_divided};
+use self::libc::{c_int, c_long, c_longlong, c_uint};
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn division(_: c_uint, _: *mut c_int);
+
+    #[no_mangle]
+    fn compound_division(_: c_long, _: c_long) -> c_long;
+
+    #[no_mangle]
+    fn wrapping_divide(_: c_int, _: c_int) -> c_int;
+
+    #[no_mangle]
+    fn wrapping_remainder(_: c_int, _: c_int) -> c_int;
+
+    #[no_mangle]
+    fn wrapping_compound(_: c_longlong, _: c_longlong) -> c_longlong;
+}
+
+const BUFFER_SIZE: usize = 12;
+
+pub fn test_negative_operands() {
+    let mut buffer = [0; BUFFER_SIZE];
+    let mut rust_buffer = [0; BUFFER_SIZE];
+    let expected_buffer = [3, 1, -3, -1, -3, 1, 3, -1, -2, -2, 0, 1];
+
+    unsafe {
+        division(BUFFER_SIZE as u32, buffer.as_mut_ptr());
+        rust_division(BUFFER_SIZE as u32, rust_buffer.as_mut_ptr());
+    }
+
+    assert_eq!(buffer, rust_buffer);
+    assert_eq!(buffer, expected_buffer);
+}
+
+pub fn test_compound_division() {
+    for &(a, b) in &[(17, 5), (-17, 5), (17, -5), (-17, -5)] {
+        unsafe {
+            assert_eq!(compound_division(a, b), rust_compound_division(a, b));
+        }
+    }
+}
+
+pub fn test_wrapping_division() {
+    for &(a, b) in &[(7, 2), (-7, 2), (7, -2), (-7, -2), (c_int::min_value(), 2)] {
+        unsafe {
+            assert_eq!(wrapping_divide(a, b), rust_wrapping_divide(a, b));
+            assert_eq!(wrapping_remainder(a, b), rust_wrapping_remainder(a, b));
+            assert_eq!(wrapping_compound(a as c_longlong, b as c_longlong),
+                       rust_wrapping_compound(a as c_longlong, b as c_longlong));
+        }
+    }
+}
+
+pub fn test_int_min_divided_by_minus_one() {
+    // Undefined in C, so there is nothing to compare to
+    unsafe {
+        assert_eq!(rust_wrapping_divide(c_int::min_value(), -1), c_int::min_value());
+        assert_eq!(rust_wrapping_remainder(c_int::min_value(), -1), 0);
+        assert_eq!(rust_wrapping_compound(c_longlong::min_value(), -1), c_longlong::min_value());
+        assert_eq!(rust_int_min_divided(), c_int::min_value());
+    }
+}
@@ -0,0 +1,38 @@
+extern crate libc;
+
+use int_modes::rust_int_modes;
+use self::libc::{c_int, c_uint};
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn int_modes(_: c_uint, _: *mut c_int);
+}
+
+const BUFFER_SIZE: usize = 7;
+
+pub fn test_int_modes() {
+    let mut buffer = [0; BUFFER_SIZE];
+    let mut rust_buffer = [0; BUFFER_SIZE];
+    let expected_buffer = [1, 2, 4, 8, 256, 15, -128];
+
+    unsafe {
+        int_modes(BUFFER_SIZE as u32, buffer.as_mut_ptr());
+        rust_int_modes(BUFFER_SIZE as u32, rust_buffer.as_mut_ptr());
+    }
+
+    assert_eq!(buffer, expected_buffer);
+    assert_eq!(buffer, rust_buffer);
+}
+
+// Typedefs with an integer mode become fixed-width integers rather than C integer types
+pub fn test_int_mode_types() {
+    let translated = include_str!("int_modes.rs");
+
+    assert!(translated.contains("pub type mode_qi = i8;"));
+    assert!(translated.contains("pub type mode_uqi = u8;"));
+    assert!(translated.contains("pub type mode_hi = i16;"));
+    assert!(translated.contains("pub type mode_si = i32;"));
+    assert!(translated.contains("pub type mode_di = i64;"));
+    assert!(translated.contains("pub type mode_udi = u64;"));
+}
@@ -0,0 +1,53 @@
+extern crate libc;
+
+use volatile_array::{rust_fill_dma, rust_fill_dma_rows};
+use self::libc::{c_int, c_uint};
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn fill_dma(_: c_uint, _: *mut c_int);
+
+    #[no_mangle]
+    fn fill_dma_rows(_: c_uint, _: *mut c_int);
+}
+
+const BUFFER_SIZE: usize = 8;
+
+pub fn test_buffer() {
+    let mut buffer = [0; BUFFER_SIZE];
+    let mut rust_buffer = [0; BUFFER_SIZE];
+    let expected_buffer = [0, 3, 16, 9, 12, 15, 18, 21];
+
+    unsafe {
+        fill_dma(BUFFER_SIZE as u32, buffer.as_mut_ptr());
+        rust_fill_dma(BUFFER_SIZE as u32, rust_buffer.as_mut_ptr());
+    }
+
+    assert_eq!(buffer, rust_buffer);
+    assert_eq!(buffer, expected_buffer);
+}
+
+pub fn test_rows() {
+    let mut buffer = [0; BUFFER_SIZE];
+    let mut rust_buffer = [0; BUFFER_SIZE];
+    let expected_buffer = [0, 1, 2, 3, 4, 5, 6, 7];
+
+    unsafe {
+        fill_dma_rows(BUFFER_SIZE as u32, buffer.as_mut_ptr());
+        rust_fill_dma_rows(BUFFER_SIZE as u32, rust_buffer.as_mut_ptr());
+    }
+
+    assert_eq!(buffer, rust_buffer);
+    assert_eq!(buffer, expected_buffer);
+}
+
+// Each element is accessed on its own, rather than through a volatile copy of the whole array
+pub fn test_element_accesses() {
+    let translated = include_str!("volatile_array.rs");
+
+    assert!(translated.contains("write_volatile(&mut dma["));
+    assert!(translated.contains("read_volatile::<uint8_t>(&dma["));
+    assert!(!translated.contains("read_volatile::<[uint8_t; 8]>"));
+    assert!(!translated.contains("read_volatile::<[uint8_t; 4]>"));
+}
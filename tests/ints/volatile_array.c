@@ -0,0 +1,34 @@
+#include <stdint.h>
+
+// A DMA buffer: every element access has to reach memory
+void fill_dma(const unsigned buffer_size, int buffer[]) {
+    volatile uint8_t dma[8];
+
+    if (buffer_size < 8) { return; }
+
+    for (int i = 0; i < 8; i++) {
+        dma[i] = i * 3;
+    }
+    dma[2] += 10;
+
+    for (int i = 0; i < 8; i++) {
+        buffer[i] = dma[i];
+    }
+}
+
+// Rows of a two-dimensional volatile array are only read element by element too
+void fill_dma_rows(const unsigned buffer_size, int buffer[]) {
+    volatile uint8_t rows[2][4];
+
+    if (buffer_size < 8) { return; }
+
+    for (int i = 0; i < 2; i++) {
+        for (int j = 0; j < 4; j++) {
+            rows[i][j] = i * 4 + j;
+        }
+    }
+
+    for (int i = 0; i < 8; i++) {
+        buffer[i] = rows[i / 4][i % 4];
+    }
+}
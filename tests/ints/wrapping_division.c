@@ -0,0 +1,24 @@
+//! signed_division=wrapping
+
+#include <limits.h>
+
+int wrapping_divide(int a, int b) {
+  return a / b;
+}
+
+int wrapping_remainder(int a, int b) {
+  return a % b;
+}
+
+long long wrapping_compound(long long a, long long b) {
+  long long q = a;
+  q /= b;
+  long long r = a;
+  r %= b;
+  return q + r;
+}
+
+int int_min_divided(void) {
+  volatile int minus_one = -1;
+  return INT_MIN / minus_one;
+}
@@ -0,0 +1,16 @@
+int add_ints(int a, int b) {
+    return a + b;
+}
+
+// Resolves to the definition of `add_ints` at link time
+int sum_ints(int a, int b) __attribute__((alias("add_ints")));
+
+static int negate_int(int x) {
+    return -x;
+}
+
+static int flip_int(int) __attribute__((alias("negate_int")));
+
+int flip_sum(int a, int b) {
+    return flip_int(sum_ints(a, b));
+}
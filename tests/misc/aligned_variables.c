@@ -0,0 +1,28 @@
+#include <stdint.h>
+
+typedef int wide_int __attribute__((aligned(16)));
+
+_Alignas(32) int counter = 3;
+static wide_int wide = 5;
+
+// Alignments are checked on addresses, since `_Alignof` only sees the types
+void aligned_variables(unsigned buffer_size, int buffer[]) {
+    _Alignas(64) int local = 7;
+    wide_int local_wide = 9;
+    static _Alignas(32) int calls;
+    int i = 0;
+
+    calls++;
+    local += local_wide;
+    counter += wide;
+
+    buffer[i++] = (uintptr_t)&counter % 32 == 0;
+    buffer[i++] = (uintptr_t)&wide % 16 == 0;
+    buffer[i++] = (uintptr_t)&local % 64 == 0;
+    buffer[i++] = (uintptr_t)&local_wide % 16 == 0;
+    buffer[i++] = (uintptr_t)&calls % 32 == 0;
+    buffer[i++] = local;
+    buffer[i++] = counter;
+    buffer[i++] = calls;
+    buffer[i++] = sizeof(local_wide);
+}
@@ -0,0 +1,29 @@
+//! annotate_signatures
+
+#include <stddef.h>
+
+struct span {
+    const char *start;
+    size_t len;
+    unsigned int owned : 1;
+};
+
+size_t count_char(const char *str, size_t len, char c) {
+    size_t count = 0;
+    for (size_t i = 0; i < len; i++) {
+        if (str[i] == c) {
+            count++;
+        }
+    }
+    return count;
+}
+
+int sum_span(struct span *spans, int n, int (*weight)(char)) {
+    int total = 0;
+    for (int i = 0; i < n; i++) {
+        for (size_t j = 0; j < spans[i].len; j++) {
+            total += weight(spans[i].start[j]);
+        }
+    }
+    return total;
+}
@@ -0,0 +1,52 @@
+//! differential_tests
+
+struct point {
+    int x;
+    int y;
+};
+
+int clamp(int x, int lo, int hi) {
+    if (lo > hi) {
+        return lo;
+    }
+    return x < lo ? lo : x > hi ? hi : x;
+}
+
+unsigned int checksum(const unsigned char *buf, int len) {
+    unsigned int sum = 0;
+    for (int i = 0; i < len; i++) {
+        sum = sum * 31 + buf[i];
+    }
+    return sum;
+}
+
+void scale(int *buf, int len, int factor) {
+    for (int i = 0; i < len; i++) {
+        buf[i] *= factor;
+    }
+}
+
+double average(const double *xs, int len) {
+    double sum = 0;
+    if (len == 0) {
+        return 0;
+    }
+    for (int i = 0; i < len; i++) {
+        sum += xs[i];
+    }
+    return sum / len;
+}
+
+// Fuzzed character buffers end in a NUL, so this stops inside them
+int string_length(const char *s) {
+    int len = 0;
+    while (s[len]) {
+        len++;
+    }
+    return len;
+}
+
+// Records don't get a differential test
+int manhattan(struct point *p) {
+    return p->x + p->y;
+}
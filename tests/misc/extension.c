@@ -0,0 +1,23 @@
+__extension__ typedef long long wide;
+
+struct pair {
+  __extension__ wide first;
+  int second;
+};
+
+#define square(x) __extension__ ({ wide _x = (x); _x * _x; })
+
+wide extension(int n) {
+  struct pair p = { 0, n };
+
+  // Statement expression prefixed by `__extension__`
+  p.first = square(p.second) + 1;
+
+  // `__extension__` on an lvalue and on a plain expression
+  __extension__ p.second = __extension__ (p.second * 2);
+
+  // `__extension__` on a statement expression used as a statement
+  __extension__ ({ p.first += p.second; });
+
+  return p.first;
+}
@@ -0,0 +1,14 @@
+//! translation_error
+
+// Only the `#error` on the active preprocessing path stops the translation
+#if 0
+#error never reached
+#endif
+
+#ifndef CONFIGURED
+#error run the configure script first
+#endif
+
+int answer(void) {
+    return 42;
+}
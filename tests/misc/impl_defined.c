@@ -0,0 +1,23 @@
+//! report_impl_defined
+
+int shift_signed(int x) {
+    return x >> 1;
+}
+
+unsigned shift_unsigned(unsigned u) {
+    return u >> 1;
+}
+
+// Constants that fit in the target type convert the same everywhere
+signed char narrow_constant(void) {
+    return (signed char) 'a';
+}
+
+signed char narrow_variable(int x) {
+    return (signed char) x;
+}
+
+int narrow_wide_constant(void) {
+    __int128 w = (__int128) (unsigned __int128) 5;
+    return w == 5;
+}
@@ -0,0 +1,18 @@
+//! Shared by the tests checking what the translator attaches to the items it emits
+
+/// The attribute and comment lines directly above the line declaring `decl`
+pub fn attrs_of<'a>(translated: &'a str, decl: &str) -> &'a str {
+    let decl = translated.find(decl).expect("declaration not found");
+    let line_start = translated[..decl].rfind('\n').map_or(0, |i| i + 1);
+
+    let mut start = line_start;
+    for line in translated[..line_start].lines().rev() {
+        let line = line.trim_left();
+        if !line.starts_with("#[") && !line.starts_with("//") {
+            break;
+        }
+        start = translated[..start - 1].rfind('\n').map_or(0, |i| i + 1);
+    }
+
+    &translated[start..line_start]
+}
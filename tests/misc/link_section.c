@@ -0,0 +1,17 @@
+// Firmware copies functions in `.text.ramfunc` to RAM at startup
+__attribute__((section(".text.ramfunc")))
+int scale_sample(int sample) {
+    return sample * 3 + 1;
+}
+
+// Given on the definition only, after a prototype without it
+static int clamp_sample(int sample);
+
+__attribute__((section(".text.ramfunc")))
+static int clamp_sample(int sample) {
+    return sample > 100 ? 100 : sample;
+}
+
+int process_sample(int sample) {
+    return clamp_sample(scale_sample(sample));
+}
@@ -0,0 +1,18 @@
+// Declared but never used, so nothing needs to define them
+extern int sharedCounter;
+int computeTotal(int firstValue, int secondValue);
+
+int add_two(int value) {
+        return value + 2;
+}
+
+// Defined as a wrapper forwarding to `add_two`
+int addTwo(int someValue) __attribute__((alias("add_two")));
+
+void fillBuffer(unsigned bufferSize, int buffer[]) {
+        int lastValue = 0;
+        for (unsigned i = 0; i < bufferSize; i++) {
+                lastValue = add_two(lastValue);
+                buffer[i] = lastValue;
+        }
+}
@@ -0,0 +1,21 @@
+struct __attribute__((warn_unused_result)) status {
+    int code;
+};
+
+__attribute__((warn_unused_result))
+int checked_divide(int num, int den, int *out) {
+    if (den == 0) {
+        return -1;
+    }
+    *out = num / den;
+    return 0;
+}
+
+struct status make_status(int code) {
+    struct status s = { code };
+    return s;
+}
+
+int plain(int x) {
+    return x;
+}
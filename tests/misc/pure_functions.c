@@ -0,0 +1,13 @@
+__attribute__((pure))
+int pure_sum(const int *values, unsigned len) {
+    int total = 0;
+    for (unsigned i = 0; i < len; i++) {
+        total += values[i];
+    }
+    return total;
+}
+
+__attribute__((const))
+int const_square(int x) {
+    return x * x;
+}
@@ -0,0 +1,21 @@
+#pragma region arithmetic
+int region_inc(int x) {
+        return x + 1;
+}
+
+int region_inc_twice(int x) {
+        return region_inc(region_inc(x));
+}
+#pragma endregion
+
+#pragma region buffers
+void region_fill(unsigned buffer_size, int buffer[]) {
+        for (unsigned i = 0; i < buffer_size; i++) {
+                buffer[i] = region_inc_twice(i);
+        }
+}
+#pragma endregion
+
+int region_after(void) {
+        return 0;
+}
@@ -0,0 +1,15 @@
+static int slots[4];
+
+int *restrict claim_slot(int index) {
+    return &slots[index];
+}
+
+int fill_slots(int value) {
+    int total = 0;
+    for (int i = 0; i < 4; i++) {
+        int *slot = claim_slot(i);
+        *slot = value + i;
+        total += *slot;
+    }
+    return total;
+}
@@ -0,0 +1,41 @@
+//! split_functions_over=12
+
+unsigned checksum(unsigned len, const unsigned char *data) {
+    unsigned hash = 5381;
+    unsigned odd = 0;
+    unsigned even = 0;
+    unsigned i;
+
+    for (i = 0; i < len; i++) {
+        hash = hash * 33 + data[i];
+        if (data[i] & 1) {
+            odd++;
+        } else {
+            even++;
+        }
+    }
+
+    hash ^= hash >> 7;
+    hash += odd * 3;
+    hash ^= hash << 11;
+    hash += even * 5;
+    hash ^= hash >> 13;
+
+    for (i = 0; i < len; i += 2) {
+        hash += data[i];
+        hash ^= hash << 3;
+    }
+
+    switch (hash % 4) {
+        case 0: hash += 1; break;
+        case 1: hash += 3; break;
+        case 2: hash += 5; break;
+        default: hash += 7; break;
+    }
+
+    hash ^= hash >> 17;
+    hash *= 0x9e3779b1u;
+    hash ^= hash >> 15;
+
+    return hash;
+}
@@ -0,0 +1,12 @@
+struct pair {
+    int first;
+    int second;
+};
+
+_Static_assert(sizeof(struct pair) == 2 * sizeof(int), "pair is unpadded");
+
+int sum_pair(int first, int second) {
+    _Static_assert(sizeof(int) >= 2, "int holds at least 16 bits");
+    struct pair p = { first, second };
+    return p.first + p.second;
+}
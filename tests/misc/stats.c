@@ -0,0 +1,13 @@
+//! stats
+
+// The `goto` makes the translator build and structure a control flow graph
+int count_down(int n) {
+    int steps = 0;
+again:
+    if (n > 0) {
+        n--;
+        steps++;
+        goto again;
+    }
+    return steps;
+}
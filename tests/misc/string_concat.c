@@ -0,0 +1,23 @@
+#include <stdio.h>
+#include <wchar.h>
+
+#define STR(x) #x
+#define XSTR(x) STR(x)
+#define MAJOR 2
+#define MINOR 13
+
+static const wchar_t wide[] = L"wide" " " "str" "ing";
+
+void string_concat(const unsigned buffer_size, int buffer[const]) {
+    char out[32];
+    int len = snprintf(out, sizeof(out), "%s" "-" "%d:" "%s",
+                       "v" XSTR(MAJOR) "." XSTR(MINOR), 7, STR(a b));
+    printf("concat" "enated: " "%s" "\n", out);
+
+    int i = 0;
+    buffer[i++] = len;
+    for (int j = 0; j <= len && i < buffer_size; j++)
+        buffer[i++] = out[j];
+    for (int j = 0; j < sizeof(wide) / sizeof(wide[0]) && i < buffer_size; j++)
+        buffer[i++] = wide[j];
+}
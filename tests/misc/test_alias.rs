@@ -0,0 +1,24 @@
+extern crate libc;
+
+use alias::{rust_add_ints, rust_flip_sum, rust_sum_ints};
+use self::libc::c_int;
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn sum_ints(_: c_int, _: c_int) -> c_int;
+}
+
+pub fn test_alias_reaches_target() {
+    for i in -10..10 {
+        let ret = unsafe { sum_ints(i, 3) };
+        let rust_ret = unsafe { rust_sum_ints(i, 3) };
+
+        assert_eq!(ret, rust_ret);
+        assert_eq!(rust_ret, unsafe { rust_add_ints(i, 3) });
+    }
+}
+
+pub fn test_static_alias() {
+    assert_eq!(unsafe { rust_flip_sum(2, 3) }, -5);
+}
@@ -0,0 +1,26 @@
+extern crate libc;
+
+use aligned_variables::rust_aligned_variables;
+use self::libc::{c_int, c_uint};
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn aligned_variables(_: c_uint, _: *mut c_int);
+}
+
+const BUFFER_SIZE: usize = 9;
+
+pub fn test_aligned_variables() {
+    let mut buffer = [0; BUFFER_SIZE];
+    let mut rust_buffer = [0; BUFFER_SIZE];
+    let expected_buffer = [1, 1, 1, 1, 1, 16, 8, 1, 4];
+
+    unsafe {
+        aligned_variables(BUFFER_SIZE as c_uint, buffer.as_mut_ptr());
+        rust_aligned_variables(BUFFER_SIZE as c_uint, rust_buffer.as_mut_ptr());
+    }
+
+    assert_eq!(buffer, rust_buffer);
+    assert_eq!(buffer, expected_buffer);
+}
@@ -0,0 +1,38 @@
+extern crate libc;
+
+use annotate_signatures::rust_count_char;
+use self::libc::{c_char, size_t};
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn count_char(_: *const c_char, _: size_t, _: c_char) -> size_t;
+}
+
+#[path = "item_attrs.rs"]
+mod item_attrs;
+
+use self::item_attrs::attrs_of;
+
+pub fn test_signature_comments() {
+    let translated = include_str!("annotate_signatures.rs");
+
+    assert!(attrs_of(translated, "struct span ")
+        .contains("// C: struct span { const char *start; size_t len; unsigned int owned : 1; }"));
+    assert!(attrs_of(translated, "fn rust_count_char(")
+        .contains("// C: size_t count_char(const char *, size_t, char)"));
+    assert!(attrs_of(translated, "fn rust_sum_span(")
+        .contains("// C: int sum_span(struct span *, int, int (*)(char))"));
+}
+
+pub fn test_count_char() {
+    let text = b"annotated signatures\0";
+    let len = text.len() as size_t - 1;
+
+    for &c in b"aent" {
+        let ret = unsafe { count_char(text.as_ptr() as *const c_char, len, c as c_char) };
+        let rust_ret = unsafe { rust_count_char(text.as_ptr() as *const c_char, len, c as c_char) };
+
+        assert_eq!(ret, rust_ret);
+    }
+}
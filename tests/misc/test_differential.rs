@@ -0,0 +1,30 @@
+extern crate libc;
+
+use differential::rust_clamp;
+use self::libc::c_int;
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn clamp(_: c_int, _: c_int, _: c_int) -> c_int;
+}
+
+// The generated `#[test]`s themselves are run by the test script, see `differential_tests`
+pub fn test_generated_harnesses() {
+    let translated = include_str!("differential.rs");
+
+    assert!(translated.contains("mod c2rust_differential_tests"));
+    assert!(translated.contains("fn differential_clamp()"));
+    assert!(translated.contains("fn differential_checksum()"));
+    assert!(translated.contains("fn differential_scale()"));
+    assert!(translated.contains("fn differential_average()"));
+    assert!(translated.contains("fn differential_string_length()"));
+    assert!(!translated.contains("fn differential_manhattan()"));
+}
+
+pub fn test_clamp() {
+    unsafe {
+        assert_eq!(clamp(5, 0, 3), rust_clamp(5, 0, 3));
+        assert_eq!(clamp(-5, 0, 3), rust_clamp(-5, 0, 3));
+    }
+}
@@ -0,0 +1,21 @@
+extern crate libc;
+
+use extension::rust_extension;
+use self::libc::{c_int, c_longlong};
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn extension(_: c_int) -> c_longlong;
+}
+
+pub fn test_extension() {
+    for n in -3..4 {
+        unsafe {
+            assert_eq!(extension(n), rust_extension(n));
+        }
+    }
+    unsafe {
+        assert_eq!(rust_extension(3), 16);
+    }
+}
@@ -0,0 +1,8 @@
+pub fn test_active_error() {
+    // The importer's stderr, kept in place of the translation
+    let report = include_str!("hash_error.translation_error");
+
+    assert!(report.lines().any(|line| line.contains("hash_error.c:9:") &&
+                                      line.ends_with(": error: run the configure script first")));
+    assert!(!report.contains("never reached"));
+}
@@ -0,0 +1,51 @@
+extern crate libc;
+
+use impl_defined::{rust_narrow_constant, rust_narrow_variable, rust_narrow_wide_constant,
+                   rust_shift_signed, rust_shift_unsigned};
+use self::libc::{c_int, c_schar, c_uint};
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn shift_signed(_: c_int) -> c_int;
+
+    #[no_mangle]
+    fn shift_unsigned(_: c_uint) -> c_uint;
+
+    #[no_mangle]
+    fn narrow_constant() -> c_schar;
+
+    #[no_mangle]
+    fn narrow_variable(_: c_int) -> c_schar;
+
+    #[no_mangle]
+    fn narrow_wide_constant() -> c_int;
+}
+
+// Only the shift of `x` and the narrowing of `x` depend on implementation-defined behavior
+pub fn test_report() {
+    let report = include_str!("impl_defined.impl_defined");
+    let sites: Vec<&str> = report.lines().filter(|line| line.contains(": implementation-defined: ")).collect();
+
+    assert_eq!(sites.len(), 2, "unexpected report:\n{}", report);
+    assert!(sites[0].contains("impl_defined.c:4:"), "unexpected report:\n{}", report);
+    assert!(sites[0].ends_with("right shift of a signed value"), "unexpected report:\n{}", report);
+    assert!(sites[1].contains("impl_defined.c:17:"), "unexpected report:\n{}", report);
+    assert!(sites[1].ends_with("conversion to a signed type that cannot represent every value"),
+            "unexpected report:\n{}", report);
+}
+
+pub fn test_translation() {
+    for &x in &[-7, -1, 0, 1, 300] {
+        unsafe {
+            assert_eq!(shift_signed(x), rust_shift_signed(x));
+            assert_eq!(shift_unsigned(x as c_uint), rust_shift_unsigned(x as c_uint));
+            assert_eq!(narrow_variable(x), rust_narrow_variable(x));
+        }
+    }
+
+    unsafe {
+        assert_eq!(narrow_constant(), rust_narrow_constant());
+        assert_eq!(narrow_wide_constant(), rust_narrow_wide_constant());
+    }
+}
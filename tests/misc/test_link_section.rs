@@ -0,0 +1,35 @@
+extern crate libc;
+
+use link_section::rust_process_sample;
+use self::libc::c_int;
+
+#[path = "item_attrs.rs"]
+mod item_attrs;
+
+use self::item_attrs::attrs_of;
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn process_sample(_: c_int) -> c_int;
+}
+
+pub fn test_link_section_attributes() {
+    let translated = include_str!("link_section.rs");
+
+    for name in &["fn rust_scale_sample(", "fn rust_clamp_sample("] {
+        assert!(attrs_of(translated, name).contains("#[link_section = \".text.ramfunc\"]"),
+                "no link_section on {}", name);
+    }
+
+    assert!(!attrs_of(translated, "fn rust_process_sample(").contains("link_section"));
+}
+
+pub fn test_process_sample() {
+    for i in 0..50 {
+        let ret = unsafe { process_sample(i) };
+        let rust_ret = unsafe { rust_process_sample(i) };
+
+        assert_eq!(ret, rust_ret);
+    }
+}
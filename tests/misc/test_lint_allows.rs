@@ -0,0 +1,67 @@
+extern crate libc;
+
+use lint_allows::{rust_add_two, rust_fillBuffer};
+use self::libc::{c_int, c_uint};
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn add_two(_: c_int) -> c_int;
+
+    #[no_mangle]
+    fn fillBuffer(_: c_uint, _: *mut c_int);
+}
+
+#[path = "item_attrs.rs"]
+mod item_attrs;
+
+use self::item_attrs::attrs_of;
+
+const BUFFER_SIZE: usize = 5;
+
+pub fn test_foreign_item_allows() {
+    let translated = include_str!("lint_allows.rs");
+
+    assert!(attrs_of(translated, "static mut sharedCounter")
+        .contains("#[allow(non_upper_case_globals)]"));
+    assert!(attrs_of(translated, "fn computeTotal(")
+        .contains("#[allow(non_snake_case)]"));
+}
+
+pub fn test_alias_allows() {
+    let translated = include_str!("lint_allows.rs");
+
+    assert!(attrs_of(translated, "fn rust_addTwo(")
+        .contains("#[allow(non_snake_case)]"));
+}
+
+pub fn test_no_needless_allows() {
+    let translated = include_str!("lint_allows.rs");
+
+    assert!(attrs_of(translated, "fn rust_fillBuffer(")
+        .contains("#[allow(non_snake_case)]"));
+    assert!(!attrs_of(translated, "fn rust_add_two(").contains("#[allow("));
+}
+
+pub fn test_snake_case() {
+    for i in 0..10 {
+        let ret = unsafe { add_two(i) };
+        let rust_ret = unsafe { rust_add_two(i) };
+
+        assert_eq!(ret, rust_ret);
+    }
+}
+
+pub fn test_camel_case() {
+    let mut buffer = [0; BUFFER_SIZE];
+    let mut rust_buffer = [0; BUFFER_SIZE];
+    let expected_buffer = [2, 4, 6, 8, 10];
+
+    unsafe {
+        fillBuffer(BUFFER_SIZE as u32, buffer.as_mut_ptr());
+        rust_fillBuffer(BUFFER_SIZE as u32, rust_buffer.as_mut_ptr());
+    }
+
+    assert_eq!(buffer, rust_buffer);
+    assert_eq!(buffer, expected_buffer);
+}
@@ -0,0 +1,36 @@
+extern crate libc;
+
+use must_use::{rust_checked_divide, rust_make_status};
+use self::libc::c_int;
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn checked_divide(_: c_int, _: c_int, _: *mut c_int) -> c_int;
+}
+
+#[path = "item_attrs.rs"]
+mod item_attrs;
+
+use self::item_attrs::attrs_of;
+
+pub fn test_must_use_attributes() {
+    let translated = include_str!("must_use.rs");
+
+    assert!(attrs_of(translated, "fn rust_checked_divide(").contains("#[must_use]"));
+    assert!(attrs_of(translated, "struct status ").contains("#[must_use]"));
+    assert!(attrs_of(translated, "fn rust_make_status(").contains("#[must_use]"));
+    assert!(!attrs_of(translated, "fn rust_plain(").contains("#[must_use]"));
+}
+
+pub fn test_checked_divide() {
+    let mut out = 0;
+    let mut rust_out = 0;
+
+    unsafe {
+        assert_eq!(checked_divide(7, 2, &mut out), rust_checked_divide(7, 2, &mut rust_out));
+        assert_eq!(out, rust_out);
+        assert_eq!(checked_divide(7, 0, &mut out), rust_checked_divide(7, 0, &mut rust_out));
+        assert_eq!(rust_make_status(3).code, 3);
+    }
+}
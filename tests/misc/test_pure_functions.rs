@@ -0,0 +1,34 @@
+extern crate libc;
+
+use pure_functions::{rust_pure_sum, rust_const_square};
+use self::libc::{c_int, c_uint};
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn pure_sum(_: *const c_int, _: c_uint) -> c_int;
+
+    #[no_mangle]
+    fn const_square(_: c_int) -> c_int;
+}
+
+const BUFFER_SIZE: usize = 4;
+
+pub fn test_pure_sum() {
+    let buffer = [1, 2, 3, 4];
+
+    let ret = unsafe { pure_sum(buffer.as_ptr(), BUFFER_SIZE as u32) };
+    let rust_ret = unsafe { rust_pure_sum(buffer.as_ptr(), BUFFER_SIZE as u32) };
+
+    assert_eq!(ret, rust_ret);
+    assert_eq!(ret, 10);
+}
+
+pub fn test_const_square() {
+    for i in -5..5 {
+        let ret = unsafe { const_square(i) };
+        let rust_ret = unsafe { rust_const_square(i) };
+
+        assert_eq!(ret, rust_ret);
+    }
+}
@@ -0,0 +1,43 @@
+extern crate libc;
+
+use regions::rust_region_fill;
+use self::libc::{c_int, c_uint};
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn region_fill(_: c_uint, _: *mut c_int);
+}
+
+const BUFFER_SIZE: usize = 5;
+
+// Region pragmas are kept as `// region: NAME` and `// endregion` comments
+pub fn test_region_comments() {
+    let translated = include_str!("regions.rs");
+    let find = |s: &str| translated.find(s).expect(&format!("`{}` not found", s));
+
+    let arithmetic = find("// region: arithmetic");
+    let buffers = find("// region: buffers");
+    assert_eq!(translated.matches("// endregion").count(), 2);
+
+    // Each comment stays in front of the declaration that followed the pragma
+    assert!(arithmetic < find("fn rust_region_inc("));
+    assert!(find("// endregion") < buffers);
+    assert!(buffers < find("fn rust_region_fill("));
+    assert!(find("fn rust_region_fill(") < translated.rfind("// endregion").unwrap());
+    assert!(translated.rfind("// endregion").unwrap() < find("fn rust_region_after("));
+}
+
+pub fn test_regions() {
+    let mut buffer = [0; BUFFER_SIZE];
+    let mut rust_buffer = [0; BUFFER_SIZE];
+    let expected_buffer = [2, 3, 4, 5, 6];
+
+    unsafe {
+        region_fill(BUFFER_SIZE as u32, buffer.as_mut_ptr());
+        rust_region_fill(BUFFER_SIZE as u32, rust_buffer.as_mut_ptr());
+    }
+
+    assert_eq!(buffer, rust_buffer);
+    assert_eq!(buffer, expected_buffer);
+}
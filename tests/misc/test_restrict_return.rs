@@ -0,0 +1,26 @@
+extern crate libc;
+
+use restrict_return::rust_fill_slots;
+use self::libc::c_int;
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn fill_slots(_: c_int) -> c_int;
+}
+
+pub fn test_restrict_return_doc() {
+    let translated = include_str!("restrict_return.rs");
+    let decl = translated.find("fn rust_claim_slot(").expect("declaration not found");
+
+    assert!(translated[..decl].contains("Returns a `restrict` pointer"));
+}
+
+pub fn test_fill_slots() {
+    for i in 0..5 {
+        let ret = unsafe { fill_slots(i) };
+        let rust_ret = unsafe { rust_fill_slots(i) };
+
+        assert_eq!(ret, rust_ret);
+    }
+}
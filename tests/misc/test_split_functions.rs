@@ -0,0 +1,29 @@
+extern crate libc;
+
+use split_functions::rust_checksum;
+use self::libc::c_uint;
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn checksum(_: c_uint, _: *const u8) -> c_uint;
+}
+
+// The oversized function is translated into helpers declared inside it
+pub fn test_split_into_helpers() {
+    let translated = include_str!("split_functions.rs");
+
+    assert!(translated.contains("fn rust_checksum_part1("));
+    assert!(translated.contains("rust_checksum_part1(&mut "));
+}
+
+pub fn test_same_behavior() {
+    let inputs: [&[u8]; 4] = [b"", b"a", b"hello, world", b"\x00\x01\x02\x03\xff\xfe"];
+
+    for input in inputs.iter() {
+        let ret = unsafe { checksum(input.len() as c_uint, input.as_ptr()) };
+        let rust_ret = unsafe { rust_checksum(input.len() as c_uint, input.as_ptr()) };
+
+        assert_eq!(ret, rust_ret);
+    }
+}
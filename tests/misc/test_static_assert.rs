@@ -0,0 +1,29 @@
+extern crate libc;
+
+use static_assert::rust_sum_pair;
+use self::libc::c_int;
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn sum_pair(_: c_int, _: c_int) -> c_int;
+}
+
+// A failing `_Static_assert` is already rejected by clang, so only the messages rustc would
+// report for a failing translation can be checked here
+pub fn test_static_assert_messages() {
+    let translated = include_str!("static_assert.rs");
+
+    assert!(translated.contains(
+        "static_assert.c:6: static assertion `sizeof(struct pair) == 2 * sizeof(int)` failed: pair is unpadded"
+    ));
+    assert!(translated.contains(
+        "static_assert.c:9: static assertion `sizeof(int) >= 2` failed: int holds at least 16 bits"
+    ));
+}
+
+pub fn test_sum_pair() {
+    unsafe {
+        assert_eq!(sum_pair(3, 4), rust_sum_pair(3, 4));
+    }
+}
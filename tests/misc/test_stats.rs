@@ -0,0 +1,28 @@
+extern crate libc;
+
+use stats::rust_count_down;
+use self::libc::c_int;
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn count_down(_: c_int) -> c_int;
+}
+
+pub fn test_stats_phases() {
+    let report = include_str!("stats.stats");
+
+    for phase in &["parse", "lower", "translate", "structure-CFG", "print", "peak memory"] {
+        assert!(report.lines().any(|line| line.starts_with(&format!("stats: {} ", phase))),
+                "no {} phase in the report:\n{}", phase, report);
+    }
+}
+
+pub fn test_count_down() {
+    for i in 0..10 {
+        let ret = unsafe { count_down(i) };
+        let rust_ret = unsafe { rust_count_down(i) };
+
+        assert_eq!(ret, rust_ret);
+    }
+}
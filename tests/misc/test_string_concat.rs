@@ -0,0 +1,29 @@
+extern crate libc;
+
+use string_concat::rust_string_concat;
+use self::libc::{c_int, c_uint};
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn string_concat(_: c_uint, _: *mut c_int);
+}
+
+const BUFFER_SIZE: usize = 26;
+
+pub fn test_string_concat() {
+    let mut buffer = [0; BUFFER_SIZE];
+    let mut rust_buffer = [0; BUFFER_SIZE];
+    let mut expected_buffer = vec![11];
+    expected_buffer.extend(b"v2.13-7:a b\0".iter().map(|&c| c as c_int));
+    expected_buffer.extend(b"wide string\0".iter().map(|&c| c as c_int));
+    expected_buffer.push(0);
+
+    unsafe {
+        string_concat(BUFFER_SIZE as c_uint, buffer.as_mut_ptr());
+        rust_string_concat(BUFFER_SIZE as c_uint, rust_buffer.as_mut_ptr());
+    }
+
+    assert_eq!(buffer, rust_buffer);
+    assert_eq!(&buffer[..], &expected_buffer[..]);
+}
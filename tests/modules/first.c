@@ -0,0 +1,9 @@
+//! link_modules
+
+int second_value(int x);
+extern int shared_counter;
+
+int first_value(int x) {
+    shared_counter += 1;
+    return second_value(x) * 2;
+}
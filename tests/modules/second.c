@@ -0,0 +1,13 @@
+//! link_modules
+
+int first_value(int x);
+
+int shared_counter = 0;
+
+int second_value(int x) {
+    return x + 1;
+}
+
+int call_first(int x) {
+    return first_value(x) + shared_counter;
+}
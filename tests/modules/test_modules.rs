@@ -0,0 +1,20 @@
+extern crate libc;
+
+use second::rust_call_first;
+use self::libc::c_int;
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn call_first(_: c_int) -> c_int;
+}
+
+// Both translation units reference each other's functions and variables
+pub fn test_cross_module_calls() {
+    for i in 0..10 {
+        let ret = unsafe { call_first(i) };
+        let rust_ret = unsafe { rust_call_first(i) };
+
+        assert_eq!(ret, rust_ret);
+    }
+}
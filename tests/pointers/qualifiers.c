@@ -0,0 +1,30 @@
+typedef int (*sum_fn)(const int *, int);
+
+static int sum(const int *values, int n) {
+    int total = 0;
+    for (int i = 0; i < n; i++) {
+        total += values[i];
+    }
+    return total;
+}
+
+static void store(volatile int *slot, int value) {
+    *slot = value;
+}
+
+// Dropping `const` implicitly only draws a warning, and is translated as an explicit cast
+void qualifiers(unsigned n, int *buffer) {
+    const int *view = buffer;
+    sum_fn f = sum;
+
+    buffer[0] = sum(buffer, n);
+    buffer[1] = f(view, n);
+
+    store(&buffer[2], view[0] + view[1]);
+
+    int *writable = (int *)view;
+    writable[3] = sum(view, 3);
+
+    int *implicit = view;
+    implicit[4] = view[0];
+}
@@ -0,0 +1,15 @@
+//! restrict_checks
+
+// Copies `n` ints, assuming the buffers don't overlap
+void copy_ints(int n, int *restrict dst, const int *restrict src) {
+    for (int i = 0; i < n; i++) {
+        dst[i] = src[i];
+    }
+}
+
+// Function pointers are left out of the checks
+void map_ints(int n, int *restrict dst, const int *restrict src, int (*f)(int)) {
+    for (int i = 0; i < n; i++) {
+        dst[i] = f(src[i]);
+    }
+}
@@ -0,0 +1,26 @@
+extern crate libc;
+
+use qualifiers::rust_qualifiers;
+use self::libc::{c_int, c_uint};
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn qualifiers(_: c_uint, _: *mut c_int);
+}
+
+const BUFFER_SIZE: usize = 5;
+
+pub fn test_qualifiers() {
+    let mut buffer = [1, 2, 3, 4, 5];
+    let mut rust_buffer = [1, 2, 3, 4, 5];
+    let expected_buffer = [15, 29, 44, 88, 15];
+
+    unsafe {
+        qualifiers(BUFFER_SIZE as c_uint, buffer.as_mut_ptr());
+        rust_qualifiers(BUFFER_SIZE as c_uint, rust_buffer.as_mut_ptr());
+    }
+
+    assert_eq!(buffer, rust_buffer);
+    assert_eq!(buffer, expected_buffer);
+}
@@ -0,0 +1,82 @@
+extern crate libc;
+
+use restrict_checks::{rust_copy_ints, rust_map_ints};
+use self::libc::c_int;
+
+use std::env;
+use std::process::Command;
+use std::ptr;
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn copy_ints(_: c_int, _: *mut c_int, _: *const c_int);
+
+    #[no_mangle]
+    fn map_ints(_: c_int, _: *mut c_int, _: *const c_int, _: Option<unsafe extern "C" fn(c_int) -> c_int>);
+}
+
+const BUFFER_SIZE: usize = 4;
+
+pub fn test_distinct_buffers() {
+    let src = [1, 2, 3, 4];
+    let mut buffer = [0; BUFFER_SIZE];
+    let mut rust_buffer = [0; BUFFER_SIZE];
+
+    unsafe {
+        copy_ints(BUFFER_SIZE as c_int, buffer.as_mut_ptr(), src.as_ptr());
+        rust_copy_ints(BUFFER_SIZE as c_int, rust_buffer.as_mut_ptr(), src.as_ptr());
+    }
+
+    assert_eq!(buffer, src);
+    assert_eq!(buffer, rust_buffer);
+}
+
+unsafe extern "C" fn double(x: c_int) -> c_int {
+    x * 2
+}
+
+pub fn test_function_pointer_argument() {
+    let src = [1, 2, 3, 4];
+    let mut buffer = [0; BUFFER_SIZE];
+    let mut rust_buffer = [0; BUFFER_SIZE];
+
+    unsafe {
+        map_ints(BUFFER_SIZE as c_int, buffer.as_mut_ptr(), src.as_ptr(), Some(double));
+        rust_map_ints(BUFFER_SIZE as c_int, rust_buffer.as_mut_ptr(), src.as_ptr(), Some(double));
+    }
+
+    assert_eq!(buffer, [2, 4, 6, 8]);
+    assert_eq!(buffer, rust_buffer);
+}
+
+// Null pointers don't point to anything, so they never alias
+pub fn test_null_buffers() {
+    unsafe {
+        rust_copy_ints(0, ptr::null_mut(), ptr::null());
+    }
+}
+
+// Passing the same buffer as both `restrict` arguments trips the check in debug builds. The
+// panic can't be caught since it unwinds out of an `extern "C"` fn, so the call is made in a
+// child process running this same test, which must then exit unsuccessfully.
+pub fn test_aliased_buffers() {
+    if env::var_os("RESTRICT_CHECKS_CHILD").is_some() {
+        let mut buffer = [1, 2, 3, 4];
+        let buffer_ptr = buffer.as_mut_ptr();
+
+        unsafe {
+            rust_copy_ints(BUFFER_SIZE as c_int, buffer_ptr, buffer_ptr);
+        }
+
+        return
+    }
+
+    let status = Command::new(env::current_exe().unwrap())
+        .args(env::args().skip(1))
+        .env("RESTRICT_CHECKS_CHILD", "1")
+        .status()
+        .unwrap();
+
+    assert!(!status.success());
+}
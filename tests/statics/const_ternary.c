@@ -0,0 +1,20 @@
+#define BUFFER_SIZE 64
+#define SMALL_BUFFERS 0
+
+enum mode { MODE_FAST = 1, MODE_SAFE = 2 };
+
+// Initializers of statics have to be constant in Rust as well, so these are folded
+static int chunk_size = SMALL_BUFFERS ? 16 : BUFFER_SIZE > 32 ? 32 : BUFFER_SIZE;
+static int mode = (BUFFER_SIZE >= 64 && !SMALL_BUFFERS) ? MODE_FAST : MODE_SAFE;
+static unsigned wrapped = 1 ? -1 : 0;
+
+void const_ternary(const unsigned buffer_size, int buffer[]) {
+    if (buffer_size < 4) return;
+
+    static int local = MODE_SAFE == 2 ? 'y' : 'n';
+
+    buffer[0] = chunk_size;
+    buffer[1] = mode;
+    buffer[2] = wrapped == 0xffffffffu;
+    buffer[3] = local;
+}
@@ -0,0 +1,24 @@
+// Minimal version of `entry` in storage.c: buffer indices computed by functions with static state
+static int calls;
+
+static int next_index(void) {
+    static int index = 0;
+    calls++;
+    return index++;
+}
+
+void static_indices(const unsigned buffer_size, int buffer[]) {
+    if (buffer_size < 7) return;
+
+    // Each index is computed exactly once, even where the element is both read and written
+    buffer[next_index()] = 10;
+    buffer[next_index()] += 5;
+    buffer[next_index()]++;
+    buffer[3] = next_index();
+    buffer[4] = calls;
+    buffer[5] = buffer[next_index() - 4];
+
+    calls--;
+    next_index();
+    buffer[6] = calls;
+}
@@ -0,0 +1,26 @@
+extern crate libc;
+
+use const_ternary::rust_const_ternary;
+use self::libc::{c_int, c_uint};
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn const_ternary(_: c_uint, _: *mut c_int);
+}
+
+const BUFFER_SIZE: usize = 4;
+
+pub fn test_const_ternary() {
+    let mut buffer = [0; BUFFER_SIZE];
+    let mut rust_buffer = [0; BUFFER_SIZE];
+    let expected_buffer = [32, 1, 1, 'y' as c_int];
+
+    unsafe {
+        const_ternary(BUFFER_SIZE as u32, buffer.as_mut_ptr());
+        rust_const_ternary(BUFFER_SIZE as u32, rust_buffer.as_mut_ptr());
+    }
+
+    assert_eq!(buffer, rust_buffer);
+    assert_eq!(buffer, expected_buffer);
+}
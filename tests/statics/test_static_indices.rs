@@ -0,0 +1,26 @@
+extern crate libc;
+
+use static_indices::rust_static_indices;
+use self::libc::{c_int, c_uint};
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn static_indices(_: c_uint, _: *mut c_int);
+}
+
+const BUFFER_SIZE: usize = 7;
+
+pub fn test_static_indices() {
+    let mut buffer = [0; BUFFER_SIZE];
+    let mut rust_buffer = [0; BUFFER_SIZE];
+    let expected_buffer = [10, 5, 1, 3, 4, 10, 5];
+
+    unsafe {
+        static_indices(BUFFER_SIZE as u32, buffer.as_mut_ptr());
+        rust_static_indices(BUFFER_SIZE as u32, rust_buffer.as_mut_ptr());
+    }
+
+    assert_eq!(buffer, rust_buffer);
+    assert_eq!(buffer, expected_buffer);
+}
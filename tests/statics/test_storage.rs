@@ -16,8 +16,7 @@ pub fn test_buffer() {
     let mut rust_buffer = [0; BUFFER_SIZE];
     let expected_buffer = [1, 4, 2, 0, 0, 0, 0, 4, 4, 104, 111];
 
-    // REVIEW: C and Rust seem to be producing different results when called side by side,
-    // I suspect some sort of UB buffer overflow going on
+    // The C and Rust statics are separate, so each side starts from a fresh `counter`
     unsafe {
         entry(BUFFER_SIZE as u32, buffer.as_mut_ptr());
         rust_entry(BUFFER_SIZE as u32, rust_buffer.as_mut_ptr());
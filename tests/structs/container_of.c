@@ -0,0 +1,51 @@
+#include <stddef.h>
+
+#define container_of(ptr, type, member) \
+    ((type *)((char *)(ptr) - offsetof(type, member)))
+
+// The GNU form, with arithmetic on `void *` and a statement expression checking the type of `ptr`
+#define gnu_container_of(ptr, type, member) ({ \
+    const __typeof__(((type *)0)->member) *__mptr = (ptr); \
+    (type *)((void *)__mptr - offsetof(type, member)); })
+
+struct list_node {
+    struct list_node *next;
+};
+
+struct item {
+    int id;
+    double weight;
+    struct list_node node;
+    int value;
+};
+
+// Sums `id * value` over the items linked through their embedded `node`
+static int sum_list(struct list_node *head) {
+    int total = 0;
+    for (struct list_node *n = head; n; n = n->next) {
+        struct item *it = container_of(n, struct item, node);
+        total += it->id * it->value;
+    }
+    return total;
+}
+
+void container_of_test(unsigned buffer_size, int buffer[]) {
+    struct item items[3] = {
+        { 1, 4.5, { NULL }, 10 },
+        { 2, 1.5, { NULL }, 20 },
+        { 3, 2.5, { NULL }, 30 },
+    };
+    items[0].node.next = &items[1].node;
+    items[1].node.next = &items[2].node;
+
+    if (buffer_size < 4) return;
+
+    buffer[0] = sum_list(&items[0].node);
+
+    // Writes through the recovered container are visible in the original struct
+    struct item *last = gnu_container_of(items[1].node.next, struct item, node);
+    last->value = 7;
+    buffer[1] = items[2].value;
+    buffer[2] = last == &items[2];
+    buffer[3] = (int)gnu_container_of(&items[0].node, struct item, node)->weight;
+}
@@ -0,0 +1,65 @@
+//! destructor=resource=release_resource, destructor=handle=release_handle, destructor=lease=release_lease
+
+struct resource {
+    int *releases;
+    int value;
+};
+
+void release_resource(struct resource *r) {
+    *r->releases += 1;
+}
+
+int use_resource(int *releases, int value) {
+    struct resource r;
+    r.releases = releases;
+    r.value = value;
+
+    int result = r.value * 2;
+    release_resource(&r);
+    return result;
+}
+
+struct handle {
+    int *releases;
+    int value;
+};
+
+void release_handle(struct handle *h) {
+    *h->releases += 1;
+}
+
+static void close_handle(struct handle *h) {
+    release_handle(h);
+}
+
+int use_handle(int *releases, int value) {
+    struct handle h;
+    h.releases = releases;
+    h.value = value;
+
+    int result = h.value * 2;
+    close_handle(&h);
+    return result;
+}
+
+struct lease {
+    int *releases;
+    int value;
+};
+
+void release_lease(struct lease *l) {
+    *l->releases += 1;
+}
+
+// The early return leaves the lease unreleased, which a `Drop` impl would release anyway
+int use_lease(int *releases, int value) {
+    struct lease l;
+    l.releases = releases;
+    l.value = value;
+
+    if (value < 0) return -1;
+
+    int result = l.value * 2;
+    release_lease(&l);
+    return result;
+}
@@ -0,0 +1,47 @@
+struct leaf {
+    int d;
+    int pad;
+};
+
+struct inner {
+    struct leaf c[3];
+};
+
+struct outer {
+    int tag;
+    struct inner *b;
+};
+
+// Reads `a[i].b->c[j].d` and writes through the same chain
+int read_chain(struct outer *a, int i, int j) {
+    a[i].b->c[j].d += 1;
+    return a[i].b->c[j].d * 10 + (&a[i])->b[0].c[j].pad;
+}
+
+static int next_index(int *counter) {
+    return (*counter)++;
+}
+
+// Fills some chains and reads them back with indices from side-effecting calls. C leaves the
+// order of the calls within one expression unspecified, so calls sharing a counter are made in
+// their own statements, and the one expression with several calls gives each its own counter
+int chain_order(void) {
+    struct inner inners[2] = { { { { 1, 2 }, { 3, 4 }, { 5, 6 } } },
+                               { { { 7, 8 }, { 9, 10 }, { 11, 12 } } } };
+    struct outer outers[2] = { { 0, &inners[1] }, { 1, &inners[0] } };
+    int counter = 0;
+
+    int i = next_index(&counter);
+    int j = next_index(&counter);
+    int first = outers[i].b->c[j].d;
+    i = next_index(&counter) % 2;
+    j = next_index(&counter) % 3;
+    int second = outers[i].b->c[j].pad;
+    outers[0].b->c[2].d = read_chain(outers, 1, 2);
+
+    // Each index must be evaluated exactly once, even though the place is both read and written
+    int row = 1, col = 0;
+    int third = outers[next_index(&row)].b->c[next_index(&col)].d += 100;
+
+    return (third + row + col) * 10000 + first * 1000 + second * 100 + inners[1].c[2].d + counter;
+}
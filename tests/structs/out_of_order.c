@@ -0,0 +1,36 @@
+// Types used through pointers before their definitions appear
+
+enum shape;
+
+struct node {
+    struct edge *first;     // `struct edge` is first declared here
+    enum shape *shape;
+    int id;
+};
+
+enum shape { ROUND = 3, SQUARE = 4 };
+
+struct edge {
+    struct node *to;
+    struct edge *next;
+};
+
+static int shape_sides(const enum shape *shape) {
+    return *shape == SQUARE ? 4 : 0;
+}
+
+void out_of_order(const unsigned buffer_size, int buffer[const]) {
+    enum shape square = SQUARE, round = ROUND;
+    struct node a = { 0, &square, 1 }, b = { 0, &round, 2 };
+    struct edge ab = { &b, 0 }, ba = { &a, 0 }, aa = { &a, &ab };
+    a.first = &aa;
+    b.first = &ba;
+
+    int i = 0;
+    for (struct edge *e = a.first; e && i < buffer_size; e = e->next) {
+        buffer[i++] = e->to->id;
+        buffer[i++] = shape_sides(e->to->shape);
+        buffer[i++] = e->to->first->to->id;
+    }
+    buffer[i++] = *b.shape;
+}
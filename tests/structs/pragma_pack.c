@@ -0,0 +1,53 @@
+#define PACKED_BEGIN _Pragma("pack(push, 1)")
+#define PACKED_END _Pragma("pack(pop)")
+
+PACKED_BEGIN
+struct header {
+    char tag;
+    int length;
+    short flags;
+};
+PACKED_END
+
+struct padded {
+    char tag;
+    int length;
+};
+
+// Packing that changes nothing: no field is aligned more than the cap
+#pragma pack(push, 8)
+struct wide {
+    char tag;
+    int length;
+};
+#pragma pack(pop)
+
+struct __attribute__((packed, aligned(4))) bytes {
+    char data[3];
+};
+
+_Pragma("region sizes")
+unsigned long header_size(void) {
+    return sizeof(struct header);
+}
+
+unsigned long padded_size(void) {
+    return sizeof(struct padded);
+}
+
+unsigned long wide_size(void) {
+    return sizeof(struct wide);
+}
+
+unsigned long bytes_size(void) {
+    return sizeof(struct bytes);
+}
+_Pragma("endregion")
+
+int header_total(unsigned count, const struct header headers[]) {
+    int total = 0;
+    for (unsigned i = 0; i < count; i++) {
+        total += headers[i].length + headers[i].flags;
+    }
+    return total;
+}
@@ -0,0 +1,27 @@
+extern crate libc;
+
+use container_of::rust_container_of_test;
+use self::libc::{c_int, c_uint};
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn container_of_test(_: c_uint, _: *mut c_int);
+}
+
+const BUFFER_SIZE: usize = 4;
+
+// Containers recovered from pointers to an embedded member are the enclosing structs
+pub fn test_container_of() {
+    let mut buffer = [0; BUFFER_SIZE];
+    let mut rust_buffer = [0; BUFFER_SIZE];
+    let expected_buffer = [140, 7, 1, 4];
+
+    unsafe {
+        container_of_test(BUFFER_SIZE as c_uint, buffer.as_mut_ptr());
+        rust_container_of_test(BUFFER_SIZE as c_uint, rust_buffer.as_mut_ptr());
+    }
+
+    assert_eq!(buffer, rust_buffer);
+    assert_eq!(buffer, expected_buffer);
+}
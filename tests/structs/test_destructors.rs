@@ -0,0 +1,70 @@
+extern crate libc;
+
+use destructors::{resource, rust_use_handle, rust_use_lease, rust_use_resource};
+use self::libc::c_int;
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn use_resource(_: *mut c_int, _: c_int) -> c_int;
+    #[no_mangle]
+    fn use_handle(_: *mut c_int, _: c_int) -> c_int;
+    #[no_mangle]
+    fn use_lease(_: *mut c_int, _: c_int) -> c_int;
+}
+
+pub fn test_released_once() {
+    let mut releases = 0;
+    let mut rust_releases = 0;
+
+    unsafe {
+        assert_eq!(use_resource(&mut releases, 21), 42);
+        assert_eq!(rust_use_resource(&mut rust_releases, 21), 42);
+    }
+
+    assert_eq!(releases, 1);
+    assert_eq!(rust_releases, 1);
+}
+
+pub fn test_drop_releases() {
+    let mut releases = 0;
+
+    {
+        let _r = resource { releases: &mut releases, value: 0 };
+    }
+
+    assert_eq!(releases, 1);
+}
+
+pub fn test_released_through_pointer_once() {
+    let mut releases = 0;
+    let mut rust_releases = 0;
+
+    unsafe {
+        assert_eq!(use_handle(&mut releases, 21), 42);
+        assert_eq!(rust_use_handle(&mut rust_releases, 21), 42);
+    }
+
+    assert_eq!(releases, 1);
+    assert_eq!(rust_releases, 1);
+}
+
+pub fn test_early_return_not_released() {
+    let mut releases = 0;
+    let mut rust_releases = 0;
+
+    unsafe {
+        assert_eq!(use_lease(&mut releases, -1), -1);
+        assert_eq!(rust_use_lease(&mut rust_releases, -1), -1);
+        assert_eq!(use_lease(&mut releases, 21), 42);
+        assert_eq!(rust_use_lease(&mut rust_releases, 21), 42);
+    }
+
+    assert_eq!(releases, 1);
+    assert_eq!(rust_releases, 1);
+
+    // The lease keeps its explicit release instead of getting a `Drop` impl
+    let translated = include_str!("destructors.rs");
+    assert!(!translated.contains("impl Drop for lease"));
+    assert!(translated.contains("impl Drop for resource"));
+}
@@ -0,0 +1,56 @@
+extern crate libc;
+
+use member_chains::{inner, leaf, outer, rust_chain_order, rust_read_chain};
+use self::libc::c_int;
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn read_chain(_: *mut outer, _: c_int, _: c_int) -> c_int;
+
+    #[no_mangle]
+    fn chain_order() -> c_int;
+}
+
+fn mk_inner(base: c_int) -> inner {
+    inner {
+        c: [
+            leaf { d: base, pad: base + 1 },
+            leaf { d: base + 2, pad: base + 3 },
+            leaf { d: base + 4, pad: base + 5 },
+        ],
+    }
+}
+
+unsafe fn mk_outers(inners: *mut inner) -> [outer; 2] {
+    [
+        outer { tag: 0, b: inners.offset(1) },
+        outer { tag: 1, b: inners },
+    ]
+}
+
+pub fn test_read_chain() {
+    let mut inners = [mk_inner(10), mk_inner(20)];
+    let mut rust_inners = [mk_inner(10), mk_inner(20)];
+
+    let mut outers = unsafe { mk_outers(inners.as_mut_ptr()) };
+    let mut rust_outers = unsafe { mk_outers(rust_inners.as_mut_ptr()) };
+
+    for &(i, j) in &[(0, 0), (0, 2), (1, 1), (0, 2)] {
+        let ret = unsafe { read_chain(outers.as_mut_ptr(), i, j) };
+        let rust_ret = unsafe { rust_read_chain(rust_outers.as_mut_ptr(), i, j) };
+
+        assert_eq!(ret, rust_ret);
+    }
+
+    assert_eq!(inners[1].c[2].d, 26);
+    assert_eq!(rust_inners[1].c[2].d, 26);
+}
+
+pub fn test_chain_order() {
+    let ret = unsafe { chain_order() };
+    let rust_ret = unsafe { rust_chain_order() };
+
+    assert_eq!(ret, rust_ret);
+    assert_eq!(ret, 1049870);
+}
@@ -0,0 +1,26 @@
+extern crate libc;
+
+use out_of_order::rust_out_of_order;
+use self::libc::{c_int, c_uint};
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn out_of_order(_: c_uint, _: *mut c_int);
+}
+
+const BUFFER_SIZE: usize = 7;
+
+pub fn test_out_of_order() {
+    let mut buffer = [0; BUFFER_SIZE];
+    let mut rust_buffer = [0; BUFFER_SIZE];
+    let expected_buffer = [1, 4, 1, 2, 0, 1, 3];
+
+    unsafe {
+        out_of_order(BUFFER_SIZE as c_uint, buffer.as_mut_ptr());
+        rust_out_of_order(BUFFER_SIZE as c_uint, rust_buffer.as_mut_ptr());
+    }
+
+    assert_eq!(buffer, rust_buffer);
+    assert_eq!(buffer, expected_buffer);
+}
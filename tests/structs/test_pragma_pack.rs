@@ -0,0 +1,63 @@
+extern crate libc;
+
+use pragma_pack::{header, rust_header_size, rust_padded_size, rust_header_total};
+use pragma_pack::{rust_wide_size, rust_bytes_size};
+use self::libc::{c_int, c_uint, c_ulong};
+
+use std::mem::size_of;
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn header_size() -> c_ulong;
+
+    #[no_mangle]
+    fn padded_size() -> c_ulong;
+
+    #[no_mangle]
+    fn wide_size() -> c_ulong;
+
+    #[no_mangle]
+    fn bytes_size() -> c_ulong;
+
+    #[no_mangle]
+    fn header_total(_: c_uint, _: *const header) -> c_int;
+}
+
+// `_Pragma("pack(push, 1)")` packs the struct just like `#pragma pack(push, 1)` would
+pub fn test_packed_size() {
+    unsafe {
+        assert_eq!(header_size(), rust_header_size());
+        assert_eq!(padded_size(), rust_padded_size());
+    }
+
+    assert_eq!(size_of::<header>(), 7);
+    assert_eq!(rust_header_size(), 7);
+    assert_eq!(rust_padded_size(), 8);
+}
+
+// Packing that doesn't change the layout is left out, so it doesn't get in the way of `repr(C)`
+// or of an alignment request
+pub fn test_redundant_packing() {
+    unsafe {
+        assert_eq!(wide_size(), rust_wide_size());
+        assert_eq!(bytes_size(), rust_bytes_size());
+    }
+
+    assert_eq!(rust_wide_size(), 8);
+    assert_eq!(rust_bytes_size(), 4);
+}
+
+pub fn test_packed_access() {
+    let headers = [
+        header { tag: 1, length: 100, flags: 2 },
+        header { tag: 2, length: 200, flags: 3 },
+        header { tag: 3, length: 300, flags: 4 },
+    ];
+
+    let ret = unsafe { header_total(headers.len() as c_uint, headers.as_ptr()) };
+    let rust_ret = unsafe { rust_header_total(headers.len() as c_uint, headers.as_ptr()) };
+
+    assert_eq!(ret, rust_ret);
+    assert_eq!(ret, 609);
+}
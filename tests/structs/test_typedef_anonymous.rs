@@ -0,0 +1,39 @@
+extern crate libc;
+
+use typedef_anonymous::{Point, Rect, Vector, rust_contains};
+use self::libc::c_int;
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct CPoint {
+    x: c_int,
+    y: c_int,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct CRect {
+    origin: CPoint,
+    width: c_int,
+    height: c_int,
+}
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn contains(_: CRect, _: CPoint, _: CPoint) -> c_int;
+}
+
+pub fn test_contains() {
+    let rect = CRect { origin: CPoint { x: 1, y: 1 }, width: 4, height: 2 };
+    let rust_rect = Rect { origin: Point { x: 1, y: 1 }, width: 4, height: 2 };
+
+    for &(x, y) in &[(0, 0), (1, 1), (2, 2), (4, 2), (5, 2), (3, 3)] {
+        let ret = unsafe { contains(rect, CPoint { x, y }, CPoint { x: 1, y: 0 }) };
+        let rust_ret = unsafe {
+            rust_contains(rust_rect, Point { x, y }, Vector { x: 1, y: 0 })
+        };
+
+        assert_eq!(ret, rust_ret);
+    }
+}
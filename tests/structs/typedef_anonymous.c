@@ -0,0 +1,36 @@
+typedef struct {
+    int x;
+    int y;
+} Point;
+
+typedef struct {
+    Point origin;
+    int width;
+    int height;
+} Rect;
+
+// Only the first typedef names the anonymous struct, the other is an alias of it
+typedef Point Vector;
+
+typedef enum {
+    NARROW,
+    WIDE,
+} Shape;
+
+Shape classify(Rect r);
+
+int contains(Rect r, Point p, Vector offset) {
+    int x = p.x + offset.x;
+    int y = p.y + offset.y;
+
+    if (classify(r) == WIDE && r.height == 0) {
+        return 0;
+    }
+
+    return r.origin.x <= x && x < r.origin.x + r.width &&
+           r.origin.y <= y && y < r.origin.y + r.height;
+}
+
+Shape classify(Rect r) {
+    return r.width > r.height ? WIDE : NARROW;
+}
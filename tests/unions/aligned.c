@@ -0,0 +1,16 @@
+union aligned_union {
+    char c;
+    int i;
+} __attribute__((aligned(16)));
+
+struct aligned_struct {
+    char c;
+} __attribute__((aligned(32)));
+
+unsigned aligned_union_alignment(void) {
+    return _Alignof(union aligned_union);
+}
+
+unsigned aligned_struct_alignment(void) {
+    return _Alignof(struct aligned_struct);
+}
@@ -0,0 +1,41 @@
+struct tagged {
+    int tag;
+    union {
+        int i;
+        char c;
+        double d;
+    } u;
+    int trailer;
+};
+
+struct outer {
+    struct tagged first;
+    struct tagged second;
+};
+
+void designated_entry(const unsigned int buffer_size, int buffer[const])
+{
+    int i = 0;
+
+    struct tagged t1 = { .tag = 1, .u.i = 5 };
+    struct tagged t2 = { .tag = 2, .u.c = 'a', .trailer = 3 };
+    struct tagged t3 = { .u = { .i = 7 }, .tag = 4 };
+    struct tagged t4 = { .tag = 5 };
+
+    struct outer o = { .first.tag = 6, .first.u.i = 8, .second = { .tag = 9, .u.c = 10 } };
+
+    buffer[i++] = t1.tag;
+    buffer[i++] = t1.u.i;
+    buffer[i++] = t1.trailer;
+    buffer[i++] = t2.tag;
+    buffer[i++] = t2.u.c;
+    buffer[i++] = t2.trailer;
+    buffer[i++] = t3.tag;
+    buffer[i++] = t3.u.i;
+    buffer[i++] = t4.tag;
+    buffer[i++] = t4.u.i;
+    buffer[i++] = o.first.tag;
+    buffer[i++] = o.first.u.i;
+    buffer[i++] = o.second.tag;
+    buffer[i++] = o.second.u.c;
+}
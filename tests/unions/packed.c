@@ -0,0 +1,36 @@
+union __attribute__((packed)) payload {
+    char bytes[6];
+    int word;
+    short halves[3];
+};
+
+// `data` starts at offset 1, so none of its fields is aligned
+struct __attribute__((packed)) frame {
+    char tag;
+    union payload data;
+};
+
+unsigned long payload_size(void) {
+    return sizeof(union payload);
+}
+
+unsigned long frame_size(void) {
+    return sizeof(struct frame);
+}
+
+int read_word(const struct frame *f) {
+    return f->data.word;
+}
+
+int read_half(struct frame *f, int i) {
+    union payload copy = f->data;
+    return copy.halves[i];
+}
+
+void write_word(struct frame *f, int word) {
+    f->data.word = word;
+}
+
+long word_offset(struct frame *f) {
+    return (char *)&f->data.word - (char *)f;
+}
@@ -0,0 +1,34 @@
+extern crate libc;
+
+use std::mem;
+
+use aligned::{aligned_union, aligned_struct};
+use aligned::{rust_aligned_union_alignment, rust_aligned_struct_alignment};
+use self::libc::c_uint;
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn aligned_union_alignment() -> c_uint;
+
+    #[no_mangle]
+    fn aligned_struct_alignment() -> c_uint;
+}
+
+pub fn test_aligned_union() {
+    let alignment = unsafe { aligned_union_alignment() };
+    let rust_alignment = unsafe { rust_aligned_union_alignment() };
+
+    assert_eq!(alignment, rust_alignment);
+    assert_eq!(alignment as usize, mem::align_of::<aligned_union>());
+    assert_eq!(alignment, 16);
+}
+
+pub fn test_aligned_struct() {
+    let alignment = unsafe { aligned_struct_alignment() };
+    let rust_alignment = unsafe { rust_aligned_struct_alignment() };
+
+    assert_eq!(alignment, rust_alignment);
+    assert_eq!(alignment as usize, mem::align_of::<aligned_struct>());
+    assert_eq!(alignment, 32);
+}
@@ -0,0 +1,26 @@
+extern crate libc;
+
+use designated_init::rust_designated_entry;
+use self::libc::{c_int, c_uint};
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn designated_entry(_: c_uint, _: *mut c_int);
+}
+
+const BUFFER_SIZE: usize = 14;
+
+pub fn test_designated_init() {
+    let mut buffer = [0; BUFFER_SIZE];
+    let mut rust_buffer = [0; BUFFER_SIZE];
+    let expected_buffer = [1, 5, 0, 2, 97, 3, 4, 7, 5, 0, 6, 8, 9, 10];
+
+    unsafe {
+        designated_entry(BUFFER_SIZE as u32, buffer.as_mut_ptr());
+        rust_designated_entry(BUFFER_SIZE as u32, rust_buffer.as_mut_ptr());
+    }
+
+    assert_eq!(buffer, rust_buffer);
+    assert_eq!(buffer, expected_buffer);
+}
@@ -0,0 +1,65 @@
+extern crate libc;
+
+use std::mem;
+
+use packed::{frame, payload};
+use packed::{rust_frame_size, rust_payload_size, rust_read_half, rust_read_word, rust_word_offset,
+             rust_write_word};
+use self::libc::{c_int, c_long, c_ulong};
+
+#[link(name = "test")]
+extern "C" {
+    #[no_mangle]
+    fn payload_size() -> c_ulong;
+
+    #[no_mangle]
+    fn frame_size() -> c_ulong;
+
+    #[no_mangle]
+    fn read_word(_: *const frame) -> c_int;
+
+    #[no_mangle]
+    fn read_half(_: *mut frame, _: c_int) -> c_int;
+
+    #[no_mangle]
+    fn write_word(_: *mut frame, _: c_int);
+
+    #[no_mangle]
+    fn word_offset(_: *mut frame) -> c_long;
+}
+
+fn mk_frame() -> frame {
+    frame { tag: 7, data: payload { bytes: [1, 2, 3, 4, 5, 6] } }
+}
+
+pub fn test_packed_size() {
+    unsafe {
+        assert_eq!(payload_size(), rust_payload_size());
+        assert_eq!(frame_size(), rust_frame_size());
+    }
+
+    assert_eq!(mem::size_of::<payload>(), 6);
+    assert_eq!(mem::align_of::<payload>(), 1);
+    assert_eq!(mem::size_of::<frame>(), 7);
+}
+
+// `data.word` is misaligned within the frame, and is read without a reference to it
+pub fn test_misaligned_read() {
+    let mut frame = mk_frame();
+    let mut rust_frame = mk_frame();
+
+    unsafe {
+        assert_eq!(read_word(&frame), rust_read_word(&rust_frame));
+        for i in 0..3 {
+            assert_eq!(read_half(&mut frame, i), rust_read_half(&mut rust_frame, i));
+        }
+
+        write_word(&mut frame, -2);
+        rust_write_word(&mut rust_frame, -2);
+        assert_eq!(read_word(&frame), rust_read_word(&rust_frame));
+        assert_eq!(rust_read_word(&rust_frame), -2);
+
+        assert_eq!(word_offset(&mut frame), rust_word_offset(&mut rust_frame));
+        assert_eq!(rust_word_offset(&mut rust_frame), 1);
+    }
+}
@@ -0,0 +1,39 @@
+extern crate libc;
+
+use std::ptr;
+
+use transparent::{rust_read_value, rust_scaled_value, rust_sum_readings};
+use self::libc::c_int;
+
+#[link(name = "test")]
+extern "C" {
+    // A `transparent_union` parameter is passed like its first field, an `int *`
+    #[no_mangle]
+    fn read_value(_: *mut c_int) -> c_int;
+
+    #[no_mangle]
+    fn scaled_value(_: *mut c_int, _: c_int) -> c_int;
+
+    #[no_mangle]
+    fn sum_readings(_: c_int) -> c_int;
+}
+
+pub fn test_transparent_union_argument() {
+    let mut reading = [6, 7];
+
+    unsafe {
+        assert_eq!(read_value(reading.as_mut_ptr()), rust_read_value(reading.as_mut_ptr()));
+        assert_eq!(scaled_value(reading.as_mut_ptr(), 0), rust_scaled_value(reading.as_mut_ptr(), 0));
+        assert_eq!(scaled_value(ptr::null_mut(), 5), rust_scaled_value(ptr::null_mut(), 5));
+        assert_eq!(rust_scaled_value(reading.as_mut_ptr(), 0), 42);
+    }
+}
+
+pub fn test_transparent_union_calls() {
+    for i in 0..5 {
+        let ret = unsafe { sum_readings(i) };
+        let rust_ret = unsafe { rust_sum_readings(i) };
+
+        assert_eq!(ret, rust_ret);
+    }
+}
@@ -0,0 +1,28 @@
+struct reading {
+    int value;
+    int scale;
+};
+
+typedef union {
+    int *value;
+    struct reading *reading;
+} __attribute__((transparent_union)) reading_arg;
+
+/* Reads the `int` at the start of either kind of pointer */
+int read_value(reading_arg arg) {
+    return *arg.value;
+}
+
+int scaled_value(reading_arg arg, int fallback) {
+    if (!arg.reading) {
+        return fallback;
+    }
+    return arg.reading->value * arg.reading->scale;
+}
+
+int sum_readings(int n) {
+    struct reading r = { n, 3 };
+    int plain = n + 1;
+
+    return read_value(&plain) + read_value(&r) + scaled_value(&r, 0) + scaled_value((int *)0, 7);
+}